@@ -0,0 +1,31 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    // sqlx::migrate!() embeds the directory, new files have to trigger a rebuild
+    println!("cargo:rerun-if-changed=migrations");
+
+    // GIT_COMMIT wins so builds without a .git directory can still set it
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    let commit = env::var("GIT_COMMIT")
+        .ok()
+        .or_else(git_commit)
+        .unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=BUILD_GIT_COMMIT={commit}");
+
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "unknown".into());
+    println!("cargo:rustc-env=BUILD_PROFILE={profile}");
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
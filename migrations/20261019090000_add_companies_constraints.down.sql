@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE public.companies DROP CONSTRAINT companies_code_key;
+ALTER TABLE public.companies DROP CONSTRAINT companies_email_key;
+ALTER TABLE public.companies DROP CONSTRAINT companies_pkey;
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE public.companies ADD CONSTRAINT companies_pkey PRIMARY KEY (id);
+ALTER TABLE public.companies ADD CONSTRAINT companies_email_key UNIQUE (email);
+ALTER TABLE public.companies ADD CONSTRAINT companies_code_key UNIQUE (code);
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE public.users DROP CONSTRAINT users_email_key;
+ALTER TABLE public.users DROP CONSTRAINT users_username_key;
+ALTER TABLE public.users DROP CONSTRAINT users_pkey;
+ALTER TABLE public.users DROP COLUMN role;
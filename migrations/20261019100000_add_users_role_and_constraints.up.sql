@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE public.users ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'staff';
+ALTER TABLE public.users ADD CONSTRAINT users_pkey PRIMARY KEY (id);
+ALTER TABLE public.users ADD CONSTRAINT users_username_key UNIQUE (username);
+ALTER TABLE public.users ADD CONSTRAINT users_email_key UNIQUE (email);
@@ -0,0 +1,8 @@
+-- token buckets of the postgres rate limit backend, losing them on a crash
+-- only resets the limits so the table skips the WAL
+CREATE UNLOGGED TABLE public.rate_limit_buckets (
+    key TEXT NOT NULL,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT rate_limit_buckets_pkey PRIMARY KEY (key)
+);
@@ -0,0 +1,17 @@
+-- the product catalogue, every product belongs to a company and its sku is
+-- unique within that company. companies with products cannot be deleted
+CREATE TABLE public.products (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    sku VARCHAR(50) NOT NULL,
+    name VARCHAR(150) NOT NULL,
+    description text,
+    category VARCHAR(100),
+    unit_of_measure VARCHAR(20) NOT NULL,
+    barcode VARCHAR(50),
+    is_active boolean NOT NULL DEFAULT true,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT products_pkey PRIMARY KEY (id),
+    CONSTRAINT products_company_id_sku_key UNIQUE (company_id, sku),
+    CONSTRAINT products_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE public.warehouse_locations;
+DROP TABLE public.warehouses;
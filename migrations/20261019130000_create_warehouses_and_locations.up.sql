@@ -0,0 +1,30 @@
+-- warehouses of a company and the locations inside them. a location can sit
+-- inside another location of the same warehouse (zone > aisle > shelf > bin),
+-- the composite foreign key keeps parents in the same warehouse
+CREATE TABLE public.warehouses (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    code VARCHAR(20) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    address text,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT warehouses_pkey PRIMARY KEY (id),
+    CONSTRAINT warehouses_company_id_code_key UNIQUE (company_id, code),
+    CONSTRAINT warehouses_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id)
+);
+
+CREATE TABLE public.warehouse_locations (
+    id uuid NOT NULL,
+    warehouse_id uuid NOT NULL,
+    parent_id uuid,
+    code VARCHAR(30) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    kind VARCHAR(20) NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT warehouse_locations_pkey PRIMARY KEY (id),
+    CONSTRAINT warehouse_locations_warehouse_id_code_key UNIQUE (warehouse_id, code),
+    CONSTRAINT warehouse_locations_warehouse_id_id_key UNIQUE (warehouse_id, id),
+    CONSTRAINT warehouse_locations_kind_check CHECK (kind IN ('zone', 'aisle', 'rack', 'shelf', 'bin')),
+    CONSTRAINT warehouse_locations_warehouse_id_fkey FOREIGN KEY (warehouse_id) REFERENCES public.warehouses (id),
+    CONSTRAINT warehouse_locations_parent_fkey FOREIGN KEY (warehouse_id, parent_id) REFERENCES public.warehouse_locations (warehouse_id, id)
+);
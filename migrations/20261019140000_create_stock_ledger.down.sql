@@ -0,0 +1,5 @@
+DROP TABLE public.company_stock_settings;
+DROP TABLE public.stock_balances;
+DROP TRIGGER stock_movements_immutable ON public.stock_movements;
+DROP FUNCTION public.stock_movements_immutable();
+DROP TABLE public.stock_movements;
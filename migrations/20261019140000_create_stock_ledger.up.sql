@@ -0,0 +1,78 @@
+-- the stock ledger. every change of stock is a row in stock_movements, rows are
+-- never changed or removed, a wrong entry is corrected by posting a reversal.
+-- stock_balances holds the running quantity per product and location, it is
+-- updated in the same transaction as the movement and its row is locked while
+-- posting, so the balance always equals the sum of the ledger
+-- every movement names what posted it. movements posted by hand have no
+-- source_id, the documents that post movements add their source to
+-- stock_movements_source_check and keep their id in source_id
+CREATE TABLE public.stock_movements (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    product_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    movement_type VARCHAR(20) NOT NULL,
+    quantity bigint NOT NULL,
+    balance_after bigint NOT NULL,
+    reversal_of uuid,
+    source VARCHAR(20) NOT NULL,
+    source_id uuid,
+    reference VARCHAR(100),
+    note text,
+    posted_by uuid NOT NULL,
+    posted_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_movements_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_movements_movement_type_check CHECK (
+        movement_type IN ('receipt', 'issue', 'adjustment', 'transfer_in', 'transfer_out', 'reversal')
+    ),
+    -- receipts add, issues take away, adjustments and reversals go either way
+    CONSTRAINT stock_movements_quantity_check CHECK (
+        quantity <> 0
+        AND (movement_type NOT IN ('receipt', 'transfer_in') OR quantity > 0)
+        AND (movement_type NOT IN ('issue', 'transfer_out') OR quantity < 0)
+    ),
+    CONSTRAINT stock_movements_reversal_check CHECK ((movement_type = 'reversal') = (reversal_of IS NOT NULL)),
+    CONSTRAINT stock_movements_source_check CHECK (source IN ('manual')),
+    CONSTRAINT stock_movements_source_id_check CHECK ((source = 'manual') = (source_id IS NULL)),
+    CONSTRAINT stock_movements_reversal_of_key UNIQUE (reversal_of),
+    CONSTRAINT stock_movements_reversal_of_fkey FOREIGN KEY (reversal_of) REFERENCES public.stock_movements (id),
+    CONSTRAINT stock_movements_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_movements_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_movements_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id)
+);
+
+CREATE INDEX stock_movements_company_id_posted_at_idx ON public.stock_movements (company_id, posted_at);
+CREATE INDEX stock_movements_product_id_location_id_idx ON public.stock_movements (product_id, location_id);
+
+CREATE FUNCTION public.stock_movements_immutable() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'stock movements can not be changed, post a reversal instead';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER stock_movements_immutable
+    BEFORE UPDATE OR DELETE ON public.stock_movements
+    FOR EACH ROW EXECUTE FUNCTION public.stock_movements_immutable();
+
+CREATE TABLE public.stock_balances (
+    product_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_balances_pkey PRIMARY KEY (product_id, location_id),
+    CONSTRAINT stock_balances_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_balances_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_balances_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id)
+);
+
+CREATE INDEX stock_balances_company_id_idx ON public.stock_balances (company_id);
+
+-- stock rules of a company, a missing row means the defaults
+CREATE TABLE public.company_stock_settings (
+    company_id uuid NOT NULL,
+    allow_negative_stock boolean NOT NULL DEFAULT false,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT company_stock_settings_pkey PRIMARY KEY (company_id),
+    CONSTRAINT company_stock_settings_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+-- movements already posted by transfers stay in the ledger, the narrower check
+-- only applies to new rows
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual')) NOT VALID;
+
+DROP TABLE public.stock_transfer_lines;
+DROP TABLE public.stock_transfers;
+ALTER TABLE public.warehouses DROP CONSTRAINT warehouses_company_id_id_key;
@@ -0,0 +1,56 @@
+-- transfers move stock between two warehouses of the same company. dispatching
+-- posts a transfer_out per line at the source, receiving posts transfer_in at
+-- the destination, what is dispatched but not yet received is in transit.
+-- the composite foreign keys keep both warehouses in the company of the transfer
+ALTER TABLE public.warehouses
+    ADD CONSTRAINT warehouses_company_id_id_key UNIQUE (company_id, id);
+
+CREATE TABLE public.stock_transfers (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    source_warehouse_id uuid NOT NULL,
+    destination_warehouse_id uuid NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    note text,
+    created_by uuid NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    dispatched_at timestamp with time zone,
+    received_at timestamp with time zone,
+    cancelled_at timestamp with time zone,
+    CONSTRAINT stock_transfers_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_transfers_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT stock_transfers_status_check CHECK (
+        status IN ('draft', 'dispatched', 'partially_received', 'received', 'cancelled')
+    ),
+    CONSTRAINT stock_transfers_warehouses_check CHECK (source_warehouse_id <> destination_warehouse_id),
+    CONSTRAINT stock_transfers_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_transfers_source_warehouse_fkey FOREIGN KEY (company_id, source_warehouse_id) REFERENCES public.warehouses (company_id, id),
+    CONSTRAINT stock_transfers_destination_warehouse_fkey FOREIGN KEY (company_id, destination_warehouse_id) REFERENCES public.warehouses (company_id, id)
+);
+
+CREATE INDEX stock_transfers_company_id_status_idx ON public.stock_transfers (company_id, status);
+
+CREATE TABLE public.stock_transfer_lines (
+    id uuid NOT NULL,
+    transfer_id uuid NOT NULL,
+    position integer NOT NULL,
+    product_id uuid NOT NULL,
+    source_location_id uuid NOT NULL,
+    destination_location_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    received_quantity bigint NOT NULL DEFAULT 0,
+    CONSTRAINT stock_transfer_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_transfer_lines_transfer_id_position_key UNIQUE (transfer_id, position),
+    CONSTRAINT stock_transfer_lines_quantity_check CHECK (quantity > 0),
+    CONSTRAINT stock_transfer_lines_received_quantity_check CHECK (received_quantity >= 0 AND received_quantity <= quantity),
+    CONSTRAINT stock_transfer_lines_transfer_id_fkey FOREIGN KEY (transfer_id) REFERENCES public.stock_transfers (id) ON DELETE CASCADE,
+    CONSTRAINT stock_transfer_lines_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_transfer_lines_source_location_id_fkey FOREIGN KEY (source_location_id) REFERENCES public.warehouse_locations (id),
+    CONSTRAINT stock_transfer_lines_destination_location_id_fkey FOREIGN KEY (destination_location_id) REFERENCES public.warehouse_locations (id)
+);
+
+-- movements posted by transfers carry the id of the transfer in source_id
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer'));
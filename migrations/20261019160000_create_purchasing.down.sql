@@ -0,0 +1,11 @@
+-- movements already posted by goods receipts stay in the ledger, the narrower
+-- check only applies to new rows
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer')) NOT VALID;
+
+DROP TABLE public.goods_receipt_lines;
+DROP TABLE public.goods_receipts;
+DROP TABLE public.purchase_order_lines;
+DROP TABLE public.purchase_orders;
+DROP TABLE public.suppliers;
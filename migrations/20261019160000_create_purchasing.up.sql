@@ -0,0 +1,111 @@
+-- suppliers a company buys from, optionally linked to a company of its own in
+-- the system. purchase orders go draft -> approved -> partially_received ->
+-- closed, goods receipts post a receipt movement per line into a location of
+-- the chosen warehouse. the tolerances are whole percentages of the ordered
+-- quantity, copied from the supplier onto each order
+CREATE TABLE public.suppliers (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    linked_company_id uuid,
+    code VARCHAR(20) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    email VARCHAR(100),
+    phone_number VARCHAR(20),
+    address text,
+    over_receipt_tolerance_pct integer NOT NULL DEFAULT 0,
+    under_receipt_tolerance_pct integer NOT NULL DEFAULT 0,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT suppliers_pkey PRIMARY KEY (id),
+    CONSTRAINT suppliers_company_id_code_key UNIQUE (company_id, code),
+    CONSTRAINT suppliers_company_id_linked_company_id_key UNIQUE (company_id, linked_company_id),
+    CONSTRAINT suppliers_company_id_id_key UNIQUE (company_id, id),
+    CONSTRAINT suppliers_linked_company_id_check CHECK (linked_company_id <> company_id),
+    CONSTRAINT suppliers_over_receipt_tolerance_pct_check CHECK (over_receipt_tolerance_pct BETWEEN 0 AND 100),
+    CONSTRAINT suppliers_under_receipt_tolerance_pct_check CHECK (under_receipt_tolerance_pct BETWEEN 0 AND 100),
+    CONSTRAINT suppliers_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT suppliers_linked_company_id_fkey FOREIGN KEY (linked_company_id) REFERENCES public.companies (id)
+);
+
+CREATE TABLE public.purchase_orders (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    supplier_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    note text,
+    over_receipt_tolerance_pct integer NOT NULL,
+    under_receipt_tolerance_pct integer NOT NULL,
+    created_by uuid NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    approved_by uuid,
+    approved_at timestamp with time zone,
+    closed_at timestamp with time zone,
+    CONSTRAINT purchase_orders_pkey PRIMARY KEY (id),
+    CONSTRAINT purchase_orders_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT purchase_orders_company_id_id_key UNIQUE (company_id, id),
+    CONSTRAINT purchase_orders_status_check CHECK (
+        status IN ('draft', 'approved', 'partially_received', 'closed')
+    ),
+    CONSTRAINT purchase_orders_over_receipt_tolerance_pct_check CHECK (over_receipt_tolerance_pct BETWEEN 0 AND 100),
+    CONSTRAINT purchase_orders_under_receipt_tolerance_pct_check CHECK (under_receipt_tolerance_pct BETWEEN 0 AND 100),
+    CONSTRAINT purchase_orders_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT purchase_orders_supplier_fkey FOREIGN KEY (company_id, supplier_id) REFERENCES public.suppliers (company_id, id)
+);
+
+CREATE INDEX purchase_orders_company_id_status_idx ON public.purchase_orders (company_id, status);
+CREATE INDEX purchase_orders_supplier_id_idx ON public.purchase_orders (supplier_id);
+
+-- received_quantity may pass quantity within the over-receipt tolerance
+CREATE TABLE public.purchase_order_lines (
+    id uuid NOT NULL,
+    purchase_order_id uuid NOT NULL,
+    position integer NOT NULL,
+    product_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    received_quantity bigint NOT NULL DEFAULT 0,
+    CONSTRAINT purchase_order_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT purchase_order_lines_purchase_order_id_position_key UNIQUE (purchase_order_id, position),
+    CONSTRAINT purchase_order_lines_quantity_check CHECK (quantity > 0),
+    CONSTRAINT purchase_order_lines_received_quantity_check CHECK (received_quantity >= 0),
+    CONSTRAINT purchase_order_lines_purchase_order_id_fkey FOREIGN KEY (purchase_order_id) REFERENCES public.purchase_orders (id) ON DELETE CASCADE,
+    CONSTRAINT purchase_order_lines_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id)
+);
+
+CREATE TABLE public.goods_receipts (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    purchase_order_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    warehouse_id uuid NOT NULL,
+    note text,
+    received_by uuid NOT NULL,
+    received_at timestamp with time zone NOT NULL,
+    CONSTRAINT goods_receipts_pkey PRIMARY KEY (id),
+    CONSTRAINT goods_receipts_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT goods_receipts_purchase_order_fkey FOREIGN KEY (company_id, purchase_order_id) REFERENCES public.purchase_orders (company_id, id),
+    CONSTRAINT goods_receipts_warehouse_fkey FOREIGN KEY (company_id, warehouse_id) REFERENCES public.warehouses (company_id, id)
+);
+
+CREATE INDEX goods_receipts_purchase_order_id_idx ON public.goods_receipts (purchase_order_id);
+
+CREATE TABLE public.goods_receipt_lines (
+    id uuid NOT NULL,
+    goods_receipt_id uuid NOT NULL,
+    position integer NOT NULL,
+    purchase_order_line_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    movement_id uuid NOT NULL,
+    CONSTRAINT goods_receipt_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT goods_receipt_lines_goods_receipt_id_position_key UNIQUE (goods_receipt_id, position),
+    CONSTRAINT goods_receipt_lines_quantity_check CHECK (quantity > 0),
+    CONSTRAINT goods_receipt_lines_goods_receipt_id_fkey FOREIGN KEY (goods_receipt_id) REFERENCES public.goods_receipts (id),
+    CONSTRAINT goods_receipt_lines_purchase_order_line_id_fkey FOREIGN KEY (purchase_order_line_id) REFERENCES public.purchase_order_lines (id),
+    CONSTRAINT goods_receipt_lines_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id),
+    CONSTRAINT goods_receipt_lines_movement_id_fkey FOREIGN KEY (movement_id) REFERENCES public.stock_movements (id)
+);
+
+-- movements posted by goods receipts carry the id of the goods receipt in source_id
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer', 'goods_receipt'));
@@ -0,0 +1,11 @@
+-- movements already posted by shipments stay in the ledger, the narrower check
+-- only applies to new rows
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer', 'goods_receipt')) NOT VALID;
+
+DROP TABLE public.shipment_lines;
+DROP TABLE public.shipments;
+DROP TABLE public.stock_reservations;
+DROP TABLE public.sales_order_lines;
+DROP TABLE public.sales_orders;
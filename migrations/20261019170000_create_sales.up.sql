@@ -0,0 +1,121 @@
+-- sales orders a company takes from a customer, another company in the system.
+-- orders go draft -> confirmed -> partially_shipped -> shipped, or are
+-- cancelled before everything shipped. confirming reserves what the order's
+-- warehouse has available for every line, the rest is backordered until a
+-- later reservation covers it. a reservation lowers what is available but
+-- leaves the ledger alone, stock only leaves when a shipment is shipped
+CREATE TABLE public.sales_orders (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    customer_id uuid NOT NULL,
+    warehouse_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    note text,
+    reserved_until timestamp with time zone,
+    created_by uuid NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    confirmed_by uuid,
+    confirmed_at timestamp with time zone,
+    shipped_at timestamp with time zone,
+    cancelled_at timestamp with time zone,
+    CONSTRAINT sales_orders_pkey PRIMARY KEY (id),
+    CONSTRAINT sales_orders_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT sales_orders_company_id_id_key UNIQUE (company_id, id),
+    CONSTRAINT sales_orders_status_check CHECK (
+        status IN ('draft', 'confirmed', 'partially_shipped', 'shipped', 'cancelled')
+    ),
+    CONSTRAINT sales_orders_customer_id_check CHECK (customer_id <> company_id),
+    CONSTRAINT sales_orders_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT sales_orders_customer_id_fkey FOREIGN KEY (customer_id) REFERENCES public.companies (id),
+    CONSTRAINT sales_orders_warehouse_fkey FOREIGN KEY (company_id, warehouse_id) REFERENCES public.warehouses (company_id, id)
+);
+
+CREATE INDEX sales_orders_company_id_status_idx ON public.sales_orders (company_id, status);
+CREATE INDEX sales_orders_reserved_until_idx ON public.sales_orders (reserved_until) WHERE reserved_until IS NOT NULL;
+
+-- reserved_quantity is held for the line, picked_quantity is the part of it on
+-- shipments not shipped yet. what is neither reserved nor shipped is backordered
+CREATE TABLE public.sales_order_lines (
+    id uuid NOT NULL,
+    sales_order_id uuid NOT NULL,
+    position integer NOT NULL,
+    product_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    reserved_quantity bigint NOT NULL DEFAULT 0,
+    picked_quantity bigint NOT NULL DEFAULT 0,
+    shipped_quantity bigint NOT NULL DEFAULT 0,
+    CONSTRAINT sales_order_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT sales_order_lines_sales_order_id_position_key UNIQUE (sales_order_id, position),
+    CONSTRAINT sales_order_lines_quantity_check CHECK (
+        quantity > 0
+        AND shipped_quantity >= 0
+        AND picked_quantity >= 0
+        AND picked_quantity <= reserved_quantity
+        AND reserved_quantity + shipped_quantity <= quantity
+    ),
+    CONSTRAINT sales_order_lines_sales_order_id_fkey FOREIGN KEY (sales_order_id) REFERENCES public.sales_orders (id) ON DELETE CASCADE,
+    CONSTRAINT sales_order_lines_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id)
+);
+
+-- reserved quantity per product and warehouse, the sum of reserved_quantity
+-- over the lines of the open orders. the row is locked while reserving, like
+-- stock_balances while posting, so two orders can not take the same stock
+CREATE TABLE public.stock_reservations (
+    product_id uuid NOT NULL,
+    warehouse_id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_reservations_pkey PRIMARY KEY (product_id, warehouse_id),
+    CONSTRAINT stock_reservations_quantity_check CHECK (quantity >= 0),
+    CONSTRAINT stock_reservations_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_reservations_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_reservations_warehouse_fkey FOREIGN KEY (company_id, warehouse_id) REFERENCES public.warehouses (company_id, id)
+);
+
+-- reserved stock picked from locations of the order's warehouse. shipments go
+-- picking -> packed -> shipped, shipping posts an issue movement per line.
+-- a cancelled shipment hands its quantities back to the reservation
+CREATE TABLE public.shipments (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    sales_order_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    note text,
+    created_by uuid NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    packed_at timestamp with time zone,
+    shipped_by uuid,
+    shipped_at timestamp with time zone,
+    cancelled_at timestamp with time zone,
+    CONSTRAINT shipments_pkey PRIMARY KEY (id),
+    CONSTRAINT shipments_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT shipments_status_check CHECK (status IN ('picking', 'packed', 'shipped', 'cancelled')),
+    CONSTRAINT shipments_sales_order_fkey FOREIGN KEY (company_id, sales_order_id) REFERENCES public.sales_orders (company_id, id)
+);
+
+CREATE INDEX shipments_sales_order_id_idx ON public.shipments (sales_order_id);
+
+CREATE TABLE public.shipment_lines (
+    id uuid NOT NULL,
+    shipment_id uuid NOT NULL,
+    position integer NOT NULL,
+    sales_order_line_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    movement_id uuid,
+    CONSTRAINT shipment_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT shipment_lines_shipment_id_position_key UNIQUE (shipment_id, position),
+    CONSTRAINT shipment_lines_quantity_check CHECK (quantity > 0),
+    CONSTRAINT shipment_lines_shipment_id_fkey FOREIGN KEY (shipment_id) REFERENCES public.shipments (id),
+    CONSTRAINT shipment_lines_sales_order_line_id_fkey FOREIGN KEY (sales_order_line_id) REFERENCES public.sales_order_lines (id),
+    CONSTRAINT shipment_lines_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id),
+    CONSTRAINT shipment_lines_movement_id_fkey FOREIGN KEY (movement_id) REFERENCES public.stock_movements (id)
+);
+
+-- movements posted by shipments carry the id of the shipment in source_id
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer', 'goods_receipt', 'shipment'));
@@ -0,0 +1,7 @@
+ALTER TABLE public.shipment_lines DROP COLUMN lot_id;
+ALTER TABLE public.goods_receipt_lines DROP COLUMN lot_id;
+ALTER TABLE public.stock_transfer_lines DROP COLUMN lot_id;
+ALTER TABLE public.stock_movements DROP COLUMN lot_id;
+DROP TABLE public.stock_lot_balances;
+DROP TABLE public.stock_lots;
+ALTER TABLE public.products DROP COLUMN is_lot_tracked;
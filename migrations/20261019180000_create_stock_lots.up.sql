@@ -0,0 +1,57 @@
+-- lots of the products flagged as lot tracked. every movement of such a
+-- product names its lot, stock_lot_balances holds the running quantity per lot
+-- and location next to stock_balances and is locked and updated in the same
+-- transaction, so the lot balances of a product and location add up to its
+-- balance. the flag is fixed once the product is created
+ALTER TABLE public.products
+    ADD COLUMN is_lot_tracked boolean NOT NULL DEFAULT false;
+
+CREATE TABLE public.stock_lots (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    product_id uuid NOT NULL,
+    lot_number VARCHAR(50) NOT NULL,
+    manufactured_on date,
+    expires_on date,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_lots_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_lots_product_id_lot_number_key UNIQUE (product_id, lot_number),
+    CONSTRAINT stock_lots_dates_check CHECK (expires_on >= manufactured_on),
+    CONSTRAINT stock_lots_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_lots_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id)
+);
+
+CREATE INDEX stock_lots_company_id_expires_on_idx ON public.stock_lots (company_id, expires_on);
+
+CREATE TABLE public.stock_lot_balances (
+    lot_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    product_id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_lot_balances_pkey PRIMARY KEY (lot_id, location_id),
+    CONSTRAINT stock_lot_balances_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id),
+    CONSTRAINT stock_lot_balances_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id),
+    CONSTRAINT stock_lot_balances_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_lot_balances_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id)
+);
+
+CREATE INDEX stock_lot_balances_product_id_location_id_idx ON public.stock_lot_balances (product_id, location_id);
+
+ALTER TABLE public.stock_movements
+    ADD COLUMN lot_id uuid,
+    ADD CONSTRAINT stock_movements_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id);
+
+-- the lines that post movements carry the lot they post
+ALTER TABLE public.stock_transfer_lines
+    ADD COLUMN lot_id uuid,
+    ADD CONSTRAINT stock_transfer_lines_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id);
+
+ALTER TABLE public.goods_receipt_lines
+    ADD COLUMN lot_id uuid,
+    ADD CONSTRAINT goods_receipt_lines_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id);
+
+ALTER TABLE public.shipment_lines
+    ADD COLUMN lot_id uuid,
+    ADD CONSTRAINT shipment_lines_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id);
@@ -0,0 +1,6 @@
+ALTER TABLE public.shipment_lines DROP COLUMN serial_numbers;
+ALTER TABLE public.goods_receipt_lines DROP COLUMN serial_numbers;
+ALTER TABLE public.stock_transfer_lines DROP COLUMN serial_numbers;
+DROP TABLE public.stock_movement_serials;
+DROP TABLE public.stock_serials;
+ALTER TABLE public.products DROP CONSTRAINT products_tracking_check, DROP COLUMN is_serialised;
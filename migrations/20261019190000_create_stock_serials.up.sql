@@ -0,0 +1,51 @@
+-- serial numbers of the products flagged as serialised, one row per unit. every
+-- movement of such a product lists one serial per unit it moves in
+-- stock_movement_serials and moves the serials with it, so the in stock serials
+-- of a product and location count up to its balance. a serial is at a location
+-- exactly while it is in stock. the flag is fixed once the product is created
+-- and excludes lot tracking
+ALTER TABLE public.products
+    ADD COLUMN is_serialised boolean NOT NULL DEFAULT false,
+    ADD CONSTRAINT products_tracking_check CHECK (NOT (is_lot_tracked AND is_serialised));
+
+CREATE TABLE public.stock_serials (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    product_id uuid NOT NULL,
+    serial_number VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    location_id uuid,
+    created_at timestamp with time zone NOT NULL,
+    updated_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_serials_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_serials_product_id_serial_number_key UNIQUE (product_id, serial_number),
+    CONSTRAINT stock_serials_status_check CHECK (status IN ('in_stock', 'in_transit', 'issued')),
+    CONSTRAINT stock_serials_location_check CHECK ((status = 'in_stock') = (location_id IS NOT NULL)),
+    CONSTRAINT stock_serials_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_serials_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_serials_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id)
+);
+
+CREATE INDEX stock_serials_company_id_idx ON public.stock_serials (company_id);
+CREATE INDEX stock_serials_product_id_location_id_idx ON public.stock_serials (product_id, location_id);
+
+CREATE TABLE public.stock_movement_serials (
+    movement_id uuid NOT NULL,
+    serial_id uuid NOT NULL,
+    CONSTRAINT stock_movement_serials_pkey PRIMARY KEY (movement_id, serial_id),
+    CONSTRAINT stock_movement_serials_movement_id_fkey FOREIGN KEY (movement_id) REFERENCES public.stock_movements (id),
+    CONSTRAINT stock_movement_serials_serial_id_fkey FOREIGN KEY (serial_id) REFERENCES public.stock_serials (id)
+);
+
+CREATE INDEX stock_movement_serials_serial_id_idx ON public.stock_movement_serials (serial_id);
+
+-- the lines that post movements carry the serials they post, in the order the
+-- units of a transfer line are received
+ALTER TABLE public.stock_transfer_lines
+    ADD COLUMN serial_numbers text[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE public.goods_receipt_lines
+    ADD COLUMN serial_numbers text[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE public.shipment_lines
+    ADD COLUMN serial_numbers text[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+ALTER TABLE public.sales_order_lines DROP COLUMN unit_quantity, DROP COLUMN unit;
+ALTER TABLE public.purchase_order_lines DROP COLUMN unit_quantity, DROP COLUMN unit;
+DROP TABLE public.product_units;
@@ -0,0 +1,53 @@
+-- the units a product is bought or sold in besides its unit_of_measure, which
+-- stays the base unit the ledger counts in. factor is the number of base units
+-- in one unit, kept as exact numeric. a quantity entered in the unit may have
+-- up to decimal_places decimals, rounding says how a converted quantity that
+-- is not a whole number of base units is turned into one: exact refuses it
+CREATE TABLE public.product_units (
+    id uuid NOT NULL,
+    product_id uuid NOT NULL,
+    code VARCHAR(20) NOT NULL,
+    factor numeric NOT NULL,
+    decimal_places smallint NOT NULL DEFAULT 0,
+    rounding VARCHAR(20) NOT NULL DEFAULT 'exact',
+    is_purchase_unit boolean NOT NULL DEFAULT true,
+    is_sales_unit boolean NOT NULL DEFAULT true,
+    created_at timestamp with time zone NOT NULL,
+    CONSTRAINT product_units_pkey PRIMARY KEY (id),
+    CONSTRAINT product_units_product_id_code_key UNIQUE (product_id, code),
+    CONSTRAINT product_units_factor_check CHECK (factor > 0),
+    CONSTRAINT product_units_decimal_places_check CHECK (decimal_places BETWEEN 0 AND 6),
+    CONSTRAINT product_units_rounding_check CHECK (rounding IN ('exact', 'up', 'down', 'half_up')),
+    CONSTRAINT product_units_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id) ON DELETE CASCADE
+);
+
+-- order lines keep the unit and quantity they were entered in, quantity stays
+-- the converted base quantity everything else works with. existing lines were
+-- entered in the base unit
+ALTER TABLE public.purchase_order_lines
+    ADD COLUMN unit VARCHAR(20),
+    ADD COLUMN unit_quantity numeric;
+
+UPDATE public.purchase_order_lines l
+SET unit = p.unit_of_measure, unit_quantity = l.quantity
+FROM public.products p
+WHERE p.id = l.product_id;
+
+ALTER TABLE public.purchase_order_lines
+    ALTER COLUMN unit SET NOT NULL,
+    ALTER COLUMN unit_quantity SET NOT NULL,
+    ADD CONSTRAINT purchase_order_lines_unit_quantity_check CHECK (unit_quantity > 0);
+
+ALTER TABLE public.sales_order_lines
+    ADD COLUMN unit VARCHAR(20),
+    ADD COLUMN unit_quantity numeric;
+
+UPDATE public.sales_order_lines l
+SET unit = p.unit_of_measure, unit_quantity = l.quantity
+FROM public.products p
+WHERE p.id = l.product_id;
+
+ALTER TABLE public.sales_order_lines
+    ALTER COLUMN unit SET NOT NULL,
+    ALTER COLUMN unit_quantity SET NOT NULL,
+    ADD CONSTRAINT sales_order_lines_unit_quantity_check CHECK (unit_quantity > 0);
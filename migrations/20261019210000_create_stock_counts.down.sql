@@ -0,0 +1,9 @@
+-- movements already posted by counts stay in the ledger, the narrower check
+-- only applies to new rows
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer', 'goods_receipt', 'shipment')) NOT VALID;
+
+DROP TABLE public.stock_count_entries;
+DROP TABLE public.stock_count_lines;
+DROP TABLE public.stock_counts;
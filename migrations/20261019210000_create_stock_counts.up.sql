@@ -0,0 +1,83 @@
+-- count sessions take stock of a whole warehouse, or of one location of it and
+-- everything below it. opening a session snapshots the on-hand quantity of
+-- every product and lot in scope as the expected quantity of a line, stock
+-- found that was not expected gets a line with 0 expected. every entry of a
+-- counter is kept, the latest one is the counted quantity of its line.
+-- sessions go open -> completed, or open -> pending_approval -> completed when
+-- a variance passes the approval threshold, and can be cancelled until they
+-- are completed. completing posts an adjustment per line with a variance,
+-- nothing can be posted on a counted location while its session is open or
+-- pending approval
+CREATE TABLE public.stock_counts (
+    id uuid NOT NULL,
+    company_id uuid NOT NULL,
+    number VARCHAR(30) NOT NULL,
+    warehouse_id uuid NOT NULL,
+    location_id uuid,
+    status VARCHAR(20) NOT NULL,
+    is_blind boolean NOT NULL DEFAULT false,
+    approval_threshold bigint,
+    note text,
+    created_by uuid NOT NULL,
+    created_at timestamp with time zone NOT NULL,
+    submitted_at timestamp with time zone,
+    approved_by uuid,
+    approved_at timestamp with time zone,
+    completed_at timestamp with time zone,
+    cancelled_at timestamp with time zone,
+    CONSTRAINT stock_counts_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_counts_company_id_number_key UNIQUE (company_id, number),
+    CONSTRAINT stock_counts_status_check CHECK (
+        status IN ('open', 'pending_approval', 'completed', 'cancelled')
+    ),
+    CONSTRAINT stock_counts_approval_threshold_check CHECK (approval_threshold >= 0),
+    CONSTRAINT stock_counts_company_id_fkey FOREIGN KEY (company_id) REFERENCES public.companies (id),
+    CONSTRAINT stock_counts_warehouse_fkey FOREIGN KEY (company_id, warehouse_id) REFERENCES public.warehouses (company_id, id),
+    CONSTRAINT stock_counts_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id)
+);
+
+CREATE INDEX stock_counts_company_id_status_idx ON public.stock_counts (company_id, status);
+CREATE INDEX stock_counts_warehouse_id_status_idx ON public.stock_counts (warehouse_id, status);
+
+CREATE TABLE public.stock_count_lines (
+    id uuid NOT NULL,
+    count_id uuid NOT NULL,
+    position integer NOT NULL,
+    product_id uuid NOT NULL,
+    location_id uuid NOT NULL,
+    lot_id uuid,
+    expected_quantity bigint NOT NULL,
+    counted_quantity bigint,
+    counted_by uuid,
+    counted_at timestamp with time zone,
+    movement_id uuid,
+    CONSTRAINT stock_count_lines_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_count_lines_count_id_position_key UNIQUE (count_id, position),
+    CONSTRAINT stock_count_lines_count_id_product_id_location_id_lot_id_key UNIQUE NULLS NOT DISTINCT (count_id, product_id, location_id, lot_id),
+    CONSTRAINT stock_count_lines_counted_quantity_check CHECK (counted_quantity >= 0),
+    CONSTRAINT stock_count_lines_count_id_fkey FOREIGN KEY (count_id) REFERENCES public.stock_counts (id) ON DELETE CASCADE,
+    CONSTRAINT stock_count_lines_product_id_fkey FOREIGN KEY (product_id) REFERENCES public.products (id),
+    CONSTRAINT stock_count_lines_location_id_fkey FOREIGN KEY (location_id) REFERENCES public.warehouse_locations (id),
+    CONSTRAINT stock_count_lines_lot_id_fkey FOREIGN KEY (lot_id) REFERENCES public.stock_lots (id),
+    CONSTRAINT stock_count_lines_movement_id_fkey FOREIGN KEY (movement_id) REFERENCES public.stock_movements (id)
+);
+
+CREATE TABLE public.stock_count_entries (
+    id uuid NOT NULL,
+    count_id uuid NOT NULL,
+    line_id uuid NOT NULL,
+    quantity bigint NOT NULL,
+    counted_by uuid NOT NULL,
+    counted_at timestamp with time zone NOT NULL,
+    CONSTRAINT stock_count_entries_pkey PRIMARY KEY (id),
+    CONSTRAINT stock_count_entries_quantity_check CHECK (quantity >= 0),
+    CONSTRAINT stock_count_entries_count_id_fkey FOREIGN KEY (count_id) REFERENCES public.stock_counts (id) ON DELETE CASCADE,
+    CONSTRAINT stock_count_entries_line_id_fkey FOREIGN KEY (line_id) REFERENCES public.stock_count_lines (id) ON DELETE CASCADE
+);
+
+CREATE INDEX stock_count_entries_count_id_idx ON public.stock_count_entries (count_id);
+
+-- movements posted by counts carry the id of the count in source_id
+ALTER TABLE public.stock_movements
+    DROP CONSTRAINT stock_movements_source_check,
+    ADD CONSTRAINT stock_movements_source_check CHECK (source IN ('manual', 'transfer', 'goods_receipt', 'shipment', 'count'));
@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::app_cli::create_user::create_user;
+use crate::app_cli::migrate::migrate;
+use crate::app_cli::seed::seed;
+use crate::app_cli::serve::serve;
+use crate::app_config::config::AppConfig;
+use crate::app_response::error::error_chain;
+use crate::user::domain::user::UserRole;
+
+// every command reads the same configuration as the server, see config.example.toml
+#[derive(Debug, Parser)]
+#[command(name = "be-inventory-rust", version, about = "Inventory backend")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum Command {
+    /// Run the HTTP server, the default without a command
+    Serve,
+    /// Apply, revert or list the migrations embedded in this binary
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Create a user, the password is read from stdin
+    CreateUser {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        email: String,
+        /// Display name, the username when left out
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        phone_number: Option<String>,
+        #[arg(long, default_value = "staff")]
+        role: UserRole,
+    },
+    /// Insert fixture data, rows that already exist are left alone
+    Seed {
+        #[arg(long, value_enum)]
+        fixtures: Fixtures,
+    },
+    /// Load and validate the configuration, then print it with secrets hidden
+    CheckConfig,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum MigrateAction {
+    /// Apply every pending migration
+    Up,
+    /// Revert the most recently applied migrations
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// List the migrations and whether they are applied
+    Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Fixtures {
+    /// An admin and a staff account plus a few companies, for local development
+    Dev,
+}
+
+pub async fn run(cli: Cli) -> ExitCode {
+    let command = cli.command.unwrap_or(Command::Serve);
+
+    // logging is configured from the config, so load errors go to stderr
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result: Result<(), Box<dyn Error>> = match command {
+        Command::Serve => serve(config).await,
+        Command::Migrate { action } => migrate(&config, action).await,
+        Command::CreateUser {
+            username,
+            email,
+            name,
+            phone_number,
+            role,
+        } => create_user(&config, username, email, name, phone_number, role).await,
+        Command::Seed { fixtures } => seed(&config, fixtures).await,
+        Command::CheckConfig => {
+            println!("{config:#?}");
+            println!("configuration is valid");
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", error_chain(err.as_ref()));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use clap::{CommandFactory, Parser};
+
+use crate::app_cli::cli::{Cli, Command, Fixtures, MigrateAction};
+use crate::user::domain::user::UserRole;
+
+fn parse(args: &[&str]) -> Result<Option<Command>, clap::Error> {
+    let args = std::iter::once("be-inventory-rust").chain(args.iter().copied());
+    Cli::try_parse_from(args).map(|cli| cli.command)
+}
+
+#[test]
+fn cli_definition_is_consistent() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn no_command_means_serve() {
+    assert_eq!(parse(&[]).unwrap(), None);
+    assert_eq!(parse(&["serve"]).unwrap(), Some(Command::Serve));
+}
+
+#[test]
+fn migrate_actions_are_parsed() {
+    let migrate = |action| Some(Command::Migrate { action });
+
+    assert_eq!(
+        parse(&["migrate", "up"]).unwrap(),
+        migrate(MigrateAction::Up)
+    );
+    assert_eq!(
+        parse(&["migrate", "status"]).unwrap(),
+        migrate(MigrateAction::Status)
+    );
+    assert_eq!(
+        parse(&["migrate", "down"]).unwrap(),
+        migrate(MigrateAction::Down { steps: 1 })
+    );
+    assert_eq!(
+        parse(&["migrate", "down", "--steps", "3"]).unwrap(),
+        migrate(MigrateAction::Down { steps: 3 })
+    );
+    assert!(parse(&["migrate", "sideways"]).is_err());
+}
+
+#[test]
+fn create_user_takes_a_role() {
+    let command = parse(&[
+        "create-user",
+        "--username",
+        "budi",
+        "--email",
+        "budi@mail.com",
+        "--role",
+        "admin",
+    ])
+    .unwrap();
+
+    assert_eq!(
+        command,
+        Some(Command::CreateUser {
+            username: "budi".into(),
+            email: "budi@mail.com".into(),
+            name: None,
+            phone_number: None,
+            role: UserRole::Admin,
+        })
+    );
+    let unknown_role = parse(&[
+        "create-user",
+        "--username",
+        "a",
+        "--email",
+        "a@b",
+        "--role",
+        "root",
+    ]);
+    assert!(unknown_role.is_err());
+}
+
+#[test]
+fn seed_needs_known_fixtures() {
+    assert_eq!(
+        parse(&["seed", "--fixtures", "dev"]).unwrap(),
+        Some(Command::Seed {
+            fixtures: Fixtures::Dev
+        })
+    );
+    assert!(parse(&["seed", "--fixtures", "prod"]).is_err());
+    assert!(parse(&["seed"]).is_err());
+}
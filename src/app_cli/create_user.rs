@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::app_config::config::AppConfig;
+use crate::app_database::pool::connect_pool;
+use crate::user::domain::user::UserRole;
+use crate::user::repository::user_repository_sqlx::UserRepositorySqlx;
+use crate::user::usecase::user_usecase::{CreateUserInput, UserUsecase};
+
+// the password never goes on the command line where ps and shell history see it:
+// `echo "$ADMIN_PASSWORD" | be-inventory-rust create-user --username admin ...`
+pub async fn create_user(
+    config: &AppConfig,
+    username: String,
+    email: String,
+    name: Option<String>,
+    phone_number: Option<String>,
+    role: UserRole,
+) -> Result<(), Box<dyn Error>> {
+    let password = read_password()?;
+
+    let pool = connect_pool(&config.database).await?;
+    let usecase = UserUsecase::new(UserRepositorySqlx::new(pool.clone()));
+    let input = CreateUserInput {
+        name: name.unwrap_or_else(|| username.clone()),
+        username,
+        email,
+        phone_number,
+        role,
+        password,
+    };
+    let user = usecase.create_user(input).await;
+    pool.close().await;
+
+    let user = user?;
+    println!("created {} user {} ({})", user.role, user.username, user.id);
+    Ok(())
+}
+
+fn read_password() -> Result<String, Box<dyn Error>> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("password (input is echoed): ");
+        std::io::stderr().flush()?;
+    }
+
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+
+use crate::app_cli::cli::MigrateAction;
+use crate::app_config::config::AppConfig;
+use crate::app_database::migrations::{
+    MIGRATOR, MigrationState, migrate_down, migrate_up, migration_status,
+};
+use crate::app_database::pool::connect_pool;
+
+pub async fn migrate(config: &AppConfig, action: MigrateAction) -> Result<(), Box<dyn Error>> {
+    let pool = connect_pool(&config.database).await?;
+
+    match action {
+        MigrateAction::Up => {
+            let applied = migrate_up(&pool).await?;
+            if applied.is_empty() {
+                println!("database is up to date");
+            }
+            for version in applied {
+                println!("applied  {version} {}", description(version));
+            }
+        }
+        MigrateAction::Down { steps } => {
+            let reverted = migrate_down(&pool, steps).await?;
+            if reverted.is_empty() {
+                println!("nothing to revert");
+            }
+            for version in reverted {
+                println!("reverted {version} {}", description(version));
+            }
+        }
+        MigrateAction::Status => {
+            for migration in migration_status(&pool).await? {
+                let state = match migration.state {
+                    MigrationState::Applied => "applied",
+                    MigrationState::Pending => "pending",
+                    MigrationState::Modified => "modified",
+                    MigrationState::Failed => "failed",
+                };
+                println!(
+                    "{:<8} {} {}",
+                    state, migration.version, migration.description
+                );
+            }
+        }
+    }
+
+    pool.close().await;
+    Ok(())
+}
+
+fn description(version: i64) -> String {
+    MIGRATOR
+        .iter()
+        .find(|migration| migration.version == version)
+        .map(|migration| migration.description.to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,9 @@
+pub mod cli;
+pub mod create_user;
+pub mod migrate;
+pub mod seed;
+pub mod serve;
+#[cfg(test)]
+mod cli_test;
+#[cfg(test)]
+mod seed_test;
@@ -0,0 +1,126 @@
+use std::error::Error;
+
+use crate::app_cli::cli::Fixtures;
+use crate::app_config::config::AppConfig;
+use crate::app_database::pool::connect_pool;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::company::usecase::company_usecase::{CompanyUsecase, CompanyUsecaseError};
+use crate::user::domain::user::UserRole;
+use crate::user::repository::user_repository::UserRepository;
+use crate::user::repository::user_repository_sqlx::UserRepositorySqlx;
+use crate::user::usecase::user_usecase::{CreateUserInput, UserUsecase, UserUsecaseError};
+
+// well known credentials, never seed them outside local development
+const DEV_PASSWORD: &str = "password";
+
+const DEV_USERS: [(&str, &str, UserRole); 2] = [
+    ("admin", "Administrator", UserRole::Admin),
+    ("staff", "Warehouse Staff", UserRole::Staff),
+];
+
+// code, name, email, phone number, address
+const DEV_COMPANIES: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "ACM",
+        "Acme Corporation",
+        "contact@acme.test",
+        "0211234567",
+        "Jakarta",
+    ),
+    (
+        "GLB",
+        "Globex",
+        "hello@globex.test",
+        "0227654321",
+        "Bandung",
+    ),
+    (
+        "INI",
+        "Initech",
+        "info@initech.test",
+        "0311112222",
+        "Surabaya",
+    ),
+];
+
+#[derive(Debug, Default, PartialEq)]
+pub struct SeedReport {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+pub async fn seed(config: &AppConfig, fixtures: Fixtures) -> Result<(), Box<dyn Error>> {
+    let pool = connect_pool(&config.database).await?;
+    let users = UserUsecase::new(UserRepositorySqlx::new(pool.clone()));
+    let companies =
+        CompanyUsecase::new(CompanyRepositorySqlx::new(pool.clone()), AppMetrics::new());
+
+    let report = match fixtures {
+        Fixtures::Dev => seed_dev(&users, &companies).await,
+    };
+    pool.close().await;
+
+    let report = report?;
+    for name in &report.created {
+        println!("created {name}");
+    }
+    for name in &report.skipped {
+        println!("skipped {name}, already exists");
+    }
+    Ok(())
+}
+
+// safe to run again, existing users and companies are skipped, not updated
+pub async fn seed_dev<U, C>(
+    users: &UserUsecase<U>,
+    companies: &CompanyUsecase<C>,
+) -> Result<SeedReport, Box<dyn Error>>
+where
+    U: UserRepository,
+    C: CompanyRepository,
+{
+    let mut report = SeedReport::default();
+
+    for (username, name, role) in DEV_USERS {
+        let label = format!("user {username}");
+        let input = CreateUserInput {
+            name: name.into(),
+            username: username.into(),
+            email: format!("{username}@inventory.test"),
+            phone_number: None,
+            role,
+            password: DEV_PASSWORD.into(),
+        };
+        match users.create_user(input).await {
+            Ok(_) => report.created.push(label),
+            Err(UserUsecaseError::UsernameAlreadyExist | UserUsecaseError::EmailAlreadyExist) => {
+                report.skipped.push(label)
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    for (code, name, email, phone_number, address) in DEV_COMPANIES {
+        let label = format!("company {code}");
+        let created = companies
+            .create_company(
+                name.into(),
+                email.into(),
+                code.into(),
+                Some(phone_number.into()),
+                Some(address.into()),
+            )
+            .await;
+        match created {
+            Ok(_) => report.created.push(label),
+            Err(CompanyUsecaseError::CodeAlreadyExist | CompanyUsecaseError::EmailAlreadyExist) => {
+                report.skipped.push(label)
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,37 @@
+use crate::app_cli::seed::seed_dev;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::usecase::company_usecase::CompanyUsecase;
+use crate::user::domain::user::UserRole;
+use crate::user::repository::user_repository_memory::UserRepositoryMemory;
+use crate::user::usecase::user_usecase::UserUsecase;
+
+#[tokio::test]
+async fn dev_fixtures_can_be_seeded_twice() {
+    let user_repo = UserRepositoryMemory::new();
+    let company_repo = CompanyRepositoryMemory::new();
+    let users = UserUsecase::new(user_repo.clone());
+    let companies = CompanyUsecase::new(company_repo.clone(), AppMetrics::new());
+
+    let first = seed_dev(&users, &companies).await.unwrap();
+    let second = seed_dev(&users, &companies).await.unwrap();
+
+    assert_eq!(
+        first.created,
+        [
+            "user admin",
+            "user staff",
+            "company ACM",
+            "company GLB",
+            "company INI"
+        ]
+    );
+    assert!(first.skipped.is_empty());
+    assert!(second.created.is_empty());
+    assert_eq!(second.skipped, first.created);
+    assert_eq!(user_repo.users().await.len(), 2);
+    assert_eq!(company_repo.companies().await.len(), 3);
+
+    let admin = users.authenticate("admin", "password").await.unwrap();
+    assert_eq!(admin.role, UserRole::Admin);
+}
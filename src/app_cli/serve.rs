@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use crate::app_config::config::AppConfig;
+use crate::app_database::migrations::migrate_up;
+use crate::app_database::pool::connect_pool;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_lifecycle::shutdown::{serve_with_shutdown, shutdown_signal};
+use crate::app_logging::logging::init_logging;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_metrics::pool_metrics::spawn_pool_metrics;
+use crate::app_rate_limit::rate_limit::{rate_limit_backend, spawn_rate_limit_sweeper};
+use crate::app_router::router::{AppDeps, build_app};
+use crate::sales::reservation_sweeper::spawn_reservation_sweeper;
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecase;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+
+pub async fn serve(config: AppConfig) -> Result<(), Box<dyn Error>> {
+    init_logging(&config.logging);
+
+    info!("server starting");
+    if config.errors.debug {
+        warn!("errors.debug is on, 500 responses include their cause");
+    }
+
+    let pool = connect_pool(&config.database).await?;
+
+    if config.database.auto_migrate {
+        let applied = migrate_up(&pool).await?;
+        info!(?applied, "database migrated");
+    }
+
+    let lifecycle = Lifecycle::new();
+    let metrics = AppMetrics::new();
+
+    if config.metrics.enabled {
+        let every = Duration::from_secs(config.metrics.pool_sample_interval_secs);
+        spawn_pool_metrics(&lifecycle, pool.clone(), metrics.clone(), every);
+    }
+
+    let rate_limit_backend = rate_limit_backend(&config.rate_limit, &pool);
+    if config.rate_limit.enabled {
+        spawn_rate_limit_sweeper(&lifecycle, &config.rate_limit, rate_limit_backend.clone());
+    }
+
+    let sales = SalesOrderUsecase::new(StockRepositorySqlx::new(pool.clone()), metrics.clone());
+    spawn_reservation_sweeper(&lifecycle, &config.sales, sales);
+
+    let deps = AppDeps::postgres(pool.clone(), lifecycle.clone(), metrics, rate_limit_backend);
+    let app = build_app(&config, deps);
+
+    let address = config.server.address();
+    let listener = TcpListener::bind(&address).await?;
+    info!("listening on {address}");
+
+    serve_with_shutdown(
+        listener,
+        app,
+        lifecycle,
+        &config.shutdown,
+        shutdown_signal(),
+    )
+    .await?;
+
+    info!("closing database pool");
+    pool.close().await;
+    info!("server stopped");
+    Ok(())
+}
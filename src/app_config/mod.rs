@@ -0,0 +1,3 @@
+pub mod config;
+#[cfg(test)]
+mod config_test;
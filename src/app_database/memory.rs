@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::error::{DatabaseError, ErrorKind};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::app_database::unit_of_work::UnitOfWork;
+
+// in-memory counterpart of a database used by the in-memory repositories.
+// a unit of work holds the store lock until it finishes, so units of work run
+// one after another and see nothing of each other (serializable).
+pub struct MemoryStore<T> {
+    state: Arc<Mutex<T>>,
+}
+
+impl<T> Clone for MemoryStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Default + Send + 'static> Default for MemoryStore<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Clone + Send + 'static> MemoryStore<T> {
+    pub fn new(state: T) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub async fn begin(&self) -> MemoryUnitOfWork<T> {
+        let committed = self.state.clone().lock_owned().await;
+        let staged = committed.clone();
+        MemoryUnitOfWork { committed, staged }
+    }
+
+    // the last committed state, waits for a running unit of work to finish
+    pub async fn snapshot(&self) -> T {
+        self.state.lock().await.clone()
+    }
+}
+
+pub struct MemoryUnitOfWork<T> {
+    committed: OwnedMutexGuard<T>,
+    staged: T,
+}
+
+impl<T> MemoryUnitOfWork<T> {
+    pub fn state(&self) -> &T {
+        &self.staged
+    }
+
+    pub fn state_mut(&mut self) -> &mut T {
+        &mut self.staged
+    }
+}
+
+#[async_trait]
+impl<T: Send> UnitOfWork for MemoryUnitOfWork<T> {
+    async fn commit(mut self) -> Result<(), sqlx::Error> {
+        *self.committed = self.staged;
+        Ok(())
+    }
+
+    async fn rollback(self) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+}
+
+// the error postgres reports when a unique constraint is violated
+pub fn unique_violation(constraint: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(MemoryDatabaseError {
+        constraint,
+        kind: ErrorKind::UniqueViolation,
+    }))
+}
+
+// the error postgres reports when a row points at a missing row, or a row still
+// pointed at is deleted
+pub fn foreign_key_violation(constraint: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(MemoryDatabaseError {
+        constraint,
+        kind: ErrorKind::ForeignKeyViolation,
+    }))
+}
+
+#[derive(Debug)]
+struct MemoryDatabaseError {
+    constraint: &'static str,
+    kind: ErrorKind,
+}
+
+impl fmt::Display for MemoryDatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} \"{}\"", self.message(), self.constraint)
+    }
+}
+
+impl std::error::Error for MemoryDatabaseError {}
+
+impl DatabaseError for MemoryDatabaseError {
+    fn message(&self) -> &str {
+        match self.kind {
+            ErrorKind::ForeignKeyViolation => "violates foreign key constraint",
+            _ => "duplicate key value violates unique constraint",
+        }
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        match self.kind {
+            ErrorKind::ForeignKeyViolation => Some(Cow::Borrowed("23503")),
+            _ => Some(Cow::Borrowed("23505")),
+        }
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        Some(self.constraint)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self.kind {
+            ErrorKind::ForeignKeyViolation => ErrorKind::ForeignKeyViolation,
+            _ => ErrorKind::UniqueViolation,
+        }
+    }
+}
+
+// postgres ILIKE: case insensitive, % matches any run of characters, _ matches
+// exactly one and a backslash escapes the next character
+pub fn ilike(value: &str, pattern: &str) -> bool {
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    like_from(&value, &pattern)
+}
+
+fn like_from(value: &[char], pattern: &[char]) -> bool {
+    match pattern.first() {
+        None => value.is_empty(),
+        Some('%') => (0..=value.len()).any(|skip| like_from(&value[skip..], &pattern[1..])),
+        Some('_') => !value.is_empty() && like_from(&value[1..], &pattern[1..]),
+        Some('\\') if pattern.len() > 1 => {
+            value.first() == Some(&pattern[1]) && like_from(&value[1..], &pattern[2..])
+        }
+        Some(c) => value.first() == Some(c) && like_from(&value[1..], &pattern[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn commit_publishes_staged_state() {
+        let store = MemoryStore::new(vec![1]);
+
+        let mut uow = store.begin().await;
+        uow.state_mut().push(2);
+        uow.commit().await.unwrap();
+
+        assert_eq!(store.snapshot().await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn rollback_and_drop_discard_staged_state() {
+        let store = MemoryStore::new(vec![1]);
+
+        let mut uow = store.begin().await;
+        uow.state_mut().push(2);
+        uow.rollback().await.unwrap();
+
+        let mut uow = store.begin().await;
+        uow.state_mut().push(3);
+        drop(uow);
+
+        assert_eq!(store.snapshot().await, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn unit_of_work_sees_its_own_writes() {
+        let store = MemoryStore::new(vec![1]);
+
+        let mut uow = store.begin().await;
+        uow.state_mut().push(2);
+
+        assert_eq!(uow.state(), &vec![1, 2]);
+    }
+
+    #[test]
+    fn ilike_follows_postgres_wildcards() {
+        assert!(ilike("Acme Corp", "%acme%"));
+        assert!(ilike("ACME", "a_me"));
+        assert!(ilike("100%", "100\\%"));
+        assert!(!ilike("1000", "100\\%"));
+        assert!(!ilike("Acme", "%corp%"));
+        assert!(ilike("", "%%"));
+    }
+
+    #[test]
+    fn unique_violation_reports_constraint() {
+        let err = unique_violation("companies_code_key");
+        let db_err = err.as_database_error().unwrap();
+
+        assert!(db_err.is_unique_violation());
+        assert_eq!(db_err.constraint(), Some("companies_code_key"));
+    }
+
+    #[test]
+    fn foreign_key_violation_reports_constraint() {
+        let err = foreign_key_violation("products_company_id_fkey");
+        let db_err = err.as_database_error().unwrap();
+
+        assert!(db_err.is_foreign_key_violation());
+        assert_eq!(db_err.constraint(), Some("products_company_id_fkey"));
+    }
+}
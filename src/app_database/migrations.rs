@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use sqlx::migrate::{MigrateError, Migrator};
+
+// migrations/ is embedded at build time, the binary carries its own schema
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    // applied, but the file changed since
+    Modified,
+    // a previous run failed half way, needs a manual fix
+    Failed,
+}
+
+// every migration of this build with its state in the database, oldest first
+pub async fn migration_status(pool: &PgPool) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let applied = applied_migrations(pool).await?;
+
+    let status = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| {
+            let state = match applied.get(&migration.version) {
+                None => MigrationState::Pending,
+                Some((false, _)) => MigrationState::Failed,
+                Some((true, checksum)) if *checksum != *migration.checksum => {
+                    MigrationState::Modified
+                }
+                Some(_) => MigrationState::Applied,
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+
+    Ok(status)
+}
+
+// applies every pending migration, returns the versions that were applied
+pub async fn migrate_up(pool: &PgPool) -> Result<Vec<i64>, MigrateError> {
+    let before = applied_versions(pool).await?;
+    MIGRATOR.run(pool).await?;
+    let after = applied_versions(pool).await?;
+
+    Ok(after.into_iter().filter(|v| !before.contains(v)).collect())
+}
+
+// reverts the last `steps` applied migrations, newest first, returns their versions
+pub async fn migrate_down(pool: &PgPool, steps: usize) -> Result<Vec<i64>, MigrateError> {
+    let mut applied = applied_versions(pool).await?;
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let steps = steps.min(applied.len());
+    if steps == 0 {
+        return Ok(Vec::new());
+    }
+    // undo reverts everything newer than the target version
+    let target = applied.get(steps).copied().unwrap_or(0);
+    MIGRATOR.undo(pool, target).await?;
+
+    Ok(applied[..steps].to_vec())
+}
+
+async fn applied_versions(pool: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+    let applied = applied_migrations(pool).await?;
+    let mut versions: Vec<i64> = applied.into_keys().collect();
+    versions.sort_unstable();
+    Ok(versions)
+}
+
+// empty before the first migration, sqlx creates the table on the first run
+async fn applied_migrations(pool: &PgPool) -> Result<HashMap<i64, (bool, Vec<u8>)>, sqlx::Error> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+        .fetch_one(pool)
+        .await?;
+    if exists.is_none() {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i64, bool, Vec<u8>)> =
+        sqlx::query_as("SELECT version, success, checksum FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(version, success, checksum)| (version, (success, checksum)))
+        .collect())
+}
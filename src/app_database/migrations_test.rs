@@ -0,0 +1,74 @@
+use crate::app_database::migrations::{MigrationState, migrate_down, migrate_up, migration_status};
+use crate::app_database::test_database::TestDatabase;
+
+const ROLE_MIGRATION: i64 = 20261019100000;
+
+async fn states(db: &TestDatabase) -> Vec<MigrationState> {
+    let status = migration_status(&db.pool).await.unwrap();
+    status
+        .into_iter()
+        .map(|migration| migration.state)
+        .collect()
+}
+
+async fn has_role_column(db: &TestDatabase) -> bool {
+    sqlx::query_scalar(
+        "SELECT EXISTS (
+            SELECT 1 FROM information_schema.columns
+            WHERE table_name = 'users' AND column_name = 'role'
+        )",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap()
+}
+
+// needs TEST_DATABASE_URL, skipped without it
+#[tokio::test]
+async fn migrations_can_be_reverted_and_reapplied() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let total = states(&db).await.len();
+    assert!(
+        states(&db)
+            .await
+            .iter()
+            .all(|s| *s == MigrationState::Applied)
+    );
+    assert!(migrate_up(&db.pool).await.unwrap().is_empty());
+
+    // back to before the users role migration, newer migrations go with it
+    let versions: Vec<i64> = migration_status(&db.pool)
+        .await
+        .unwrap()
+        .iter()
+        .map(|migration| migration.version)
+        .collect();
+    let steps = total - versions.iter().position(|v| *v == ROLE_MIGRATION).unwrap();
+    let reverted = migrate_down(&db.pool, steps).await.unwrap();
+    assert_eq!(reverted.len(), steps);
+    assert!(reverted[0] > reverted[1], "newest first: {reverted:?}");
+    assert_eq!(reverted.last(), Some(&ROLE_MIGRATION));
+    let after_down = states(&db).await;
+    assert!(
+        after_down[total - steps..]
+            .iter()
+            .all(|s| *s == MigrationState::Pending)
+    );
+    assert!(!has_role_column(&db).await);
+
+    let applied = migrate_up(&db.pool).await.unwrap();
+    assert_eq!(applied, reverted.into_iter().rev().collect::<Vec<_>>());
+    assert!(has_role_column(&db).await);
+
+    // reverting everything leaves an empty schema that migrates again
+    assert_eq!(
+        migrate_down(&db.pool, usize::MAX).await.unwrap().len(),
+        total
+    );
+    assert!(migrate_down(&db.pool, 1).await.unwrap().is_empty());
+    assert_eq!(migrate_up(&db.pool).await.unwrap().len(), total);
+
+    db.drop_database().await;
+}
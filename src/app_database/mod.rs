@@ -0,0 +1,11 @@
+pub mod migrations;
+#[cfg(test)]
+mod migrations_test;
+pub mod pool;
+pub mod unit_of_work;
+#[cfg(any(test, feature = "testing"))]
+pub mod memory;
+#[cfg(test)]
+pub mod test_database;
+#[cfg(test)]
+pub mod test_seed;
@@ -0,0 +1,55 @@
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{ConnectOptions, Connection, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::app_database::migrations::MIGRATOR;
+
+// a throwaway database with every migration applied. tests that need postgres
+// read TEST_DATABASE_URL and are skipped when it is not set.
+pub struct TestDatabase {
+    pub pool: PgPool,
+    name: String,
+    admin: PgConnectOptions,
+}
+
+impl TestDatabase {
+    pub async fn create() -> Option<Self> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set, skipping postgres test");
+            return None;
+        };
+
+        let admin: PgConnectOptions = url.parse().expect("invalid TEST_DATABASE_URL");
+        let name = format!("test_{}", Uuid::new_v4().simple());
+
+        let mut conn = admin.connect().await.expect("can not connect to TEST_DATABASE_URL");
+        sqlx::query(&format!(r#"CREATE DATABASE "{name}""#))
+            .execute(&mut conn)
+            .await
+            .expect("can not create test database");
+        conn.close().await.ok();
+
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(admin.clone().database(&name))
+            .await
+            .expect("can not connect to test database");
+        MIGRATOR
+            .run(&pool)
+            .await
+            .expect("can not migrate test database");
+
+        Some(Self { pool, name, admin })
+    }
+
+    pub async fn drop_database(self) {
+        self.pool.close().await;
+
+        let mut conn: PgConnection = self.admin.connect().await.expect("can not reconnect");
+        sqlx::query(&format!(r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#, self.name))
+            .execute(&mut conn)
+            .await
+            .expect("can not drop test database");
+        conn.close().await.ok();
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository::ProductRepository;
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+
+// rows the tests seed through the repositories. the contract tests use fixed
+// ids so the memory and the sqlx run of a scenario see the same rows
+
+pub const ACME_ID: Uuid = Uuid::from_u128(1);
+pub const GLOBEX_ID: Uuid = Uuid::from_u128(2);
+
+// postgres keeps microseconds, whole minutes survive the round trip
+pub fn at(minutes: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes)
+}
+
+// a company with an id of its own, for tests that do not need fixed ids
+pub fn company(code: &str) -> Company {
+    company_with_id(Uuid::new_v4(), code)
+}
+
+fn company_with_id(id: Uuid, code: &str) -> Company {
+    Company {
+        id,
+        name: format!("Company {code}"),
+        email: format!("{}@mail.com", code.to_lowercase()),
+        code: code.into(),
+        phone_number: None,
+        address: None,
+        created_at: at(0),
+    }
+}
+
+// ACM and GLB
+pub fn companies() -> Vec<Company> {
+    vec![
+        company_with_id(ACME_ID, "ACM"),
+        company_with_id(GLOBEX_ID, "GLB"),
+    ]
+}
+
+pub async fn seed_companies<R: CompanyRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    for company in companies() {
+        repo.create_company(&mut uow, company).await.unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+// an active product counted in pieces, neither lot tracked nor serialised
+pub fn product(id: Uuid, company_id: Uuid, sku: &str) -> Product {
+    Product {
+        id,
+        company_id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: at(0),
+    }
+}
+
+pub fn warehouse(id: Uuid, company_id: Uuid, code: &str) -> Warehouse {
+    Warehouse {
+        id,
+        company_id,
+        code: code.into(),
+        name: code.to_lowercase(),
+        address: None,
+        created_at: at(0),
+    }
+}
+
+// a bin at the top of the warehouse
+pub fn bin(id: Uuid, warehouse_id: Uuid, code: &str) -> Location {
+    Location {
+        id,
+        warehouse_id,
+        parent_id: None,
+        code: code.into(),
+        name: format!("Bin {code}"),
+        kind: LocationKind::Bin,
+        created_at: at(0),
+    }
+}
+
+// what a scenario stocks, the companies are seeded before it. locations are
+// created in order, parents first
+pub struct Catalogue {
+    pub products: Vec<Product>,
+    pub warehouses: Vec<Warehouse>,
+    pub locations: Vec<Location>,
+}
+
+pub async fn seed_catalogue<P: ProductRepository, W: WarehouseRepository>(
+    products: &P,
+    warehouses: &W,
+    catalogue: Catalogue,
+) {
+    let mut uow = products.begin().await.unwrap();
+    for product in catalogue.products {
+        products.create_product(&mut uow, product).await.unwrap();
+    }
+    uow.commit().await.unwrap();
+
+    let mut uow = warehouses.begin().await.unwrap();
+    for warehouse in catalogue.warehouses {
+        warehouses
+            .create_warehouse(&mut uow, warehouse)
+            .await
+            .unwrap();
+    }
+    for location in catalogue.locations {
+        warehouses
+            .create_location(&mut uow, location)
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
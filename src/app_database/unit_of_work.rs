@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use sqlx::Postgres;
+
+// the unit of work opened by a usecase, handed to every repository call that
+// must be atomic and finished with commit or rollback.
+// dropping it without commit behaves like rollback.
+#[async_trait]
+pub trait UnitOfWork: Send {
+    async fn commit(self) -> Result<(), sqlx::Error>;
+    async fn rollback(self) -> Result<(), sqlx::Error>;
+}
+
+// implemented by repositories so usecases can open a unit of work on them
+#[async_trait]
+pub trait Transactional: Send + Sync {
+    type Uow: UnitOfWork;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error>;
+}
+
+pub type PgUnitOfWork = sqlx::Transaction<'static, Postgres>;
+
+#[async_trait]
+impl UnitOfWork for PgUnitOfWork {
+    async fn commit(self) -> Result<(), sqlx::Error> {
+        sqlx::Transaction::commit(self).await
+    }
+
+    async fn rollback(self) -> Result<(), sqlx::Error> {
+        sqlx::Transaction::rollback(self).await
+    }
+}
+
+// commits when the work succeeded, rolls back and keeps the original error otherwise
+pub async fn finish<U, T, E>(
+    uow: U,
+    result: Result<T, E>,
+    on_commit_error: impl FnOnce(sqlx::Error) -> E,
+) -> Result<T, E>
+where
+    U: UnitOfWork,
+{
+    match result {
+        Ok(value) => {
+            uow.commit().await.map_err(on_commit_error)?;
+            Ok(value)
+        }
+        Err(err) => {
+            // the caller cares about why the work failed, not about the rollback
+            let _ = uow.rollback().await;
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Up,
+    Down,
+}
+
+// status plus whatever the check wants to report, flattened into one object:
+// {"status": "up", "latency_ms": 2}
+#[derive(Serialize, Debug, Clone)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+impl CheckResult {
+    pub fn up() -> Self {
+        Self {
+            status: CheckStatus::Up,
+            details: Map::new(),
+        }
+    }
+
+    pub fn down(error: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Down,
+            details: Map::new(),
+        }
+        .with("error", error.into())
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.details.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.status == CheckStatus::Up
+    }
+}
+
+// one dependency the instance needs to serve traffic
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn check(&self) -> CheckResult;
+}
+
+// runs every check concurrently, a check that does not answer within the
+// timeout counts as down
+#[derive(Clone)]
+pub struct HealthChecks {
+    checks: Vec<Arc<dyn HealthCheck>>,
+    timeout: Duration,
+}
+
+impl HealthChecks {
+    pub fn new(checks: Vec<Arc<dyn HealthCheck>>, timeout: Duration) -> Self {
+        Self { checks, timeout }
+    }
+
+    pub async fn run(&self) -> Vec<(&'static str, CheckResult)> {
+        let results = self.checks.iter().map(|check| async move {
+            let result = match tokio::time::timeout(self.timeout, check.check()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    CheckResult::down(format!("timed out after {}ms", self.timeout.as_millis()))
+                }
+            };
+            (check.name(), result)
+        });
+
+        join_all(results).await
+    }
+}
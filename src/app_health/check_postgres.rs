@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+use sqlx::{Pool, Postgres};
+use tracing::warn;
+
+use crate::app_database::migrations::MIGRATOR;
+use crate::app_health::check::{CheckResult, HealthCheck};
+
+pub struct DatabaseCheck {
+    pool: Pool<Postgres>,
+}
+
+impl DatabaseCheck {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DatabaseCheck {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn check(&self) -> CheckResult {
+        let started = Instant::now();
+        match sqlx::query("SELECT 1").execute(&self.pool).await {
+            Ok(_) => CheckResult::up().with("latency_ms", started.elapsed().as_millis() as u64),
+            // the error can name hosts, users and databases, the readiness
+            // answer is public so it only goes to the log
+            Err(err) => {
+                warn!(check = self.name(), cause = %err, "health check failed");
+                CheckResult::down("unavailable")
+            }
+        }
+    }
+}
+
+// every migration known to this build is applied successfully and unchanged
+pub struct MigrationsCheck {
+    pool: Pool<Postgres>,
+    migrator: &'static Migrator,
+}
+
+impl MigrationsCheck {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self::with_migrator(pool, &MIGRATOR)
+    }
+
+    pub fn with_migrator(pool: Pool<Postgres>, migrator: &'static Migrator) -> Self {
+        Self { pool, migrator }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for MigrationsCheck {
+    fn name(&self) -> &'static str {
+        "migrations"
+    }
+
+    async fn check(&self) -> CheckResult {
+        let applied: Vec<(i64, bool, Vec<u8>)> =
+            match sqlx::query_as("SELECT version, success, checksum FROM _sqlx_migrations")
+                .fetch_all(&self.pool)
+                .await
+            {
+                Ok(rows) => rows,
+                Err(err) => {
+                    warn!(check = self.name(), cause = %err, "health check failed");
+                    return CheckResult::down("can not read migrations");
+                }
+            };
+        let applied: HashMap<i64, (bool, Vec<u8>)> = applied
+            .into_iter()
+            .map(|(version, success, checksum)| (version, (success, checksum)))
+            .collect();
+
+        let mut pending = Vec::new();
+        let mut failed = Vec::new();
+        let mut modified = Vec::new();
+        let mut expected = 0;
+        for migration in self.migrator.iter() {
+            if migration.migration_type.is_down_migration() {
+                continue;
+            }
+            expected += 1;
+            match applied.get(&migration.version) {
+                None => pending.push(migration.version),
+                Some((false, _)) => failed.push(migration.version),
+                Some((true, checksum)) if *checksum != *migration.checksum => {
+                    modified.push(migration.version)
+                }
+                Some(_) => {}
+            }
+        }
+
+        let result = if pending.is_empty() && failed.is_empty() && modified.is_empty() {
+            CheckResult::up()
+        } else {
+            CheckResult::down("database schema does not match this build")
+                .with("pending", pending)
+                .with("failed", failed)
+                .with("modified", modified)
+        };
+        result
+            .with("expected", expected)
+            .with("applied", applied.len())
+    }
+}
+
+// exhausted means every connection is open and none is idle, requests would
+// queue for acquire_timeout
+pub struct PoolCheck {
+    pool: Pool<Postgres>,
+}
+
+impl PoolCheck {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for PoolCheck {
+    fn name(&self) -> &'static str {
+        "pool"
+    }
+
+    async fn check(&self) -> CheckResult {
+        let size = self.pool.size();
+        let idle = self.pool.num_idle() as u32;
+        let max = self.pool.options().get_max_connections();
+
+        let result = if size >= max && idle == 0 {
+            CheckResult::down("connection pool exhausted")
+        } else {
+            CheckResult::up()
+        };
+        result
+            .with("size", size)
+            .with("idle", idle)
+            .with("max", max)
+    }
+}
+
+pub fn postgres_checks(pool: &Pool<Postgres>) -> Vec<Arc<dyn HealthCheck>> {
+    vec![
+        Arc::new(DatabaseCheck::new(pool.clone())),
+        Arc::new(MigrationsCheck::new(pool.clone())),
+        Arc::new(PoolCheck::new(pool.clone())),
+    ]
+}
@@ -0,0 +1,95 @@
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Map, Value, json};
+
+use crate::app_health::check::{CheckResult, HealthChecks};
+use crate::app_lifecycle::lifecycle::Lifecycle;
+
+// probes read these directly, so no ResponseSuccess envelope
+
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "process is up", body = Object, example = json!({ "status": "alive" })),
+    )
+)]
+pub async fn live_handler() -> impl IntoResponse {
+    Json(json!({ "status": "alive" }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "every dependency is up", body = Object, example = json!({
+            "status": "ready",
+            "checks": { "server": { "status": "up" }, "database": { "status": "up", "latency_ms": 1 } },
+        })),
+        (status = 503, description = "starting, shutting down or a dependency is down", body = Object, example = json!({
+            "status": "not_ready",
+            "checks": { "server": { "status": "down", "error": "shutting down" } },
+        })),
+    )
+)]
+pub async fn ready_handler(
+    State(checks): State<HealthChecks>,
+    Extension(lifecycle): Extension<Lifecycle>,
+) -> Response {
+    // not ready while starting up and from the moment shutdown begins
+    let server = if lifecycle.is_ready() {
+        CheckResult::up()
+    } else if lifecycle.is_shutting_down() {
+        CheckResult::down("shutting down")
+    } else {
+        CheckResult::down("not accepting traffic")
+    };
+
+    let mut ready = server.is_up();
+    let mut breakdown = Map::new();
+    breakdown.insert("server".into(), json!(server));
+    for (name, result) in checks.run().await {
+        ready &= result.is_up();
+        breakdown.insert(name.into(), json!(result));
+    }
+
+    let (status, label) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+    (
+        status,
+        Json(json!({ "status": label, "checks": Value::Object(breakdown) })),
+    )
+        .into_response()
+}
+
+// git_commit and build_profile come from build.rs
+#[utoipa::path(
+    get,
+    path = "/health/version",
+    tag = "health",
+    responses(
+        (status = 200, description = "build information", body = Object, example = json!({
+            "name": "be-inventory-rust",
+            "version": "0.1.0",
+            "git_commit": "0123456789ab",
+            "build_profile": "release",
+        })),
+    )
+)]
+pub async fn version_handler() -> impl IntoResponse {
+    Json(json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_commit": env!("BUILD_GIT_COMMIT"),
+        "build_profile": env!("BUILD_PROFILE"),
+    }))
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use crate::app_health::check::{CheckResult, HealthCheck};
+use crate::app_router::test_app::{TestApp, test_config};
+
+struct StaticCheck {
+    name: &'static str,
+    result: CheckResult,
+    delay: Duration,
+}
+
+#[async_trait]
+impl HealthCheck for StaticCheck {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn check(&self) -> CheckResult {
+        tokio::time::sleep(self.delay).await;
+        self.result.clone()
+    }
+}
+
+fn check(name: &'static str, result: CheckResult) -> Arc<dyn HealthCheck> {
+    Arc::new(StaticCheck {
+        name,
+        result,
+        delay: Duration::ZERO,
+    })
+}
+
+fn app(checks: Vec<Arc<dyn HealthCheck>>) -> TestApp {
+    TestApp::with_health_checks(test_config(), Vec::new(), checks)
+}
+
+#[tokio::test]
+async fn live_is_up_without_token() {
+    let app = app(vec![check("database", CheckResult::down("refused"))]);
+
+    let response = app.request(Method::GET, "/health/live", None, None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json(), json!({ "status": "alive" }));
+}
+
+#[tokio::test]
+async fn ready_reports_every_dependency() {
+    let app = app(vec![
+        check("database", CheckResult::up().with("latency_ms", 1)),
+        check("pool", CheckResult::up().with("idle", 2)),
+    ]);
+
+    let response = app.request(Method::GET, "/health/ready", None, None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!({
+            "status": "ready",
+            "checks": {
+                "server": { "status": "up" },
+                "database": { "status": "up", "latency_ms": 1 },
+                "pool": { "status": "up", "idle": 2 },
+            },
+        })
+    );
+}
+
+#[tokio::test]
+async fn ready_is_unavailable_when_a_dependency_is_down() {
+    let app = app(vec![
+        check("database", CheckResult::up()),
+        check(
+            "migrations",
+            CheckResult::down("pending").with("pending", vec![3]),
+        ),
+    ]);
+
+    let response = app.request(Method::GET, "/health/ready", None, None).await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    let json = response.json();
+    assert_eq!(json["status"], "not_ready");
+    assert_eq!(json["checks"]["database"]["status"], "up");
+    assert_eq!(
+        json["checks"]["migrations"],
+        json!({ "status": "down", "error": "pending", "pending": [3] })
+    );
+}
+
+#[tokio::test]
+async fn ready_times_out_slow_checks() {
+    let mut config = test_config();
+    config.health.check_timeout_ms = 50;
+    let slow = Arc::new(StaticCheck {
+        name: "database",
+        result: CheckResult::up(),
+        delay: Duration::from_secs(5),
+    });
+    let app = TestApp::with_health_checks(config, Vec::new(), vec![slow]);
+
+    let response = app.request(Method::GET, "/health/ready", None, None).await;
+
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.json()["checks"]["database"],
+        json!({ "status": "down", "error": "timed out after 50ms" })
+    );
+}
+
+#[tokio::test]
+async fn ready_turns_unavailable_on_shutdown() {
+    let app = app(vec![check("database", CheckResult::up())]);
+
+    app.lifecycle.mark_not_ready();
+    let response = app.request(Method::GET, "/health/ready", None, None).await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.json()["checks"]["server"],
+        json!({ "status": "down", "error": "not accepting traffic" })
+    );
+
+    app.lifecycle.shutdown();
+    let response = app.request(Method::GET, "/health/ready", None, None).await;
+    assert_eq!(
+        response.json()["checks"]["server"],
+        json!({ "status": "down", "error": "shutting down" })
+    );
+}
+
+#[tokio::test]
+async fn version_reports_build_info() {
+    let app = app(Vec::new());
+
+    let response = app
+        .request(Method::GET, "/health/version", None, None)
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["name"], env!("CARGO_PKG_NAME"));
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["git_commit"].as_str().is_some_and(|c| !c.is_empty()));
+    assert_eq!(json["build_profile"], "debug");
+}
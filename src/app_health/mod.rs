@@ -0,0 +1,8 @@
+pub mod check;
+pub mod check_postgres;
+#[cfg(test)]
+mod check_postgres_test;
+pub mod health_handler;
+#[cfg(test)]
+mod health_handler_test;
+pub mod routes;
@@ -0,0 +1,13 @@
+use axum::{Router, routing::get};
+
+use crate::app_health::check::HealthChecks;
+use crate::app_health::health_handler::{live_handler, ready_handler, version_handler};
+
+// unauthenticated, these are for the orchestrator and deploy tooling
+pub fn health_routes(checks: HealthChecks) -> Router {
+    Router::new()
+        .route("/live", get(live_handler))
+        .route("/ready", get(ready_handler))
+        .route("/version", get(version_handler))
+        .with_state(checks)
+}
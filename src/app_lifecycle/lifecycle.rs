@@ -0,0 +1,97 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{info, warn};
+
+// shared between the router, background tasks and the shutdown sequence.
+// ready is what the readiness probe reports, shutdown is cancelled once the
+// server stops accepting connections
+#[derive(Clone)]
+pub struct Lifecycle {
+    inner: Arc<LifecycleInner>,
+}
+
+struct LifecycleInner {
+    ready: AtomicBool,
+    shutdown: CancellationToken,
+    tasks: TaskTracker,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lifecycle {
+    // starts not ready, main marks it ready once the listener is bound
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(LifecycleInner {
+                ready: AtomicBool::new(false),
+                shutdown: CancellationToken::new(),
+                tasks: TaskTracker::new(),
+            }),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.inner.ready.load(Ordering::SeqCst) && !self.is_shutting_down()
+    }
+
+    pub fn mark_ready(&self) {
+        self.inner.ready.store(true, Ordering::SeqCst);
+    }
+
+    pub fn mark_not_ready(&self) {
+        self.inner.ready.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutdown.is_cancelled()
+    }
+
+    pub fn shutdown(&self) {
+        self.mark_not_ready();
+        self.inner.shutdown.cancel();
+    }
+
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.inner.shutdown.clone()
+    }
+
+    // the task gets the shutdown token and is expected to return soon after it
+    // is cancelled, wait_for_tasks gives it the rest of the drain timeout
+    pub fn spawn<F, Fut>(&self, name: &'static str, task: F)
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let future = task(self.shutdown_token());
+        self.inner.tasks.spawn(async move {
+            future.await;
+            info!("background task {name} stopped");
+        });
+    }
+
+    // true when every background task finished in time
+    pub async fn wait_for_tasks(&self, timeout: Duration) -> bool {
+        self.inner.tasks.close();
+        if tokio::time::timeout(timeout, self.inner.tasks.wait())
+            .await
+            .is_err()
+        {
+            warn!(
+                "{} background task(s) still running after {:?}",
+                self.inner.tasks.len(),
+                timeout
+            );
+            return false;
+        }
+        true
+    }
+}
@@ -0,0 +1,4 @@
+pub mod lifecycle;
+pub mod shutdown;
+#[cfg(test)]
+mod shutdown_test;
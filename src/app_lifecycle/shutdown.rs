@@ -0,0 +1,91 @@
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::time::{Instant, sleep, timeout};
+use tracing::{info, warn};
+
+use crate::app_config::config::ShutdownConfig;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+
+// serves until `signal` resolves, then:
+// 1. readiness turns unhealthy and stays so for readiness_delay_secs while
+//    requests are still served, so load balancers stop routing here
+// 2. the listener closes, background tasks are cancelled and in-flight
+//    requests get until drain_timeout_secs to finish
+// 3. whatever is left after the drain timeout is aborted
+pub async fn serve_with_shutdown(
+    listener: TcpListener,
+    app: Router,
+    lifecycle: Lifecycle,
+    config: &ShutdownConfig,
+    signal: impl Future<Output = ()>,
+) -> io::Result<()> {
+    let token = lifecycle.shutdown_token();
+    let mut server = tokio::spawn(
+        // ConnectInfo gives the rate limiter the client address
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+            .with_graceful_shutdown(token.cancelled_owned())
+            .into_future(),
+    );
+
+    lifecycle.mark_ready();
+
+    tokio::select! {
+        _ = signal => {}
+        result = &mut server => return result.map_err(io::Error::other)?,
+    }
+
+    info!("shutdown signal received, marking instance not ready");
+    lifecycle.mark_not_ready();
+    sleep(Duration::from_secs(config.readiness_delay_secs)).await;
+
+    let drain_timeout = Duration::from_secs(config.drain_timeout_secs);
+    let deadline = Instant::now() + drain_timeout;
+    info!("draining connections for up to {drain_timeout:?}");
+    lifecycle.shutdown();
+
+    let result = match timeout(drain_timeout, &mut server).await {
+        Ok(result) => result.map_err(io::Error::other)?,
+        Err(_) => {
+            warn!("drain timeout reached, aborting remaining connections");
+            server.abort();
+            Ok(())
+        }
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    lifecycle.wait_for_tasks(remaining).await;
+
+    result
+}
+
+// SIGINT (ctrl+c) everywhere, SIGTERM on unix
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for ctrl+c");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received SIGINT"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
@@ -0,0 +1,157 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use axum::Router;
+use axum::routing::get;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep};
+
+use crate::app_config::config::ShutdownConfig;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_lifecycle::shutdown::serve_with_shutdown;
+
+struct Server {
+    addr: SocketAddr,
+    lifecycle: Lifecycle,
+    signal: oneshot::Sender<()>,
+    handle: JoinHandle<std::io::Result<()>>,
+}
+
+async fn start(lifecycle: Lifecycle, drain_timeout_secs: u64, readiness_delay_secs: u64) -> Server {
+    let app = Router::new()
+        .route("/fast", get(|| async { "fast" }))
+        .route(
+            "/slow",
+            get(|| async {
+                sleep(Duration::from_millis(300)).await;
+                "slow"
+            }),
+        )
+        .route(
+            "/hang",
+            get(|| async {
+                sleep(Duration::from_secs(60)).await;
+                "hang"
+            }),
+        );
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = ShutdownConfig {
+        drain_timeout_secs,
+        readiness_delay_secs,
+    };
+    let (signal, received) = oneshot::channel::<()>();
+
+    let handle = tokio::spawn({
+        let lifecycle = lifecycle.clone();
+        async move {
+            let signal = async {
+                received.await.ok();
+            };
+            serve_with_shutdown(listener, app, lifecycle, &config, signal).await
+        }
+    });
+
+    // mark_ready happens right after the server task starts
+    while !lifecycle.is_ready() {
+        sleep(Duration::from_millis(5)).await;
+    }
+
+    Server {
+        addr,
+        lifecycle,
+        signal,
+        handle,
+    }
+}
+
+async fn http_get(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+#[tokio::test]
+async fn in_flight_requests_finish_before_shutdown_completes() {
+    let server = start(Lifecycle::new(), 5, 0).await;
+
+    let in_flight = tokio::spawn(http_get(server.addr, "/slow"));
+    sleep(Duration::from_millis(100)).await;
+    server.signal.send(()).unwrap();
+
+    let response = in_flight.await.unwrap().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    assert!(response.ends_with("slow"), "{response}");
+
+    server.handle.await.unwrap().unwrap();
+    assert!(server.lifecycle.is_shutting_down());
+    assert!(!server.lifecycle.is_ready());
+    assert!(TcpStream::connect(server.addr).await.is_err());
+}
+
+#[tokio::test]
+async fn readiness_turns_unhealthy_while_requests_are_still_served() {
+    let server = start(Lifecycle::new(), 5, 1).await;
+    assert!(server.lifecycle.is_ready());
+
+    server.signal.send(()).unwrap();
+    sleep(Duration::from_millis(200)).await;
+
+    assert!(!server.lifecycle.is_ready());
+    assert!(!server.lifecycle.is_shutting_down());
+    let response = http_get(server.addr, "/fast").await.unwrap();
+    assert!(response.ends_with("fast"), "{response}");
+
+    server.handle.await.unwrap().unwrap();
+    assert!(server.lifecycle.is_shutting_down());
+}
+
+#[tokio::test]
+async fn background_tasks_are_cancelled_on_shutdown() {
+    let lifecycle = Lifecycle::new();
+    let stopped = Arc::new(AtomicBool::new(false));
+    lifecycle.spawn("cooperative", {
+        let stopped = stopped.clone();
+        |token| async move {
+            token.cancelled().await;
+            stopped.store(true, Ordering::SeqCst);
+        }
+    });
+    let server = start(lifecycle, 5, 0).await;
+
+    server.signal.send(()).unwrap();
+    server.handle.await.unwrap().unwrap();
+
+    assert!(stopped.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn drain_timeout_bounds_shutdown() {
+    let lifecycle = Lifecycle::new();
+    // ignores the token, shutdown must not wait for it past the drain timeout
+    lifecycle.spawn("stubborn", |_token| async {
+        sleep(Duration::from_secs(60)).await;
+    });
+    let server = start(lifecycle, 1, 0).await;
+
+    let _hanging = tokio::spawn(http_get(server.addr, "/hang"));
+    sleep(Duration::from_millis(100)).await;
+
+    let started = Instant::now();
+    server.signal.send(()).unwrap();
+    server.handle.await.unwrap().unwrap();
+
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_secs(1), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(3), "{elapsed:?}");
+}
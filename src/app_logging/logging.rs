@@ -0,0 +1,21 @@
+use tracing_subscriber::EnvFilter;
+
+use crate::app_config::config::{LogFormat, LoggingConfig};
+
+// the level is validated with the rest of the config, so EnvFilter::new does
+// not fall back silently here
+pub fn init_logging(config: &LoggingConfig) {
+    let builder = tracing_subscriber::fmt().with_env_filter(EnvFilter::new(&config.level));
+
+    match config.format {
+        LogFormat::Text => builder.init(),
+        // span_list carries the request span (request_id, route, user_id) on
+        // every line logged below it, not only on the innermost span
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .init(),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod logging;
+
+#[cfg(test)]
+pub mod test_logs;
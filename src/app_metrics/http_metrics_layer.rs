@@ -0,0 +1,74 @@
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::response::Response;
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::app_metrics::metrics::AppMetrics;
+
+// added with Router::route_layer so MatchedPath is already known, the route
+// label is the template (/company/:id) and never the raw path
+#[derive(Clone)]
+pub struct HttpMetricsLayer {
+    metrics: AppMetrics,
+}
+
+impl HttpMetricsLayer {
+    pub fn new(metrics: AppMetrics) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for HttpMetricsLayer {
+    type Service = HttpMetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpMetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpMetricsService<S> {
+    inner: S,
+    metrics: AppMetrics,
+}
+
+impl<S> Service<Request<Body>> for HttpMetricsService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let method = req.method().clone();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| "unmatched".into());
+        let metrics = self.metrics.clone();
+        let started = Instant::now();
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let response = future.await?;
+            metrics.observe_http_request(&method, &route, response.status(), started.elapsed());
+            Ok(response)
+        })
+    }
+}
@@ -0,0 +1,379 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+// one registry per app instance instead of the prometheus default registry, so
+// every test app counts on its own
+#[derive(Clone)]
+pub struct AppMetrics {
+    inner: Arc<AppMetricsInner>,
+}
+
+struct AppMetricsInner {
+    registry: Registry,
+    http_requests_total: IntCounterVec,
+    http_request_duration_seconds: HistogramVec,
+    db_pool_connections: IntGaugeVec,
+    db_pool_max_connections: IntGauge,
+    db_pool_acquire_wait_seconds: Histogram,
+    db_pool_acquire_timeouts_total: IntCounter,
+    login_attempts_total: IntCounterVec,
+    company_operations_total: IntCounterVec,
+    product_operations_total: IntCounterVec,
+    warehouse_operations_total: IntCounterVec,
+    stock_movements_total: IntCounterVec,
+    stock_transfer_operations_total: IntCounterVec,
+    stock_lot_operations_total: IntCounterVec,
+    stock_count_operations_total: IntCounterVec,
+    purchasing_operations_total: IntCounterVec,
+    sales_operations_total: IntCounterVec,
+    rate_limited_requests_total: IntCounterVec,
+}
+
+impl Default for AppMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppMetrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests by matched route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by matched route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Open database connections by state"),
+            &["state"],
+        )
+        .unwrap();
+        let db_pool_max_connections = IntGauge::new(
+            "db_pool_max_connections",
+            "Configured maximum of database connections",
+        )
+        .unwrap();
+        let db_pool_acquire_wait_seconds = Histogram::with_opts(HistogramOpts::new(
+            "db_pool_acquire_wait_seconds",
+            "Time the pool sampler waited for a connection",
+        ))
+        .unwrap();
+        let db_pool_acquire_timeouts_total = IntCounter::new(
+            "db_pool_acquire_timeouts_total",
+            "Pool sampler acquires that failed or timed out",
+        )
+        .unwrap();
+        let login_attempts_total = IntCounterVec::new(
+            Opts::new("login_attempts_total", "Login attempts by result"),
+            &["result"],
+        )
+        .unwrap();
+        let company_operations_total = IntCounterVec::new(
+            Opts::new(
+                "company_operations_total",
+                "Committed company writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let product_operations_total = IntCounterVec::new(
+            Opts::new(
+                "product_operations_total",
+                "Committed product writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let warehouse_operations_total = IntCounterVec::new(
+            Opts::new(
+                "warehouse_operations_total",
+                "Committed warehouse and location writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let stock_movements_total = IntCounterVec::new(
+            Opts::new(
+                "stock_movements_total",
+                "Committed stock movements by movement type",
+            ),
+            &["movement_type"],
+        )
+        .unwrap();
+        let stock_transfer_operations_total = IntCounterVec::new(
+            Opts::new(
+                "stock_transfer_operations_total",
+                "Committed stock transfer writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let stock_lot_operations_total = IntCounterVec::new(
+            Opts::new(
+                "stock_lot_operations_total",
+                "Committed stock lot writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let stock_count_operations_total = IntCounterVec::new(
+            Opts::new(
+                "stock_count_operations_total",
+                "Committed stock count writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let purchasing_operations_total = IntCounterVec::new(
+            Opts::new(
+                "purchasing_operations_total",
+                "Committed supplier, purchase order and goods receipt writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let sales_operations_total = IntCounterVec::new(
+            Opts::new(
+                "sales_operations_total",
+                "Committed sales order and shipment writes by operation",
+            ),
+            &["operation"],
+        )
+        .unwrap();
+        let rate_limited_requests_total = IntCounterVec::new(
+            Opts::new(
+                "rate_limited_requests_total",
+                "Requests rejected with 429 by route group",
+            ),
+            &["group"],
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(http_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(http_request_duration_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_max_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_acquire_wait_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_acquire_timeouts_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(login_attempts_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(company_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(product_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(warehouse_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stock_movements_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stock_transfer_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stock_lot_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stock_count_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(purchasing_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(sales_operations_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(rate_limited_requests_total.clone()))
+            .unwrap();
+
+        Self {
+            inner: Arc::new(AppMetricsInner {
+                registry,
+                http_requests_total,
+                http_request_duration_seconds,
+                db_pool_connections,
+                db_pool_max_connections,
+                db_pool_acquire_wait_seconds,
+                db_pool_acquire_timeouts_total,
+                login_attempts_total,
+                company_operations_total,
+                product_operations_total,
+                warehouse_operations_total,
+                stock_movements_total,
+                stock_transfer_operations_total,
+                stock_lot_operations_total,
+                stock_count_operations_total,
+                purchasing_operations_total,
+                sales_operations_total,
+                rate_limited_requests_total,
+            }),
+        }
+    }
+
+    pub fn observe_http_request(
+        &self,
+        method: &Method,
+        route: &str,
+        status: StatusCode,
+        elapsed: Duration,
+    ) {
+        let labels = [method.as_str(), route, status.as_str()];
+        self.inner
+            .http_requests_total
+            .with_label_values(&labels)
+            .inc();
+        self.inner
+            .http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn set_pool_connections(&self, size: u32, idle: u32, max: u32) {
+        let connections = &self.inner.db_pool_connections;
+        connections.with_label_values(&["idle"]).set(idle as i64);
+        connections
+            .with_label_values(&["in_use"])
+            .set(size.saturating_sub(idle) as i64);
+        self.inner.db_pool_max_connections.set(max as i64);
+    }
+
+    pub fn observe_pool_acquire(&self, waited: Duration) {
+        self.inner
+            .db_pool_acquire_wait_seconds
+            .observe(waited.as_secs_f64());
+    }
+
+    pub fn pool_acquire_timed_out(&self) {
+        self.inner.db_pool_acquire_timeouts_total.inc();
+    }
+
+    pub fn login_attempt(&self, success: bool) {
+        let result = if success { "success" } else { "failure" };
+        self.inner
+            .login_attempts_total
+            .with_label_values(&[result])
+            .inc();
+    }
+
+    // operation is create, update or delete
+    pub fn company_operation(&self, operation: &str) {
+        self.inner
+            .company_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create, update or delete
+    pub fn product_operation(&self, operation: &str) {
+        self.inner
+            .product_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create, update or delete on a warehouse, or the same
+    // prefixed with location_
+    pub fn warehouse_operation(&self, operation: &str) {
+        self.inner
+            .warehouse_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    pub fn stock_movement_posted(&self, movement_type: &str) {
+        self.inner
+            .stock_movements_total
+            .with_label_values(&[movement_type])
+            .inc();
+    }
+
+    // operation is create, update, dispatch, receive or cancel
+    pub fn stock_transfer_operation(&self, operation: &str) {
+        self.inner
+            .stock_transfer_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create
+    pub fn stock_lot_operation(&self, operation: &str) {
+        self.inner
+            .stock_lot_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create, count, complete, approve or cancel
+    pub fn stock_count_operation(&self, operation: &str) {
+        self.inner
+            .stock_count_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create, update or delete prefixed with supplier_ or
+    // purchase_order_, or approve, close and receive on a purchase order
+    pub fn purchasing_operation(&self, operation: &str) {
+        self.inner
+            .purchasing_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    // operation is create, update or delete prefixed with sales_order_,
+    // confirm, reserve, cancel and expire on an order, or create, pack, ship
+    // and cancel prefixed with shipment_
+    pub fn sales_operation(&self, operation: &str) {
+        self.inner
+            .sales_operations_total
+            .with_label_values(&[operation])
+            .inc();
+    }
+
+    pub fn rate_limited(&self, group: &str) {
+        self.inner
+            .rate_limited_requests_total
+            .with_label_values(&[group])
+            .inc();
+    }
+
+    // prometheus text exposition format
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.inner.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
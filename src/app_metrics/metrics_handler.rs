@@ -0,0 +1,32 @@
+use axum::{
+    Extension,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "prometheus text exposition format", content_type = "text/plain", body = String),
+        (status = 500, description = "metrics could not be encoded", body = ResponseErrorBody),
+    )
+)]
+pub async fn metrics_handler(Extension(metrics): Extension<AppMetrics>) -> Response {
+    match metrics.render() {
+        Ok(body) => (
+            StatusCode::OK,
+            [(
+                header::CONTENT_TYPE,
+                "text/plain; version=0.0.4; charset=utf-8",
+            )],
+            body,
+        )
+            .into_response(),
+        Err(err) => ResponseError::internal(err).into_response(),
+    }
+}
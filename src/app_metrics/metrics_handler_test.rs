@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode, header};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_metrics::pool_metrics::spawn_pool_metrics;
+use crate::app_router::test_app::{TestApp, test_config};
+
+// value of the sample written exactly as `series`, e.g. login_attempts_total{result="success"}
+fn sample(metrics: &str, series: &str) -> Option<f64> {
+    metrics.lines().find_map(|line| {
+        let value = line.strip_prefix(series)?.strip_prefix(' ')?;
+        value.parse().ok()
+    })
+}
+
+async fn scrape(app: &TestApp) -> String {
+    let response = app.request(Method::GET, "/metrics", None, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(
+        response
+            .header(header::CONTENT_TYPE)
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4")
+    );
+    response.text()
+}
+
+fn company_payload(code: &str) -> serde_json::Value {
+    json!({
+        "name": format!("Company {code}"),
+        "email": format!("{}@mail.com", code.to_lowercase()),
+        "code": code,
+        "phone_number": "0811",
+        "address": "Jakarta",
+    })
+}
+
+#[tokio::test]
+async fn requests_are_counted_by_matched_route_and_status() {
+    let app = TestApp::new();
+    let token = app.login().await;
+
+    app.request(Method::GET, "/company", Some(&token), None)
+        .await;
+    app.request(Method::GET, "/company", None, None).await;
+    for _ in 0..2 {
+        let uri = format!("/company/{}", Uuid::new_v4());
+        app.request(Method::DELETE, &uri, Some(&token), None).await;
+    }
+    app.request(Method::GET, "/not-a-route", None, None).await;
+
+    let metrics = scrape(&app).await;
+    let count = |series: &str| sample(&metrics, series);
+    assert_eq!(
+        count(r#"http_requests_total{method="POST",route="/login",status="200"}"#),
+        Some(1.0)
+    );
+    assert_eq!(
+        count(r#"http_requests_total{method="GET",route="/company",status="200"}"#),
+        Some(1.0)
+    );
+    assert_eq!(
+        count(r#"http_requests_total{method="DELETE",route="/company/:id",status="404"}"#),
+        Some(2.0)
+    );
+    assert!(
+        metrics.contains(
+            r#"http_request_duration_seconds_count{method="DELETE",route="/company/:id",status="404"} 2"#
+        ),
+        "{metrics}"
+    );
+    // unauthenticated requests are still counted, unmatched paths are not
+    assert_eq!(
+        count(r#"http_requests_total{method="GET",route="/company",status="401"}"#),
+        Some(1.0)
+    );
+    assert!(!metrics.contains("not-a-route"), "{metrics}");
+}
+
+#[tokio::test]
+async fn login_attempts_are_counted_by_result() {
+    let app = TestApp::new();
+
+    app.login().await;
+    let body = json!({ "username": "admin", "password": "wrong" });
+    for _ in 0..3 {
+        app.request(Method::POST, "/login", None, Some(body.clone()))
+            .await;
+    }
+
+    let metrics = scrape(&app).await;
+    assert_eq!(
+        sample(&metrics, r#"login_attempts_total{result="success"}"#),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(&metrics, r#"login_attempts_total{result="failure"}"#),
+        Some(3.0)
+    );
+}
+
+#[tokio::test]
+async fn committed_company_writes_are_counted() {
+    let app = TestApp::new();
+    let token = app.login().await;
+
+    let created = app
+        .request(
+            Method::POST,
+            "/company",
+            Some(&token),
+            Some(company_payload("ACM")),
+        )
+        .await;
+    let id = created.json()["data"]["id"].as_str().unwrap().to_string();
+    // rejected, not counted
+    app.request(
+        Method::POST,
+        "/company",
+        Some(&token),
+        Some(company_payload("ACM")),
+    )
+    .await;
+    app.request(
+        Method::PUT,
+        &format!("/company/{id}"),
+        Some(&token),
+        Some(company_payload("ACM")),
+    )
+    .await;
+    // rolled back as a whole, not counted
+    let body = json!({
+        "atomic": true,
+        "operations": [
+            { "op": "create", "data": company_payload("GLB") },
+            { "op": "delete", "id": Uuid::new_v4() },
+        ],
+    });
+    app.request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+    let body = json!({
+        "operations": [
+            { "op": "create", "data": company_payload("INI") },
+            { "op": "delete", "id": id },
+        ],
+    });
+    app.request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+
+    let metrics = scrape(&app).await;
+    let count = |operation: &str| {
+        let series = format!(r#"company_operations_total{{operation="{operation}"}}"#);
+        sample(&metrics, &series)
+    };
+    assert_eq!(count("create"), Some(2.0));
+    assert_eq!(count("update"), Some(1.0));
+    assert_eq!(count("delete"), Some(1.0));
+}
+
+#[tokio::test]
+async fn metrics_endpoint_can_be_disabled() {
+    let mut config = test_config();
+    config.metrics.enabled = false;
+    let app = TestApp::with_config(config, Vec::new());
+
+    let response = app.request(Method::GET, "/metrics", None, None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn pool_gauges_split_idle_and_in_use() {
+    let metrics = AppMetrics::new();
+
+    metrics.set_pool_connections(4, 1, 10);
+
+    let text = metrics.render().unwrap();
+    assert_eq!(
+        sample(&text, r#"db_pool_connections{state="idle"}"#),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(&text, r#"db_pool_connections{state="in_use"}"#),
+        Some(3.0)
+    );
+    assert_eq!(sample(&text, "db_pool_max_connections"), Some(10.0));
+}
+
+// needs TEST_DATABASE_URL, skipped without it
+#[tokio::test]
+async fn pool_sampler_records_until_shutdown() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let lifecycle = Lifecycle::new();
+    let metrics = AppMetrics::new();
+
+    spawn_pool_metrics(
+        &lifecycle,
+        db.pool.clone(),
+        metrics.clone(),
+        Duration::from_millis(10),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    lifecycle.shutdown();
+    assert!(lifecycle.wait_for_tasks(Duration::from_secs(1)).await);
+
+    let text = metrics.render().unwrap();
+    let max = db.pool.options().get_max_connections() as f64;
+    assert_eq!(sample(&text, "db_pool_max_connections"), Some(max));
+    assert!(sample(&text, "db_pool_acquire_wait_seconds_count").unwrap() >= 1.0);
+    assert_eq!(sample(&text, "db_pool_acquire_timeouts_total"), Some(0.0));
+
+    db.drop_database().await;
+}
@@ -0,0 +1,6 @@
+pub mod http_metrics_layer;
+pub mod metrics;
+pub mod metrics_handler;
+#[cfg(test)]
+mod metrics_handler_test;
+pub mod pool_metrics;
@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use sqlx::{Pool, Postgres};
+use tokio::time::interval;
+
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_metrics::metrics::AppMetrics;
+
+// sqlx keeps no wait statistics, so every tick the sampler records the pool
+// size and times one acquire of its own, which is what a request arriving at
+// that moment would have waited
+pub fn spawn_pool_metrics(
+    lifecycle: &Lifecycle,
+    pool: Pool<Postgres>,
+    metrics: AppMetrics,
+    every: Duration,
+) {
+    lifecycle.spawn("pool_metrics", move |shutdown| async move {
+        let mut ticker = interval(every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => sample_pool(&pool, &metrics, &shutdown).await,
+            }
+        }
+    });
+}
+
+async fn sample_pool(
+    pool: &Pool<Postgres>,
+    metrics: &AppMetrics,
+    shutdown: &tokio_util::sync::CancellationToken,
+) {
+    metrics.set_pool_connections(
+        pool.size(),
+        pool.num_idle() as u32,
+        pool.options().get_max_connections(),
+    );
+
+    let started = Instant::now();
+    tokio::select! {
+        _ = shutdown.cancelled() => {}
+        connection = pool.acquire() => match connection {
+            Ok(_) => metrics.observe_pool_acquire(started.elapsed()),
+            Err(_) => metrics.pool_acquire_timed_out(),
+        },
+    }
+}
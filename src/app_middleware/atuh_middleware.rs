@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use axum::{
+    Extension,
     body::Body,
     http::{Request, header::AUTHORIZATION},
     middleware::Next,
@@ -6,13 +9,18 @@ use axum::{
 };
 
 use crate::{
+    app_config::config::AppConfig,
     app_middleware::jwt_token::{
         jwt::verify_token,
     },
     app_response::error::ResponseError,
 };
 
-pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Response {
+pub async fn auth_middleware(
+    Extension(config): Extension<Arc<AppConfig>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
     let auth = match req.headers()
         .get(AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
@@ -26,11 +34,13 @@ pub async fn auth_middleware(mut req: Request<Body>, next: Next) -> Response {
         None => return ResponseError::Unauthorized.into_response(),
     };
 
-    let claims = match verify_token(token) {
+    let claims = match verify_token(&config.jwt, token) {
         Ok(c) => c,
         Err(_) => return ResponseError::InvalidToken.into_response(),
     };
 
+    tracing::Span::current().record("user_id", claims.sub.as_str());
+
     // inject ke request
     req.extensions_mut().insert(claims);
 
@@ -0,0 +1,18 @@
+use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
+
+tokio::task_local! {
+    static ERROR_DEBUG: bool;
+}
+
+// development only, puts the cause of 500 responses in the detail field
+pub async fn error_debug_middleware(
+    State(enabled): State<bool>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    ERROR_DEBUG.scope(enabled, next.run(req)).await
+}
+
+pub fn error_debug_enabled() -> bool {
+    ERROR_DEBUG.try_with(|enabled| *enabled).unwrap_or(false)
+}
@@ -1,7 +1,10 @@
+use std::sync::Arc;
+
 use axum::async_trait;
 use axum::extract::FromRequestParts;
 use axum::http::{header, request::Parts};
 
+use crate::app_config::config::AppConfig;
 use crate::app_middleware::jwt_token::jwt::verify_token;
 use crate::app_response::error::ResponseError;
 
@@ -28,7 +31,14 @@ where
             .strip_prefix("Bearer ")
             .ok_or(ResponseError::Unauthorized)?;
 
-        let claims = verify_token(token).map_err(|_| ResponseError::InvalidToken)?;
+        let config = parts
+            .extensions
+            .get::<Arc<AppConfig>>()
+            .ok_or_else(|| ResponseError::internal("AppConfig extension is missing"))?;
+
+        let claims = verify_token(&config.jwt, token).map_err(|_| ResponseError::InvalidToken)?;
+
+        tracing::Span::current().record("user_id", claims.sub.as_str());
 
         Ok(AuthUser {
             user_id: claims.sub,
@@ -1,13 +1,15 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 
+use crate::app_config::config::JwtConfig;
 use crate::app_middleware::jwt_token::claims::Claims;
 
-const SECRET: &[u8] = b"my-secret-key";
-
-pub fn generate_token(user_id: &str) -> Result<String, jsonwebtoken::errors::Error> {
+pub fn generate_token(
+    config: &JwtConfig,
+    user_id: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
     let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(24))
+        .checked_add_signed(Duration::hours(config.expiration_hours))
         .unwrap()
         .timestamp() as usize;
 
@@ -19,16 +21,19 @@ pub fn generate_token(user_id: &str) -> Result<String, jsonwebtoken::errors::Err
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(SECRET),
+        &EncodingKey::from_secret(config.secret.as_bytes()),
     )
 }
 
-pub fn verify_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+pub fn verify_token(
+    config: &JwtConfig,
+    token: &str,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
     let data = decode::<Claims>(
         token, 
-        &DecodingKey::from_secret(SECRET), 
+        &DecodingKey::from_secret(config.secret.as_bytes()), 
         &Validation::default(),
     )?;
 
     Ok(data.claims)
-}
\ No newline at end of file
+}
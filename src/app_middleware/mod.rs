@@ -1,2 +1,13 @@
 pub mod atuh_middleware;
-pub mod jwt_token;
\ No newline at end of file
+pub mod cors;
+pub mod error_debug;
+pub mod jwt_token;
+pub mod request_id;
+pub mod request_limits;
+pub mod request_span;
+pub mod security_headers;
+
+#[cfg(test)]
+mod request_id_test;
+#[cfg(test)]
+mod request_limits_test;
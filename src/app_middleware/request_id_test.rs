@@ -0,0 +1,120 @@
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode, header};
+use uuid::Uuid;
+
+use crate::app_logging::test_logs::capture_logs;
+use crate::app_middleware::request_id::{REQUEST_ID_HEADER, current_request_id};
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, TestResponse, test_config};
+
+async fn request_with_id(app: &TestApp, uri: &str, id: &str) -> TestResponse {
+    let request = Request::builder()
+        .uri(uri)
+        .header(REQUEST_ID_HEADER, id)
+        .body(Body::empty())
+        .unwrap();
+    app.send(request).await
+}
+
+#[tokio::test]
+async fn request_id_is_generated_when_missing() {
+    let app = TestApp::new();
+
+    let first = app.request(Method::GET, "/company", None, None).await;
+    let second = app.request(Method::GET, "/company", None, None).await;
+
+    let first_id = first.header(REQUEST_ID_HEADER).unwrap();
+    assert!(Uuid::parse_str(first_id).is_ok(), "{first_id}");
+    assert_ne!(Some(first_id), second.header(REQUEST_ID_HEADER));
+    assert_eq!(first.json()["request_id"], first_id);
+}
+
+#[tokio::test]
+async fn incoming_request_id_is_echoed_and_used_in_errors() {
+    let app = TestApp::new();
+
+    let response = request_with_id(&app, "/company", "abc-123.retry:1").await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header(REQUEST_ID_HEADER), Some("abc-123.retry:1"));
+    assert_eq!(response.json()["request_id"], "abc-123.retry:1");
+}
+
+#[tokio::test]
+async fn unusable_request_id_is_replaced() {
+    let app = TestApp::new();
+    let too_long = "a".repeat(129);
+
+    for id in ["", "has space", "quote\"d", too_long.as_str()] {
+        let response = request_with_id(&app, "/health/live", id).await;
+
+        let echoed = response.header(REQUEST_ID_HEADER).unwrap();
+        assert!(Uuid::parse_str(echoed).is_ok(), "{id:?} -> {echoed}");
+    }
+}
+
+#[tokio::test]
+async fn every_response_carries_a_request_id() {
+    let mut config = test_config();
+    config.cors.allowed_origins = vec!["https://app.example.com".into()];
+    let app = TestApp::with_config(config, Vec::new());
+    let token = app.login().await;
+
+    let success = app
+        .request(Method::GET, "/company", Some(&token), None)
+        .await;
+    let unmatched = app.request(Method::GET, "/not-a-route", None, None).await;
+    let preflight = app.preflight("/company", "https://app.example.com").await;
+
+    for response in [success, unmatched, preflight] {
+        assert!(response.header(REQUEST_ID_HEADER).is_some());
+    }
+}
+
+#[tokio::test]
+async fn request_id_is_only_set_while_handling_a_request() {
+    assert_eq!(current_request_id(), None);
+}
+
+#[tokio::test]
+async fn request_span_records_route_status_and_user() {
+    let app = TestApp::new();
+    let token = app.login().await;
+    let (logs, _guard) = capture_logs();
+
+    let uri = format!("/company/{}", Uuid::new_v4());
+    let request = Request::builder()
+        .method(Method::DELETE)
+        .uri(uri)
+        .header(REQUEST_ID_HEADER, "trace-me")
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let lines = logs.lines();
+    let completed = lines
+        .iter()
+        .find(|line| line["message"] == "request completed")
+        .expect("no request completed event");
+    let span = &completed["spans"][0];
+    assert_eq!(span["name"], "request");
+    assert_eq!(span["request_id"], "trace-me");
+    assert_eq!(span["method"], "DELETE");
+    assert_eq!(span["route"], "/company/:id");
+    assert_eq!(span["status"], 404);
+    assert_eq!(span["user_id"], TEST_ADMIN_ID.to_string());
+    assert!(span["latency_ms"].is_number());
+
+    // the usecase span closes inside the request span, close events list the parents
+    let usecase_closed = lines
+        .iter()
+        .filter(|line| line["message"] == "close")
+        .find(|line| {
+            let target = line["target"].as_str().unwrap();
+            target.ends_with("company_usecase")
+        })
+        .expect("no usecase span");
+    assert_eq!(usecase_closed["spans"][0]["name"], "request");
+    assert_eq!(usecase_closed["spans"][0]["request_id"], "trace-me");
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{Request, header::CONTENT_LENGTH},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use tokio::time::timeout;
+use std::time::Duration;
+
+use crate::app_config::config::RequestsConfig;
+use crate::app_response::error::ResponseError;
+
+// added with Router::layer so MatchedPath is known for the per-route timeout.
+// the body is read here in full, so handlers get it at once and a client that
+// sends slowly only holds a connection for body_timeout_secs
+pub async fn request_limits_middleware(
+    State(config): State<Arc<RequestsConfig>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let max_bytes = config.max_body_bytes;
+    let declared_len = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if declared_len.is_some_and(|len| len > max_bytes) {
+        return ResponseError::PayloadTooLarge { max_bytes }.into_response();
+    }
+
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let (parts, body) = req.into_parts();
+
+    let body_timeout = Duration::from_secs(config.body_timeout_secs);
+    let body = match timeout(body_timeout, Limited::new(body, max_bytes).collect()).await {
+        Ok(Ok(collected)) => collected.to_bytes(),
+        Ok(Err(err)) if err.is::<LengthLimitError>() => {
+            return ResponseError::PayloadTooLarge { max_bytes }.into_response();
+        }
+        Ok(Err(_)) => {
+            return ResponseError::BadRequest("request body could not be read".into())
+                .into_response();
+        }
+        Err(_) => return ResponseError::RequestTimeout.into_response(),
+    };
+    let req = Request::from_parts(parts, Body::from(body));
+
+    // dropping the handler future rolls back its open unit of work
+    let handler_timeout = config.timeout(route.as_deref());
+    match timeout(handler_timeout, next.run(req)).await {
+        Ok(response) => response,
+        Err(_) => ResponseError::GatewayTimeout.into_response(),
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::{Body, Bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use axum::middleware::from_fn_with_state;
+use axum::routing::{get, post};
+use futures::stream;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::app_config::config::{RequestsConfig, RouteTimeout};
+use crate::app_middleware::request_limits::request_limits_middleware;
+use crate::app_router::test_app::{TestApp, test_config};
+
+fn requests_config() -> RequestsConfig {
+    RequestsConfig {
+        max_body_bytes: 64,
+        body_timeout_secs: 1,
+        timeout_secs: 30,
+        route_timeouts: vec![RouteTimeout {
+            route: "/slow/:id".into(),
+            secs: 1,
+        }],
+    }
+}
+
+fn app() -> Router {
+    Router::new()
+        .route(
+            "/slow/:id",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "done"
+            }),
+        )
+        .route("/echo", post(|body: Bytes| async move { body }))
+        .route_layer(from_fn_with_state(
+            Arc::new(requests_config()),
+            request_limits_middleware,
+        ))
+}
+
+async fn send(app: Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(json!(null)))
+}
+
+#[tokio::test]
+async fn slow_handlers_get_504_after_their_route_timeout() {
+    let request = Request::builder()
+        .uri("/slow/1")
+        .body(Body::empty())
+        .unwrap();
+
+    let (status, body) = send(app(), request).await;
+
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(
+        body,
+        json!({ "status": 504, "message": "request took too long", "detail": null })
+    );
+}
+
+#[tokio::test]
+async fn bodies_that_do_not_arrive_get_408() {
+    let body = Body::from_stream(stream::pending::<Result<Bytes, std::io::Error>>());
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/echo")
+        .body(body)
+        .unwrap();
+
+    let (status, body) = send(app(), request).await;
+
+    assert_eq!(status, StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(body["message"], "request timeout");
+}
+
+#[tokio::test]
+async fn streamed_bodies_over_the_limit_get_413() {
+    let chunks = (0..10).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; 10])));
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/echo")
+        .body(Body::from_stream(stream::iter(chunks)))
+        .unwrap();
+
+    let (status, body) = send(app(), request).await;
+
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["detail"], "at most 64 bytes");
+}
+
+#[tokio::test]
+async fn bodies_within_the_limit_reach_the_handler() {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/echo")
+        .body(Body::from("x".repeat(64)))
+        .unwrap();
+
+    let response = app().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn declared_length_over_the_limit_is_rejected_up_front() {
+    let mut config = test_config();
+    config.requests.max_body_bytes = 100;
+    let app = TestApp::with_config(config, Vec::new());
+    let body = json!({ "username": "admin", "password": "x".repeat(200) });
+
+    let response = app.request(Method::POST, "/login", None, Some(body)).await;
+
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        response.error_json(),
+        json!({ "status": 413, "message": "request body too large", "detail": "at most 100 bytes" })
+    );
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        Some("application/json")
+    );
+}
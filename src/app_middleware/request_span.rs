@@ -0,0 +1,99 @@
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::response::Response;
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+use crate::app_middleware::request_id::RequestId;
+
+// one span per request, everything logged while handling it (usecase,
+// repository, errors) is nested under it and carries the request id.
+// user_id is filled in by the auth middleware, status and latency at the end
+#[derive(Clone, Default)]
+pub struct RequestSpanLayer;
+
+impl<S> Layer<S> for RequestSpanLayer {
+    type Service = RequestSpanService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestSpanService { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestSpanService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for RequestSpanService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let span = request_span(&req);
+        let started = Instant::now();
+        let future = {
+            let _entered = span.enter();
+            self.inner.call(req)
+        };
+
+        Box::pin(
+            async move {
+                let response = future.await?;
+                let status = response.status();
+                let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+                let span = Span::current();
+                span.record("status", status.as_u16());
+                span.record("latency_ms", latency_ms);
+                if status.is_server_error() {
+                    tracing::error!("request failed");
+                } else {
+                    tracing::info!("request completed");
+                }
+
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+fn request_span(req: &Request<Body>) -> Span {
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(RequestId::as_str)
+        .unwrap_or_default();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or("unmatched");
+
+    tracing::info_span!(
+        "request",
+        request_id,
+        method = %req.method(),
+        route,
+        status = Empty,
+        latency_ms = Empty,
+        user_id = Empty,
+    )
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, HeaderName, HeaderValue, Request, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::app_config::config::{FrameOptions, SecurityHeadersConfig};
+
+// None when disabled, otherwise the headers added to every response
+pub fn security_headers(config: &SecurityHeadersConfig) -> Option<Arc<HeaderMap>> {
+    if !config.enabled {
+        return None;
+    }
+
+    let mut headers = HeaderMap::new();
+    if config.hsts_max_age_secs > 0 {
+        let mut hsts = format!("max-age={}", config.hsts_max_age_secs);
+        if config.hsts_include_subdomains {
+            hsts.push_str("; includeSubDomains");
+        }
+        headers.insert(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_str(&hsts).unwrap(),
+        );
+    }
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    let frame_options = match config.frame_options {
+        FrameOptions::Deny => "DENY",
+        FrameOptions::SameOrigin => "SAMEORIGIN",
+    };
+    headers.insert(
+        header::X_FRAME_OPTIONS,
+        HeaderValue::from_static(frame_options),
+    );
+    // the api links nowhere, nothing to leak
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("no-referrer"),
+    );
+
+    Some(Arc::new(headers))
+}
+
+// a handler that sets one of the headers itself keeps its value
+pub async fn security_headers_middleware(
+    State(headers): State<Arc<HeaderMap>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+
+    let response_headers = response.headers_mut();
+    for (name, value) in headers.iter() {
+        if !response_headers.contains_key(name) {
+            response_headers.insert(HeaderName::clone(name), value.clone());
+        }
+    }
+    response
+}
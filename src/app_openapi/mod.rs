@@ -0,0 +1,6 @@
+pub mod openapi;
+pub mod openapi_handler;
+pub mod routes;
+
+#[cfg(test)]
+mod openapi_test;
@@ -0,0 +1,148 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::app_health::health_handler as health;
+use crate::app_metrics::metrics_handler as metrics;
+use crate::company::handler::company_handler as company;
+use crate::login::handler::login_handler as login;
+use crate::product::handler::product_handler as product;
+use crate::purchasing::handler::purchase_order_handler as purchase_order;
+use crate::purchasing::handler::supplier_handler as supplier;
+use crate::sales::handler::sales_order_handler as sales_order;
+use crate::sales::handler::shipment_handler as shipment;
+use crate::stock::handler::count_handler as count;
+use crate::stock::handler::lot_handler as lot;
+use crate::stock::handler::serial_handler as serial;
+use crate::stock::handler::stock_handler as stock;
+use crate::stock::handler::transfer_handler as transfer;
+use crate::warehouse::handler::warehouse_handler as warehouse;
+
+// every handler mounted by build_app belongs in paths, openapi_test fails
+// when a route is missing here or the committed docs/openapi.json is stale
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "be-inventory-rust", description = "Inventory backend API"),
+    paths(
+        login::login,
+        company::get_companies_handler,
+        company::create_company_handler,
+        company::export_companies_handler,
+        company::bulk_company_handler,
+        company::update_company_handler,
+        company::delete_company_handler,
+        product::get_products_handler,
+        product::create_product_handler,
+        product::get_product_handler,
+        product::update_product_handler,
+        product::delete_product_handler,
+        product::get_product_units_handler,
+        product::create_product_unit_handler,
+        product::update_product_unit_handler,
+        product::delete_product_unit_handler,
+        warehouse::get_company_warehouses_handler,
+        warehouse::create_warehouse_handler,
+        warehouse::get_warehouse_handler,
+        warehouse::update_warehouse_handler,
+        warehouse::delete_warehouse_handler,
+        warehouse::get_locations_handler,
+        warehouse::create_location_handler,
+        warehouse::update_location_handler,
+        warehouse::delete_location_handler,
+        stock::post_movement_handler,
+        stock::get_movements_handler,
+        stock::get_movement_handler,
+        stock::reverse_movement_handler,
+        stock::get_balances_handler,
+        stock::get_reconciliation_handler,
+        stock::get_stock_settings_handler,
+        stock::update_stock_settings_handler,
+        transfer::post_transfer_handler,
+        transfer::get_transfers_handler,
+        transfer::get_transfer_handler,
+        transfer::update_transfer_handler,
+        transfer::dispatch_transfer_handler,
+        transfer::receive_transfer_handler,
+        transfer::cancel_transfer_handler,
+        transfer::get_in_transit_handler,
+        lot::post_lot_handler,
+        lot::get_lots_handler,
+        lot::get_lot_handler,
+        lot::get_expiring_lots_handler,
+        serial::get_serials_handler,
+        serial::get_serial_handler,
+        serial::get_serial_reconciliation_handler,
+        count::post_count_handler,
+        count::get_counts_handler,
+        count::get_count_handler,
+        count::record_count_handler,
+        count::complete_count_handler,
+        count::approve_count_handler,
+        count::cancel_count_handler,
+        supplier::create_supplier_handler,
+        supplier::get_company_suppliers_handler,
+        supplier::get_supplier_handler,
+        supplier::update_supplier_handler,
+        supplier::delete_supplier_handler,
+        purchase_order::post_purchase_order_handler,
+        purchase_order::get_purchase_orders_handler,
+        purchase_order::get_purchase_order_handler,
+        purchase_order::update_purchase_order_handler,
+        purchase_order::delete_purchase_order_handler,
+        purchase_order::approve_purchase_order_handler,
+        purchase_order::close_purchase_order_handler,
+        purchase_order::post_goods_receipt_handler,
+        purchase_order::get_goods_receipts_handler,
+        purchase_order::get_goods_receipt_handler,
+        sales_order::post_sales_order_handler,
+        sales_order::get_sales_orders_handler,
+        sales_order::get_sales_order_handler,
+        sales_order::update_sales_order_handler,
+        sales_order::delete_sales_order_handler,
+        sales_order::confirm_sales_order_handler,
+        sales_order::reserve_sales_order_handler,
+        sales_order::cancel_sales_order_handler,
+        sales_order::get_stock_availability_handler,
+        shipment::post_shipment_handler,
+        shipment::get_shipments_handler,
+        shipment::get_pick_suggestions_handler,
+        shipment::get_shipment_handler,
+        shipment::pack_shipment_handler,
+        shipment::ship_shipment_handler,
+        shipment::cancel_shipment_handler,
+        health::live_handler,
+        health::ready_handler,
+        health::version_handler,
+        metrics::metrics_handler,
+    ),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "get a bearer token"),
+        (name = "company", description = "companies, needs a bearer token"),
+        (name = "product", description = "product catalogue of the companies, needs a bearer token"),
+        (name = "warehouse", description = "warehouses of the companies and their bin locations, needs a bearer token"),
+        (name = "stock", description = "append-only stock ledger, on-hand balances, lots, serials, transfers between warehouses and stock counts, needs a bearer token"),
+        (name = "purchasing", description = "suppliers, purchase orders and goods receipts posting into the stock ledger, needs a bearer token"),
+        (name = "sales", description = "sales orders reserving stock, backorders and shipments issuing it from the stock ledger, needs a bearer token"),
+        (name = "health", description = "probes for the orchestrator"),
+        (name = "metrics", description = "prometheus scrape endpoint"),
+    )
+)]
+pub struct ApiDoc;
+
+// token from POST /login, sent as Authorization: Bearer <token>
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
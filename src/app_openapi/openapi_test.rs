@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::{Method, StatusCode};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::app_openapi::openapi::ApiDoc;
+use crate::app_router::test_app::TestApp;
+
+const SPEC_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/docs/openapi.json");
+
+// the documentation pages themselves are not part of the api
+const UNDOCUMENTED: [&str; 3] = ["/openapi.json", "/docs", "/redoc"];
+
+const METHODS: [Method; 5] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+
+type Operations = BTreeMap<String, BTreeSet<String>>;
+
+fn documented_operations() -> Operations {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .map(|(path, item)| {
+            let methods = item
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|method| method.to_uppercase())
+                .collect();
+            (path.replace('{', ":").replace('}', ""), methods)
+        })
+        .collect()
+}
+
+// axum has no api to list routes, its Debug output has every path next to its
+// route id as `): "/company/:id",`
+fn routed_paths(app: &TestApp) -> BTreeSet<String> {
+    format!("{:#?}", app.router())
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("): \""))
+        .filter_map(|rest| rest.strip_suffix("\","))
+        .filter(|path| !UNDOCUMENTED.contains(path))
+        .map(String::from)
+        .collect()
+}
+
+// a method the path does not serve answers 405, an unknown path an empty 404
+async fn routed_methods(app: &TestApp, token: &str, path: &str) -> BTreeSet<String> {
+    let uri = path
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with(':') {
+                Uuid::new_v4().to_string()
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut methods = BTreeSet::new();
+    for method in METHODS {
+        let response = app.request(method.clone(), &uri, Some(token), None).await;
+        let unrouted = response.status == StatusCode::METHOD_NOT_ALLOWED
+            || (response.status == StatusCode::NOT_FOUND && response.body.is_empty());
+        if !unrouted {
+            methods.insert(method.to_string());
+        }
+    }
+    methods
+}
+
+#[tokio::test]
+async fn spec_matches_the_router() {
+    let app = TestApp::new();
+    let token = app.login().await;
+
+    let paths = routed_paths(&app);
+    assert!(
+        paths.contains("/company/:id"),
+        "route listing broke: {paths:?}"
+    );
+
+    let mut routed = Operations::new();
+    for path in paths {
+        // fallback entries show up in the listing but serve nothing
+        let methods = routed_methods(&app, &token, &path).await;
+        if !methods.is_empty() {
+            routed.insert(path, methods);
+        }
+    }
+
+    assert_eq!(
+        routed,
+        documented_operations(),
+        "routes and ApiDoc disagree, add or remove #[utoipa::path] handlers in app_openapi::openapi"
+    );
+}
+
+#[tokio::test]
+async fn openapi_json_is_served_with_docs_pages() {
+    let app = TestApp::new();
+
+    let response = app.request(Method::GET, "/openapi.json", None, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let spec = response.json();
+    assert_eq!(spec["openapi"], "3.1.0");
+    assert_eq!(
+        spec["components"]["securitySchemes"]["bearer_auth"]["scheme"],
+        "bearer"
+    );
+
+    for page in ["/docs", "/redoc"] {
+        let response = app.request(Method::GET, page, None, None).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.text().contains("/openapi.json"));
+    }
+}
+
+#[tokio::test]
+async fn docs_can_be_disabled() {
+    let mut config = crate::app_router::test_app::test_config();
+    config.openapi.enabled = false;
+    let app = TestApp::with_config(config, Vec::new());
+
+    for page in UNDOCUMENTED {
+        let response = app.request(Method::GET, page, None, None).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+    }
+}
+
+// clients generate code from docs/openapi.json, a payload change has to show
+// up in the diff. UPDATE_OPENAPI=1 cargo test rewrites the file
+#[test]
+fn committed_spec_is_up_to_date() {
+    let spec = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(SPEC_FILE, &spec).unwrap();
+        return;
+    }
+
+    let committed = std::fs::read_to_string(SPEC_FILE).unwrap_or_default();
+    assert!(
+        committed == spec,
+        "docs/openapi.json is out of date, run UPDATE_OPENAPI=1 cargo test committed_spec"
+    );
+}
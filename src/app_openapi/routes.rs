@@ -0,0 +1,12 @@
+use axum::{Router, routing::get};
+
+use crate::app_openapi::openapi_handler::{
+    openapi_json_handler, redoc_handler, swagger_ui_handler,
+};
+
+pub fn openapi_routes() -> Router {
+    Router::new()
+        .route("/openapi.json", get(openapi_json_handler))
+        .route("/docs", get(swagger_ui_handler))
+        .route("/redoc", get(redoc_handler))
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use crate::app_config::config::RateLimitQuota;
+use crate::app_rate_limit::rate_limit::{RateLimitBackend, RateLimitDecision, take_token};
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// buckets of this instance only, behind several instances every one of them
+// allows the full quota
+#[derive(Default)]
+pub struct MemoryRateLimitBackend {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl MemoryRateLimitBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for MemoryRateLimitBackend {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &RateLimitQuota,
+    ) -> Result<RateLimitDecision, sqlx::Error> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: quota.burst as f64,
+            updated: now,
+        });
+        let (tokens, decision) = take_token(quota, bucket.tokens, now - bucket.updated);
+        bucket.tokens = tokens;
+        bucket.updated = now;
+
+        Ok(decision)
+    }
+
+    async fn prune(&self, idle: Duration) -> Result<u64, sqlx::Error> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let before = buckets.len();
+        buckets.retain(|_, bucket| now - bucket.updated < idle);
+        Ok((before - buckets.len()) as u64)
+    }
+}
@@ -0,0 +1,9 @@
+pub mod memory_backend;
+pub mod postgres_backend;
+pub mod rate_limit;
+pub mod rate_limit_layer;
+
+#[cfg(test)]
+mod postgres_backend_test;
+#[cfg(test)]
+mod rate_limit_test;
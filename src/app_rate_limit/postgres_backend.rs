@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::app_config::config::RateLimitQuota;
+use crate::app_rate_limit::rate_limit::{RateLimitBackend, RateLimitDecision, take_token};
+
+// buckets in the rate_limit_buckets table, shared by every instance on the
+// same database. the database clock is used so instances with skewed clocks
+// agree on how much a bucket refilled
+pub struct PgRateLimitBackend {
+    pool: PgPool,
+}
+
+impl PgRateLimitBackend {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for PgRateLimitBackend {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &RateLimitQuota,
+    ) -> Result<RateLimitDecision, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // inserts a full bucket or locks the existing one (the no-op update
+        // makes RETURNING see it), concurrent requests for the key queue here
+        let (tokens, elapsed_secs): (f64, f64) = sqlx::query_as(
+            r#"
+            INSERT INTO rate_limit_buckets (key, tokens, updated_at)
+            VALUES ($1, $2, now())
+            ON CONFLICT (key) DO UPDATE SET key = EXCLUDED.key
+            RETURNING tokens, EXTRACT(EPOCH FROM now() - updated_at)::float8
+            "#,
+        )
+        .bind(key)
+        .bind(quota.burst as f64)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let elapsed = Duration::from_secs_f64(elapsed_secs.max(0.0));
+        let (tokens, decision) = take_token(quota, tokens, elapsed);
+
+        sqlx::query("UPDATE rate_limit_buckets SET tokens = $2, updated_at = now() WHERE key = $1")
+            .bind(key)
+            .bind(tokens)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(decision)
+    }
+
+    async fn prune(&self, idle: Duration) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM rate_limit_buckets WHERE updated_at < now() - make_interval(secs => $1)",
+        )
+        .bind(idle.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
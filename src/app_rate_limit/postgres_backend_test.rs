@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use crate::app_config::config::RateLimitQuota;
+use crate::app_database::test_database::TestDatabase;
+use crate::app_rate_limit::postgres_backend::PgRateLimitBackend;
+use crate::app_rate_limit::rate_limit::RateLimitBackend;
+
+const QUOTA: RateLimitQuota = RateLimitQuota {
+    burst: 3,
+    per_minute: 1,
+};
+
+// needs TEST_DATABASE_URL, skipped without it
+#[tokio::test]
+async fn buckets_are_shared_between_instances() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let first = PgRateLimitBackend::new(db.pool.clone());
+    let second = PgRateLimitBackend::new(db.pool.clone());
+
+    let a = first.take("login:ip:10.0.0.1", &QUOTA).await.unwrap();
+    let b = second.take("login:ip:10.0.0.1", &QUOTA).await.unwrap();
+    let c = first.take("login:ip:10.0.0.1", &QUOTA).await.unwrap();
+    let d = second.take("login:ip:10.0.0.1", &QUOTA).await.unwrap();
+    let other = second.take("login:ip:10.0.0.2", &QUOTA).await.unwrap();
+
+    assert_eq!([a.remaining, b.remaining, c.remaining], [2, 1, 0]);
+    assert!(!d.allowed);
+    assert!(d.retry_after.unwrap() > Duration::from_secs(59));
+    assert!(other.allowed);
+
+    db.drop_database().await;
+}
+
+// needs TEST_DATABASE_URL, skipped without it
+#[tokio::test]
+async fn concurrent_requests_do_not_overspend() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let backend = std::sync::Arc::new(PgRateLimitBackend::new(db.pool.clone()));
+
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let backend = backend.clone();
+            tokio::spawn(async move { backend.take("company:user:1", &QUOTA).await.unwrap() })
+        })
+        .collect();
+    let mut allowed = 0;
+    for task in tasks {
+        if task.await.unwrap().allowed {
+            allowed += 1;
+        }
+    }
+    assert_eq!(allowed, 3);
+
+    assert_eq!(backend.prune(Duration::from_secs(60)).await.unwrap(), 0);
+    assert_eq!(backend.prune(Duration::ZERO).await.unwrap(), 1);
+
+    db.drop_database().await;
+}
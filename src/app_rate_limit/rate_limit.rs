@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tokio::time::interval;
+use tracing::warn;
+
+use crate::app_config::config::{RateLimitBackendKind, RateLimitConfig, RateLimitQuota};
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_rate_limit::memory_backend::MemoryRateLimitBackend;
+use crate::app_rate_limit::postgres_backend::PgRateLimitBackend;
+
+// names accepted under rate_limit.groups, one per router that is limited
+pub const RATE_LIMIT_GROUPS: &[&str] = &["login", "company", "product", "warehouse", "stock", "purchasing", "sales"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    // until the bucket is full again
+    pub reset_after: Duration,
+    // until the next request is allowed, only when this one was not
+    pub retry_after: Option<Duration>,
+}
+
+// where the buckets live, a missing bucket counts as a full one
+#[async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &RateLimitQuota,
+    ) -> Result<RateLimitDecision, sqlx::Error>;
+
+    // forgets buckets untouched for `idle`, returns how many
+    async fn prune(&self, idle: Duration) -> Result<u64, sqlx::Error>;
+}
+
+pub fn rate_limit_backend(config: &RateLimitConfig, pool: &PgPool) -> Arc<dyn RateLimitBackend> {
+    match config.backend {
+        RateLimitBackendKind::Memory => Arc::new(MemoryRateLimitBackend::new()),
+        RateLimitBackendKind::Postgres => Arc::new(PgRateLimitBackend::new(pool.clone())),
+    }
+}
+
+// refills a bucket holding `tokens` for `elapsed`, then takes one token out of
+// it when there is one. returns the tokens left and the decision
+pub fn take_token(
+    quota: &RateLimitQuota,
+    tokens: f64,
+    elapsed: Duration,
+) -> (f64, RateLimitDecision) {
+    let rate = quota.per_second();
+    let burst = quota.burst as f64;
+
+    let refilled = (tokens + elapsed.as_secs_f64() * rate).min(burst);
+    let allowed = refilled >= 1.0;
+    let left = if allowed { refilled - 1.0 } else { refilled };
+
+    let decision = RateLimitDecision {
+        allowed,
+        limit: quota.burst,
+        remaining: left.floor() as u32,
+        reset_after: Duration::from_secs_f64((burst - left) / rate),
+        retry_after: (!allowed).then(|| Duration::from_secs_f64((1.0 - left) / rate)),
+    };
+    (left, decision)
+}
+
+// buckets idle for longer than the slowest refill are full, dropping them
+// changes nothing for the client
+pub fn spawn_rate_limit_sweeper(
+    lifecycle: &Lifecycle,
+    config: &RateLimitConfig,
+    backend: Arc<dyn RateLimitBackend>,
+) {
+    let idle = config
+        .groups
+        .values()
+        .chain([&config.default])
+        .map(RateLimitQuota::refill_time)
+        .max()
+        .unwrap_or_default();
+    let every = Duration::from_secs(config.sweep_interval_secs);
+
+    lifecycle.spawn("rate_limit_sweeper", move |shutdown| async move {
+        let mut ticker = interval(every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(err) = backend.prune(idle).await {
+                        warn!(cause = %err, "pruning rate limit buckets failed");
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,210 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Request};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use tower::{Layer, Service};
+use tracing::warn;
+
+use crate::app_config::config::{JwtConfig, RateLimitConfig, RateLimitQuota};
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::jwt_token::jwt::verify_token;
+use crate::app_rate_limit::rate_limit::{RateLimitBackend, RateLimitDecision};
+use crate::app_response::error::ResponseError;
+
+// draft-ietf-httpapi-ratelimit-headers
+pub const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+// shared by the layers of every route group, see RateLimitConfig
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<RateLimiterInner>,
+}
+
+struct RateLimiterInner {
+    config: RateLimitConfig,
+    jwt: JwtConfig,
+    backend: Arc<dyn RateLimitBackend>,
+    metrics: AppMetrics,
+}
+
+impl RateLimiter {
+    pub fn new(
+        config: &RateLimitConfig,
+        jwt: &JwtConfig,
+        backend: Arc<dyn RateLimitBackend>,
+        metrics: AppMetrics,
+    ) -> Self {
+        Self {
+            inner: Arc::new(RateLimiterInner {
+                config: config.clone(),
+                jwt: jwt.clone(),
+                backend,
+                metrics,
+            }),
+        }
+    }
+
+    // for Router::layer on the router of one route group, lets everything
+    // through when rate limiting is disabled
+    pub fn layer(&self, group: &'static str) -> RateLimitLayer {
+        let quota = self
+            .inner
+            .config
+            .enabled
+            .then(|| self.inner.config.quota(group));
+        RateLimitLayer {
+            limiter: self.clone(),
+            group,
+            quota,
+        }
+    }
+
+    // the user of a valid bearer token, the client ip otherwise. tokens are
+    // checked here because the limit runs before the auth middleware, so
+    // floods with bad tokens are limited too
+    fn client_key(&self, req: &Request<Body>) -> String {
+        let user = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| verify_token(&self.inner.jwt, token).ok());
+        if let Some(claims) = user {
+            return format!("user:{}", claims.sub);
+        }
+
+        match self.client_ip(req) {
+            Some(ip) => format!("ip:{ip}"),
+            None => "ip:unknown".into(),
+        }
+    }
+
+    fn client_ip(&self, req: &Request<Body>) -> Option<IpAddr> {
+        if self.inner.config.trust_forwarded_for {
+            // the last entry is the one our proxy added, earlier ones come
+            // from the client and can be anything
+            let forwarded = req
+                .headers()
+                .get_all(X_FORWARDED_FOR)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .next_back()
+                .and_then(|ip| ip.trim().parse().ok());
+            if forwarded.is_some() {
+                return forwarded;
+            }
+        }
+
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+    group: &'static str,
+    quota: Option<RateLimitQuota>,
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let Some(quota) = self.layer.quota else {
+            return Box::pin(self.inner.call(req));
+        };
+
+        let limiter = self.layer.limiter.clone();
+        let group = self.layer.group;
+        let key = format!("{group}:{}", limiter.client_key(&req));
+        // the ready service is used after the await, a fresh clone stays behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            // fails open, an unavailable backend must not take the api down with it
+            let decision = match limiter.inner.backend.take(&key, &quota).await {
+                Ok(decision) => Some(decision),
+                Err(err) => {
+                    warn!(cause = %err, group, "rate limit backend failed, request let through");
+                    None
+                }
+            };
+
+            let mut response = match decision {
+                Some(decision) if !decision.allowed => {
+                    limiter.inner.metrics.rate_limited(group);
+                    let retry_after = decision.retry_after.unwrap_or_default();
+                    ResponseError::TooManyRequests {
+                        retry_after_secs: ceil_secs(retry_after),
+                    }
+                    .into_response()
+                }
+                _ => inner.call(req).await?,
+            };
+            if let Some(decision) = decision {
+                set_rate_limit_headers(response.headers_mut(), &decision);
+            }
+            Ok(response)
+        })
+    }
+}
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(
+        RATE_LIMIT_RESET,
+        HeaderValue::from(ceil_secs(decision.reset_after)),
+    );
+    if let Some(retry_after) = decision.retry_after {
+        headers.insert(RETRY_AFTER, HeaderValue::from(ceil_secs(retry_after)));
+    }
+}
+
+// headers carry whole seconds, rounding down would invite a retry that fails
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs_f64().ceil() as u64
+}
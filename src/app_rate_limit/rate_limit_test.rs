@@ -0,0 +1,235 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::{Method, Request, StatusCode};
+use serde_json::json;
+
+use crate::app_config::config::{AppConfig, RateLimitQuota};
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_rate_limit::memory_backend::MemoryRateLimitBackend;
+use crate::app_rate_limit::rate_limit::{RateLimitBackend, RateLimitDecision, take_token};
+use crate::app_rate_limit::rate_limit_layer::{
+    RATE_LIMIT_LIMIT, RATE_LIMIT_REMAINING, RATE_LIMIT_RESET,
+};
+use crate::app_router::router::{AppDeps, build_app};
+use crate::app_router::test_app::{TestApp, TestResponse, test_config};
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::user::repository::user_repository_memory::UserRepositoryMemory;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+
+const ONE_PER_SECOND: RateLimitQuota = RateLimitQuota {
+    burst: 2,
+    per_minute: 60,
+};
+
+fn config_with_company_quota(burst: u32) -> AppConfig {
+    let mut config = test_config();
+    config.rate_limit.groups.insert(
+        "company".into(),
+        RateLimitQuota {
+            burst,
+            per_minute: 1,
+        },
+    );
+    config
+}
+
+async fn get_companies(app: &TestApp, token: Option<&str>, from: Option<&str>) -> TestResponse {
+    let mut request = Request::builder().uri("/company");
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+    }
+    if let Some(from) = from {
+        request = request.header("x-forwarded-for", from);
+    }
+    let mut request = request.body(Body::empty()).unwrap();
+    let peer: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(peer));
+    app.send(request).await
+}
+
+#[test]
+fn bucket_allows_the_burst_then_refills_over_time() {
+    let (tokens, first) = take_token(&ONE_PER_SECOND, 2.0, Duration::ZERO);
+    let (tokens, second) = take_token(&ONE_PER_SECOND, tokens, Duration::ZERO);
+    let (tokens, third) = take_token(&ONE_PER_SECOND, tokens, Duration::ZERO);
+
+    assert!(first.allowed && second.allowed);
+    assert_eq!((first.remaining, second.remaining), (1, 0));
+    assert_eq!(
+        third,
+        RateLimitDecision {
+            allowed: false,
+            limit: 2,
+            remaining: 0,
+            reset_after: Duration::from_secs(2),
+            retry_after: Some(Duration::from_secs(1)),
+        }
+    );
+
+    let (tokens, half) = take_token(&ONE_PER_SECOND, tokens, Duration::from_millis(500));
+    assert!(!half.allowed);
+    assert_eq!(half.retry_after, Some(Duration::from_millis(500)));
+
+    let (_, refilled) = take_token(&ONE_PER_SECOND, tokens, Duration::from_millis(500));
+    assert!(refilled.allowed);
+
+    // a long pause never fills the bucket beyond the burst
+    let (tokens, _) = take_token(&ONE_PER_SECOND, 0.0, Duration::from_secs(3600));
+    assert_eq!(tokens, 1.0);
+}
+
+#[tokio::test]
+async fn memory_backend_prunes_idle_buckets() {
+    let backend = MemoryRateLimitBackend::new();
+    backend.take("a", &ONE_PER_SECOND).await.unwrap();
+    backend.take("b", &ONE_PER_SECOND).await.unwrap();
+
+    assert_eq!(backend.prune(Duration::from_secs(60)).await.unwrap(), 0);
+    assert_eq!(backend.prune(Duration::ZERO).await.unwrap(), 2);
+    assert!(backend.is_empty());
+}
+
+#[tokio::test]
+async fn login_is_limited_by_client_with_headers_and_429() {
+    let app = TestApp::new();
+    let body = json!({ "username": "admin", "password": "wrong" });
+
+    for remaining in (0..10).rev() {
+        let response = app
+            .request(Method::POST, "/login", None, Some(body.clone()))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.header(RATE_LIMIT_LIMIT), Some("10"));
+        assert_eq!(
+            response.header(RATE_LIMIT_REMAINING),
+            Some(remaining.to_string().as_str())
+        );
+    }
+
+    let limited = app.request(Method::POST, "/login", None, Some(body)).await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.header(RETRY_AFTER), Some("6"));
+    assert_eq!(limited.header(RATE_LIMIT_RESET), Some("60"));
+    assert_eq!(
+        limited.error_json(),
+        json!({ "status": 429, "message": "too many requests", "detail": "retry after 6 seconds" })
+    );
+
+    let metrics = app.request(Method::GET, "/metrics", None, None).await;
+    assert!(
+        metrics
+            .text()
+            .contains(r#"rate_limited_requests_total{group="login"} 1"#)
+    );
+}
+
+#[tokio::test]
+async fn users_and_addresses_have_their_own_buckets() {
+    let app = TestApp::with_config(config_with_company_quota(2), Vec::new());
+    let token = app.login().await;
+
+    for _ in 0..2 {
+        let response = get_companies(&app, Some(&token), None).await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
+    let response = get_companies(&app, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+
+    // same address without the token, a different client
+    let response = get_companies(&app, None, None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.header(RATE_LIMIT_REMAINING), Some("1"));
+}
+
+#[tokio::test]
+async fn forwarded_for_is_only_used_when_trusted() {
+    let mut config = config_with_company_quota(1);
+    let app = TestApp::with_config(config.clone(), Vec::new());
+
+    // untrusted, both requests come from the peer address
+    get_companies(&app, None, Some("1.1.1.1")).await;
+    let response = get_companies(&app, None, Some("2.2.2.2")).await;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+
+    config.rate_limit.trust_forwarded_for = true;
+    let app = TestApp::with_config(config, Vec::new());
+
+    let first = get_companies(&app, None, Some("9.9.9.9, 1.1.1.1")).await;
+    let other_client = get_companies(&app, None, Some("9.9.9.9, 2.2.2.2")).await;
+    let same_client = get_companies(&app, None, Some("8.8.8.8, 1.1.1.1")).await;
+    assert_eq!(first.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(other_client.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(same_client.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn disabled_limit_and_unlimited_routes_have_no_headers() {
+    let mut config = config_with_company_quota(1);
+    config.rate_limit.enabled = false;
+    let app = TestApp::with_config(config, Vec::new());
+
+    for _ in 0..3 {
+        let response = get_companies(&app, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(response.header(RATE_LIMIT_LIMIT), None);
+    }
+
+    let health = TestApp::new()
+        .request(Method::GET, "/health/live", None, None)
+        .await;
+    assert_eq!(health.header(RATE_LIMIT_LIMIT), None);
+}
+
+struct FailingBackend;
+
+#[async_trait]
+impl RateLimitBackend for FailingBackend {
+    async fn take(
+        &self,
+        _key: &str,
+        _quota: &RateLimitQuota,
+    ) -> Result<RateLimitDecision, sqlx::Error> {
+        Err(sqlx::Error::PoolTimedOut)
+    }
+
+    async fn prune(&self, _idle: Duration) -> Result<u64, sqlx::Error> {
+        Err(sqlx::Error::PoolTimedOut)
+    }
+}
+
+#[tokio::test]
+async fn failing_backend_lets_requests_through() {
+    let deps = AppDeps {
+        company_repository: CompanyRepositoryMemory::new(),
+        product_repository: ProductRepositoryMemory::default(),
+        warehouse_repository: WarehouseRepositoryMemory::default(),
+        stock_repository: StockRepositoryMemory::default(),
+        user_repository: UserRepositoryMemory::new(),
+        lifecycle: Lifecycle::new(),
+        health_checks: Vec::new(),
+        metrics: AppMetrics::new(),
+        rate_limit_backend: Arc::new(FailingBackend),
+    };
+    let router = build_app(&config_with_company_quota(1), deps);
+
+    for _ in 0..3 {
+        let request = Request::builder()
+            .uri("/company")
+            .body(Body::empty())
+            .unwrap();
+        let response = tower::ServiceExt::oneshot(router.clone(), request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(RATE_LIMIT_LIMIT).is_none());
+    }
+}
@@ -1,11 +1,20 @@
 use serde::Deserialize;
+use utoipa::IntoParams;
 
-#[derive(Deserialize, Debug)]
+use crate::app_response::error::ResponseError;
+
+#[derive(Deserialize, Debug, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationRequest {
+    /// starts at 1
     pub page: Option<u32>,
+    /// capped by pagination.max_per_page
     pub per_page: Option<u32>,
+    #[param(ignore)]
     pub offset: Option<u32>,
+    /// matches name or code
     pub search: Option<String>,
+    /// comma separated fields, prefix with - for descending
     pub sort: Option<String>,
 }
 
@@ -33,4 +42,19 @@ impl PaginationRequest {
         Some(receiver.join(","))
     }
 
+    // sort ends up in ORDER BY as written, only the given columns get through
+    pub fn validate_sort(&self, columns: &[&str]) -> Result<(), ResponseError> {
+        let Some(sort) = &self.sort else {
+            return Ok(());
+        };
+        for s in sort.split(",").map(str::trim).filter(|s| !s.is_empty()) {
+            let column = s.strip_prefix("-").unwrap_or(s);
+            if !columns.contains(&column) {
+                return Err(ResponseError::BadRequest(format!(
+                    "Sort by {column} is not supported"
+                )));
+            }
+        }
+        Ok(())
+    }
 }
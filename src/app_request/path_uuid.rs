@@ -28,3 +28,28 @@ where
         Ok(PathUuid(uuid))
     }
 }
+
+// the two ids of a nested route like /warehouse/:id/locations/:location_id, in
+// path order
+pub struct PathUuidPair(pub Uuid, pub Uuid);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for PathUuidPair
+where
+    S: Send + Sync,
+{
+    type Rejection = ResponseError;
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Path((first, second)) = Path::<(String, String)>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ResponseError::BadRequest("missing id".into()))?;
+
+        let parse =
+            |id: &str| Uuid::parse_str(id).map_err(|_| ResponseError::BadRequest("invalid uuid".into()));
+
+        Ok(PathUuidPair(parse(&first)?, parse(&second)?))
+    }
+}
@@ -1,91 +1,168 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header::RETRY_AFTER},
     response::{IntoResponse, Response},
 };
 use core::fmt;
 use serde::Serialize;
+use std::error::Error;
+use tracing::error;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Debug)]
+use crate::app_middleware::error_debug::error_debug_enabled;
+use crate::app_middleware::request_id::current_request_id;
+
+// what went wrong below the handler, never shown to clients unless error debug
+// is turned on in the config
+pub type ErrorSource = Box<dyn Error + Send + Sync + 'static>;
+
+#[derive(Serialize, Debug, ToSchema)]
 pub struct ResponseErrorBody {
     status: u16,
     message: String,
     detail: Option<String>,
+    // set when the error is turned into a response, not on bodies nested in
+    // other responses like bulk results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
 #[derive(Debug)]
 pub enum ResponseError {
     BadRequest(String),
     NotFound(String),
-    DatabaseError,
+    DatabaseError(ErrorSource),
     Unauthorized,
     InvalidToken,
-    InternalServerError,
+    PayloadTooLarge { max_bytes: usize },
+    // the request body did not arrive in time
+    RequestTimeout,
+    TooManyRequests { retry_after_secs: u64 },
+    // the handler did not finish in time
+    GatewayTimeout,
+    InternalServerError(ErrorSource),
 }
+
 impl fmt::Display for ResponseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ResponseError::BadRequest(msg) => write!(f, "warning_bad_request: {}", msg),
             ResponseError::NotFound(msg) => write!(f, "warning_not_found: {}", msg),
-            ResponseError::DatabaseError => write!(f, "error_storage"),
+            ResponseError::DatabaseError(_) => write!(f, "error_storage"),
             ResponseError::Unauthorized => write!(f, "unauthorized_user"),
             ResponseError::InvalidToken => write!(f, "invalid_token"),
-            ResponseError::InternalServerError => write!(f, "error_server"),
+            ResponseError::PayloadTooLarge { .. } => write!(f, "warning_payload_too_large"),
+            ResponseError::RequestTimeout => write!(f, "warning_request_timeout"),
+            ResponseError::TooManyRequests { .. } => write!(f, "warning_too_many_requests"),
+            ResponseError::GatewayTimeout => write!(f, "error_timeout"),
+            ResponseError::InternalServerError(_) => write!(f, "error_server"),
         }
     }
 }
 
-impl IntoResponse for ResponseError {
-    fn into_response(self) -> Response {
+impl Error for ResponseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            ResponseError::BadRequest(msg) => (StatusCode::BAD_REQUEST, {
-                let body = ResponseErrorBody {
-                    status: StatusCode::BAD_REQUEST.as_u16(),
-                    message: msg,
-                    detail: None,
-                };
-                Json(body)
-            })
-                .into_response(),
-            ResponseError::NotFound(msg) => (StatusCode::NOT_FOUND, {
-                let body = ResponseErrorBody {
-                    status: StatusCode::NOT_FOUND.as_u16(),
-                    message: msg,
-                    detail: None,
-                };
-                Json(body)
-            })
-                .into_response(),
-            ResponseError::DatabaseError => (StatusCode::INTERNAL_SERVER_ERROR, {
-                let body = ResponseErrorBody {
-                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
-                    message: "internal server error".into(),
-                    detail: Some("critical storage error".into()),
-                };
-                Json(body)
-            })
-                .into_response(),
-            ResponseError::Unauthorized => (StatusCode::UNAUTHORIZED, {
-                let body = ResponseErrorBody {
-                    status: StatusCode::UNAUTHORIZED.as_u16(),
-                    message: "unauthorized".into(),
-                    detail: None,
-                };
-                Json(body)
-            })
-                .into_response(),
-            ResponseError::InvalidToken => (StatusCode::UNAUTHORIZED, {
-                let body = ResponseErrorBody {
-                    status: StatusCode::UNAUTHORIZED.as_u16(),
-                    message: "invalid token".into(),
-                    detail: None,
-                };
-                Json(body)
-            })
-                .into_response(),
-            ResponseError::InternalServerError => {
-                (StatusCode::INTERNAL_SERVER_ERROR, "internal server error").into_response()
+            ResponseError::DatabaseError(err) | ResponseError::InternalServerError(err) => {
+                Some(err.as_ref())
             }
+            _ => None,
+        }
+    }
+}
+
+impl ResponseError {
+    pub fn database(err: impl Into<ErrorSource>) -> Self {
+        ResponseError::DatabaseError(err.into())
+    }
+
+    pub fn internal(err: impl Into<ErrorSource>) -> Self {
+        ResponseError::InternalServerError(err.into())
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ResponseError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ResponseError::NotFound(_) => StatusCode::NOT_FOUND,
+            ResponseError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ResponseError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ResponseError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ResponseError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ResponseError::RequestTimeout => StatusCode::REQUEST_TIMEOUT,
+            ResponseError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ResponseError::GatewayTimeout => StatusCode::GATEWAY_TIMEOUT,
+            ResponseError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    // runs inside the request span, so the log line carries the request id the
+    // client got in the response
+    pub fn log_source(&self) {
+        if let Some(source) = self.source() {
+            error!(cause = %error_chain(source), "{}", self);
+        }
+    }
+
+    pub fn body(&self) -> ResponseErrorBody {
+        let (message, detail) = match self {
+            ResponseError::BadRequest(msg) => (msg.clone(), None),
+            ResponseError::NotFound(msg) => (msg.clone(), None),
+            ResponseError::DatabaseError(_) => (
+                "internal server error".into(),
+                Some("critical storage error".into()),
+            ),
+            ResponseError::Unauthorized => ("unauthorized".into(), None),
+            ResponseError::InvalidToken => ("invalid token".into(), None),
+            ResponseError::PayloadTooLarge { max_bytes } => (
+                "request body too large".into(),
+                Some(format!("at most {max_bytes} bytes")),
+            ),
+            ResponseError::RequestTimeout => ("request timeout".into(), None),
+            ResponseError::GatewayTimeout => ("request took too long".into(), None),
+            ResponseError::TooManyRequests { retry_after_secs } => (
+                "too many requests".into(),
+                Some(format!("retry after {retry_after_secs} seconds")),
+            ),
+            ResponseError::InternalServerError(_) => ("internal server error".into(), None),
+        };
+        let detail = match self.source() {
+            Some(source) if error_debug_enabled() => Some(error_chain(source)),
+            _ => detail,
+        };
+
+        ResponseErrorBody {
+            status: self.status_code().as_u16(),
+            message,
+            detail,
+            request_id: None,
+        }
+    }
+}
+
+impl IntoResponse for ResponseError {
+    fn into_response(self) -> Response {
+        self.log_source();
+
+        let mut body = self.body();
+        body.request_id = current_request_id();
+        let mut response = (self.status_code(), Json(body)).into_response();
+        if let ResponseError::TooManyRequests { retry_after_secs } = self {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after_secs.into());
+        }
+        response
+    }
+}
+
+// "outer: inner: innermost", the way anyhow prints {:#}
+pub fn error_chain(err: &(dyn Error + 'static)) -> String {
+    let mut chain = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        chain.push_str(": ");
+        chain.push_str(&err.to_string());
+        source = err.source();
+    }
+    chain
 }
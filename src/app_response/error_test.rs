@@ -0,0 +1,131 @@
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode};
+use axum::middleware::from_fn_with_state;
+use axum::routing::get;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use crate::app_logging::test_logs::capture_logs;
+use crate::app_middleware::error_debug::error_debug_middleware;
+use crate::app_middleware::request_id::{REQUEST_ID_HEADER, RequestIdLayer};
+use crate::app_middleware::request_span::RequestSpanLayer;
+use crate::app_response::error::{ResponseError, error_chain};
+use crate::company::usecase::company_usecase::CompanyUsecaseError;
+
+// same layer order as build_app
+fn app(debug: bool) -> Router {
+    Router::new()
+        .route(
+            "/storage",
+            get(|| async {
+                let err = CompanyUsecaseError::DatabaseError(sqlx::Error::PoolTimedOut);
+                Err::<(), _>(ResponseError::database(err))
+            }),
+        )
+        .route(
+            "/internal",
+            get(|| async { Err::<(), _>(ResponseError::internal("signing key missing")) }),
+        )
+        .route(
+            "/missing",
+            get(|| async { Err::<(), _>(ResponseError::NotFound("data not found".into())) }),
+        )
+        .layer(from_fn_with_state(debug, error_debug_middleware))
+        .layer(RequestSpanLayer)
+        .layer(RequestIdLayer)
+}
+
+async fn get_json(app: Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(uri)
+        .header(REQUEST_ID_HEADER, "req-1")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn clients_get_a_sanitised_message() {
+    let (status, body) = get_json(app(false), "/storage").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        body,
+        json!({
+            "status": 500,
+            "message": "internal server error",
+            "detail": "critical storage error",
+            "request_id": "req-1",
+        })
+    );
+
+    let (status, body) = get_json(app(false), "/internal").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body["message"], "internal server error");
+    assert_eq!(body["detail"], Value::Null);
+}
+
+#[tokio::test]
+async fn debug_mode_puts_the_cause_in_detail() {
+    let (_, body) = get_json(app(true), "/storage").await;
+    assert_eq!(
+        body["detail"],
+        "company storage failed: pool timed out while waiting for an open connection"
+    );
+
+    let (_, body) = get_json(app(true), "/internal").await;
+    assert_eq!(body["detail"], "signing key missing");
+
+    // client errors have no cause to show
+    let (_, body) = get_json(app(true), "/missing").await;
+    assert_eq!(body["detail"], Value::Null);
+}
+
+#[tokio::test]
+async fn cause_is_logged_with_the_request_id() {
+    let (logs, _guard) = capture_logs();
+
+    get_json(app(false), "/storage").await;
+    get_json(app(false), "/missing").await;
+
+    let errors: Vec<Value> = logs
+        .lines()
+        .into_iter()
+        .filter(|line| line["level"] == "ERROR")
+        .collect();
+    let logged = errors
+        .iter()
+        .find(|line| line["message"] == "error_storage")
+        .expect("cause not logged");
+    assert_eq!(
+        logged["cause"],
+        "company storage failed: pool timed out while waiting for an open connection"
+    );
+    assert_eq!(logged["spans"][0]["request_id"], "req-1");
+    assert_eq!(logged["spans"][0]["route"], "/storage");
+    // a 404 is not worth an error line
+    assert!(
+        errors
+            .iter()
+            .all(|line| line["spans"][0]["route"] != "/missing")
+    );
+}
+
+#[test]
+fn error_chain_follows_every_source() {
+    let err = CompanyUsecaseError::DatabaseError(sqlx::Error::PoolClosed);
+    assert_eq!(
+        error_chain(&err),
+        "company storage failed: attempted to acquire a connection on a closed pool"
+    );
+
+    let err = ResponseError::database(err);
+    assert_eq!(
+        error_chain(&err),
+        "error_storage: company storage failed: \
+         attempted to acquire a connection on a closed pool"
+    );
+}
@@ -1,2 +1,5 @@
 pub mod error;
-pub mod success;
\ No newline at end of file
+pub mod success;
+
+#[cfg(test)]
+mod error_test;
@@ -4,9 +4,10 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Debug)]
-struct ResponseSuccessBody<T> {
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ResponseSuccessBody<T> {
     message: String,
     http_code: u16,
     data: Option<T>,
@@ -14,8 +15,8 @@ struct ResponseSuccessBody<T> {
     meta: Option<PaginationMeta>,
 }
 
-#[derive(Serialize, Debug)]
-struct PaginationMeta {
+#[derive(Serialize, Debug, ToSchema)]
+pub struct PaginationMeta {
     page: u32,
     per_page: u32,
     total_data: u64,
@@ -49,7 +50,7 @@ where
                 let body: ResponseSuccessBody<T> = ResponseSuccessBody {
                     message: "success".into(),
                     http_code: status.as_u16(),
-                    data: data,
+                    data,
                     meta: None,
                 };
                 Json(body)
@@ -67,7 +68,7 @@ where
                 let body: ResponseSuccessBody<T> = ResponseSuccessBody {
                     message: "success".into(),
                     http_code: StatusCode::OK.as_u16(),
-                    data: data,
+                    data,
                     meta: Some(meta),
                 };
                 Json(body)
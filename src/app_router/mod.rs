@@ -0,0 +1,5 @@
+pub mod router;
+#[cfg(test)]
+mod router_test;
+#[cfg(test)]
+pub mod test_app;
@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{Extension, Router, extract::DefaultBodyLimit, middleware, routing::get};
+use tower_http::compression::CompressionLayer;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
+use sqlx::{Pool, Postgres};
+
+use crate::app_config::config::AppConfig;
+use crate::app_health::check::{HealthCheck, HealthChecks};
+use crate::app_health::check_postgres::postgres_checks;
+use crate::app_health::routes::health_routes;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_metrics::http_metrics_layer::HttpMetricsLayer;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_metrics::metrics_handler::metrics_handler;
+use crate::app_middleware::cors::cors_layer;
+use crate::app_middleware::error_debug::error_debug_middleware;
+use crate::app_middleware::request_id::RequestIdLayer;
+use crate::app_middleware::request_limits::request_limits_middleware;
+use crate::app_middleware::request_span::RequestSpanLayer;
+use crate::app_middleware::security_headers::{security_headers, security_headers_middleware};
+use crate::app_openapi::routes::openapi_routes;
+use crate::app_rate_limit::rate_limit::RateLimitBackend;
+use crate::app_rate_limit::rate_limit_layer::RateLimiter;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::company::routes::company_routes;
+use crate::login::routes::login_routes;
+use crate::product::repository::product_repository::ProductRepository;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::product::routes::product_routes;
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::routes::purchasing_routes;
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::routes::sales_routes;
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::stock::repository::transfer_repository::TransferRepository;
+use crate::stock::routes::stock_routes;
+use crate::user::repository::user_repository::UserRepository;
+use crate::user::repository::user_repository_sqlx::UserRepositorySqlx;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+use crate::warehouse::routes::warehouse_routes;
+
+// everything the router needs from the outside, tests swap in the in-memory repositories
+pub struct AppDeps<
+    C: CompanyRepository,
+    P: ProductRepository,
+    W: WarehouseRepository,
+    S: TransferRepository + CountRepository + PurchasingRepository + SalesRepository,
+    U: UserRepository,
+> {
+    pub company_repository: C,
+    pub product_repository: P,
+    pub warehouse_repository: W,
+    pub stock_repository: S,
+    pub user_repository: U,
+    pub lifecycle: Lifecycle,
+    pub health_checks: Vec<Arc<dyn HealthCheck>>,
+    pub metrics: AppMetrics,
+    pub rate_limit_backend: Arc<dyn RateLimitBackend>,
+}
+
+impl
+    AppDeps<
+        CompanyRepositorySqlx,
+        ProductRepositorySqlx,
+        WarehouseRepositorySqlx,
+        StockRepositorySqlx,
+        UserRepositorySqlx,
+    >
+{
+    pub fn postgres(
+        pool: Pool<Postgres>,
+        lifecycle: Lifecycle,
+        metrics: AppMetrics,
+        rate_limit_backend: Arc<dyn RateLimitBackend>,
+    ) -> Self {
+        Self {
+            health_checks: postgres_checks(&pool),
+            company_repository: CompanyRepositorySqlx::new(pool.clone()),
+            product_repository: ProductRepositorySqlx::new(pool.clone()),
+            warehouse_repository: WarehouseRepositorySqlx::new(pool.clone()),
+            stock_repository: StockRepositorySqlx::new(pool.clone()),
+            user_repository: UserRepositorySqlx::new(pool),
+            lifecycle,
+            metrics,
+            rate_limit_backend,
+        }
+    }
+}
+
+pub fn build_app<C, P, W, S, U>(config: &AppConfig, deps: AppDeps<C, P, W, S, U>) -> Router
+where
+    C: CompanyRepository + 'static,
+    P: ProductRepository + 'static,
+    W: WarehouseRepository + 'static,
+    S: TransferRepository
+        + CountRepository
+        + PurchasingRepository
+        + SalesRepository
+        + Clone
+        + 'static,
+    U: UserRepository + 'static,
+{
+    let health_checks = HealthChecks::new(
+        deps.health_checks,
+        Duration::from_millis(config.health.check_timeout_ms),
+    );
+
+    let rate_limiter = RateLimiter::new(
+        &config.rate_limit,
+        &config.jwt,
+        deps.rate_limit_backend,
+        deps.metrics.clone(),
+    );
+
+    // probes, metrics and docs are not rate limited
+    let mut router = Router::new()
+        .nest("/health", health_routes(health_checks))
+        .merge(login_routes(deps.user_repository).layer(rate_limiter.layer("login")))
+        .nest(
+            "/company",
+            company_routes(deps.company_repository, deps.metrics.clone())
+                .layer(rate_limiter.layer("company")),
+        )
+        .nest(
+            "/product",
+            product_routes(deps.product_repository, deps.metrics.clone())
+                .layer(rate_limiter.layer("product")),
+        )
+        .merge(
+            warehouse_routes(deps.warehouse_repository, deps.metrics.clone())
+                .route_layer(rate_limiter.layer("warehouse")),
+        )
+        .merge(
+            purchasing_routes(deps.stock_repository.clone(), deps.metrics.clone())
+                .route_layer(rate_limiter.layer("purchasing")),
+        )
+        .merge(
+            sales_routes(deps.stock_repository.clone(), deps.metrics.clone())
+                .route_layer(rate_limiter.layer("sales")),
+        )
+        .merge(
+            stock_routes(deps.stock_repository, deps.metrics.clone())
+                .route_layer(rate_limiter.layer("stock")),
+        );
+
+    if config.openapi.enabled {
+        router = router.merge(openapi_routes());
+    }
+
+    // inside the metrics layer so 408, 413 and 504 responses are counted, /metrics
+    // itself takes no body and is left out
+    router = router.route_layer(middleware::from_fn_with_state(
+        Arc::new(config.requests.clone()),
+        request_limits_middleware,
+    ));
+
+    // route_layer only wraps routes added before it, keep it after the last route
+    if config.metrics.enabled {
+        router = router
+            .route("/metrics", get(metrics_handler))
+            .route_layer(HttpMetricsLayer::new(deps.metrics.clone()));
+    }
+
+    let mut router = router
+        // request_limits_middleware enforces requests.max_body_bytes instead
+        .layer(DefaultBodyLimit::disable())
+        .layer(Extension(Arc::new(config.clone())))
+        .layer(Extension(deps.lifecycle))
+        .layer(Extension(deps.metrics))
+        .layer(middleware::from_fn_with_state(
+            config.errors.debug,
+            error_debug_middleware,
+        ))
+        .layer(RequestSpanLayer);
+
+    if config.compression.enabled {
+        let predicate =
+            DefaultPredicate::new().and(SizeAbove::new(config.compression.min_size_bytes));
+        router = router.layer(CompressionLayer::new().compress_when(predicate));
+    }
+    if let Some(cors) = cors_layer(&config.cors) {
+        router = router.layer(cors);
+    }
+    // outside cors so preflight responses get them as well
+    if let Some(headers) = security_headers(&config.security_headers) {
+        router = router.layer(middleware::from_fn_with_state(
+            headers,
+            security_headers_middleware,
+        ));
+    }
+    // the request id goes outermost so every response carries it
+    router.layer(RequestIdLayer)
+}
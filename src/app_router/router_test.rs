@@ -0,0 +1,127 @@
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode, header};
+
+use crate::app_config::config::FrameOptions;
+use crate::app_router::test_app::{TestApp, test_config};
+
+const SECURITY_HEADERS: [header::HeaderName; 4] = [
+    header::STRICT_TRANSPORT_SECURITY,
+    header::X_CONTENT_TYPE_OPTIONS,
+    header::X_FRAME_OPTIONS,
+    header::REFERRER_POLICY,
+];
+
+#[tokio::test]
+async fn every_response_gets_security_headers() {
+    let mut config = test_config();
+    config.cors.allowed_origins = vec!["https://app.example.com".into()];
+    let app = TestApp::with_config(config, Vec::new());
+
+    let ok = app.request(Method::GET, "/health/live", None, None).await;
+    let unmatched = app.request(Method::GET, "/not-a-route", None, None).await;
+    let preflight = app.preflight("/company", "https://app.example.com").await;
+
+    for response in [ok, unmatched, preflight] {
+        assert_eq!(
+            response.header(header::STRICT_TRANSPORT_SECURITY),
+            Some("max-age=31536000; includeSubDomains")
+        );
+        assert_eq!(
+            response.header(header::X_CONTENT_TYPE_OPTIONS),
+            Some("nosniff")
+        );
+        assert_eq!(response.header(header::X_FRAME_OPTIONS), Some("DENY"));
+        assert_eq!(
+            response.header(header::REFERRER_POLICY),
+            Some("no-referrer")
+        );
+    }
+}
+
+#[tokio::test]
+async fn security_headers_follow_the_config() {
+    let mut config = test_config();
+    config.security_headers.hsts_max_age_secs = 0;
+    config.security_headers.frame_options = FrameOptions::SameOrigin;
+    let app = TestApp::with_config(config.clone(), Vec::new());
+
+    let response = app.request(Method::GET, "/health/live", None, None).await;
+    assert_eq!(response.header(header::STRICT_TRANSPORT_SECURITY), None);
+    assert_eq!(response.header(header::X_FRAME_OPTIONS), Some("SAMEORIGIN"));
+
+    config.security_headers.enabled = false;
+    let app = TestApp::with_config(config, Vec::new());
+    let response = app.request(Method::GET, "/health/live", None, None).await;
+    for name in SECURITY_HEADERS {
+        assert_eq!(response.header(name), None);
+    }
+}
+
+#[tokio::test]
+async fn cors_exposes_headers_the_spa_reads() {
+    let mut config = test_config();
+    config.cors.allowed_origins = vec!["https://app.example.com".into()];
+    config.cors.allow_credentials = true;
+    let app = TestApp::with_config(config, Vec::new());
+
+    let request = Request::builder()
+        .uri("/health/live")
+        .header(header::ORIGIN, "https://app.example.com")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+
+    let exposed = response
+        .header(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+        .unwrap();
+    assert!(exposed.contains("x-request-id"), "{exposed}");
+    assert!(exposed.contains("ratelimit-remaining"), "{exposed}");
+    assert_eq!(
+        response.header(header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        Some("true")
+    );
+}
+
+async fn get_openapi(app: &TestApp, encoding: &str) -> crate::app_router::test_app::TestResponse {
+    let request = Request::builder()
+        .uri("/openapi.json")
+        .header(header::ACCEPT_ENCODING, encoding)
+        .body(Body::empty())
+        .unwrap();
+    app.send(request).await
+}
+
+#[tokio::test]
+async fn large_responses_are_compressed() {
+    let app = TestApp::new();
+
+    let gzip = get_openapi(&app, "gzip").await;
+    let brotli = get_openapi(&app, "br").await;
+    let identity = get_openapi(&app, "identity").await;
+
+    assert_eq!(gzip.status, StatusCode::OK);
+    assert_eq!(gzip.header(header::CONTENT_ENCODING), Some("gzip"));
+    assert_eq!(brotli.header(header::CONTENT_ENCODING), Some("br"));
+    assert_eq!(identity.header(header::CONTENT_ENCODING), None);
+    assert!(gzip.body.len() < identity.body.len());
+
+    // below min_size_bytes
+    let request = Request::builder()
+        .uri("/health/live")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .body(Body::empty())
+        .unwrap();
+    let small = app.send(request).await;
+    assert_eq!(small.header(header::CONTENT_ENCODING), None);
+}
+
+#[tokio::test]
+async fn compression_can_be_disabled() {
+    let mut config = test_config();
+    config.compression.enabled = false;
+    let app = TestApp::with_config(config, Vec::new());
+
+    let response = get_openapi(&app, "gzip").await;
+
+    assert_eq!(response.header(header::CONTENT_ENCODING), None);
+}
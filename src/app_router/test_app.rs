@@ -0,0 +1,253 @@
+use std::sync::{Arc, LazyLock};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use axum::Router;
+use axum::body::{Body, Bytes, to_bytes};
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use chrono::Utc;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::app_config::config::AppConfig;
+use crate::app_health::check::HealthCheck;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::request_id::REQUEST_ID_HEADER;
+use crate::app_rate_limit::memory_backend::MemoryRateLimitBackend;
+use crate::app_router::router::{AppDeps, build_app};
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::user::domain::user::{User, UserRole};
+use crate::user::repository::user_repository_memory::UserRepositoryMemory;
+use crate::user::usecase::password::hash_password_with;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+
+// the account TestApp::login signs in with, sub of its tokens
+pub const TEST_ADMIN_ID: Uuid = Uuid::from_u128(0x0b5e_55ed_a0d1_4f00_8000_0000_0000_0001);
+
+// hashed once with the cheapest argon2 parameters, the defaults take long in
+// debug builds and verification follows the parameters stored in the hash
+static TEST_ADMIN_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    let params = Params::new(Params::MIN_M_COST, 1, 1, None).unwrap();
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    hash_password_with(&argon2, "password").unwrap()
+});
+
+pub fn test_admin() -> User {
+    User {
+        id: TEST_ADMIN_ID,
+        name: "Administrator".into(),
+        username: "admin".into(),
+        email: "admin@inventory.test".into(),
+        phone_number: None,
+        encrypted_password: Some(TEST_ADMIN_PASSWORD_HASH.clone()),
+        role: UserRole::Admin,
+        created_at: Utc::now(),
+    }
+}
+
+// the router from build_app on top of the in-memory repositories, requests go
+// through tower::ServiceExt::oneshot so no socket or database is needed
+pub struct TestApp {
+    router: Router,
+    pub companies: CompanyRepositoryMemory,
+    pub products: ProductRepositoryMemory,
+    pub warehouses: WarehouseRepositoryMemory,
+    pub stocks: StockRepositoryMemory,
+    pub users: UserRepositoryMemory,
+    pub lifecycle: Lifecycle,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            panic!(
+                "body is not json ({e}): {}",
+                String::from_utf8_lossy(&self.body)
+            )
+        })
+    }
+
+    // error body without request_id, after checking it matches the response header
+    pub fn error_json(&self) -> Value {
+        let mut body = self.json();
+        let request_id = body
+            .as_object_mut()
+            .and_then(|body| body.remove("request_id"));
+        assert_eq!(
+            request_id.as_ref().and_then(Value::as_str),
+            self.header(REQUEST_ID_HEADER),
+            "request_id in the body does not match the header"
+        );
+        body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn header(&self, name: header::HeaderName) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+// an error response as TestResponse::error_json returns it
+pub fn error_body(status: u16, message: &str) -> Value {
+    json!({ "status": status, "message": message, "detail": null })
+}
+
+pub fn test_config() -> AppConfig {
+    let mut config = AppConfig::default();
+    config.database.url = "postgres://localhost/unused".into();
+    config.jwt.secret = "test-secret-key-of-at-least-32-bytes".into();
+    config
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::with_companies(Vec::new())
+    }
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_companies(companies: Vec<Company>) -> Self {
+        Self::with_config(test_config(), companies)
+    }
+
+    pub fn with_config(config: AppConfig, companies: Vec<Company>) -> Self {
+        Self::with_health_checks(config, companies, Vec::new())
+    }
+
+    pub fn with_health_checks(
+        config: AppConfig,
+        companies: Vec<Company>,
+        health_checks: Vec<Arc<dyn HealthCheck>>,
+    ) -> Self {
+        let companies = CompanyRepositoryMemory::with_companies(companies);
+        let products = ProductRepositoryMemory::new(companies.clone());
+        let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+        let stocks =
+            StockRepositoryMemory::new(companies.clone(), products.clone(), warehouses.clone());
+        let users = UserRepositoryMemory::with_users(vec![test_admin()]);
+        let lifecycle = Lifecycle::new();
+        lifecycle.mark_ready();
+        let deps = AppDeps {
+            company_repository: companies.clone(),
+            product_repository: products.clone(),
+            warehouse_repository: warehouses.clone(),
+            stock_repository: stocks.clone(),
+            user_repository: users.clone(),
+            lifecycle: lifecycle.clone(),
+            health_checks,
+            metrics: AppMetrics::new(),
+            rate_limit_backend: Arc::new(MemoryRateLimitBackend::new()),
+        };
+
+        Self {
+            router: build_app(&config, deps),
+            companies,
+            products,
+            warehouses,
+            stocks,
+            users,
+            lifecycle,
+        }
+    }
+
+    pub fn router(&self) -> &Router {
+        &self.router
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        self.send(request).await
+    }
+
+    pub async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn preflight(&self, uri: &str, origin: &str) -> TestResponse {
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri(uri)
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap();
+
+        self.send(request).await
+    }
+
+    // POSTs the body, expects 201 and returns the created data
+    pub async fn create(&self, token: &str, uri: &str, body: Value) -> Value {
+        let response = self
+            .request(Method::POST, uri, Some(token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::CREATED, "{}", response.text());
+        response.json()["data"].clone()
+    }
+
+    // POSTs to an action, the body of a failed one comes from error_json
+    pub async fn post(&self, token: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let response = self.request(Method::POST, uri, Some(token), body).await;
+        if response.status.is_success() {
+            (response.status, response.json())
+        } else {
+            (response.status, response.error_json())
+        }
+    }
+
+    // logs in as test_admin and returns the bearer token
+    pub async fn login(&self) -> String {
+        let body = json!({ "username": "admin", "password": "password" });
+        let response = self.request(Method::POST, "/login", None, Some(body)).await;
+        assert_eq!(
+            response.status,
+            StatusCode::OK,
+            "login failed: {}",
+            response.text()
+        );
+
+        response.json()["data"].as_str().unwrap().to_string()
+    }
+}
@@ -0,0 +1,261 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+
+use axum::body::{Body, Bytes};
+use chrono::Utc;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use rust_xlsxwriter::{Workbook, XlsxError};
+use serde_json::{Map, Value};
+use tokio::sync::mpsc;
+use tokio_util::io::ReaderStream;
+use tracing::error;
+
+use crate::app_response::error::{ResponseError, error_chain};
+use crate::company::domain::company::Company;
+use crate::company::handler::map_company_error::map_usecase_company_error;
+use crate::company::usecase::company_usecase::CompanyUsecaseError;
+
+// how many rows may wait between the database stream and the xlsx writer thread
+const XLSX_CHANNEL_SIZE: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn parse(raw: Option<&str>) -> Result<Self, ResponseError> {
+        match raw.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("csv") => Ok(ExportFormat::Csv),
+            Some("xlsx") => Ok(ExportFormat::Xlsx),
+            Some("ndjson") => Ok(ExportFormat::Ndjson),
+            Some(other) => Err(ResponseError::BadRequest(format!(
+                "unsupported export format: {other}"
+            ))),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn content_disposition(&self) -> String {
+        format!(
+            "attachment; filename=\"companies-{}.{}\"",
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            self.extension()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompanyColumn {
+    Id,
+    Name,
+    Code,
+    Email,
+    PhoneNumber,
+    Address,
+    CreatedAt,
+}
+
+impl CompanyColumn {
+    const ALL: [CompanyColumn; 7] = [
+        CompanyColumn::Id,
+        CompanyColumn::Name,
+        CompanyColumn::Code,
+        CompanyColumn::Email,
+        CompanyColumn::PhoneNumber,
+        CompanyColumn::Address,
+        CompanyColumn::CreatedAt,
+    ];
+
+    // columns=name,code,email -> keeps the requested order, empty means every column
+    pub fn parse_list(raw: Option<&str>) -> Result<Vec<Self>, ResponseError> {
+        let raw = match raw {
+            Some(v) if !v.trim().is_empty() => v,
+            _ => return Ok(Self::ALL.to_vec()),
+        };
+
+        let mut columns = Vec::new();
+        for name in raw.split(",") {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+
+            let column = Self::ALL
+                .into_iter()
+                .find(|c| c.name() == name)
+                .ok_or_else(|| ResponseError::BadRequest(format!("unknown column: {name}")))?;
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+
+        if columns.is_empty() {
+            return Ok(Self::ALL.to_vec());
+        }
+        Ok(columns)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompanyColumn::Id => "id",
+            CompanyColumn::Name => "name",
+            CompanyColumn::Code => "code",
+            CompanyColumn::Email => "email",
+            CompanyColumn::PhoneNumber => "phone_number",
+            CompanyColumn::Address => "address",
+            CompanyColumn::CreatedAt => "created_at",
+        }
+    }
+
+    pub fn text(&self, company: &Company) -> String {
+        match self {
+            CompanyColumn::Id => company.id.to_string(),
+            CompanyColumn::Name => company.name.clone(),
+            CompanyColumn::Code => company.code.clone(),
+            CompanyColumn::Email => company.email.clone(),
+            CompanyColumn::PhoneNumber => company.phone_number.clone().unwrap_or_default(),
+            CompanyColumn::Address => company.address.clone().unwrap_or_default(),
+            CompanyColumn::CreatedAt => company.created_at.to_rfc3339(),
+        }
+    }
+
+    pub fn json(&self, company: &Company) -> Value {
+        match self {
+            CompanyColumn::PhoneNumber => company.phone_number.clone().into(),
+            CompanyColumn::Address => company.address.clone().into(),
+            _ => self.text(company).into(),
+        }
+    }
+}
+
+fn stream_error(err: CompanyUsecaseError) -> std::io::Error {
+    // headers are already sent, the only thing left is to abort the body
+    error!(cause = %error_chain(&err), "company export aborted");
+    std::io::Error::other("company export aborted")
+}
+
+fn csv_line<I, T>(fields: I) -> Result<Bytes, std::io::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| e.into_error())
+}
+
+pub fn csv_body(
+    columns: Vec<CompanyColumn>,
+    companies: BoxStream<'static, Result<Company, CompanyUsecaseError>>,
+) -> Body {
+    let header = csv_line(columns.iter().map(|c| c.name()));
+    let rows = companies.map_err(stream_error).and_then(move |company| {
+        let line = csv_line(columns.iter().map(|c| c.text(&company)));
+        async move { line }
+    });
+
+    Body::from_stream(stream::once(async move { header }).chain(rows))
+}
+
+pub fn ndjson_body(
+    columns: Vec<CompanyColumn>,
+    companies: BoxStream<'static, Result<Company, CompanyUsecaseError>>,
+) -> Body {
+    let rows = companies.map_err(stream_error).and_then(move |company| {
+        let object: Map<String, Value> = columns
+            .iter()
+            .map(|c| (c.name().to_string(), c.json(&company)))
+            .collect();
+        let line = serde_json::to_vec(&object)
+            .map(|mut line| {
+                line.push(b'\n');
+                Bytes::from(line)
+            })
+            .map_err(std::io::Error::other);
+        async move { line }
+    });
+
+    Body::from_stream(rows)
+}
+
+// the xlsx container is a zip archive so it can only be sent once complete.
+// the constant memory worksheet flushes rows to a temp file and the archive is
+// written to another one, which is streamed back, so neither the rows nor the
+// archive are held in memory
+pub async fn xlsx_body(
+    columns: Vec<CompanyColumn>,
+    mut companies: BoxStream<'static, Result<Company, CompanyUsecaseError>>,
+) -> Result<Body, ResponseError> {
+    let (sender, receiver) = mpsc::channel::<Company>(XLSX_CHANNEL_SIZE);
+    let writer = tokio::task::spawn_blocking(move || write_xlsx(&columns, receiver));
+
+    while let Some(company) = companies.next().await {
+        let company = company.map_err(map_usecase_company_error)?;
+        if sender.send(company).await.is_err() {
+            // writer stopped early, its error is reported below
+            break;
+        }
+    }
+    drop(sender);
+
+    let file = writer
+        .await
+        .map_err(ResponseError::internal)?
+        .map_err(ResponseError::internal)?;
+
+    Ok(Body::from_stream(ReaderStream::new(
+        tokio::fs::File::from_std(file),
+    )))
+}
+
+fn write_xlsx(
+    columns: &[CompanyColumn],
+    mut receiver: mpsc::Receiver<Company>,
+) -> Result<File, XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet_with_constant_memory();
+    worksheet.set_name("companies")?;
+
+    for (col, column) in columns.iter().enumerate() {
+        worksheet.write_string(0, col as u16, column.name())?;
+    }
+
+    let mut row: u32 = 1;
+    while let Some(company) = receiver.blocking_recv() {
+        for (col, column) in columns.iter().enumerate() {
+            worksheet.write_string(row, col as u16, column.text(&company))?;
+        }
+        row += 1;
+    }
+
+    // unnamed, the file is gone once the response body drops it
+    let mut file = tempfile::tempfile()?;
+    workbook.save_to_writer(&mut file)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(file)
+}
@@ -1,13 +1,19 @@
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::company::handler::types::{
+    BulkCompanyItemResponse, BulkCompanyOperationRequest, BulkCompanyRequest, ExportCompanyRequest,
+};
 use crate::{company::handler::types::ProcessCompanyRequest, app_request::{pagination::PaginationRequest, path_uuid::PathUuid}};
+use crate::company::handler::company_export::{
+    CompanyColumn, ExportFormat, csv_body, ndjson_body, xlsx_body,
+};
 use crate::company::{
     handler::map_company_error::map_usecase_company_error,
     repository::company_repository::CompanyRepository,
@@ -15,8 +21,15 @@ use crate::company::{
 use crate::company::{
     handler::map_company_error::validate_company_input, usecase::company_usecase::CompanyUsecase,
 };
-use crate::app_response::error::ResponseError;
-use crate::app_response::success::ResponseSuccess;
+use crate::company::handler::map_company_error::{
+    map_bulk_company_error, validate_bulk_company_input, with_operation_index,
+};
+use crate::company::repository::helper_query::COMPANY_SORT_COLUMNS;
+use crate::company::usecase::dto::{BulkCompanyOperation, BulkCompanyOutcome};
+use crate::app_config::config::AppConfig;
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::company::domain::company::Company;
 
 // order parameter in handler MUST
 // 1. STATE
@@ -25,6 +38,20 @@ use crate::app_response::success::ResponseSuccess;
 // 4. HEADER / EXTENSION
 // 5. JSON / FORM / MULTIPART
 
+#[utoipa::path(
+    post,
+    path = "/company",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    request_body = ProcessCompanyRequest,
+    responses(
+        (status = 201, description = "company created", body = ResponseSuccessBody<Company>),
+        (status = 400, description = "invalid input", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
 pub async fn create_company_handler<R: CompanyRepository>(
     State(usecase): State<Arc<CompanyUsecase<R>>>,
     Json(req): Json<ProcessCompanyRequest>,
@@ -39,6 +66,22 @@ pub async fn create_company_handler<R: CompanyRepository>(
     Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(company)))
 }
 
+#[utoipa::path(
+    put,
+    path = "/company/{id}",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    request_body = ProcessCompanyRequest,
+    responses(
+        (status = 201, description = "company updated", body = ResponseSuccessBody<Company>),
+        (status = 400, description = "invalid input", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
 pub async fn update_company_handler<R: CompanyRepository>(
     State(usecase): State<Arc<CompanyUsecase<R>>>,
     PathUuid(id): PathUuid,
@@ -61,6 +104,21 @@ pub async fn update_company_handler<R: CompanyRepository>(
     Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(company)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/company/{id}",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    responses(
+        (status = 200, description = "company deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "company still has products or warehouses", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
 pub async fn delete_company_handler<R: CompanyRepository>(
     State(usecase): State<Arc<CompanyUsecase<R>>>,
     Path(id): Path<Uuid>,
@@ -73,12 +131,29 @@ pub async fn delete_company_handler<R: CompanyRepository>(
     Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
 }
 
+#[utoipa::path(
+    get,
+    path = "/company",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest),
+    responses(
+        (status = 200, description = "one page of companies, meta holds the totals", body = ResponseSuccessBody<Vec<Company>>),
+        (status = 400, description = "unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
 pub async fn get_companies_handler<R: CompanyRepository>(
     State(usecase): State<Arc<CompanyUsecase<R>>>,
     Query(q): Query<PaginationRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
 ) -> Result<impl IntoResponse, ResponseError> {
-    let page = q.page.unwrap_or(1);
-    let per_page = q.per_page.unwrap_or(1);
+    q.validate_sort(&COMPANY_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
     let search = q.search.unwrap_or("".into());
     let sort = q.sort.unwrap_or("".into());
     
@@ -101,3 +176,196 @@ pub async fn get_companies_handler<R: CompanyRepository>(
         Some(company_list_data.data),
     ))
 }
+
+#[utoipa::path(
+    get,
+    path = "/company/export",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    params(ExportCompanyRequest),
+    responses(
+        (status = 200, description = "every matching company, streamed", content(
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        )),
+        (status = 400, description = "unknown format, column or sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn export_companies_handler<R: CompanyRepository>(
+    State(usecase): State<Arc<CompanyUsecase<R>>>,
+    Query(q): Query<ExportCompanyRequest>,
+) -> Result<Response, ResponseError> {
+    let format = ExportFormat::parse(q.format.as_deref())?;
+    let columns = CompanyColumn::parse_list(q.columns.as_deref())?;
+
+    // same search and sort as the list endpoint, without the page window
+    let query = PaginationRequest {
+        page: None,
+        per_page: None,
+        offset: None,
+        search: q.search,
+        sort: q.sort,
+    };
+    query.validate_sort(&COMPANY_SORT_COLUMNS)?;
+    let companies = usecase.export_company(query);
+
+    let body = match format {
+        ExportFormat::Csv => csv_body(columns, companies),
+        ExportFormat::Ndjson => ndjson_body(columns, companies),
+        ExportFormat::Xlsx => xlsx_body(columns, companies).await?,
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (header::CONTENT_DISPOSITION, format.content_disposition()),
+    ];
+    Ok((StatusCode::OK, headers, body).into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/company/bulk",
+    tag = "company",
+    security(("bearer_auth" = [])),
+    request_body = BulkCompanyRequest,
+    responses(
+        (status = 200, description = "atomic batch applied", body = ResponseSuccessBody<Vec<BulkCompanyItemResponse>>),
+        (status = 207, description = "result of every operation in order", body = ResponseSuccessBody<Vec<BulkCompanyItemResponse>>),
+        (status = 400, description = "invalid batch, or an atomic batch was rolled back", body = ResponseErrorBody),
+        (status = 404, description = "an atomic batch was rolled back on a missing company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn bulk_company_handler<R: CompanyRepository>(
+    State(usecase): State<Arc<CompanyUsecase<R>>>,
+    Json(req): Json<BulkCompanyRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_bulk_company_input(&req)?;
+
+    if req.atomic {
+        let mut operations = Vec::with_capacity(req.operations.len());
+        for (index, operation) in req.operations.into_iter().enumerate() {
+            let operation =
+                into_bulk_operation(operation).map_err(|e| with_operation_index(index, e))?;
+            operations.push(operation);
+        }
+
+        let items = usecase
+            .bulk_company_atomic(operations)
+            .await
+            .map_err(map_bulk_company_error)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, outcome)| bulk_item_success(index, outcome))
+            .collect::<Vec<_>>();
+
+        return Ok(ResponseSuccess::Object(StatusCode::OK, Some(items)));
+    }
+
+    // invalid items are answered right away, the rest go to the usecase in the same order
+    let mut items: Vec<Option<BulkCompanyItemResponse>> = Vec::with_capacity(req.operations.len());
+    let mut pending = Vec::new();
+    let mut operations = Vec::new();
+    for (index, operation) in req.operations.into_iter().enumerate() {
+        let (op, id) = bulk_operation_label(&operation);
+        match into_bulk_operation(operation) {
+            Ok(operation) => {
+                items.push(None);
+                pending.push(index);
+                operations.push(operation);
+            }
+            Err(err) => items.push(Some(bulk_item_error(index, op, id, err))),
+        }
+    }
+
+    let labels = operations.iter().map(bulk_usecase_operation_label).collect::<Vec<_>>();
+    let results = usecase.bulk_company(operations).await;
+    for ((index, (op, id)), result) in pending.into_iter().zip(labels).zip(results) {
+        items[index] = Some(match result {
+            Ok(outcome) => bulk_item_success(index, outcome),
+            Err(err) => bulk_item_error(index, op, id, map_usecase_company_error(err)),
+        });
+    }
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::MULTI_STATUS,
+        Some(items.into_iter().flatten().collect::<Vec<_>>()),
+    ))
+}
+
+fn into_bulk_operation(
+    operation: BulkCompanyOperationRequest,
+) -> Result<BulkCompanyOperation, ResponseError> {
+    match operation {
+        BulkCompanyOperationRequest::Create { data } => {
+            validate_company_input(&data)?;
+            Ok(BulkCompanyOperation::Create(data.into()))
+        }
+        BulkCompanyOperationRequest::Update { id, data } => {
+            validate_company_input(&data)?;
+            Ok(BulkCompanyOperation::Update(id, data.into()))
+        }
+        BulkCompanyOperationRequest::Delete { id } => Ok(BulkCompanyOperation::Delete(id)),
+    }
+}
+
+fn bulk_operation_label(operation: &BulkCompanyOperationRequest) -> (&'static str, Option<Uuid>) {
+    match operation {
+        BulkCompanyOperationRequest::Create { .. } => ("create", None),
+        BulkCompanyOperationRequest::Update { id, .. } => ("update", Some(*id)),
+        BulkCompanyOperationRequest::Delete { id } => ("delete", Some(*id)),
+    }
+}
+
+fn bulk_usecase_operation_label(operation: &BulkCompanyOperation) -> (&'static str, Option<Uuid>) {
+    match operation {
+        BulkCompanyOperation::Create(_) => ("create", None),
+        BulkCompanyOperation::Update(id, _) => ("update", Some(*id)),
+        BulkCompanyOperation::Delete(id) => ("delete", Some(*id)),
+    }
+}
+
+fn bulk_item_success(index: usize, outcome: BulkCompanyOutcome) -> BulkCompanyItemResponse {
+    let (op, status, id, data) = match outcome {
+        BulkCompanyOutcome::Created(company) => {
+            ("create", StatusCode::CREATED, company.id, Some(company))
+        }
+        BulkCompanyOutcome::Updated(company) => {
+            ("update", StatusCode::OK, company.id, Some(company))
+        }
+        BulkCompanyOutcome::Deleted(id) => ("delete", StatusCode::OK, id, None),
+    };
+
+    BulkCompanyItemResponse {
+        index,
+        op,
+        id: Some(id),
+        status: status.as_u16(),
+        data,
+        error: None,
+    }
+}
+
+fn bulk_item_error(
+    index: usize,
+    op: &'static str,
+    id: Option<Uuid>,
+    err: ResponseError,
+) -> BulkCompanyItemResponse {
+    err.log_source();
+
+    BulkCompanyItemResponse {
+        index,
+        op,
+        id,
+        status: err.status_code().as_u16(),
+        data: None,
+        error: Some(err.body()),
+    }
+}
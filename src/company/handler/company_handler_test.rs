@@ -0,0 +1,499 @@
+use axum::http::{Method, StatusCode, header};
+use chrono::{TimeZone, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_router::test_app::{TestApp, error_body, test_config};
+use crate::company::domain::company::Company;
+
+fn company(code: &str, name: &str) -> Company {
+    Company {
+        id: Uuid::new_v4(),
+        name: name.into(),
+        email: format!("{}@mail.com", code.to_lowercase()),
+        code: code.into(),
+        phone_number: Some("0811".into()),
+        address: Some("Jakarta".into()),
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+fn payload(code: &str, name: &str) -> Value {
+    json!({
+        "name": name,
+        "email": format!("{}@mail.com", code.to_lowercase()),
+        "code": code,
+        "phone_number": "0811",
+        "address": "Jakarta",
+    })
+}
+
+fn codes(data: &Value) -> Vec<&str> {
+    data.as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["code"].as_str().unwrap())
+        .collect()
+}
+
+async fn setup(companies: Vec<Company>) -> (TestApp, String) {
+    let app = TestApp::with_companies(companies);
+    let token = app.login().await;
+    (app, token)
+}
+
+fn every_company_route() -> Vec<(Method, String)> {
+    let id = Uuid::new_v4();
+    vec![
+        (Method::GET, "/company".into()),
+        (Method::POST, "/company".into()),
+        (Method::GET, "/company/export".into()),
+        (Method::POST, "/company/bulk".into()),
+        (Method::PUT, format!("/company/{id}")),
+        (Method::DELETE, format!("/company/{id}")),
+    ]
+}
+
+#[tokio::test]
+async fn company_routes_require_bearer_token() {
+    let app = TestApp::new();
+
+    for (method, uri) in every_company_route() {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+        assert_eq!(response.error_json(), error_body(401, "unauthorized"));
+
+        let response = app
+            .request(method.clone(), &uri, Some("not-a-jwt"), None)
+            .await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+        assert_eq!(response.error_json(), error_body(401, "invalid token"));
+    }
+}
+
+#[tokio::test]
+async fn get_companies_returns_page_with_meta() {
+    let (app, token) = setup(vec![
+        company("GLB", "Globex"),
+        company("ACM", "Acme"),
+        company("INI", "Initech"),
+    ])
+    .await;
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company?page=2&per_page=2&sort=code",
+            Some(&token),
+            None,
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["message"], "success");
+    assert_eq!(json["http_code"], 200);
+    assert_eq!(codes(&json["data"]), vec!["INI"]);
+    assert_eq!(
+        json["meta"],
+        json!({ "page": 2, "per_page": 2, "total_data": 3, "total_page": 2 })
+    );
+}
+
+#[tokio::test]
+async fn get_companies_searches_name_and_code() {
+    let (app, token) = setup(vec![
+        company("GLB", "Globex"),
+        company("ACM", "Acme"),
+        company("INI", "Initech"),
+    ])
+    .await;
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company?per_page=10&search=ni&sort=-code",
+            Some(&token),
+            None,
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let json = response.json();
+    assert_eq!(codes(&json["data"]), vec!["INI"]);
+    assert_eq!(json["meta"]["total_data"], 1);
+}
+
+#[tokio::test]
+async fn get_companies_applies_configured_page_size() {
+    let mut config = test_config();
+    config.pagination.default_per_page = 2;
+    config.pagination.max_per_page = 3;
+    let companies = ["ACM", "GLB", "INI", "UMB"]
+        .map(|code| company(code, code))
+        .to_vec();
+    let app = TestApp::with_config(config, companies);
+    let token = app.login().await;
+
+    let response = app
+        .request(Method::GET, "/company?sort=code", Some(&token), None)
+        .await;
+    let json = response.json();
+    assert_eq!(codes(&json["data"]), vec!["ACM", "GLB"]);
+    assert_eq!(json["meta"]["per_page"], 2);
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company?sort=code&per_page=50",
+            Some(&token),
+            None,
+        )
+        .await;
+    let json = response.json();
+    assert_eq!(codes(&json["data"]), vec!["ACM", "GLB", "INI"]);
+    assert_eq!(json["meta"]["per_page"], 3);
+    assert_eq!(json["meta"]["total_page"], 2);
+
+    let response = app
+        .request(Method::GET, "/company?sort=code&page=0", Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["meta"]["page"], 1);
+}
+
+#[tokio::test]
+async fn company_list_and_export_refuse_unknown_sort() {
+    let (app, token) = setup(vec![company("ACM", "Acme")]).await;
+
+    for uri in [
+        "/company?sort=code;DROP%20TABLE%20companies",
+        "/company/export?sort=(SELECT%201)",
+        "/company/export?format=xlsx&sort=-password",
+    ] {
+        let response = app.request(Method::GET, uri, Some(&token), None).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{uri}");
+        let message = response.error_json()["message"].clone();
+        assert!(
+            message.as_str().unwrap().starts_with("Sort by "),
+            "{uri} {message}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn create_company_returns_created_company() {
+    let (app, token) = setup(Vec::new()).await;
+
+    let response = app
+        .request(
+            Method::POST,
+            "/company",
+            Some(&token),
+            Some(payload("ACM", "Acme")),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    let json = response.json();
+    assert_eq!(json["http_code"], 201);
+    assert_eq!(json["data"]["code"], "ACM");
+    assert_eq!(json["data"]["email"], "acm@mail.com");
+
+    let stored = app.companies.companies().await;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(json["data"]["id"], stored[0].id.to_string());
+}
+
+#[tokio::test]
+async fn create_company_validates_input() {
+    let (app, token) = setup(Vec::new()).await;
+
+    let mut body = payload("ACM", "Acme");
+    body["name"] = json!("");
+    let response = app
+        .request(Method::POST, "/company", Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "Name is required"));
+
+    let mut body = payload("ACM", "Acme");
+    body["address"] = Value::Null;
+    let response = app
+        .request(Method::POST, "/company", Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "Address is required"));
+
+    assert!(app.companies.companies().await.is_empty());
+}
+
+#[tokio::test]
+async fn create_company_rejects_existing_email_and_code() {
+    let (app, token) = setup(vec![company("ACM", "Acme")]).await;
+
+    let response = app
+        .request(
+            Method::POST,
+            "/company",
+            Some(&token),
+            Some(payload("ACM", "Other")),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "email already exist"));
+
+    let mut body = payload("ACM", "Other");
+    body["email"] = json!("other@mail.com");
+    let response = app
+        .request(Method::POST, "/company", Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "code already exist"));
+}
+
+#[tokio::test]
+async fn update_company_changes_company() {
+    let acme = company("ACM", "Acme");
+    let (app, token) = setup(vec![acme.clone()]).await;
+
+    let uri = format!("/company/{}", acme.id);
+    let response = app
+        .request(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            Some(payload("ACM", "Acme Renamed")),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::CREATED);
+    let json = response.json();
+    assert_eq!(json["data"]["id"], acme.id.to_string());
+    assert_eq!(json["data"]["name"], "Acme Renamed");
+    assert_eq!(app.companies.companies().await[0].name, "Acme Renamed");
+}
+
+#[tokio::test]
+async fn update_company_rejects_bad_id_and_missing_company() {
+    let (app, token) = setup(vec![company("ACM", "Acme")]).await;
+
+    let body = payload("ACM", "Acme");
+    let response = app
+        .request(
+            Method::PUT,
+            "/company/123",
+            Some(&token),
+            Some(body.clone()),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "invalid uuid"));
+
+    let uri = format!("/company/{}", Uuid::new_v4());
+    let response = app
+        .request(Method::PUT, &uri, Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "data not found"));
+}
+
+#[tokio::test]
+async fn delete_company_removes_company() {
+    let acme = company("ACM", "Acme");
+    let (app, token) = setup(vec![acme.clone(), company("GLB", "Globex")]).await;
+
+    let uri = format!("/company/{}", acme.id);
+    let response = app.request(Method::DELETE, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json(),
+        json!({ "message": "success", "http_code": 200, "data": null })
+    );
+
+    let stored = app.companies.companies().await;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].code, "GLB");
+
+    let response = app.request(Method::DELETE, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "data not found"));
+}
+
+#[tokio::test]
+async fn export_companies_streams_requested_format() {
+    let (app, token) = setup(vec![company("GLB", "Globex"), company("ACM", "Acme")]).await;
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company/export?columns=code,name&sort=code",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        Some("text/csv; charset=utf-8")
+    );
+    assert!(
+        response
+            .header(header::CONTENT_DISPOSITION)
+            .unwrap()
+            .ends_with(".csv\"")
+    );
+    assert_eq!(response.text(), "code,name\nACM,Acme\nGLB,Globex\n");
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company/export?format=ndjson&columns=code&search=glo",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        Some("application/x-ndjson")
+    );
+    assert_eq!(response.text(), "{\"code\":\"GLB\"}\n");
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company/export?format=xlsx",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+    );
+    assert!(response.body.starts_with(b"PK\x03\x04"));
+
+    let response = app
+        .request(
+            Method::GET,
+            "/company/export?format=pdf",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "unsupported export format: pdf")
+    );
+}
+
+#[tokio::test]
+async fn bulk_company_atomic_applies_every_operation() {
+    let acme = company("ACM", "Acme");
+    let globex = company("GLB", "Globex");
+    let (app, token) = setup(vec![acme.clone(), globex.clone()]).await;
+
+    let body = json!({
+        "atomic": true,
+        "operations": [
+            { "op": "create", "data": payload("INI", "Initech") },
+            { "op": "update", "id": acme.id, "data": payload("ACM", "Acme Renamed") },
+            { "op": "delete", "id": globex.id },
+        ],
+    });
+    let response = app
+        .request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let items = response.json()["data"].clone();
+    let statuses: Vec<_> = items
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["status"].clone())
+        .collect();
+    assert_eq!(statuses, vec![json!(201), json!(200), json!(200)]);
+    assert_eq!(items[1]["data"]["name"], "Acme Renamed");
+
+    let mut stored: Vec<_> = app
+        .companies
+        .companies()
+        .await
+        .into_iter()
+        .map(|c| c.code)
+        .collect();
+    stored.sort();
+    assert_eq!(stored, vec!["ACM", "INI"]);
+}
+
+#[tokio::test]
+async fn bulk_company_atomic_rolls_back_on_failure() {
+    let (app, token) = setup(vec![company("ACM", "Acme")]).await;
+
+    let body = json!({
+        "atomic": true,
+        "operations": [
+            { "op": "create", "data": payload("INI", "Initech") },
+            { "op": "create", "data": payload("ACM", "Acme Again") },
+        ],
+    });
+    let response = app
+        .request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "operation 1: email already exist")
+    );
+    assert_eq!(app.companies.companies().await.len(), 1);
+}
+
+#[tokio::test]
+async fn bulk_company_reports_each_operation() {
+    let (app, token) = setup(vec![company("ACM", "Acme")]).await;
+
+    let mut invalid = payload("UMB", "Umbrella");
+    invalid["name"] = json!("");
+    let missing = Uuid::new_v4();
+    let body = json!({
+        "operations": [
+            { "op": "create", "data": payload("INI", "Initech") },
+            { "op": "create", "data": invalid },
+            { "op": "delete", "id": missing },
+        ],
+    });
+    let response = app
+        .request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    let json = response.json();
+    assert_eq!(json["http_code"], 207);
+    let items = json["data"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0]["status"], 201);
+    assert_eq!(items[1]["status"], 400);
+    assert_eq!(items[1]["error"], error_body(400, "Name is required"));
+    assert_eq!(items[2]["status"], 404);
+    assert_eq!(items[2]["id"], missing.to_string());
+
+    assert_eq!(app.companies.companies().await.len(), 2);
+}
+
+#[tokio::test]
+async fn bulk_company_rejects_empty_batch() {
+    let (app, token) = setup(Vec::new()).await;
+
+    let body = json!({ "operations": [] });
+    let response = app
+        .request(Method::POST, "/company/bulk", Some(&token), Some(body))
+        .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "Operations is required"));
+}
@@ -1,10 +1,11 @@
-use crate::company::handler::types::ProcessCompanyRequest;
+use crate::company::handler::types::{BulkCompanyRequest, ProcessCompanyRequest};
 use crate::company::usecase::company_usecase::CompanyUsecaseError;
+use crate::company::usecase::dto::BulkCompanyError;
 use crate::app_helper::helper::is_option_has_string_value;
 use crate::app_response::error::ResponseError;
 
 pub fn map_usecase_company_error(err: CompanyUsecaseError) -> ResponseError {
-    match err {
+    match &err {
         CompanyUsecaseError::EmailAlreadyExist => {
             ResponseError::BadRequest("email already exist".into())
         }
@@ -14,18 +15,56 @@ pub fn map_usecase_company_error(err: CompanyUsecaseError) -> ResponseError {
         CompanyUsecaseError::NotFound => {
             ResponseError::NotFound("data not found".into())
         }
-        CompanyUsecaseError::DatabaseError => ResponseError::DatabaseError,
+        CompanyUsecaseError::InUse => {
+            ResponseError::BadRequest("company is still in use".into())
+        }
+        CompanyUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+pub fn map_bulk_company_error(err: BulkCompanyError) -> ResponseError {
+    let response = map_usecase_company_error(err.error);
+    match err.index {
+        Some(index) => with_operation_index(index, response),
+        None => response,
     }
 }
 
+// tells the client which item of the batch the message belongs to
+pub fn with_operation_index(index: usize, err: ResponseError) -> ResponseError {
+    match err {
+        ResponseError::BadRequest(msg) => {
+            ResponseError::BadRequest(format!("operation {index}: {msg}"))
+        }
+        ResponseError::NotFound(msg) => {
+            ResponseError::NotFound(format!("operation {index}: {msg}"))
+        }
+        other => other,
+    }
+}
+
+pub const MAX_BULK_COMPANY_OPERATIONS: usize = 500;
+
+pub fn validate_bulk_company_input(req: &BulkCompanyRequest) -> Result<(), ResponseError> {
+    if req.operations.is_empty() {
+        return Err(ResponseError::BadRequest("Operations is required".into()));
+    }
+    if req.operations.len() > MAX_BULK_COMPANY_OPERATIONS {
+        return Err(ResponseError::BadRequest(format!(
+            "Operations can not be more than {MAX_BULK_COMPANY_OPERATIONS}"
+        )));
+    }
+    Ok(())
+}
+
 pub fn validate_company_input(req: &ProcessCompanyRequest) -> Result<(), ResponseError> {
-    if req.name == "" {
+    if req.name.is_empty() {
         return Err(ResponseError::BadRequest("Name is required".into()));
     }
-    if req.email == "" {
+    if req.email.is_empty() {
         return Err(ResponseError::BadRequest("Email is required".into()));
     }
-    if req.code == "" {
+    if req.code.is_empty() {
         return Err(ResponseError::BadRequest("Code is required".into()));
     }
     if !is_option_has_string_value(&req.phone_number) {
@@ -1,3 +1,6 @@
+pub mod company_export;
 pub mod company_handler;
+#[cfg(test)]
+mod company_handler_test;
 pub mod map_company_error;
-pub mod types;
\ No newline at end of file
+pub mod types;
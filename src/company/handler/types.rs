@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-#[derive(Deserialize, Serialize)]
+use crate::app_response::error::ResponseErrorBody;
+use crate::company::domain::company::Company;
+use crate::company::usecase::dto::CompanyInput;
+
+#[derive(Deserialize, Serialize, ToSchema)]
 pub struct ProcessCompanyRequest {
     // pub id: Option<String>,
     pub name: String,
@@ -8,4 +14,54 @@ pub struct ProcessCompanyRequest {
     pub code: String,
     pub phone_number: Option<String>,
     pub address: Option<String>,
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportCompanyRequest {
+    /// csv (default), xlsx or ndjson
+    pub format: Option<String>,
+    /// comma separated column names, every column when missing
+    pub columns: Option<String>,
+    /// matches name or code
+    pub search: Option<String>,
+    /// comma separated fields, prefix with - for descending
+    pub sort: Option<String>,
+}
+
+impl From<ProcessCompanyRequest> for CompanyInput {
+    fn from(req: ProcessCompanyRequest) -> Self {
+        CompanyInput {
+            name: req.name,
+            email: req.email,
+            code: req.code,
+            phone_number: req.phone_number,
+            address: req.address,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BulkCompanyRequest {
+    #[serde(default)]
+    pub atomic: bool,
+    pub operations: Vec<BulkCompanyOperationRequest>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkCompanyOperationRequest {
+    Create { data: ProcessCompanyRequest },
+    Update { id: Uuid, data: ProcessCompanyRequest },
+    Delete { id: Uuid },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkCompanyItemResponse {
+    pub index: usize,
+    pub op: &'static str,
+    pub id: Option<Uuid>,
+    pub status: u16,
+    pub data: Option<Company>,
+    pub error: Option<ResponseErrorBody>,
+}
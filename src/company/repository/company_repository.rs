@@ -1,17 +1,23 @@
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use uuid::Uuid;
+use crate::app_database::unit_of_work::Transactional;
 use crate::company::domain::company::Company;
 use crate::app_request::pagination::PaginationRequest;
 
+// every call takes the unit of work opened by the usecase, so a usecase method
+// is atomic as a whole
 #[async_trait]
-pub trait CompanyRepository: Send + Sync {
-    async fn get_company_by_id(&self, id: &Uuid) -> Result<Option<Company>, sqlx::Error>;
-    async fn count_all_companies(&self, query: &PaginationRequest, ) -> Result<i64, sqlx::Error>;
-    async fn find_all_companies(&self, query: &PaginationRequest) -> Result<Vec<Company>, sqlx::Error>;
-    async fn check_existing_company_email(&self, email: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
-    async fn check_existing_company_code(&self, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
-    async fn create_company(&self, company: Company) -> Result<Company, sqlx::Error>;
-    async fn update_company(&self, company: Company) -> Result<Company, sqlx::Error>;
-    async fn delete_company(&self, id: &Uuid) -> Result<(), sqlx::Error>;
+pub trait CompanyRepository: Transactional {
+    async fn get_company_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Company>, sqlx::Error>;
+    async fn count_all_companies(&self, uow: &mut Self::Uow, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_all_companies(&self, uow: &mut Self::Uow, query: &PaginationRequest) -> Result<Vec<Company>, sqlx::Error>;
+    // rows are fetched lazily, the stream owns everything it needs so it can outlive the request extractors
+    fn stream_all_companies(&self, query: PaginationRequest) -> BoxStream<'static, Result<Company, sqlx::Error>>;
+    async fn check_existing_company_email(&self, uow: &mut Self::Uow, email: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn check_existing_company_code(&self, uow: &mut Self::Uow, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_company(&self, uow: &mut Self::Uow, company: Company) -> Result<Company, sqlx::Error>;
+    async fn update_company(&self, uow: &mut Self::Uow, company: Company) -> Result<Company, sqlx::Error>;
+    async fn delete_company(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
     // async fn delete_company(&self, id: Uuid) -> Result<(), sqlx::Error>;
-}
\ No newline at end of file
+}
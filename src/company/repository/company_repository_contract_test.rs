@@ -0,0 +1,319 @@
+// the same scenarios run against the in-memory and the postgres repository so
+// the in-memory one keeps behaving like the real thing.
+// postgres runs need TEST_DATABASE_URL and are skipped without it.
+use chrono::{Duration, TimeZone, Utc};
+use futures::TryStreamExt;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                super::$scenario(&super::CompanyRepositoryMemory::new()).await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::$scenario(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(creates_and_finds_by_id);
+contract_test!(checks_existing_email_and_code);
+contract_test!(enforces_unique_constraints);
+contract_test!(searches_sorts_and_pages);
+contract_test!(streams_without_page_window);
+contract_test!(updates_and_deletes);
+contract_test!(rolls_back_uncommitted_work);
+
+fn company(code: &str, name: &str, minutes: i64) -> Company {
+    Company {
+        id: Uuid::new_v4(),
+        name: name.into(),
+        email: format!("{}@mail.com", code.to_lowercase()),
+        code: code.into(),
+        phone_number: None,
+        address: Some(format!("address {code}")),
+        // postgres keeps microseconds, whole minutes survive the round trip
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes),
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+async fn seed<R: CompanyRepository>(repo: &R, companies: Vec<Company>) {
+    let mut uow = repo.begin().await.unwrap();
+    for company in companies {
+        repo.create_company(&mut uow, company).await.unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn codes(companies: &[Company]) -> Vec<&str> {
+    companies.iter().map(|c| c.code.as_str()).collect()
+}
+
+async fn creates_and_finds_by_id<R: CompanyRepository>(repo: &R) {
+    let acme = company("ACM", "Acme", 0);
+    seed(repo, vec![acme.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_company_by_id(&mut uow, &acme.id).await.unwrap(),
+        Some(acme)
+    );
+    assert_eq!(
+        repo.get_company_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+async fn checks_existing_email_and_code<R: CompanyRepository>(repo: &R) {
+    let acme = company("ACM", "Acme", 0);
+    seed(repo, vec![acme.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let other = Uuid::new_v4();
+    assert!(
+        repo.check_existing_company_email(&mut uow, "acm@mail.com", None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        repo.check_existing_company_email(&mut uow, "acm@mail.com", Some(&other))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company_email(&mut uow, "acm@mail.com", Some(&acme.id))
+            .await
+            .unwrap()
+    );
+    // equality, not ILIKE
+    assert!(
+        !repo
+            .check_existing_company_email(&mut uow, "ACM@mail.com", None)
+            .await
+            .unwrap()
+    );
+
+    assert!(
+        repo.check_existing_company_code(&mut uow, "ACM", None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company_code(&mut uow, "ACM", Some(&acme.id))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company_code(&mut uow, "acm", None)
+            .await
+            .unwrap()
+    );
+}
+
+async fn enforces_unique_constraints<R: CompanyRepository>(repo: &R) {
+    let acme = company("ACM", "Acme", 0);
+    let globex = company("GLB", "Globex", 1);
+    seed(repo, vec![acme.clone(), globex.clone()]).await;
+
+    let violated = |err: sqlx::Error| {
+        err.as_database_error()
+            .and_then(|e| e.constraint().map(String::from))
+    };
+
+    let mut same_email = company("NEW", "New", 2);
+    same_email.email = acme.email.clone();
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.create_company(&mut uow, same_email).await.unwrap_err();
+    assert_eq!(violated(err).as_deref(), Some("companies_email_key"));
+    uow.rollback().await.unwrap();
+
+    let mut same_code = globex.clone();
+    same_code.code = acme.code.clone();
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.update_company(&mut uow, same_code).await.unwrap_err();
+    assert_eq!(violated(err).as_deref(), Some("companies_code_key"));
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .update_company(&mut uow, company("GHO", "Ghost", 3))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx::Error::RowNotFound));
+}
+
+async fn searches_sorts_and_pages<R: CompanyRepository>(repo: &R) {
+    seed(
+        repo,
+        vec![
+            company("ACM", "Acme Corp", 0),
+            company("GLB", "Globex", 1),
+            company("INI", "Initech Acme", 2),
+            company("UMB", "Umbrella", 3),
+            company("XAC", "Xanadu", 4),
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+
+    // name or code, case insensitive
+    let search = query(Some("aC"), Some("code"), 10, 0);
+    assert_eq!(
+        repo.count_all_companies(&mut uow, &search).await.unwrap(),
+        3
+    );
+    let found = repo.find_all_companies(&mut uow, &search).await.unwrap();
+    assert_eq!(codes(&found), vec!["ACM", "INI", "XAC"]);
+
+    let found = repo
+        .find_all_companies(&mut uow, &query(None, Some("-created_at"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["UMB", "INI"]);
+
+    // sort keys apply in order, NULL address sorts last ascending
+    let found = repo
+        .find_all_companies(&mut uow, &query(None, Some("-name"), 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["XAC", "UMB", "INI", "GLB", "ACM"]);
+
+    assert_eq!(
+        repo.count_all_companies(&mut uow, &query(Some("zzz"), None, 10, 0))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        repo.count_all_companies(&mut uow, &query(None, None, 1, 0))
+            .await
+            .unwrap(),
+        5
+    );
+
+    // LIKE wildcards in the search are not escaped
+    let found = repo
+        .find_all_companies(&mut uow, &query(Some("G_B"), None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["GLB"]);
+}
+
+async fn streams_without_page_window<R: CompanyRepository>(repo: &R) {
+    seed(
+        repo,
+        vec![
+            company("ACM", "Acme", 0),
+            company("GLB", "Globex", 1),
+            company("ACX", "Acx", 2),
+        ],
+    )
+    .await;
+
+    let streamed: Vec<Company> = repo
+        .stream_all_companies(query(Some("ac"), Some("-code"), 1, 1))
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(codes(&streamed), vec!["ACX", "ACM"]);
+}
+
+async fn updates_and_deletes<R: CompanyRepository>(repo: &R) {
+    let acme = company("ACM", "Acme", 0);
+    let globex = company("GLB", "Globex", 1);
+    seed(repo, vec![acme.clone(), globex.clone()]).await;
+
+    let mut changed = acme.clone();
+    changed.name = "Acme Renamed".into();
+    changed.phone_number = Some("0811".into());
+
+    let mut uow = repo.begin().await.unwrap();
+    repo.update_company(&mut uow, changed.clone())
+        .await
+        .unwrap();
+    repo.delete_company(&mut uow, &globex.id).await.unwrap();
+    // deleting a missing row is not an error
+    repo.delete_company(&mut uow, &Uuid::new_v4())
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_company_by_id(&mut uow, &acme.id).await.unwrap(),
+        Some(changed)
+    );
+    assert_eq!(
+        repo.get_company_by_id(&mut uow, &globex.id).await.unwrap(),
+        None
+    );
+}
+
+async fn rolls_back_uncommitted_work<R: CompanyRepository>(repo: &R) {
+    let acme = company("ACM", "Acme", 0);
+    seed(repo, vec![acme.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    repo.create_company(&mut uow, company("GLB", "Globex", 1))
+        .await
+        .unwrap();
+    repo.delete_company(&mut uow, &acme.id).await.unwrap();
+    assert_eq!(
+        repo.count_all_companies(&mut uow, &query(None, None, 1, 0))
+            .await
+            .unwrap(),
+        1
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    repo.create_company(&mut uow, company("INI", "Initech", 2))
+        .await
+        .unwrap();
+    drop(uow);
+
+    let mut uow = repo.begin().await.unwrap();
+    let all = repo
+        .find_all_companies(&mut uow, &query(None, None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(all, vec![acme]);
+}
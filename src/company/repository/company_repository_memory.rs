@@ -0,0 +1,267 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use uuid::Uuid;
+
+use crate::app_database::memory::{MemoryStore, MemoryUnitOfWork, ilike, unique_violation};
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::company::repository::helper_query::COMPANY_SORT_COLUMNS;
+
+// mirrors CompanyRepositorySqlx on top of a vector: ILIKE search on name and
+// code, ORDER BY the requested columns (strings compare bytewise like the C
+// collation, NULLs last ascending and first descending), LIMIT/OFFSET and the
+// unique constraints of the companies table
+#[derive(Clone, Default)]
+pub struct CompanyRepositoryMemory {
+    store: MemoryStore<Vec<Company>>,
+}
+
+impl CompanyRepositoryMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_companies(companies: Vec<Company>) -> Self {
+        Self {
+            store: MemoryStore::new(companies),
+        }
+    }
+
+    // committed rows, in insertion order
+    pub async fn companies(&self) -> Vec<Company> {
+        self.store.snapshot().await
+    }
+}
+
+#[async_trait]
+impl Transactional for CompanyRepositoryMemory {
+    type Uow = MemoryUnitOfWork<Vec<Company>>;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        Ok(self.store.begin().await)
+    }
+}
+
+#[async_trait]
+impl CompanyRepository for CompanyRepositoryMemory {
+    async fn get_company_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Company>, sqlx::Error> {
+        Ok(uow.state().iter().find(|c| c.id == *id).cloned())
+    }
+
+    async fn count_all_companies(
+        &self,
+        uow: &mut Self::Uow,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(search_companies(uow.state(), &query.search).len() as i64)
+    }
+
+    async fn find_all_companies(
+        &self,
+        uow: &mut Self::Uow,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Company>, sqlx::Error> {
+        let companies = sorted_companies(uow.state(), query)?;
+
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query.per_page.unwrap_or(1) as usize;
+        Ok(companies.into_iter().skip(offset).take(limit).collect())
+    }
+
+    fn stream_all_companies(
+        &self,
+        query: PaginationRequest,
+    ) -> BoxStream<'static, Result<Company, sqlx::Error>> {
+        let store = self.store.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let companies = sorted_companies(&store.snapshot().await, &query)?;
+            for company in companies {
+                yield company;
+            }
+        })
+    }
+
+    async fn check_existing_company_email(
+        &self,
+        uow: &mut Self::Uow,
+        email: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .iter()
+            .any(|c| c.email == email && Some(&c.id) != id))
+    }
+
+    async fn check_existing_company_code(
+        &self,
+        uow: &mut Self::Uow,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .iter()
+            .any(|c| c.code == code && Some(&c.id) != id))
+    }
+
+    async fn create_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
+        check_constraints(uow.state(), &company, true)?;
+        uow.state_mut().push(company.clone());
+
+        Ok(company)
+    }
+
+    async fn update_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
+        // UPDATE .. RETURNING fetched with fetch_one fails when no row matched
+        if !uow.state().iter().any(|c| c.id == company.id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_constraints(uow.state(), &company, false)?;
+
+        let existing = uow
+            .state_mut()
+            .iter_mut()
+            .find(|c| c.id == company.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        existing.name = company.name.clone();
+        existing.email = company.email.clone();
+        existing.code = company.code.clone();
+        existing.phone_number = company.phone_number.clone();
+        existing.address = company.address.clone();
+
+        Ok(company)
+    }
+
+    async fn delete_company(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        uow.state_mut().retain(|c| c.id != *id);
+
+        Ok(())
+    }
+}
+
+fn check_constraints(
+    companies: &[Company],
+    company: &Company,
+    is_insert: bool,
+) -> Result<(), sqlx::Error> {
+    if is_insert && companies.iter().any(|c| c.id == company.id) {
+        return Err(unique_violation("companies_pkey"));
+    }
+    for other in companies.iter().filter(|c| c.id != company.id) {
+        if other.email == company.email {
+            return Err(unique_violation("companies_email_key"));
+        }
+        if other.code == company.code {
+            return Err(unique_violation("companies_code_key"));
+        }
+    }
+    Ok(())
+}
+
+// WHERE (name ILIKE %s% OR code ILIKE %s%), see helper_query::apply_search_filter
+fn search_companies<'a>(companies: &'a [Company], search: &Option<String>) -> Vec<&'a Company> {
+    match search {
+        Some(s) => {
+            let pattern = format!("%{s}%");
+            companies
+                .iter()
+                .filter(|c| ilike(&c.name, &pattern) || ilike(&c.code, &pattern))
+                .collect()
+        }
+        None => companies.iter().collect(),
+    }
+}
+
+fn sorted_companies(
+    companies: &[Company],
+    query: &PaginationRequest,
+) -> Result<Vec<Company>, sqlx::Error> {
+    let keys = sort_keys(query)?;
+    let mut companies: Vec<Company> = search_companies(companies, &query.search)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    // stable sort, rows equal on every key keep insertion order
+    companies.sort_by(|a, b| {
+        keys.iter()
+            .map(|(column, descending)| {
+                let ordering = compare_column(a, b, column);
+                if *descending { ordering.reverse() } else { ordering }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    Ok(companies)
+}
+
+// same parsing as PaginationRequest::format_sort, unknown columns fail like postgres does
+fn sort_keys(query: &PaginationRequest) -> Result<Vec<(String, bool)>, sqlx::Error> {
+    let Some(sort) = &query.sort else {
+        return Ok(Vec::new());
+    };
+
+    let mut keys = Vec::new();
+    for s in sort.split(",") {
+        let s = s.trim();
+        if s.is_empty() {
+            continue;
+        }
+
+        let (column, descending) = match s.strip_prefix("-") {
+            Some(column) => (column, true),
+            None => (s, false),
+        };
+        if !is_company_column(column) {
+            return Err(sqlx::Error::ColumnNotFound(column.to_string()));
+        }
+        keys.push((column.to_string(), descending));
+    }
+    Ok(keys)
+}
+
+fn is_company_column(column: &str) -> bool {
+    COMPANY_SORT_COLUMNS.contains(&column)
+}
+
+fn compare_column(a: &Company, b: &Company, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "name" => a.name.cmp(&b.name),
+        "email" => a.email.cmp(&b.email),
+        "code" => a.code.cmp(&b.code),
+        "phone_number" => compare_nullable(&a.phone_number, &b.phone_number),
+        "address" => compare_nullable(&a.address, &b.address),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        _ => Ordering::Equal,
+    }
+}
+
+// postgres puts NULL after every value when sorting ascending
+fn compare_nullable(a: &Option<String>, b: &Option<String>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -1,8 +1,11 @@
 use async_trait::async_trait;
+use futures::TryStreamExt;
+use futures::stream::BoxStream;
 use sqlx::{PgPool, QueryBuilder};
-// use tracing::{debug, info};
+use tracing::instrument;
 use uuid::Uuid;
 
+use crate::app_database::unit_of_work::{PgUnitOfWork, Transactional};
 use crate::company::domain::company::Company;
 use crate::company::repository::company_repository::CompanyRepository;
 use crate::company::repository::helper_query::apply_search_filter;
@@ -18,9 +21,24 @@ impl CompanyRepositorySqlx {
     }
 }
 
+#[async_trait]
+impl Transactional for CompanyRepositorySqlx {
+    type Uow = PgUnitOfWork;
+
+    #[instrument(level = "debug", skip_all)]
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.pool.begin().await
+    }
+}
+
 #[async_trait]
 impl CompanyRepository for CompanyRepositorySqlx {
-    async fn get_company_by_id(&self, id: &Uuid) -> Result<Option<Company>, sqlx::Error> {
+    #[instrument(level = "debug", skip_all, fields(company_id = %id))]
+    async fn get_company_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Company>, sqlx::Error> {
         let company = sqlx::query_as!(
             Company,
             r#"
@@ -30,14 +48,16 @@ impl CompanyRepository for CompanyRepositorySqlx {
             "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut **uow)
         .await?;
 
-        Ok(Some(company))
+        Ok(company)
     }
 
+    #[instrument(level = "debug", skip_all)]
     async fn check_existing_company_email(
         &self,
+        uow: &mut Self::Uow,
         email: &str,
         id: Option<&Uuid>,
     ) -> Result<bool, sqlx::Error> {
@@ -54,7 +74,7 @@ impl CompanyRepository for CompanyRepositorySqlx {
                     email,
                     id
                 )
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **uow)
                 .await?
             }
             None => {
@@ -68,7 +88,7 @@ impl CompanyRepository for CompanyRepositorySqlx {
                     "#,
                     email
                 )
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **uow)
                 .await?
             }
         };
@@ -76,8 +96,10 @@ impl CompanyRepository for CompanyRepositorySqlx {
         Ok(is_exist.unwrap_or(false))
     }
 
+    #[instrument(level = "debug", skip_all, fields(code = %code))]
     async fn check_existing_company_code(
         &self,
+        uow: &mut Self::Uow,
         code: &str,
         id: Option<&Uuid>,
     ) -> Result<bool, sqlx::Error> {
@@ -94,7 +116,7 @@ impl CompanyRepository for CompanyRepositorySqlx {
                     code,
                     id,
                 )
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **uow)
                 .await?
             }
             None => {
@@ -108,7 +130,7 @@ impl CompanyRepository for CompanyRepositorySqlx {
                     "#,
                     code,
                 )
-                .fetch_one(&self.pool)
+                .fetch_one(&mut **uow)
                 .await?
             }
         };
@@ -116,7 +138,12 @@ impl CompanyRepository for CompanyRepositorySqlx {
         Ok(is_exist.unwrap_or(false))
     }
 
-    async fn create_company(&self, company: Company) -> Result<Company, sqlx::Error> {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company.id))]
+    async fn create_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
         sqlx::query!(
             r#"
             INSERT INTO companies
@@ -132,13 +159,18 @@ impl CompanyRepository for CompanyRepositorySqlx {
             company.address,
             company.created_at,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut **uow)
         .await?;
 
         Ok(company)
     }
 
-    async fn update_company(&self, company: Company) -> Result<Company, sqlx::Error> {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company.id))]
+    async fn update_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
         sqlx::query!(
             r#"
             UPDATE companies
@@ -157,21 +189,27 @@ impl CompanyRepository for CompanyRepositorySqlx {
             company.address,
             company.id,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut **uow)
         .await?;
 
         Ok(company)
     }
 
-    async fn delete_company(&self, id: &Uuid) -> Result<(), sqlx::Error> {
+    #[instrument(level = "debug", skip_all, fields(company_id = %id))]
+    async fn delete_company(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
         sqlx::query!(r#"DELETE FROM companies WHERE id = $1"#, id,)
-            .execute(&self.pool)
+            .execute(&mut **uow)
             .await?;
 
         Ok(())
     }
 
-    async fn count_all_companies(&self, query: &PaginationRequest) -> Result<i64, sqlx::Error> {
+    #[instrument(level = "debug", skip_all)]
+    async fn count_all_companies(
+        &self,
+        uow: &mut Self::Uow,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
         let mut qb = QueryBuilder::new("SELECT COUNT(id)FROM companies");
 
         apply_search_filter(&mut qb, &query.search);
@@ -185,12 +223,18 @@ impl CompanyRepository for CompanyRepositorySqlx {
         //         .push(" ) ");
         // }
 
-        let total: i64 = qb.build_query_scalar().fetch_one(&self.pool).await?;
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
         Ok(total)
     }
 
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(per_page = ?query.per_page, offset = ?query.offset)
+    )]
     async fn find_all_companies(
         &self,
+        uow: &mut Self::Uow,
         query: &PaginationRequest,
     ) -> Result<Vec<Company>, sqlx::Error> {
         let mut qb = QueryBuilder::new(
@@ -212,7 +256,7 @@ impl CompanyRepository for CompanyRepositorySqlx {
         // }
 
         if let Some(s) = query.format_sort() {
-            qb.push(" ORDER BY ").push(format!("{}", s));
+            qb.push(" ORDER BY ").push(s);
         }
 
         qb.push(" LIMIT ")
@@ -222,7 +266,34 @@ impl CompanyRepository for CompanyRepositorySqlx {
             qb.push(" OFFSET ").push_bind(o as i64); // FOR LIMIT MUST i64
         }
 
-        let companies = qb.build_query_as::<Company>().fetch_all(&self.pool).await?;
+        let companies = qb.build_query_as::<Company>().fetch_all(&mut **uow).await?;
         Ok(companies)
     }
+
+    fn stream_all_companies(
+        &self,
+        query: PaginationRequest,
+    ) -> BoxStream<'static, Result<Company, sqlx::Error>> {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut qb = QueryBuilder::new(
+                "
+                SELECT id, name, email, code, phone_number, address, created_at
+                FROM companies
+            ",
+            );
+
+            apply_search_filter(&mut qb, &query.search);
+
+            if let Some(s) = query.format_sort() {
+                qb.push(" ORDER BY ").push(s);
+            }
+
+            let mut rows = qb.build_query_as::<Company>().fetch(&pool);
+            while let Some(company) = rows.try_next().await? {
+                yield company;
+            }
+        })
+    }
 }
@@ -1,5 +1,17 @@
 use sqlx::{Postgres, QueryBuilder};
 
+// sortable columns of companies, sort is pushed into ORDER BY as written so the
+// handler rejects anything else
+pub const COMPANY_SORT_COLUMNS: [&str; 7] = [
+    "id",
+    "name",
+    "email",
+    "code",
+    "phone_number",
+    "address",
+    "created_at",
+];
+
 pub fn apply_search_filter(
     qb: &mut QueryBuilder<Postgres>,
     search: &Option<String>,
@@ -1,3 +1,7 @@
 pub mod company_repository;
 pub mod company_repository_sqlx;
-pub mod helper_query;
\ No newline at end of file
+#[cfg(any(test, feature = "testing"))]
+pub mod company_repository_memory;
+#[cfg(test)]
+mod company_repository_contract_test;
+pub mod helper_query;
@@ -1,27 +1,67 @@
+use core::fmt;
+
 use chrono::Utc;
+use futures::StreamExt;
+use futures::TryStreamExt;
+use futures::stream::BoxStream;
+use tracing::instrument;
 use uuid::Uuid;
 
+use crate::app_database::unit_of_work::{UnitOfWork, finish};
+use crate::app_metrics::metrics::AppMetrics;
 use crate::app_request::pagination::PaginationRequest;
 use crate::company::domain::company::Company;
 use crate::company::repository::company_repository::CompanyRepository;
-use crate::company::usecase::dto::ListCompanyResult;
+use crate::company::usecase::dto::{
+    BulkCompanyError, BulkCompanyOperation, BulkCompanyOutcome, CompanyInput, ListCompanyResult,
+};
+
+// names of the unique constraints on companies, see migrations
+const COMPANY_EMAIL_CONSTRAINT: &str = "companies_email_key";
+const COMPANY_CODE_CONSTRAINT: &str = "companies_code_key";
 
 pub struct CompanyUsecase<R: CompanyRepository> {
     repo: R,
+    metrics: AppMetrics,
 }
 
+#[derive(Debug)]
 pub enum CompanyUsecaseError {
     EmailAlreadyExist,
     CodeAlreadyExist,
     NotFound,
-    DatabaseError,
+    // other rows, like products, still point at the company
+    InUse,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for CompanyUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompanyUsecaseError::EmailAlreadyExist => write!(f, "company email already exists"),
+            CompanyUsecaseError::CodeAlreadyExist => write!(f, "company code already exists"),
+            CompanyUsecaseError::NotFound => write!(f, "company not found"),
+            CompanyUsecaseError::InUse => write!(f, "company is still in use"),
+            CompanyUsecaseError::DatabaseError(_) => write!(f, "company storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for CompanyUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CompanyUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 impl<R: CompanyRepository> CompanyUsecase<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
     }
 
+    #[instrument(skip_all, fields(code = %code))]
     pub async fn create_company(
         &self,
         name: String,
@@ -30,40 +70,23 @@ impl<R: CompanyRepository> CompanyUsecase<R> {
         phone_number: Option<String>,
         address: Option<String>,
     ) -> Result<Company, CompanyUsecaseError> {
-        let is_company_email_exist = self
-            .repo
-            .check_existing_company_email(&email, None)
-            .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-        if is_company_email_exist {
-            return Err(CompanyUsecaseError::EmailAlreadyExist);
-        }
-
-        let is_company_code_exist = self
-            .repo
-            .check_existing_company_code(&code, None)
-            .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-        if is_company_code_exist {
-            return Err(CompanyUsecaseError::CodeAlreadyExist);
-        }
-
-        let company = Company {
-            id: Uuid::new_v4(),
+        let input = CompanyInput {
             name,
             email,
             code,
             phone_number,
             address,
-            created_at: Utc::now(),
         };
 
-        self.repo
-            .create_company(company)
-            .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)
+        let mut uow = self.begin().await?;
+        let result = self.create_company_in(&mut uow, input).await;
+        let company = finish(uow, result, CompanyUsecaseError::DatabaseError).await?;
+
+        self.metrics.company_operation("create");
+        Ok(company)
     }
 
+    #[instrument(skip_all, fields(company_id = %id, code = %code))]
     pub async fn update_company(
         &self,
         id: Uuid,
@@ -73,83 +96,280 @@ impl<R: CompanyRepository> CompanyUsecase<R> {
         phone_number: Option<String>,
         address: Option<String>,
     ) -> Result<Company, CompanyUsecaseError> {
-        let get_company = self
+        let input = CompanyInput {
+            name,
+            email,
+            code,
+            phone_number,
+            address,
+        };
+
+        let mut uow = self.begin().await?;
+        let result = self.update_company_in(&mut uow, id, input).await;
+        let company = finish(uow, result, CompanyUsecaseError::DatabaseError).await?;
+
+        self.metrics.company_operation("update");
+        Ok(company)
+    }
+
+    #[instrument(skip_all, fields(company_id = %id))]
+    pub async fn delete_company(&self, id: Uuid) -> Result<(), CompanyUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_company_in(&mut uow, id).await;
+        finish(uow, result, CompanyUsecaseError::DatabaseError).await?;
+
+        self.metrics.company_operation("delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_company(
+        &self,
+        query: &PaginationRequest,
+    ) -> Result<ListCompanyResult, CompanyUsecaseError> {
+        let mut uow = self.begin().await?;
+
+        let total_company = self
             .repo
-            .get_company_by_id(&id)
+            .count_all_companies(&mut uow, query)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-        if get_company.is_none() {
-            return Err(CompanyUsecaseError::NotFound);
-        }
+            .map_err(CompanyUsecaseError::DatabaseError)?;
 
-        let is_company_email_exist = self
+        // let total_page: u32 = (total_company as f64 / (query.per_page.unwrap_or(1) as f64)).ceil() as u32;
+
+        let companies = self
             .repo
-            .check_existing_company_email(&email, Some(&id))
+            .find_all_companies(&mut uow, query)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-        if is_company_email_exist {
-            return Err(CompanyUsecaseError::EmailAlreadyExist);
+            .map_err(CompanyUsecaseError::DatabaseError)?;
+
+        let result = Ok(ListCompanyResult {
+            data: companies,
+            total_data: total_company,
+        });
+        finish(uow, result, CompanyUsecaseError::DatabaseError).await
+    }
+
+    pub fn export_company(
+        &self,
+        query: PaginationRequest,
+    ) -> BoxStream<'static, Result<Company, CompanyUsecaseError>> {
+        self.repo
+            .stream_all_companies(query)
+            .map_err(CompanyUsecaseError::DatabaseError)
+            .boxed()
+    }
+
+    // every operation runs in its own unit of work, one failure does not stop the rest
+    #[instrument(skip_all, fields(operations = operations.len()))]
+    pub async fn bulk_company(
+        &self,
+        operations: Vec<BulkCompanyOperation>,
+    ) -> Vec<Result<BulkCompanyOutcome, CompanyUsecaseError>> {
+        let mut results = Vec::with_capacity(operations.len());
+
+        for operation in operations {
+            let result = match self.begin().await {
+                Ok(mut uow) => {
+                    let result = self.apply_bulk_operation(&mut uow, operation).await;
+                    finish(uow, result, CompanyUsecaseError::DatabaseError).await
+                }
+                Err(err) => Err(err),
+            };
+            if let Ok(outcome) = &result {
+                self.record_outcome(outcome);
+            }
+            results.push(result);
         }
 
-        let is_company_code_exist = self
-            .repo
-            .check_existing_company_code(&code, Some(&id))
+        results
+    }
+
+    // the whole batch shares one unit of work, the first failure rolls everything back
+    #[instrument(skip_all, fields(operations = operations.len()))]
+    pub async fn bulk_company_atomic(
+        &self,
+        operations: Vec<BulkCompanyOperation>,
+    ) -> Result<Vec<BulkCompanyOutcome>, BulkCompanyError> {
+        let mut uow = self.begin().await.map_err(|error| BulkCompanyError {
+            index: None,
+            error,
+        })?;
+
+        let mut outcomes = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.into_iter().enumerate() {
+            match self.apply_bulk_operation(&mut uow, operation).await {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(error) => {
+                    let _ = uow.rollback().await;
+                    return Err(BulkCompanyError {
+                        index: Some(index),
+                        error,
+                    });
+                }
+            }
+        }
+
+        uow.commit().await.map_err(|err| BulkCompanyError {
+            index: None,
+            error: CompanyUsecaseError::DatabaseError(err),
+        })?;
+
+        outcomes.iter().for_each(|outcome| self.record_outcome(outcome));
+        Ok(outcomes)
+    }
+
+    // only committed writes are counted
+    fn record_outcome(&self, outcome: &BulkCompanyOutcome) {
+        let operation = match outcome {
+            BulkCompanyOutcome::Created(_) => "create",
+            BulkCompanyOutcome::Updated(_) => "update",
+            BulkCompanyOutcome::Deleted(_) => "delete",
+        };
+        self.metrics.company_operation(operation);
+    }
+
+    async fn begin(&self) -> Result<R::Uow, CompanyUsecaseError> {
+        self.repo
+            .begin()
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-        if is_company_code_exist {
-            return Err(CompanyUsecaseError::CodeAlreadyExist);
+            .map_err(CompanyUsecaseError::DatabaseError)
+    }
+
+    async fn apply_bulk_operation(
+        &self,
+        uow: &mut R::Uow,
+        operation: BulkCompanyOperation,
+    ) -> Result<BulkCompanyOutcome, CompanyUsecaseError> {
+        match operation {
+            BulkCompanyOperation::Create(input) => self
+                .create_company_in(uow, input)
+                .await
+                .map(BulkCompanyOutcome::Created),
+            BulkCompanyOperation::Update(id, input) => self
+                .update_company_in(uow, id, input)
+                .await
+                .map(BulkCompanyOutcome::Updated),
+            BulkCompanyOperation::Delete(id) => self
+                .delete_company_in(uow, id)
+                .await
+                .map(|_| BulkCompanyOutcome::Deleted(id)),
         }
+    }
 
-        let mut company = get_company.unwrap();
-        company.name = name;
-        company.code = code;
-        company.email = email;
-        company.phone_number = phone_number;
-        company.address = address;
+    async fn create_company_in(
+        &self,
+        uow: &mut R::Uow,
+        input: CompanyInput,
+    ) -> Result<Company, CompanyUsecaseError> {
+        self.check_company_unique(uow, &input, None).await?;
+
+        let company = Company {
+            id: Uuid::new_v4(),
+            name: input.name,
+            email: input.email,
+            code: input.code,
+            phone_number: input.phone_number,
+            address: input.address,
+            created_at: Utc::now(),
+        };
 
         self.repo
-            .update_company(company)
+            .create_company(uow, company)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)
+            .map_err(map_company_write_error)
     }
 
-    pub async fn delete_company(&self, id: Uuid) -> Result<(), CompanyUsecaseError> {
+    async fn update_company_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: CompanyInput,
+    ) -> Result<Company, CompanyUsecaseError> {
+        let mut company = self
+            .repo
+            .get_company_by_id(uow, &id)
+            .await
+            .map_err(CompanyUsecaseError::DatabaseError)?
+            .ok_or(CompanyUsecaseError::NotFound)?;
+
+        self.check_company_unique(uow, &input, Some(&id)).await?;
+
+        company.name = input.name;
+        company.code = input.code;
+        company.email = input.email;
+        company.phone_number = input.phone_number;
+        company.address = input.address;
+
+        self.repo
+            .update_company(uow, company)
+            .await
+            .map_err(map_company_write_error)
+    }
+
+    async fn delete_company_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), CompanyUsecaseError> {
         let get_company = self
             .repo
-            .get_company_by_id(&id)
+            .get_company_by_id(uow, &id)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
+            .map_err(CompanyUsecaseError::DatabaseError)?;
 
         if get_company.is_none() {
             return Err(CompanyUsecaseError::NotFound);
         }
         self.repo
-            .delete_company(&id)
+            .delete_company(uow, &id)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)
+            .map_err(map_company_write_error)
     }
 
-    pub async fn list_company(
+    async fn check_company_unique(
         &self,
-        query: &PaginationRequest,
-    ) -> Result<ListCompanyResult, CompanyUsecaseError> {
-        let total_company = self
+        uow: &mut R::Uow,
+        input: &CompanyInput,
+        id: Option<&Uuid>,
+    ) -> Result<(), CompanyUsecaseError> {
+        let is_company_email_exist = self
             .repo
-            .count_all_companies(&query)
+            .check_existing_company_email(uow, &input.email, id)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
-
-        // let total_page: u32 = (total_company as f64 / (query.per_page.unwrap_or(1) as f64)).ceil() as u32;
+            .map_err(CompanyUsecaseError::DatabaseError)?;
+        if is_company_email_exist {
+            return Err(CompanyUsecaseError::EmailAlreadyExist);
+        }
 
-        let companies = self
+        let is_company_code_exist = self
             .repo
-            .find_all_companies(&query)
+            .check_existing_company_code(uow, &input.code, id)
             .await
-            .map_err(|_| CompanyUsecaseError::DatabaseError)?;
+            .map_err(CompanyUsecaseError::DatabaseError)?;
+        if is_company_code_exist {
+            return Err(CompanyUsecaseError::CodeAlreadyExist);
+        }
 
-        Ok(ListCompanyResult {
-            data: companies,
-            total_data: total_company,
-        })
+        Ok(())
+    }
+}
+
+// a concurrent unit of work can still claim the same email or code between the
+// check and the write, the unique constraints catch that case. deleting a
+// company other tables point at fails on their foreign keys
+fn map_company_write_error(err: sqlx::Error) -> CompanyUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    let is_referenced = err
+        .as_database_error()
+        .is_some_and(|e| e.is_foreign_key_violation());
+    match constraint.as_deref() {
+        Some(COMPANY_EMAIL_CONSTRAINT) => CompanyUsecaseError::EmailAlreadyExist,
+        Some(COMPANY_CODE_CONSTRAINT) => CompanyUsecaseError::CodeAlreadyExist,
+        _ if is_referenced => CompanyUsecaseError::InUse,
+        _ => CompanyUsecaseError::DatabaseError(err),
     }
 }
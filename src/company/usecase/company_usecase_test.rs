@@ -0,0 +1,536 @@
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use futures::TryStreamExt;
+use futures::stream::BoxStream;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository::CompanyRepository;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::usecase::company_usecase::{CompanyUsecase, CompanyUsecaseError};
+use crate::company::usecase::dto::{BulkCompanyOperation, BulkCompanyOutcome, CompanyInput};
+
+fn company(code: &str, email: &str) -> Company {
+    Company {
+        id: Uuid::new_v4(),
+        name: format!("Company {code}"),
+        email: email.into(),
+        code: code.into(),
+        phone_number: Some("0811".into()),
+        address: Some("Jakarta".into()),
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+fn input(code: &str, email: &str) -> CompanyInput {
+    CompanyInput {
+        name: format!("Company {code}"),
+        email: email.into(),
+        code: code.into(),
+        phone_number: Some("0811".into()),
+        address: Some("Jakarta".into()),
+    }
+}
+
+fn query(search: Option<&str>, sort: Option<&str>, page: u32, per_page: u32) -> PaginationRequest {
+    PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+// the usecase owns its repository, the clone shares the same store so the test
+// can look at what was committed
+fn setup(
+    companies: Vec<Company>,
+) -> (
+    CompanyUsecase<CompanyRepositoryMemory>,
+    CompanyRepositoryMemory,
+) {
+    let repo = CompanyRepositoryMemory::with_companies(companies);
+    (CompanyUsecase::new(repo.clone(), AppMetrics::new()), repo)
+}
+
+async fn create(
+    usecase: &CompanyUsecase<impl CompanyRepository>,
+    input: CompanyInput,
+) -> Result<Company, CompanyUsecaseError> {
+    usecase
+        .create_company(
+            input.name,
+            input.email,
+            input.code,
+            input.phone_number,
+            input.address,
+        )
+        .await
+}
+
+async fn update(
+    usecase: &CompanyUsecase<impl CompanyRepository>,
+    id: Uuid,
+    input: CompanyInput,
+) -> Result<Company, CompanyUsecaseError> {
+    usecase
+        .update_company(
+            id,
+            input.name,
+            input.email,
+            input.code,
+            input.phone_number,
+            input.address,
+        )
+        .await
+}
+
+#[tokio::test]
+async fn create_company_stores_company() {
+    let (usecase, repo) = setup(vec![]);
+
+    let created = create(&usecase, input("ACM", "acme@mail.com"))
+        .await
+        .unwrap();
+
+    let stored = repo.companies().await;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, created.id);
+    assert_eq!(stored[0].code, "ACM");
+}
+
+#[tokio::test]
+async fn create_company_rejects_existing_email() {
+    let (usecase, repo) = setup(vec![company("ACM", "acme@mail.com")]);
+
+    let result = create(&usecase, input("NEW", "acme@mail.com")).await;
+
+    assert!(matches!(
+        result,
+        Err(CompanyUsecaseError::EmailAlreadyExist)
+    ));
+    assert_eq!(repo.companies().await.len(), 1);
+}
+
+#[tokio::test]
+async fn create_company_rejects_existing_code() {
+    let (usecase, repo) = setup(vec![company("ACM", "acme@mail.com")]);
+
+    let result = create(&usecase, input("ACM", "new@mail.com")).await;
+
+    assert!(matches!(result, Err(CompanyUsecaseError::CodeAlreadyExist)));
+    assert_eq!(repo.companies().await.len(), 1);
+}
+
+#[tokio::test]
+async fn create_company_maps_unique_violation_of_a_concurrent_write() {
+    // the checks pass, then the write hits the unique constraint
+    let repo = FaultyCompanyRepository::new(vec![company("ACM", "acme@mail.com")], None);
+    let usecase = CompanyUsecase::new(repo.skip_unique_checks(), AppMetrics::new());
+
+    let result = create(&usecase, input("ACM", "new@mail.com")).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::CodeAlreadyExist)));
+
+    let result = create(&usecase, input("NEW", "acme@mail.com")).await;
+    assert!(matches!(
+        result,
+        Err(CompanyUsecaseError::EmailAlreadyExist)
+    ));
+}
+
+#[tokio::test]
+async fn update_company_changes_fields() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone()]);
+
+    let updated = update(&usecase, existing.id, input("ACX", "acx@mail.com"))
+        .await
+        .unwrap();
+
+    assert_eq!(updated.id, existing.id);
+    assert_eq!(updated.created_at, existing.created_at);
+    let stored = repo.companies().await;
+    assert_eq!(stored[0].code, "ACX");
+    assert_eq!(stored[0].email, "acx@mail.com");
+}
+
+#[tokio::test]
+async fn update_company_keeps_its_own_email_and_code() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, _) = setup(vec![existing.clone()]);
+
+    let result = update(&usecase, existing.id, input("ACM", "acme@mail.com")).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn update_company_not_found() {
+    let (usecase, _) = setup(vec![company("ACM", "acme@mail.com")]);
+
+    let result = update(&usecase, Uuid::new_v4(), input("NEW", "new@mail.com")).await;
+
+    assert!(matches!(result, Err(CompanyUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn update_company_rejects_email_of_another_company() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone(), company("GLB", "globex@mail.com")]);
+
+    let result = update(&usecase, existing.id, input("ACM", "globex@mail.com")).await;
+
+    assert!(matches!(
+        result,
+        Err(CompanyUsecaseError::EmailAlreadyExist)
+    ));
+    assert_eq!(repo.companies().await[0].email, "acme@mail.com");
+}
+
+#[tokio::test]
+async fn update_company_rejects_code_of_another_company() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone(), company("GLB", "globex@mail.com")]);
+
+    let result = update(&usecase, existing.id, input("GLB", "acme@mail.com")).await;
+
+    assert!(matches!(result, Err(CompanyUsecaseError::CodeAlreadyExist)));
+    assert_eq!(repo.companies().await[0].code, "ACM");
+}
+
+#[tokio::test]
+async fn delete_company_removes_company() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone(), company("GLB", "globex@mail.com")]);
+
+    usecase.delete_company(existing.id).await.unwrap();
+
+    let stored = repo.companies().await;
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].code, "GLB");
+}
+
+#[tokio::test]
+async fn delete_company_not_found() {
+    let (usecase, _) = setup(vec![company("ACM", "acme@mail.com")]);
+
+    let result = usecase.delete_company(Uuid::new_v4()).await;
+
+    assert!(matches!(result, Err(CompanyUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn list_company_searches_sorts_and_pages() {
+    let (usecase, _) = setup(vec![
+        company("ACM", "acme@mail.com"),
+        company("GLB", "globex@mail.com"),
+        company("ACX", "acx@mail.com"),
+        company("INI", "initech@mail.com"),
+    ]);
+
+    let result = usecase
+        .list_company(&query(Some("ac"), Some("-code"), 1, 1))
+        .await
+        .unwrap();
+
+    assert_eq!(result.total_data, 2);
+    assert_eq!(result.data.len(), 1);
+    assert_eq!(result.data[0].code, "ACX");
+
+    let result = usecase
+        .list_company(&query(Some("ac"), Some("-code"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(result.data[0].code, "ACM");
+}
+
+#[tokio::test]
+async fn export_company_streams_every_matching_company() {
+    let (usecase, _) = setup(vec![
+        company("ACM", "acme@mail.com"),
+        company("GLB", "globex@mail.com"),
+        company("ACX", "acx@mail.com"),
+    ]);
+
+    let exported: Vec<Company> = usecase
+        .export_company(query(Some("ac"), Some("code"), 1, 1))
+        .try_collect()
+        .await
+        .unwrap();
+
+    let codes: Vec<&str> = exported.iter().map(|c| c.code.as_str()).collect();
+    assert_eq!(codes, vec!["ACM", "ACX"]);
+}
+
+#[tokio::test]
+async fn usecases_report_database_errors() {
+    let existing = company("ACM", "acme@mail.com");
+
+    for fail_on in [FailOn::Begin, FailOn::Read] {
+        let usecase = CompanyUsecase::new(
+            FaultyCompanyRepository::new(vec![existing.clone()], Some(fail_on)),
+            AppMetrics::new(),
+        );
+
+        let result = create(&usecase, input("NEW", "new@mail.com")).await;
+        assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+        let result = update(&usecase, existing.id, input("NEW", "new@mail.com")).await;
+        assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+        let result = usecase.delete_company(existing.id).await;
+        assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+        let result = usecase.list_company(&query(None, None, 1, 10)).await;
+        assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+    }
+
+    let usecase = CompanyUsecase::new(
+        FaultyCompanyRepository::new(vec![existing.clone()], Some(FailOn::Write)),
+        AppMetrics::new(),
+    );
+    let result = create(&usecase, input("NEW", "new@mail.com")).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+    let result = update(&usecase, existing.id, input("NEW", "new@mail.com")).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+    let result = usecase.delete_company(existing.id).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+
+    let usecase = CompanyUsecase::new(
+        FaultyCompanyRepository::new(vec![existing], Some(FailOn::Read)),
+        AppMetrics::new(),
+    );
+    let result: Result<Vec<Company>, _> = usecase
+        .export_company(query(None, None, 1, 10))
+        .try_collect()
+        .await;
+    assert!(matches!(result, Err(CompanyUsecaseError::DatabaseError(_))));
+}
+
+#[tokio::test]
+async fn bulk_company_atomic_rolls_back_when_an_operation_is_rejected() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone()]);
+
+    let result = usecase
+        .bulk_company_atomic(vec![
+            BulkCompanyOperation::Create(input("NEW", "new@mail.com")),
+            BulkCompanyOperation::Delete(existing.id),
+            BulkCompanyOperation::Create(input("NEW", "other@mail.com")),
+        ])
+        .await;
+
+    let err = result.err().unwrap();
+    assert_eq!(err.index, Some(2));
+    assert!(matches!(err.error, CompanyUsecaseError::CodeAlreadyExist));
+    assert_eq!(repo.companies().await, vec![existing]);
+}
+
+#[tokio::test]
+async fn bulk_company_atomic_rolls_back_when_the_database_fails() {
+    let existing = company("ACM", "acme@mail.com");
+    let repo = FaultyCompanyRepository::new(vec![existing.clone()], Some(FailOn::Delete));
+    let inner = repo.inner.clone();
+    let usecase = CompanyUsecase::new(repo, AppMetrics::new());
+
+    let result = usecase
+        .bulk_company_atomic(vec![
+            BulkCompanyOperation::Create(input("NEW", "new@mail.com")),
+            BulkCompanyOperation::Update(existing.id, input("ACX", "acme@mail.com")),
+            BulkCompanyOperation::Delete(existing.id),
+        ])
+        .await;
+
+    let err = result.err().unwrap();
+    assert_eq!(err.index, Some(2));
+    assert!(matches!(err.error, CompanyUsecaseError::DatabaseError(_)));
+    assert_eq!(inner.companies().await, vec![existing]);
+}
+
+#[tokio::test]
+async fn bulk_company_atomic_sees_its_own_writes() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone()]);
+
+    // the code is free again once the first operation deleted its owner
+    let outcomes = usecase
+        .bulk_company_atomic(vec![
+            BulkCompanyOperation::Delete(existing.id),
+            BulkCompanyOperation::Create(input("ACM", "acme@mail.com")),
+        ])
+        .await
+        .ok()
+        .unwrap();
+
+    assert!(matches!(outcomes[0], BulkCompanyOutcome::Deleted(id) if id == existing.id));
+    let BulkCompanyOutcome::Created(created) = &outcomes[1] else {
+        panic!("expected a created company");
+    };
+    assert_eq!(repo.companies().await, vec![created.clone()]);
+}
+
+#[tokio::test]
+async fn bulk_company_keeps_successful_operations() {
+    let existing = company("ACM", "acme@mail.com");
+    let (usecase, repo) = setup(vec![existing.clone()]);
+
+    let results = usecase
+        .bulk_company(vec![
+            BulkCompanyOperation::Create(input("NEW", "new@mail.com")),
+            BulkCompanyOperation::Create(input("ACM", "other@mail.com")),
+            BulkCompanyOperation::Delete(Uuid::new_v4()),
+            BulkCompanyOperation::Update(existing.id, input("ACX", "acme@mail.com")),
+        ])
+        .await;
+
+    assert!(matches!(results[0], Ok(BulkCompanyOutcome::Created(_))));
+    assert!(matches!(
+        results[1],
+        Err(CompanyUsecaseError::CodeAlreadyExist)
+    ));
+    assert!(matches!(results[2], Err(CompanyUsecaseError::NotFound)));
+    assert!(matches!(results[3], Ok(BulkCompanyOutcome::Updated(_))));
+
+    let mut codes: Vec<String> = repo.companies().await.into_iter().map(|c| c.code).collect();
+    codes.sort();
+    assert_eq!(codes, vec!["ACX", "NEW"]);
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FailOn {
+    Begin,
+    Read,
+    Write,
+    Delete,
+}
+
+// wraps the in-memory repository to inject storage failures
+struct FaultyCompanyRepository {
+    inner: CompanyRepositoryMemory,
+    fail_on: Option<FailOn>,
+    skip_unique_checks: bool,
+}
+
+impl FaultyCompanyRepository {
+    fn new(companies: Vec<Company>, fail_on: Option<FailOn>) -> Self {
+        Self {
+            inner: CompanyRepositoryMemory::with_companies(companies),
+            fail_on,
+            skip_unique_checks: false,
+        }
+    }
+
+    fn skip_unique_checks(mut self) -> Self {
+        self.skip_unique_checks = true;
+        self
+    }
+
+    fn fail(&self, operations: &[FailOn]) -> Result<(), sqlx::Error> {
+        match self.fail_on {
+            Some(fail_on) if operations.contains(&fail_on) => Err(sqlx::Error::PoolTimedOut),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Transactional for FaultyCompanyRepository {
+    type Uow = <CompanyRepositoryMemory as Transactional>::Uow;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.fail(&[FailOn::Begin])?;
+        self.inner.begin().await
+    }
+}
+
+#[async_trait]
+impl CompanyRepository for FaultyCompanyRepository {
+    async fn get_company_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Company>, sqlx::Error> {
+        self.fail(&[FailOn::Read])?;
+        self.inner.get_company_by_id(uow, id).await
+    }
+
+    async fn count_all_companies(
+        &self,
+        uow: &mut Self::Uow,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        self.fail(&[FailOn::Read])?;
+        self.inner.count_all_companies(uow, query).await
+    }
+
+    async fn find_all_companies(
+        &self,
+        uow: &mut Self::Uow,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Company>, sqlx::Error> {
+        self.fail(&[FailOn::Read])?;
+        self.inner.find_all_companies(uow, query).await
+    }
+
+    fn stream_all_companies(
+        &self,
+        query: PaginationRequest,
+    ) -> BoxStream<'static, Result<Company, sqlx::Error>> {
+        match self.fail(&[FailOn::Read]) {
+            Ok(()) => self.inner.stream_all_companies(query),
+            Err(err) => Box::pin(futures::stream::once(async move { Err(err) })),
+        }
+    }
+
+    async fn check_existing_company_email(
+        &self,
+        uow: &mut Self::Uow,
+        email: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        self.fail(&[FailOn::Read])?;
+        if self.skip_unique_checks {
+            return Ok(false);
+        }
+        self.inner
+            .check_existing_company_email(uow, email, id)
+            .await
+    }
+
+    async fn check_existing_company_code(
+        &self,
+        uow: &mut Self::Uow,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        self.fail(&[FailOn::Read])?;
+        if self.skip_unique_checks {
+            return Ok(false);
+        }
+        self.inner.check_existing_company_code(uow, code, id).await
+    }
+
+    async fn create_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
+        self.fail(&[FailOn::Write])?;
+        self.inner.create_company(uow, company).await
+    }
+
+    async fn update_company(
+        &self,
+        uow: &mut Self::Uow,
+        company: Company,
+    ) -> Result<Company, sqlx::Error> {
+        self.fail(&[FailOn::Write])?;
+        self.inner.update_company(uow, company).await
+    }
+
+    async fn delete_company(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        self.fail(&[FailOn::Write, FailOn::Delete])?;
+        self.inner.delete_company(uow, id).await
+    }
+}
@@ -1,6 +1,35 @@
+use uuid::Uuid;
+
 use crate::company::domain::company::Company;
+use crate::company::usecase::company_usecase::CompanyUsecaseError;
 
 pub struct ListCompanyResult {
     pub data: Vec<Company>,
     pub total_data: i64,
-}
\ No newline at end of file
+}
+
+pub struct CompanyInput {
+    pub name: String,
+    pub email: String,
+    pub code: String,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+}
+
+pub enum BulkCompanyOperation {
+    Create(CompanyInput),
+    Update(Uuid, CompanyInput),
+    Delete(Uuid),
+}
+
+pub enum BulkCompanyOutcome {
+    Created(Company),
+    Updated(Company),
+    Deleted(Uuid),
+}
+
+pub struct BulkCompanyError {
+    // None when the failure happened while committing the whole batch
+    pub index: Option<usize>,
+    pub error: CompanyUsecaseError,
+}
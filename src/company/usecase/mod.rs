@@ -1,2 +1,4 @@
 pub mod company_usecase;
-pub mod dto;
\ No newline at end of file
+pub mod dto;
+#[cfg(test)]
+mod company_usecase_test;
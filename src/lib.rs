@@ -0,0 +1,22 @@
+pub mod company;
+pub mod app_cli;
+pub mod app_config;
+pub mod app_database;
+pub mod app_health;
+pub mod app_helper;
+pub mod app_lifecycle;
+pub mod app_logging;
+pub mod app_metrics;
+pub mod app_request;
+pub mod app_response;
+pub mod app_middleware;
+pub mod app_openapi;
+pub mod app_rate_limit;
+pub mod app_router;
+pub mod login;
+pub mod product;
+pub mod purchasing;
+pub mod sales;
+pub mod stock;
+pub mod user;
+pub mod warehouse;
@@ -1,25 +1,47 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use std::sync::Arc;
 
-use crate::{app_middleware::jwt_token::jwt::generate_token, app_response::{error::ResponseError, success::ResponseSuccess}, login::handler::types::Login};
+use axum::{Extension, Json, extract::State, http::StatusCode, response::IntoResponse};
 
-static USERNAME: &str = "admin";
-static PASSWORD: &str = "password";
-static USER_ID: &str = "super_admin";
+use crate::{app_config::config::AppConfig, app_metrics::metrics::AppMetrics, app_middleware::jwt_token::jwt::generate_token, app_response::{error::{ResponseError, ResponseErrorBody}, success::{ResponseSuccess, ResponseSuccessBody}}, login::handler::types::Login};
+use crate::user::repository::user_repository::UserRepository;
+use crate::user::usecase::user_usecase::{UserUsecase, UserUsecaseError};
 
-pub async fn login(
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    request_body = Login,
+    responses(
+        (status = 200, description = "bearer token in data", body = ResponseSuccessBody<String>),
+        (status = 400, description = "invalid username or password", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage failure or token could not be signed", body = ResponseErrorBody),
+    )
+)]
+pub async fn login<R: UserRepository>(
+    State(usecase): State<Arc<UserUsecase<R>>>,
+    Extension(config): Extension<Arc<AppConfig>>,
+    Extension(metrics): Extension<AppMetrics>,
     Json(req): Json<Login>
 ) -> Result<impl IntoResponse, ResponseError> {
-    if req.username != USERNAME || req.password != PASSWORD {
-        return Err(ResponseError::BadRequest("invalid username or password".into()))
-    }
+    let user = match usecase.authenticate(&req.username, &req.password).await {
+        Ok(user) => user,
+        Err(UserUsecaseError::InvalidCredentials) => {
+            metrics.login_attempt(false);
+            return Err(ResponseError::BadRequest("invalid username or password".into()))
+        }
+        Err(UserUsecaseError::DatabaseError(err)) => return Err(ResponseError::database(err)),
+        Err(err) => return Err(ResponseError::internal(err)),
+    };
 
-    let generate_token = generate_token(USER_ID);
+    let generate_token = generate_token(&config.jwt, &user.id.to_string());
     let token = match generate_token {
         Ok(token) => token,
-        Err(_) => {
-            return Err(ResponseError::InternalServerError)
+        Err(err) => {
+            return Err(ResponseError::internal(err))
         }
     };
 
+    metrics.login_attempt(true);
     Ok(ResponseSuccess::Object(StatusCode::OK, Some(token)))
-}
\ No newline at end of file
+}
@@ -0,0 +1,106 @@
+use axum::http::{Method, StatusCode, header};
+use chrono::Utc;
+use serde_json::json;
+
+use crate::app_middleware::jwt_token::jwt::verify_token;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, test_config};
+
+#[tokio::test]
+async fn login_returns_token_in_success_envelope() {
+    let app = TestApp::new();
+
+    let body = json!({ "username": "admin", "password": "password" });
+    let response = app.request(Method::POST, "/login", None, Some(body)).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let json = response.json();
+    assert_eq!(json["message"], "success");
+    assert_eq!(json["http_code"], 200);
+    assert!(json.get("meta").is_none());
+
+    let claims = verify_token(&test_config().jwt, json["data"].as_str().unwrap()).unwrap();
+    assert_eq!(claims.sub, TEST_ADMIN_ID.to_string());
+}
+
+#[tokio::test]
+async fn login_rejects_wrong_credentials() {
+    let app = TestApp::new();
+
+    for (username, password) in [("admin", "wrong"), ("someone", "password")] {
+        let body = json!({ "username": username, "password": password });
+        let response = app.request(Method::POST, "/login", None, Some(body)).await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.error_json(),
+            json!({ "status": 400, "message": "invalid username or password", "detail": null })
+        );
+    }
+}
+
+#[tokio::test]
+async fn login_rejects_malformed_body() {
+    let app = TestApp::new();
+
+    let body = json!({ "username": "admin" });
+    let response = app.request(Method::POST, "/login", None, Some(body)).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.request(Method::POST, "/login", None, None).await;
+    assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn login_only_accepts_post() {
+    let app = TestApp::new();
+
+    let response = app.request(Method::GET, "/login", None, None).await;
+    assert_eq!(response.status, StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn login_signs_token_with_configured_secret_and_expiry() {
+    let mut config = test_config();
+    config.jwt.secret = "another-secret-key".into();
+    config.jwt.expiration_hours = 2;
+    let app = TestApp::with_config(config.clone(), Vec::new());
+
+    let token = app.login().await;
+
+    let claims = verify_token(&config.jwt, &token).unwrap();
+    let expires_in = claims.exp as i64 - Utc::now().timestamp();
+    assert!(
+        (7000..=7200).contains(&expires_in),
+        "expires in {expires_in}s"
+    );
+    assert!(verify_token(&test_config().jwt, &token).is_err());
+
+    // tokens signed with the default secret are rejected by this app
+    let other = TestApp::new().login().await;
+    let response = app
+        .request(Method::GET, "/company", Some(&other), None)
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn cors_follows_configured_origins() {
+    let mut config = test_config();
+    config.cors.allowed_origins = vec!["https://app.example.com".into()];
+    let app = TestApp::with_config(config, Vec::new());
+
+    let allowed = app.preflight("/login", "https://app.example.com").await;
+    assert_eq!(
+        allowed.header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        Some("https://app.example.com")
+    );
+
+    let denied = app.preflight("/login", "https://evil.example.com").await;
+    assert_eq!(denied.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), None);
+
+    // no origins configured, no cors headers
+    let response = TestApp::new()
+        .preflight("/login", "https://app.example.com")
+        .await;
+    assert_eq!(response.header(header::ACCESS_CONTROL_ALLOW_ORIGIN), None);
+}
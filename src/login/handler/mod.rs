@@ -1,2 +1,4 @@
 pub mod types;
-pub mod login_handler;
\ No newline at end of file
+pub mod login_handler;
+#[cfg(test)]
+mod login_handler_test;
\ No newline at end of file
@@ -1,7 +1,7 @@
 use serde::Deserialize;
+use utoipa::ToSchema;
 
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct Login {
     pub username: String,
     pub password: String,
@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+use axum::{Router, routing::post};
+
+use crate::login::handler::login_handler::login;
+use crate::user::repository::user_repository::UserRepository;
+use crate::user::usecase::user_usecase::UserUsecase;
+
+pub fn login_routes<R: UserRepository + 'static>(repo: R) -> Router {
+    let usecase = Arc::new(UserUsecase::new(repo));
+
+    Router::new()
+        .route("/login", post(login))
+        .with_state(usecase)
+}
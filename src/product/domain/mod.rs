@@ -0,0 +1,4 @@
+pub mod product;
+pub mod product_unit;
+#[cfg(test)]
+mod product_unit_test;
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct Product {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub unit_of_measure: String,
+    pub barcode: Option<String>,
+    pub is_active: bool,
+    /// stock of the product is kept per lot, fixed once created
+    pub is_lot_tracked: bool,
+    /// every unit in stock has a serial number, fixed once created and
+    /// excludes lot tracking
+    pub is_serialised: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// narrows a product listing on top of search, None matches every product
+#[derive(Debug, Clone, Default)]
+pub struct ProductFilter {
+    pub company_id: Option<Uuid>,
+    pub is_active: Option<bool>,
+}
@@ -0,0 +1,173 @@
+use core::fmt;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// most decimals a quantity entered in a unit may carry
+pub const MAX_DECIMAL_PLACES: i16 = 6;
+
+// a unit the product is bought or sold in besides its unit_of_measure, which
+// stays the base unit the ledger counts in
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct ProductUnit {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    /// e.g. box or carton, never the product's unit_of_measure
+    pub code: String,
+    /// base units in one of this unit, exact
+    pub factor: Decimal,
+    /// decimals a quantity entered in this unit may have
+    pub decimal_places: i16,
+    pub rounding: UnitRounding,
+    /// purchase order lines may be entered in it
+    pub is_purchase_unit: bool,
+    /// sales order lines may be entered in it
+    pub is_sales_unit: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ProductUnit {
+    pub fn allows(&self, purpose: UnitPurpose) -> bool {
+        match purpose {
+            UnitPurpose::Purchase => self.is_purchase_unit,
+            UnitPurpose::Sales => self.is_sales_unit,
+        }
+    }
+}
+
+// stored as text in product_units.rounding, how a converted quantity that is
+// not a whole number of base units becomes one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum UnitRounding {
+    // refused
+    Exact,
+    Up,
+    Down,
+    // half a base unit or more goes up
+    HalfUp,
+}
+
+impl UnitRounding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitRounding::Exact => "exact",
+            UnitRounding::Up => "up",
+            UnitRounding::Down => "down",
+            UnitRounding::HalfUp => "half_up",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitPurpose {
+    Purchase,
+    Sales,
+}
+
+// how a quantity entered in a unit comes to base units
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitConversion {
+    pub unit: String,
+    pub factor: Decimal,
+    pub decimal_places: i16,
+    pub rounding: UnitRounding,
+}
+
+impl UnitConversion {
+    // the base unit converts one to one and takes whole quantities only
+    pub fn base(unit: String) -> Self {
+        UnitConversion {
+            unit,
+            factor: Decimal::ONE,
+            decimal_places: 0,
+            rounding: UnitRounding::Exact,
+        }
+    }
+
+    // the base quantity a positive quantity in the unit comes to
+    pub fn to_base(&self, quantity: Decimal) -> Result<i64, UnitConversionError> {
+        let decimal_places = u32::try_from(self.decimal_places).unwrap_or(0);
+        if quantity.normalize().scale() > decimal_places {
+            return Err(UnitConversionError::TooManyDecimals {
+                unit: self.unit.clone(),
+                decimal_places: self.decimal_places,
+            });
+        }
+        let out_of_range = || UnitConversionError::OutOfRange {
+            unit: self.unit.clone(),
+        };
+        let base = quantity.checked_mul(self.factor).ok_or_else(out_of_range)?;
+        let whole = match self.rounding {
+            UnitRounding::Exact if !base.fract().is_zero() => {
+                return Err(UnitConversionError::NotWhole {
+                    unit: self.unit.clone(),
+                });
+            }
+            UnitRounding::Exact => base,
+            UnitRounding::Up => base.round_dp_with_strategy(0, RoundingStrategy::AwayFromZero),
+            UnitRounding::Down => base.round_dp_with_strategy(0, RoundingStrategy::ToZero),
+            UnitRounding::HalfUp => {
+                base.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            }
+        };
+        if whole.is_zero() {
+            return Err(UnitConversionError::RoundsToZero {
+                unit: self.unit.clone(),
+            });
+        }
+        whole.to_i64().ok_or_else(out_of_range)
+    }
+}
+
+impl From<ProductUnit> for UnitConversion {
+    fn from(unit: ProductUnit) -> Self {
+        UnitConversion {
+            unit: unit.code,
+            factor: unit.factor,
+            decimal_places: unit.decimal_places,
+            rounding: unit.rounding,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitConversionError {
+    TooManyDecimals { unit: String, decimal_places: i16 },
+    // exact rounding and the quantity is not a whole number of base units
+    NotWhole { unit: String },
+    RoundsToZero { unit: String },
+    // past what the ledger holds
+    OutOfRange { unit: String },
+}
+
+impl fmt::Display for UnitConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitConversionError::TooManyDecimals {
+                unit,
+                decimal_places,
+            } => write!(
+                f,
+                "quantity in {unit} can have at most {decimal_places} decimal places"
+            ),
+            UnitConversionError::NotWhole { unit } => {
+                write!(f, "quantity in {unit} is not a whole number of base units")
+            }
+            UnitConversionError::RoundsToZero { unit } => {
+                write!(f, "quantity in {unit} rounds to zero base units")
+            }
+            UnitConversionError::OutOfRange { unit } => {
+                write!(f, "quantity in {unit} is out of range in base units")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnitConversionError {}
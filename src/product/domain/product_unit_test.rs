@@ -0,0 +1,119 @@
+use rust_decimal::Decimal;
+
+use crate::product::domain::product_unit::{
+    MAX_DECIMAL_PLACES, UnitConversion, UnitConversionError, UnitRounding,
+};
+
+fn conversion(factor: &str, decimal_places: i16, rounding: UnitRounding) -> UnitConversion {
+    UnitConversion {
+        unit: "box".into(),
+        factor: factor.parse().unwrap(),
+        decimal_places,
+        rounding,
+    }
+}
+
+fn quantity(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn not_whole() -> UnitConversionError {
+    UnitConversionError::NotWhole { unit: "box".into() }
+}
+
+fn rounds_to_zero() -> UnitConversionError {
+    UnitConversionError::RoundsToZero { unit: "box".into() }
+}
+
+fn out_of_range() -> UnitConversionError {
+    UnitConversionError::OutOfRange { unit: "box".into() }
+}
+
+#[test]
+fn base_unit_takes_whole_quantities_one_to_one() {
+    let base = UnitConversion::base("pcs".into());
+
+    assert_eq!(base.to_base(quantity("7")), Ok(7));
+    // trailing zeros are not decimals
+    assert_eq!(base.to_base(quantity("7.00")), Ok(7));
+    assert_eq!(
+        base.to_base(quantity("7.5")),
+        Err(UnitConversionError::TooManyDecimals {
+            unit: "pcs".into(),
+            decimal_places: 0,
+        })
+    );
+}
+
+#[test]
+fn each_rounding_turns_a_fraction_into_whole_base_units() {
+    // half a base unit per box, three boxes are one and a half
+    let cases = [
+        (UnitRounding::Exact, Err(not_whole())),
+        (UnitRounding::Up, Ok(2)),
+        (UnitRounding::Down, Ok(1)),
+        (UnitRounding::HalfUp, Ok(2)),
+    ];
+    for (rounding, expected) in cases {
+        let unit = conversion("0.5", 0, rounding);
+        assert_eq!(unit.to_base(quantity("3")), expected, "{rounding:?}");
+        assert_eq!(unit.to_base(quantity("4")), Ok(2), "{rounding:?}");
+    }
+
+    // below the midpoint half up goes down, above it goes up
+    let unit = conversion("0.3", 0, UnitRounding::HalfUp);
+    assert_eq!(unit.to_base(quantity("4")), Ok(1));
+    assert_eq!(unit.to_base(quantity("6")), Ok(2));
+}
+
+#[test]
+fn quantity_rounding_to_nothing_is_refused() {
+    for rounding in [UnitRounding::Down, UnitRounding::HalfUp] {
+        let unit = conversion("0.3", 0, rounding);
+        assert_eq!(
+            unit.to_base(quantity("1")),
+            Err(rounds_to_zero()),
+            "{rounding:?}"
+        );
+    }
+    let unit = conversion("0.3", 0, UnitRounding::Up);
+    assert_eq!(unit.to_base(quantity("1")), Ok(1));
+}
+
+#[test]
+fn decimals_are_limited_by_the_unit() {
+    let unit = conversion("12", 1, UnitRounding::Exact);
+    assert_eq!(unit.to_base(quantity("2.5")), Ok(30));
+    assert_eq!(unit.to_base(quantity("2.50")), Ok(30));
+    assert_eq!(
+        unit.to_base(quantity("2.25")),
+        Err(UnitConversionError::TooManyDecimals {
+            unit: "box".into(),
+            decimal_places: 1,
+        })
+    );
+
+    // the finest scale a unit may take still converts exactly
+    let unit = conversion("1000000", MAX_DECIMAL_PLACES, UnitRounding::Exact);
+    assert_eq!(unit.to_base(quantity("0.000001")), Ok(1));
+    assert_eq!(unit.to_base(quantity("1.234567")), Ok(1_234_567));
+    assert!(matches!(
+        unit.to_base(quantity("0.0000001")),
+        Err(UnitConversionError::TooManyDecimals { .. })
+    ));
+}
+
+#[test]
+fn quantity_past_the_ledger_range_is_refused() {
+    let unit = conversion("1", 0, UnitRounding::Exact);
+    assert_eq!(unit.to_base(Decimal::from(i64::MAX)), Ok(i64::MAX));
+    // fits a decimal but not the ledger
+    assert_eq!(
+        unit.to_base(Decimal::from(i64::MAX) + Decimal::ONE),
+        Err(out_of_range())
+    );
+
+    // the multiplication itself overflows
+    let unit = conversion("2", 0, UnitRounding::Exact);
+    assert_eq!(unit.to_base(Decimal::MAX), Err(out_of_range()));
+}
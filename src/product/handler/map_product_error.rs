@@ -0,0 +1,97 @@
+use rust_decimal::Decimal;
+
+use crate::app_response::error::ResponseError;
+use crate::product::domain::product_unit::MAX_DECIMAL_PLACES;
+use crate::product::handler::types::{ProcessProductRequest, ProcessProductUnitRequest};
+use crate::product::usecase::product_usecase::ProductUsecaseError;
+
+pub fn map_usecase_product_error(err: ProductUsecaseError) -> ResponseError {
+    match &err {
+        ProductUsecaseError::SkuAlreadyExist => {
+            ResponseError::BadRequest("sku already exist".into())
+        }
+        ProductUsecaseError::CompanyNotFound => {
+            ResponseError::BadRequest("company not found".into())
+        }
+        ProductUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        ProductUsecaseError::InUse => ResponseError::BadRequest("product is still in use".into()),
+        ProductUsecaseError::LotTrackingFixed => {
+            ResponseError::BadRequest("lot tracking can not change".into())
+        }
+        ProductUsecaseError::SerialTrackingFixed => {
+            ResponseError::BadRequest("serial tracking can not change".into())
+        }
+        ProductUsecaseError::BaseUnitFixed => ResponseError::BadRequest(
+            "unit of measure can not change once the product has units, movements or order lines"
+                .into(),
+        ),
+        ProductUsecaseError::TrackingConflict => {
+            ResponseError::BadRequest("product can not be lot tracked and serialised".into())
+        }
+        ProductUsecaseError::UnitNotFound => ResponseError::NotFound("data not found".into()),
+        ProductUsecaseError::UnitCodeAlreadyExist => {
+            ResponseError::BadRequest("unit code already exist".into())
+        }
+        ProductUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column sizes of the products table, longer values would fail the insert
+const MAX_SKU_LEN: usize = 50;
+const MAX_NAME_LEN: usize = 150;
+const MAX_CATEGORY_LEN: usize = 100;
+const MAX_UNIT_OF_MEASURE_LEN: usize = 20;
+const MAX_BARCODE_LEN: usize = 50;
+
+pub fn validate_product_input(req: &ProcessProductRequest) -> Result<(), ResponseError> {
+    if req.sku.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Sku is required".into()));
+    }
+    if req.name.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Name is required".into()));
+    }
+    if req.unit_of_measure.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Unit of measure is required".into()));
+    }
+
+    let lengths = [
+        ("Sku", Some(&req.sku), MAX_SKU_LEN),
+        ("Name", Some(&req.name), MAX_NAME_LEN),
+        ("Category", req.category.as_ref(), MAX_CATEGORY_LEN),
+        ("Unit of measure", Some(&req.unit_of_measure), MAX_UNIT_OF_MEASURE_LEN),
+        ("Barcode", req.barcode.as_ref(), MAX_BARCODE_LEN),
+    ];
+    for (field, value, max) in lengths {
+        if value.is_some_and(|v| v.chars().count() > max) {
+            return Err(ResponseError::BadRequest(format!(
+                "{field} can not be longer than {max} characters"
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_product_unit_input(req: &ProcessProductUnitRequest) -> Result<(), ResponseError> {
+    if req.code.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Code is required".into()));
+    }
+    if req.code.chars().count() > MAX_UNIT_OF_MEASURE_LEN {
+        return Err(ResponseError::BadRequest(format!(
+            "Code can not be longer than {MAX_UNIT_OF_MEASURE_LEN} characters"
+        )));
+    }
+    if req.factor <= Decimal::ZERO {
+        return Err(ResponseError::BadRequest(
+            "Factor must be greater than 0".into(),
+        ));
+    }
+    if req
+        .decimal_places
+        .is_some_and(|places| !(0..=MAX_DECIMAL_PLACES).contains(&places))
+    {
+        return Err(ResponseError::BadRequest(format!(
+            "Decimal places must be between 0 and {MAX_DECIMAL_PLACES}"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod map_product_error;
+pub mod product_handler;
+#[cfg(test)]
+mod product_handler_test;
+pub mod types;
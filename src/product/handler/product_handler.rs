@@ -0,0 +1,315 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_request::{
+    pagination::PaginationRequest,
+    path_uuid::{PathUuid, PathUuidPair},
+};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::product::domain::product::Product;
+use crate::product::domain::product_unit::ProductUnit;
+use crate::product::handler::map_product_error::{
+    map_usecase_product_error, validate_product_input, validate_product_unit_input,
+};
+use crate::product::handler::types::{
+    CreateProductRequest, ProcessProductRequest, ProcessProductUnitRequest, ProductFilterRequest,
+};
+use crate::product::repository::helper_query::PRODUCT_SORT_COLUMNS;
+use crate::product::repository::product_repository::ProductRepository;
+use crate::product::usecase::product_usecase::ProductUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/product",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    request_body = CreateProductRequest,
+    responses(
+        (status = 201, description = "product created", body = ResponseSuccessBody<Product>),
+        (status = 400, description = "invalid input, unknown company or taken sku", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn create_product_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    Json(req): Json<CreateProductRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_product_input(&req.product)?;
+
+    let product = usecase
+        .create_product(req.company_id, req.product.into())
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(product)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/product/{id}",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "product id")),
+    responses(
+        (status = 200, description = "the product", body = ResponseSuccessBody<Product>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "product not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_product_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let product = usecase
+        .get_product(id)
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(product)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "product id")),
+    request_body = ProcessProductRequest,
+    responses(
+        (status = 200, description = "product updated", body = ResponseSuccessBody<Product>),
+        (status = 400, description = "invalid input, taken sku, a tracking change, or a unit of measure change once the product has units, movements or order lines", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "product not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_product_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<ProcessProductRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_product_input(&req)?;
+
+    let product = usecase
+        .update_product(id, req.into())
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(product)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/product/{id}",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "product id")),
+    responses(
+        (status = 200, description = "product deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or product still has stock movements", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "product not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_product_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_product(id)
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
+
+// search matches sku, name or barcode
+#[utoipa::path(
+    get,
+    path = "/product",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, ProductFilterRequest),
+    responses(
+        (status = 200, description = "one page of products, meta holds the totals", body = ResponseSuccessBody<Vec<Product>>),
+        (status = 400, description = "unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_products_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<ProductFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&PRODUCT_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let product_list_data = usecase
+        .list_product(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        product_list_data.total_data as u64,
+        Some(product_list_data.data),
+    ))
+}
+
+// the units besides unit_of_measure, ordered by code
+#[utoipa::path(
+    get,
+    path = "/product/{id}/units",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "product id")),
+    responses(
+        (status = 200, description = "every unit of the product", body = ResponseSuccessBody<Vec<ProductUnit>>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "product not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_product_units_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuid(product_id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let units = usecase
+        .list_units(product_id)
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(units)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/product/{id}/units",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "product id")),
+    request_body = ProcessProductUnitRequest,
+    responses(
+        (status = 201, description = "unit created", body = ResponseSuccessBody<ProductUnit>),
+        (status = 400, description = "invalid input or taken code", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "product not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn create_product_unit_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuid(product_id): PathUuid,
+    Json(req): Json<ProcessProductUnitRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_product_unit_input(&req)?;
+
+    let unit = usecase
+        .create_unit(product_id, req.into())
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(unit)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/product/{id}/units/{unit_id}",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "product id"),
+        ("unit_id" = Uuid, Path, description = "unit id"),
+    ),
+    request_body = ProcessProductUnitRequest,
+    responses(
+        (status = 200, description = "unit updated, existing order lines keep their quantities", body = ResponseSuccessBody<ProductUnit>),
+        (status = 400, description = "invalid input or taken code", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "unit not found for this product", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_product_unit_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuidPair(product_id, id): PathUuidPair,
+    Json(req): Json<ProcessProductUnitRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_product_unit_input(&req)?;
+
+    let unit = usecase
+        .update_unit(product_id, id, req.into())
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(unit)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/product/{id}/units/{unit_id}",
+    tag = "product",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "product id"),
+        ("unit_id" = Uuid, Path, description = "unit id"),
+    ),
+    responses(
+        (status = 200, description = "unit deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "unit not found for this product", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_product_unit_handler<R: ProductRepository>(
+    State(usecase): State<Arc<ProductUsecase<R>>>,
+    PathUuidPair(product_id, id): PathUuidPair,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_unit(product_id, id)
+        .await
+        .map_err(map_usecase_product_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
@@ -0,0 +1,319 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TestApp, error_body};
+use crate::company::domain::company::Company;
+
+fn payload(company_id: Uuid, sku: &str) -> Value {
+    json!({
+        "company_id": company_id,
+        "sku": sku,
+        "name": format!("Product {sku}"),
+        "description": "stocked item",
+        "category": "hardware",
+        "unit_of_measure": "pcs",
+        "barcode": null,
+    })
+}
+
+fn skus(data: &Value) -> Vec<&str> {
+    data.as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["sku"].as_str().unwrap())
+        .collect()
+}
+
+async fn setup() -> (TestApp, String, Company, Company) {
+    let (acme, globex) = (company("ACM"), company("GLB"));
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+    (app, token, acme, globex)
+}
+
+#[tokio::test]
+async fn product_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, "/product".to_string()),
+        (Method::POST, "/product".to_string()),
+        (Method::GET, format!("/product/{id}")),
+        (Method::PUT, format!("/product/{id}")),
+        (Method::DELETE, format!("/product/{id}")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn product_crud_round_trip() {
+    let (app, token, acme, _) = setup().await;
+
+    let created = app
+        .create(&token, "/product", payload(acme.id, "W-1"))
+        .await;
+    assert_eq!(created["company_id"], acme.id.to_string());
+    assert_eq!(created["is_active"], true);
+    let uri = format!("/product/{}", created["id"].as_str().unwrap());
+
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"], created);
+
+    let mut body = payload(acme.id, "W-2");
+    body.as_object_mut().unwrap().remove("company_id");
+    body["is_active"] = json!(false);
+    let response = app
+        .request(Method::PUT, &uri, Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let updated = &response.json()["data"];
+    assert_eq!(updated["sku"], "W-2");
+    assert_eq!(updated["is_active"], false);
+    assert_eq!(updated["created_at"], created["created_at"]);
+
+    let response = app.request(Method::DELETE, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "data not found"));
+    assert!(app.products.products().await.is_empty());
+}
+
+#[tokio::test]
+async fn create_product_rejects_invalid_input() {
+    let (app, token, acme, _) = setup().await;
+    app.create(&token, "/product", payload(acme.id, "W-1"))
+        .await;
+
+    let mut empty_sku = payload(acme.id, " ");
+    empty_sku["sku"] = json!(" ");
+    let mut long_barcode = payload(acme.id, "W-2");
+    long_barcode["barcode"] = json!("9".repeat(51));
+
+    let cases = [
+        (empty_sku, "Sku is required"),
+        (long_barcode, "Barcode can not be longer than 50 characters"),
+        (payload(Uuid::new_v4(), "W-2"), "company not found"),
+        (payload(acme.id, "W-1"), "sku already exist"),
+    ];
+    for (body, message) in cases {
+        let response = app
+            .request(Method::POST, "/product", Some(&token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(response.error_json(), error_body(400, message));
+    }
+    assert_eq!(app.products.products().await.len(), 1);
+}
+
+#[tokio::test]
+async fn get_products_filters_searches_and_pages() {
+    let (app, token, acme, globex) = setup().await;
+    for sku in ["B-2", "A-1", "B-1"] {
+        app.create(&token, "/product", payload(acme.id, sku)).await;
+    }
+    app.create(&token, "/product", payload(globex.id, "B-3"))
+        .await;
+
+    let uri = format!(
+        "/product?company_id={}&search=b-&sort=-sku&page=1&per_page=1",
+        acme.id
+    );
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let json = response.json();
+    assert_eq!(skus(&json["data"]), vec!["B-2"]);
+    assert_eq!(
+        json["meta"],
+        json!({ "page": 1, "per_page": 1, "total_data": 2, "total_page": 2 })
+    );
+
+    let response = app
+        .request(
+            Method::GET,
+            "/product?per_page=10&sort=sku&is_active=true",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(
+        skus(&response.json()["data"]),
+        vec!["A-1", "B-1", "B-2", "B-3"]
+    );
+}
+
+#[tokio::test]
+async fn get_products_rejects_unknown_sort_column() {
+    let (app, token, _, _) = setup().await;
+
+    let response = app
+        .request(
+            Method::GET,
+            "/product?sort=name,-price;drop",
+            Some(&token),
+            None,
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "Sort by price;drop is not supported")
+    );
+}
+
+// a factor sent as a json number keeps every digit, f64 would round it
+#[tokio::test]
+async fn product_unit_factor_is_read_exactly() {
+    let (app, token, acme, _) = setup().await;
+    let product = app
+        .create(&token, "/product", payload(acme.id, "W-1"))
+        .await;
+    let units_uri = format!("/product/{}/units", product["id"].as_str().unwrap());
+
+    let body: Value =
+        serde_json::from_str(r#"{ "code": "dose", "factor": 0.123456789012345678 }"#).unwrap();
+    let unit = app.create(&token, &units_uri, body).await;
+    assert_eq!(unit["factor"], "0.123456789012345678");
+
+    let body: Value =
+        serde_json::from_str(r#"{ "code": "drum", "factor": 1234567890.0987654321 }"#).unwrap();
+    let unit = app.create(&token, &units_uri, body).await;
+    assert_eq!(unit["factor"], "1234567890.0987654321");
+}
+
+#[tokio::test]
+async fn product_units_convert_order_lines_to_the_base_unit() {
+    let (app, token, acme, globex) = setup().await;
+    let product = app
+        .create(&token, "/product", payload(acme.id, "W-1"))
+        .await;
+    let product_id = product["id"].as_str().unwrap();
+    let units_uri = format!("/product/{product_id}/units");
+
+    let response = app
+        .request(
+            Method::POST,
+            &units_uri,
+            Some(&token),
+            Some(json!({ "code": "box", "factor": "12", "decimal_places": 1, "rounding": "half_up" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{}", response.text());
+    let box_unit = response.json()["data"].clone();
+    assert_eq!(box_unit["factor"], "12");
+    assert_eq!(box_unit["is_sales_unit"], true);
+
+    let cases = [
+        (
+            json!({ "code": "pcs", "factor": 1 }),
+            "unit code already exist",
+        ),
+        (
+            json!({ "code": "box", "factor": 6 }),
+            "unit code already exist",
+        ),
+        (json!({ "code": " ", "factor": 6 }), "Code is required"),
+        (
+            json!({ "code": "pack", "factor": 0 }),
+            "Factor must be greater than 0",
+        ),
+        (
+            json!({ "code": "pack", "factor": 6, "decimal_places": 7 }),
+            "Decimal places must be between 0 and 6",
+        ),
+    ];
+    for (body, message) in cases {
+        let response = app
+            .request(Method::POST, &units_uri, Some(&token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(response.error_json(), error_body(400, message));
+    }
+
+    let warehouse = app
+        .request(
+            Method::POST,
+            &format!("/company/{}/warehouses", acme.id),
+            Some(&token),
+            Some(json!({ "code": "NORTH", "name": "north" })),
+        )
+        .await
+        .json()["data"]
+        .clone();
+    let order = |quantity: Value| {
+        json!({
+            "company_id": acme.id,
+            "customer_id": globex.id,
+            "warehouse_id": warehouse["id"],
+            "number": "SO-1",
+            "lines": [{ "product_id": product_id, "unit": "box", "quantity": quantity }],
+        })
+    };
+    let response = app
+        .request(
+            Method::POST,
+            "/sales/orders",
+            Some(&token),
+            Some(order(json!("1.25"))),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "quantity in box can have at most 1 decimal places")
+    );
+    let response = app
+        .request(
+            Method::POST,
+            "/sales/orders",
+            Some(&token),
+            Some(order(json!("1.5"))),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{}", response.text());
+    let line = &response.json()["data"]["lines"][0];
+    assert_eq!(line["unit"], "box");
+    assert_eq!(line["unit_quantity"], "1.5");
+    assert_eq!(line["quantity"], 18);
+
+    // a unit no longer sold in is refused on new lines
+    let unit_uri = format!("{units_uri}/{}", box_unit["id"].as_str().unwrap());
+    let response = app
+        .request(
+            Method::PUT,
+            &unit_uri,
+            Some(&token),
+            Some(json!({ "code": "box", "factor": "12", "is_sales_unit": false })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    let mut body = order(json!(1));
+    body["number"] = json!("SO-2");
+    let response = app
+        .request(Method::POST, "/sales/orders", Some(&token), Some(body))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.error_json(), error_body(400, "unit not found"));
+
+    let response = app
+        .request(Method::DELETE, &unit_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app
+        .request(Method::GET, &units_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"], json!([]));
+    let response = app
+        .request(Method::DELETE, &unit_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,97 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::product::domain::product::ProductFilter;
+use crate::product::domain::product_unit::UnitRounding;
+use crate::product::usecase::dto::{ProductInput, ProductUnitInput};
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProcessProductRequest {
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    /// e.g. pcs, box, kg
+    pub unit_of_measure: String,
+    pub barcode: Option<String>,
+    /// true when missing
+    pub is_active: Option<bool>,
+    /// false when missing on create, can not change once created
+    pub is_lot_tracked: Option<bool>,
+    /// false when missing on create, can not change once created, a product is
+    /// either lot tracked or serialised
+    pub is_serialised: Option<bool>,
+}
+
+// the owning company is set once, updates take ProcessProductRequest
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct CreateProductRequest {
+    pub company_id: Uuid,
+    #[serde(flatten)]
+    pub product: ProcessProductRequest,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProcessProductUnitRequest {
+    /// e.g. box or carton, not the product's unit_of_measure
+    pub code: String,
+    /// base units in one of this unit, e.g. "12" or "0.5"
+    pub factor: Decimal,
+    /// decimals a quantity in this unit may have, 0 when missing, at most 6
+    pub decimal_places: Option<i16>,
+    /// exact when missing
+    pub rounding: Option<UnitRounding>,
+    /// true when missing
+    pub is_purchase_unit: Option<bool>,
+    /// true when missing
+    pub is_sales_unit: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductFilterRequest {
+    /// only products of this company
+    pub company_id: Option<Uuid>,
+    /// only active or only inactive products
+    pub is_active: Option<bool>,
+}
+
+impl From<ProcessProductRequest> for ProductInput {
+    fn from(req: ProcessProductRequest) -> Self {
+        ProductInput {
+            sku: req.sku,
+            name: req.name,
+            description: req.description,
+            category: req.category,
+            unit_of_measure: req.unit_of_measure,
+            barcode: req.barcode,
+            is_active: req.is_active.unwrap_or(true),
+            is_lot_tracked: req.is_lot_tracked,
+            is_serialised: req.is_serialised,
+        }
+    }
+}
+
+impl From<ProductFilterRequest> for ProductFilter {
+    fn from(req: ProductFilterRequest) -> Self {
+        ProductFilter {
+            company_id: req.company_id,
+            is_active: req.is_active,
+        }
+    }
+}
+
+impl From<ProcessProductUnitRequest> for ProductUnitInput {
+    fn from(req: ProcessProductUnitRequest) -> Self {
+        ProductUnitInput {
+            code: req.code,
+            factor: req.factor,
+            decimal_places: req.decimal_places.unwrap_or(0),
+            rounding: req.rounding.unwrap_or(UnitRounding::Exact),
+            is_purchase_unit: req.is_purchase_unit.unwrap_or(true),
+            is_sales_unit: req.is_sales_unit.unwrap_or(true),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::product::domain::product::ProductFilter;
+
+// sortable columns of products, sort is pushed into ORDER BY as written so the
+// handler rejects anything else
+pub const PRODUCT_SORT_COLUMNS: [&str; 10] = [
+    "id",
+    "company_id",
+    "sku",
+    "name",
+    "description",
+    "category",
+    "unit_of_measure",
+    "barcode",
+    "is_active",
+    "created_at",
+];
+
+pub fn apply_product_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &ProductFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE TRUE");
+    if let Some(company_id) = filter.company_id {
+        qb.push(" AND company_id = ").push_bind(company_id);
+    }
+    if let Some(is_active) = filter.is_active {
+        qb.push(" AND is_active = ").push_bind(is_active);
+    }
+    if let Some(s) = search {
+        qb.push(" AND (")
+            .push(" sku ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(" OR name ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(" OR barcode ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(")");
+    }
+}
@@ -0,0 +1,7 @@
+pub mod product_repository;
+pub mod product_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod product_repository_memory;
+#[cfg(test)]
+mod product_repository_contract_test;
+pub mod helper_query;
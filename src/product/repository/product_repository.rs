@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::ProductUnit;
+
+// every call takes the unit of work opened by the usecase, like CompanyRepository
+#[async_trait]
+pub trait ProductRepository: Transactional {
+    async fn get_product_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Product>, sqlx::Error>;
+    async fn count_all_products(&self, uow: &mut Self::Uow, filter: &ProductFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_all_products(&self, uow: &mut Self::Uow, filter: &ProductFilter, query: &PaginationRequest) -> Result<Vec<Product>, sqlx::Error>;
+    async fn check_existing_company(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<bool, sqlx::Error>;
+    async fn check_existing_product_sku(&self, uow: &mut Self::Uow, company_id: &Uuid, sku: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_product(&self, uow: &mut Self::Uow, product: Product) -> Result<Product, sqlx::Error>;
+    async fn update_product(&self, uow: &mut Self::Uow, product: Product) -> Result<Product, sqlx::Error>;
+    // the units go with it
+    async fn delete_product(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+    // any unit, movement or order line counts in the base unit of the product
+    async fn check_product_in_use(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<bool, sqlx::Error>;
+    // ordered by code
+    async fn find_product_units(&self, uow: &mut Self::Uow, product_id: &Uuid) -> Result<Vec<ProductUnit>, sqlx::Error>;
+    async fn get_product_unit_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<ProductUnit>, sqlx::Error>;
+    async fn check_existing_product_unit_code(&self, uow: &mut Self::Uow, product_id: &Uuid, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_product_unit(&self, uow: &mut Self::Uow, unit: ProductUnit) -> Result<ProductUnit, sqlx::Error>;
+    async fn update_product_unit(&self, uow: &mut Self::Uow, unit: ProductUnit) -> Result<ProductUnit, sqlx::Error>;
+    async fn delete_product_unit(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+}
@@ -0,0 +1,444 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// company_repository_contract_test. both start with the companies ACM and GLB.
+// postgres runs need TEST_DATABASE_URL and are skipped without it.
+use chrono::{Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{ACME_ID, GLOBEX_ID, companies, seed_companies};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::company::usecase::company_usecase::{CompanyUsecase, CompanyUsecaseError};
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::{ProductUnit, UnitRounding};
+use crate::product::repository::product_repository::ProductRepository;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                super::$scenario(&super::ProductRepositoryMemory::new(companies)).await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::$scenario(&super::ProductRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(creates_and_finds_by_id);
+contract_test!(checks_company_and_sku);
+contract_test!(enforces_constraints);
+contract_test!(filters_searches_sorts_and_pages);
+contract_test!(updates_and_deletes);
+contract_test!(stores_units_of_a_product);
+
+fn product(company_id: Uuid, sku: &str, name: &str, minutes: i64) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id,
+        sku: sku.into(),
+        name: name.into(),
+        description: None,
+        category: Some("general".into()),
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        // postgres keeps microseconds, whole minutes survive the round trip
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(minutes),
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+async fn seed<R: ProductRepository>(repo: &R, products: Vec<Product>) {
+    let mut uow = repo.begin().await.unwrap();
+    for product in products {
+        repo.create_product(&mut uow, product).await.unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn skus(products: &[Product]) -> Vec<&str> {
+    products.iter().map(|p| p.sku.as_str()).collect()
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn creates_and_finds_by_id<R: ProductRepository>(repo: &R) {
+    let mut widget = product(ACME_ID, "W-1", "Widget", 0);
+    widget.description = Some("a widget".into());
+    widget.barcode = Some("8991234567890".into());
+    seed(repo, vec![widget.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_product_by_id(&mut uow, &widget.id).await.unwrap(),
+        Some(widget)
+    );
+    assert_eq!(
+        repo.get_product_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+async fn checks_company_and_sku<R: ProductRepository>(repo: &R) {
+    let widget = product(ACME_ID, "W-1", "Widget", 0);
+    seed(repo, vec![widget.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    assert!(
+        repo.check_existing_company(&mut uow, &ACME_ID)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap()
+    );
+
+    let cases = [
+        (ACME_ID, "W-1", None, true),
+        (ACME_ID, "W-1", Some(Uuid::new_v4()), true),
+        (ACME_ID, "W-1", Some(widget.id), false),
+        // per company and case sensitive
+        (GLOBEX_ID, "W-1", None, false),
+        (ACME_ID, "w-1", None, false),
+    ];
+    for (company_id, sku, id, expected) in cases {
+        let exists = repo
+            .check_existing_product_sku(&mut uow, &company_id, sku, id.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(exists, expected, "{sku} {id:?}");
+    }
+}
+
+async fn enforces_constraints<R: ProductRepository>(repo: &R) {
+    let widget = product(ACME_ID, "W-1", "Widget", 0);
+    let gadget = product(ACME_ID, "G-1", "Gadget", 1);
+    seed(repo, vec![widget.clone(), gadget.clone()]).await;
+
+    // the same sku in another company is fine
+    seed(repo, vec![product(GLOBEX_ID, "W-1", "Widget", 2)]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_product(&mut uow, product(ACME_ID, "W-1", "Copy", 3))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("products_company_id_sku_key")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_product(&mut uow, product(Uuid::new_v4(), "X-1", "Orphan", 3))
+        .await
+        .unwrap_err();
+    assert_eq!(violated(err).as_deref(), Some("products_company_id_fkey"));
+    uow.rollback().await.unwrap();
+
+    let mut same_sku = gadget.clone();
+    same_sku.sku = widget.sku.clone();
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.update_product(&mut uow, same_sku).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("products_company_id_sku_key")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .update_product(&mut uow, product(ACME_ID, "GH-1", "Ghost", 3))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx::Error::RowNotFound));
+}
+
+async fn filters_searches_sorts_and_pages<R: ProductRepository>(repo: &R) {
+    let mut inactive = product(ACME_ID, "B-2", "Bolt Large", 1);
+    inactive.is_active = false;
+    let mut scanned = product(ACME_ID, "N-1", "Nut", 2);
+    scanned.barcode = Some("899BOLT".into());
+    seed(
+        repo,
+        vec![
+            product(ACME_ID, "B-1", "Bolt", 0),
+            inactive,
+            scanned,
+            product(ACME_ID, "W-1", "Washer", 3),
+            product(GLOBEX_ID, "B-1", "Bolt", 4),
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let acme = ProductFilter {
+        company_id: Some(ACME_ID),
+        is_active: None,
+    };
+
+    // sku, name or barcode, case insensitive
+    let search = query(Some("bolt"), Some("sku"), 10, 0);
+    assert_eq!(
+        repo.count_all_products(&mut uow, &acme, &search)
+            .await
+            .unwrap(),
+        3
+    );
+    let found = repo
+        .find_all_products(&mut uow, &acme, &search)
+        .await
+        .unwrap();
+    assert_eq!(skus(&found), vec!["B-1", "B-2", "N-1"]);
+
+    let active = ProductFilter {
+        is_active: Some(true),
+        ..acme.clone()
+    };
+    let found = repo
+        .find_all_products(&mut uow, &active, &search)
+        .await
+        .unwrap();
+    assert_eq!(skus(&found), vec!["B-1", "N-1"]);
+
+    let every = ProductFilter::default();
+    assert_eq!(
+        repo.count_all_products(&mut uow, &every, &query(None, None, 1, 0))
+            .await
+            .unwrap(),
+        5
+    );
+    let found = repo
+        .find_all_products(&mut uow, &every, &query(None, Some("-created_at"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(skus(&found), vec!["W-1", "N-1"]);
+
+    // NULL barcodes sort last ascending, then by sku descending
+    let found = repo
+        .find_all_products(&mut uow, &acme, &query(None, Some("barcode,-sku"), 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(skus(&found), vec!["N-1", "W-1", "B-2", "B-1"]);
+}
+
+async fn updates_and_deletes<R: ProductRepository>(repo: &R) {
+    let widget = product(ACME_ID, "W-1", "Widget", 0);
+    let gadget = product(ACME_ID, "G-1", "Gadget", 1);
+    seed(repo, vec![widget.clone(), gadget.clone()]).await;
+
+    let mut changed = widget.clone();
+    changed.name = "Widget Pro".into();
+    changed.barcode = Some("123".into());
+    changed.is_active = false;
+
+    let mut uow = repo.begin().await.unwrap();
+    repo.update_product(&mut uow, changed.clone())
+        .await
+        .unwrap();
+    repo.delete_product(&mut uow, &gadget.id).await.unwrap();
+    // deleting a missing row is not an error
+    repo.delete_product(&mut uow, &Uuid::new_v4())
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_product_by_id(&mut uow, &widget.id).await.unwrap(),
+        Some(changed)
+    );
+    assert_eq!(
+        repo.get_product_by_id(&mut uow, &gadget.id).await.unwrap(),
+        None
+    );
+}
+
+fn unit(product: &Product, code: &str, factor: &str) -> ProductUnit {
+    ProductUnit {
+        id: Uuid::new_v4(),
+        product_id: product.id,
+        code: code.into(),
+        factor: factor.parse().unwrap(),
+        decimal_places: 0,
+        rounding: UnitRounding::Exact,
+        is_purchase_unit: true,
+        is_sales_unit: true,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+async fn stores_units_of_a_product<R: ProductRepository>(repo: &R) {
+    let widget = product(ACME_ID, "W-1", "Widget", 0);
+    let gadget = product(ACME_ID, "G-1", "Gadget", 1);
+    seed(repo, vec![widget.clone(), gadget.clone()]).await;
+    let carton = unit(&widget, "carton", "48");
+    let mut half = unit(&widget, "half", "0.5");
+    half.decimal_places = 2;
+    half.rounding = UnitRounding::HalfUp;
+    let gadget_box = unit(&gadget, "box", "12");
+
+    let mut uow = repo.begin().await.unwrap();
+    assert!(
+        !repo
+            .check_product_in_use(&mut uow, &widget.id)
+            .await
+            .unwrap()
+    );
+    for unit in [carton.clone(), half.clone(), gadget_box.clone()] {
+        repo.create_product_unit(&mut uow, unit).await.unwrap();
+    }
+    assert!(
+        repo.check_product_in_use(&mut uow, &widget.id)
+            .await
+            .unwrap()
+    );
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    // ordered by code, the factor comes back exactly
+    let units = repo.find_product_units(&mut uow, &widget.id).await.unwrap();
+    assert_eq!(units, vec![carton.clone(), half.clone()]);
+    assert_eq!(units[1].factor, Decimal::new(5, 1));
+    assert!(
+        repo.check_existing_product_unit_code(&mut uow, &widget.id, "carton", None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_product_unit_code(&mut uow, &widget.id, "carton", Some(&carton.id))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_product_unit_code(&mut uow, &gadget.id, "carton", None)
+            .await
+            .unwrap()
+    );
+
+    let err = repo
+        .create_product_unit(&mut uow, unit(&widget, "carton", "24"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("product_units_product_id_code_key")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_product_unit(
+            &mut uow,
+            unit(&product(ACME_ID, "X-1", "Ghost", 2), "box", "6"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("product_units_product_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let changed = ProductUnit {
+        code: "case".into(),
+        factor: "24.125".parse().unwrap(),
+        is_sales_unit: false,
+        ..carton.clone()
+    };
+    let mut uow = repo.begin().await.unwrap();
+    repo.update_product_unit(&mut uow, changed.clone())
+        .await
+        .unwrap();
+    repo.delete_product_unit(&mut uow, &half.id).await.unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_product_unit_by_id(&mut uow, &carton.id)
+            .await
+            .unwrap(),
+        Some(changed)
+    );
+    assert_eq!(
+        repo.get_product_unit_by_id(&mut uow, &half.id)
+            .await
+            .unwrap(),
+        None
+    );
+
+    // the units go with the product
+    repo.delete_product(&mut uow, &gadget.id).await.unwrap();
+    assert_eq!(
+        repo.get_product_unit_by_id(&mut uow, &gadget_box.id)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+// only postgres enforces the foreign key on the company side
+#[tokio::test]
+async fn company_with_products_cannot_be_deleted() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let company_repo = CompanyRepositorySqlx::new(db.pool.clone());
+    seed_companies(&company_repo).await;
+    seed(
+        &ProductRepositorySqlx::new(db.pool.clone()),
+        vec![product(ACME_ID, "W-1", "Widget", 0)],
+    )
+    .await;
+
+    let usecase = CompanyUsecase::new(company_repo, AppMetrics::new());
+    let result = usecase.delete_company(ACME_ID).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::InUse)));
+    usecase.delete_company(GLOBEX_ID).await.unwrap();
+
+    db.drop_database().await;
+}
@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::app_database::memory::{
+    MemoryStore, MemoryUnitOfWork, foreign_key_violation, ilike, unique_violation,
+};
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::ProductUnit;
+use crate::product::repository::helper_query::PRODUCT_SORT_COLUMNS;
+use crate::product::repository::product_repository::ProductRepository;
+
+// both tables in one store, a unit of work sees them together
+#[derive(Debug, Clone, Default)]
+pub struct ProductTables {
+    pub products: Vec<Product>,
+    pub units: Vec<ProductUnit>,
+}
+
+// mirrors ProductRepositorySqlx on top of vectors, the same way
+// CompanyRepositoryMemory does. the foreign key to companies is checked against
+// the committed rows of the company repository it was built with
+#[derive(Clone, Default)]
+pub struct ProductRepositoryMemory {
+    store: MemoryStore<ProductTables>,
+    companies: CompanyRepositoryMemory,
+}
+
+impl ProductRepositoryMemory {
+    pub fn new(companies: CompanyRepositoryMemory) -> Self {
+        Self::with_products(companies, Vec::new())
+    }
+
+    pub fn with_products(companies: CompanyRepositoryMemory, products: Vec<Product>) -> Self {
+        Self {
+            store: MemoryStore::new(ProductTables {
+                products,
+                units: Vec::new(),
+            }),
+            companies,
+        }
+    }
+
+    // committed rows, in insertion order
+    pub async fn products(&self) -> Vec<Product> {
+        self.store.snapshot().await.products
+    }
+
+    pub async fn units(&self) -> Vec<ProductUnit> {
+        self.store.snapshot().await.units
+    }
+
+    async fn company_exists(&self, company_id: &Uuid) -> bool {
+        self.companies
+            .companies()
+            .await
+            .iter()
+            .any(|c| c.id == *company_id)
+    }
+}
+
+#[async_trait]
+impl Transactional for ProductRepositoryMemory {
+    type Uow = MemoryUnitOfWork<ProductTables>;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        Ok(self.store.begin().await)
+    }
+}
+
+#[async_trait]
+impl ProductRepository for ProductRepositoryMemory {
+    async fn get_product_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        Ok(uow.state().products.iter().find(|p| p.id == *id).cloned())
+    }
+
+    async fn count_all_products(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &ProductFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(search_products(&uow.state().products, filter, &query.search).len() as i64)
+    }
+
+    async fn find_all_products(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &ProductFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        let products = sorted_products(&uow.state().products, filter, query)?;
+
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query.per_page.unwrap_or(1) as usize;
+        Ok(products.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn check_existing_company(
+        &self,
+        _uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(self.company_exists(company_id).await)
+    }
+
+    async fn check_existing_product_sku(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        sku: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .products
+            .iter()
+            .any(|p| p.company_id == *company_id && p.sku == sku && Some(&p.id) != id))
+    }
+
+    async fn create_product(
+        &self,
+        uow: &mut Self::Uow,
+        product: Product,
+    ) -> Result<Product, sqlx::Error> {
+        check_constraints(&uow.state().products, &product, true)?;
+        if !self.company_exists(&product.company_id).await {
+            return Err(foreign_key_violation("products_company_id_fkey"));
+        }
+        uow.state_mut().products.push(product.clone());
+
+        Ok(product)
+    }
+
+    async fn update_product(
+        &self,
+        uow: &mut Self::Uow,
+        product: Product,
+    ) -> Result<Product, sqlx::Error> {
+        // UPDATE .. RETURNING fetched with fetch_one fails when no row matched
+        if !uow.state().products.iter().any(|p| p.id == product.id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_constraints(&uow.state().products, &product, false)?;
+
+        let existing = uow
+            .state_mut()
+            .products
+            .iter_mut()
+            .find(|p| p.id == product.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // company_id, is_lot_tracked, is_serialised and created_at are not part of
+        // the UPDATE
+        existing.sku = product.sku.clone();
+        existing.name = product.name.clone();
+        existing.description = product.description.clone();
+        existing.category = product.category.clone();
+        existing.unit_of_measure = product.unit_of_measure.clone();
+        existing.barcode = product.barcode.clone();
+        existing.is_active = product.is_active;
+
+        Ok(product)
+    }
+
+    async fn delete_product(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        let tables = uow.state_mut();
+        tables.products.retain(|p| p.id != *id);
+        tables.units.retain(|u| u.product_id != *id);
+
+        Ok(())
+    }
+
+    // movements and order lines live in the stores of the other repositories,
+    // only the units are seen here
+    async fn check_product_in_use(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().units.iter().any(|u| u.product_id == *id))
+    }
+
+    async fn find_product_units(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Vec<ProductUnit>, sqlx::Error> {
+        let mut units: Vec<ProductUnit> = uow
+            .state()
+            .units
+            .iter()
+            .filter(|u| u.product_id == *product_id)
+            .cloned()
+            .collect();
+        units.sort_by(|a, b| a.code.cmp(&b.code));
+
+        Ok(units)
+    }
+
+    async fn get_product_unit_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<ProductUnit>, sqlx::Error> {
+        Ok(uow.state().units.iter().find(|u| u.id == *id).cloned())
+    }
+
+    async fn check_existing_product_unit_code(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .units
+            .iter()
+            .any(|u| u.product_id == *product_id && u.code == code && Some(&u.id) != id))
+    }
+
+    async fn create_product_unit(
+        &self,
+        uow: &mut Self::Uow,
+        unit: ProductUnit,
+    ) -> Result<ProductUnit, sqlx::Error> {
+        let tables = uow.state();
+        if tables.units.iter().any(|u| u.id == unit.id) {
+            return Err(unique_violation("product_units_pkey"));
+        }
+        check_unit_code(&tables.units, &unit)?;
+        if !tables.products.iter().any(|p| p.id == unit.product_id) {
+            return Err(foreign_key_violation("product_units_product_id_fkey"));
+        }
+        uow.state_mut().units.push(unit.clone());
+
+        Ok(unit)
+    }
+
+    async fn update_product_unit(
+        &self,
+        uow: &mut Self::Uow,
+        unit: ProductUnit,
+    ) -> Result<ProductUnit, sqlx::Error> {
+        check_unit_code(&uow.state().units, &unit)?;
+        let existing = uow
+            .state_mut()
+            .units
+            .iter_mut()
+            .find(|u| u.id == unit.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // product_id and created_at are not part of the UPDATE
+        existing.code = unit.code.clone();
+        existing.factor = unit.factor;
+        existing.decimal_places = unit.decimal_places;
+        existing.rounding = unit.rounding;
+        existing.is_purchase_unit = unit.is_purchase_unit;
+        existing.is_sales_unit = unit.is_sales_unit;
+
+        Ok(unit)
+    }
+
+    async fn delete_product_unit(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        uow.state_mut().units.retain(|u| u.id != *id);
+
+        Ok(())
+    }
+}
+
+fn check_unit_code(units: &[ProductUnit], unit: &ProductUnit) -> Result<(), sqlx::Error> {
+    let product_id = units
+        .iter()
+        .find(|u| u.id == unit.id)
+        .map_or(unit.product_id, |u| u.product_id);
+    if units
+        .iter()
+        .any(|u| u.id != unit.id && u.product_id == product_id && u.code == unit.code)
+    {
+        return Err(unique_violation("product_units_product_id_code_key"));
+    }
+    Ok(())
+}
+
+fn check_constraints(
+    products: &[Product],
+    product: &Product,
+    is_insert: bool,
+) -> Result<(), sqlx::Error> {
+    if is_insert && products.iter().any(|p| p.id == product.id) {
+        return Err(unique_violation("products_pkey"));
+    }
+    let company_id = products
+        .iter()
+        .find(|p| p.id == product.id)
+        .map_or(product.company_id, |p| p.company_id);
+    for other in products.iter().filter(|p| p.id != product.id) {
+        if other.company_id == company_id && other.sku == product.sku {
+            return Err(unique_violation("products_company_id_sku_key"));
+        }
+    }
+    Ok(())
+}
+
+// see helper_query::apply_product_filter
+fn search_products<'a>(
+    products: &'a [Product],
+    filter: &ProductFilter,
+    search: &Option<String>,
+) -> Vec<&'a Product> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    products
+        .iter()
+        .filter(|p| filter.company_id.is_none_or(|id| p.company_id == id))
+        .filter(|p| filter.is_active.is_none_or(|active| p.is_active == active))
+        .filter(|p| match &pattern {
+            Some(pattern) => {
+                ilike(&p.sku, pattern)
+                    || ilike(&p.name, pattern)
+                    || p.barcode.as_deref().is_some_and(|b| ilike(b, pattern))
+            }
+            None => true,
+        })
+        .collect()
+}
+
+fn sorted_products(
+    products: &[Product],
+    filter: &ProductFilter,
+    query: &PaginationRequest,
+) -> Result<Vec<Product>, sqlx::Error> {
+    let keys = sort_keys(query)?;
+    let mut products: Vec<Product> = search_products(products, filter, &query.search)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    // stable sort, rows equal on every key keep insertion order
+    products.sort_by(|a, b| {
+        keys.iter()
+            .map(|(column, descending)| {
+                let ordering = compare_column(a, b, column);
+                if *descending { ordering.reverse() } else { ordering }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    Ok(products)
+}
+
+// same parsing as PaginationRequest::format_sort, unknown columns fail like postgres does
+fn sort_keys(query: &PaginationRequest) -> Result<Vec<(String, bool)>, sqlx::Error> {
+    let Some(sort) = &query.sort else {
+        return Ok(Vec::new());
+    };
+
+    let mut keys = Vec::new();
+    for s in sort.split(",") {
+        let s = s.trim();
+        if s.is_empty() {
+            continue;
+        }
+
+        let (column, descending) = match s.strip_prefix("-") {
+            Some(column) => (column, true),
+            None => (s, false),
+        };
+        if !PRODUCT_SORT_COLUMNS.contains(&column) {
+            return Err(sqlx::Error::ColumnNotFound(column.to_string()));
+        }
+        keys.push((column.to_string(), descending));
+    }
+    Ok(keys)
+}
+
+fn compare_column(a: &Product, b: &Product, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "company_id" => a.company_id.cmp(&b.company_id),
+        "sku" => a.sku.cmp(&b.sku),
+        "name" => a.name.cmp(&b.name),
+        "description" => compare_nullable(&a.description, &b.description),
+        "category" => compare_nullable(&a.category, &b.category),
+        "unit_of_measure" => a.unit_of_measure.cmp(&b.unit_of_measure),
+        "barcode" => compare_nullable(&a.barcode, &b.barcode),
+        "is_active" => a.is_active.cmp(&b.is_active),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        _ => Ordering::Equal,
+    }
+}
+
+// postgres puts NULL after every value when sorting ascending
+fn compare_nullable(a: &Option<String>, b: &Option<String>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
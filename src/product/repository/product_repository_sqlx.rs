@@ -0,0 +1,390 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, QueryBuilder};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::{PgUnitOfWork, Transactional};
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::{ProductUnit, UnitRounding};
+use crate::product::repository::helper_query::apply_product_filter;
+use crate::product::repository::product_repository::ProductRepository;
+
+pub struct ProductRepositorySqlx {
+    pool: PgPool,
+}
+
+impl ProductRepositorySqlx {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Transactional for ProductRepositorySqlx {
+    type Uow = PgUnitOfWork;
+
+    #[instrument(level = "debug", skip_all)]
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.pool.begin().await
+    }
+}
+
+#[async_trait]
+impl ProductRepository for ProductRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(product_id = %id))]
+    async fn get_product_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        let product = sqlx::query_as!(
+            Product,
+            r#"
+            SELECT id, company_id, sku, name, description, category, unit_of_measure,
+                   barcode, is_active, is_lot_tracked, is_serialised, created_at
+            FROM products
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(product)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn count_all_products(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &ProductFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM products");
+
+        apply_product_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_all_products(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &ProductFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Product>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, sku, name, description, category, unit_of_measure,
+                   barcode, is_active, is_lot_tracked, is_serialised, created_at
+            FROM products
+        ",
+        );
+
+        apply_product_filter(&mut qb, filter, &query.search);
+
+        if let Some(s) = query.format_sort() {
+            qb.push(" ORDER BY ").push(s);
+        }
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let products = qb.build_query_as::<Product>().fetch_all(&mut **uow).await?;
+        Ok(products)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_company(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM companies WHERE id = $1)"#,
+            company_id
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id, sku = %sku))]
+    async fn check_existing_product_sku(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        sku: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM products
+                WHERE company_id = $1 AND sku = $2 AND id IS DISTINCT FROM $3
+            )
+            "#,
+            company_id,
+            sku,
+            id as Option<&Uuid>,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product.id))]
+    async fn create_product(
+        &self,
+        uow: &mut Self::Uow,
+        product: Product,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO products
+            (id, company_id, sku, name, description, category, unit_of_measure, barcode,
+             is_active, is_lot_tracked, is_serialised, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            product.id,
+            product.company_id,
+            product.sku,
+            product.name,
+            product.description,
+            product.category,
+            product.unit_of_measure,
+            product.barcode,
+            product.is_active,
+            product.is_lot_tracked,
+            product.is_serialised,
+            product.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(product)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product.id))]
+    async fn update_product(
+        &self,
+        uow: &mut Self::Uow,
+        product: Product,
+    ) -> Result<Product, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE products
+            SET sku = $1,
+                name = $2,
+                description = $3,
+                category = $4,
+                unit_of_measure = $5,
+                barcode = $6,
+                is_active = $7
+            WHERE id = $8
+            RETURNING id
+            "#,
+            product.sku,
+            product.name,
+            product.description,
+            product.category,
+            product.unit_of_measure,
+            product.barcode,
+            product.is_active,
+            product.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(product)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %id))]
+    async fn delete_product(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM products WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %id))]
+    async fn check_product_in_use(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_used = sqlx::query_scalar!(
+            r#"
+            SELECT
+                EXISTS (SELECT 1 FROM product_units WHERE product_id = $1)
+                OR EXISTS (SELECT 1 FROM stock_movements WHERE product_id = $1)
+                OR EXISTS (SELECT 1 FROM stock_transfer_lines WHERE product_id = $1)
+                OR EXISTS (SELECT 1 FROM purchase_order_lines WHERE product_id = $1)
+                OR EXISTS (SELECT 1 FROM sales_order_lines WHERE product_id = $1)
+            "#,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_used.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id))]
+    async fn find_product_units(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Vec<ProductUnit>, sqlx::Error> {
+        let units = sqlx::query_as!(
+            ProductUnit,
+            r#"
+            SELECT id, product_id, code, factor, decimal_places,
+                   rounding AS "rounding: UnitRounding", is_purchase_unit, is_sales_unit,
+                   created_at
+            FROM product_units
+            WHERE product_id = $1
+            ORDER BY code
+            "#,
+            product_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(units)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(unit_id = %id))]
+    async fn get_product_unit_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<ProductUnit>, sqlx::Error> {
+        let unit = sqlx::query_as!(
+            ProductUnit,
+            r#"
+            SELECT id, product_id, code, factor, decimal_places,
+                   rounding AS "rounding: UnitRounding", is_purchase_unit, is_sales_unit,
+                   created_at
+            FROM product_units
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(unit)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, code = %code))]
+    async fn check_existing_product_unit_code(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM product_units
+                WHERE product_id = $1 AND code = $2 AND id IS DISTINCT FROM $3
+            )
+            "#,
+            product_id,
+            code,
+            id as Option<&Uuid>,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(unit_id = %unit.id))]
+    async fn create_product_unit(
+        &self,
+        uow: &mut Self::Uow,
+        unit: ProductUnit,
+    ) -> Result<ProductUnit, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO product_units
+            (id, product_id, code, factor, decimal_places, rounding, is_purchase_unit,
+             is_sales_unit, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            unit.id,
+            unit.product_id,
+            unit.code,
+            unit.factor,
+            unit.decimal_places,
+            unit.rounding.as_str(),
+            unit.is_purchase_unit,
+            unit.is_sales_unit,
+            unit.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(unit)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(unit_id = %unit.id))]
+    async fn update_product_unit(
+        &self,
+        uow: &mut Self::Uow,
+        unit: ProductUnit,
+    ) -> Result<ProductUnit, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE product_units
+            SET code = $1,
+                factor = $2,
+                decimal_places = $3,
+                rounding = $4,
+                is_purchase_unit = $5,
+                is_sales_unit = $6
+            WHERE id = $7
+            RETURNING id
+            "#,
+            unit.code,
+            unit.factor,
+            unit.decimal_places,
+            unit.rounding.as_str(),
+            unit.is_purchase_unit,
+            unit.is_sales_unit,
+            unit.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(unit)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(unit_id = %id))]
+    async fn delete_product_unit(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM product_units WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::atuh_middleware::auth_middleware;
+use crate::product::handler::product_handler::{
+    create_product_handler, create_product_unit_handler, delete_product_handler,
+    delete_product_unit_handler, get_product_handler, get_product_units_handler,
+    get_products_handler, update_product_handler, update_product_unit_handler,
+};
+use crate::product::repository::product_repository::ProductRepository;
+use crate::product::usecase::product_usecase::ProductUsecase;
+use axum::{Router, routing::delete, routing::get, routing::post, routing::put};
+use axum::middleware;
+
+pub fn product_routes<R: ProductRepository + 'static>(repo: R, metrics: AppMetrics) -> Router {
+    let usecase = Arc::new(ProductUsecase::new(repo, metrics));
+
+    Router::new()
+        .route("/", get(get_products_handler))
+        .route("/", post(create_product_handler))
+        .route("/:id", get(get_product_handler))
+        .route("/:id", put(update_product_handler))
+        .route("/:id", delete(delete_product_handler))
+        .route("/:id/units", get(get_product_units_handler))
+        .route("/:id/units", post(create_product_unit_handler))
+        .route("/:id/units/:unit_id", put(update_product_unit_handler))
+        .route("/:id/units/:unit_id", delete(delete_product_unit_handler))
+        .with_state(usecase)
+        .layer(middleware::from_fn(auth_middleware))
+}
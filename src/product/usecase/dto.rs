@@ -0,0 +1,34 @@
+use rust_decimal::Decimal;
+
+use crate::product::domain::product::Product;
+use crate::product::domain::product_unit::UnitRounding;
+
+pub struct ListProductResult {
+    pub data: Vec<Product>,
+    pub total_data: i64,
+}
+
+// the fields a client sets, the owning company is given on create only
+pub struct ProductInput {
+    pub sku: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub unit_of_measure: String,
+    pub barcode: Option<String>,
+    pub is_active: bool,
+    // None keeps the current value, a new product is not lot tracked
+    pub is_lot_tracked: Option<bool>,
+    // None keeps the current value, a new product is not serialised
+    pub is_serialised: Option<bool>,
+}
+
+// a unit of the product besides its base unit, the product comes from the path
+pub struct ProductUnitInput {
+    pub code: String,
+    pub factor: Decimal,
+    pub decimal_places: i16,
+    pub rounding: UnitRounding,
+    pub is_purchase_unit: bool,
+    pub is_sales_unit: bool,
+}
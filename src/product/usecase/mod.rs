@@ -0,0 +1,4 @@
+pub mod product_usecase;
+pub mod dto;
+#[cfg(test)]
+mod product_usecase_test;
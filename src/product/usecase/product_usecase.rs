@@ -0,0 +1,506 @@
+use core::fmt;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::ProductUnit;
+use crate::product::repository::product_repository::ProductRepository;
+use crate::product::usecase::dto::{ListProductResult, ProductInput, ProductUnitInput};
+
+// names of the constraints on products, see migrations
+const PRODUCT_SKU_CONSTRAINT: &str = "products_company_id_sku_key";
+const PRODUCT_COMPANY_CONSTRAINT: &str = "products_company_id_fkey";
+const UNIT_CODE_CONSTRAINT: &str = "product_units_product_id_code_key";
+const UNIT_PRODUCT_CONSTRAINT: &str = "product_units_product_id_fkey";
+
+pub struct ProductUsecase<R: ProductRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum ProductUsecaseError {
+    SkuAlreadyExist,
+    CompanyNotFound,
+    NotFound,
+    // stock movements or balances still point at the product
+    InUse,
+    // lot tracking is set on create, existing stock has no lots
+    LotTrackingFixed,
+    // same for serials
+    SerialTrackingFixed,
+    // units, movements and order lines are counted in the base unit
+    BaseUnitFixed,
+    // lot tracked and serialised at once
+    TrackingConflict,
+    UnitNotFound,
+    // taken by another unit of the product or its base unit
+    UnitCodeAlreadyExist,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for ProductUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProductUsecaseError::SkuAlreadyExist => write!(f, "product sku already exists"),
+            ProductUsecaseError::CompanyNotFound => write!(f, "product company not found"),
+            ProductUsecaseError::NotFound => write!(f, "product not found"),
+            ProductUsecaseError::InUse => write!(f, "product is still in use"),
+            ProductUsecaseError::LotTrackingFixed => {
+                write!(f, "product lot tracking can not change")
+            }
+            ProductUsecaseError::SerialTrackingFixed => {
+                write!(f, "product serial tracking can not change")
+            }
+            ProductUsecaseError::BaseUnitFixed => {
+                write!(f, "product base unit can not change once it is in use")
+            }
+            ProductUsecaseError::TrackingConflict => {
+                write!(f, "product can not be lot tracked and serialised")
+            }
+            ProductUsecaseError::UnitNotFound => write!(f, "product unit not found"),
+            ProductUsecaseError::UnitCodeAlreadyExist => {
+                write!(f, "product unit code already exists")
+            }
+            ProductUsecaseError::DatabaseError(_) => write!(f, "product storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for ProductUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProductUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: ProductRepository> ProductUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, sku = %input.sku))]
+    pub async fn create_product(
+        &self,
+        company_id: Uuid,
+        input: ProductInput,
+    ) -> Result<Product, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_product_in(&mut uow, company_id, input).await;
+        let product = finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("create");
+        Ok(product)
+    }
+
+    #[instrument(skip_all, fields(product_id = %id))]
+    pub async fn get_product(&self, id: Uuid) -> Result<Product, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .repo
+            .get_product_by_id(&mut uow, &id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)
+            .and_then(|product| product.ok_or(ProductUsecaseError::NotFound));
+        finish(uow, result, ProductUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(product_id = %id, sku = %input.sku))]
+    pub async fn update_product(
+        &self,
+        id: Uuid,
+        input: ProductInput,
+    ) -> Result<Product, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_product_in(&mut uow, id, input).await;
+        let product = finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("update");
+        Ok(product)
+    }
+
+    #[instrument(skip_all, fields(product_id = %id))]
+    pub async fn delete_product(&self, id: Uuid) -> Result<(), ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_product_in(&mut uow, id).await;
+        finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_product(
+        &self,
+        filter: &ProductFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListProductResult, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+
+        let total_product = self
+            .repo
+            .count_all_products(&mut uow, filter, query)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+
+        let products = self
+            .repo
+            .find_all_products(&mut uow, filter, query)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+
+        let result = Ok(ListProductResult {
+            data: products,
+            total_data: total_product,
+        });
+        finish(uow, result, ProductUsecaseError::DatabaseError).await
+    }
+
+    // the units besides the base unit, ordered by code
+    #[instrument(skip_all, fields(product_id = %product_id))]
+    pub async fn list_units(
+        &self,
+        product_id: Uuid,
+    ) -> Result<Vec<ProductUnit>, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_units_in(&mut uow, product_id).await;
+        finish(uow, result, ProductUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(product_id = %product_id, code = %input.code))]
+    pub async fn create_unit(
+        &self,
+        product_id: Uuid,
+        input: ProductUnitInput,
+    ) -> Result<ProductUnit, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_unit_in(&mut uow, product_id, input).await;
+        let unit = finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("unit_create");
+        Ok(unit)
+    }
+
+    // existing order lines keep the base quantity they were converted to
+    #[instrument(skip_all, fields(product_id = %product_id, unit_id = %id, code = %input.code))]
+    pub async fn update_unit(
+        &self,
+        product_id: Uuid,
+        id: Uuid,
+        input: ProductUnitInput,
+    ) -> Result<ProductUnit, ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_unit_in(&mut uow, product_id, id, input).await;
+        let unit = finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("unit_update");
+        Ok(unit)
+    }
+
+    #[instrument(skip_all, fields(product_id = %product_id, unit_id = %id))]
+    pub async fn delete_unit(&self, product_id: Uuid, id: Uuid) -> Result<(), ProductUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_unit_in(&mut uow, product_id, id).await;
+        finish(uow, result, ProductUsecaseError::DatabaseError).await?;
+
+        self.metrics.product_operation("unit_delete");
+        Ok(())
+    }
+
+    async fn begin(&self) -> Result<R::Uow, ProductUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)
+    }
+
+    async fn create_product_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: ProductInput,
+    ) -> Result<Product, ProductUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, &company_id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(ProductUsecaseError::CompanyNotFound);
+        }
+        self.check_product_sku(uow, &company_id, &input.sku, None)
+            .await?;
+        let is_lot_tracked = input.is_lot_tracked.unwrap_or(false);
+        let is_serialised = input.is_serialised.unwrap_or(false);
+        if is_lot_tracked && is_serialised {
+            return Err(ProductUsecaseError::TrackingConflict);
+        }
+
+        let product = Product {
+            id: Uuid::new_v4(),
+            company_id,
+            sku: input.sku,
+            name: input.name,
+            description: input.description,
+            category: input.category,
+            unit_of_measure: input.unit_of_measure,
+            barcode: input.barcode,
+            is_active: input.is_active,
+            is_lot_tracked,
+            is_serialised,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_product(uow, product)
+            .await
+            .map_err(map_product_write_error)
+    }
+
+    async fn update_product_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: ProductInput,
+    ) -> Result<Product, ProductUsecaseError> {
+        let mut product = self
+            .repo
+            .get_product_by_id(uow, &id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?
+            .ok_or(ProductUsecaseError::NotFound)?;
+        if input
+            .is_lot_tracked
+            .is_some_and(|tracked| tracked != product.is_lot_tracked)
+        {
+            return Err(ProductUsecaseError::LotTrackingFixed);
+        }
+        if input
+            .is_serialised
+            .is_some_and(|serialised| serialised != product.is_serialised)
+        {
+            return Err(ProductUsecaseError::SerialTrackingFixed);
+        }
+        // with no units left the base unit can not clash with one of their codes
+        if input.unit_of_measure != product.unit_of_measure {
+            let is_used = self
+                .repo
+                .check_product_in_use(uow, &id)
+                .await
+                .map_err(ProductUsecaseError::DatabaseError)?;
+            if is_used {
+                return Err(ProductUsecaseError::BaseUnitFixed);
+            }
+        }
+
+        self.check_product_sku(uow, &product.company_id, &input.sku, Some(&id))
+            .await?;
+
+        product.sku = input.sku;
+        product.name = input.name;
+        product.description = input.description;
+        product.category = input.category;
+        product.unit_of_measure = input.unit_of_measure;
+        product.barcode = input.barcode;
+        product.is_active = input.is_active;
+
+        self.repo
+            .update_product(uow, product)
+            .await
+            .map_err(map_product_write_error)
+    }
+
+    async fn delete_product_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), ProductUsecaseError> {
+        let get_product = self
+            .repo
+            .get_product_by_id(uow, &id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+
+        if get_product.is_none() {
+            return Err(ProductUsecaseError::NotFound);
+        }
+        self.repo
+            .delete_product(uow, &id)
+            .await
+            .map_err(map_product_write_error)
+    }
+
+    async fn list_units_in(
+        &self,
+        uow: &mut R::Uow,
+        product_id: Uuid,
+    ) -> Result<Vec<ProductUnit>, ProductUsecaseError> {
+        self.get_product_in(uow, &product_id).await?;
+        self.repo
+            .find_product_units(uow, &product_id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)
+    }
+
+    async fn create_unit_in(
+        &self,
+        uow: &mut R::Uow,
+        product_id: Uuid,
+        input: ProductUnitInput,
+    ) -> Result<ProductUnit, ProductUsecaseError> {
+        let product = self.get_product_in(uow, &product_id).await?;
+        self.check_unit_code(uow, &product, &input.code, None)
+            .await?;
+
+        let unit = ProductUnit {
+            id: Uuid::new_v4(),
+            product_id,
+            code: input.code,
+            factor: input.factor.normalize(),
+            decimal_places: input.decimal_places,
+            rounding: input.rounding,
+            is_purchase_unit: input.is_purchase_unit,
+            is_sales_unit: input.is_sales_unit,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_product_unit(uow, unit)
+            .await
+            .map_err(map_product_write_error)
+    }
+
+    async fn update_unit_in(
+        &self,
+        uow: &mut R::Uow,
+        product_id: Uuid,
+        id: Uuid,
+        input: ProductUnitInput,
+    ) -> Result<ProductUnit, ProductUsecaseError> {
+        let product = self.get_product_in(uow, &product_id).await?;
+        let mut unit = self.get_unit_in(uow, &product_id, &id).await?;
+        self.check_unit_code(uow, &product, &input.code, Some(&id))
+            .await?;
+
+        unit.code = input.code;
+        unit.factor = input.factor.normalize();
+        unit.decimal_places = input.decimal_places;
+        unit.rounding = input.rounding;
+        unit.is_purchase_unit = input.is_purchase_unit;
+        unit.is_sales_unit = input.is_sales_unit;
+
+        self.repo
+            .update_product_unit(uow, unit)
+            .await
+            .map_err(map_product_write_error)
+    }
+
+    async fn delete_unit_in(
+        &self,
+        uow: &mut R::Uow,
+        product_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), ProductUsecaseError> {
+        self.get_unit_in(uow, &product_id, &id).await?;
+        self.repo
+            .delete_product_unit(uow, &id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)
+    }
+
+    async fn get_product_in(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<Product, ProductUsecaseError> {
+        self.repo
+            .get_product_by_id(uow, id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?
+            .ok_or(ProductUsecaseError::NotFound)
+    }
+
+    // a unit of another product is not found either
+    async fn get_unit_in(
+        &self,
+        uow: &mut R::Uow,
+        product_id: &Uuid,
+        id: &Uuid,
+    ) -> Result<ProductUnit, ProductUsecaseError> {
+        self.repo
+            .get_product_unit_by_id(uow, id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?
+            .filter(|unit| unit.product_id == *product_id)
+            .ok_or(ProductUsecaseError::UnitNotFound)
+    }
+
+    async fn check_unit_code(
+        &self,
+        uow: &mut R::Uow,
+        product: &Product,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), ProductUsecaseError> {
+        if product.unit_of_measure == code {
+            return Err(ProductUsecaseError::UnitCodeAlreadyExist);
+        }
+        let is_code_exist = self
+            .repo
+            .check_existing_product_unit_code(uow, &product.id, code, id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+        if is_code_exist {
+            return Err(ProductUsecaseError::UnitCodeAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_product_sku(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        sku: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), ProductUsecaseError> {
+        let is_sku_exist = self
+            .repo
+            .check_existing_product_sku(uow, company_id, sku, id)
+            .await
+            .map_err(ProductUsecaseError::DatabaseError)?;
+        if is_sku_exist {
+            return Err(ProductUsecaseError::SkuAlreadyExist);
+        }
+
+        Ok(())
+    }
+}
+
+// a concurrent unit of work can still claim the sku or delete the company
+// between the check and the write, the constraints catch that case. deleting a
+// product the stock ledger points at fails on its foreign keys
+fn map_product_write_error(err: sqlx::Error) -> ProductUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    let is_referenced = err
+        .as_database_error()
+        .is_some_and(|e| e.is_foreign_key_violation());
+    match constraint.as_deref() {
+        Some(PRODUCT_SKU_CONSTRAINT) => ProductUsecaseError::SkuAlreadyExist,
+        Some(PRODUCT_COMPANY_CONSTRAINT) => ProductUsecaseError::CompanyNotFound,
+        Some(UNIT_CODE_CONSTRAINT) => ProductUsecaseError::UnitCodeAlreadyExist,
+        Some(UNIT_PRODUCT_CONSTRAINT) => ProductUsecaseError::NotFound,
+        _ if is_referenced => ProductUsecaseError::InUse,
+        _ => ProductUsecaseError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,262 @@
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::{Product, ProductFilter};
+use crate::product::domain::product_unit::UnitRounding;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::usecase::dto::{ProductInput, ProductUnitInput};
+use crate::product::usecase::product_usecase::{ProductUsecase, ProductUsecaseError};
+
+fn input(sku: &str) -> ProductInput {
+    ProductInput {
+        sku: sku.into(),
+        name: format!("Product {sku}"),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: None,
+        is_serialised: None,
+    }
+}
+
+fn query(search: Option<&str>, sort: Option<&str>, page: u32, per_page: u32) -> PaginationRequest {
+    PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+// two companies, the clone of the repository shares the store so the test can
+// look at what was committed
+fn setup() -> (
+    ProductUsecase<ProductRepositoryMemory>,
+    ProductRepositoryMemory,
+    Company,
+    Company,
+) {
+    let (acme, globex) = (company("ACM"), company("GLB"));
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let repo = ProductRepositoryMemory::new(companies);
+    let usecase = ProductUsecase::new(repo.clone(), AppMetrics::new());
+    (usecase, repo, acme, globex)
+}
+
+async fn stored(repo: &ProductRepositoryMemory) -> Vec<Product> {
+    repo.products().await
+}
+
+#[tokio::test]
+async fn create_product_stores_product_of_company() {
+    let (usecase, repo, acme, _) = setup();
+
+    let created = usecase.create_product(acme.id, input("W-1")).await.unwrap();
+
+    assert_eq!(created.company_id, acme.id);
+    assert_eq!(stored(&repo).await, vec![created]);
+}
+
+#[tokio::test]
+async fn create_product_needs_an_existing_company() {
+    let (usecase, repo, _, _) = setup();
+
+    let result = usecase.create_product(Uuid::new_v4(), input("W-1")).await;
+
+    assert!(matches!(result, Err(ProductUsecaseError::CompanyNotFound)));
+    assert!(stored(&repo).await.is_empty());
+}
+
+#[tokio::test]
+async fn sku_is_unique_per_company() {
+    let (usecase, repo, acme, globex) = setup();
+    usecase.create_product(acme.id, input("W-1")).await.unwrap();
+
+    let result = usecase.create_product(acme.id, input("W-1")).await;
+    assert!(matches!(result, Err(ProductUsecaseError::SkuAlreadyExist)));
+
+    usecase
+        .create_product(globex.id, input("W-1"))
+        .await
+        .unwrap();
+    assert_eq!(stored(&repo).await.len(), 2);
+}
+
+#[tokio::test]
+async fn update_product_keeps_company_and_checks_sku() {
+    let (usecase, repo, acme, _) = setup();
+    let widget = usecase.create_product(acme.id, input("W-1")).await.unwrap();
+    usecase.create_product(acme.id, input("G-1")).await.unwrap();
+
+    let result = usecase.update_product(widget.id, input("G-1")).await;
+    assert!(matches!(result, Err(ProductUsecaseError::SkuAlreadyExist)));
+
+    let mut change = input("W-1");
+    change.name = "Widget".into();
+    change.is_active = false;
+    let updated = usecase.update_product(widget.id, change).await.unwrap();
+    assert_eq!(updated.company_id, acme.id);
+    assert_eq!(updated.created_at, widget.created_at);
+    assert!(!updated.is_active);
+    assert_eq!(stored(&repo).await[0], updated);
+
+    let result = usecase.update_product(Uuid::new_v4(), input("X-1")).await;
+    assert!(matches!(result, Err(ProductUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn lot_tracking_is_fixed_once_created() {
+    let (usecase, _, acme, _) = setup();
+    let mut tracked = input("L-1");
+    tracked.is_lot_tracked = Some(true);
+    let product = usecase.create_product(acme.id, tracked).await.unwrap();
+    assert!(product.is_lot_tracked);
+
+    // missing keeps the flag, a different value is refused
+    let updated = usecase
+        .update_product(product.id, input("L-1"))
+        .await
+        .unwrap();
+    assert!(updated.is_lot_tracked);
+    let mut change = input("L-1");
+    change.is_lot_tracked = Some(false);
+    let result = usecase.update_product(product.id, change).await;
+    assert!(matches!(result, Err(ProductUsecaseError::LotTrackingFixed)));
+}
+
+#[tokio::test]
+async fn base_unit_is_fixed_once_the_product_has_units() {
+    let (usecase, repo, acme, _) = setup();
+    let widget = usecase.create_product(acme.id, input("W-1")).await.unwrap();
+
+    let mut change = input("W-1");
+    change.unit_of_measure = "box".into();
+    let updated = usecase.update_product(widget.id, change).await.unwrap();
+    assert_eq!(updated.unit_of_measure, "box");
+
+    usecase
+        .create_unit(widget.id, unit_input("carton", "12"))
+        .await
+        .unwrap();
+    let result = usecase.update_product(widget.id, input("W-1")).await;
+    assert!(matches!(result, Err(ProductUsecaseError::BaseUnitFixed)));
+    assert_eq!(stored(&repo).await[0].unit_of_measure, "box");
+
+    // the rest of the product still changes while the base unit stays
+    let mut change = input("W-1");
+    change.unit_of_measure = "box".into();
+    change.name = "Widget".into();
+    let updated = usecase.update_product(widget.id, change).await.unwrap();
+    assert_eq!(updated.name, "Widget");
+}
+
+#[tokio::test]
+async fn get_and_delete_product() {
+    let (usecase, repo, acme, _) = setup();
+    let widget = usecase.create_product(acme.id, input("W-1")).await.unwrap();
+
+    assert_eq!(usecase.get_product(widget.id).await.unwrap(), widget);
+
+    usecase.delete_product(widget.id).await.unwrap();
+    assert!(stored(&repo).await.is_empty());
+    let result = usecase.get_product(widget.id).await;
+    assert!(matches!(result, Err(ProductUsecaseError::NotFound)));
+    let result = usecase.delete_product(widget.id).await;
+    assert!(matches!(result, Err(ProductUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn list_product_filters_by_company_and_pages() {
+    let (usecase, _, acme, globex) = setup();
+    for sku in ["C-1", "A-1", "B-1"] {
+        usecase.create_product(acme.id, input(sku)).await.unwrap();
+    }
+    usecase
+        .create_product(globex.id, input("A-2"))
+        .await
+        .unwrap();
+
+    let filter = ProductFilter {
+        company_id: Some(acme.id),
+        is_active: None,
+    };
+    let result = usecase
+        .list_product(&filter, &query(None, Some("sku"), 1, 2))
+        .await
+        .unwrap();
+
+    assert_eq!(result.total_data, 3);
+    let skus: Vec<&str> = result.data.iter().map(|p| p.sku.as_str()).collect();
+    assert_eq!(skus, vec!["A-1", "B-1"]);
+
+    let result = usecase
+        .list_product(&ProductFilter::default(), &query(Some("a-"), None, 1, 10))
+        .await
+        .unwrap();
+    assert_eq!(result.total_data, 2);
+}
+
+fn unit_input(code: &str, factor: &str) -> ProductUnitInput {
+    ProductUnitInput {
+        code: code.into(),
+        factor: factor.parse().unwrap(),
+        decimal_places: 0,
+        rounding: UnitRounding::Exact,
+        is_purchase_unit: true,
+        is_sales_unit: true,
+    }
+}
+
+#[tokio::test]
+async fn units_are_unique_per_product_and_never_the_base_unit() {
+    let (usecase, repo, acme, _) = setup();
+    let widget = usecase.create_product(acme.id, input("W-1")).await.unwrap();
+    let gadget = usecase.create_product(acme.id, input("G-1")).await.unwrap();
+
+    let carton = usecase
+        .create_unit(widget.id, unit_input("carton", "48.00"))
+        .await
+        .unwrap();
+    // stored normalised, so it reads back the way it was meant
+    assert_eq!(carton.factor.to_string(), "48");
+    usecase
+        .create_unit(gadget.id, unit_input("carton", "24"))
+        .await
+        .unwrap();
+
+    for code in ["carton", "pcs"] {
+        let result = usecase.create_unit(widget.id, unit_input(code, "6")).await;
+        assert!(
+            matches!(result, Err(ProductUsecaseError::UnitCodeAlreadyExist)),
+            "{code}"
+        );
+    }
+    // the base unit can not be renamed onto one of the units either
+    let mut change = input("W-1");
+    change.unit_of_measure = "carton".into();
+    let result = usecase.update_product(widget.id, change).await;
+    assert!(matches!(result, Err(ProductUsecaseError::BaseUnitFixed)));
+
+    let result = usecase
+        .update_unit(gadget.id, carton.id, unit_input("case", "6"))
+        .await;
+    assert!(matches!(result, Err(ProductUsecaseError::UnitNotFound)));
+    let result = usecase.list_units(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(ProductUsecaseError::NotFound)));
+
+    let case = usecase
+        .update_unit(widget.id, carton.id, unit_input("case", "0.5"))
+        .await
+        .unwrap();
+    assert_eq!(usecase.list_units(widget.id).await.unwrap(), vec![case]);
+    usecase.delete_unit(widget.id, carton.id).await.unwrap();
+    assert!(usecase.list_units(widget.id).await.unwrap().is_empty());
+    assert_eq!(repo.units().await.len(), 1);
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// goods received against a purchase order into one warehouse. it is posted when
+// it is created and never changes, every line points at its receipt movement
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct GoodsReceipt {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub purchase_order_id: Uuid,
+    pub number: String,
+    pub warehouse_id: Uuid,
+    pub note: Option<String>,
+    pub received_by: Uuid,
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct GoodsReceiptLine {
+    pub id: Uuid,
+    pub goods_receipt_id: Uuid,
+    /// order of the lines in the receipt, from 1
+    pub position: i32,
+    pub purchase_order_line_id: Uuid,
+    /// a location of the receipt's warehouse
+    pub location_id: Uuid,
+    /// the lot received, set for lot tracked products
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    /// the serials received, set for serialised products
+    pub serial_numbers: Vec<String>,
+    pub movement_id: Uuid,
+}
@@ -0,0 +1,3 @@
+pub mod goods_receipt;
+pub mod purchase_order;
+pub mod supplier;
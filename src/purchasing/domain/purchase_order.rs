@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// an order placed with a supplier. the lines are edited while it is a draft,
+// goods are only received against an approved order
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct PurchaseOrder {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub supplier_id: Uuid,
+    pub number: String,
+    pub status: PurchaseOrderStatus,
+    pub note: Option<String>,
+    pub over_receipt_tolerance_pct: i32,
+    pub under_receipt_tolerance_pct: i32,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl PurchaseOrder {
+    // the most a line may have received in total
+    pub fn receivable_quantity(&self, line: &PurchaseOrderLine) -> i64 {
+        let limit =
+            i128::from(line.quantity) * i128::from(100 + self.over_receipt_tolerance_pct) / 100;
+        i64::try_from(limit).unwrap_or(i64::MAX)
+    }
+
+    // short by no more than the under-receipt tolerance
+    pub fn is_line_complete(&self, line: &PurchaseOrderLine) -> bool {
+        i128::from(line.received_quantity) * 100
+            >= i128::from(line.quantity) * i128::from(100 - self.under_receipt_tolerance_pct)
+    }
+}
+
+// stored as text in purchase_orders.status. draft -> approved ->
+// partially_received -> closed, an order closes by itself once every line is
+// complete or is closed by hand to give up on the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Approved,
+    PartiallyReceived,
+    Closed,
+}
+
+impl PurchaseOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurchaseOrderStatus::Draft => "draft",
+            PurchaseOrderStatus::Approved => "approved",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Closed => "closed",
+        }
+    }
+
+    // approved and not closed, goods can be received
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            PurchaseOrderStatus::Approved | PurchaseOrderStatus::PartiallyReceived
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct PurchaseOrderLine {
+    pub id: Uuid,
+    pub purchase_order_id: Uuid,
+    /// order of the lines in the purchase order, from 1
+    pub position: i32,
+    pub product_id: Uuid,
+    /// in the product's base unit, converted from unit_quantity
+    pub quantity: i64,
+    /// the unit the line was entered in
+    pub unit: String,
+    /// the quantity as entered, in unit
+    pub unit_quantity: Decimal,
+    /// summed over every goods receipt
+    pub received_quantity: i64,
+}
+
+// narrows a purchase order listing on top of search, orders are always listed per company
+#[derive(Debug, Clone)]
+pub struct PurchaseOrderFilter {
+    pub company_id: Uuid,
+    pub supplier_id: Option<Uuid>,
+    pub status: Option<PurchaseOrderStatus>,
+}
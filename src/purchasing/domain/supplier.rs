@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// someone a company buys from. linked_company_id is set when the supplier is
+// itself a company in the system, standalone suppliers only have their contact
+// details. the tolerances are the defaults for new purchase orders
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct Supplier {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub linked_company_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    /// percent of the ordered quantity that may be received on top of it
+    pub over_receipt_tolerance_pct: i32,
+    /// percent of the ordered quantity that may be missing for a line to count as complete
+    pub under_receipt_tolerance_pct: i32,
+    pub created_at: DateTime<Utc>,
+}
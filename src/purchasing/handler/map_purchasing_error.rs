@@ -0,0 +1,140 @@
+use crate::app_response::error::ResponseError;
+use crate::purchasing::handler::types::ProcessSupplierRequest;
+use crate::purchasing::usecase::purchase_order_usecase::PurchaseOrderUsecaseError;
+use crate::purchasing::usecase::supplier_usecase::SupplierUsecaseError;
+use crate::stock::handler::map_stock_error::map_usecase_stock_error;
+
+pub fn map_usecase_supplier_error(err: SupplierUsecaseError) -> ResponseError {
+    match &err {
+        SupplierUsecaseError::CompanyNotFound => {
+            ResponseError::NotFound("company not found".into())
+        }
+        SupplierUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        SupplierUsecaseError::CodeAlreadyExist => {
+            ResponseError::BadRequest("code already exist".into())
+        }
+        SupplierUsecaseError::LinkedCompanyNotFound => {
+            ResponseError::BadRequest("linked company not found".into())
+        }
+        SupplierUsecaseError::SelfLink => {
+            ResponseError::BadRequest("a company can not be its own supplier".into())
+        }
+        SupplierUsecaseError::LinkAlreadyExist => {
+            ResponseError::BadRequest("linked company already has a supplier".into())
+        }
+        SupplierUsecaseError::InvalidTolerance => {
+            ResponseError::BadRequest("tolerance must be between 0 and 100".into())
+        }
+        SupplierUsecaseError::InUse => ResponseError::BadRequest("supplier is still in use".into()),
+        SupplierUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+pub fn map_usecase_purchase_order_error(err: PurchaseOrderUsecaseError) -> ResponseError {
+    match err {
+        PurchaseOrderUsecaseError::CompanyNotFound => {
+            ResponseError::NotFound("company not found".into())
+        }
+        PurchaseOrderUsecaseError::NotFound | PurchaseOrderUsecaseError::ReceiptNotFound => {
+            ResponseError::NotFound("data not found".into())
+        }
+        PurchaseOrderUsecaseError::NumberAlreadyExist => {
+            ResponseError::BadRequest("purchase order number already exists".into())
+        }
+        PurchaseOrderUsecaseError::SupplierNotFound => {
+            ResponseError::BadRequest("supplier not found".into())
+        }
+        PurchaseOrderUsecaseError::CompanyMismatch => ResponseError::BadRequest(
+            "supplier, products and warehouse must belong to the order's company".into(),
+        ),
+        PurchaseOrderUsecaseError::ProductNotFound => {
+            ResponseError::BadRequest("product not found".into())
+        }
+        PurchaseOrderUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        PurchaseOrderUsecaseError::UnitNotFound => {
+            ResponseError::BadRequest("unit not found".into())
+        }
+        PurchaseOrderUsecaseError::Conversion(err) => ResponseError::BadRequest(err.to_string()),
+        PurchaseOrderUsecaseError::InvalidTolerance => {
+            ResponseError::BadRequest("tolerance must be between 0 and 100".into())
+        }
+        PurchaseOrderUsecaseError::NoLines => {
+            ResponseError::BadRequest("lines are required".into())
+        }
+        PurchaseOrderUsecaseError::InvalidStatus(status) => {
+            ResponseError::BadRequest(format!("purchase order is {}", status.as_str()))
+        }
+        PurchaseOrderUsecaseError::LineNotFound => {
+            ResponseError::BadRequest("purchase order line not found".into())
+        }
+        PurchaseOrderUsecaseError::ReceiptNumberAlreadyExist => {
+            ResponseError::BadRequest("goods receipt number already exists".into())
+        }
+        PurchaseOrderUsecaseError::WarehouseNotFound => {
+            ResponseError::BadRequest("warehouse not found".into())
+        }
+        PurchaseOrderUsecaseError::LocationNotFound => {
+            ResponseError::BadRequest("location not found in the receipt's warehouse".into())
+        }
+        PurchaseOrderUsecaseError::OverReceipt {
+            line_id,
+            receivable,
+        } => ResponseError::BadRequest(format!(
+            "line {line_id} can receive at most {receivable} in total"
+        )),
+        PurchaseOrderUsecaseError::Posting(err) => map_usecase_stock_error(err),
+        PurchaseOrderUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column sizes of the suppliers table
+const MAX_SUPPLIER_CODE_LEN: usize = 20;
+const MAX_NAME_LEN: usize = 100;
+const MAX_EMAIL_LEN: usize = 100;
+const MAX_PHONE_NUMBER_LEN: usize = 20;
+
+pub fn validate_supplier_input(req: &ProcessSupplierRequest) -> Result<(), ResponseError> {
+    if req.code.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Code is required".into()));
+    }
+    if req.name.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Name is required".into()));
+    }
+
+    let lengths = [
+        ("Code", Some(&req.code), MAX_SUPPLIER_CODE_LEN),
+        ("Name", Some(&req.name), MAX_NAME_LEN),
+        ("Email", req.email.as_ref(), MAX_EMAIL_LEN),
+        (
+            "Phone number",
+            req.phone_number.as_ref(),
+            MAX_PHONE_NUMBER_LEN,
+        ),
+    ];
+    for (field, value, max) in lengths {
+        if value.is_some_and(|v| v.chars().count() > max) {
+            return Err(ResponseError::BadRequest(format!(
+                "{field} can not be longer than {max} characters"
+            )));
+        }
+    }
+    Ok(())
+}
+
+// column size of the document numbers, purchase_orders, goods_receipts,
+// sales_orders and shipments
+const MAX_NUMBER_LEN: usize = 30;
+
+pub fn validate_document_number(number: &str) -> Result<(), ResponseError> {
+    if number.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Number is required".into()));
+    }
+    if number.chars().count() > MAX_NUMBER_LEN {
+        return Err(ResponseError::BadRequest(format!(
+            "Number can not be longer than {MAX_NUMBER_LEN} characters"
+        )));
+    }
+    Ok(())
+}
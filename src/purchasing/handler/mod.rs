@@ -0,0 +1,8 @@
+pub mod map_purchasing_error;
+pub mod purchase_order_handler;
+#[cfg(test)]
+mod purchase_order_handler_test;
+pub mod supplier_handler;
+#[cfg(test)]
+mod supplier_handler_test;
+pub mod types;
@@ -0,0 +1,360 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::purchasing::domain::goods_receipt::GoodsReceipt;
+use crate::purchasing::domain::purchase_order::PurchaseOrder;
+use crate::purchasing::handler::map_purchasing_error::{
+    map_usecase_purchase_order_error, validate_document_number,
+};
+use crate::purchasing::handler::types::{
+    GoodsReceiptResponse, PostGoodsReceiptRequest, PostPurchaseOrderRequest,
+    PurchaseOrderFilterRequest, PurchaseOrderResponse, UpdatePurchaseOrderRequest,
+};
+use crate::purchasing::repository::helper_query::PURCHASE_ORDER_SORT_COLUMNS;
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::usecase::purchase_order_usecase::PurchaseOrderUsecase;
+use crate::stock::handler::map_stock_error::posted_by;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/purchasing/orders",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    request_body = PostPurchaseOrderRequest,
+    responses(
+        (status = 201, description = "draft purchase order created, tolerances left out are taken from the supplier", body = ResponseSuccessBody<PurchaseOrderResponse>),
+        (status = 400, description = "invalid input, number taken, or a supplier or product that is unknown or of another company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostPurchaseOrderRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+    let created_by = posted_by(&claims)?;
+
+    let order = usecase
+        .create_order(req.company_id, req.into(), created_by)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(PurchaseOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/purchasing/orders",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, PurchaseOrderFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's purchase orders without their lines, search matches the number", body = ResponseSuccessBody<Vec<PurchaseOrder>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_purchase_orders_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<PurchaseOrderFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&PURCHASE_ORDER_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let order_list_data = usecase
+        .list_orders(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        order_list_data.total_data as u64,
+        Some(order_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/purchasing/orders/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    responses(
+        (status = 200, description = "the purchase order with its lines and received quantities", body = ResponseSuccessBody<PurchaseOrderResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let order = usecase
+        .get_order(id)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(PurchaseOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/purchasing/orders/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    request_body = UpdatePurchaseOrderRequest,
+    responses(
+        (status = 200, description = "draft updated", body = ResponseSuccessBody<PurchaseOrderResponse>),
+        (status = 400, description = "invalid id or input, number taken, or the order is no longer a draft", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<UpdatePurchaseOrderRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+
+    let order = usecase
+        .update_order(id, req.into())
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(PurchaseOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/purchasing/orders/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    responses(
+        (status = 200, description = "draft deleted with its lines", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or the order is no longer a draft", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_order(id)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
+
+#[utoipa::path(
+    post,
+    path = "/purchasing/orders/{id}/approve",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    responses(
+        (status = 200, description = "order approved, goods can be received against it", body = ResponseSuccessBody<PurchaseOrderResponse>),
+        (status = 400, description = "invalid id, not a draft or no lines", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn approve_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let approved_by = posted_by(&claims)?;
+
+    let order = usecase
+        .approve_order(id, approved_by)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(PurchaseOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/purchasing/orders/{id}/close",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    responses(
+        (status = 200, description = "order closed, whatever was not received is given up", body = ResponseSuccessBody<PurchaseOrderResponse>),
+        (status = 400, description = "invalid id, or the order is a draft or already closed", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn close_purchase_order_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let order = usecase
+        .close_order(id)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(PurchaseOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/purchasing/orders/{id}/receipts",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    request_body = PostGoodsReceiptRequest,
+    responses(
+        (status = 201, description = "goods receipt posted into the warehouse, the order closes once every line is complete", body = ResponseSuccessBody<GoodsReceiptResponse>),
+        (status = 400, description = "invalid id or input, order not open, number taken, unknown line, location outside the warehouse, or over the receipt tolerance", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_goods_receipt_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostGoodsReceiptRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+    let received_by = posted_by(&claims)?;
+
+    let receipt = usecase
+        .receive_goods(id, req.into(), received_by)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(GoodsReceiptResponse::from(receipt)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/purchasing/orders/{id}/receipts",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "purchase order id")),
+    responses(
+        (status = 200, description = "the order's goods receipts without their lines, oldest first", body = ResponseSuccessBody<Vec<GoodsReceipt>>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "purchase order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_goods_receipts_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let receipts = usecase
+        .list_receipts(id)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(receipts)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/purchasing/receipts/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "goods receipt id")),
+    responses(
+        (status = 200, description = "the goods receipt with its lines and their movements", body = ResponseSuccessBody<GoodsReceiptResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "goods receipt not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_goods_receipt_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<PurchaseOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let receipt = usecase
+        .get_receipt(id)
+        .await
+        .map_err(map_usecase_purchase_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(GoodsReceiptResponse::from(receipt)),
+    ))
+}
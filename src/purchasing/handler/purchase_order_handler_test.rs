@@ -0,0 +1,393 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body};
+use crate::company::domain::company::Company;
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    supplier_id: String,
+    product_id: String,
+    warehouse_id: String,
+    location_id: String,
+}
+
+impl Fixture {
+    fn order(&self, number: &str, quantity: i64) -> Value {
+        json!({
+            "company_id": self.acme.id,
+            "supplier_id": self.supplier_id,
+            "number": number,
+            "lines": [{ "product_id": self.product_id, "quantity": quantity }],
+        })
+    }
+
+    fn receipt(&self, number: &str, line_id: &Value, quantity: i64) -> Value {
+        json!({
+            "number": number,
+            "warehouse_id": self.warehouse_id,
+            "lines": [{ "line_id": line_id, "location_id": self.location_id, "quantity": quantity }],
+        })
+    }
+
+    async fn on_hand(&self) -> Value {
+        let response = self
+            .app
+            .request(
+                Method::GET,
+                &format!(
+                    "/stock/balances?company_id={}&location_id={}",
+                    self.acme.id, self.location_id
+                ),
+                Some(&self.token),
+                None,
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+        response.json()["data"][0]["quantity"].clone()
+    }
+
+    // creates and approves an order, returned as (order uri, line id)
+    async fn approved(&self, number: &str, quantity: i64) -> (String, Value) {
+        let draft = self
+            .app
+            .create(
+                &self.token,
+                "/purchasing/orders",
+                self.order(number, quantity),
+            )
+            .await;
+        let uri = format!("/purchasing/orders/{}", draft["id"].as_str().unwrap());
+        let (status, _) = self
+            .app
+            .post(&self.token, &format!("{uri}/approve"), None)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        (uri, draft["lines"][0]["id"].clone())
+    }
+}
+
+// ACM buys bolts from a supplier allowing 10% over and 10% under receipt
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+
+    let supplier = app
+        .create(
+            &token,
+            &format!("/company/{}/suppliers", acme.id),
+            json!({
+                "code": "SUP",
+                "name": "Supplier",
+                "over_receipt_tolerance_pct": 10,
+                "under_receipt_tolerance_pct": 10,
+            }),
+        )
+        .await;
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "MAIN", "name": "Main" }),
+        )
+        .await;
+    let warehouse_id = warehouse["id"].as_str().unwrap().to_string();
+    let location = app
+        .create(
+            &token,
+            &format!("/warehouse/{warehouse_id}/locations"),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+
+    Fixture {
+        app,
+        token,
+        acme,
+        supplier_id: supplier["id"].as_str().unwrap().to_string(),
+        product_id: product["id"].as_str().unwrap().to_string(),
+        warehouse_id,
+        location_id: location["id"].as_str().unwrap().to_string(),
+    }
+}
+
+#[tokio::test]
+async fn purchasing_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/purchasing/orders?company_id={id}")),
+        (Method::POST, "/purchasing/orders".to_string()),
+        (Method::GET, format!("/purchasing/orders/{id}")),
+        (Method::PUT, format!("/purchasing/orders/{id}")),
+        (Method::DELETE, format!("/purchasing/orders/{id}")),
+        (Method::POST, format!("/purchasing/orders/{id}/approve")),
+        (Method::POST, format!("/purchasing/orders/{id}/close")),
+        (Method::GET, format!("/purchasing/orders/{id}/receipts")),
+        (Method::POST, format!("/purchasing/orders/{id}/receipts")),
+        (Method::GET, format!("/purchasing/receipts/{id}")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn order_is_received_into_stock_until_it_closes() {
+    let f = setup().await;
+
+    let draft = f
+        .app
+        .create(&f.token, "/purchasing/orders", f.order("PO-1", 10))
+        .await;
+    assert_eq!(draft["status"], "draft");
+    assert_eq!(draft["created_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(draft["over_receipt_tolerance_pct"], 10);
+    let uri = format!("/purchasing/orders/{}", draft["id"].as_str().unwrap());
+
+    let mut update = f.order("PO-1", 20);
+    update.as_object_mut().unwrap().remove("company_id");
+    update["under_receipt_tolerance_pct"] = json!(0);
+    let response = f
+        .app
+        .request(Method::PUT, &uri, Some(&f.token), Some(update))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    let body = response.json();
+    assert_eq!(body["data"]["lines"][0]["quantity"], 20);
+    assert_eq!(body["data"]["under_receipt_tolerance_pct"], 0);
+    let line_id = body["data"]["lines"][0]["id"].clone();
+
+    let receipts = format!("{uri}/receipts");
+    let (status, body) = f
+        .app
+        .post(&f.token, &receipts, Some(f.receipt("GR-1", &line_id, 5)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "purchase order is draft"));
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/approve"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "approved");
+    assert_eq!(body["data"]["approved_by"], TEST_ADMIN_ID.to_string());
+
+    let receipt = f
+        .app
+        .create(&f.token, &receipts, f.receipt("GR-1", &line_id, 15))
+        .await;
+    assert_eq!(receipt["received_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(receipt["lines"][0]["quantity"], 15);
+    assert_eq!(f.on_hand().await, 15);
+
+    let response = f.app.request(Method::GET, &uri, Some(&f.token), None).await;
+    let body = response.json();
+    assert_eq!(body["data"]["status"], "partially_received");
+    assert_eq!(body["data"]["lines"][0]["received_quantity"], 15);
+
+    let (status, body) = f
+        .app
+        .post(&f.token, &receipts, Some(f.receipt("GR-2", &line_id, 8)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            &format!(
+                "line {} can receive at most 22 in total",
+                line_id.as_str().unwrap()
+            )
+        )
+    );
+
+    f.app
+        .create(&f.token, &receipts, f.receipt("GR-2", &line_id, 7))
+        .await;
+    assert_eq!(f.on_hand().await, 22);
+    let response = f.app.request(Method::GET, &uri, Some(&f.token), None).await;
+    let body = response.json();
+    assert_eq!(body["data"]["status"], "closed");
+    assert!(body["data"]["closed_at"].is_string());
+
+    let response = f
+        .app
+        .request(Method::GET, &receipts, Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"][0]["number"], "GR-1");
+
+    let receipt_uri = format!("/purchasing/receipts/{}", receipt["id"].as_str().unwrap());
+    let response = f
+        .app
+        .request(Method::GET, &receipt_uri, Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json()["data"]["lines"][0]["movement_id"],
+        receipt["lines"][0]["movement_id"]
+    );
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!(
+                "/purchasing/orders?company_id={}&status=closed&search=po&sort=-number",
+                f.acme.id
+            ),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total_data"], 1);
+}
+
+#[tokio::test]
+async fn under_receipt_completes_or_closes_by_hand() {
+    let f = setup().await;
+
+    let (uri, line_id) = f.approved("PO-1", 10).await;
+    f.app
+        .create(
+            &f.token,
+            &format!("{uri}/receipts"),
+            f.receipt("GR-1", &line_id, 9),
+        )
+        .await;
+    let response = f.app.request(Method::GET, &uri, Some(&f.token), None).await;
+    assert_eq!(response.json()["data"]["status"], "closed");
+
+    let (uri, line_id) = f.approved("PO-2", 10).await;
+    f.app
+        .create(
+            &f.token,
+            &format!("{uri}/receipts"),
+            f.receipt("GR-2", &line_id, 5),
+        )
+        .await;
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/close"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "closed");
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/close"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "purchase order is closed"));
+
+    let response = f
+        .app
+        .request(Method::DELETE, &uri, Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(f.on_hand().await, 14);
+}
+
+#[tokio::test]
+async fn purchase_order_rejects_invalid_input() {
+    let f = setup().await;
+
+    let mut no_lines = f.order("PO-1", 1);
+    no_lines["lines"] = json!([]);
+    let mut tolerance = f.order("PO-1", 1);
+    tolerance["over_receipt_tolerance_pct"] = json!(150);
+    let mut unknown_supplier = f.order("PO-1", 1);
+    unknown_supplier["supplier_id"] = json!(Uuid::new_v4());
+    let cases = [
+        (f.order("", 1), "Number is required"),
+        (
+            f.order(&"P".repeat(31), 1),
+            "Number can not be longer than 30 characters",
+        ),
+        (f.order("PO-1", 0), "quantity is invalid"),
+        (tolerance, "tolerance must be between 0 and 100"),
+        (unknown_supplier, "supplier not found"),
+    ];
+    for (body, message) in cases {
+        let (status, body) = f.app.post(&f.token, "/purchasing/orders", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(body, error_body(400, message));
+    }
+
+    let (status, body) = f
+        .app
+        .post(&f.token, "/purchasing/orders", Some(no_lines))
+        .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let uri = format!(
+        "/purchasing/orders/{}",
+        body["data"]["id"].as_str().unwrap()
+    );
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/approve"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "lines are required"));
+
+    let (status, body) = f
+        .app
+        .post(&f.token, "/purchasing/orders", Some(f.order("PO-1", 1)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "purchase order number already exists")
+    );
+
+    let (uri, line_id) = f.approved("PO-2", 5).await;
+    let receipts = format!("{uri}/receipts");
+    let mut wrong_bin = f.receipt("GR-1", &line_id, 1);
+    wrong_bin["lines"][0]["location_id"] = json!(Uuid::new_v4());
+    let cases = [
+        (f.receipt("", &line_id, 1), "Number is required"),
+        (f.receipt("GR-1", &line_id, 0), "quantity is invalid"),
+        (
+            f.receipt("GR-1", &json!(Uuid::new_v4()), 1),
+            "purchase order line not found",
+        ),
+        (wrong_bin, "location not found in the receipt's warehouse"),
+    ];
+    for (body, message) in cases {
+        let (status, body) = f.app.post(&f.token, &receipts, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(body, error_body(400, message));
+    }
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/purchasing/orders?company_id={}&sort=note", f.acme.id),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/purchasing/receipts/{}", Uuid::new_v4()),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
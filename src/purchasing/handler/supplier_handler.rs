@@ -0,0 +1,190 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::handler::map_purchasing_error::{
+    map_usecase_supplier_error, validate_supplier_input,
+};
+use crate::purchasing::handler::types::ProcessSupplierRequest;
+use crate::purchasing::repository::helper_query::SUPPLIER_SORT_COLUMNS;
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::usecase::supplier_usecase::SupplierUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/company/{id}/suppliers",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    request_body = ProcessSupplierRequest,
+    responses(
+        (status = 201, description = "supplier created", body = ResponseSuccessBody<Supplier>),
+        (status = 400, description = "invalid input, taken code, or a linked company that is unknown, the company itself or already linked", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn create_supplier_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<SupplierUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+    Json(req): Json<ProcessSupplierRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_supplier_input(&req)?;
+
+    let supplier = usecase
+        .create_supplier(company_id, req.into())
+        .await
+        .map_err(map_usecase_supplier_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(supplier)))
+}
+
+// search matches code or name
+#[utoipa::path(
+    get,
+    path = "/company/{id}/suppliers",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id"), PaginationRequest),
+    responses(
+        (status = 200, description = "one page of the company's suppliers, meta holds the totals", body = ResponseSuccessBody<Vec<Supplier>>),
+        (status = 400, description = "invalid id or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_company_suppliers_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<SupplierUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+    Query(q): Query<PaginationRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&SUPPLIER_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let supplier_list_data = usecase
+        .list_company_suppliers(company_id, &query)
+        .await
+        .map_err(map_usecase_supplier_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        supplier_list_data.total_data as u64,
+        Some(supplier_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/supplier/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "supplier id")),
+    responses(
+        (status = 200, description = "the supplier", body = ResponseSuccessBody<Supplier>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "supplier not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_supplier_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<SupplierUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let supplier = usecase
+        .get_supplier(id)
+        .await
+        .map_err(map_usecase_supplier_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(supplier)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/supplier/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "supplier id")),
+    request_body = ProcessSupplierRequest,
+    responses(
+        (status = 200, description = "supplier updated, existing purchase orders keep their tolerances", body = ResponseSuccessBody<Supplier>),
+        (status = 400, description = "invalid input, taken code, or a linked company that is unknown, the company itself or already linked", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "supplier not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_supplier_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<SupplierUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<ProcessSupplierRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_supplier_input(&req)?;
+
+    let supplier = usecase
+        .update_supplier(id, req.into())
+        .await
+        .map_err(map_usecase_supplier_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(supplier)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/supplier/{id}",
+    tag = "purchasing",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "supplier id")),
+    responses(
+        (status = 200, description = "supplier deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or the supplier still has purchase orders", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "supplier not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_supplier_handler<R: PurchasingRepository>(
+    State(usecase): State<Arc<SupplierUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_supplier(id)
+        .await
+        .map_err(map_usecase_supplier_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
@@ -0,0 +1,143 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TestApp, error_body};
+
+#[tokio::test]
+async fn supplier_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/company/{id}/suppliers")),
+        (Method::POST, format!("/company/{id}/suppliers")),
+        (Method::GET, format!("/supplier/{id}")),
+        (Method::PUT, format!("/supplier/{id}")),
+        (Method::DELETE, format!("/supplier/{id}")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn supplier_crud_with_linked_company() {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+    let uri = format!("/company/{}/suppliers", acme.id);
+
+    let response = app
+        .request(
+            Method::POST,
+            &uri,
+            Some(&token),
+            Some(json!({
+                "linked_company_id": globex.id,
+                "code": "GLB",
+                "name": "Globex",
+                "over_receipt_tolerance_pct": 10,
+            })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{}", response.text());
+    let supplier = response.json()["data"].clone();
+    assert_eq!(supplier["linked_company_id"], globex.id.to_string());
+    assert_eq!(supplier["over_receipt_tolerance_pct"], 10);
+    assert_eq!(supplier["under_receipt_tolerance_pct"], 0);
+    let supplier_uri = format!("/supplier/{}", supplier["id"].as_str().unwrap());
+
+    let response = app
+        .request(
+            Method::POST,
+            &uri,
+            Some(&token),
+            Some(json!({ "code": "STD", "name": "Standalone" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("{uri}?search=glo&sort=-code"),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["meta"]["total_data"], 1);
+    assert_eq!(body["data"][0]["code"], "GLB");
+
+    let response = app
+        .request(
+            Method::PUT,
+            &supplier_uri,
+            Some(&token),
+            Some(json!({ "code": "GLB", "name": "Globex Corp", "under_receipt_tolerance_pct": 5 })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    let body = response.json();
+    assert_eq!(body["data"]["name"], "Globex Corp");
+    assert!(body["data"]["linked_company_id"].is_null());
+
+    let response = app
+        .request(Method::DELETE, &supplier_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app
+        .request(Method::GET, &supplier_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn supplier_rejects_invalid_input() {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+    let uri = format!("/company/{}/suppliers", acme.id);
+
+    let cases = [
+        (json!({ "code": "", "name": "Globex" }), "Code is required"),
+        (
+            json!({ "code": "GLB", "name": "N".repeat(101) }),
+            "Name can not be longer than 100 characters",
+        ),
+        (
+            json!({ "code": "GLB", "name": "Globex", "over_receipt_tolerance_pct": 101 }),
+            "tolerance must be between 0 and 100",
+        ),
+        (
+            json!({ "linked_company_id": acme.id, "code": "GLB", "name": "Globex" }),
+            "a company can not be its own supplier",
+        ),
+        (
+            json!({ "linked_company_id": Uuid::new_v4(), "code": "GLB", "name": "Globex" }),
+            "linked company not found",
+        ),
+    ];
+    for (body, message) in cases {
+        let response = app
+            .request(Method::POST, &uri, Some(&token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(response.error_json(), error_body(400, message));
+    }
+
+    let response = app
+        .request(
+            Method::POST,
+            &format!("/company/{}/suppliers", Uuid::new_v4()),
+            Some(&token),
+            Some(json!({ "code": "GLB", "name": "Globex" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "company not found"));
+}
@@ -0,0 +1,215 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{
+    PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine, PurchaseOrderStatus,
+};
+use crate::purchasing::usecase::dto::{
+    GoodsReceiptDetail, GoodsReceiptInput, GoodsReceiptLineInput, PurchaseOrderDetail,
+    PurchaseOrderInput, PurchaseOrderLineInput, SupplierInput,
+};
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProcessSupplierRequest {
+    /// the company this supplier is in the system, none for a standalone supplier
+    pub linked_company_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    /// percent of the ordered quantity that may be received on top, 0 to 100
+    #[serde(default)]
+    pub over_receipt_tolerance_pct: i32,
+    /// percent of the ordered quantity that may be missing for a line to be complete, 0 to 100
+    #[serde(default)]
+    pub under_receipt_tolerance_pct: i32,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PurchaseOrderLineRequest {
+    pub product_id: Uuid,
+    /// the product's unit_of_measure or one of its purchase units, the
+    /// unit_of_measure when missing
+    pub unit: Option<String>,
+    /// in unit, e.g. 2 or "1.5"
+    pub quantity: Decimal,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostPurchaseOrderRequest {
+    /// the supplier and every product belong to this company
+    pub company_id: Uuid,
+    pub supplier_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    /// defaults to the supplier's
+    pub over_receipt_tolerance_pct: Option<i32>,
+    /// defaults to the supplier's
+    pub under_receipt_tolerance_pct: Option<i32>,
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct UpdatePurchaseOrderRequest {
+    pub supplier_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    /// defaults to the supplier's
+    pub over_receipt_tolerance_pct: Option<i32>,
+    /// defaults to the supplier's
+    pub under_receipt_tolerance_pct: Option<i32>,
+    /// replace the lines of the draft
+    pub lines: Vec<PurchaseOrderLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GoodsReceiptLineRequest {
+    /// a line of the purchase order
+    pub line_id: Uuid,
+    /// a location of the receipt's warehouse
+    pub location_id: Uuid,
+    /// required for lot tracked products, see POST /stock/lots
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    /// one per unit for serialised products
+    #[serde(default)]
+    pub serial_numbers: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostGoodsReceiptRequest {
+    pub number: String,
+    pub warehouse_id: Uuid,
+    pub note: Option<String>,
+    /// lines of the order left out receive nothing
+    pub lines: Vec<GoodsReceiptLineRequest>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PurchaseOrderResponse {
+    #[serde(flatten)]
+    pub order: PurchaseOrder,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GoodsReceiptResponse {
+    #[serde(flatten)]
+    pub receipt: GoodsReceipt,
+    pub lines: Vec<GoodsReceiptLine>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurchaseOrderFilterRequest {
+    /// the purchase orders of this company
+    pub company_id: Uuid,
+    pub supplier_id: Option<Uuid>,
+    pub status: Option<PurchaseOrderStatus>,
+}
+
+impl From<ProcessSupplierRequest> for SupplierInput {
+    fn from(req: ProcessSupplierRequest) -> Self {
+        SupplierInput {
+            linked_company_id: req.linked_company_id,
+            code: req.code,
+            name: req.name,
+            email: req.email,
+            phone_number: req.phone_number,
+            address: req.address,
+            over_receipt_tolerance_pct: req.over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct: req.under_receipt_tolerance_pct,
+        }
+    }
+}
+
+impl From<PurchaseOrderLineRequest> for PurchaseOrderLineInput {
+    fn from(req: PurchaseOrderLineRequest) -> Self {
+        PurchaseOrderLineInput {
+            product_id: req.product_id,
+            unit: req.unit,
+            quantity: req.quantity,
+        }
+    }
+}
+
+impl From<PostPurchaseOrderRequest> for PurchaseOrderInput {
+    fn from(req: PostPurchaseOrderRequest) -> Self {
+        PurchaseOrderInput {
+            supplier_id: req.supplier_id,
+            number: req.number,
+            note: req.note,
+            over_receipt_tolerance_pct: req.over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct: req.under_receipt_tolerance_pct,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<UpdatePurchaseOrderRequest> for PurchaseOrderInput {
+    fn from(req: UpdatePurchaseOrderRequest) -> Self {
+        PurchaseOrderInput {
+            supplier_id: req.supplier_id,
+            number: req.number,
+            note: req.note,
+            over_receipt_tolerance_pct: req.over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct: req.under_receipt_tolerance_pct,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<GoodsReceiptLineRequest> for GoodsReceiptLineInput {
+    fn from(req: GoodsReceiptLineRequest) -> Self {
+        GoodsReceiptLineInput {
+            line_id: req.line_id,
+            location_id: req.location_id,
+            lot_id: req.lot_id,
+            quantity: req.quantity,
+            serial_numbers: req.serial_numbers,
+        }
+    }
+}
+
+impl From<PostGoodsReceiptRequest> for GoodsReceiptInput {
+    fn from(req: PostGoodsReceiptRequest) -> Self {
+        GoodsReceiptInput {
+            number: req.number,
+            warehouse_id: req.warehouse_id,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<PurchaseOrderDetail> for PurchaseOrderResponse {
+    fn from(detail: PurchaseOrderDetail) -> Self {
+        PurchaseOrderResponse {
+            order: detail.order,
+            lines: detail.lines,
+        }
+    }
+}
+
+impl From<GoodsReceiptDetail> for GoodsReceiptResponse {
+    fn from(detail: GoodsReceiptDetail) -> Self {
+        GoodsReceiptResponse {
+            receipt: detail.receipt,
+            lines: detail.lines,
+        }
+    }
+}
+
+impl From<PurchaseOrderFilterRequest> for PurchaseOrderFilter {
+    fn from(req: PurchaseOrderFilterRequest) -> Self {
+        PurchaseOrderFilter {
+            company_id: req.company_id,
+            supplier_id: req.supplier_id,
+            status: req.status,
+        }
+    }
+}
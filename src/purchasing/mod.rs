@@ -0,0 +1,5 @@
+pub mod domain;
+pub mod handler;
+pub mod repository;
+pub mod routes;
+pub mod usecase;
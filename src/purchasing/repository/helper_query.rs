@@ -0,0 +1,61 @@
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::purchasing::domain::purchase_order::PurchaseOrderFilter;
+
+// sortable columns, sort is pushed into ORDER BY as written so the handler
+// rejects anything else
+pub const SUPPLIER_SORT_COLUMNS: [&str; 6] = [
+    "id",
+    "linked_company_id",
+    "code",
+    "name",
+    "email",
+    "created_at",
+];
+
+pub const PURCHASE_ORDER_SORT_COLUMNS: [&str; 7] = [
+    "id",
+    "supplier_id",
+    "number",
+    "status",
+    "created_at",
+    "approved_at",
+    "closed_at",
+];
+
+pub const SUPPLIER_DEFAULT_ORDER: &str = "code";
+pub const PURCHASE_ORDER_DEFAULT_ORDER: &str = "created_at, id";
+
+pub fn apply_supplier_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    company_id: &Uuid,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(*company_id);
+    if let Some(s) = search {
+        qb.push(" AND (")
+            .push(" code ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(" OR name ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(")");
+    }
+}
+
+pub fn apply_purchase_order_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &PurchaseOrderFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(supplier_id) = filter.supplier_id {
+        qb.push(" AND supplier_id = ").push_bind(supplier_id);
+    }
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(s) = search {
+        qb.push(" AND number ILIKE ").push_bind(format!("%{s}%"));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod purchasing_repository;
+pub mod purchasing_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod purchasing_repository_memory;
+#[cfg(test)]
+mod purchasing_repository_contract_test;
+pub mod helper_query;
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::app_request::pagination::PaginationRequest;
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::stock::repository::stock_repository::StockRepository;
+
+// goods receipts post to the ledger in the same unit of work, so purchasing
+// lives on top of the stock repository
+#[async_trait]
+pub trait PurchasingRepository: StockRepository {
+    async fn get_supplier_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Supplier>, sqlx::Error>;
+    async fn count_company_suppliers(&self, uow: &mut Self::Uow, company_id: &Uuid, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_company_suppliers(&self, uow: &mut Self::Uow, company_id: &Uuid, query: &PaginationRequest) -> Result<Vec<Supplier>, sqlx::Error>;
+    async fn check_existing_supplier_code(&self, uow: &mut Self::Uow, company_id: &Uuid, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    // a company is linked to at most one supplier of each company
+    async fn check_existing_supplier_link(&self, uow: &mut Self::Uow, company_id: &Uuid, linked_company_id: &Uuid, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_supplier(&self, uow: &mut Self::Uow, supplier: Supplier) -> Result<Supplier, sqlx::Error>;
+    async fn update_supplier(&self, uow: &mut Self::Uow, supplier: Supplier) -> Result<Supplier, sqlx::Error>;
+    async fn delete_supplier(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+    async fn check_existing_purchase_order_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_purchase_order(&self, uow: &mut Self::Uow, order: PurchaseOrder) -> Result<PurchaseOrder, sqlx::Error>;
+    async fn update_purchase_order(&self, uow: &mut Self::Uow, order: PurchaseOrder) -> Result<PurchaseOrder, sqlx::Error>;
+    async fn get_purchase_order_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<PurchaseOrder>, sqlx::Error>;
+    // like get_purchase_order_by_id, the row stays locked until the unit of
+    // work finishes so receipts against one order queue up
+    async fn lock_purchase_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<PurchaseOrder>, sqlx::Error>;
+    // the lines go with it
+    async fn delete_purchase_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+    async fn count_purchase_orders(&self, uow: &mut Self::Uow, filter: &PurchaseOrderFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_purchase_orders(&self, uow: &mut Self::Uow, filter: &PurchaseOrderFilter, query: &PaginationRequest) -> Result<Vec<PurchaseOrder>, sqlx::Error>;
+    // ordered by position
+    async fn find_purchase_order_lines(&self, uow: &mut Self::Uow, purchase_order_id: &Uuid) -> Result<Vec<PurchaseOrderLine>, sqlx::Error>;
+    // drops the lines of the order and stores the given ones
+    async fn replace_purchase_order_lines(&self, uow: &mut Self::Uow, purchase_order_id: &Uuid, lines: Vec<PurchaseOrderLine>) -> Result<Vec<PurchaseOrderLine>, sqlx::Error>;
+    async fn update_purchase_order_received_quantity(&self, uow: &mut Self::Uow, line_id: &Uuid, received_quantity: i64) -> Result<(), sqlx::Error>;
+    async fn check_existing_goods_receipt_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str) -> Result<bool, sqlx::Error>;
+    async fn create_goods_receipt(&self, uow: &mut Self::Uow, receipt: GoodsReceipt, lines: Vec<GoodsReceiptLine>) -> Result<(GoodsReceipt, Vec<GoodsReceiptLine>), sqlx::Error>;
+    async fn get_goods_receipt_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<GoodsReceipt>, sqlx::Error>;
+    // ordered by position
+    async fn find_goods_receipt_lines(&self, uow: &mut Self::Uow, goods_receipt_id: &Uuid) -> Result<Vec<GoodsReceiptLine>, sqlx::Error>;
+    // oldest first
+    async fn find_purchase_order_receipts(&self, uow: &mut Self::Uow, purchase_order_id: &Uuid) -> Result<Vec<GoodsReceipt>, sqlx::Error>;
+}
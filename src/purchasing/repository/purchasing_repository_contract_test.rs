@@ -0,0 +1,734 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// stock_repository_contract_test. ACM buys from GLB and from a standalone
+// supplier into its north warehouse, GLB has a warehouse of its own. postgres
+// runs need TEST_DATABASE_URL and are skipped without it.
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{
+    ACME_ID, Catalogue, GLOBEX_ID, at, bin, companies, product, seed_catalogue, seed_companies,
+    warehouse,
+};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{
+    PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine, PurchaseOrderStatus,
+};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+const BOLT_ID: Uuid = Uuid::from_u128(11);
+const NUT_ID: Uuid = Uuid::from_u128(12);
+const NORTH_ID: Uuid = Uuid::from_u128(21);
+const GLOBEX_MAIN_ID: Uuid = Uuid::from_u128(23);
+const NORTH_BIN_ID: Uuid = Uuid::from_u128(31);
+const GLOBEX_BIN_ID: Uuid = Uuid::from_u128(33);
+const USER_ID: Uuid = Uuid::from_u128(99);
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                let products = super::ProductRepositoryMemory::new(companies.clone());
+                let warehouses = super::WarehouseRepositoryMemory::new(companies.clone());
+                super::seed_catalogue(&products, &warehouses, super::catalogue()).await;
+                super::$scenario(&super::StockRepositoryMemory::new(
+                    companies, products, warehouses,
+                ))
+                .await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::seed_catalogue(
+                    &super::ProductRepositorySqlx::new(db.pool.clone()),
+                    &super::WarehouseRepositorySqlx::new(db.pool.clone()),
+                    super::catalogue(),
+                )
+                .await;
+                super::$scenario(&super::StockRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(stores_and_searches_suppliers);
+contract_test!(enforces_supplier_constraints);
+contract_test!(stores_purchase_orders_and_lines);
+contract_test!(filters_sorts_and_pages_purchase_orders);
+contract_test!(stores_goods_receipts);
+
+fn catalogue() -> Catalogue {
+    Catalogue {
+        products: vec![
+            product(BOLT_ID, ACME_ID, "BOLT"),
+            product(NUT_ID, ACME_ID, "NUT"),
+        ],
+        warehouses: vec![
+            warehouse(NORTH_ID, ACME_ID, "NORTH"),
+            warehouse(GLOBEX_MAIN_ID, GLOBEX_ID, "MAIN"),
+        ],
+        locations: vec![
+            bin(NORTH_BIN_ID, NORTH_ID, "A"),
+            bin(GLOBEX_BIN_ID, GLOBEX_MAIN_ID, "A"),
+        ],
+    }
+}
+
+fn supplier(code: &str, linked_company_id: Option<Uuid>) -> Supplier {
+    Supplier {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        linked_company_id,
+        code: code.into(),
+        name: format!("Supplier {code}"),
+        email: None,
+        phone_number: None,
+        address: None,
+        over_receipt_tolerance_pct: 10,
+        under_receipt_tolerance_pct: 5,
+        created_at: at(0),
+    }
+}
+
+fn order(
+    supplier: &Supplier,
+    number: &str,
+    status: PurchaseOrderStatus,
+    minutes: i64,
+) -> PurchaseOrder {
+    PurchaseOrder {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        supplier_id: supplier.id,
+        number: number.into(),
+        status,
+        note: None,
+        over_receipt_tolerance_pct: 10,
+        under_receipt_tolerance_pct: 5,
+        created_by: USER_ID,
+        created_at: at(minutes),
+        approved_by: None,
+        approved_at: None,
+        closed_at: None,
+    }
+}
+
+fn line(
+    order: &PurchaseOrder,
+    position: i32,
+    product_id: Uuid,
+    quantity: i64,
+) -> PurchaseOrderLine {
+    PurchaseOrderLine {
+        id: Uuid::new_v4(),
+        purchase_order_id: order.id,
+        position,
+        product_id,
+        quantity,
+        unit: "pcs".into(),
+        unit_quantity: Decimal::from(quantity),
+        received_quantity: 0,
+    }
+}
+
+fn receipt(order: &PurchaseOrder, number: &str, minutes: i64) -> GoodsReceipt {
+    GoodsReceipt {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        purchase_order_id: order.id,
+        number: number.into(),
+        warehouse_id: NORTH_ID,
+        note: None,
+        received_by: USER_ID,
+        received_at: at(minutes),
+    }
+}
+
+fn receipt_line(
+    receipt: &GoodsReceipt,
+    position: i32,
+    line: &PurchaseOrderLine,
+    movement: &StockMovement,
+) -> GoodsReceiptLine {
+    GoodsReceiptLine {
+        id: Uuid::new_v4(),
+        goods_receipt_id: receipt.id,
+        position,
+        purchase_order_line_id: line.id,
+        location_id: NORTH_BIN_ID,
+        lot_id: None,
+        quantity: movement.quantity,
+        serial_numbers: Vec::new(),
+        movement_id: movement.id,
+    }
+}
+
+fn movement(product_id: Uuid, quantity: i64, minutes: i64) -> StockMovement {
+    StockMovement {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        product_id,
+        location_id: NORTH_BIN_ID,
+        movement_type: MovementType::Receipt,
+        quantity,
+        balance_after: quantity,
+        reversal_of: None,
+        source: MovementSource::Manual,
+        source_id: None,
+        lot_id: None,
+        reference: None,
+        note: None,
+        posted_by: USER_ID,
+        posted_at: at(minutes),
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+async fn seed_suppliers<R: PurchasingRepository>(repo: &R, suppliers: &[Supplier]) {
+    let mut uow = repo.begin().await.unwrap();
+    for supplier in suppliers {
+        repo.create_supplier(&mut uow, supplier.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+async fn seed_orders<R: PurchasingRepository>(
+    repo: &R,
+    orders: &[(PurchaseOrder, Vec<PurchaseOrderLine>)],
+) {
+    let mut uow = repo.begin().await.unwrap();
+    for (order, lines) in orders {
+        repo.create_purchase_order(&mut uow, order.clone())
+            .await
+            .unwrap();
+        repo.replace_purchase_order_lines(&mut uow, &order.id, lines.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn stores_and_searches_suppliers<R: PurchasingRepository>(repo: &R) {
+    let linked = supplier("GLB", Some(GLOBEX_ID));
+    let standalone = supplier("LOCAL", None);
+    seed_suppliers(repo, &[linked.clone(), standalone.clone()]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_supplier_by_id(&mut uow, &linked.id).await.unwrap();
+    assert_eq!(stored, Some(linked.clone()));
+    assert_eq!(
+        repo.get_supplier_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+
+    let cases = [
+        (ACME_ID, "GLB", None, true),
+        (ACME_ID, "GLB", Some(&linked.id), false),
+        (GLOBEX_ID, "GLB", None, false),
+    ];
+    for (company_id, code, id, expected) in cases {
+        let taken = repo
+            .check_existing_supplier_code(&mut uow, &company_id, code, id)
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{company_id} {code} {id:?}");
+    }
+    let cases = [(None, true), (Some(&linked.id), false)];
+    for (id, expected) in cases {
+        let taken = repo
+            .check_existing_supplier_link(&mut uow, &ACME_ID, &GLOBEX_ID, id)
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{id:?}");
+    }
+
+    let cases = [
+        (None, None, vec!["GLB", "LOCAL"]),
+        (None, Some("-code"), vec!["LOCAL", "GLB"]),
+        (Some("supplier loc"), None, vec!["LOCAL"]),
+    ];
+    for (search, sort, expected) in cases {
+        let query = query(search, sort, 10, 0);
+        let found = repo
+            .find_company_suppliers(&mut uow, &ACME_ID, &query)
+            .await
+            .unwrap();
+        let codes: Vec<&str> = found.iter().map(|s| s.code.as_str()).collect();
+        assert_eq!(codes, expected, "{search:?} {sort:?}");
+        let count = repo
+            .count_company_suppliers(&mut uow, &ACME_ID, &query)
+            .await
+            .unwrap();
+        assert_eq!(count, expected.len() as i64);
+    }
+
+    let updated = Supplier {
+        linked_company_id: None,
+        name: "Globex Trading".into(),
+        email: Some("sales@globex.com".into()),
+        over_receipt_tolerance_pct: 0,
+        ..linked.clone()
+    };
+    repo.update_supplier(&mut uow, updated.clone())
+        .await
+        .unwrap();
+    repo.delete_supplier(&mut uow, &standalone.id)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_supplier_by_id(&mut uow, &linked.id).await.unwrap();
+    assert_eq!(stored, Some(updated));
+    let stored = repo
+        .get_supplier_by_id(&mut uow, &standalone.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, None);
+    uow.rollback().await.unwrap();
+}
+
+async fn enforces_supplier_constraints<R: PurchasingRepository>(repo: &R) {
+    let stored = supplier("GLB", Some(GLOBEX_ID));
+    seed_suppliers(repo, std::slice::from_ref(&stored)).await;
+
+    let cases = [
+        (
+            Supplier {
+                id: stored.id,
+                ..supplier("OTHER", None)
+            },
+            "suppliers_pkey",
+        ),
+        (supplier("GLB", None), "suppliers_company_id_code_key"),
+        (
+            supplier("GLB2", Some(GLOBEX_ID)),
+            "suppliers_company_id_linked_company_id_key",
+        ),
+        (
+            Supplier {
+                company_id: Uuid::new_v4(),
+                ..supplier("OTHER", None)
+            },
+            "suppliers_company_id_fkey",
+        ),
+        (
+            supplier("OTHER", Some(Uuid::new_v4())),
+            "suppliers_linked_company_id_fkey",
+        ),
+    ];
+    for (supplier, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_supplier(&mut uow, supplier).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    // orders keep their supplier
+    let draft = order(&stored, "PO-1", PurchaseOrderStatus::Draft, 0);
+    seed_orders(repo, &[(draft, vec![])]).await;
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .delete_supplier(&mut uow, &stored.id)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("purchase_orders_supplier_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn stores_purchase_orders_and_lines<R: PurchasingRepository>(repo: &R) {
+    let acme_supplier = supplier("SUP", None);
+    let globex_supplier = Supplier {
+        company_id: GLOBEX_ID,
+        ..supplier("SUP", None)
+    };
+    seed_suppliers(repo, &[acme_supplier.clone(), globex_supplier.clone()]).await;
+    let draft = order(&acme_supplier, "PO-1", PurchaseOrderStatus::Draft, 0);
+    let lines = vec![line(&draft, 1, BOLT_ID, 100), line(&draft, 2, NUT_ID, 10)];
+    seed_orders(repo, &[(draft.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo
+        .get_purchase_order_by_id(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(draft.clone()));
+    let locked = repo.lock_purchase_order(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(locked, Some(draft.clone()));
+    assert_eq!(
+        repo.find_purchase_order_lines(&mut uow, &draft.id)
+            .await
+            .unwrap(),
+        lines
+    );
+    let cases = [
+        (ACME_ID, None, true),
+        (ACME_ID, Some(&draft.id), false),
+        (GLOBEX_ID, None, false),
+    ];
+    for (company_id, id, expected) in cases {
+        let taken = repo
+            .check_existing_purchase_order_number(&mut uow, &company_id, "PO-1", id)
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{company_id} {id:?}");
+    }
+
+    // the new lines replace the old ones in their own order
+    let replaced = vec![line(&draft, 1, NUT_ID, 20)];
+    repo.replace_purchase_order_lines(&mut uow, &draft.id, replaced.clone())
+        .await
+        .unwrap();
+    let approved = PurchaseOrder {
+        status: PurchaseOrderStatus::Approved,
+        note: Some("call before delivery".into()),
+        approved_by: Some(USER_ID),
+        approved_at: Some(at(5)),
+        ..draft.clone()
+    };
+    repo.update_purchase_order(&mut uow, approved.clone())
+        .await
+        .unwrap();
+    repo.update_purchase_order_received_quantity(&mut uow, &replaced[0].id, 7)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo
+        .get_purchase_order_by_id(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(approved));
+    let stored = repo
+        .find_purchase_order_lines(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        stored,
+        vec![PurchaseOrderLine {
+            received_quantity: 7,
+            ..replaced[0].clone()
+        }]
+    );
+
+    // a supplier of another company fails the composite key
+    let err = repo
+        .create_purchase_order(
+            &mut uow,
+            order(&globex_supplier, "PO-2", PurchaseOrderStatus::Draft, 1),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("purchase_orders_supplier_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .replace_purchase_order_lines(
+            &mut uow,
+            &draft.id,
+            vec![line(&draft, 1, Uuid::new_v4(), 1)],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("purchase_order_lines_product_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    // the lines go with the order
+    let mut uow = repo.begin().await.unwrap();
+    repo.delete_purchase_order(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_purchase_order_by_id(&mut uow, &draft.id)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repo.find_purchase_order_lines(&mut uow, &draft.id)
+            .await
+            .unwrap(),
+        vec![]
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn filters_sorts_and_pages_purchase_orders<R: PurchasingRepository>(repo: &R) {
+    let first_supplier = supplier("A", None);
+    let second_supplier = supplier("B", None);
+    seed_suppliers(repo, &[first_supplier.clone(), second_supplier.clone()]).await;
+    seed_orders(
+        repo,
+        &[
+            (
+                order(&first_supplier, "PO-2", PurchaseOrderStatus::Draft, 0),
+                vec![],
+            ),
+            (
+                order(&first_supplier, "PO-1", PurchaseOrderStatus::Approved, 1),
+                vec![],
+            ),
+            (
+                order(&second_supplier, "OTHER-1", PurchaseOrderStatus::Draft, 2),
+                vec![],
+            ),
+        ],
+    )
+    .await;
+
+    let filter =
+        |supplier_id: Option<Uuid>, status: Option<PurchaseOrderStatus>| PurchaseOrderFilter {
+            company_id: ACME_ID,
+            supplier_id,
+            status,
+        };
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (
+            filter(None, None),
+            None,
+            None,
+            10,
+            0,
+            vec!["PO-2", "PO-1", "OTHER-1"],
+        ),
+        (
+            filter(None, None),
+            None,
+            Some("number"),
+            10,
+            0,
+            vec!["OTHER-1", "PO-1", "PO-2"],
+        ),
+        (
+            filter(None, None),
+            None,
+            Some("-created_at"),
+            2,
+            1,
+            vec!["PO-1", "PO-2"],
+        ),
+        (
+            filter(None, Some(PurchaseOrderStatus::Draft)),
+            None,
+            None,
+            10,
+            0,
+            vec!["PO-2", "OTHER-1"],
+        ),
+        (
+            filter(Some(first_supplier.id), None),
+            Some("po-"),
+            Some("-number"),
+            10,
+            0,
+            vec!["PO-2", "PO-1"],
+        ),
+    ];
+    for (filter, search, sort, per_page, offset, expected) in cases {
+        let query = query(search, sort, per_page, offset);
+        let found = repo
+            .find_purchase_orders(&mut uow, &filter, &query)
+            .await
+            .unwrap();
+        let numbers: Vec<&str> = found.iter().map(|o| o.number.as_str()).collect();
+        assert_eq!(numbers, expected, "{search:?} {sort:?}");
+    }
+
+    let count = repo
+        .count_purchase_orders(
+            &mut uow,
+            &filter(None, None),
+            &query(Some("PO"), None, 1, 0),
+        )
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    let globex = PurchaseOrderFilter {
+        company_id: GLOBEX_ID,
+        supplier_id: None,
+        status: None,
+    };
+    let count = repo
+        .count_purchase_orders(&mut uow, &globex, &query(None, None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+    uow.rollback().await.unwrap();
+}
+
+async fn stores_goods_receipts<R: PurchasingRepository>(repo: &R) {
+    let acme_supplier = supplier("SUP", None);
+    seed_suppliers(repo, std::slice::from_ref(&acme_supplier)).await;
+    let approved = order(&acme_supplier, "PO-1", PurchaseOrderStatus::Approved, 0);
+    let lines = vec![
+        line(&approved, 1, BOLT_ID, 100),
+        line(&approved, 2, NUT_ID, 10),
+    ];
+    seed_orders(repo, &[(approved.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let bolts = movement(BOLT_ID, 60, 1);
+    let nuts = movement(NUT_ID, 10, 1);
+    for movement in [&bolts, &nuts] {
+        repo.create_movement(&mut uow, movement.clone())
+            .await
+            .unwrap();
+    }
+    let first = receipt(&approved, "GR-1", 1);
+    let first_lines = vec![
+        receipt_line(&first, 1, &lines[0], &bolts),
+        receipt_line(&first, 2, &lines[1], &nuts),
+    ];
+    let created = repo
+        .create_goods_receipt(&mut uow, first.clone(), first_lines.clone())
+        .await
+        .unwrap();
+    assert_eq!(created, (first.clone(), first_lines.clone()));
+    let more_bolts = movement(BOLT_ID, 40, 2);
+    repo.create_movement(&mut uow, more_bolts.clone())
+        .await
+        .unwrap();
+    let second = receipt(&approved, "GR-2", 2);
+    repo.create_goods_receipt(
+        &mut uow,
+        second.clone(),
+        vec![receipt_line(&second, 1, &lines[0], &more_bolts)],
+    )
+    .await
+    .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo
+        .get_goods_receipt_by_id(&mut uow, &first.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(first.clone()));
+    let stored = repo
+        .find_goods_receipt_lines(&mut uow, &first.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, first_lines);
+    let receipts = repo
+        .find_purchase_order_receipts(&mut uow, &approved.id)
+        .await
+        .unwrap();
+    assert_eq!(receipts, vec![first.clone(), second]);
+    let cases = [(ACME_ID, true), (GLOBEX_ID, false)];
+    for (company_id, expected) in cases {
+        let taken = repo
+            .check_existing_goods_receipt_number(&mut uow, &company_id, "GR-1")
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{company_id}");
+    }
+    uow.rollback().await.unwrap();
+
+    // an order with receipts can not go away
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .delete_purchase_order(&mut uow, &approved.id)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("goods_receipts_purchase_order_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let cases = [
+        (
+            receipt(&approved, "GR-1", 3),
+            vec![],
+            "goods_receipts_company_id_number_key",
+        ),
+        // a warehouse of another company fails the composite key
+        (
+            GoodsReceipt {
+                warehouse_id: GLOBEX_MAIN_ID,
+                ..receipt(&approved, "GR-3", 3)
+            },
+            vec![],
+            "goods_receipts_warehouse_fkey",
+        ),
+        (
+            receipt(&approved, "GR-3", 3),
+            vec![GoodsReceiptLine {
+                movement_id: Uuid::new_v4(),
+                ..first_lines[0].clone()
+            }],
+            "goods_receipt_lines_movement_id_fkey",
+        ),
+    ];
+    for (receipt, lines, constraint) in cases {
+        let lines = lines
+            .into_iter()
+            .map(|line| GoodsReceiptLine {
+                id: Uuid::new_v4(),
+                goods_receipt_id: receipt.id,
+                ..line
+            })
+            .collect();
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo
+            .create_goods_receipt(&mut uow, receipt, lines)
+            .await
+            .unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+}
@@ -0,0 +1,599 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::app_database::memory::{foreign_key_violation, ilike, unique_violation};
+use crate::app_request::pagination::PaginationRequest;
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{
+    PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine,
+};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::repository::helper_query::{
+    PURCHASE_ORDER_SORT_COLUMNS, SUPPLIER_SORT_COLUMNS,
+};
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::stock::repository::stock_repository_memory::{
+    StockRepositoryMemory, StockTables, compare_by, compare_nullable, page, sort_keys,
+};
+
+// mirrors the PurchasingRepository part of StockRepositorySqlx. suppliers,
+// orders and receipts share the stock store, so the foreign keys between them
+// are checked against the staged rows of the unit of work
+impl StockRepositoryMemory {
+    async fn check_supplier_references(&self, supplier: &Supplier) -> Result<(), sqlx::Error> {
+        if !self.company_exists(&supplier.company_id).await {
+            return Err(foreign_key_violation("suppliers_company_id_fkey"));
+        }
+        if let Some(linked_company_id) = &supplier.linked_company_id
+            && !self.company_exists(linked_company_id).await
+        {
+            return Err(foreign_key_violation("suppliers_linked_company_id_fkey"));
+        }
+        Ok(())
+    }
+}
+
+fn check_supplier_keys(suppliers: &[Supplier], supplier: &Supplier) -> Result<(), sqlx::Error> {
+    for other in suppliers
+        .iter()
+        .filter(|s| s.id != supplier.id && s.company_id == supplier.company_id)
+    {
+        if other.code == supplier.code {
+            return Err(unique_violation("suppliers_company_id_code_key"));
+        }
+        if supplier.linked_company_id.is_some()
+            && other.linked_company_id == supplier.linked_company_id
+        {
+            return Err(unique_violation(
+                "suppliers_company_id_linked_company_id_key",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_purchase_order_supplier(
+    tables: &StockTables,
+    order: &PurchaseOrder,
+) -> Result<(), sqlx::Error> {
+    let is_supplier_of_company = tables
+        .suppliers
+        .iter()
+        .any(|s| s.id == order.supplier_id && s.company_id == order.company_id);
+    if !is_supplier_of_company {
+        return Err(foreign_key_violation("purchase_orders_supplier_fkey"));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PurchasingRepository for StockRepositoryMemory {
+    async fn get_supplier_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Supplier>, sqlx::Error> {
+        Ok(uow.state().suppliers.iter().find(|s| s.id == *id).cloned())
+    }
+
+    async fn count_company_suppliers(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_suppliers(&uow.state().suppliers, company_id, &query.search).len() as i64)
+    }
+
+    async fn find_company_suppliers(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Supplier>, sqlx::Error> {
+        let keys = sort_keys(query, &SUPPLIER_SORT_COLUMNS)?;
+        let mut suppliers: Vec<Supplier> =
+            filter_suppliers(&uow.state().suppliers, company_id, &query.search)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            suppliers.sort_by(|a, b| a.code.cmp(&b.code));
+        } else {
+            suppliers.sort_by(|a, b| compare_by(&keys, |column| compare_supplier(a, b, column)));
+        }
+
+        Ok(page(suppliers, query))
+    }
+
+    async fn check_existing_supplier_code(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().suppliers.iter().any(|s| {
+            s.company_id == *company_id && s.code == code && id.is_none_or(|id| s.id != *id)
+        }))
+    }
+
+    async fn check_existing_supplier_link(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        linked_company_id: &Uuid,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().suppliers.iter().any(|s| {
+            s.company_id == *company_id
+                && s.linked_company_id == Some(*linked_company_id)
+                && id.is_none_or(|id| s.id != *id)
+        }))
+    }
+
+    async fn create_supplier(
+        &self,
+        uow: &mut Self::Uow,
+        supplier: Supplier,
+    ) -> Result<Supplier, sqlx::Error> {
+        let suppliers = &uow.state().suppliers;
+        if suppliers.iter().any(|s| s.id == supplier.id) {
+            return Err(unique_violation("suppliers_pkey"));
+        }
+        check_supplier_keys(suppliers, &supplier)?;
+        self.check_supplier_references(&supplier).await?;
+        uow.state_mut().suppliers.push(supplier.clone());
+
+        Ok(supplier)
+    }
+
+    async fn update_supplier(
+        &self,
+        uow: &mut Self::Uow,
+        supplier: Supplier,
+    ) -> Result<Supplier, sqlx::Error> {
+        check_supplier_keys(&uow.state().suppliers, &supplier)?;
+        self.check_supplier_references(&supplier).await?;
+        let stored = uow
+            .state_mut()
+            .suppliers
+            .iter_mut()
+            .find(|s| s.id == supplier.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // the owning company never changes
+        *stored = Supplier {
+            company_id: stored.company_id,
+            created_at: stored.created_at,
+            ..supplier.clone()
+        };
+
+        Ok(supplier)
+    }
+
+    async fn delete_supplier(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        if uow
+            .state()
+            .purchase_orders
+            .iter()
+            .any(|o| o.supplier_id == *id)
+        {
+            return Err(foreign_key_violation("purchase_orders_supplier_fkey"));
+        }
+        uow.state_mut().suppliers.retain(|s| s.id != *id);
+
+        Ok(())
+    }
+
+    async fn check_existing_purchase_order_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().purchase_orders.iter().any(|o| {
+            o.company_id == *company_id && o.number == number && id.is_none_or(|id| o.id != *id)
+        }))
+    }
+
+    async fn create_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: PurchaseOrder,
+    ) -> Result<PurchaseOrder, sqlx::Error> {
+        let orders = &uow.state().purchase_orders;
+        if orders.iter().any(|o| o.id == order.id) {
+            return Err(unique_violation("purchase_orders_pkey"));
+        }
+        if orders
+            .iter()
+            .any(|o| o.company_id == order.company_id && o.number == order.number)
+        {
+            return Err(unique_violation("purchase_orders_company_id_number_key"));
+        }
+        if !self.company_exists(&order.company_id).await {
+            return Err(foreign_key_violation("purchase_orders_company_id_fkey"));
+        }
+        check_purchase_order_supplier(uow.state(), &order)?;
+        uow.state_mut().purchase_orders.push(order.clone());
+
+        Ok(order)
+    }
+
+    async fn update_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: PurchaseOrder,
+    ) -> Result<PurchaseOrder, sqlx::Error> {
+        if uow.state().purchase_orders.iter().any(|o| {
+            o.id != order.id && o.company_id == order.company_id && o.number == order.number
+        }) {
+            return Err(unique_violation("purchase_orders_company_id_number_key"));
+        }
+        check_purchase_order_supplier(uow.state(), &order)?;
+        let stored = uow
+            .state_mut()
+            .purchase_orders
+            .iter_mut()
+            .find(|o| o.id == order.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // the company and the creator never change
+        *stored = PurchaseOrder {
+            company_id: stored.company_id,
+            created_by: stored.created_by,
+            created_at: stored.created_at,
+            ..order.clone()
+        };
+
+        Ok(order)
+    }
+
+    async fn get_purchase_order_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<PurchaseOrder>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .purchase_orders
+            .iter()
+            .find(|o| o.id == *id)
+            .cloned())
+    }
+
+    async fn lock_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<PurchaseOrder>, sqlx::Error> {
+        self.get_purchase_order_by_id(uow, id).await
+    }
+
+    async fn delete_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        if uow
+            .state()
+            .goods_receipts
+            .iter()
+            .any(|r| r.purchase_order_id == *id)
+        {
+            return Err(foreign_key_violation("goods_receipts_purchase_order_fkey"));
+        }
+        let tables = uow.state_mut();
+        tables.purchase_orders.retain(|o| o.id != *id);
+        tables
+            .purchase_order_lines
+            .retain(|l| l.purchase_order_id != *id);
+
+        Ok(())
+    }
+
+    async fn count_purchase_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(
+            filter_purchase_orders(&uow.state().purchase_orders, filter, &query.search).len()
+                as i64,
+        )
+    }
+
+    async fn find_purchase_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<PurchaseOrder>, sqlx::Error> {
+        let keys = sort_keys(query, &PURCHASE_ORDER_SORT_COLUMNS)?;
+        let mut orders: Vec<PurchaseOrder> =
+            filter_purchase_orders(&uow.state().purchase_orders, filter, &query.search)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            orders.sort_by_key(|o| (o.created_at, o.id));
+        } else {
+            orders.sort_by(|a, b| compare_by(&keys, |column| compare_purchase_order(a, b, column)));
+        }
+
+        Ok(page(orders, query))
+    }
+
+    async fn find_purchase_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+    ) -> Result<Vec<PurchaseOrderLine>, sqlx::Error> {
+        let mut lines: Vec<PurchaseOrderLine> = uow
+            .state()
+            .purchase_order_lines
+            .iter()
+            .filter(|l| l.purchase_order_id == *purchase_order_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn replace_purchase_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+        lines: Vec<PurchaseOrderLine>,
+    ) -> Result<Vec<PurchaseOrderLine>, sqlx::Error> {
+        let mut kept: Vec<PurchaseOrderLine> = uow
+            .state()
+            .purchase_order_lines
+            .iter()
+            .filter(|l| l.purchase_order_id != *purchase_order_id)
+            .cloned()
+            .collect();
+        for line in &lines {
+            if kept.iter().any(|l| l.id == line.id) {
+                return Err(unique_violation("purchase_order_lines_pkey"));
+            }
+            if kept.iter().any(|l| {
+                l.purchase_order_id == line.purchase_order_id && l.position == line.position
+            }) {
+                return Err(unique_violation(
+                    "purchase_order_lines_purchase_order_id_position_key",
+                ));
+            }
+            if !uow
+                .state()
+                .purchase_orders
+                .iter()
+                .any(|o| o.id == line.purchase_order_id)
+            {
+                return Err(foreign_key_violation(
+                    "purchase_order_lines_purchase_order_id_fkey",
+                ));
+            }
+            if self.product_company(&line.product_id).await.is_none() {
+                return Err(foreign_key_violation(
+                    "purchase_order_lines_product_id_fkey",
+                ));
+            }
+            kept.push(line.clone());
+        }
+        uow.state_mut().purchase_order_lines = kept;
+
+        Ok(lines)
+    }
+
+    async fn update_purchase_order_received_quantity(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        received_quantity: i64,
+    ) -> Result<(), sqlx::Error> {
+        let line = uow
+            .state_mut()
+            .purchase_order_lines
+            .iter_mut()
+            .find(|l| l.id == *line_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        line.received_quantity = received_quantity;
+
+        Ok(())
+    }
+
+    async fn check_existing_goods_receipt_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .goods_receipts
+            .iter()
+            .any(|r| r.company_id == *company_id && r.number == number))
+    }
+
+    async fn create_goods_receipt(
+        &self,
+        uow: &mut Self::Uow,
+        receipt: GoodsReceipt,
+        lines: Vec<GoodsReceiptLine>,
+    ) -> Result<(GoodsReceipt, Vec<GoodsReceiptLine>), sqlx::Error> {
+        let tables = uow.state();
+        if tables.goods_receipts.iter().any(|r| r.id == receipt.id) {
+            return Err(unique_violation("goods_receipts_pkey"));
+        }
+        if tables
+            .goods_receipts
+            .iter()
+            .any(|r| r.company_id == receipt.company_id && r.number == receipt.number)
+        {
+            return Err(unique_violation("goods_receipts_company_id_number_key"));
+        }
+        if !tables
+            .purchase_orders
+            .iter()
+            .any(|o| o.id == receipt.purchase_order_id && o.company_id == receipt.company_id)
+        {
+            return Err(foreign_key_violation("goods_receipts_purchase_order_fkey"));
+        }
+        if self.warehouse_company(&receipt.warehouse_id).await != Some(receipt.company_id) {
+            return Err(foreign_key_violation("goods_receipts_warehouse_fkey"));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let earlier = &lines[..i];
+            if tables.goods_receipt_lines.iter().any(|l| l.id == line.id)
+                || earlier.iter().any(|l| l.id == line.id)
+            {
+                return Err(unique_violation("goods_receipt_lines_pkey"));
+            }
+            if earlier.iter().any(|l| l.position == line.position) {
+                return Err(unique_violation(
+                    "goods_receipt_lines_goods_receipt_id_position_key",
+                ));
+            }
+            if !tables
+                .purchase_order_lines
+                .iter()
+                .any(|l| l.id == line.purchase_order_line_id)
+            {
+                return Err(foreign_key_violation(
+                    "goods_receipt_lines_purchase_order_line_id_fkey",
+                ));
+            }
+            if self.location_warehouse(&line.location_id).await.is_none() {
+                return Err(foreign_key_violation(
+                    "goods_receipt_lines_location_id_fkey",
+                ));
+            }
+            if let Some(lot_id) = line.lot_id
+                && !tables.lots.iter().any(|l| l.id == lot_id)
+            {
+                return Err(foreign_key_violation("goods_receipt_lines_lot_id_fkey"));
+            }
+            if !tables.movements.iter().any(|m| m.id == line.movement_id) {
+                return Err(foreign_key_violation(
+                    "goods_receipt_lines_movement_id_fkey",
+                ));
+            }
+        }
+        let tables = uow.state_mut();
+        tables.goods_receipts.push(receipt.clone());
+        tables.goods_receipt_lines.extend(lines.iter().cloned());
+
+        Ok((receipt, lines))
+    }
+
+    async fn get_goods_receipt_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<GoodsReceipt>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .goods_receipts
+            .iter()
+            .find(|r| r.id == *id)
+            .cloned())
+    }
+
+    async fn find_goods_receipt_lines(
+        &self,
+        uow: &mut Self::Uow,
+        goods_receipt_id: &Uuid,
+    ) -> Result<Vec<GoodsReceiptLine>, sqlx::Error> {
+        let mut lines: Vec<GoodsReceiptLine> = uow
+            .state()
+            .goods_receipt_lines
+            .iter()
+            .filter(|l| l.goods_receipt_id == *goods_receipt_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn find_purchase_order_receipts(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+    ) -> Result<Vec<GoodsReceipt>, sqlx::Error> {
+        let mut receipts: Vec<GoodsReceipt> = uow
+            .state()
+            .goods_receipts
+            .iter()
+            .filter(|r| r.purchase_order_id == *purchase_order_id)
+            .cloned()
+            .collect();
+        receipts.sort_by_key(|r| (r.received_at, r.id));
+
+        Ok(receipts)
+    }
+}
+
+// see helper_query::apply_supplier_filter
+fn filter_suppliers<'a>(
+    suppliers: &'a [Supplier],
+    company_id: &Uuid,
+    search: &Option<String>,
+) -> Vec<&'a Supplier> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    suppliers
+        .iter()
+        .filter(|s| s.company_id == *company_id)
+        .filter(|s| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| ilike(&s.code, p) || ilike(&s.name, p))
+        })
+        .collect()
+}
+
+// see helper_query::apply_purchase_order_filter
+fn filter_purchase_orders<'a>(
+    orders: &'a [PurchaseOrder],
+    filter: &PurchaseOrderFilter,
+    search: &Option<String>,
+) -> Vec<&'a PurchaseOrder> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    orders
+        .iter()
+        .filter(|o| o.company_id == filter.company_id)
+        .filter(|o| filter.supplier_id.is_none_or(|id| o.supplier_id == id))
+        .filter(|o| filter.status.is_none_or(|status| o.status == status))
+        .filter(|o| pattern.as_ref().is_none_or(|p| ilike(&o.number, p)))
+        .collect()
+}
+
+fn compare_supplier(a: &Supplier, b: &Supplier, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "linked_company_id" => compare_nullable(&a.linked_company_id, &b.linked_company_id),
+        "code" => a.code.cmp(&b.code),
+        "name" => a.name.cmp(&b.name),
+        "email" => compare_nullable(&a.email, &b.email),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_purchase_order(a: &PurchaseOrder, b: &PurchaseOrder, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "supplier_id" => a.supplier_id.cmp(&b.supplier_id),
+        "number" => a.number.cmp(&b.number),
+        "status" => a.status.as_str().cmp(b.status.as_str()),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "approved_at" => compare_nullable(&a.approved_at, &b.approved_at),
+        "closed_at" => compare_nullable(&a.closed_at, &b.closed_at),
+        _ => Ordering::Equal,
+    }
+}
@@ -0,0 +1,662 @@
+use async_trait::async_trait;
+use sqlx::QueryBuilder;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_request::pagination::PaginationRequest;
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{
+    PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine, PurchaseOrderStatus,
+};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::repository::helper_query::{
+    PURCHASE_ORDER_DEFAULT_ORDER, SUPPLIER_DEFAULT_ORDER, apply_purchase_order_filter,
+    apply_supplier_filter,
+};
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+
+#[async_trait]
+impl PurchasingRepository for StockRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(supplier_id = %id))]
+    async fn get_supplier_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Supplier>, sqlx::Error> {
+        let supplier = sqlx::query_as!(
+            Supplier,
+            r#"
+            SELECT id, company_id, linked_company_id, code, name, email, phone_number, address,
+                   over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_at
+            FROM suppliers
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(supplier)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn count_company_suppliers(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM suppliers");
+
+        apply_supplier_filter(&mut qb, company_id, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_company_suppliers(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Supplier>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, linked_company_id, code, name, email, phone_number, address,
+                   over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_at
+            FROM suppliers
+        ",
+        );
+
+        apply_supplier_filter(&mut qb, company_id, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(SUPPLIER_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let suppliers = qb
+            .build_query_as::<Supplier>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(suppliers)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_supplier_code(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM suppliers
+                WHERE company_id = $1 AND code = $2 AND ($3::uuid IS NULL OR id <> $3)
+            )
+            "#,
+            company_id,
+            code,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id, linked_company_id = %linked_company_id))]
+    async fn check_existing_supplier_link(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        linked_company_id: &Uuid,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM suppliers
+                WHERE company_id = $1 AND linked_company_id = $2
+                  AND ($3::uuid IS NULL OR id <> $3)
+            )
+            "#,
+            company_id,
+            linked_company_id,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(supplier_id = %supplier.id))]
+    async fn create_supplier(
+        &self,
+        uow: &mut Self::Uow,
+        supplier: Supplier,
+    ) -> Result<Supplier, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO suppliers
+            (id, company_id, linked_company_id, code, name, email, phone_number, address,
+             over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            supplier.id,
+            supplier.company_id,
+            supplier.linked_company_id,
+            supplier.code,
+            supplier.name,
+            supplier.email,
+            supplier.phone_number,
+            supplier.address,
+            supplier.over_receipt_tolerance_pct,
+            supplier.under_receipt_tolerance_pct,
+            supplier.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(supplier)
+    }
+
+    // the owning company never changes
+    #[instrument(level = "debug", skip_all, fields(supplier_id = %supplier.id))]
+    async fn update_supplier(
+        &self,
+        uow: &mut Self::Uow,
+        supplier: Supplier,
+    ) -> Result<Supplier, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE suppliers
+            SET linked_company_id = $1,
+                code = $2,
+                name = $3,
+                email = $4,
+                phone_number = $5,
+                address = $6,
+                over_receipt_tolerance_pct = $7,
+                under_receipt_tolerance_pct = $8
+            WHERE id = $9
+            RETURNING id
+            "#,
+            supplier.linked_company_id,
+            supplier.code,
+            supplier.name,
+            supplier.email,
+            supplier.phone_number,
+            supplier.address,
+            supplier.over_receipt_tolerance_pct,
+            supplier.under_receipt_tolerance_pct,
+            supplier.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(supplier)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(supplier_id = %id))]
+    async fn delete_supplier(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM suppliers WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_purchase_order_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM purchase_orders
+                WHERE company_id = $1 AND number = $2 AND ($3::uuid IS NULL OR id <> $3)
+            )
+            "#,
+            company_id,
+            number,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %order.id))]
+    async fn create_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: PurchaseOrder,
+    ) -> Result<PurchaseOrder, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO purchase_orders
+            (id, company_id, supplier_id, number, status, note, over_receipt_tolerance_pct,
+             under_receipt_tolerance_pct, created_by, created_at, approved_by, approved_at,
+             closed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+            order.id,
+            order.company_id,
+            order.supplier_id,
+            order.number,
+            order.status.as_str(),
+            order.note,
+            order.over_receipt_tolerance_pct,
+            order.under_receipt_tolerance_pct,
+            order.created_by,
+            order.created_at,
+            order.approved_by,
+            order.approved_at,
+            order.closed_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    // the company and the creator never change
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %order.id))]
+    async fn update_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: PurchaseOrder,
+    ) -> Result<PurchaseOrder, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE purchase_orders
+            SET supplier_id = $1,
+                number = $2,
+                status = $3,
+                note = $4,
+                over_receipt_tolerance_pct = $5,
+                under_receipt_tolerance_pct = $6,
+                approved_by = $7,
+                approved_at = $8,
+                closed_at = $9
+            WHERE id = $10
+            RETURNING id
+            "#,
+            order.supplier_id,
+            order.number,
+            order.status.as_str(),
+            order.note,
+            order.over_receipt_tolerance_pct,
+            order.under_receipt_tolerance_pct,
+            order.approved_by,
+            order.approved_at,
+            order.closed_at,
+            order.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %id))]
+    async fn get_purchase_order_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<PurchaseOrder>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            PurchaseOrder,
+            r#"
+            SELECT id, company_id, supplier_id, number, status AS "status: PurchaseOrderStatus",
+                   note, over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_by,
+                   created_at, approved_by, approved_at, closed_at
+            FROM purchase_orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %id))]
+    async fn lock_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<PurchaseOrder>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            PurchaseOrder,
+            r#"
+            SELECT id, company_id, supplier_id, number, status AS "status: PurchaseOrderStatus",
+                   note, over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_by,
+                   created_at, approved_by, approved_at, closed_at
+            FROM purchase_orders
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %id))]
+    async fn delete_purchase_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM purchase_orders WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_purchase_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM purchase_orders");
+
+        apply_purchase_order_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_purchase_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<PurchaseOrder>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, supplier_id, number, status, note,
+                   over_receipt_tolerance_pct, under_receipt_tolerance_pct, created_by,
+                   created_at, approved_by, approved_at, closed_at
+            FROM purchase_orders
+        ",
+        );
+
+        apply_purchase_order_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(PURCHASE_ORDER_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let orders = qb
+            .build_query_as::<PurchaseOrder>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(orders)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %purchase_order_id))]
+    async fn find_purchase_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+    ) -> Result<Vec<PurchaseOrderLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            PurchaseOrderLine,
+            r#"
+            SELECT id, purchase_order_id, position, product_id, quantity, unit, unit_quantity,
+                   received_quantity
+            FROM purchase_order_lines
+            WHERE purchase_order_id = $1
+            ORDER BY position
+            "#,
+            purchase_order_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %purchase_order_id, lines = lines.len()))]
+    async fn replace_purchase_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+        lines: Vec<PurchaseOrderLine>,
+    ) -> Result<Vec<PurchaseOrderLine>, sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM purchase_order_lines WHERE purchase_order_id = $1"#,
+            purchase_order_id
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO purchase_order_lines
+                (id, purchase_order_id, position, product_id, quantity, unit, unit_quantity,
+                 received_quantity)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+                line.id,
+                line.purchase_order_id,
+                line.position,
+                line.product_id,
+                line.quantity,
+                line.unit,
+                line.unit_quantity,
+                line.received_quantity,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %line_id))]
+    async fn update_purchase_order_received_quantity(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        received_quantity: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE purchase_order_lines
+            SET received_quantity = $1
+            WHERE id = $2
+            RETURNING id
+            "#,
+            received_quantity,
+            line_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_goods_receipt_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM goods_receipts WHERE company_id = $1 AND number = $2
+            )
+            "#,
+            company_id,
+            number,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(goods_receipt_id = %receipt.id, lines = lines.len()))]
+    async fn create_goods_receipt(
+        &self,
+        uow: &mut Self::Uow,
+        receipt: GoodsReceipt,
+        lines: Vec<GoodsReceiptLine>,
+    ) -> Result<(GoodsReceipt, Vec<GoodsReceiptLine>), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO goods_receipts
+            (id, company_id, purchase_order_id, number, warehouse_id, note, received_by,
+             received_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            receipt.id,
+            receipt.company_id,
+            receipt.purchase_order_id,
+            receipt.number,
+            receipt.warehouse_id,
+            receipt.note,
+            receipt.received_by,
+            receipt.received_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO goods_receipt_lines
+                (id, goods_receipt_id, position, purchase_order_line_id, location_id, lot_id,
+                 quantity, serial_numbers, movement_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                line.id,
+                line.goods_receipt_id,
+                line.position,
+                line.purchase_order_line_id,
+                line.location_id,
+                line.lot_id,
+                line.quantity,
+                &line.serial_numbers,
+                line.movement_id,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok((receipt, lines))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(goods_receipt_id = %id))]
+    async fn get_goods_receipt_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<GoodsReceipt>, sqlx::Error> {
+        let receipt = sqlx::query_as!(
+            GoodsReceipt,
+            r#"
+            SELECT id, company_id, purchase_order_id, number, warehouse_id, note, received_by,
+                   received_at
+            FROM goods_receipts
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(receipt)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(goods_receipt_id = %goods_receipt_id))]
+    async fn find_goods_receipt_lines(
+        &self,
+        uow: &mut Self::Uow,
+        goods_receipt_id: &Uuid,
+    ) -> Result<Vec<GoodsReceiptLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            GoodsReceiptLine,
+            r#"
+            SELECT id, goods_receipt_id, position, purchase_order_line_id, location_id,
+                   lot_id, quantity, serial_numbers, movement_id
+            FROM goods_receipt_lines
+            WHERE goods_receipt_id = $1
+            ORDER BY position
+            "#,
+            goods_receipt_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(purchase_order_id = %purchase_order_id))]
+    async fn find_purchase_order_receipts(
+        &self,
+        uow: &mut Self::Uow,
+        purchase_order_id: &Uuid,
+    ) -> Result<Vec<GoodsReceipt>, sqlx::Error> {
+        let receipts = sqlx::query_as!(
+            GoodsReceipt,
+            r#"
+            SELECT id, company_id, purchase_order_id, number, warehouse_id, note, received_by,
+                   received_at
+            FROM goods_receipts
+            WHERE purchase_order_id = $1
+            ORDER BY received_at, id
+            "#,
+            purchase_order_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(receipts)
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::atuh_middleware::auth_middleware;
+use crate::purchasing::handler::purchase_order_handler::{
+    approve_purchase_order_handler, close_purchase_order_handler, delete_purchase_order_handler,
+    get_goods_receipt_handler, get_goods_receipts_handler, get_purchase_order_handler,
+    get_purchase_orders_handler, post_goods_receipt_handler, post_purchase_order_handler,
+    update_purchase_order_handler,
+};
+use crate::purchasing::handler::supplier_handler::{
+    create_supplier_handler, delete_supplier_handler, get_company_suppliers_handler,
+    get_supplier_handler, update_supplier_handler,
+};
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::usecase::purchase_order_usecase::PurchaseOrderUsecase;
+use crate::purchasing::usecase::supplier_usecase::SupplierUsecase;
+use axum::middleware;
+use axum::{Router, routing::delete, routing::get, routing::post, routing::put};
+
+// full paths, the suppliers of a company hang below /company
+pub fn purchasing_routes<R: PurchasingRepository + Clone + 'static>(
+    repo: R,
+    metrics: AppMetrics,
+) -> Router {
+    let supplier_usecase = Arc::new(SupplierUsecase::new(repo.clone(), metrics.clone()));
+    let order_usecase = Arc::new(PurchaseOrderUsecase::new(repo, metrics));
+
+    let orders = Router::new()
+        .route("/purchasing/orders", get(get_purchase_orders_handler))
+        .route("/purchasing/orders", post(post_purchase_order_handler))
+        .route("/purchasing/orders/:id", get(get_purchase_order_handler))
+        .route("/purchasing/orders/:id", put(update_purchase_order_handler))
+        .route(
+            "/purchasing/orders/:id",
+            delete(delete_purchase_order_handler),
+        )
+        .route(
+            "/purchasing/orders/:id/approve",
+            post(approve_purchase_order_handler),
+        )
+        .route(
+            "/purchasing/orders/:id/close",
+            post(close_purchase_order_handler),
+        )
+        .route(
+            "/purchasing/orders/:id/receipts",
+            get(get_goods_receipts_handler),
+        )
+        .route(
+            "/purchasing/orders/:id/receipts",
+            post(post_goods_receipt_handler),
+        )
+        .route("/purchasing/receipts/:id", get(get_goods_receipt_handler))
+        .with_state(order_usecase);
+
+    Router::new()
+        .route("/company/:id/suppliers", get(get_company_suppliers_handler))
+        .route("/company/:id/suppliers", post(create_supplier_handler))
+        .route("/supplier/:id", get(get_supplier_handler))
+        .route("/supplier/:id", put(update_supplier_handler))
+        .route("/supplier/:id", delete(delete_supplier_handler))
+        .with_state(supplier_usecase)
+        .merge(orders)
+        // merged at the root, layer would put the fallback behind auth as well
+        .route_layer(middleware::from_fn(auth_middleware))
+}
@@ -0,0 +1,73 @@
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{PurchaseOrder, PurchaseOrderLine};
+use crate::purchasing::domain::supplier::Supplier;
+
+pub struct ListSupplierResult {
+    pub data: Vec<Supplier>,
+    pub total_data: i64,
+}
+
+// the fields a client sets, the owning company comes from the path on create
+pub struct SupplierInput {
+    pub linked_company_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub address: Option<String>,
+    pub over_receipt_tolerance_pct: i32,
+    pub under_receipt_tolerance_pct: i32,
+}
+
+// quantity is in unit, the product's base unit when None
+pub struct PurchaseOrderLineInput {
+    pub product_id: Uuid,
+    pub unit: Option<String>,
+    pub quantity: Decimal,
+}
+
+// the lines replace whatever the draft held, in the given order. tolerances
+// left out are taken from the supplier
+pub struct PurchaseOrderInput {
+    pub supplier_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    pub over_receipt_tolerance_pct: Option<i32>,
+    pub under_receipt_tolerance_pct: Option<i32>,
+    pub lines: Vec<PurchaseOrderLineInput>,
+}
+
+pub struct PurchaseOrderDetail {
+    pub order: PurchaseOrder,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+pub struct ListPurchaseOrderResult {
+    pub data: Vec<PurchaseOrder>,
+    pub total_data: i64,
+}
+
+// quantity arriving for one line of the order, put into a location of the
+// receipt's warehouse
+pub struct GoodsReceiptLineInput {
+    pub line_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    pub serial_numbers: Vec<String>,
+}
+
+pub struct GoodsReceiptInput {
+    pub number: String,
+    pub warehouse_id: Uuid,
+    pub note: Option<String>,
+    pub lines: Vec<GoodsReceiptLineInput>,
+}
+
+pub struct GoodsReceiptDetail {
+    pub receipt: GoodsReceipt,
+    pub lines: Vec<GoodsReceiptLine>,
+}
@@ -0,0 +1,7 @@
+pub mod supplier_usecase;
+pub mod purchase_order_usecase;
+pub mod dto;
+#[cfg(test)]
+mod supplier_usecase_test;
+#[cfg(test)]
+mod purchase_order_usecase_test;
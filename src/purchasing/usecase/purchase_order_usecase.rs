@@ -0,0 +1,895 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product_unit::{UnitConversionError, UnitPurpose};
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{
+    PurchaseOrder, PurchaseOrderFilter, PurchaseOrderLine, PurchaseOrderStatus,
+};
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::usecase::dto::{
+    GoodsReceiptDetail, GoodsReceiptInput, GoodsReceiptLineInput, ListPurchaseOrderResult,
+    PurchaseOrderDetail, PurchaseOrderInput, PurchaseOrderLineInput,
+};
+use crate::purchasing::usecase::supplier_usecase::is_valid_tolerance;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::usecase::stock_usecase::{
+    Posting, StockUsecaseError, find_conversion_in, post_in,
+};
+
+// names of the constraints on the purchasing tables, see migrations
+const ORDER_NUMBER_CONSTRAINT: &str = "purchase_orders_company_id_number_key";
+const ORDER_COMPANY_CONSTRAINT: &str = "purchase_orders_company_id_fkey";
+const ORDER_SUPPLIER_CONSTRAINT: &str = "purchase_orders_supplier_fkey";
+const LINE_PRODUCT_CONSTRAINT: &str = "purchase_order_lines_product_id_fkey";
+const RECEIPT_NUMBER_CONSTRAINT: &str = "goods_receipts_company_id_number_key";
+const RECEIPT_WAREHOUSE_CONSTRAINT: &str = "goods_receipts_warehouse_fkey";
+const RECEIPT_LOCATION_CONSTRAINT: &str = "goods_receipt_lines_location_id_fkey";
+
+pub struct PurchaseOrderUsecase<R: PurchasingRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum PurchaseOrderUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    NumberAlreadyExist,
+    SupplierNotFound,
+    // a supplier, product or warehouse of another company
+    CompanyMismatch,
+    ProductNotFound,
+    InvalidQuantity,
+    // the product has no such purchase unit
+    UnitNotFound,
+    // the quantity does not come to a whole base quantity in its unit
+    Conversion(UnitConversionError),
+    InvalidTolerance,
+    NoLines,
+    // the order can not take this step from its current status
+    InvalidStatus(PurchaseOrderStatus),
+    LineNotFound,
+    ReceiptNotFound,
+    ReceiptNumberAlreadyExist,
+    WarehouseNotFound,
+    // the location is missing or not in the receipt's warehouse
+    LocationNotFound,
+    // the line would receive more than its quantity and over-receipt tolerance allow
+    OverReceipt { line_id: Uuid, receivable: i64 },
+    // a posting failed for a reason the order does not translate
+    Posting(StockUsecaseError),
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for PurchaseOrderUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PurchaseOrderUsecaseError::CompanyNotFound => {
+                write!(f, "purchase order company not found")
+            }
+            PurchaseOrderUsecaseError::NotFound => write!(f, "purchase order not found"),
+            PurchaseOrderUsecaseError::NumberAlreadyExist => {
+                write!(f, "purchase order number already exists")
+            }
+            PurchaseOrderUsecaseError::SupplierNotFound => {
+                write!(f, "purchase order supplier not found")
+            }
+            PurchaseOrderUsecaseError::CompanyMismatch => {
+                write!(f, "purchase order refers to another company")
+            }
+            PurchaseOrderUsecaseError::ProductNotFound => {
+                write!(f, "purchase order product not found")
+            }
+            PurchaseOrderUsecaseError::InvalidQuantity => {
+                write!(f, "purchase order quantity is invalid")
+            }
+            PurchaseOrderUsecaseError::InvalidTolerance => {
+                write!(f, "receipt tolerance must be between 0 and 100")
+            }
+            PurchaseOrderUsecaseError::UnitNotFound => write!(f, "purchase order unit not found"),
+            PurchaseOrderUsecaseError::Conversion(err) => write!(f, "{err}"),
+            PurchaseOrderUsecaseError::NoLines => write!(f, "purchase order has no lines"),
+            PurchaseOrderUsecaseError::InvalidStatus(status) => {
+                write!(f, "purchase order is {}", status.as_str())
+            }
+            PurchaseOrderUsecaseError::LineNotFound => write!(f, "purchase order line not found"),
+            PurchaseOrderUsecaseError::ReceiptNotFound => write!(f, "goods receipt not found"),
+            PurchaseOrderUsecaseError::ReceiptNumberAlreadyExist => {
+                write!(f, "goods receipt number already exists")
+            }
+            PurchaseOrderUsecaseError::WarehouseNotFound => {
+                write!(f, "goods receipt warehouse not found")
+            }
+            PurchaseOrderUsecaseError::LocationNotFound => {
+                write!(f, "goods receipt location not found")
+            }
+            PurchaseOrderUsecaseError::OverReceipt {
+                line_id,
+                receivable,
+            } => write!(
+                f,
+                "line {line_id} can receive at most {receivable} in total"
+            ),
+            PurchaseOrderUsecaseError::Posting(err) => {
+                write!(f, "goods receipt posting failed: {err}")
+            }
+            PurchaseOrderUsecaseError::DatabaseError(_) => {
+                write!(f, "purchase order storage failed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PurchaseOrderUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PurchaseOrderUsecaseError::Posting(err) => Some(err),
+            PurchaseOrderUsecaseError::Conversion(err) => Some(err),
+            PurchaseOrderUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: PurchasingRepository> PurchaseOrderUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, number = %input.number))]
+    pub async fn create_order(
+        &self,
+        company_id: Uuid,
+        input: PurchaseOrderInput,
+        created_by: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .create_order_in(&mut uow, company_id, input, created_by)
+            .await;
+        let detail = finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("purchase_order_create");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn update_order(
+        &self,
+        id: Uuid,
+        input: PurchaseOrderInput,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_order_in(&mut uow, id, input).await;
+        let detail = finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("purchase_order_update");
+        Ok(detail)
+    }
+
+    // only drafts, anything approved has to be closed instead
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn delete_order(&self, id: Uuid) -> Result<(), PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_order_in(&mut uow, id).await;
+        finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("purchase_order_delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn get_order(
+        &self,
+        id: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_order_in(&mut uow, id).await;
+        finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_orders(
+        &self,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListPurchaseOrderResult, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_orders_in(&mut uow, filter, query).await;
+        finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn approve_order(
+        &self,
+        id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.approve_order_in(&mut uow, id, approved_by).await;
+        let detail = finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("approve");
+        Ok(detail)
+    }
+
+    // gives up on whatever was not received yet
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn close_order(
+        &self,
+        id: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.close_order_in(&mut uow, id).await;
+        let detail = finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("close");
+        Ok(detail)
+    }
+
+    // posts a receipt movement per line into the given locations and closes
+    // the order once every line is complete
+    #[instrument(skip_all, fields(purchase_order_id = %id, number = %input.number, lines = input.lines.len()))]
+    pub async fn receive_goods(
+        &self,
+        id: Uuid,
+        input: GoodsReceiptInput,
+        received_by: Uuid,
+    ) -> Result<GoodsReceiptDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .receive_goods_in(&mut uow, id, input, received_by)
+            .await;
+        let (detail, movements) =
+            finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("receive");
+        for movement in &movements {
+            self.metrics
+                .stock_movement_posted(movement.movement_type.as_str());
+        }
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(purchase_order_id = %id))]
+    pub async fn list_receipts(
+        &self,
+        id: Uuid,
+    ) -> Result<Vec<GoodsReceipt>, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_receipts_in(&mut uow, id).await;
+        finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(goods_receipt_id = %id))]
+    pub async fn get_receipt(
+        &self,
+        id: Uuid,
+    ) -> Result<GoodsReceiptDetail, PurchaseOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_receipt_in(&mut uow, id).await;
+        finish(uow, result, PurchaseOrderUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, PurchaseOrderUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)
+    }
+
+    async fn create_order_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: PurchaseOrderInput,
+        created_by: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+        self.check_order_number(uow, &company_id, &input.number, None)
+            .await?;
+        let (over_receipt_tolerance_pct, under_receipt_tolerance_pct) =
+            self.resolve_tolerances(uow, &company_id, &input).await?;
+
+        let order = PurchaseOrder {
+            id: Uuid::new_v4(),
+            company_id,
+            supplier_id: input.supplier_id,
+            number: input.number,
+            status: PurchaseOrderStatus::Draft,
+            note: input.note,
+            over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct,
+            created_by,
+            created_at: Utc::now(),
+            approved_by: None,
+            approved_at: None,
+            closed_at: None,
+        };
+        let lines = self.build_lines(uow, &order, input.lines).await?;
+
+        let order = self
+            .repo
+            .create_purchase_order(uow, order)
+            .await
+            .map_err(map_purchasing_write_error)?;
+        let lines = self
+            .repo
+            .replace_purchase_order_lines(uow, &order.id, lines)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        Ok(PurchaseOrderDetail { order, lines })
+    }
+
+    async fn update_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: PurchaseOrderInput,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != PurchaseOrderStatus::Draft {
+            return Err(PurchaseOrderUsecaseError::InvalidStatus(order.status));
+        }
+        self.check_order_number(uow, &order.company_id, &input.number, Some(&id))
+            .await?;
+        let (over_receipt_tolerance_pct, under_receipt_tolerance_pct) = self
+            .resolve_tolerances(uow, &order.company_id, &input)
+            .await?;
+
+        let order = PurchaseOrder {
+            supplier_id: input.supplier_id,
+            number: input.number,
+            note: input.note,
+            over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct,
+            ..order
+        };
+        let lines = self.build_lines(uow, &order, input.lines).await?;
+
+        let order = self
+            .repo
+            .update_purchase_order(uow, order)
+            .await
+            .map_err(map_purchasing_write_error)?;
+        let lines = self
+            .repo
+            .replace_purchase_order_lines(uow, &order.id, lines)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        Ok(PurchaseOrderDetail { order, lines })
+    }
+
+    async fn delete_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), PurchaseOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != PurchaseOrderStatus::Draft {
+            return Err(PurchaseOrderUsecaseError::InvalidStatus(order.status));
+        }
+
+        self.repo
+            .delete_purchase_order(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)
+    }
+
+    async fn get_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let order = self
+            .repo
+            .get_purchase_order_by_id(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::NotFound)?;
+        let lines = self.find_lines(uow, &id).await?;
+
+        Ok(PurchaseOrderDetail { order, lines })
+    }
+
+    async fn list_orders_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &PurchaseOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListPurchaseOrderResult, PurchaseOrderUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_order = self
+            .repo
+            .count_purchase_orders(uow, filter, query)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+
+        let orders = self
+            .repo
+            .find_purchase_orders(uow, filter, query)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+
+        Ok(ListPurchaseOrderResult {
+            data: orders,
+            total_data: total_order,
+        })
+    }
+
+    async fn approve_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != PurchaseOrderStatus::Draft {
+            return Err(PurchaseOrderUsecaseError::InvalidStatus(order.status));
+        }
+        let lines = self.find_lines(uow, &id).await?;
+        if lines.is_empty() {
+            return Err(PurchaseOrderUsecaseError::NoLines);
+        }
+
+        let order = PurchaseOrder {
+            status: PurchaseOrderStatus::Approved,
+            approved_by: Some(approved_by),
+            approved_at: Some(Utc::now()),
+            ..order
+        };
+        let order = self
+            .repo
+            .update_purchase_order(uow, order)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        Ok(PurchaseOrderDetail { order, lines })
+    }
+
+    async fn close_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<PurchaseOrderDetail, PurchaseOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if !order.status.is_open() {
+            return Err(PurchaseOrderUsecaseError::InvalidStatus(order.status));
+        }
+        let lines = self.find_lines(uow, &id).await?;
+
+        let order = PurchaseOrder {
+            status: PurchaseOrderStatus::Closed,
+            closed_at: Some(Utc::now()),
+            ..order
+        };
+        let order = self
+            .repo
+            .update_purchase_order(uow, order)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        Ok(PurchaseOrderDetail { order, lines })
+    }
+
+    async fn receive_goods_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: GoodsReceiptInput,
+        received_by: Uuid,
+    ) -> Result<(GoodsReceiptDetail, Vec<StockMovement>), PurchaseOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if !order.status.is_open() {
+            return Err(PurchaseOrderUsecaseError::InvalidStatus(order.status));
+        }
+        if input.lines.is_empty() {
+            return Err(PurchaseOrderUsecaseError::NoLines);
+        }
+        self.check_receipt_number(uow, &order.company_id, &input.number)
+            .await?;
+        let warehouse_company = self
+            .repo
+            .get_warehouse_company(uow, &input.warehouse_id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::WarehouseNotFound)?;
+        if warehouse_company != order.company_id {
+            return Err(PurchaseOrderUsecaseError::CompanyMismatch);
+        }
+        let mut lines = self.find_lines(uow, &id).await?;
+
+        // the same line may be listed more than once, the tolerance applies to
+        // what it received in total
+        let mut received: HashMap<Uuid, i64> = HashMap::new();
+        for receipt_line in &input.lines {
+            if receipt_line.quantity <= 0 {
+                return Err(PurchaseOrderUsecaseError::InvalidQuantity);
+            }
+            if !lines.iter().any(|line| line.id == receipt_line.line_id) {
+                return Err(PurchaseOrderUsecaseError::LineNotFound);
+            }
+            let location_warehouse = self
+                .repo
+                .get_location_warehouse(uow, &receipt_line.location_id)
+                .await
+                .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+            if location_warehouse != Some(input.warehouse_id) {
+                return Err(PurchaseOrderUsecaseError::LocationNotFound);
+            }
+            let total = received.entry(receipt_line.line_id).or_default();
+            *total = total
+                .checked_add(receipt_line.quantity)
+                .ok_or(PurchaseOrderUsecaseError::InvalidQuantity)?;
+        }
+
+        for line in lines.iter_mut() {
+            let Some(quantity) = received.get(&line.id).copied() else {
+                continue;
+            };
+            let receivable = order.receivable_quantity(line);
+            let received_quantity = line
+                .received_quantity
+                .checked_add(quantity)
+                .filter(|total| *total <= receivable)
+                .ok_or(PurchaseOrderUsecaseError::OverReceipt {
+                    line_id: line.id,
+                    receivable,
+                })?;
+            line.received_quantity = received_quantity;
+            self.repo
+                .update_purchase_order_received_quantity(uow, &line.id, received_quantity)
+                .await
+                .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+        }
+
+        let receipt = GoodsReceipt {
+            id: Uuid::new_v4(),
+            company_id: order.company_id,
+            purchase_order_id: order.id,
+            number: input.number,
+            warehouse_id: input.warehouse_id,
+            note: input.note,
+            received_by,
+            received_at: Utc::now(),
+        };
+        let (receipt_lines, movements) =
+            self.post_lines(uow, &receipt, &lines, input.lines).await?;
+        let (receipt, receipt_lines) = self
+            .repo
+            .create_goods_receipt(uow, receipt, receipt_lines)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        let is_complete = lines.iter().all(|line| order.is_line_complete(line));
+        let order = if is_complete {
+            PurchaseOrder {
+                status: PurchaseOrderStatus::Closed,
+                closed_at: Some(Utc::now()),
+                ..order
+            }
+        } else {
+            PurchaseOrder {
+                status: PurchaseOrderStatus::PartiallyReceived,
+                ..order
+            }
+        };
+        self.repo
+            .update_purchase_order(uow, order)
+            .await
+            .map_err(map_purchasing_write_error)?;
+
+        Ok((
+            GoodsReceiptDetail {
+                receipt,
+                lines: receipt_lines,
+            },
+            movements,
+        ))
+    }
+
+    // one receipt movement per receipt line, referenced by the receipt number.
+    // balances are locked in product and location order so two receipts over
+    // the same products can not deadlock each other
+    async fn post_lines(
+        &self,
+        uow: &mut R::Uow,
+        receipt: &GoodsReceipt,
+        order_lines: &[PurchaseOrderLine],
+        inputs: Vec<GoodsReceiptLineInput>,
+    ) -> Result<(Vec<GoodsReceiptLine>, Vec<StockMovement>), PurchaseOrderUsecaseError> {
+        let product_by_line: HashMap<Uuid, Uuid> = order_lines
+            .iter()
+            .map(|line| (line.id, line.product_id))
+            .collect();
+        let mut postings: Vec<(i32, GoodsReceiptLineInput)> = (1..).zip(inputs).collect();
+        postings.sort_by_key(|(position, input)| {
+            (
+                product_by_line[&input.line_id],
+                input.location_id,
+                input.lot_id,
+                *position,
+            )
+        });
+
+        let mut lines = Vec::with_capacity(postings.len());
+        let mut movements = Vec::with_capacity(postings.len());
+        for (position, input) in postings {
+            let serial_numbers: Vec<String> = input
+                .serial_numbers
+                .iter()
+                .map(|number| number.trim().to_string())
+                .collect();
+            let posting = Posting {
+                company_id: receipt.company_id,
+                product_id: product_by_line[&input.line_id],
+                location_id: input.location_id,
+                movement_type: MovementType::Receipt,
+                lot_id: input.lot_id,
+                quantity: input.quantity,
+                serial_numbers: serial_numbers.clone(),
+                reversal_of: None,
+                source: MovementSource::GoodsReceipt,
+                source_id: Some(receipt.id),
+                reference: Some(receipt.number.clone()),
+                note: None,
+                posted_by: receipt.received_by,
+            };
+            let movement = post_in(&self.repo, uow, posting)
+                .await
+                .map_err(map_posting_error)?;
+            lines.push(GoodsReceiptLine {
+                id: Uuid::new_v4(),
+                goods_receipt_id: receipt.id,
+                position,
+                purchase_order_line_id: input.line_id,
+                location_id: input.location_id,
+                lot_id: input.lot_id,
+                quantity: input.quantity,
+                serial_numbers,
+                movement_id: movement.id,
+            });
+            movements.push(movement);
+        }
+        lines.sort_by_key(|line| line.position);
+
+        Ok((lines, movements))
+    }
+
+    async fn list_receipts_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<Vec<GoodsReceipt>, PurchaseOrderUsecaseError> {
+        self.repo
+            .get_purchase_order_by_id(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::NotFound)?;
+
+        self.repo
+            .find_purchase_order_receipts(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)
+    }
+
+    async fn get_receipt_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<GoodsReceiptDetail, PurchaseOrderUsecaseError> {
+        let receipt = self
+            .repo
+            .get_goods_receipt_by_id(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::ReceiptNotFound)?;
+        let lines = self
+            .repo
+            .find_goods_receipt_lines(uow, &id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+
+        Ok(GoodsReceiptDetail { receipt, lines })
+    }
+
+    // the supplier must belong to the company of the order, its tolerances
+    // fill in whatever the input leaves out
+    async fn resolve_tolerances(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        input: &PurchaseOrderInput,
+    ) -> Result<(i32, i32), PurchaseOrderUsecaseError> {
+        let supplier = self
+            .repo
+            .get_supplier_by_id(uow, &input.supplier_id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::SupplierNotFound)?;
+        if supplier.company_id != *company_id {
+            return Err(PurchaseOrderUsecaseError::CompanyMismatch);
+        }
+
+        let over = input
+            .over_receipt_tolerance_pct
+            .unwrap_or(supplier.over_receipt_tolerance_pct);
+        let under = input
+            .under_receipt_tolerance_pct
+            .unwrap_or(supplier.under_receipt_tolerance_pct);
+        if !is_valid_tolerance(over) || !is_valid_tolerance(under) {
+            return Err(PurchaseOrderUsecaseError::InvalidTolerance);
+        }
+
+        Ok((over, under))
+    }
+
+    async fn build_lines(
+        &self,
+        uow: &mut R::Uow,
+        order: &PurchaseOrder,
+        inputs: Vec<PurchaseOrderLineInput>,
+    ) -> Result<Vec<PurchaseOrderLine>, PurchaseOrderUsecaseError> {
+        let mut lines = Vec::with_capacity(inputs.len());
+        for (position, input) in (1..).zip(inputs) {
+            if input.quantity <= Decimal::ZERO {
+                return Err(PurchaseOrderUsecaseError::InvalidQuantity);
+            }
+            let product_company = self
+                .repo
+                .get_product_company(uow, &input.product_id)
+                .await
+                .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+                .ok_or(PurchaseOrderUsecaseError::ProductNotFound)?;
+            if product_company != order.company_id {
+                return Err(PurchaseOrderUsecaseError::CompanyMismatch);
+            }
+            let conversion = find_conversion_in(
+                &self.repo,
+                uow,
+                &input.product_id,
+                input.unit.as_deref(),
+                UnitPurpose::Purchase,
+            )
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::UnitNotFound)?;
+            let quantity = conversion
+                .to_base(input.quantity)
+                .map_err(PurchaseOrderUsecaseError::Conversion)?;
+
+            lines.push(PurchaseOrderLine {
+                id: Uuid::new_v4(),
+                purchase_order_id: order.id,
+                position,
+                product_id: input.product_id,
+                quantity,
+                unit: conversion.unit,
+                unit_quantity: input.quantity.normalize(),
+                received_quantity: 0,
+            });
+        }
+
+        Ok(lines)
+    }
+
+    async fn check_order_number(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), PurchaseOrderUsecaseError> {
+        let is_number_exist = self
+            .repo
+            .check_existing_purchase_order_number(uow, company_id, number, id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(PurchaseOrderUsecaseError::NumberAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_receipt_number(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<(), PurchaseOrderUsecaseError> {
+        let is_number_exist = self
+            .repo
+            .check_existing_goods_receipt_number(uow, company_id, number)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(PurchaseOrderUsecaseError::ReceiptNumberAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), PurchaseOrderUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(PurchaseOrderUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn lock_order(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<PurchaseOrder, PurchaseOrderUsecaseError> {
+        self.repo
+            .lock_purchase_order(uow, id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)?
+            .ok_or(PurchaseOrderUsecaseError::NotFound)
+    }
+
+    async fn find_lines(
+        &self,
+        uow: &mut R::Uow,
+        purchase_order_id: &Uuid,
+    ) -> Result<Vec<PurchaseOrderLine>, PurchaseOrderUsecaseError> {
+        self.repo
+            .find_purchase_order_lines(uow, purchase_order_id)
+            .await
+            .map_err(PurchaseOrderUsecaseError::DatabaseError)
+    }
+}
+
+// receipts only add stock, what can fail is the storage or a location that
+// went away in between
+fn map_posting_error(err: StockUsecaseError) -> PurchaseOrderUsecaseError {
+    match err {
+        StockUsecaseError::LocationNotFound => PurchaseOrderUsecaseError::LocationNotFound,
+        StockUsecaseError::ProductNotFound => PurchaseOrderUsecaseError::ProductNotFound,
+        StockUsecaseError::DatabaseError(err) => PurchaseOrderUsecaseError::DatabaseError(err),
+        err => PurchaseOrderUsecaseError::Posting(err),
+    }
+}
+
+// a concurrent unit of work can still claim a number or delete the supplier,
+// a product, warehouse or location between the check and the write, the
+// constraints catch that
+fn map_purchasing_write_error(err: sqlx::Error) -> PurchaseOrderUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(ORDER_NUMBER_CONSTRAINT) => PurchaseOrderUsecaseError::NumberAlreadyExist,
+        Some(ORDER_COMPANY_CONSTRAINT) => PurchaseOrderUsecaseError::CompanyNotFound,
+        Some(ORDER_SUPPLIER_CONSTRAINT) => PurchaseOrderUsecaseError::SupplierNotFound,
+        Some(LINE_PRODUCT_CONSTRAINT) => PurchaseOrderUsecaseError::ProductNotFound,
+        Some(RECEIPT_NUMBER_CONSTRAINT) => PurchaseOrderUsecaseError::ReceiptNumberAlreadyExist,
+        Some(RECEIPT_WAREHOUSE_CONSTRAINT) => PurchaseOrderUsecaseError::WarehouseNotFound,
+        Some(RECEIPT_LOCATION_CONSTRAINT) => PurchaseOrderUsecaseError::LocationNotFound,
+        _ => PurchaseOrderUsecaseError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,610 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::domain::product_unit::UnitRounding;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::usecase::dto::ProductUnitInput;
+use crate::product::usecase::product_usecase::ProductUsecase;
+use crate::purchasing::domain::purchase_order::{PurchaseOrderFilter, PurchaseOrderStatus};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::usecase::dto::{
+    GoodsReceiptInput, GoodsReceiptLineInput, PurchaseOrderDetail, PurchaseOrderInput,
+    PurchaseOrderLineInput, SupplierInput,
+};
+use crate::purchasing::usecase::purchase_order_usecase::{
+    PurchaseOrderUsecase, PurchaseOrderUsecaseError,
+};
+use crate::purchasing::usecase::supplier_usecase::SupplierUsecase;
+use crate::stock::domain::movement::{MovementType, StockMovementFilter};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::stock_usecase::StockUsecase;
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+// a warehouse with a single bin
+async fn bin(warehouses: &WarehouseRepositoryMemory, company: &Company, code: &str) -> Location {
+    let usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = usecase
+        .create_warehouse(
+            company.id,
+            WarehouseInput {
+                code: code.into(),
+                name: code.to_lowercase(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    usecase
+        .create_location(
+            warehouse.id,
+            LocationInput {
+                parent_id: None,
+                code: "A-01".into(),
+                name: "Bin A-01".into(),
+                kind: LocationKind::Bin,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn supplier(repo: &StockRepositoryMemory, company: &Company, code: &str) -> Supplier {
+    SupplierUsecase::new(repo.clone(), AppMetrics::new())
+        .create_supplier(
+            company.id,
+            SupplierInput {
+                linked_company_id: None,
+                code: code.into(),
+                name: format!("Supplier {code}"),
+                email: None,
+                phone_number: None,
+                address: None,
+                over_receipt_tolerance_pct: 10,
+                under_receipt_tolerance_pct: 5,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+struct Fixture {
+    usecase: PurchaseOrderUsecase<StockRepositoryMemory>,
+    products: ProductUsecase<ProductRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    acme: Company,
+    supplier: Supplier,
+    globex_supplier: Supplier,
+    bolt: Product,
+    nut: Product,
+    gear: Product,
+    north: Location,
+    globex_bin: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    fn order(&self, number: &str, lines: &[(&Product, i64)]) -> PurchaseOrderInput {
+        PurchaseOrderInput {
+            supplier_id: self.supplier.id,
+            number: number.into(),
+            note: None,
+            over_receipt_tolerance_pct: None,
+            under_receipt_tolerance_pct: None,
+            lines: lines
+                .iter()
+                .map(|(product, quantity)| PurchaseOrderLineInput {
+                    product_id: product.id,
+                    unit: None,
+                    quantity: Decimal::from(*quantity),
+                })
+                .collect(),
+        }
+    }
+
+    // quantities per line of the order, in line order, into the north bin
+    fn receipt(
+        &self,
+        number: &str,
+        order: &PurchaseOrderDetail,
+        quantities: &[i64],
+    ) -> GoodsReceiptInput {
+        GoodsReceiptInput {
+            number: number.into(),
+            warehouse_id: self.north.warehouse_id,
+            note: None,
+            lines: order
+                .lines
+                .iter()
+                .zip(quantities)
+                .filter(|(_, quantity)| **quantity > 0)
+                .map(|(line, quantity)| GoodsReceiptLineInput {
+                    line_id: line.id,
+                    location_id: self.north.id,
+                    lot_id: None,
+                    quantity: *quantity,
+                    serial_numbers: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    async fn approved(&self, number: &str, lines: &[(&Product, i64)]) -> PurchaseOrderDetail {
+        let draft = self
+            .usecase
+            .create_order(self.acme.id, self.order(number, lines), self.user)
+            .await
+            .unwrap();
+        self.usecase
+            .approve_order(draft.order.id, self.user)
+            .await
+            .unwrap()
+    }
+
+    async fn on_hand(&self, product: &Product) -> i64 {
+        let filter = StockMovementFilter {
+            company_id: product.company_id,
+            product_id: Some(product.id),
+            location_id: Some(self.north.id),
+            movement_type: None,
+            lot_id: None,
+        };
+        let result = self.stock.list_movements(&filter, &query()).await.unwrap();
+        result.data.iter().map(|movement| movement.quantity).sum()
+    }
+}
+
+// ACM buys bolts and nuts from its supplier into the north warehouse, GLB has
+// a product, warehouse and supplier of its own
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let bolt = product(&acme, "BOLT");
+    let nut = product(&acme, "NUT");
+    let gear = product(&globex, "GEAR");
+    let products = ProductRepositoryMemory::with_products(
+        companies.clone(),
+        vec![bolt.clone(), nut.clone(), gear.clone()],
+    );
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let north = bin(&warehouses, &acme, "NORTH").await;
+    let globex_bin = bin(&warehouses, &globex, "MAIN").await;
+    let product_usecase = ProductUsecase::new(products.clone(), AppMetrics::new());
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    let supplier_of_acme = supplier(&repo, &acme, "SUP").await;
+    let globex_supplier = supplier(&repo, &globex, "SUP").await;
+    Fixture {
+        usecase: PurchaseOrderUsecase::new(repo.clone(), AppMetrics::new()),
+        products: product_usecase,
+        stock: StockUsecase::new(repo, AppMetrics::new()),
+        acme,
+        supplier: supplier_of_acme,
+        globex_supplier,
+        bolt,
+        nut,
+        gear,
+        north,
+        globex_bin,
+        user: Uuid::new_v4(),
+    }
+}
+
+#[tokio::test]
+async fn receipts_post_stock_until_order_closes() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_order(
+            f.acme.id,
+            f.order("PO-1", &[(&f.bolt, 100), (&f.nut, 10)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(draft.order.status, PurchaseOrderStatus::Draft);
+    // tolerances come from the supplier
+    assert_eq!(draft.order.over_receipt_tolerance_pct, 10);
+    assert_eq!(draft.order.under_receipt_tolerance_pct, 5);
+
+    let order = f
+        .usecase
+        .approve_order(draft.order.id, f.user)
+        .await
+        .unwrap();
+    assert_eq!(order.order.status, PurchaseOrderStatus::Approved);
+    assert_eq!(order.order.approved_by, Some(f.user));
+
+    let first = f
+        .usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[60]), f.user)
+        .await
+        .unwrap();
+    assert_eq!(first.lines.len(), 1);
+    assert_eq!(f.on_hand(&f.bolt).await, 60);
+    let partial = f.usecase.get_order(order.order.id).await.unwrap();
+    assert_eq!(partial.order.status, PurchaseOrderStatus::PartiallyReceived);
+    assert_eq!(partial.lines[0].received_quantity, 60);
+
+    f.usecase
+        .receive_goods(order.order.id, f.receipt("GR-2", &order, &[40, 10]), f.user)
+        .await
+        .unwrap();
+    assert_eq!(f.on_hand(&f.bolt).await, 100);
+    assert_eq!(f.on_hand(&f.nut).await, 10);
+    let closed = f.usecase.get_order(order.order.id).await.unwrap();
+    assert_eq!(closed.order.status, PurchaseOrderStatus::Closed);
+    assert!(closed.order.closed_at.is_some());
+
+    let receipts = f.usecase.list_receipts(order.order.id).await.unwrap();
+    let numbers: Vec<&str> = receipts.iter().map(|r| r.number.as_str()).collect();
+    assert_eq!(numbers, ["GR-1", "GR-2"]);
+
+    // every receipt line points at its receipt movement
+    let filter = StockMovementFilter {
+        company_id: f.acme.id,
+        product_id: None,
+        location_id: None,
+        movement_type: Some(MovementType::Receipt),
+        lot_id: None,
+    };
+    let movements = f.stock.list_movements(&filter, &query()).await.unwrap();
+    assert_eq!(movements.total_data, 3);
+    let receipt = f.usecase.get_receipt(first.receipt.id).await.unwrap();
+    let movement = movements
+        .data
+        .iter()
+        .find(|movement| movement.id == receipt.lines[0].movement_id)
+        .unwrap();
+    assert_eq!(movement.reference.as_deref(), Some("GR-1"));
+}
+
+#[tokio::test]
+async fn receipts_follow_over_and_under_tolerance() {
+    let f = setup().await;
+    let order = f.approved("PO-1", &[(&f.bolt, 100)]).await;
+
+    // 10% over on 100 allows 110 in total
+    let result = f
+        .usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[111]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::OverReceipt {
+            receivable: 110,
+            ..
+        })
+    ));
+    // nothing of the refused receipt stays behind
+    assert_eq!(f.on_hand(&f.bolt).await, 0);
+
+    // 5% under on 100 counts 95 as complete
+    f.usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[94]), f.user)
+        .await
+        .unwrap();
+    let partial = f.usecase.get_order(order.order.id).await.unwrap();
+    assert_eq!(partial.order.status, PurchaseOrderStatus::PartiallyReceived);
+
+    f.usecase
+        .receive_goods(order.order.id, f.receipt("GR-2", &order, &[1]), f.user)
+        .await
+        .unwrap();
+    let closed = f.usecase.get_order(order.order.id).await.unwrap();
+    assert_eq!(closed.order.status, PurchaseOrderStatus::Closed);
+
+    let result = f
+        .usecase
+        .receive_goods(order.order.id, f.receipt("GR-3", &order, &[1]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidStatus(
+            PurchaseOrderStatus::Closed
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn receive_validates_warehouse_location_and_number() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_order(f.acme.id, f.order("PO-1", &[(&f.bolt, 10)]), f.user)
+        .await
+        .unwrap();
+    let result = f
+        .usecase
+        .receive_goods(draft.order.id, f.receipt("GR-1", &draft, &[1]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidStatus(
+            PurchaseOrderStatus::Draft
+        ))
+    ));
+
+    let order = f
+        .usecase
+        .approve_order(draft.order.id, f.user)
+        .await
+        .unwrap();
+
+    let mut input = f.receipt("GR-1", &order, &[1]);
+    input.warehouse_id = f.globex_bin.warehouse_id;
+    let result = f.usecase.receive_goods(order.order.id, input, f.user).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::CompanyMismatch)
+    ));
+
+    let mut input = f.receipt("GR-1", &order, &[1]);
+    input.lines[0].location_id = f.globex_bin.id;
+    let result = f.usecase.receive_goods(order.order.id, input, f.user).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::LocationNotFound)
+    ));
+
+    let mut input = f.receipt("GR-1", &order, &[1]);
+    input.lines[0].line_id = Uuid::new_v4();
+    let result = f.usecase.receive_goods(order.order.id, input, f.user).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::LineNotFound)
+    ));
+
+    f.usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[1]), f.user)
+        .await
+        .unwrap();
+    let result = f
+        .usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[1]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::ReceiptNumberAlreadyExist)
+    ));
+}
+
+#[tokio::test]
+async fn create_validates_supplier_products_and_tolerance() {
+    let f = setup().await;
+    let mut input = f.order("PO-1", &[(&f.bolt, 10)]);
+    input.over_receipt_tolerance_pct = Some(0);
+    let created = f
+        .usecase
+        .create_order(f.acme.id, input, f.user)
+        .await
+        .unwrap();
+    // an explicit tolerance wins over the supplier's
+    assert_eq!(created.order.over_receipt_tolerance_pct, 0);
+    assert_eq!(created.order.under_receipt_tolerance_pct, 5);
+
+    let result = f
+        .usecase
+        .create_order(f.acme.id, f.order("PO-1", &[(&f.bolt, 10)]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::NumberAlreadyExist)
+    ));
+
+    let mut input = f.order("PO-2", &[(&f.bolt, 10)]);
+    input.supplier_id = f.globex_supplier.id;
+    let result = f.usecase.create_order(f.acme.id, input, f.user).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::CompanyMismatch)
+    ));
+
+    let result = f
+        .usecase
+        .create_order(f.acme.id, f.order("PO-2", &[(&f.gear, 1)]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::CompanyMismatch)
+    ));
+
+    let result = f
+        .usecase
+        .create_order(f.acme.id, f.order("PO-2", &[(&f.bolt, 0)]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidQuantity)
+    ));
+
+    let mut input = f.order("PO-2", &[(&f.bolt, 1)]);
+    input.under_receipt_tolerance_pct = Some(-1);
+    let result = f.usecase.create_order(f.acme.id, input, f.user).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidTolerance)
+    ));
+}
+
+#[tokio::test]
+async fn only_drafts_are_edited_and_open_orders_closed() {
+    let f = setup().await;
+    let empty = f
+        .usecase
+        .create_order(f.acme.id, f.order("PO-1", &[]), f.user)
+        .await
+        .unwrap();
+    let result = f.usecase.approve_order(empty.order.id, f.user).await;
+    assert!(matches!(result, Err(PurchaseOrderUsecaseError::NoLines)));
+    let result = f.usecase.close_order(empty.order.id).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidStatus(
+            PurchaseOrderStatus::Draft
+        ))
+    ));
+    f.usecase.delete_order(empty.order.id).await.unwrap();
+
+    let order = f.approved("PO-2", &[(&f.nut, 5)]).await;
+    let result = f
+        .usecase
+        .update_order(order.order.id, f.order("PO-2", &[(&f.nut, 6)]))
+        .await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidStatus(
+            PurchaseOrderStatus::Approved
+        ))
+    ));
+    let result = f.usecase.delete_order(order.order.id).await;
+    assert!(matches!(
+        result,
+        Err(PurchaseOrderUsecaseError::InvalidStatus(
+            PurchaseOrderStatus::Approved
+        ))
+    ));
+
+    let closed = f.usecase.close_order(order.order.id).await.unwrap();
+    assert_eq!(closed.order.status, PurchaseOrderStatus::Closed);
+
+    let filter = PurchaseOrderFilter {
+        company_id: f.acme.id,
+        supplier_id: Some(f.supplier.id),
+        status: Some(PurchaseOrderStatus::Closed),
+    };
+    let result = f.usecase.list_orders(&filter, &query()).await.unwrap();
+    assert_eq!(result.total_data, 1);
+    assert_eq!(result.data[0].number, "PO-2");
+}
+
+#[tokio::test]
+async fn lines_in_other_units_are_converted_to_the_base_unit() {
+    let f = setup().await;
+    let units = [
+        ("carton", "48", 2, UnitRounding::Exact, true),
+        ("pack", "2.5", 1, UnitRounding::HalfUp, true),
+        ("display", "6", 0, UnitRounding::Exact, false),
+    ];
+    for (code, factor, decimal_places, rounding, is_purchase_unit) in units {
+        let input = ProductUnitInput {
+            code: code.into(),
+            factor: factor.parse().unwrap(),
+            decimal_places,
+            rounding,
+            is_purchase_unit,
+            is_sales_unit: true,
+        };
+        f.products.create_unit(f.bolt.id, input).await.unwrap();
+    }
+    let line = |unit: Option<&str>, quantity: &str| PurchaseOrderLineInput {
+        product_id: f.bolt.id,
+        unit: unit.map(String::from),
+        quantity: quantity.parse().unwrap(),
+    };
+
+    let mut input = f.order("PO-1", &[]);
+    input.lines = vec![
+        line(Some("carton"), "2"),
+        line(Some("carton"), "1.50"),
+        line(Some("pcs"), "5"),
+        // 0.75 bolts, half up
+        line(Some("pack"), "0.3"),
+    ];
+    let draft = f
+        .usecase
+        .create_order(f.acme.id, input, f.user)
+        .await
+        .unwrap();
+    let entered: Vec<(&str, String, i64)> = draft
+        .lines
+        .iter()
+        .map(|l| (l.unit.as_str(), l.unit_quantity.to_string(), l.quantity))
+        .collect();
+    assert_eq!(
+        entered,
+        [
+            ("carton", "2".to_string(), 96),
+            ("carton", "1.5".to_string(), 72),
+            ("pcs", "5".to_string(), 5),
+            ("pack", "0.3".to_string(), 1),
+        ]
+    );
+
+    // the ledger only ever sees bolts
+    let order = f
+        .usecase
+        .approve_order(draft.order.id, f.user)
+        .await
+        .unwrap();
+    f.usecase
+        .receive_goods(order.order.id, f.receipt("GR-1", &order, &[96]), f.user)
+        .await
+        .unwrap();
+    assert_eq!(f.on_hand(&f.bolt).await, 96);
+
+    let cases = [
+        (
+            line(Some("carton"), "0.125"),
+            "quantity in carton can have at most 2 decimal places",
+        ),
+        (
+            line(Some("carton"), "0.01"),
+            "quantity in carton is not a whole number of base units",
+        ),
+        (
+            line(Some("pack"), "0.1"),
+            "quantity in pack rounds to zero base units",
+        ),
+        (
+            line(None, "1.5"),
+            "quantity in pcs can have at most 0 decimal places",
+        ),
+        (line(Some("display"), "1"), "purchase order unit not found"),
+        (line(Some("pallet"), "1"), "purchase order unit not found"),
+    ];
+    for (line, message) in cases {
+        let mut input = f.order("PO-2", &[]);
+        input.lines = vec![line];
+        let Err(err) = f.usecase.create_order(f.acme.id, input, f.user).await else {
+            panic!("order should be refused with {message}");
+        };
+        assert_eq!(err.to_string(), message);
+    }
+}
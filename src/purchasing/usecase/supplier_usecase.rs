@@ -0,0 +1,344 @@
+use core::fmt;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::purchasing::domain::supplier::Supplier;
+use crate::purchasing::repository::purchasing_repository::PurchasingRepository;
+use crate::purchasing::usecase::dto::{ListSupplierResult, SupplierInput};
+
+// names of the constraints on suppliers, see migrations
+const SUPPLIER_CODE_CONSTRAINT: &str = "suppliers_company_id_code_key";
+const SUPPLIER_LINK_CONSTRAINT: &str = "suppliers_company_id_linked_company_id_key";
+const SUPPLIER_COMPANY_CONSTRAINT: &str = "suppliers_company_id_fkey";
+const SUPPLIER_LINKED_COMPANY_CONSTRAINT: &str = "suppliers_linked_company_id_fkey";
+
+pub struct SupplierUsecase<R: PurchasingRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum SupplierUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    CodeAlreadyExist,
+    LinkedCompanyNotFound,
+    // a company can not buy from itself
+    SelfLink,
+    // another supplier of the company is linked to the same company
+    LinkAlreadyExist,
+    InvalidTolerance,
+    InUse,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for SupplierUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SupplierUsecaseError::CompanyNotFound => write!(f, "supplier company not found"),
+            SupplierUsecaseError::NotFound => write!(f, "supplier not found"),
+            SupplierUsecaseError::CodeAlreadyExist => write!(f, "supplier code already exists"),
+            SupplierUsecaseError::LinkedCompanyNotFound => write!(f, "linked company not found"),
+            SupplierUsecaseError::SelfLink => write!(f, "a company can not supply itself"),
+            SupplierUsecaseError::LinkAlreadyExist => {
+                write!(f, "linked company already has a supplier")
+            }
+            SupplierUsecaseError::InvalidTolerance => {
+                write!(f, "receipt tolerance must be between 0 and 100")
+            }
+            SupplierUsecaseError::InUse => write!(f, "supplier is still in use"),
+            SupplierUsecaseError::DatabaseError(_) => write!(f, "supplier storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for SupplierUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SupplierUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: PurchasingRepository> SupplierUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, code = %input.code))]
+    pub async fn create_supplier(
+        &self,
+        company_id: Uuid,
+        input: SupplierInput,
+    ) -> Result<Supplier, SupplierUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_supplier_in(&mut uow, company_id, input).await;
+        let supplier = finish(uow, result, SupplierUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("supplier_create");
+        Ok(supplier)
+    }
+
+    #[instrument(skip_all, fields(supplier_id = %id))]
+    pub async fn get_supplier(&self, id: Uuid) -> Result<Supplier, SupplierUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_supplier_in(&mut uow, &id).await;
+        finish(uow, result, SupplierUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(supplier_id = %id, code = %input.code))]
+    pub async fn update_supplier(
+        &self,
+        id: Uuid,
+        input: SupplierInput,
+    ) -> Result<Supplier, SupplierUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_supplier_in(&mut uow, id, input).await;
+        let supplier = finish(uow, result, SupplierUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("supplier_update");
+        Ok(supplier)
+    }
+
+    #[instrument(skip_all, fields(supplier_id = %id))]
+    pub async fn delete_supplier(&self, id: Uuid) -> Result<(), SupplierUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_supplier_in(&mut uow, id).await;
+        finish(uow, result, SupplierUsecaseError::DatabaseError).await?;
+
+        self.metrics.purchasing_operation("supplier_delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_company_suppliers(
+        &self,
+        company_id: Uuid,
+        query: &PaginationRequest,
+    ) -> Result<ListSupplierResult, SupplierUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .list_company_suppliers_in(&mut uow, &company_id, query)
+            .await;
+        finish(uow, result, SupplierUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, SupplierUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)
+    }
+
+    async fn get_supplier_in(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<Supplier, SupplierUsecaseError> {
+        self.repo
+            .get_supplier_by_id(uow, id)
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)?
+            .ok_or(SupplierUsecaseError::NotFound)
+    }
+
+    async fn create_supplier_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: SupplierInput,
+    ) -> Result<Supplier, SupplierUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+        self.check_input(uow, &company_id, &input, None).await?;
+
+        let supplier = Supplier {
+            id: Uuid::new_v4(),
+            company_id,
+            linked_company_id: input.linked_company_id,
+            code: input.code,
+            name: input.name,
+            email: input.email,
+            phone_number: input.phone_number,
+            address: input.address,
+            over_receipt_tolerance_pct: input.over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct: input.under_receipt_tolerance_pct,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_supplier(uow, supplier)
+            .await
+            .map_err(map_supplier_write_error)
+    }
+
+    async fn update_supplier_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: SupplierInput,
+    ) -> Result<Supplier, SupplierUsecaseError> {
+        let supplier = self.get_supplier_in(uow, &id).await?;
+        self.check_input(uow, &supplier.company_id, &input, Some(&id))
+            .await?;
+
+        // existing purchase orders keep the tolerances they were created with
+        let supplier = Supplier {
+            linked_company_id: input.linked_company_id,
+            code: input.code,
+            name: input.name,
+            email: input.email,
+            phone_number: input.phone_number,
+            address: input.address,
+            over_receipt_tolerance_pct: input.over_receipt_tolerance_pct,
+            under_receipt_tolerance_pct: input.under_receipt_tolerance_pct,
+            ..supplier
+        };
+
+        self.repo
+            .update_supplier(uow, supplier)
+            .await
+            .map_err(map_supplier_write_error)
+    }
+
+    async fn delete_supplier_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), SupplierUsecaseError> {
+        self.get_supplier_in(uow, &id).await?;
+
+        // purchase orders keep the supplier, they have to be removed first
+        self.repo
+            .delete_supplier(uow, &id)
+            .await
+            .map_err(map_delete_error)
+    }
+
+    async fn list_company_suppliers_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<ListSupplierResult, SupplierUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        let total_supplier = self
+            .repo
+            .count_company_suppliers(uow, company_id, query)
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)?;
+
+        let suppliers = self
+            .repo
+            .find_company_suppliers(uow, company_id, query)
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)?;
+
+        Ok(ListSupplierResult {
+            data: suppliers,
+            total_data: total_supplier,
+        })
+    }
+
+    async fn check_input(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        input: &SupplierInput,
+        id: Option<&Uuid>,
+    ) -> Result<(), SupplierUsecaseError> {
+        if !is_valid_tolerance(input.over_receipt_tolerance_pct)
+            || !is_valid_tolerance(input.under_receipt_tolerance_pct)
+        {
+            return Err(SupplierUsecaseError::InvalidTolerance);
+        }
+
+        let is_code_exist = self
+            .repo
+            .check_existing_supplier_code(uow, company_id, &input.code, id)
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)?;
+        if is_code_exist {
+            return Err(SupplierUsecaseError::CodeAlreadyExist);
+        }
+
+        if let Some(linked_company_id) = &input.linked_company_id {
+            if linked_company_id == company_id {
+                return Err(SupplierUsecaseError::SelfLink);
+            }
+            let is_company_exist = self
+                .repo
+                .check_existing_company(uow, linked_company_id)
+                .await
+                .map_err(SupplierUsecaseError::DatabaseError)?;
+            if !is_company_exist {
+                return Err(SupplierUsecaseError::LinkedCompanyNotFound);
+            }
+            let is_link_exist = self
+                .repo
+                .check_existing_supplier_link(uow, company_id, linked_company_id, id)
+                .await
+                .map_err(SupplierUsecaseError::DatabaseError)?;
+            if is_link_exist {
+                return Err(SupplierUsecaseError::LinkAlreadyExist);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), SupplierUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(SupplierUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(SupplierUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+// a percentage of the ordered quantity
+pub(crate) fn is_valid_tolerance(pct: i32) -> bool {
+    (0..=100).contains(&pct)
+}
+
+// a concurrent unit of work can still claim the code or link, or delete a
+// company between the check and the write, the constraints catch that
+fn map_supplier_write_error(err: sqlx::Error) -> SupplierUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(SUPPLIER_CODE_CONSTRAINT) => SupplierUsecaseError::CodeAlreadyExist,
+        Some(SUPPLIER_LINK_CONSTRAINT) => SupplierUsecaseError::LinkAlreadyExist,
+        Some(SUPPLIER_COMPANY_CONSTRAINT) => SupplierUsecaseError::CompanyNotFound,
+        Some(SUPPLIER_LINKED_COMPANY_CONSTRAINT) => SupplierUsecaseError::LinkedCompanyNotFound,
+        _ => SupplierUsecaseError::DatabaseError(err),
+    }
+}
+
+// on delete every foreign key violation means something still points at the row
+fn map_delete_error(err: sqlx::Error) -> SupplierUsecaseError {
+    let is_referenced = err
+        .as_database_error()
+        .is_some_and(|e| e.is_foreign_key_violation());
+    if is_referenced {
+        return SupplierUsecaseError::InUse;
+    }
+    SupplierUsecaseError::DatabaseError(err)
+}
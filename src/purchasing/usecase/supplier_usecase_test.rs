@@ -0,0 +1,133 @@
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::purchasing::usecase::dto::SupplierInput;
+use crate::purchasing::usecase::supplier_usecase::{SupplierUsecase, SupplierUsecaseError};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+
+fn supplier(code: &str, linked_company_id: Option<Uuid>) -> SupplierInput {
+    SupplierInput {
+        linked_company_id,
+        code: code.into(),
+        name: format!("Supplier {code}"),
+        email: None,
+        phone_number: None,
+        address: None,
+        over_receipt_tolerance_pct: 10,
+        under_receipt_tolerance_pct: 5,
+    }
+}
+
+fn query(search: Option<&str>) -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: search.map(String::from),
+        sort: None,
+    }
+}
+
+// ACM buys, GLB is another company in the system
+fn setup() -> (SupplierUsecase<StockRepositoryMemory>, Company, Company) {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let products = ProductRepositoryMemory::new(companies.clone());
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    (SupplierUsecase::new(repo, AppMetrics::new()), acme, globex)
+}
+
+#[tokio::test]
+async fn supplier_is_linked_or_standalone() {
+    let (usecase, acme, globex) = setup();
+    let linked = usecase
+        .create_supplier(acme.id, supplier("GLB", Some(globex.id)))
+        .await
+        .unwrap();
+    assert_eq!(linked.linked_company_id, Some(globex.id));
+    let standalone = usecase
+        .create_supplier(acme.id, supplier("LOCAL", None))
+        .await
+        .unwrap();
+    assert_eq!(standalone.linked_company_id, None);
+
+    // a company links to at most one supplier of each company
+    let err = usecase
+        .create_supplier(acme.id, supplier("GLB2", Some(globex.id)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::LinkAlreadyExist));
+    // re-saving the linked supplier is not a conflict with itself
+    let updated = usecase
+        .update_supplier(linked.id, supplier("GLB", Some(globex.id)))
+        .await
+        .unwrap();
+    assert_eq!(updated.id, linked.id);
+
+    let result = usecase
+        .list_company_suppliers(acme.id, &query(Some("loc")))
+        .await
+        .unwrap();
+    assert_eq!(result.total_data, 1);
+    assert_eq!(result.data[0].id, standalone.id);
+}
+
+#[tokio::test]
+async fn create_validates_link_code_and_tolerance() {
+    let (usecase, acme, _) = setup();
+    usecase
+        .create_supplier(acme.id, supplier("S-1", None))
+        .await
+        .unwrap();
+
+    let err = usecase
+        .create_supplier(acme.id, supplier("S-1", None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::CodeAlreadyExist));
+
+    let err = usecase
+        .create_supplier(acme.id, supplier("S-2", Some(acme.id)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::SelfLink));
+
+    let err = usecase
+        .create_supplier(acme.id, supplier("S-2", Some(Uuid::new_v4())))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::LinkedCompanyNotFound));
+
+    let mut input = supplier("S-2", None);
+    input.over_receipt_tolerance_pct = 101;
+    let err = usecase.create_supplier(acme.id, input).await.unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::InvalidTolerance));
+
+    let err = usecase
+        .create_supplier(Uuid::new_v4(), supplier("S-2", None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::CompanyNotFound));
+}
+
+#[tokio::test]
+async fn delete_removes_supplier() {
+    let (usecase, acme, _) = setup();
+    let created = usecase
+        .create_supplier(acme.id, supplier("S-1", None))
+        .await
+        .unwrap();
+
+    usecase.delete_supplier(created.id).await.unwrap();
+
+    let err = usecase.get_supplier(created.id).await.unwrap_err();
+    assert!(matches!(err, SupplierUsecaseError::NotFound));
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// stock of a product in a warehouse. on_hand is the sum of its balances over
+// the warehouse's locations, reserved is held for open sales orders. stock in
+// lots past their expiry date is on hand but never available
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockAvailability {
+    pub product_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub on_hand: i64,
+    pub reserved: i64,
+    /// on_hand less expired lots and reserved, negative when lots expired under the reservations
+    pub available: i64,
+}
+
+// availability is always listed per company and warehouse
+#[derive(Debug, Clone)]
+pub struct StockAvailabilityFilter {
+    pub company_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub product_id: Option<Uuid>,
+}
+
+// quantity of a product held for open sales orders in a warehouse, the sum of
+// the reserved quantities of their lines
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct StockReservation {
+    pub product_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub company_id: Uuid,
+    pub quantity: i64,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,4 @@
+pub mod availability;
+pub mod pick;
+pub mod sales_order;
+pub mod shipment;
@@ -0,0 +1,28 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// stock of a product at a location of a warehouse that no open shipment has
+// picked yet, per lot for lot tracked products
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct PickableStock {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub expires_on: Option<NaiveDate>,
+    pub quantity: i64,
+}
+
+// where to pick a part of an order line from, lots expiring first are picked
+// first
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct PickSuggestion {
+    pub line_id: Uuid,
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub expires_on: Option<NaiveDate>,
+    pub quantity: i64,
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// an order a customer placed with the company, served from one warehouse. the
+// lines are edited while it is a draft, stock is reserved on confirmation
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct SalesOrder {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    /// the company buying
+    pub customer_id: Uuid,
+    /// reservations and shipments are made in this warehouse
+    pub warehouse_id: Uuid,
+    pub number: String,
+    pub status: SalesOrderStatus,
+    pub note: Option<String>,
+    /// reservations not picked by then are released, none keeps them until shipped or cancelled
+    pub reserved_until: Option<DateTime<Utc>>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_by: Option<Uuid>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub shipped_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+// stored as text in sales_orders.status. draft -> confirmed ->
+// partially_shipped -> shipped, an open order can be cancelled which releases
+// whatever it still has reserved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum SalesOrderStatus {
+    Draft,
+    Confirmed,
+    PartiallyShipped,
+    Shipped,
+    Cancelled,
+}
+
+impl SalesOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SalesOrderStatus::Draft => "draft",
+            SalesOrderStatus::Confirmed => "confirmed",
+            SalesOrderStatus::PartiallyShipped => "partially_shipped",
+            SalesOrderStatus::Shipped => "shipped",
+            SalesOrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // confirmed and not shipped in full nor cancelled, stock can be reserved
+    // and shipped
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            SalesOrderStatus::Confirmed | SalesOrderStatus::PartiallyShipped
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct SalesOrderLine {
+    pub id: Uuid,
+    pub sales_order_id: Uuid,
+    /// order of the lines in the sales order, from 1
+    pub position: i32,
+    pub product_id: Uuid,
+    /// in the product's base unit, converted from unit_quantity
+    pub quantity: i64,
+    /// the unit the line was entered in
+    pub unit: String,
+    /// the quantity as entered, in unit
+    pub unit_quantity: Decimal,
+    /// held in the order's warehouse and not shipped yet
+    pub reserved_quantity: i64,
+    /// the part of the reservation on shipments that are not shipped yet
+    pub picked_quantity: i64,
+    /// summed over every shipped shipment
+    pub shipped_quantity: i64,
+}
+
+impl SalesOrderLine {
+    // neither reserved nor shipped, waiting for stock
+    pub fn backordered_quantity(&self) -> i64 {
+        self.quantity - self.reserved_quantity - self.shipped_quantity
+    }
+
+    // reserved and not on a shipment yet
+    pub fn pickable_quantity(&self) -> i64 {
+        self.reserved_quantity - self.picked_quantity
+    }
+}
+
+// narrows a sales order listing on top of search, orders are always listed per company
+#[derive(Debug, Clone)]
+pub struct SalesOrderFilter {
+    pub company_id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub status: Option<SalesOrderStatus>,
+}
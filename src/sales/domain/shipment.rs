@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// reserved stock of a sales order picked from locations of its warehouse. the
+// ledger only changes when it ships, every line then points at its issue movement
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct Shipment {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub sales_order_id: Uuid,
+    pub number: String,
+    pub status: ShipmentStatus,
+    pub note: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub packed_at: Option<DateTime<Utc>>,
+    pub shipped_by: Option<Uuid>,
+    pub shipped_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+// stored as text in shipments.status. picking -> packed -> shipped, cancelled
+// from either of the first two
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum ShipmentStatus {
+    Picking,
+    Packed,
+    Shipped,
+    Cancelled,
+}
+
+impl ShipmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShipmentStatus::Picking => "picking",
+            ShipmentStatus::Packed => "packed",
+            ShipmentStatus::Shipped => "shipped",
+            ShipmentStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // its quantities are still picked from the reservation
+    pub fn is_open(&self) -> bool {
+        matches!(self, ShipmentStatus::Picking | ShipmentStatus::Packed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct ShipmentLine {
+    pub id: Uuid,
+    pub shipment_id: Uuid,
+    /// order of the lines in the shipment, from 1
+    pub position: i32,
+    pub sales_order_line_id: Uuid,
+    /// a location of the order's warehouse
+    pub location_id: Uuid,
+    /// the lot picked, set for lot tracked products
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    /// the serials picked, set for serialised products
+    pub serial_numbers: Vec<String>,
+    /// the issue movement, set once shipped
+    pub movement_id: Option<Uuid>,
+}
@@ -0,0 +1,82 @@
+use crate::app_response::error::ResponseError;
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecaseError;
+use crate::sales::usecase::shipment_usecase::ShipmentUsecaseError;
+use crate::stock::handler::map_stock_error::map_usecase_stock_error;
+
+pub fn map_usecase_sales_order_error(err: SalesOrderUsecaseError) -> ResponseError {
+    match err {
+        SalesOrderUsecaseError::CompanyNotFound => {
+            ResponseError::NotFound("company not found".into())
+        }
+        SalesOrderUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        SalesOrderUsecaseError::NumberAlreadyExist => {
+            ResponseError::BadRequest("sales order number already exists".into())
+        }
+        SalesOrderUsecaseError::CustomerNotFound => {
+            ResponseError::BadRequest("customer not found".into())
+        }
+        SalesOrderUsecaseError::InvalidCustomer => {
+            ResponseError::BadRequest("a company can not sell to itself".into())
+        }
+        SalesOrderUsecaseError::WarehouseNotFound => {
+            ResponseError::BadRequest("warehouse not found".into())
+        }
+        SalesOrderUsecaseError::CompanyMismatch => ResponseError::BadRequest(
+            "products and warehouse must belong to the order's company".into(),
+        ),
+        SalesOrderUsecaseError::ProductNotFound => {
+            ResponseError::BadRequest("product not found".into())
+        }
+        SalesOrderUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        SalesOrderUsecaseError::UnitNotFound => ResponseError::BadRequest("unit not found".into()),
+        SalesOrderUsecaseError::Conversion(err) => ResponseError::BadRequest(err.to_string()),
+        SalesOrderUsecaseError::NoLines => ResponseError::BadRequest("lines are required".into()),
+        SalesOrderUsecaseError::InvalidStatus(status) => {
+            ResponseError::BadRequest(format!("sales order is {}", status.as_str()))
+        }
+        SalesOrderUsecaseError::InvalidReservedUntil => {
+            ResponseError::BadRequest("reserved_until must be in the future".into())
+        }
+        SalesOrderUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+pub fn map_usecase_shipment_error(err: ShipmentUsecaseError) -> ResponseError {
+    match err {
+        ShipmentUsecaseError::SalesOrderNotFound | ShipmentUsecaseError::NotFound => {
+            ResponseError::NotFound("data not found".into())
+        }
+        ShipmentUsecaseError::NumberAlreadyExist => {
+            ResponseError::BadRequest("shipment number already exists".into())
+        }
+        ShipmentUsecaseError::InvalidOrderStatus(status) => {
+            ResponseError::BadRequest(format!("sales order is {}", status.as_str()))
+        }
+        ShipmentUsecaseError::InvalidStatus(status) => {
+            ResponseError::BadRequest(format!("shipment is {}", status.as_str()))
+        }
+        ShipmentUsecaseError::NoLines => ResponseError::BadRequest("lines are required".into()),
+        ShipmentUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        ShipmentUsecaseError::LineNotFound => {
+            ResponseError::BadRequest("sales order line not found".into())
+        }
+        ShipmentUsecaseError::LocationNotFound => {
+            ResponseError::BadRequest("location not found in the order's warehouse".into())
+        }
+        ShipmentUsecaseError::OverPick { line_id, pickable } => {
+            ResponseError::BadRequest(format!("line {line_id} can pick at most {pickable} more"))
+        }
+        ShipmentUsecaseError::InsufficientStock {
+            product_id,
+            available,
+        } => ResponseError::BadRequest(format!(
+            "insufficient stock of product {product_id}, {available} available"
+        )),
+        ShipmentUsecaseError::Posting(err) => map_usecase_stock_error(err),
+        ShipmentUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
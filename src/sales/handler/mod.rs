@@ -0,0 +1,8 @@
+pub mod map_sales_error;
+pub mod sales_order_handler;
+#[cfg(test)]
+mod sales_order_handler_test;
+pub mod shipment_handler;
+#[cfg(test)]
+mod shipment_handler_test;
+pub mod types;
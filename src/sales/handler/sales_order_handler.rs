@@ -0,0 +1,327 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::purchasing::handler::map_purchasing_error::validate_document_number;
+use crate::sales::domain::availability::StockAvailability;
+use crate::sales::domain::sales_order::SalesOrder;
+use crate::sales::handler::map_sales_error::map_usecase_sales_order_error;
+use crate::sales::handler::types::{
+    PostSalesOrderRequest, ReservationRequest, SalesOrderFilterRequest, SalesOrderResponse,
+    StockAvailabilityFilterRequest, UpdateSalesOrderRequest,
+};
+use crate::sales::repository::helper_query::SALES_ORDER_SORT_COLUMNS;
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecase;
+use crate::stock::handler::map_stock_error::posted_by;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/sales/orders",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    request_body = PostSalesOrderRequest,
+    responses(
+        (status = 201, description = "draft sales order created, nothing is reserved before confirmation", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid input, number taken, or a customer, warehouse or product that is unknown or of another company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostSalesOrderRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+    let created_by = posted_by(&claims)?;
+
+    let order = usecase
+        .create_order(req.company_id, req.into(), created_by)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/orders",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, SalesOrderFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's sales orders without their lines, search matches the number", body = ResponseSuccessBody<Vec<SalesOrder>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_sales_orders_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<SalesOrderFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&SALES_ORDER_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let order_list_data = usecase
+        .list_orders(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        order_list_data.total_data as u64,
+        Some(order_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/orders/{id}",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    responses(
+        (status = 200, description = "the sales order with its reserved, picked, shipped and backordered quantities", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let order = usecase
+        .get_order(id)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/sales/orders/{id}",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    request_body = UpdateSalesOrderRequest,
+    responses(
+        (status = 200, description = "draft updated", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid id or input, number taken, or the order is no longer a draft", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<UpdateSalesOrderRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+
+    let order = usecase
+        .update_order(id, req.into())
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sales/orders/{id}",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    responses(
+        (status = 200, description = "draft deleted with its lines", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or the order is no longer a draft", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_order(id)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/orders/{id}/confirm",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    request_body = ReservationRequest,
+    responses(
+        (status = 200, description = "order confirmed, what the warehouse has available is reserved and the rest backordered", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid id, not a draft, no lines, or reserved_until in the past", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn confirm_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<ReservationRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let confirmed_by = posted_by(&claims)?;
+
+    let order = usecase
+        .confirm_order(id, req.into(), confirmed_by)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/orders/{id}/reserve",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    request_body = ReservationRequest,
+    responses(
+        (status = 200, description = "backorders reserved as far as the warehouse has stock available", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid id, order not open, or reserved_until in the past", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn reserve_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<ReservationRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let order = usecase
+        .reserve_order(id, req.into())
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/orders/{id}/cancel",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    responses(
+        (status = 200, description = "order cancelled, its reservations released and open shipments cancelled", body = ResponseSuccessBody<SalesOrderResponse>),
+        (status = 400, description = "invalid id, or the order is shipped or cancelled already", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn cancel_sales_order_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let order = usecase
+        .cancel_order(id)
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SalesOrderResponse::from(order)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/availability",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(StockAvailabilityFilterRequest),
+    responses(
+        (status = 200, description = "on hand, reserved and available quantity per product in the warehouse", body = ResponseSuccessBody<Vec<StockAvailability>>),
+        (status = 400, description = "missing company or warehouse, or a warehouse that is unknown or of another company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_stock_availability_handler<R: SalesRepository>(
+    State(usecase): State<Arc<SalesOrderUsecase<R>>>,
+    Query(filter): Query<StockAvailabilityFilterRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let availability = usecase
+        .get_availability(&filter.into())
+        .await
+        .map_err(map_usecase_sales_order_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(availability)))
+}
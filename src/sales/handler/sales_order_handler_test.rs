@@ -0,0 +1,319 @@
+use axum::http::{Method, StatusCode};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body};
+use crate::company::domain::company::Company;
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    globex: Company,
+    product_id: String,
+    warehouse_id: String,
+}
+
+impl Fixture {
+    fn order(&self, number: &str, quantity: i64) -> Value {
+        json!({
+            "company_id": self.acme.id,
+            "customer_id": self.globex.id,
+            "warehouse_id": self.warehouse_id,
+            "number": number,
+            "lines": [{ "product_id": self.product_id, "quantity": quantity }],
+        })
+    }
+
+    async fn availability(&self) -> Value {
+        let response = self
+            .app
+            .request(
+                Method::GET,
+                &format!(
+                    "/sales/availability?company_id={}&warehouse_id={}",
+                    self.acme.id, self.warehouse_id
+                ),
+                Some(&self.token),
+                None,
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        response.json()["data"][0].clone()
+    }
+}
+
+// ACM sells bolts to GLB out of a warehouse holding 10 of them
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let product_id = product["id"].as_str().unwrap().to_string();
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "MAIN", "name": "Main" }),
+        )
+        .await;
+    let warehouse_id = warehouse["id"].as_str().unwrap().to_string();
+    let location = app
+        .create(
+            &token,
+            &format!("/warehouse/{warehouse_id}/locations"),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+    app.create(
+        &token,
+        "/stock/movements",
+        json!({
+            "product_id": product_id,
+            "location_id": location["id"],
+            "movement_type": "receipt",
+            "quantity": 10,
+        }),
+    )
+    .await;
+
+    Fixture {
+        app,
+        token,
+        acme,
+        globex,
+        product_id,
+        warehouse_id,
+    }
+}
+
+#[tokio::test]
+async fn sales_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/sales/orders?company_id={id}")),
+        (Method::POST, "/sales/orders".to_string()),
+        (Method::GET, format!("/sales/orders/{id}")),
+        (Method::PUT, format!("/sales/orders/{id}")),
+        (Method::DELETE, format!("/sales/orders/{id}")),
+        (Method::POST, format!("/sales/orders/{id}/confirm")),
+        (Method::POST, format!("/sales/orders/{id}/reserve")),
+        (Method::POST, format!("/sales/orders/{id}/cancel")),
+        (Method::GET, format!("/sales/orders/{id}/shipments")),
+        (Method::POST, format!("/sales/orders/{id}/shipments")),
+        (Method::GET, format!("/sales/shipments/{id}")),
+        (Method::POST, format!("/sales/shipments/{id}/pack")),
+        (Method::POST, format!("/sales/shipments/{id}/ship")),
+        (Method::POST, format!("/sales/shipments/{id}/cancel")),
+        (
+            Method::GET,
+            format!("/sales/availability?company_id={id}&warehouse_id={id}"),
+        ),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn confirmed_order_reserves_stock_and_backorders_the_rest() {
+    let f = setup().await;
+
+    let draft = f
+        .app
+        .create(&f.token, "/sales/orders", f.order("SO-1", 4))
+        .await;
+    assert_eq!(draft["status"], "draft");
+    assert_eq!(draft["created_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(draft["lines"][0]["backordered_quantity"], 4);
+    let uri = format!("/sales/orders/{}", draft["id"].as_str().unwrap());
+
+    let mut update = f.order("SO-1", 12);
+    update.as_object_mut().unwrap().remove("company_id");
+    let response = f
+        .app
+        .request(Method::PUT, &uri, Some(&f.token), Some(update))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    assert_eq!(response.json()["data"]["lines"][0]["quantity"], 12);
+
+    let reserved_until = Utc::now() + Duration::days(1);
+    let (status, body) = f
+        .app
+        .post(
+            &f.token,
+            &format!("{uri}/confirm"),
+            Some(json!({ "reserved_until": reserved_until })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "confirmed");
+    assert_eq!(body["data"]["confirmed_by"], TEST_ADMIN_ID.to_string());
+    assert!(body["data"]["reserved_until"].is_string());
+    assert_eq!(body["data"]["lines"][0]["reserved_quantity"], 10);
+    assert_eq!(body["data"]["lines"][0]["backordered_quantity"], 2);
+
+    assert_eq!(
+        f.availability().await,
+        json!({
+            "product_id": f.product_id,
+            "warehouse_id": f.warehouse_id,
+            "on_hand": 10,
+            "reserved": 10,
+            "available": 0,
+        })
+    );
+
+    let (status, body) = f
+        .app
+        .post(
+            &f.token,
+            &format!("{uri}/reserve"),
+            Some(json!({ "reserved_until": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["reserved_until"], Value::Null);
+    assert_eq!(body["data"]["lines"][0]["backordered_quantity"], 2);
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!(
+                "/sales/orders?company_id={}&status=confirmed&search=so&sort=-number",
+                f.acme.id
+            ),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total_data"], 1);
+
+    let response = f
+        .app
+        .request(Method::POST, &format!("{uri}/cancel"), Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    let body = response.json();
+    assert_eq!(body["data"]["status"], "cancelled");
+    assert_eq!(body["data"]["lines"][0]["reserved_quantity"], 0);
+    assert_eq!(f.availability().await["available"], 10);
+}
+
+#[tokio::test]
+async fn sales_order_rejects_invalid_input() {
+    let f = setup().await;
+
+    let mut to_itself = f.order("SO-1", 1);
+    to_itself["customer_id"] = json!(f.acme.id);
+    let mut unknown_product = f.order("SO-1", 1);
+    unknown_product["lines"][0]["product_id"] = json!(Uuid::new_v4());
+    let cases = [
+        (f.order("", 1), "Number is required"),
+        (
+            f.order(&"S".repeat(31), 1),
+            "Number can not be longer than 30 characters",
+        ),
+        (f.order("SO-1", 0), "quantity is invalid"),
+        (to_itself, "a company can not sell to itself"),
+        (unknown_product, "product not found"),
+    ];
+    for (body, message) in cases {
+        let (status, body) = f.app.post(&f.token, "/sales/orders", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(body, error_body(400, message));
+    }
+
+    let draft = f
+        .app
+        .create(&f.token, "/sales/orders", f.order("SO-1", 1))
+        .await;
+    let uri = format!("/sales/orders/{}", draft["id"].as_str().unwrap());
+    let (status, body) = f
+        .app
+        .post(&f.token, "/sales/orders", Some(f.order("SO-1", 1)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "sales order number already exists"));
+
+    let past = Utc::now() - Duration::hours(1);
+    let (status, body) = f
+        .app
+        .post(
+            &f.token,
+            &format!("{uri}/confirm"),
+            Some(json!({ "reserved_until": past })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "reserved_until must be in the future")
+    );
+    let (status, body) = f
+        .app
+        .post(
+            &f.token,
+            &format!("{uri}/reserve"),
+            Some(json!({ "reserved_until": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "sales order is draft"));
+
+    let response = f
+        .app
+        .request(Method::DELETE, &uri, Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = f.app.request(Method::GET, &uri, Some(&f.token), None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/sales/orders?company_id={}&sort=note", f.acme.id),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!(
+                "/sales/availability?company_id={}&warehouse_id={}",
+                f.acme.id,
+                Uuid::new_v4()
+            ),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "warehouse not found")
+    );
+}
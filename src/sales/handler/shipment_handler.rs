@@ -0,0 +1,235 @@
+use axum::{Extension, Json, extract::State, http::StatusCode, response::IntoResponse};
+use std::sync::Arc;
+
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::path_uuid::PathUuid;
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::purchasing::handler::map_purchasing_error::validate_document_number;
+use crate::sales::domain::pick::PickSuggestion;
+use crate::sales::domain::shipment::Shipment;
+use crate::sales::handler::map_sales_error::map_usecase_shipment_error;
+use crate::sales::handler::types::{PostShipmentRequest, ShipmentResponse};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::shipment_usecase::ShipmentUsecase;
+use crate::stock::handler::map_stock_error::posted_by;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/sales/orders/{id}/shipments",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    request_body = PostShipmentRequest,
+    responses(
+        (status = 201, description = "shipment started, its quantities are picked from the order's reservations", body = ResponseSuccessBody<ShipmentResponse>),
+        (status = 400, description = "invalid id or input, order not open, number taken, unknown line, location outside the warehouse, or more than the line has reserved", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_shipment_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostShipmentRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_document_number(&req.number)?;
+    let created_by = posted_by(&claims)?;
+
+    let shipment = usecase
+        .create_shipment(id, req.into(), created_by)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(ShipmentResponse::from(shipment)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/orders/{id}/shipments",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    responses(
+        (status = 200, description = "the order's shipments without their lines, oldest first", body = ResponseSuccessBody<Vec<Shipment>>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_shipments_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let shipments = usecase
+        .list_shipments(id)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(shipments)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/orders/{id}/picks",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "sales order id")),
+    responses(
+        (status = 200, description = "where to pick what the open lines still need, first expired first out, expired lots left out", body = ResponseSuccessBody<Vec<PickSuggestion>>),
+        (status = 400, description = "invalid id or order not open", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "sales order not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_pick_suggestions_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let picks = usecase
+        .suggest_picks(id)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(picks)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sales/shipments/{id}",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "shipment id")),
+    responses(
+        (status = 200, description = "the shipment with its lines and, once shipped, their movements", body = ResponseSuccessBody<ShipmentResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "shipment not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_shipment_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let shipment = usecase
+        .get_shipment(id)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(ShipmentResponse::from(shipment)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/shipments/{id}/pack",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "shipment id")),
+    responses(
+        (status = 200, description = "shipment packed and ready to ship", body = ResponseSuccessBody<ShipmentResponse>),
+        (status = 400, description = "invalid id or the shipment is not being picked", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "shipment not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn pack_shipment_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let shipment = usecase
+        .pack_shipment(id)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(ShipmentResponse::from(shipment)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/shipments/{id}/ship",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "shipment id")),
+    responses(
+        (status = 200, description = "shipment shipped, an issue movement is posted per line and the reservations are consumed", body = ResponseSuccessBody<ShipmentResponse>),
+        (status = 400, description = "invalid id, shipment not packed, or a location holds less than the shipment takes", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "shipment not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn ship_shipment_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let shipped_by = posted_by(&claims)?;
+
+    let shipment = usecase
+        .ship_shipment(id, shipped_by)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(ShipmentResponse::from(shipment)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sales/shipments/{id}/cancel",
+    tag = "sales",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "shipment id")),
+    responses(
+        (status = 200, description = "shipment cancelled, its quantities stay reserved for the order", body = ResponseSuccessBody<ShipmentResponse>),
+        (status = 400, description = "invalid id, or the shipment is shipped or cancelled already", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "shipment not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn cancel_shipment_handler<R: SalesRepository>(
+    State(usecase): State<Arc<ShipmentUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let shipment = usecase
+        .cancel_shipment(id)
+        .await
+        .map_err(map_usecase_shipment_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(ShipmentResponse::from(shipment)),
+    ))
+}
@@ -0,0 +1,270 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body};
+use crate::company::domain::company::Company;
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    product_id: String,
+    location_id: String,
+    // a confirmed order of 6 bolts, all of them reserved
+    order_uri: String,
+    line_id: Value,
+}
+
+impl Fixture {
+    fn shipment(&self, number: &str, quantity: i64) -> Value {
+        json!({
+            "number": number,
+            "lines": [{ "line_id": self.line_id, "location_id": self.location_id, "quantity": quantity }],
+        })
+    }
+
+    async fn on_hand(&self) -> Value {
+        let response = self
+            .app
+            .request(
+                Method::GET,
+                &format!(
+                    "/stock/balances?company_id={}&location_id={}",
+                    self.acme.id, self.location_id
+                ),
+                Some(&self.token),
+                None,
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+        response.json()["data"][0]["quantity"].clone()
+    }
+}
+
+// ACM sells 6 of its 10 bolts to GLB
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "MAIN", "name": "Main" }),
+        )
+        .await;
+    let location = app
+        .create(
+            &token,
+            &format!("/warehouse/{}/locations", warehouse["id"].as_str().unwrap()),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+    app.create(
+        &token,
+        "/stock/movements",
+        json!({
+            "product_id": product["id"],
+            "location_id": location["id"],
+            "movement_type": "receipt",
+            "quantity": 10,
+        }),
+    )
+    .await;
+    let draft = app
+        .create(
+            &token,
+            "/sales/orders",
+            json!({
+                "company_id": acme.id,
+                "customer_id": globex.id,
+                "warehouse_id": warehouse["id"],
+                "number": "SO-1",
+                "lines": [{ "product_id": product["id"], "quantity": 6 }],
+            }),
+        )
+        .await;
+    let order_uri = format!("/sales/orders/{}", draft["id"].as_str().unwrap());
+    let (status, _) = app
+        .post(
+            &token,
+            &format!("{order_uri}/confirm"),
+            Some(json!({ "reserved_until": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    Fixture {
+        app,
+        token,
+        acme,
+        product_id: product["id"].as_str().unwrap().to_string(),
+        location_id: location["id"].as_str().unwrap().to_string(),
+        order_uri,
+        line_id: draft["lines"][0]["id"].clone(),
+    }
+}
+
+#[tokio::test]
+async fn shipment_is_picked_packed_and_shipped() {
+    let f = setup().await;
+    let shipments = format!("{}/shipments", f.order_uri);
+
+    let shipment = f
+        .app
+        .create(&f.token, &shipments, f.shipment("SH-1", 4))
+        .await;
+    assert_eq!(shipment["status"], "picking");
+    assert_eq!(shipment["created_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(shipment["lines"][0]["movement_id"], Value::Null);
+    let uri = format!("/sales/shipments/{}", shipment["id"].as_str().unwrap());
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/ship"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "shipment is picking"));
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/pack"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "packed");
+    // the ledger only changes once it ships
+    assert_eq!(f.on_hand().await, 10);
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/ship"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "shipped");
+    assert_eq!(body["data"]["shipped_by"], TEST_ADMIN_ID.to_string());
+    let movement_id = body["data"]["lines"][0]["movement_id"].clone();
+    assert!(movement_id.is_string());
+    assert_eq!(f.on_hand().await, 6);
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/stock/movements/{}", movement_id.as_str().unwrap()),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let movement = response.json();
+    assert_eq!(movement["data"]["movement_type"], "issue");
+    assert_eq!(movement["data"]["quantity"], -4);
+    assert_eq!(movement["data"]["product_id"], f.product_id);
+    assert_eq!(movement["data"]["reference"], "SH-1");
+
+    let response = f
+        .app
+        .request(Method::GET, &f.order_uri, Some(&f.token), None)
+        .await;
+    let body = response.json();
+    assert_eq!(body["data"]["status"], "partially_shipped");
+    assert_eq!(body["data"]["lines"][0]["shipped_quantity"], 4);
+    assert_eq!(body["data"]["lines"][0]["reserved_quantity"], 2);
+
+    let last = f
+        .app
+        .create(&f.token, &shipments, f.shipment("SH-2", 2))
+        .await;
+    let uri = format!("/sales/shipments/{}", last["id"].as_str().unwrap());
+    f.app.post(&f.token, &format!("{uri}/pack"), None).await;
+    let (status, _) = f.app.post(&f.token, &format!("{uri}/ship"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let response = f
+        .app
+        .request(Method::GET, &f.order_uri, Some(&f.token), None)
+        .await;
+    assert_eq!(response.json()["data"]["status"], "shipped");
+
+    let response = f
+        .app
+        .request(Method::GET, &shipments, Some(&f.token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"][0]["number"], "SH-1");
+}
+
+#[tokio::test]
+async fn shipment_rejects_invalid_picks() {
+    let f = setup().await;
+    let shipments = format!("{}/shipments", f.order_uri);
+
+    let mut wrong_bin = f.shipment("SH-1", 1);
+    wrong_bin["lines"][0]["location_id"] = json!(Uuid::new_v4());
+    let mut unknown_line = f.shipment("SH-1", 1);
+    unknown_line["lines"][0]["line_id"] = json!(Uuid::new_v4());
+    let cases = [
+        (f.shipment("", 1), "Number is required".to_string()),
+        (f.shipment("SH-1", 0), "quantity is invalid".to_string()),
+        (unknown_line, "sales order line not found".to_string()),
+        (
+            wrong_bin,
+            "location not found in the order's warehouse".to_string(),
+        ),
+        (
+            f.shipment("SH-1", 7),
+            format!(
+                "line {} can pick at most 6 more",
+                f.line_id.as_str().unwrap()
+            ),
+        ),
+    ];
+    for (body, message) in cases {
+        let (status, body) = f.app.post(&f.token, &shipments, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(body, error_body(400, &message));
+    }
+
+    let shipment = f
+        .app
+        .create(&f.token, &shipments, f.shipment("SH-1", 6))
+        .await;
+    let uri = format!("/sales/shipments/{}", shipment["id"].as_str().unwrap());
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/cancel"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "cancelled");
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/pack"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "shipment is cancelled"));
+
+    let (status, _) = f
+        .app
+        .post(&f.token, &format!("{}/cancel", f.order_uri), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = f
+        .app
+        .post(&f.token, &shipments, Some(f.shipment("SH-2", 1)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "sales order is cancelled"));
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/sales/shipments/{}", Uuid::new_v4()),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::sales::domain::availability::StockAvailabilityFilter;
+use crate::sales::domain::sales_order::{
+    SalesOrder, SalesOrderFilter, SalesOrderLine, SalesOrderStatus,
+};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine};
+use crate::sales::usecase::dto::{
+    ReservationInput, SalesOrderDetail, SalesOrderInput, SalesOrderLineInput, ShipmentDetail,
+    ShipmentInput, ShipmentLineInput,
+};
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct SalesOrderLineRequest {
+    pub product_id: Uuid,
+    /// the product's unit_of_measure or one of its sales units, the
+    /// unit_of_measure when missing
+    pub unit: Option<String>,
+    /// in unit, e.g. 2 or "1.5"
+    pub quantity: Decimal,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostSalesOrderRequest {
+    /// the company selling, the warehouse and every product belong to it
+    pub company_id: Uuid,
+    /// the company buying
+    pub customer_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    pub lines: Vec<SalesOrderLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct UpdateSalesOrderRequest {
+    pub customer_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    /// replace the lines of the draft
+    pub lines: Vec<SalesOrderLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ReservationRequest {
+    /// reservations not picked by then are released, none keeps them until shipped or cancelled
+    pub reserved_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ShipmentLineRequest {
+    /// a line of the sales order
+    pub line_id: Uuid,
+    /// a location of the order's warehouse
+    pub location_id: Uuid,
+    /// required for lot tracked products, an expired lot can not be picked
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    /// one per unit for serialised products, in stock at the location
+    #[serde(default)]
+    pub serial_numbers: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostShipmentRequest {
+    pub number: String,
+    pub note: Option<String>,
+    /// picked from the reserved quantities of the lines
+    pub lines: Vec<ShipmentLineRequest>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SalesOrderLineResponse {
+    #[serde(flatten)]
+    pub line: SalesOrderLine,
+    /// neither reserved nor shipped, waiting for stock
+    pub backordered_quantity: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SalesOrderResponse {
+    #[serde(flatten)]
+    pub order: SalesOrder,
+    pub lines: Vec<SalesOrderLineResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ShipmentResponse {
+    #[serde(flatten)]
+    pub shipment: Shipment,
+    pub lines: Vec<ShipmentLine>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SalesOrderFilterRequest {
+    /// the sales orders of this company
+    pub company_id: Uuid,
+    pub customer_id: Option<Uuid>,
+    pub status: Option<SalesOrderStatus>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockAvailabilityFilterRequest {
+    pub company_id: Uuid,
+    /// a warehouse of the company
+    pub warehouse_id: Uuid,
+    pub product_id: Option<Uuid>,
+}
+
+impl From<SalesOrderLineRequest> for SalesOrderLineInput {
+    fn from(req: SalesOrderLineRequest) -> Self {
+        SalesOrderLineInput {
+            product_id: req.product_id,
+            unit: req.unit,
+            quantity: req.quantity,
+        }
+    }
+}
+
+impl From<PostSalesOrderRequest> for SalesOrderInput {
+    fn from(req: PostSalesOrderRequest) -> Self {
+        SalesOrderInput {
+            customer_id: req.customer_id,
+            warehouse_id: req.warehouse_id,
+            number: req.number,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<UpdateSalesOrderRequest> for SalesOrderInput {
+    fn from(req: UpdateSalesOrderRequest) -> Self {
+        SalesOrderInput {
+            customer_id: req.customer_id,
+            warehouse_id: req.warehouse_id,
+            number: req.number,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ReservationRequest> for ReservationInput {
+    fn from(req: ReservationRequest) -> Self {
+        ReservationInput {
+            reserved_until: req.reserved_until,
+        }
+    }
+}
+
+impl From<ShipmentLineRequest> for ShipmentLineInput {
+    fn from(req: ShipmentLineRequest) -> Self {
+        ShipmentLineInput {
+            line_id: req.line_id,
+            location_id: req.location_id,
+            lot_id: req.lot_id,
+            quantity: req.quantity,
+            serial_numbers: req.serial_numbers,
+        }
+    }
+}
+
+impl From<PostShipmentRequest> for ShipmentInput {
+    fn from(req: PostShipmentRequest) -> Self {
+        ShipmentInput {
+            number: req.number,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<SalesOrderDetail> for SalesOrderResponse {
+    fn from(detail: SalesOrderDetail) -> Self {
+        SalesOrderResponse {
+            order: detail.order,
+            lines: detail
+                .lines
+                .into_iter()
+                .map(|line| SalesOrderLineResponse {
+                    backordered_quantity: line.backordered_quantity(),
+                    line,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<ShipmentDetail> for ShipmentResponse {
+    fn from(detail: ShipmentDetail) -> Self {
+        ShipmentResponse {
+            shipment: detail.shipment,
+            lines: detail.lines,
+        }
+    }
+}
+
+impl From<SalesOrderFilterRequest> for SalesOrderFilter {
+    fn from(req: SalesOrderFilterRequest) -> Self {
+        SalesOrderFilter {
+            company_id: req.company_id,
+            customer_id: req.customer_id,
+            status: req.status,
+        }
+    }
+}
+
+impl From<StockAvailabilityFilterRequest> for StockAvailabilityFilter {
+    fn from(req: StockAvailabilityFilterRequest) -> Self {
+        StockAvailabilityFilter {
+            company_id: req.company_id,
+            warehouse_id: req.warehouse_id,
+            product_id: req.product_id,
+        }
+    }
+}
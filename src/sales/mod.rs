@@ -0,0 +1,6 @@
+pub mod domain;
+pub mod handler;
+pub mod repository;
+pub mod reservation_sweeper;
+pub mod routes;
+pub mod usecase;
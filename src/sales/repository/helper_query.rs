@@ -0,0 +1,36 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::sales::domain::sales_order::SalesOrderFilter;
+
+// sortable columns, sort is pushed into ORDER BY as written so the handler
+// rejects anything else
+pub const SALES_ORDER_SORT_COLUMNS: [&str; 9] = [
+    "id",
+    "customer_id",
+    "warehouse_id",
+    "number",
+    "status",
+    "reserved_until",
+    "created_at",
+    "confirmed_at",
+    "shipped_at",
+];
+
+pub const SALES_ORDER_DEFAULT_ORDER: &str = "created_at, id";
+
+pub fn apply_sales_order_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &SalesOrderFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(customer_id) = filter.customer_id {
+        qb.push(" AND customer_id = ").push_bind(customer_id);
+    }
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(s) = search {
+        qb.push(" AND number ILIKE ").push_bind(format!("%{s}%"));
+    }
+}
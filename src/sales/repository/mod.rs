@@ -0,0 +1,7 @@
+pub mod sales_repository;
+pub mod sales_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod sales_repository_memory;
+#[cfg(test)]
+mod sales_repository_contract_test;
+pub mod helper_query;
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use crate::app_request::pagination::PaginationRequest;
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::pick::PickableStock;
+use crate::sales::domain::sales_order::{SalesOrder, SalesOrderFilter, SalesOrderLine};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine};
+use crate::stock::repository::stock_repository::StockRepository;
+
+// shipping posts to the ledger and reserving reads the balances in the same
+// unit of work, so sales lives on top of the stock repository
+#[async_trait]
+pub trait SalesRepository: StockRepository {
+    async fn check_existing_sales_order_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_sales_order(&self, uow: &mut Self::Uow, order: SalesOrder) -> Result<SalesOrder, sqlx::Error>;
+    async fn update_sales_order(&self, uow: &mut Self::Uow, order: SalesOrder) -> Result<SalesOrder, sqlx::Error>;
+    async fn get_sales_order_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<SalesOrder>, sqlx::Error>;
+    // like get_sales_order_by_id, the row stays locked until the unit of work
+    // finishes so reservations and shipments of one order queue up
+    async fn lock_sales_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<SalesOrder>, sqlx::Error>;
+    // the lines go with it
+    async fn delete_sales_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+    async fn count_sales_orders(&self, uow: &mut Self::Uow, filter: &SalesOrderFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_sales_orders(&self, uow: &mut Self::Uow, filter: &SalesOrderFilter, query: &PaginationRequest) -> Result<Vec<SalesOrder>, sqlx::Error>;
+    // open orders whose reserved_until is not after the given time, oldest first
+    async fn find_expired_sales_orders(&self, uow: &mut Self::Uow, now: DateTime<Utc>) -> Result<Vec<Uuid>, sqlx::Error>;
+    // ordered by position
+    async fn find_sales_order_lines(&self, uow: &mut Self::Uow, sales_order_id: &Uuid) -> Result<Vec<SalesOrderLine>, sqlx::Error>;
+    // drops the lines of the order and stores the given ones
+    async fn replace_sales_order_lines(&self, uow: &mut Self::Uow, sales_order_id: &Uuid, lines: Vec<SalesOrderLine>) -> Result<Vec<SalesOrderLine>, sqlx::Error>;
+    // stores the reserved, picked and shipped quantities of the line
+    async fn update_sales_order_line(&self, uow: &mut Self::Uow, line: &SalesOrderLine) -> Result<(), sqlx::Error>;
+    // the reservation is locked through StockRepository::lock_reservation
+    async fn update_reservation(&self, uow: &mut Self::Uow, product_id: &Uuid, warehouse_id: &Uuid, quantity: i64, updated_at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+    // every product with a balance or a reservation in the warehouse, ordered
+    // by product. lots expired before today are not available
+    async fn find_availability(&self, uow: &mut Self::Uow, filter: &StockAvailabilityFilter, today: NaiveDate) -> Result<Vec<StockAvailability>, sqlx::Error>;
+    async fn check_existing_shipment_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str) -> Result<bool, sqlx::Error>;
+    async fn create_shipment(&self, uow: &mut Self::Uow, shipment: Shipment, lines: Vec<ShipmentLine>) -> Result<(Shipment, Vec<ShipmentLine>), sqlx::Error>;
+    async fn update_shipment(&self, uow: &mut Self::Uow, shipment: Shipment) -> Result<Shipment, sqlx::Error>;
+    async fn update_shipment_line_movement(&self, uow: &mut Self::Uow, line_id: &Uuid, movement_id: &Uuid) -> Result<(), sqlx::Error>;
+    async fn get_shipment_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Shipment>, sqlx::Error>;
+    // ordered by position
+    async fn find_shipment_lines(&self, uow: &mut Self::Uow, shipment_id: &Uuid) -> Result<Vec<ShipmentLine>, sqlx::Error>;
+    // oldest first
+    async fn find_sales_order_shipments(&self, uow: &mut Self::Uow, sales_order_id: &Uuid) -> Result<Vec<Shipment>, sqlx::Error>;
+    // stock of the products in the warehouse less what open shipments picked,
+    // only positive quantities. ordered by product, expiry date with lots
+    // without one last, location and lot
+    async fn find_pickable_stock(&self, uow: &mut Self::Uow, warehouse_id: &Uuid, product_ids: &[Uuid]) -> Result<Vec<PickableStock>, sqlx::Error>;
+}
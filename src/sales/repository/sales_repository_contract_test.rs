@@ -0,0 +1,845 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// stock_repository_contract_test. ACM sells to GLB from its north warehouse,
+// which has two bins, GLB has a warehouse of its own. postgres runs need
+// TEST_DATABASE_URL and are skipped without it.
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{
+    ACME_ID, Catalogue, GLOBEX_ID, at, bin, companies, product, seed_catalogue, seed_companies,
+    warehouse,
+};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::pick::PickableStock;
+use crate::sales::domain::sales_order::{
+    SalesOrder, SalesOrderFilter, SalesOrderLine, SalesOrderStatus,
+};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine, ShipmentStatus};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::stock::domain::lot::StockLot;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+const BOLT_ID: Uuid = Uuid::from_u128(11);
+const NUT_ID: Uuid = Uuid::from_u128(12);
+const MILK_ID: Uuid = Uuid::from_u128(13);
+const NORTH_ID: Uuid = Uuid::from_u128(21);
+const GLOBEX_MAIN_ID: Uuid = Uuid::from_u128(23);
+const NORTH_BIN_ID: Uuid = Uuid::from_u128(31);
+const NORTH_SHELF_ID: Uuid = Uuid::from_u128(32);
+const GLOBEX_BIN_ID: Uuid = Uuid::from_u128(33);
+const USER_ID: Uuid = Uuid::from_u128(99);
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                let products = super::ProductRepositoryMemory::new(companies.clone());
+                let warehouses = super::WarehouseRepositoryMemory::new(companies.clone());
+                super::seed_catalogue(&products, &warehouses, super::catalogue()).await;
+                super::$scenario(&super::StockRepositoryMemory::new(
+                    companies, products, warehouses,
+                ))
+                .await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::seed_catalogue(
+                    &super::ProductRepositorySqlx::new(db.pool.clone()),
+                    &super::WarehouseRepositorySqlx::new(db.pool.clone()),
+                    super::catalogue(),
+                )
+                .await;
+                super::$scenario(&super::StockRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(stores_sales_orders_and_lines);
+contract_test!(filters_sorts_and_pages_sales_orders);
+contract_test!(tracks_reservations_and_availability);
+contract_test!(stores_shipments);
+contract_test!(finds_pickable_stock);
+
+fn catalogue() -> Catalogue {
+    Catalogue {
+        products: vec![
+            product(BOLT_ID, ACME_ID, "BOLT"),
+            product(NUT_ID, ACME_ID, "NUT"),
+            Product {
+                is_lot_tracked: true,
+                ..product(MILK_ID, ACME_ID, "MILK")
+            },
+        ],
+        warehouses: vec![
+            warehouse(NORTH_ID, ACME_ID, "NORTH"),
+            warehouse(GLOBEX_MAIN_ID, GLOBEX_ID, "MAIN"),
+        ],
+        locations: vec![
+            bin(NORTH_BIN_ID, NORTH_ID, "A"),
+            bin(NORTH_SHELF_ID, NORTH_ID, "B"),
+            bin(GLOBEX_BIN_ID, GLOBEX_MAIN_ID, "A"),
+        ],
+    }
+}
+
+fn order(number: &str, status: SalesOrderStatus, minutes: i64) -> SalesOrder {
+    SalesOrder {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        customer_id: GLOBEX_ID,
+        warehouse_id: NORTH_ID,
+        number: number.into(),
+        status,
+        note: None,
+        reserved_until: None,
+        created_by: USER_ID,
+        created_at: at(minutes),
+        confirmed_by: None,
+        confirmed_at: None,
+        shipped_at: None,
+        cancelled_at: None,
+    }
+}
+
+fn line(order: &SalesOrder, position: i32, product_id: Uuid, quantity: i64) -> SalesOrderLine {
+    SalesOrderLine {
+        id: Uuid::new_v4(),
+        sales_order_id: order.id,
+        position,
+        product_id,
+        quantity,
+        unit: "pcs".into(),
+        unit_quantity: Decimal::from(quantity),
+        reserved_quantity: 0,
+        picked_quantity: 0,
+        shipped_quantity: 0,
+    }
+}
+
+fn shipment(order: &SalesOrder, number: &str, minutes: i64) -> Shipment {
+    Shipment {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        sales_order_id: order.id,
+        number: number.into(),
+        status: ShipmentStatus::Picking,
+        note: None,
+        created_by: USER_ID,
+        created_at: at(minutes),
+        packed_at: None,
+        shipped_by: None,
+        shipped_at: None,
+        cancelled_at: None,
+    }
+}
+
+fn shipment_line(shipment: &Shipment, position: i32, line: &SalesOrderLine) -> ShipmentLine {
+    ShipmentLine {
+        id: Uuid::new_v4(),
+        shipment_id: shipment.id,
+        position,
+        sales_order_line_id: line.id,
+        location_id: NORTH_BIN_ID,
+        lot_id: None,
+        quantity: 1,
+        serial_numbers: Vec::new(),
+        movement_id: None,
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+async fn seed_orders<R: SalesRepository>(repo: &R, orders: &[(SalesOrder, Vec<SalesOrderLine>)]) {
+    let mut uow = repo.begin().await.unwrap();
+    for (order, lines) in orders {
+        repo.create_sales_order(&mut uow, order.clone())
+            .await
+            .unwrap();
+        repo.replace_sales_order_lines(&mut uow, &order.id, lines.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+async fn seed_balance<R: SalesRepository>(
+    repo: &R,
+    product_id: Uuid,
+    location_id: Uuid,
+    quantity: i64,
+) {
+    let mut uow = repo.begin().await.unwrap();
+    repo.lock_balance(&mut uow, &ACME_ID, &product_id, &location_id)
+        .await
+        .unwrap();
+    repo.update_balance(&mut uow, &product_id, &location_id, quantity, at(1))
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn stores_sales_orders_and_lines<R: SalesRepository>(repo: &R) {
+    let draft = order("SO-1", SalesOrderStatus::Draft, 0);
+    let lines = vec![line(&draft, 1, BOLT_ID, 10), line(&draft, 2, NUT_ID, 5)];
+    seed_orders(repo, &[(draft.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo
+        .get_sales_order_by_id(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(draft.clone()));
+    let locked = repo.lock_sales_order(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(locked, Some(draft.clone()));
+    let stored = repo
+        .find_sales_order_lines(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, lines);
+    let cases = [(None, true), (Some(draft.id), false)];
+    for (id, expected) in cases {
+        let taken = repo
+            .check_existing_sales_order_number(&mut uow, &ACME_ID, "SO-1", id.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{id:?}");
+    }
+
+    // confirming keeps the creator and stores the reserved quantities
+    let confirmed = SalesOrder {
+        status: SalesOrderStatus::Confirmed,
+        reserved_until: Some(at(60)),
+        confirmed_by: Some(USER_ID),
+        confirmed_at: Some(at(1)),
+        ..draft.clone()
+    };
+    repo.update_sales_order(&mut uow, confirmed.clone())
+        .await
+        .unwrap();
+    let reserved = SalesOrderLine {
+        reserved_quantity: 8,
+        picked_quantity: 3,
+        ..lines[0].clone()
+    };
+    repo.update_sales_order_line(&mut uow, &reserved)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo
+        .get_sales_order_by_id(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(confirmed.clone()));
+    let stored = repo
+        .find_sales_order_lines(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, vec![reserved.clone(), lines[1].clone()]);
+    assert_eq!(stored[0].backordered_quantity(), 2);
+    assert_eq!(stored[0].pickable_quantity(), 5);
+
+    // only open orders past their reserved_until are expired
+    let cases = [(at(59), vec![]), (at(60), vec![draft.id])];
+    for (now, expected) in cases {
+        let expired = repo.find_expired_sales_orders(&mut uow, now).await.unwrap();
+        assert_eq!(expired, expected, "{now}");
+    }
+    repo.update_sales_order(
+        &mut uow,
+        SalesOrder {
+            status: SalesOrderStatus::Cancelled,
+            ..confirmed
+        },
+    )
+    .await
+    .unwrap();
+    let expired = repo
+        .find_expired_sales_orders(&mut uow, at(60))
+        .await
+        .unwrap();
+    assert!(expired.is_empty());
+    uow.rollback().await.unwrap();
+
+    // replacing drops the old lines
+    let mut uow = repo.begin().await.unwrap();
+    let replaced = vec![line(&draft, 1, NUT_ID, 7)];
+    repo.replace_sales_order_lines(&mut uow, &draft.id, replaced.clone())
+        .await
+        .unwrap();
+    let stored = repo
+        .find_sales_order_lines(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, replaced);
+    uow.rollback().await.unwrap();
+
+    let cases = [
+        (
+            order("SO-1", SalesOrderStatus::Draft, 1),
+            "sales_orders_company_id_number_key",
+        ),
+        (
+            SalesOrder {
+                customer_id: Uuid::new_v4(),
+                ..order("SO-2", SalesOrderStatus::Draft, 1)
+            },
+            "sales_orders_customer_id_fkey",
+        ),
+        // a warehouse of another company fails the composite key
+        (
+            SalesOrder {
+                warehouse_id: GLOBEX_MAIN_ID,
+                ..order("SO-2", SalesOrderStatus::Draft, 1)
+            },
+            "sales_orders_warehouse_fkey",
+        ),
+    ];
+    for (order, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_sales_order(&mut uow, order).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .replace_sales_order_lines(
+            &mut uow,
+            &draft.id,
+            vec![line(&draft, 1, Uuid::new_v4(), 1)],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("sales_order_lines_product_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    // the lines go with the order
+    let mut uow = repo.begin().await.unwrap();
+    repo.delete_sales_order(&mut uow, &draft.id).await.unwrap();
+    uow.commit().await.unwrap();
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_sales_order_by_id(&mut uow, &draft.id)
+            .await
+            .unwrap(),
+        None
+    );
+    let stored = repo
+        .find_sales_order_lines(&mut uow, &draft.id)
+        .await
+        .unwrap();
+    assert_eq!(stored, vec![]);
+    uow.rollback().await.unwrap();
+}
+
+async fn filters_sorts_and_pages_sales_orders<R: SalesRepository>(repo: &R) {
+    let first = order("SO-1", SalesOrderStatus::Draft, 0);
+    let second = order("SO-2", SalesOrderStatus::Confirmed, 1);
+    let third = order("EXP-3", SalesOrderStatus::Confirmed, 2);
+    seed_orders(
+        repo,
+        &[
+            (first.clone(), vec![]),
+            (second.clone(), vec![]),
+            (third.clone(), vec![]),
+        ],
+    )
+    .await;
+
+    let filter = |customer_id: Option<Uuid>, status: Option<SalesOrderStatus>| SalesOrderFilter {
+        company_id: ACME_ID,
+        customer_id,
+        status,
+    };
+    // total counts every match, not the page
+    let cases = [
+        (
+            filter(None, None),
+            query(None, None, 10, 0),
+            vec![&first, &second, &third],
+            3,
+        ),
+        (
+            filter(None, Some(SalesOrderStatus::Confirmed)),
+            query(None, None, 10, 0),
+            vec![&second, &third],
+            2,
+        ),
+        (
+            filter(Some(ACME_ID), None),
+            query(None, None, 10, 0),
+            vec![],
+            0,
+        ),
+        (
+            filter(None, None),
+            query(Some("so-"), None, 10, 0),
+            vec![&first, &second],
+            2,
+        ),
+        (
+            filter(None, None),
+            query(None, Some("number"), 10, 0),
+            vec![&third, &first, &second],
+            3,
+        ),
+        (
+            filter(None, None),
+            query(None, Some("-created_at"), 2, 1),
+            vec![&second, &first],
+            3,
+        ),
+    ];
+    let mut uow = repo.begin().await.unwrap();
+    for (filter, query, expected, expected_total) in cases {
+        let expected: Vec<SalesOrder> = expected.into_iter().cloned().collect();
+        let found = repo
+            .find_sales_orders(&mut uow, &filter, &query)
+            .await
+            .unwrap();
+        assert_eq!(found, expected, "{filter:?} {query:?}");
+        let total = repo
+            .count_sales_orders(&mut uow, &filter, &query)
+            .await
+            .unwrap();
+        assert_eq!(total, expected_total, "{filter:?} {query:?}");
+    }
+    uow.rollback().await.unwrap();
+}
+
+// lots expired before the day are on hand but not usable
+async fn tracks_reservations_and_availability<R: SalesRepository>(repo: &R) {
+    seed_balance(repo, BOLT_ID, NORTH_BIN_ID, 30).await;
+    seed_balance(repo, BOLT_ID, NORTH_SHELF_ID, 20).await;
+    seed_balance(repo, MILK_ID, NORTH_BIN_ID, 7).await;
+    let day = |day: u32| NaiveDate::from_ymd_opt(2026, 1, day).unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    for (number, expires_on, quantity) in [("L-1", day(5), 4), ("L-2", day(20), 3)] {
+        let lot = StockLot {
+            id: Uuid::new_v4(),
+            company_id: ACME_ID,
+            product_id: MILK_ID,
+            lot_number: number.into(),
+            manufactured_on: None,
+            expires_on: Some(expires_on),
+            created_at: at(0),
+        };
+        repo.create_lot(&mut uow, lot.clone()).await.unwrap();
+        repo.lock_lot_balance(&mut uow, &ACME_ID, &lot.id, &MILK_ID, &NORTH_BIN_ID)
+            .await
+            .unwrap();
+        repo.update_lot_balance(&mut uow, &lot.id, &NORTH_BIN_ID, quantity, at(1))
+            .await
+            .unwrap();
+    }
+    let cases = [
+        (BOLT_ID, day(10), 50),
+        (NUT_ID, day(10), 0),
+        // a lot is usable on its expiry date
+        (MILK_ID, day(5), 7),
+        (MILK_ID, day(10), 3),
+    ];
+    for (product_id, today, expected) in cases {
+        let usable = repo
+            .get_warehouse_usable(&mut uow, &product_id, &NORTH_ID, today)
+            .await
+            .unwrap();
+        assert_eq!(usable, expected, "{product_id} {today}");
+    }
+
+    // the first lock creates the row at 0
+    for product_id in [BOLT_ID, NUT_ID] {
+        let reserved = repo
+            .lock_reservation(&mut uow, &ACME_ID, &product_id, &NORTH_ID)
+            .await
+            .unwrap();
+        assert_eq!(reserved, 0);
+    }
+    repo.update_reservation(&mut uow, &BOLT_ID, &NORTH_ID, 35, at(2))
+        .await
+        .unwrap();
+    repo.update_reservation(&mut uow, &NUT_ID, &NORTH_ID, 4, at(2))
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let reserved = repo
+        .lock_reservation(&mut uow, &ACME_ID, &BOLT_ID, &NORTH_ID)
+        .await
+        .unwrap();
+    assert_eq!(reserved, 35);
+
+    let filter = |product_id: Option<Uuid>| StockAvailabilityFilter {
+        company_id: ACME_ID,
+        warehouse_id: NORTH_ID,
+        product_id,
+    };
+    let bolts = StockAvailability {
+        product_id: BOLT_ID,
+        warehouse_id: NORTH_ID,
+        on_hand: 50,
+        reserved: 35,
+        available: 15,
+    };
+    // reserved without stock shows as negative
+    let nuts = StockAvailability {
+        product_id: NUT_ID,
+        warehouse_id: NORTH_ID,
+        on_hand: 0,
+        reserved: 4,
+        available: -4,
+    };
+    let milk = StockAvailability {
+        product_id: MILK_ID,
+        warehouse_id: NORTH_ID,
+        on_hand: 7,
+        reserved: 0,
+        available: 3,
+    };
+    let cases = [
+        (
+            filter(None),
+            day(10),
+            vec![bolts.clone(), nuts.clone(), milk.clone()],
+        ),
+        (filter(Some(NUT_ID)), day(10), vec![nuts]),
+        (
+            filter(Some(MILK_ID)),
+            day(5),
+            vec![StockAvailability {
+                available: 7,
+                ..milk
+            }],
+        ),
+        (
+            StockAvailabilityFilter {
+                company_id: GLOBEX_ID,
+                ..filter(None)
+            },
+            day(10),
+            vec![],
+        ),
+    ];
+    for (filter, today, expected) in cases {
+        let found = repo
+            .find_availability(&mut uow, &filter, today)
+            .await
+            .unwrap();
+        assert_eq!(found, expected, "{filter:?} {today}");
+    }
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .lock_reservation(&mut uow, &ACME_ID, &BOLT_ID, &GLOBEX_MAIN_ID)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_reservations_warehouse_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn stores_shipments<R: SalesRepository>(repo: &R) {
+    let confirmed = order("SO-1", SalesOrderStatus::Confirmed, 0);
+    let lines = vec![
+        line(&confirmed, 1, BOLT_ID, 10),
+        line(&confirmed, 2, NUT_ID, 5),
+    ];
+    seed_orders(repo, &[(confirmed.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let first = shipment(&confirmed, "SH-1", 1);
+    let first_lines = vec![
+        shipment_line(&first, 1, &lines[0]),
+        shipment_line(&first, 2, &lines[1]),
+    ];
+    let created = repo
+        .create_shipment(&mut uow, first.clone(), first_lines.clone())
+        .await
+        .unwrap();
+    assert_eq!(created, (first.clone(), first_lines.clone()));
+    let second = shipment(&confirmed, "SH-2", 2);
+    repo.create_shipment(&mut uow, second.clone(), vec![])
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    // shipping stores the status and points the lines at their movements
+    let mut uow = repo.begin().await.unwrap();
+    let movement = StockMovement {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        product_id: BOLT_ID,
+        location_id: NORTH_BIN_ID,
+        movement_type: MovementType::Issue,
+        quantity: -1,
+        balance_after: -1,
+        reversal_of: None,
+        source: MovementSource::Shipment,
+        source_id: Some(first.id),
+        lot_id: None,
+        reference: Some("SH-1".into()),
+        note: None,
+        posted_by: USER_ID,
+        posted_at: at(3),
+    };
+    repo.create_movement(&mut uow, movement.clone())
+        .await
+        .unwrap();
+    repo.update_shipment_line_movement(&mut uow, &first_lines[0].id, &movement.id)
+        .await
+        .unwrap();
+    let shipped = Shipment {
+        status: ShipmentStatus::Shipped,
+        packed_at: Some(at(2)),
+        shipped_by: Some(USER_ID),
+        shipped_at: Some(at(3)),
+        ..first.clone()
+    };
+    repo.update_shipment(&mut uow, shipped.clone())
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_shipment_by_id(&mut uow, &first.id).await.unwrap();
+    assert_eq!(stored, Some(shipped.clone()));
+    let stored = repo.find_shipment_lines(&mut uow, &first.id).await.unwrap();
+    let expected = vec![
+        ShipmentLine {
+            movement_id: Some(movement.id),
+            ..first_lines[0].clone()
+        },
+        first_lines[1].clone(),
+    ];
+    assert_eq!(stored, expected);
+    let shipments = repo
+        .find_sales_order_shipments(&mut uow, &confirmed.id)
+        .await
+        .unwrap();
+    assert_eq!(shipments, vec![shipped, second]);
+    let cases = [(ACME_ID, true), (GLOBEX_ID, false)];
+    for (company_id, expected) in cases {
+        let taken = repo
+            .check_existing_shipment_number(&mut uow, &company_id, "SH-1")
+            .await
+            .unwrap();
+        assert_eq!(taken, expected, "{company_id}");
+    }
+    uow.rollback().await.unwrap();
+
+    // an order with shipments can not go away
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .delete_sales_order(&mut uow, &confirmed.id)
+        .await
+        .unwrap_err();
+    assert_eq!(violated(err).as_deref(), Some("shipments_sales_order_fkey"));
+    uow.rollback().await.unwrap();
+
+    let cases = [
+        (
+            shipment(&confirmed, "SH-1", 4),
+            vec![],
+            "shipments_company_id_number_key",
+        ),
+        (
+            shipment(&confirmed, "SH-3", 4),
+            vec![ShipmentLine {
+                location_id: Uuid::new_v4(),
+                ..first_lines[0].clone()
+            }],
+            "shipment_lines_location_id_fkey",
+        ),
+        (
+            shipment(&confirmed, "SH-3", 4),
+            vec![ShipmentLine {
+                movement_id: Some(Uuid::new_v4()),
+                ..first_lines[0].clone()
+            }],
+            "shipment_lines_movement_id_fkey",
+        ),
+    ];
+    for (shipment, lines, constraint) in cases {
+        let lines = lines
+            .into_iter()
+            .map(|line| ShipmentLine {
+                id: Uuid::new_v4(),
+                shipment_id: shipment.id,
+                ..line
+            })
+            .collect();
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo
+            .create_shipment(&mut uow, shipment, lines)
+            .await
+            .unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+}
+
+// untracked products are picked from their balances, lot tracked ones from
+// their lot balances, soonest expiry first. open shipments hold their picks
+async fn finds_pickable_stock<R: SalesRepository>(repo: &R) {
+    seed_balance(repo, BOLT_ID, NORTH_BIN_ID, 5).await;
+    seed_balance(repo, BOLT_ID, NORTH_SHELF_ID, 3).await;
+    seed_balance(repo, NUT_ID, NORTH_BIN_ID, 9).await;
+    seed_balance(repo, MILK_ID, NORTH_BIN_ID, 5).await;
+    seed_balance(repo, MILK_ID, NORTH_SHELF_ID, 2).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let mut lots = Vec::new();
+    for (number, expires_on, location_id, quantity) in [
+        ("L-1", Some(9), NORTH_BIN_ID, 4),
+        ("L-2", Some(3), NORTH_SHELF_ID, 2),
+        ("L-3", None, NORTH_BIN_ID, 1),
+    ] {
+        let lot = StockLot {
+            id: Uuid::new_v4(),
+            company_id: ACME_ID,
+            product_id: MILK_ID,
+            lot_number: number.into(),
+            manufactured_on: None,
+            expires_on: expires_on.map(|day| NaiveDate::from_ymd_opt(2026, 1, day).unwrap()),
+            created_at: at(0),
+        };
+        repo.create_lot(&mut uow, lot.clone()).await.unwrap();
+        repo.lock_lot_balance(&mut uow, &ACME_ID, &lot.id, &MILK_ID, &location_id)
+            .await
+            .unwrap();
+        repo.update_lot_balance(&mut uow, &lot.id, &location_id, quantity, at(1))
+            .await
+            .unwrap();
+        lots.push(lot);
+    }
+    uow.commit().await.unwrap();
+
+    let confirmed = order("SO-1", SalesOrderStatus::Confirmed, 0);
+    let lines = vec![
+        line(&confirmed, 1, BOLT_ID, 4),
+        line(&confirmed, 2, MILK_ID, 3),
+    ];
+    seed_orders(repo, &[(confirmed.clone(), lines.clone())]).await;
+    let mut uow = repo.begin().await.unwrap();
+    let open = shipment(&confirmed, "SH-1", 1);
+    let picked = vec![
+        shipment_line(&open, 1, &lines[0]),
+        ShipmentLine {
+            location_id: NORTH_SHELF_ID,
+            lot_id: Some(lots[1].id),
+            ..shipment_line(&open, 2, &lines[1])
+        },
+    ];
+    repo.create_shipment(&mut uow, open, picked).await.unwrap();
+    // a cancelled shipment gave its picks back
+    let cancelled = Shipment {
+        status: ShipmentStatus::Cancelled,
+        ..shipment(&confirmed, "SH-2", 2)
+    };
+    let returned = vec![shipment_line(&cancelled, 1, &lines[0])];
+    repo.create_shipment(&mut uow, cancelled, returned)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stock = repo
+        .find_pickable_stock(&mut uow, &NORTH_ID, &[BOLT_ID, MILK_ID])
+        .await
+        .unwrap();
+    let expected = vec![
+        PickableStock {
+            product_id: BOLT_ID,
+            location_id: NORTH_BIN_ID,
+            lot_id: None,
+            expires_on: None,
+            quantity: 4,
+        },
+        PickableStock {
+            product_id: BOLT_ID,
+            location_id: NORTH_SHELF_ID,
+            lot_id: None,
+            expires_on: None,
+            quantity: 3,
+        },
+        PickableStock {
+            product_id: MILK_ID,
+            location_id: NORTH_SHELF_ID,
+            lot_id: Some(lots[1].id),
+            expires_on: lots[1].expires_on,
+            quantity: 1,
+        },
+        PickableStock {
+            product_id: MILK_ID,
+            location_id: NORTH_BIN_ID,
+            lot_id: Some(lots[0].id),
+            expires_on: lots[0].expires_on,
+            quantity: 4,
+        },
+        PickableStock {
+            product_id: MILK_ID,
+            location_id: NORTH_BIN_ID,
+            lot_id: Some(lots[2].id),
+            expires_on: None,
+            quantity: 1,
+        },
+    ];
+    assert_eq!(stock, expected);
+
+    let elsewhere = repo
+        .find_pickable_stock(&mut uow, &GLOBEX_MAIN_ID, &[BOLT_ID, MILK_ID])
+        .await
+        .unwrap();
+    assert!(elsewhere.is_empty());
+    uow.rollback().await.unwrap();
+}
@@ -0,0 +1,605 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::app_database::memory::{foreign_key_violation, ilike, unique_violation};
+use crate::app_request::pagination::PaginationRequest;
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::pick::PickableStock;
+use crate::sales::domain::sales_order::{SalesOrder, SalesOrderFilter, SalesOrderLine};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine};
+use crate::sales::repository::helper_query::SALES_ORDER_SORT_COLUMNS;
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::stock::repository::stock_repository_memory::{
+    StockRepositoryMemory, compare_by, compare_nullable, expired_quantity, page, sort_keys,
+};
+
+// mirrors the SalesRepository part of StockRepositorySqlx. orders, reservations
+// and shipments share the stock store, so the foreign keys between them are
+// checked against the staged rows of the unit of work
+impl StockRepositoryMemory {
+    async fn check_sales_order_references(&self, order: &SalesOrder) -> Result<(), sqlx::Error> {
+        if !self.company_exists(&order.company_id).await {
+            return Err(foreign_key_violation("sales_orders_company_id_fkey"));
+        }
+        if !self.company_exists(&order.customer_id).await {
+            return Err(foreign_key_violation("sales_orders_customer_id_fkey"));
+        }
+        if self.warehouse_company(&order.warehouse_id).await != Some(order.company_id) {
+            return Err(foreign_key_violation("sales_orders_warehouse_fkey"));
+        }
+        Ok(())
+    }
+}
+
+fn check_sales_order_number(orders: &[SalesOrder], order: &SalesOrder) -> Result<(), sqlx::Error> {
+    if orders
+        .iter()
+        .any(|o| o.id != order.id && o.company_id == order.company_id && o.number == order.number)
+    {
+        return Err(unique_violation("sales_orders_company_id_number_key"));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl SalesRepository for StockRepositoryMemory {
+    async fn check_existing_sales_order_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().sales_orders.iter().any(|o| {
+            o.company_id == *company_id && o.number == number && id.is_none_or(|id| o.id != *id)
+        }))
+    }
+
+    async fn create_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: SalesOrder,
+    ) -> Result<SalesOrder, sqlx::Error> {
+        let orders = &uow.state().sales_orders;
+        if orders.iter().any(|o| o.id == order.id) {
+            return Err(unique_violation("sales_orders_pkey"));
+        }
+        check_sales_order_number(orders, &order)?;
+        self.check_sales_order_references(&order).await?;
+        uow.state_mut().sales_orders.push(order.clone());
+
+        Ok(order)
+    }
+
+    async fn update_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: SalesOrder,
+    ) -> Result<SalesOrder, sqlx::Error> {
+        check_sales_order_number(&uow.state().sales_orders, &order)?;
+        self.check_sales_order_references(&order).await?;
+        let stored = uow
+            .state_mut()
+            .sales_orders
+            .iter_mut()
+            .find(|o| o.id == order.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // the company and the creator never change
+        *stored = SalesOrder {
+            company_id: stored.company_id,
+            created_by: stored.created_by,
+            created_at: stored.created_at,
+            ..order.clone()
+        };
+
+        Ok(order)
+    }
+
+    async fn get_sales_order_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<SalesOrder>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .sales_orders
+            .iter()
+            .find(|o| o.id == *id)
+            .cloned())
+    }
+
+    async fn lock_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<SalesOrder>, sqlx::Error> {
+        self.get_sales_order_by_id(uow, id).await
+    }
+
+    async fn delete_sales_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        if uow
+            .state()
+            .shipments
+            .iter()
+            .any(|s| s.sales_order_id == *id)
+        {
+            return Err(foreign_key_violation("shipments_sales_order_fkey"));
+        }
+        let tables = uow.state_mut();
+        tables.sales_orders.retain(|o| o.id != *id);
+        tables.sales_order_lines.retain(|l| l.sales_order_id != *id);
+
+        Ok(())
+    }
+
+    async fn count_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_sales_orders(&uow.state().sales_orders, filter, &query.search).len() as i64)
+    }
+
+    async fn find_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<SalesOrder>, sqlx::Error> {
+        let keys = sort_keys(query, &SALES_ORDER_SORT_COLUMNS)?;
+        let mut orders: Vec<SalesOrder> =
+            filter_sales_orders(&uow.state().sales_orders, filter, &query.search)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            orders.sort_by_key(|o| (o.created_at, o.id));
+        } else {
+            orders.sort_by(|a, b| compare_by(&keys, |column| compare_sales_order(a, b, column)));
+        }
+
+        Ok(page(orders, query))
+    }
+
+    async fn find_expired_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut expired: Vec<(DateTime<Utc>, Uuid)> = uow
+            .state()
+            .sales_orders
+            .iter()
+            .filter(|o| o.status.is_open())
+            .filter_map(|o| o.reserved_until.filter(|u| *u <= now).map(|u| (u, o.id)))
+            .collect();
+        expired.sort();
+
+        Ok(expired.into_iter().map(|(_, id)| id).collect())
+    }
+
+    async fn find_sales_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<SalesOrderLine>, sqlx::Error> {
+        let mut lines: Vec<SalesOrderLine> = uow
+            .state()
+            .sales_order_lines
+            .iter()
+            .filter(|l| l.sales_order_id == *sales_order_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn replace_sales_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+        lines: Vec<SalesOrderLine>,
+    ) -> Result<Vec<SalesOrderLine>, sqlx::Error> {
+        let mut kept: Vec<SalesOrderLine> = uow
+            .state()
+            .sales_order_lines
+            .iter()
+            .filter(|l| l.sales_order_id != *sales_order_id)
+            .cloned()
+            .collect();
+        for line in &lines {
+            if kept.iter().any(|l| l.id == line.id) {
+                return Err(unique_violation("sales_order_lines_pkey"));
+            }
+            if kept
+                .iter()
+                .any(|l| l.sales_order_id == line.sales_order_id && l.position == line.position)
+            {
+                return Err(unique_violation(
+                    "sales_order_lines_sales_order_id_position_key",
+                ));
+            }
+            if !uow
+                .state()
+                .sales_orders
+                .iter()
+                .any(|o| o.id == line.sales_order_id)
+            {
+                return Err(foreign_key_violation(
+                    "sales_order_lines_sales_order_id_fkey",
+                ));
+            }
+            if self.product_company(&line.product_id).await.is_none() {
+                return Err(foreign_key_violation("sales_order_lines_product_id_fkey"));
+            }
+            kept.push(line.clone());
+        }
+        uow.state_mut().sales_order_lines = kept;
+
+        Ok(lines)
+    }
+
+    async fn update_sales_order_line(
+        &self,
+        uow: &mut Self::Uow,
+        line: &SalesOrderLine,
+    ) -> Result<(), sqlx::Error> {
+        let stored = uow
+            .state_mut()
+            .sales_order_lines
+            .iter_mut()
+            .find(|l| l.id == line.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        stored.reserved_quantity = line.reserved_quantity;
+        stored.picked_quantity = line.picked_quantity;
+        stored.shipped_quantity = line.shipped_quantity;
+
+        Ok(())
+    }
+
+    async fn update_reservation(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let reservation = uow
+            .state_mut()
+            .reservations
+            .iter_mut()
+            .find(|r| r.product_id == *product_id && r.warehouse_id == *warehouse_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        reservation.quantity = quantity;
+        reservation.updated_at = updated_at;
+
+        Ok(())
+    }
+
+    async fn find_availability(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockAvailabilityFilter,
+        today: NaiveDate,
+    ) -> Result<Vec<StockAvailability>, sqlx::Error> {
+        let locations = self.warehouse_locations(&filter.warehouse_id).await;
+        let tables = uow.state();
+        let is_selected = |company_id: &Uuid, product_id: &Uuid| {
+            *company_id == filter.company_id && filter.product_id.is_none_or(|id| id == *product_id)
+        };
+        // product -> (on hand, reserved)
+        let mut by_product: BTreeMap<Uuid, (i64, i64)> = BTreeMap::new();
+        for balance in tables.balances.iter().filter(|b| {
+            is_selected(&b.company_id, &b.product_id) && locations.contains(&b.location_id)
+        }) {
+            by_product.entry(balance.product_id).or_default().0 += balance.quantity;
+        }
+        for reservation in tables.reservations.iter().filter(|r| {
+            is_selected(&r.company_id, &r.product_id) && r.warehouse_id == filter.warehouse_id
+        }) {
+            by_product.entry(reservation.product_id).or_default().1 += reservation.quantity;
+        }
+
+        Ok(by_product
+            .into_iter()
+            .map(|(product_id, (on_hand, reserved))| StockAvailability {
+                product_id,
+                warehouse_id: filter.warehouse_id,
+                on_hand,
+                reserved,
+                available: on_hand
+                    - expired_quantity(tables, &product_id, &locations, today)
+                    - reserved,
+            })
+            .collect())
+    }
+
+    async fn check_existing_shipment_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .shipments
+            .iter()
+            .any(|s| s.company_id == *company_id && s.number == number))
+    }
+
+    async fn create_shipment(
+        &self,
+        uow: &mut Self::Uow,
+        shipment: Shipment,
+        lines: Vec<ShipmentLine>,
+    ) -> Result<(Shipment, Vec<ShipmentLine>), sqlx::Error> {
+        let tables = uow.state();
+        if tables.shipments.iter().any(|s| s.id == shipment.id) {
+            return Err(unique_violation("shipments_pkey"));
+        }
+        if tables
+            .shipments
+            .iter()
+            .any(|s| s.company_id == shipment.company_id && s.number == shipment.number)
+        {
+            return Err(unique_violation("shipments_company_id_number_key"));
+        }
+        if !tables
+            .sales_orders
+            .iter()
+            .any(|o| o.id == shipment.sales_order_id && o.company_id == shipment.company_id)
+        {
+            return Err(foreign_key_violation("shipments_sales_order_fkey"));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let earlier = &lines[..i];
+            if tables.shipment_lines.iter().any(|l| l.id == line.id)
+                || earlier.iter().any(|l| l.id == line.id)
+            {
+                return Err(unique_violation("shipment_lines_pkey"));
+            }
+            if earlier.iter().any(|l| l.position == line.position) {
+                return Err(unique_violation("shipment_lines_shipment_id_position_key"));
+            }
+            if !tables
+                .sales_order_lines
+                .iter()
+                .any(|l| l.id == line.sales_order_line_id)
+            {
+                return Err(foreign_key_violation(
+                    "shipment_lines_sales_order_line_id_fkey",
+                ));
+            }
+            if self.location_warehouse(&line.location_id).await.is_none() {
+                return Err(foreign_key_violation("shipment_lines_location_id_fkey"));
+            }
+            if let Some(lot_id) = line.lot_id
+                && !tables.lots.iter().any(|l| l.id == lot_id)
+            {
+                return Err(foreign_key_violation("shipment_lines_lot_id_fkey"));
+            }
+            if let Some(movement_id) = line.movement_id
+                && !tables.movements.iter().any(|m| m.id == movement_id)
+            {
+                return Err(foreign_key_violation("shipment_lines_movement_id_fkey"));
+            }
+        }
+        let tables = uow.state_mut();
+        tables.shipments.push(shipment.clone());
+        tables.shipment_lines.extend(lines.iter().cloned());
+
+        Ok((shipment, lines))
+    }
+
+    async fn update_shipment(
+        &self,
+        uow: &mut Self::Uow,
+        shipment: Shipment,
+    ) -> Result<Shipment, sqlx::Error> {
+        let stored = uow
+            .state_mut()
+            .shipments
+            .iter_mut()
+            .find(|s| s.id == shipment.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // only the status and its timestamps change
+        stored.status = shipment.status;
+        stored.packed_at = shipment.packed_at;
+        stored.shipped_by = shipment.shipped_by;
+        stored.shipped_at = shipment.shipped_at;
+        stored.cancelled_at = shipment.cancelled_at;
+
+        Ok(shipment)
+    }
+
+    async fn update_shipment_line_movement(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        movement_id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        if !uow.state().movements.iter().any(|m| m.id == *movement_id) {
+            return Err(foreign_key_violation("shipment_lines_movement_id_fkey"));
+        }
+        let line = uow
+            .state_mut()
+            .shipment_lines
+            .iter_mut()
+            .find(|l| l.id == *line_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        line.movement_id = Some(*movement_id);
+
+        Ok(())
+    }
+
+    async fn get_shipment_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Shipment>, sqlx::Error> {
+        Ok(uow.state().shipments.iter().find(|s| s.id == *id).cloned())
+    }
+
+    async fn find_shipment_lines(
+        &self,
+        uow: &mut Self::Uow,
+        shipment_id: &Uuid,
+    ) -> Result<Vec<ShipmentLine>, sqlx::Error> {
+        let mut lines: Vec<ShipmentLine> = uow
+            .state()
+            .shipment_lines
+            .iter()
+            .filter(|l| l.shipment_id == *shipment_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn find_sales_order_shipments(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<Shipment>, sqlx::Error> {
+        let mut shipments: Vec<Shipment> = uow
+            .state()
+            .shipments
+            .iter()
+            .filter(|s| s.sales_order_id == *sales_order_id)
+            .cloned()
+            .collect();
+        shipments.sort_by_key(|s| (s.created_at, s.id));
+
+        Ok(shipments)
+    }
+
+    async fn find_pickable_stock(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        product_ids: &[Uuid],
+    ) -> Result<Vec<PickableStock>, sqlx::Error> {
+        let locations = self.warehouse_locations(warehouse_id).await;
+        let mut lot_tracked = Vec::new();
+        for product_id in product_ids {
+            if self.product_lot_tracked(product_id).await {
+                lot_tracked.push(*product_id);
+            }
+        }
+        let tables = uow.state();
+
+        // (product, location, lot) -> quantity
+        let mut stock: BTreeMap<(Uuid, Uuid, Option<Uuid>), i64> = BTreeMap::new();
+        for balance in tables.balances.iter().filter(|b| {
+            product_ids.contains(&b.product_id)
+                && !lot_tracked.contains(&b.product_id)
+                && locations.contains(&b.location_id)
+        }) {
+            stock.insert(
+                (balance.product_id, balance.location_id, None),
+                balance.quantity,
+            );
+        }
+        for balance in tables
+            .lot_balances
+            .iter()
+            .filter(|b| lot_tracked.contains(&b.product_id) && locations.contains(&b.location_id))
+        {
+            stock.insert(
+                (
+                    balance.product_id,
+                    balance.location_id,
+                    Some(balance.lot_id),
+                ),
+                balance.quantity,
+            );
+        }
+        for line in &tables.shipment_lines {
+            let is_open = tables
+                .shipments
+                .iter()
+                .any(|s| s.id == line.shipment_id && s.status.is_open());
+            let product_id = tables
+                .sales_order_lines
+                .iter()
+                .find(|l| l.id == line.sales_order_line_id)
+                .map(|l| l.product_id);
+            if let (true, Some(product_id)) = (is_open, product_id)
+                && let Some(quantity) = stock.get_mut(&(product_id, line.location_id, line.lot_id))
+            {
+                *quantity -= line.quantity;
+            }
+        }
+
+        let mut pickable: Vec<PickableStock> = stock
+            .into_iter()
+            .filter(|(_, quantity)| *quantity > 0)
+            .map(
+                |((product_id, location_id, lot_id), quantity)| PickableStock {
+                    product_id,
+                    location_id,
+                    lot_id,
+                    expires_on: lot_id.and_then(|lot_id| {
+                        tables
+                            .lots
+                            .iter()
+                            .find(|l| l.id == lot_id)
+                            .and_then(|l| l.expires_on)
+                    }),
+                    quantity,
+                },
+            )
+            .collect();
+        // NULLS LAST, see the ORDER BY of the query
+        pickable.sort_by_key(|p| {
+            (
+                p.product_id,
+                p.expires_on.is_none(),
+                p.expires_on,
+                p.location_id,
+                p.lot_id,
+            )
+        });
+
+        Ok(pickable)
+    }
+}
+
+// see helper_query::apply_sales_order_filter
+fn filter_sales_orders<'a>(
+    orders: &'a [SalesOrder],
+    filter: &SalesOrderFilter,
+    search: &Option<String>,
+) -> Vec<&'a SalesOrder> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    orders
+        .iter()
+        .filter(|o| o.company_id == filter.company_id)
+        .filter(|o| filter.customer_id.is_none_or(|id| o.customer_id == id))
+        .filter(|o| filter.status.is_none_or(|status| o.status == status))
+        .filter(|o| pattern.as_ref().is_none_or(|p| ilike(&o.number, p)))
+        .collect()
+}
+
+fn compare_sales_order(a: &SalesOrder, b: &SalesOrder, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "customer_id" => a.customer_id.cmp(&b.customer_id),
+        "warehouse_id" => a.warehouse_id.cmp(&b.warehouse_id),
+        "number" => a.number.cmp(&b.number),
+        "status" => a.status.as_str().cmp(b.status.as_str()),
+        "reserved_until" => compare_nullable(&a.reserved_until, &b.reserved_until),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "confirmed_at" => compare_nullable(&a.confirmed_at, &b.confirmed_at),
+        "shipped_at" => compare_nullable(&a.shipped_at, &b.shipped_at),
+        _ => Ordering::Equal,
+    }
+}
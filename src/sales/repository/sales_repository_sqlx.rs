@@ -0,0 +1,671 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::QueryBuilder;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_request::pagination::PaginationRequest;
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::pick::PickableStock;
+use crate::sales::domain::sales_order::{
+    SalesOrder, SalesOrderFilter, SalesOrderLine, SalesOrderStatus,
+};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine, ShipmentStatus};
+use crate::sales::repository::helper_query::{SALES_ORDER_DEFAULT_ORDER, apply_sales_order_filter};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+
+#[async_trait]
+impl SalesRepository for StockRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_sales_order_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM sales_orders
+                WHERE company_id = $1 AND number = $2 AND ($3::uuid IS NULL OR id <> $3)
+            )
+            "#,
+            company_id,
+            number,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %order.id))]
+    async fn create_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: SalesOrder,
+    ) -> Result<SalesOrder, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO sales_orders
+            (id, company_id, customer_id, warehouse_id, number, status, note, reserved_until,
+             created_by, created_at, confirmed_by, confirmed_at, shipped_at, cancelled_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+            order.id,
+            order.company_id,
+            order.customer_id,
+            order.warehouse_id,
+            order.number,
+            order.status.as_str(),
+            order.note,
+            order.reserved_until,
+            order.created_by,
+            order.created_at,
+            order.confirmed_by,
+            order.confirmed_at,
+            order.shipped_at,
+            order.cancelled_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    // the company and the creator never change
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %order.id))]
+    async fn update_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        order: SalesOrder,
+    ) -> Result<SalesOrder, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE sales_orders
+            SET customer_id = $1,
+                warehouse_id = $2,
+                number = $3,
+                status = $4,
+                note = $5,
+                reserved_until = $6,
+                confirmed_by = $7,
+                confirmed_at = $8,
+                shipped_at = $9,
+                cancelled_at = $10
+            WHERE id = $11
+            RETURNING id
+            "#,
+            order.customer_id,
+            order.warehouse_id,
+            order.number,
+            order.status.as_str(),
+            order.note,
+            order.reserved_until,
+            order.confirmed_by,
+            order.confirmed_at,
+            order.shipped_at,
+            order.cancelled_at,
+            order.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %id))]
+    async fn get_sales_order_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<SalesOrder>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            SalesOrder,
+            r#"
+            SELECT id, company_id, customer_id, warehouse_id, number,
+                   status AS "status: SalesOrderStatus", note, reserved_until, created_by,
+                   created_at, confirmed_by, confirmed_at, shipped_at, cancelled_at
+            FROM sales_orders
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %id))]
+    async fn lock_sales_order(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<SalesOrder>, sqlx::Error> {
+        let order = sqlx::query_as!(
+            SalesOrder,
+            r#"
+            SELECT id, company_id, customer_id, warehouse_id, number,
+                   status AS "status: SalesOrderStatus", note, reserved_until, created_by,
+                   created_at, confirmed_by, confirmed_at, shipped_at, cancelled_at
+            FROM sales_orders
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(order)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %id))]
+    async fn delete_sales_order(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM sales_orders WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM sales_orders");
+
+        apply_sales_order_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<SalesOrder>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, customer_id, warehouse_id, number, status, note,
+                   reserved_until, created_by, created_at, confirmed_by, confirmed_at,
+                   shipped_at, cancelled_at
+            FROM sales_orders
+        ",
+        );
+
+        apply_sales_order_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(SALES_ORDER_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let orders = qb
+            .build_query_as::<SalesOrder>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(orders)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn find_expired_sales_orders(
+        &self,
+        uow: &mut Self::Uow,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM sales_orders
+            WHERE reserved_until <= $1 AND status IN ('confirmed', 'partially_shipped')
+            ORDER BY reserved_until, id
+            "#,
+            now
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(ids)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %sales_order_id))]
+    async fn find_sales_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<SalesOrderLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            SalesOrderLine,
+            r#"
+            SELECT id, sales_order_id, position, product_id, quantity, unit, unit_quantity,
+                   reserved_quantity, picked_quantity, shipped_quantity
+            FROM sales_order_lines
+            WHERE sales_order_id = $1
+            ORDER BY position
+            "#,
+            sales_order_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %sales_order_id, lines = lines.len()))]
+    async fn replace_sales_order_lines(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+        lines: Vec<SalesOrderLine>,
+    ) -> Result<Vec<SalesOrderLine>, sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM sales_order_lines WHERE sales_order_id = $1"#,
+            sales_order_id
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO sales_order_lines
+                (id, sales_order_id, position, product_id, quantity, unit, unit_quantity,
+                 reserved_quantity, picked_quantity, shipped_quantity)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                "#,
+                line.id,
+                line.sales_order_id,
+                line.position,
+                line.product_id,
+                line.quantity,
+                line.unit,
+                line.unit_quantity,
+                line.reserved_quantity,
+                line.picked_quantity,
+                line.shipped_quantity,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %line.id))]
+    async fn update_sales_order_line(
+        &self,
+        uow: &mut Self::Uow,
+        line: &SalesOrderLine,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE sales_order_lines
+            SET reserved_quantity = $1,
+                picked_quantity = $2,
+                shipped_quantity = $3
+            WHERE id = $4
+            RETURNING id
+            "#,
+            line.reserved_quantity,
+            line.picked_quantity,
+            line.shipped_quantity,
+            line.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, warehouse_id = %warehouse_id))]
+    async fn update_reservation(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_reservations
+            SET quantity = $1, updated_at = $2
+            WHERE product_id = $3 AND warehouse_id = $4
+            RETURNING product_id
+            "#,
+            quantity,
+            updated_at,
+            product_id,
+            warehouse_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id, warehouse_id = %filter.warehouse_id))]
+    async fn find_availability(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockAvailabilityFilter,
+        today: NaiveDate,
+    ) -> Result<Vec<StockAvailability>, sqlx::Error> {
+        let availability = sqlx::query_as!(
+            StockAvailability,
+            r#"
+            WITH on_hand AS (
+                SELECT b.product_id, SUM(b.quantity)::bigint AS quantity
+                FROM stock_balances b
+                JOIN warehouse_locations l ON l.id = b.location_id
+                WHERE b.company_id = $1 AND l.warehouse_id = $2
+                  AND ($3::uuid IS NULL OR b.product_id = $3)
+                GROUP BY b.product_id
+            ), expired AS (
+                SELECT lb.product_id, SUM(lb.quantity)::bigint AS quantity
+                FROM stock_lot_balances lb
+                JOIN stock_lots lo ON lo.id = lb.lot_id
+                JOIN warehouse_locations l ON l.id = lb.location_id
+                WHERE lb.company_id = $1 AND l.warehouse_id = $2 AND lo.expires_on < $4
+                  AND ($3::uuid IS NULL OR lb.product_id = $3)
+                GROUP BY lb.product_id
+            ), reserved AS (
+                SELECT product_id, quantity
+                FROM stock_reservations
+                WHERE company_id = $1 AND warehouse_id = $2
+                  AND ($3::uuid IS NULL OR product_id = $3)
+            )
+            SELECT COALESCE(o.product_id, r.product_id) AS "product_id!",
+                   $2::uuid AS "warehouse_id!",
+                   COALESCE(o.quantity, 0) AS "on_hand!",
+                   COALESCE(r.quantity, 0) AS "reserved!",
+                   COALESCE(o.quantity, 0) - COALESCE(e.quantity, 0) - COALESCE(r.quantity, 0) AS "available!"
+            FROM on_hand o
+            LEFT JOIN expired e ON e.product_id = o.product_id
+            FULL JOIN reserved r ON r.product_id = o.product_id
+            ORDER BY 1
+            "#,
+            filter.company_id,
+            filter.warehouse_id,
+            filter.product_id,
+            today,
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(availability)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_shipment_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM shipments WHERE company_id = $1 AND number = $2
+            )
+            "#,
+            company_id,
+            number,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(shipment_id = %shipment.id, lines = lines.len()))]
+    async fn create_shipment(
+        &self,
+        uow: &mut Self::Uow,
+        shipment: Shipment,
+        lines: Vec<ShipmentLine>,
+    ) -> Result<(Shipment, Vec<ShipmentLine>), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO shipments
+            (id, company_id, sales_order_id, number, status, note, created_by, created_at,
+             packed_at, shipped_by, shipped_at, cancelled_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            shipment.id,
+            shipment.company_id,
+            shipment.sales_order_id,
+            shipment.number,
+            shipment.status.as_str(),
+            shipment.note,
+            shipment.created_by,
+            shipment.created_at,
+            shipment.packed_at,
+            shipment.shipped_by,
+            shipment.shipped_at,
+            shipment.cancelled_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO shipment_lines
+                (id, shipment_id, position, sales_order_line_id, location_id, lot_id,
+                 quantity, serial_numbers, movement_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                line.id,
+                line.shipment_id,
+                line.position,
+                line.sales_order_line_id,
+                line.location_id,
+                line.lot_id,
+                line.quantity,
+                &line.serial_numbers,
+                line.movement_id,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok((shipment, lines))
+    }
+
+    // only the status and its timestamps change
+    #[instrument(level = "debug", skip_all, fields(shipment_id = %shipment.id))]
+    async fn update_shipment(
+        &self,
+        uow: &mut Self::Uow,
+        shipment: Shipment,
+    ) -> Result<Shipment, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE shipments
+            SET status = $1,
+                packed_at = $2,
+                shipped_by = $3,
+                shipped_at = $4,
+                cancelled_at = $5
+            WHERE id = $6
+            RETURNING id
+            "#,
+            shipment.status.as_str(),
+            shipment.packed_at,
+            shipment.shipped_by,
+            shipment.shipped_at,
+            shipment.cancelled_at,
+            shipment.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(shipment)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %line_id))]
+    async fn update_shipment_line_movement(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        movement_id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE shipment_lines
+            SET movement_id = $1
+            WHERE id = $2
+            RETURNING id
+            "#,
+            movement_id,
+            line_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(shipment_id = %id))]
+    async fn get_shipment_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Shipment>, sqlx::Error> {
+        let shipment = sqlx::query_as!(
+            Shipment,
+            r#"
+            SELECT id, company_id, sales_order_id, number, status AS "status: ShipmentStatus",
+                   note, created_by, created_at, packed_at, shipped_by, shipped_at, cancelled_at
+            FROM shipments
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(shipment)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(shipment_id = %shipment_id))]
+    async fn find_shipment_lines(
+        &self,
+        uow: &mut Self::Uow,
+        shipment_id: &Uuid,
+    ) -> Result<Vec<ShipmentLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            ShipmentLine,
+            r#"
+            SELECT id, shipment_id, position, sales_order_line_id, location_id, lot_id,
+                   quantity, serial_numbers, movement_id
+            FROM shipment_lines
+            WHERE shipment_id = $1
+            ORDER BY position
+            "#,
+            shipment_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(sales_order_id = %sales_order_id))]
+    async fn find_sales_order_shipments(
+        &self,
+        uow: &mut Self::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<Shipment>, sqlx::Error> {
+        let shipments = sqlx::query_as!(
+            Shipment,
+            r#"
+            SELECT id, company_id, sales_order_id, number, status AS "status: ShipmentStatus",
+                   note, created_by, created_at, packed_at, shipped_by, shipped_at, cancelled_at
+            FROM shipments
+            WHERE sales_order_id = $1
+            ORDER BY created_at, id
+            "#,
+            sales_order_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(shipments)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id, products = product_ids.len()))]
+    async fn find_pickable_stock(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        product_ids: &[Uuid],
+    ) -> Result<Vec<PickableStock>, sqlx::Error> {
+        // lot tracked products are picked from their lot balances, the
+        // others from their balances
+        let stock = sqlx::query_as!(
+            PickableStock,
+            r#"
+            WITH stock AS (
+                SELECT b.product_id, b.location_id, NULL::uuid AS lot_id, b.quantity
+                FROM stock_balances b
+                JOIN products p ON p.id = b.product_id
+                WHERE NOT p.is_lot_tracked
+                UNION ALL
+                SELECT product_id, location_id, lot_id, quantity
+                FROM stock_lot_balances
+            ), picked AS (
+                SELECT ol.product_id, sl.location_id, sl.lot_id, SUM(sl.quantity) AS quantity
+                FROM shipment_lines sl
+                JOIN shipments s ON s.id = sl.shipment_id
+                JOIN sales_order_lines ol ON ol.id = sl.sales_order_line_id
+                WHERE s.status IN ('picking', 'packed')
+                GROUP BY ol.product_id, sl.location_id, sl.lot_id
+            )
+            SELECT st.product_id AS "product_id!",
+                   st.location_id AS "location_id!",
+                   st.lot_id,
+                   l.expires_on AS "expires_on?",
+                   (st.quantity - COALESCE(p.quantity, 0))::bigint AS "quantity!"
+            FROM stock st
+            JOIN warehouse_locations wl ON wl.id = st.location_id
+            LEFT JOIN stock_lots l ON l.id = st.lot_id
+            LEFT JOIN picked p ON p.product_id = st.product_id
+                AND p.location_id = st.location_id
+                AND p.lot_id IS NOT DISTINCT FROM st.lot_id
+            WHERE wl.warehouse_id = $1
+              AND st.product_id = ANY($2)
+              AND st.quantity - COALESCE(p.quantity, 0) > 0
+            ORDER BY st.product_id, l.expires_on NULLS LAST, st.location_id, st.lot_id
+            "#,
+            warehouse_id,
+            product_ids,
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(stock)
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time::interval;
+use tracing::{info, warn};
+
+use crate::app_config::config::SalesConfig;
+use crate::app_lifecycle::lifecycle::Lifecycle;
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecase;
+
+// every tick the unpicked reservations of open orders past their
+// reserved_until are released, the lines keep the rest as backorder
+pub fn spawn_reservation_sweeper<R: SalesRepository + 'static>(
+    lifecycle: &Lifecycle,
+    config: &SalesConfig,
+    usecase: SalesOrderUsecase<R>,
+) {
+    let every = Duration::from_secs(config.reservation_sweep_interval_secs);
+
+    lifecycle.spawn("reservation_sweeper", move |shutdown| async move {
+        let mut ticker = interval(every);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => match usecase.release_expired_reservations(Utc::now()).await {
+                    Ok(0) => {}
+                    Ok(released) => info!(released, "expired sales order reservations released"),
+                    Err(err) => warn!(cause = %err, "releasing expired reservations failed"),
+                },
+            }
+        }
+    });
+}
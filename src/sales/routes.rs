@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::atuh_middleware::auth_middleware;
+use crate::sales::handler::sales_order_handler::{
+    cancel_sales_order_handler, confirm_sales_order_handler, delete_sales_order_handler,
+    get_sales_order_handler, get_sales_orders_handler, get_stock_availability_handler,
+    post_sales_order_handler, reserve_sales_order_handler, update_sales_order_handler,
+};
+use crate::sales::handler::shipment_handler::{
+    cancel_shipment_handler, get_pick_suggestions_handler, get_shipment_handler,
+    get_shipments_handler, pack_shipment_handler, post_shipment_handler, ship_shipment_handler,
+};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecase;
+use crate::sales::usecase::shipment_usecase::ShipmentUsecase;
+use axum::middleware;
+use axum::{Router, routing::delete, routing::get, routing::post, routing::put};
+
+pub fn sales_routes<R: SalesRepository + Clone + 'static>(repo: R, metrics: AppMetrics) -> Router {
+    let order_usecase = Arc::new(SalesOrderUsecase::new(repo.clone(), metrics.clone()));
+    let shipment_usecase = Arc::new(ShipmentUsecase::new(repo, metrics));
+
+    let shipments = Router::new()
+        .route("/sales/orders/:id/shipments", get(get_shipments_handler))
+        .route("/sales/orders/:id/shipments", post(post_shipment_handler))
+        .route("/sales/orders/:id/picks", get(get_pick_suggestions_handler))
+        .route("/sales/shipments/:id", get(get_shipment_handler))
+        .route("/sales/shipments/:id/pack", post(pack_shipment_handler))
+        .route("/sales/shipments/:id/ship", post(ship_shipment_handler))
+        .route("/sales/shipments/:id/cancel", post(cancel_shipment_handler))
+        .with_state(shipment_usecase);
+
+    Router::new()
+        .route("/sales/orders", get(get_sales_orders_handler))
+        .route("/sales/orders", post(post_sales_order_handler))
+        .route("/sales/orders/:id", get(get_sales_order_handler))
+        .route("/sales/orders/:id", put(update_sales_order_handler))
+        .route("/sales/orders/:id", delete(delete_sales_order_handler))
+        .route(
+            "/sales/orders/:id/confirm",
+            post(confirm_sales_order_handler),
+        )
+        .route(
+            "/sales/orders/:id/reserve",
+            post(reserve_sales_order_handler),
+        )
+        .route("/sales/orders/:id/cancel", post(cancel_sales_order_handler))
+        .route("/sales/availability", get(get_stock_availability_handler))
+        .with_state(order_usecase)
+        .merge(shipments)
+        // merged at the root, layer would put the fallback behind auth as well
+        .route_layer(middleware::from_fn(auth_middleware))
+}
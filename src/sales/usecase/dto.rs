@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::sales::domain::sales_order::{SalesOrder, SalesOrderLine};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine};
+
+// quantity is in unit, the product's base unit when None
+pub struct SalesOrderLineInput {
+    pub product_id: Uuid,
+    pub unit: Option<String>,
+    pub quantity: Decimal,
+}
+
+// the lines replace whatever the draft held, in the given order
+pub struct SalesOrderInput {
+    pub customer_id: Uuid,
+    pub warehouse_id: Uuid,
+    pub number: String,
+    pub note: Option<String>,
+    pub lines: Vec<SalesOrderLineInput>,
+}
+
+// given on confirmation and on every later attempt to reserve backorders,
+// none keeps the reservations until the order ships or is cancelled
+pub struct ReservationInput {
+    pub reserved_until: Option<DateTime<Utc>>,
+}
+
+pub struct SalesOrderDetail {
+    pub order: SalesOrder,
+    pub lines: Vec<SalesOrderLine>,
+}
+
+pub struct ListSalesOrderResult {
+    pub data: Vec<SalesOrder>,
+    pub total_data: i64,
+}
+
+// reserved quantity of one order line picked from a location of the order's
+// warehouse
+pub struct ShipmentLineInput {
+    pub line_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    pub serial_numbers: Vec<String>,
+}
+
+pub struct ShipmentInput {
+    pub number: String,
+    pub note: Option<String>,
+    pub lines: Vec<ShipmentLineInput>,
+}
+
+pub struct ShipmentDetail {
+    pub shipment: Shipment,
+    pub lines: Vec<ShipmentLine>,
+}
@@ -0,0 +1,7 @@
+pub mod sales_order_usecase;
+pub mod shipment_usecase;
+pub mod dto;
+#[cfg(test)]
+mod sales_order_usecase_test;
+#[cfg(test)]
+mod shipment_usecase_test;
@@ -0,0 +1,857 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product_unit::{UnitConversionError, UnitPurpose};
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::sales_order::{
+    SalesOrder, SalesOrderFilter, SalesOrderLine, SalesOrderStatus,
+};
+use crate::sales::domain::shipment::{Shipment, ShipmentStatus};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::dto::{
+    ListSalesOrderResult, ReservationInput, SalesOrderDetail, SalesOrderInput, SalesOrderLineInput,
+};
+use crate::stock::usecase::stock_usecase::find_conversion_in;
+
+// names of the constraints on the sales tables, see migrations
+const ORDER_NUMBER_CONSTRAINT: &str = "sales_orders_company_id_number_key";
+const ORDER_COMPANY_CONSTRAINT: &str = "sales_orders_company_id_fkey";
+const ORDER_CUSTOMER_CONSTRAINT: &str = "sales_orders_customer_id_fkey";
+const ORDER_CUSTOMER_CHECK: &str = "sales_orders_customer_id_check";
+const ORDER_WAREHOUSE_CONSTRAINT: &str = "sales_orders_warehouse_fkey";
+const LINE_PRODUCT_CONSTRAINT: &str = "sales_order_lines_product_id_fkey";
+
+pub struct SalesOrderUsecase<R: SalesRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum SalesOrderUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    NumberAlreadyExist,
+    CustomerNotFound,
+    // a company can not sell to itself
+    InvalidCustomer,
+    WarehouseNotFound,
+    // a product or warehouse of another company
+    CompanyMismatch,
+    ProductNotFound,
+    InvalidQuantity,
+    // the product has no such sales unit
+    UnitNotFound,
+    // the quantity does not come to a whole base quantity in its unit
+    Conversion(UnitConversionError),
+    NoLines,
+    // the order can not take this step from its current status
+    InvalidStatus(SalesOrderStatus),
+    // reserved_until is not in the future
+    InvalidReservedUntil,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for SalesOrderUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SalesOrderUsecaseError::CompanyNotFound => write!(f, "sales order company not found"),
+            SalesOrderUsecaseError::NotFound => write!(f, "sales order not found"),
+            SalesOrderUsecaseError::NumberAlreadyExist => {
+                write!(f, "sales order number already exists")
+            }
+            SalesOrderUsecaseError::CustomerNotFound => {
+                write!(f, "sales order customer not found")
+            }
+            SalesOrderUsecaseError::InvalidCustomer => {
+                write!(f, "a company can not sell to itself")
+            }
+            SalesOrderUsecaseError::WarehouseNotFound => {
+                write!(f, "sales order warehouse not found")
+            }
+            SalesOrderUsecaseError::CompanyMismatch => {
+                write!(f, "sales order refers to another company")
+            }
+            SalesOrderUsecaseError::ProductNotFound => write!(f, "sales order product not found"),
+            SalesOrderUsecaseError::InvalidQuantity => {
+                write!(f, "sales order quantity is invalid")
+            }
+            SalesOrderUsecaseError::UnitNotFound => write!(f, "sales order unit not found"),
+            SalesOrderUsecaseError::Conversion(err) => write!(f, "{err}"),
+            SalesOrderUsecaseError::NoLines => write!(f, "sales order has no lines"),
+            SalesOrderUsecaseError::InvalidStatus(status) => {
+                write!(f, "sales order is {}", status.as_str())
+            }
+            SalesOrderUsecaseError::InvalidReservedUntil => {
+                write!(f, "reserved_until must be in the future")
+            }
+            SalesOrderUsecaseError::DatabaseError(_) => write!(f, "sales order storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for SalesOrderUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SalesOrderUsecaseError::Conversion(err) => Some(err),
+            SalesOrderUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: SalesRepository> SalesOrderUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, number = %input.number))]
+    pub async fn create_order(
+        &self,
+        company_id: Uuid,
+        input: SalesOrderInput,
+        created_by: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .create_order_in(&mut uow, company_id, input, created_by)
+            .await;
+        let detail = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("sales_order_create");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn update_order(
+        &self,
+        id: Uuid,
+        input: SalesOrderInput,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_order_in(&mut uow, id, input).await;
+        let detail = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("sales_order_update");
+        Ok(detail)
+    }
+
+    // only drafts, anything confirmed has to be cancelled instead
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn delete_order(&self, id: Uuid) -> Result<(), SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_order_in(&mut uow, id).await;
+        finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("sales_order_delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn get_order(&self, id: Uuid) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_order_in(&mut uow, id).await;
+        finish(uow, result, SalesOrderUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_orders(
+        &self,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListSalesOrderResult, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_orders_in(&mut uow, filter, query).await;
+        finish(uow, result, SalesOrderUsecaseError::DatabaseError).await
+    }
+
+    // reserves what the warehouse has available, the rest of every line stays
+    // backordered
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn confirm_order(
+        &self,
+        id: Uuid,
+        input: ReservationInput,
+        confirmed_by: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .confirm_order_in(&mut uow, id, input, confirmed_by)
+            .await;
+        let detail = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("confirm");
+        Ok(detail)
+    }
+
+    // another attempt at the backorders of an open order, e.g. after a
+    // receipt or once its reservations expired
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn reserve_order(
+        &self,
+        id: Uuid,
+        input: ReservationInput,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.reserve_order_in(&mut uow, id, input).await;
+        let detail = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("reserve");
+        Ok(detail)
+    }
+
+    // releases everything the order still holds and cancels its open shipments,
+    // what already shipped stays shipped
+    #[instrument(skip_all, fields(sales_order_id = %id))]
+    pub async fn cancel_order(&self, id: Uuid) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.cancel_order_in(&mut uow, id).await;
+        let detail = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("cancel");
+        Ok(detail)
+    }
+
+    // releases the unpicked reservations of open orders whose reserved_until
+    // passed, one unit of work per order so a failing order does not hold up
+    // the others. returns how many orders were released
+    #[instrument(skip_all)]
+    pub async fn release_expired_reservations(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<usize, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .repo
+            .find_expired_sales_orders(&mut uow, now)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError);
+        let ids = finish(uow, result, SalesOrderUsecaseError::DatabaseError).await?;
+
+        let mut released = 0;
+        for id in ids {
+            let mut uow = self.begin().await?;
+            let result = self.expire_order_in(&mut uow, id, now).await;
+            match finish(uow, result, SalesOrderUsecaseError::DatabaseError).await {
+                Ok(true) => {
+                    released += 1;
+                    self.metrics.sales_operation("expire");
+                }
+                Ok(false) => {}
+                Err(err) => warn!(cause = %err, sales_order_id = %id, "reservation release failed"),
+            }
+        }
+
+        Ok(released)
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, warehouse_id = %filter.warehouse_id))]
+    pub async fn get_availability(
+        &self,
+        filter: &StockAvailabilityFilter,
+    ) -> Result<Vec<StockAvailability>, SalesOrderUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_availability_in(&mut uow, filter).await;
+        finish(uow, result, SalesOrderUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, SalesOrderUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)
+    }
+
+    async fn create_order_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: SalesOrderInput,
+        created_by: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+        self.check_order_number(uow, &company_id, &input.number, None)
+            .await?;
+        self.check_customer(uow, &company_id, &input.customer_id)
+            .await?;
+        self.check_warehouse(uow, &company_id, &input.warehouse_id)
+            .await?;
+
+        let order = SalesOrder {
+            id: Uuid::new_v4(),
+            company_id,
+            customer_id: input.customer_id,
+            warehouse_id: input.warehouse_id,
+            number: input.number,
+            status: SalesOrderStatus::Draft,
+            note: input.note,
+            reserved_until: None,
+            created_by,
+            created_at: Utc::now(),
+            confirmed_by: None,
+            confirmed_at: None,
+            shipped_at: None,
+            cancelled_at: None,
+        };
+        let lines = self.build_lines(uow, &order, input.lines).await?;
+
+        let order = self
+            .repo
+            .create_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+        let lines = self
+            .repo
+            .replace_sales_order_lines(uow, &order.id, lines)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    async fn update_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: SalesOrderInput,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != SalesOrderStatus::Draft {
+            return Err(SalesOrderUsecaseError::InvalidStatus(order.status));
+        }
+        self.check_order_number(uow, &order.company_id, &input.number, Some(&id))
+            .await?;
+        self.check_customer(uow, &order.company_id, &input.customer_id)
+            .await?;
+        self.check_warehouse(uow, &order.company_id, &input.warehouse_id)
+            .await?;
+
+        let order = SalesOrder {
+            customer_id: input.customer_id,
+            warehouse_id: input.warehouse_id,
+            number: input.number,
+            note: input.note,
+            ..order
+        };
+        let lines = self.build_lines(uow, &order, input.lines).await?;
+
+        let order = self
+            .repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+        let lines = self
+            .repo
+            .replace_sales_order_lines(uow, &order.id, lines)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    async fn delete_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != SalesOrderStatus::Draft {
+            return Err(SalesOrderUsecaseError::InvalidStatus(order.status));
+        }
+
+        self.repo
+            .delete_sales_order(uow, &id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)
+    }
+
+    async fn get_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let order = self
+            .repo
+            .get_sales_order_by_id(uow, &id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?
+            .ok_or(SalesOrderUsecaseError::NotFound)?;
+        let lines = self.find_lines(uow, &id).await?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    async fn list_orders_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &SalesOrderFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListSalesOrderResult, SalesOrderUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_order = self
+            .repo
+            .count_sales_orders(uow, filter, query)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+
+        let orders = self
+            .repo
+            .find_sales_orders(uow, filter, query)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+
+        Ok(ListSalesOrderResult {
+            data: orders,
+            total_data: total_order,
+        })
+    }
+
+    async fn confirm_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: ReservationInput,
+        confirmed_by: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != SalesOrderStatus::Draft {
+            return Err(SalesOrderUsecaseError::InvalidStatus(order.status));
+        }
+        check_reserved_until(&input)?;
+        let mut lines = self.find_lines(uow, &id).await?;
+        if lines.is_empty() {
+            return Err(SalesOrderUsecaseError::NoLines);
+        }
+
+        self.reserve_lines(uow, &order, &mut lines).await?;
+        let order = SalesOrder {
+            status: SalesOrderStatus::Confirmed,
+            reserved_until: input.reserved_until,
+            confirmed_by: Some(confirmed_by),
+            confirmed_at: Some(Utc::now()),
+            ..order
+        };
+        let order = self
+            .repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    async fn reserve_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: ReservationInput,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if !order.status.is_open() {
+            return Err(SalesOrderUsecaseError::InvalidStatus(order.status));
+        }
+        check_reserved_until(&input)?;
+        let mut lines = self.find_lines(uow, &id).await?;
+
+        self.reserve_lines(uow, &order, &mut lines).await?;
+        let order = SalesOrder {
+            reserved_until: input.reserved_until,
+            ..order
+        };
+        let order = self
+            .repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    async fn cancel_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<SalesOrderDetail, SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        if order.status != SalesOrderStatus::Draft && !order.status.is_open() {
+            return Err(SalesOrderUsecaseError::InvalidStatus(order.status));
+        }
+        let mut lines = self.find_lines(uow, &id).await?;
+
+        let now = Utc::now();
+        let shipments = self
+            .repo
+            .find_sales_order_shipments(uow, &id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        for shipment in shipments.into_iter().filter(|s| s.status.is_open()) {
+            let shipment = Shipment {
+                status: ShipmentStatus::Cancelled,
+                cancelled_at: Some(now),
+                ..shipment
+            };
+            self.repo
+                .update_shipment(uow, shipment)
+                .await
+                .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        }
+
+        let mut released: BTreeMap<Uuid, i64> = BTreeMap::new();
+        for line in lines.iter_mut().filter(|line| line.reserved_quantity > 0) {
+            *released.entry(line.product_id).or_default() += line.reserved_quantity;
+            line.reserved_quantity = 0;
+            line.picked_quantity = 0;
+            self.repo
+                .update_sales_order_line(uow, line)
+                .await
+                .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        }
+        release_in(&self.repo, uow, &order, released)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+
+        let order = SalesOrder {
+            status: SalesOrderStatus::Cancelled,
+            cancelled_at: Some(now),
+            ..order
+        };
+        let order = self
+            .repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(SalesOrderDetail { order, lines })
+    }
+
+    // picked stock is on its way out and stays reserved. false when the order
+    // changed since it was found
+    async fn expire_order_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<bool, SalesOrderUsecaseError> {
+        let order = self.lock_order(uow, &id).await?;
+        let is_expired = order.reserved_until.is_some_and(|until| until <= now);
+        if !order.status.is_open() || !is_expired {
+            return Ok(false);
+        }
+        let lines = self.find_lines(uow, &id).await?;
+
+        let mut released: BTreeMap<Uuid, i64> = BTreeMap::new();
+        for mut line in lines {
+            let unpicked = line.pickable_quantity();
+            if unpicked == 0 {
+                continue;
+            }
+            *released.entry(line.product_id).or_default() += unpicked;
+            line.reserved_quantity = line.picked_quantity;
+            self.repo
+                .update_sales_order_line(uow, &line)
+                .await
+                .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        }
+        release_in(&self.repo, uow, &order, released)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+
+        let order = SalesOrder {
+            reserved_until: None,
+            ..order
+        };
+        self.repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(map_sales_write_error)?;
+
+        Ok(true)
+    }
+
+    async fn get_availability_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockAvailabilityFilter,
+    ) -> Result<Vec<StockAvailability>, SalesOrderUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+        self.check_warehouse(uow, &filter.company_id, &filter.warehouse_id)
+            .await?;
+
+        self.repo
+            .find_availability(uow, filter, Utc::now().date_naive())
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)
+    }
+
+    // reserves what the warehouse still has available for the backorders of
+    // the lines, in line order. available is on hand less expired lots, which
+    // are never picked, and less what other orders hold. reservation rows are
+    // locked in product order so two orders over the same products can not
+    // deadlock each other
+    async fn reserve_lines(
+        &self,
+        uow: &mut R::Uow,
+        order: &SalesOrder,
+        lines: &mut [SalesOrderLine],
+    ) -> Result<(), SalesOrderUsecaseError> {
+        let mut products: Vec<Uuid> = lines
+            .iter()
+            .filter(|line| line.backordered_quantity() > 0)
+            .map(|line| line.product_id)
+            .collect();
+        products.sort();
+        products.dedup();
+
+        let today = Utc::now().date_naive();
+        for product_id in products {
+            let reserved = self
+                .repo
+                .lock_reservation(uow, &order.company_id, &product_id, &order.warehouse_id)
+                .await
+                .map_err(map_sales_write_error)?;
+            let usable = self
+                .repo
+                .get_warehouse_usable(uow, &product_id, &order.warehouse_id, today)
+                .await
+                .map_err(SalesOrderUsecaseError::DatabaseError)?;
+
+            let mut available = usable.saturating_sub(reserved).max(0);
+            let mut total = reserved;
+            for line in lines
+                .iter_mut()
+                .filter(|line| line.product_id == product_id)
+            {
+                let quantity = line.backordered_quantity().min(available);
+                if quantity == 0 {
+                    continue;
+                }
+                line.reserved_quantity += quantity;
+                available -= quantity;
+                total += quantity;
+                self.repo
+                    .update_sales_order_line(uow, line)
+                    .await
+                    .map_err(SalesOrderUsecaseError::DatabaseError)?;
+            }
+            if total != reserved {
+                self.repo
+                    .update_reservation(uow, &product_id, &order.warehouse_id, total, Utc::now())
+                    .await
+                    .map_err(SalesOrderUsecaseError::DatabaseError)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn build_lines(
+        &self,
+        uow: &mut R::Uow,
+        order: &SalesOrder,
+        inputs: Vec<SalesOrderLineInput>,
+    ) -> Result<Vec<SalesOrderLine>, SalesOrderUsecaseError> {
+        let mut lines = Vec::with_capacity(inputs.len());
+        for (position, input) in (1..).zip(inputs) {
+            if input.quantity <= Decimal::ZERO {
+                return Err(SalesOrderUsecaseError::InvalidQuantity);
+            }
+            let product_company = self
+                .repo
+                .get_product_company(uow, &input.product_id)
+                .await
+                .map_err(SalesOrderUsecaseError::DatabaseError)?
+                .ok_or(SalesOrderUsecaseError::ProductNotFound)?;
+            if product_company != order.company_id {
+                return Err(SalesOrderUsecaseError::CompanyMismatch);
+            }
+            let conversion = find_conversion_in(
+                &self.repo,
+                uow,
+                &input.product_id,
+                input.unit.as_deref(),
+                UnitPurpose::Sales,
+            )
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?
+            .ok_or(SalesOrderUsecaseError::UnitNotFound)?;
+            let quantity = conversion
+                .to_base(input.quantity)
+                .map_err(SalesOrderUsecaseError::Conversion)?;
+
+            lines.push(SalesOrderLine {
+                id: Uuid::new_v4(),
+                sales_order_id: order.id,
+                position,
+                product_id: input.product_id,
+                quantity,
+                unit: conversion.unit,
+                unit_quantity: input.quantity.normalize(),
+                reserved_quantity: 0,
+                picked_quantity: 0,
+                shipped_quantity: 0,
+            });
+        }
+
+        Ok(lines)
+    }
+
+    async fn check_order_number(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), SalesOrderUsecaseError> {
+        let is_number_exist = self
+            .repo
+            .check_existing_sales_order_number(uow, company_id, number, id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(SalesOrderUsecaseError::NumberAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_customer(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        customer_id: &Uuid,
+    ) -> Result<(), SalesOrderUsecaseError> {
+        if customer_id == company_id {
+            return Err(SalesOrderUsecaseError::InvalidCustomer);
+        }
+        let is_customer_exist = self
+            .repo
+            .check_existing_company(uow, customer_id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        if !is_customer_exist {
+            return Err(SalesOrderUsecaseError::CustomerNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn check_warehouse(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        warehouse_id: &Uuid,
+    ) -> Result<(), SalesOrderUsecaseError> {
+        let warehouse_company = self
+            .repo
+            .get_warehouse_company(uow, warehouse_id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?
+            .ok_or(SalesOrderUsecaseError::WarehouseNotFound)?;
+        if warehouse_company != *company_id {
+            return Err(SalesOrderUsecaseError::CompanyMismatch);
+        }
+
+        Ok(())
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), SalesOrderUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(SalesOrderUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn lock_order(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<SalesOrder, SalesOrderUsecaseError> {
+        self.repo
+            .lock_sales_order(uow, id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)?
+            .ok_or(SalesOrderUsecaseError::NotFound)
+    }
+
+    async fn find_lines(
+        &self,
+        uow: &mut R::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<SalesOrderLine>, SalesOrderUsecaseError> {
+        self.repo
+            .find_sales_order_lines(uow, sales_order_id)
+            .await
+            .map_err(SalesOrderUsecaseError::DatabaseError)
+    }
+}
+
+// takes the released quantities per product off the reservations in the
+// order's warehouse, locking them in product order. shared with the
+// shipments, which release what they ship
+pub(crate) async fn release_in<R: SalesRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    order: &SalesOrder,
+    released: BTreeMap<Uuid, i64>,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    for (product_id, quantity) in released {
+        let reserved = repo
+            .lock_reservation(uow, &order.company_id, &product_id, &order.warehouse_id)
+            .await?;
+        // the lines and the aggregate move together, it can only go below 0
+        // if the row was edited by hand
+        let reserved = reserved.saturating_sub(quantity).max(0);
+        repo.update_reservation(uow, &product_id, &order.warehouse_id, reserved, now)
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn check_reserved_until(input: &ReservationInput) -> Result<(), SalesOrderUsecaseError> {
+    if input
+        .reserved_until
+        .is_some_and(|until| until <= Utc::now())
+    {
+        return Err(SalesOrderUsecaseError::InvalidReservedUntil);
+    }
+    Ok(())
+}
+
+// a concurrent unit of work can still claim a number or delete the customer,
+// warehouse or a product between the check and the write, the constraints
+// catch that
+fn map_sales_write_error(err: sqlx::Error) -> SalesOrderUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(ORDER_NUMBER_CONSTRAINT) => SalesOrderUsecaseError::NumberAlreadyExist,
+        Some(ORDER_COMPANY_CONSTRAINT) => SalesOrderUsecaseError::CompanyNotFound,
+        Some(ORDER_CUSTOMER_CONSTRAINT) => SalesOrderUsecaseError::CustomerNotFound,
+        Some(ORDER_CUSTOMER_CHECK) => SalesOrderUsecaseError::InvalidCustomer,
+        Some(ORDER_WAREHOUSE_CONSTRAINT) => SalesOrderUsecaseError::WarehouseNotFound,
+        Some(LINE_PRODUCT_CONSTRAINT) => SalesOrderUsecaseError::ProductNotFound,
+        _ => SalesOrderUsecaseError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,557 @@
+use chrono::{Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::sales::domain::availability::{StockAvailability, StockAvailabilityFilter};
+use crate::sales::domain::sales_order::{SalesOrderFilter, SalesOrderStatus};
+use crate::sales::usecase::dto::{
+    ReservationInput, SalesOrderDetail, SalesOrderInput, SalesOrderLineInput,
+};
+use crate::sales::usecase::sales_order_usecase::{SalesOrderUsecase, SalesOrderUsecaseError};
+use crate::stock::domain::movement::MovementType;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::{LotInput, MovementInput};
+use crate::stock::usecase::lot_usecase::LotUsecase;
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+// a warehouse with a single bin
+async fn bin(warehouses: &WarehouseRepositoryMemory, company: &Company, code: &str) -> Location {
+    let usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = usecase
+        .create_warehouse(
+            company.id,
+            WarehouseInput {
+                code: code.into(),
+                name: code.to_lowercase(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    usecase
+        .create_location(
+            warehouse.id,
+            LocationInput {
+                parent_id: None,
+                code: "A-01".into(),
+                name: "Bin A-01".into(),
+                kind: LocationKind::Bin,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+fn until(hours: i64) -> ReservationInput {
+    ReservationInput {
+        reserved_until: Some(Utc::now() + Duration::hours(hours)),
+    }
+}
+
+fn no_expiry() -> ReservationInput {
+    ReservationInput {
+        reserved_until: None,
+    }
+}
+
+struct Fixture {
+    usecase: SalesOrderUsecase<StockRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    lots: LotUsecase<StockRepositoryMemory>,
+    acme: Company,
+    globex: Company,
+    bolt: Product,
+    nut: Product,
+    milk: Product,
+    gear: Product,
+    north: Location,
+    globex_bin: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    fn order(&self, number: &str, lines: &[(&Product, i64)]) -> SalesOrderInput {
+        SalesOrderInput {
+            customer_id: self.globex.id,
+            warehouse_id: self.north.warehouse_id,
+            number: number.into(),
+            note: None,
+            lines: lines
+                .iter()
+                .map(|(product, quantity)| SalesOrderLineInput {
+                    product_id: product.id,
+                    unit: None,
+                    quantity: Decimal::from(*quantity),
+                })
+                .collect(),
+        }
+    }
+
+    async fn confirmed(
+        &self,
+        number: &str,
+        lines: &[(&Product, i64)],
+        input: ReservationInput,
+    ) -> SalesOrderDetail {
+        let draft = self
+            .usecase
+            .create_order(self.acme.id, self.order(number, lines), self.user)
+            .await
+            .unwrap();
+        self.usecase
+            .confirm_order(draft.order.id, input, self.user)
+            .await
+            .unwrap()
+    }
+
+    fn movement(
+        &self,
+        product: &Product,
+        movement_type: MovementType,
+        lot_id: Option<Uuid>,
+        quantity: i64,
+    ) -> MovementInput {
+        MovementInput {
+            product_id: product.id,
+            location_id: self.north.id,
+            movement_type,
+            lot_id,
+            quantity,
+            serial_numbers: Vec::new(),
+            reference: None,
+            note: None,
+        }
+    }
+
+    async fn receive(&self, product: &Product, quantity: i64) {
+        self.stock
+            .post_movement(
+                self.movement(product, MovementType::Receipt, None, quantity),
+                self.user,
+            )
+            .await
+            .unwrap();
+    }
+
+    // a lot of milk expiring the given number of days from today, received
+    async fn receive_lot(&self, number: &str, expires_in_days: i64, quantity: i64) -> Uuid {
+        let lot = self
+            .lots
+            .create_lot(LotInput {
+                product_id: self.milk.id,
+                lot_number: number.into(),
+                manufactured_on: None,
+                expires_on: Some((Utc::now() + Duration::days(expires_in_days)).date_naive()),
+            })
+            .await
+            .unwrap();
+        self.stock
+            .post_movement(
+                self.movement(&self.milk, MovementType::Receipt, Some(lot.id), quantity),
+                self.user,
+            )
+            .await
+            .unwrap();
+        lot.id
+    }
+
+    async fn availability(&self, product: &Product) -> StockAvailability {
+        let filter = StockAvailabilityFilter {
+            company_id: self.acme.id,
+            warehouse_id: self.north.warehouse_id,
+            product_id: Some(product.id),
+        };
+        let mut rows = self.usecase.get_availability(&filter).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        rows.remove(0)
+    }
+}
+
+// ACM sells bolts, nuts and lot tracked milk out of its north warehouse to
+// GLB, which has a product and warehouse of its own
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let bolt = product(&acme, "BOLT");
+    let nut = product(&acme, "NUT");
+    let milk = Product {
+        is_lot_tracked: true,
+        ..product(&acme, "MILK")
+    };
+    let gear = product(&globex, "GEAR");
+    let products = ProductRepositoryMemory::with_products(
+        companies.clone(),
+        vec![bolt.clone(), nut.clone(), milk.clone(), gear.clone()],
+    );
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let north = bin(&warehouses, &acme, "NORTH").await;
+    let globex_bin = bin(&warehouses, &globex, "MAIN").await;
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    Fixture {
+        usecase: SalesOrderUsecase::new(repo.clone(), AppMetrics::new()),
+        stock: StockUsecase::new(repo.clone(), AppMetrics::new()),
+        lots: LotUsecase::new(repo, AppMetrics::new()),
+        acme,
+        globex,
+        bolt,
+        nut,
+        milk,
+        gear,
+        north,
+        globex_bin,
+        user: Uuid::new_v4(),
+    }
+}
+
+#[tokio::test]
+async fn confirmation_reserves_available_stock_and_backorders_the_rest() {
+    let f = setup().await;
+    f.receive(&f.bolt, 10).await;
+
+    let draft = f
+        .usecase
+        .create_order(
+            f.acme.id,
+            f.order("SO-1", &[(&f.bolt, 6), (&f.nut, 3)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(draft.order.status, SalesOrderStatus::Draft);
+    // nothing is held before confirmation
+    assert_eq!(f.availability(&f.bolt).await.reserved, 0);
+
+    let order = f
+        .usecase
+        .confirm_order(draft.order.id, no_expiry(), f.user)
+        .await
+        .unwrap();
+    assert_eq!(order.order.status, SalesOrderStatus::Confirmed);
+    assert_eq!(order.order.confirmed_by, Some(f.user));
+    assert_eq!(order.lines[0].reserved_quantity, 6);
+    assert_eq!(order.lines[0].backordered_quantity(), 0);
+    assert_eq!(order.lines[1].reserved_quantity, 0);
+    assert_eq!(order.lines[1].backordered_quantity(), 3);
+
+    // reserving lowers available, on hand stays until the stock ships
+    assert_eq!(
+        f.availability(&f.bolt).await,
+        StockAvailability {
+            product_id: f.bolt.id,
+            warehouse_id: f.north.warehouse_id,
+            on_hand: 10,
+            reserved: 6,
+            available: 4,
+        }
+    );
+
+    // a second order only gets what the first one left
+    let second = f.confirmed("SO-2", &[(&f.bolt, 7)], no_expiry()).await;
+    assert_eq!(second.lines[0].reserved_quantity, 4);
+    assert_eq!(second.lines[0].backordered_quantity(), 3);
+    assert_eq!(f.availability(&f.bolt).await.available, 0);
+
+    // backorders are picked up once stock arrives
+    f.receive(&f.bolt, 5).await;
+    f.receive(&f.nut, 2).await;
+    let second = f
+        .usecase
+        .reserve_order(second.order.id, no_expiry())
+        .await
+        .unwrap();
+    assert_eq!(second.lines[0].reserved_quantity, 7);
+    let order = f
+        .usecase
+        .reserve_order(order.order.id, no_expiry())
+        .await
+        .unwrap();
+    assert_eq!(order.lines[1].reserved_quantity, 2);
+    assert_eq!(order.lines[1].backordered_quantity(), 1);
+    assert_eq!(f.availability(&f.bolt).await.available, 2);
+
+    let filter = SalesOrderFilter {
+        company_id: f.acme.id,
+        customer_id: Some(f.globex.id),
+        status: Some(SalesOrderStatus::Confirmed),
+    };
+    let result = f.usecase.list_orders(&filter, &query()).await.unwrap();
+    assert_eq!(result.total_data, 2);
+}
+
+#[tokio::test]
+async fn cancellation_releases_reservations() {
+    let f = setup().await;
+    f.receive(&f.bolt, 10).await;
+
+    let order = f.confirmed("SO-1", &[(&f.bolt, 8)], no_expiry()).await;
+    assert_eq!(f.availability(&f.bolt).await.available, 2);
+
+    let cancelled = f.usecase.cancel_order(order.order.id).await.unwrap();
+    assert_eq!(cancelled.order.status, SalesOrderStatus::Cancelled);
+    assert!(cancelled.order.cancelled_at.is_some());
+    assert_eq!(cancelled.lines[0].reserved_quantity, 0);
+    assert_eq!(f.availability(&f.bolt).await.available, 10);
+
+    let result = f.usecase.cancel_order(order.order.id).await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidStatus(
+            SalesOrderStatus::Cancelled
+        ))
+    ));
+    let result = f.usecase.reserve_order(order.order.id, no_expiry()).await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidStatus(
+            SalesOrderStatus::Cancelled
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn reserved_stock_is_not_taken_by_other_postings() {
+    let f = setup().await;
+    f.receive(&f.bolt, 10).await;
+    let order = f.confirmed("SO-1", &[(&f.bolt, 8)], no_expiry()).await;
+
+    for (movement_type, quantity) in [(MovementType::Issue, 3), (MovementType::Adjustment, -3)] {
+        let result = f
+            .stock
+            .post_movement(f.movement(&f.bolt, movement_type, None, quantity), f.user)
+            .await;
+        assert!(
+            matches!(
+                result,
+                Err(StockUsecaseError::InsufficientStock { available: 2 })
+            ),
+            "{movement_type:?}"
+        );
+    }
+    f.stock
+        .post_movement(f.movement(&f.bolt, MovementType::Issue, None, 2), f.user)
+        .await
+        .unwrap();
+    assert_eq!(f.availability(&f.bolt).await.available, 0);
+    // receipts are never held back
+    f.receive(&f.bolt, 1).await;
+
+    f.usecase.cancel_order(order.order.id).await.unwrap();
+    f.stock
+        .post_movement(f.movement(&f.bolt, MovementType::Issue, None, 9), f.user)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn expired_lots_are_not_reserved() {
+    let f = setup().await;
+    let expired = f.receive_lot("L-1", -1, 4).await;
+    let fresh = f.receive_lot("L-2", 30, 3).await;
+
+    let order = f.confirmed("SO-1", &[(&f.milk, 6)], no_expiry()).await;
+    assert_eq!(order.lines[0].reserved_quantity, 3);
+    assert_eq!(order.lines[0].backordered_quantity(), 3);
+    assert_eq!(
+        f.availability(&f.milk).await,
+        StockAvailability {
+            product_id: f.milk.id,
+            warehouse_id: f.north.warehouse_id,
+            on_hand: 7,
+            reserved: 3,
+            available: 0,
+        }
+    );
+
+    // the reserved lot stays, the expired one can still be written off
+    let result = f
+        .stock
+        .post_movement(
+            f.movement(&f.milk, MovementType::Issue, Some(fresh), 1),
+            f.user,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::InsufficientStock { available: 0 })
+    ));
+    f.stock
+        .post_movement(
+            f.movement(&f.milk, MovementType::Issue, Some(expired), 4),
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(f.availability(&f.milk).await.on_hand, 3);
+}
+
+#[tokio::test]
+async fn expired_reservations_are_released() {
+    let f = setup().await;
+    f.receive(&f.bolt, 10).await;
+
+    let expiring = f.confirmed("SO-1", &[(&f.bolt, 4)], until(1)).await;
+    let kept = f.confirmed("SO-2", &[(&f.bolt, 3)], no_expiry()).await;
+    let later = f.confirmed("SO-3", &[(&f.bolt, 2)], until(48)).await;
+    assert_eq!(f.availability(&f.bolt).await.available, 1);
+
+    let released = f
+        .usecase
+        .release_expired_reservations(Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(released, 0);
+
+    let released = f
+        .usecase
+        .release_expired_reservations(Utc::now() + Duration::hours(2))
+        .await
+        .unwrap();
+    assert_eq!(released, 1);
+    assert_eq!(f.availability(&f.bolt).await.available, 5);
+
+    // the order stays open with its lines backordered
+    let expired = f.usecase.get_order(expiring.order.id).await.unwrap();
+    assert_eq!(expired.order.status, SalesOrderStatus::Confirmed);
+    assert_eq!(expired.order.reserved_until, None);
+    assert_eq!(expired.lines[0].reserved_quantity, 0);
+    assert_eq!(expired.lines[0].backordered_quantity(), 4);
+    for id in [kept.order.id, later.order.id] {
+        let order = f.usecase.get_order(id).await.unwrap();
+        assert!(order.lines[0].reserved_quantity > 0);
+    }
+
+    // released orders are not found again
+    let released = f
+        .usecase
+        .release_expired_reservations(Utc::now() + Duration::hours(2))
+        .await
+        .unwrap();
+    assert_eq!(released, 0);
+}
+
+#[tokio::test]
+async fn sales_order_rejects_invalid_input() {
+    let f = setup().await;
+
+    let mut to_itself = f.order("SO-1", &[(&f.bolt, 1)]);
+    to_itself.customer_id = f.acme.id;
+    let mut unknown_customer = f.order("SO-1", &[(&f.bolt, 1)]);
+    unknown_customer.customer_id = Uuid::new_v4();
+    let mut foreign_warehouse = f.order("SO-1", &[(&f.bolt, 1)]);
+    foreign_warehouse.warehouse_id = f.globex_bin.warehouse_id;
+    let cases = [
+        (to_itself, "a company can not sell to itself"),
+        (unknown_customer, "sales order customer not found"),
+        (foreign_warehouse, "sales order refers to another company"),
+        (
+            f.order("SO-1", &[(&f.gear, 1)]),
+            "sales order refers to another company",
+        ),
+        (
+            f.order("SO-1", &[(&f.bolt, 0)]),
+            "sales order quantity is invalid",
+        ),
+    ];
+    for (input, expected) in cases {
+        let Err(err) = f.usecase.create_order(f.acme.id, input, f.user).await else {
+            panic!("{expected} was accepted");
+        };
+        assert_eq!(err.to_string(), expected);
+    }
+
+    let draft = f
+        .usecase
+        .create_order(f.acme.id, f.order("SO-1", &[]), f.user)
+        .await
+        .unwrap();
+    let result = f
+        .usecase
+        .create_order(f.acme.id, f.order("SO-1", &[(&f.bolt, 1)]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::NumberAlreadyExist)
+    ));
+
+    let result = f
+        .usecase
+        .confirm_order(draft.order.id, no_expiry(), f.user)
+        .await;
+    assert!(matches!(result, Err(SalesOrderUsecaseError::NoLines)));
+    let result = f
+        .usecase
+        .confirm_order(draft.order.id, until(-1), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidReservedUntil)
+    ));
+    let result = f.usecase.reserve_order(draft.order.id, no_expiry()).await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidStatus(
+            SalesOrderStatus::Draft
+        ))
+    ));
+
+    let order = f.confirmed("SO-2", &[(&f.bolt, 1)], no_expiry()).await;
+    let result = f
+        .usecase
+        .update_order(order.order.id, f.order("SO-2", &[(&f.bolt, 2)]))
+        .await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidStatus(
+            SalesOrderStatus::Confirmed
+        ))
+    ));
+    let result = f.usecase.delete_order(order.order.id).await;
+    assert!(matches!(
+        result,
+        Err(SalesOrderUsecaseError::InvalidStatus(
+            SalesOrderStatus::Confirmed
+        ))
+    ));
+    f.usecase.delete_order(draft.order.id).await.unwrap();
+    let result = f.usecase.get_order(draft.order.id).await;
+    assert!(matches!(result, Err(SalesOrderUsecaseError::NotFound)));
+}
@@ -0,0 +1,717 @@
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::sales::domain::pick::{PickSuggestion, PickableStock};
+use crate::sales::domain::sales_order::{SalesOrder, SalesOrderLine, SalesOrderStatus};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine, ShipmentStatus};
+use crate::sales::repository::sales_repository::SalesRepository;
+use crate::sales::usecase::dto::{ShipmentDetail, ShipmentInput};
+use crate::sales::usecase::sales_order_usecase::release_in;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::usecase::stock_usecase::{
+    Posting, StockUsecaseError, check_lot_in, check_serials_in, post_in,
+};
+
+// names of the constraints on the shipment tables, see migrations
+const SHIPMENT_NUMBER_CONSTRAINT: &str = "shipments_company_id_number_key";
+const SHIPMENT_LOCATION_CONSTRAINT: &str = "shipment_lines_location_id_fkey";
+
+pub struct ShipmentUsecase<R: SalesRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum ShipmentUsecaseError {
+    SalesOrderNotFound,
+    NotFound,
+    NumberAlreadyExist,
+    // the order is not confirmed, or shipped or cancelled already
+    InvalidOrderStatus(SalesOrderStatus),
+    // the shipment can not take this step from its current status
+    InvalidStatus(ShipmentStatus),
+    NoLines,
+    InvalidQuantity,
+    LineNotFound,
+    // the location is missing or not in the order's warehouse
+    LocationNotFound,
+    // the line would pick more than it has reserved and not picked yet
+    OverPick { line_id: Uuid, pickable: i64 },
+    // the location holds less than the shipment takes from it
+    InsufficientStock { product_id: Uuid, available: i64 },
+    // a posting failed for a reason the shipment does not translate
+    Posting(StockUsecaseError),
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for ShipmentUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShipmentUsecaseError::SalesOrderNotFound => write!(f, "sales order not found"),
+            ShipmentUsecaseError::NotFound => write!(f, "shipment not found"),
+            ShipmentUsecaseError::NumberAlreadyExist => {
+                write!(f, "shipment number already exists")
+            }
+            ShipmentUsecaseError::InvalidOrderStatus(status) => {
+                write!(f, "sales order is {}", status.as_str())
+            }
+            ShipmentUsecaseError::InvalidStatus(status) => {
+                write!(f, "shipment is {}", status.as_str())
+            }
+            ShipmentUsecaseError::NoLines => write!(f, "shipment has no lines"),
+            ShipmentUsecaseError::InvalidQuantity => write!(f, "shipment quantity is invalid"),
+            ShipmentUsecaseError::LineNotFound => write!(f, "sales order line not found"),
+            ShipmentUsecaseError::LocationNotFound => write!(f, "shipment location not found"),
+            ShipmentUsecaseError::OverPick { line_id, pickable } => {
+                write!(f, "line {line_id} can pick at most {pickable}")
+            }
+            ShipmentUsecaseError::InsufficientStock {
+                product_id,
+                available,
+            } => write!(
+                f,
+                "insufficient stock of {product_id}, {available} available"
+            ),
+            ShipmentUsecaseError::Posting(err) => write!(f, "shipment posting failed: {err}"),
+            ShipmentUsecaseError::DatabaseError(_) => write!(f, "shipment storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for ShipmentUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShipmentUsecaseError::Posting(err) => Some(err),
+            ShipmentUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: SalesRepository> ShipmentUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    // picks reserved stock of the order, the ledger is left alone until the
+    // shipment ships
+    #[instrument(skip_all, fields(sales_order_id = %sales_order_id, number = %input.number, lines = input.lines.len()))]
+    pub async fn create_shipment(
+        &self,
+        sales_order_id: Uuid,
+        input: ShipmentInput,
+        created_by: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .create_shipment_in(&mut uow, sales_order_id, input, created_by)
+            .await;
+        let detail = finish(uow, result, ShipmentUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("shipment_create");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(shipment_id = %id))]
+    pub async fn pack_shipment(&self, id: Uuid) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.pack_shipment_in(&mut uow, id).await;
+        let detail = finish(uow, result, ShipmentUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("shipment_pack");
+        Ok(detail)
+    }
+
+    // posts an issue movement per line out of its location, the shipped
+    // quantities leave the reservations
+    #[instrument(skip_all, fields(shipment_id = %id))]
+    pub async fn ship_shipment(
+        &self,
+        id: Uuid,
+        shipped_by: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.ship_shipment_in(&mut uow, id, shipped_by).await;
+        let (detail, movements) = finish(uow, result, ShipmentUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("shipment_ship");
+        for movement in &movements {
+            self.metrics
+                .stock_movement_posted(movement.movement_type.as_str());
+        }
+        Ok(detail)
+    }
+
+    // puts the picked stock back, it stays reserved for the order
+    #[instrument(skip_all, fields(shipment_id = %id))]
+    pub async fn cancel_shipment(&self, id: Uuid) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.cancel_shipment_in(&mut uow, id).await;
+        let detail = finish(uow, result, ShipmentUsecaseError::DatabaseError).await?;
+
+        self.metrics.sales_operation("shipment_cancel");
+        Ok(detail)
+    }
+
+    // where to pick what the open lines of the order still need, lots that
+    // expire first are suggested first and expired lots not at all. nothing is
+    // picked until a shipment is created from it
+    #[instrument(skip_all, fields(sales_order_id = %sales_order_id))]
+    pub async fn suggest_picks(
+        &self,
+        sales_order_id: Uuid,
+    ) -> Result<Vec<PickSuggestion>, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.suggest_picks_in(&mut uow, sales_order_id).await;
+        finish(uow, result, ShipmentUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(shipment_id = %id))]
+    pub async fn get_shipment(&self, id: Uuid) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_shipment_in(&mut uow, id).await;
+        finish(uow, result, ShipmentUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(sales_order_id = %sales_order_id))]
+    pub async fn list_shipments(
+        &self,
+        sales_order_id: Uuid,
+    ) -> Result<Vec<Shipment>, ShipmentUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_shipments_in(&mut uow, sales_order_id).await;
+        finish(uow, result, ShipmentUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, ShipmentUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)
+    }
+
+    async fn create_shipment_in(
+        &self,
+        uow: &mut R::Uow,
+        sales_order_id: Uuid,
+        input: ShipmentInput,
+        created_by: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let order = self.lock_order(uow, &sales_order_id).await?;
+        if !order.status.is_open() {
+            return Err(ShipmentUsecaseError::InvalidOrderStatus(order.status));
+        }
+        if input.lines.is_empty() {
+            return Err(ShipmentUsecaseError::NoLines);
+        }
+        self.check_shipment_number(uow, &order.company_id, &input.number)
+            .await?;
+        let mut order_lines = self.find_order_lines(uow, &order.id).await?;
+        let today = Utc::now().date_naive();
+
+        // the same line may be picked from several locations, what it can
+        // pick applies to the total
+        let mut picked: HashMap<Uuid, i64> = HashMap::new();
+        for shipment_line in &input.lines {
+            if shipment_line.quantity <= 0 {
+                return Err(ShipmentUsecaseError::InvalidQuantity);
+            }
+            let product_id = order_lines
+                .iter()
+                .find(|line| line.id == shipment_line.line_id)
+                .map(|line| line.product_id)
+                .ok_or(ShipmentUsecaseError::LineNotFound)?;
+            let lot = check_lot_in(&self.repo, uow, &product_id, shipment_line.lot_id.as_ref())
+                .await
+                .map_err(|err| map_posting_error(err, product_id))?;
+            if lot.is_some_and(|lot| lot.is_expired(today)) {
+                return Err(ShipmentUsecaseError::Posting(StockUsecaseError::LotExpired));
+            }
+            let location_warehouse = self
+                .repo
+                .get_location_warehouse(uow, &shipment_line.location_id)
+                .await
+                .map_err(ShipmentUsecaseError::DatabaseError)?;
+            if location_warehouse != Some(order.warehouse_id) {
+                return Err(ShipmentUsecaseError::LocationNotFound);
+            }
+            // the serials are assigned now and checked again when shipped
+            check_serials_in(
+                &self.repo,
+                uow,
+                &product_id,
+                &shipment_line.location_id,
+                -shipment_line.quantity,
+                &shipment_line.serial_numbers,
+            )
+            .await
+            .map_err(|err| map_posting_error(err, product_id))?;
+            let total = picked.entry(shipment_line.line_id).or_default();
+            *total = total
+                .checked_add(shipment_line.quantity)
+                .ok_or(ShipmentUsecaseError::InvalidQuantity)?;
+        }
+
+        for line in order_lines.iter_mut() {
+            let Some(quantity) = picked.get(&line.id).copied() else {
+                continue;
+            };
+            if quantity > line.pickable_quantity() {
+                return Err(ShipmentUsecaseError::OverPick {
+                    line_id: line.id,
+                    pickable: line.pickable_quantity(),
+                });
+            }
+            line.picked_quantity += quantity;
+            self.repo
+                .update_sales_order_line(uow, line)
+                .await
+                .map_err(ShipmentUsecaseError::DatabaseError)?;
+        }
+
+        let shipment = Shipment {
+            id: Uuid::new_v4(),
+            company_id: order.company_id,
+            sales_order_id: order.id,
+            number: input.number,
+            status: ShipmentStatus::Picking,
+            note: input.note,
+            created_by,
+            created_at: Utc::now(),
+            packed_at: None,
+            shipped_by: None,
+            shipped_at: None,
+            cancelled_at: None,
+        };
+        let lines = (1..)
+            .zip(input.lines)
+            .map(|(position, input)| ShipmentLine {
+                id: Uuid::new_v4(),
+                shipment_id: shipment.id,
+                position,
+                sales_order_line_id: input.line_id,
+                location_id: input.location_id,
+                lot_id: input.lot_id,
+                quantity: input.quantity,
+                serial_numbers: input
+                    .serial_numbers
+                    .iter()
+                    .map(|number| number.trim().to_string())
+                    .collect(),
+                movement_id: None,
+            })
+            .collect();
+        let (shipment, lines) = self
+            .repo
+            .create_shipment(uow, shipment, lines)
+            .await
+            .map_err(map_shipment_write_error)?;
+
+        Ok(ShipmentDetail { shipment, lines })
+    }
+
+    async fn pack_shipment_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let (_, shipment) = self.lock_shipment(uow, &id).await?;
+        if shipment.status != ShipmentStatus::Picking {
+            return Err(ShipmentUsecaseError::InvalidStatus(shipment.status));
+        }
+        let lines = self.find_shipment_lines(uow, &id).await?;
+
+        let shipment = Shipment {
+            status: ShipmentStatus::Packed,
+            packed_at: Some(Utc::now()),
+            ..shipment
+        };
+        let shipment = self
+            .repo
+            .update_shipment(uow, shipment)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        Ok(ShipmentDetail { shipment, lines })
+    }
+
+    async fn ship_shipment_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        shipped_by: Uuid,
+    ) -> Result<(ShipmentDetail, Vec<StockMovement>), ShipmentUsecaseError> {
+        let (order, shipment) = self.lock_shipment(uow, &id).await?;
+        if shipment.status != ShipmentStatus::Packed {
+            return Err(ShipmentUsecaseError::InvalidStatus(shipment.status));
+        }
+        let lines = self.find_shipment_lines(uow, &id).await?;
+        let mut order_lines = self.find_order_lines(uow, &order.id).await?;
+
+        let mut shipped: HashMap<Uuid, i64> = HashMap::new();
+        for line in &lines {
+            *shipped.entry(line.sales_order_line_id).or_default() += line.quantity;
+        }
+        let mut released: BTreeMap<Uuid, i64> = BTreeMap::new();
+        for line in order_lines.iter_mut() {
+            let Some(quantity) = shipped.get(&line.id).copied() else {
+                continue;
+            };
+            *released.entry(line.product_id).or_default() += quantity;
+            line.reserved_quantity -= quantity;
+            line.picked_quantity -= quantity;
+            line.shipped_quantity += quantity;
+            self.repo
+                .update_sales_order_line(uow, line)
+                .await
+                .map_err(ShipmentUsecaseError::DatabaseError)?;
+        }
+        // reservations are locked before the balances, like on confirmation
+        release_in(&self.repo, uow, &order, released)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        let shipped_at = Utc::now();
+        let shipment = Shipment {
+            status: ShipmentStatus::Shipped,
+            shipped_by: Some(shipped_by),
+            shipped_at: Some(shipped_at),
+            ..shipment
+        };
+        let (lines, movements) = self
+            .post_lines(uow, &shipment, shipped_by, &order_lines, lines)
+            .await?;
+        let shipment = self
+            .repo
+            .update_shipment(uow, shipment)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        let is_complete = order_lines
+            .iter()
+            .all(|line| line.shipped_quantity >= line.quantity);
+        let order = if is_complete {
+            SalesOrder {
+                status: SalesOrderStatus::Shipped,
+                shipped_at: Some(shipped_at),
+                ..order
+            }
+        } else {
+            SalesOrder {
+                status: SalesOrderStatus::PartiallyShipped,
+                ..order
+            }
+        };
+        self.repo
+            .update_sales_order(uow, order)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        Ok((ShipmentDetail { shipment, lines }, movements))
+    }
+
+    // one issue movement per shipment line, referenced by the shipment number.
+    // balances are locked in product and location order so two shipments over
+    // the same products can not deadlock each other
+    async fn post_lines(
+        &self,
+        uow: &mut R::Uow,
+        shipment: &Shipment,
+        shipped_by: Uuid,
+        order_lines: &[SalesOrderLine],
+        lines: Vec<ShipmentLine>,
+    ) -> Result<(Vec<ShipmentLine>, Vec<StockMovement>), ShipmentUsecaseError> {
+        let product_by_line: HashMap<Uuid, Uuid> = order_lines
+            .iter()
+            .map(|line| (line.id, line.product_id))
+            .collect();
+        let mut postings: Vec<(Uuid, ShipmentLine)> = lines
+            .into_iter()
+            .map(|line| (product_by_line[&line.sales_order_line_id], line))
+            .collect();
+        postings.sort_by_key(|(product_id, line)| {
+            (*product_id, line.location_id, line.lot_id, line.position)
+        });
+
+        let mut lines = Vec::with_capacity(postings.len());
+        let mut movements = Vec::with_capacity(postings.len());
+        for (product_id, line) in postings {
+            let posting = Posting {
+                company_id: shipment.company_id,
+                product_id,
+                location_id: line.location_id,
+                movement_type: MovementType::Issue,
+                lot_id: line.lot_id,
+                quantity: -line.quantity,
+                serial_numbers: line.serial_numbers.clone(),
+                reversal_of: None,
+                source: MovementSource::Shipment,
+                source_id: Some(shipment.id),
+                reference: Some(shipment.number.clone()),
+                note: None,
+                posted_by: shipped_by,
+            };
+            let movement = post_in(&self.repo, uow, posting)
+                .await
+                .map_err(|err| map_posting_error(err, product_id))?;
+            self.repo
+                .update_shipment_line_movement(uow, &line.id, &movement.id)
+                .await
+                .map_err(ShipmentUsecaseError::DatabaseError)?;
+            lines.push(ShipmentLine {
+                movement_id: Some(movement.id),
+                ..line
+            });
+            movements.push(movement);
+        }
+        lines.sort_by_key(|line| line.position);
+
+        Ok((lines, movements))
+    }
+
+    async fn cancel_shipment_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let (order, shipment) = self.lock_shipment(uow, &id).await?;
+        if !shipment.status.is_open() {
+            return Err(ShipmentUsecaseError::InvalidStatus(shipment.status));
+        }
+        let lines = self.find_shipment_lines(uow, &id).await?;
+        let mut order_lines = self.find_order_lines(uow, &order.id).await?;
+
+        let mut unpicked: HashMap<Uuid, i64> = HashMap::new();
+        for line in &lines {
+            *unpicked.entry(line.sales_order_line_id).or_default() += line.quantity;
+        }
+        for line in order_lines.iter_mut() {
+            let Some(quantity) = unpicked.get(&line.id).copied() else {
+                continue;
+            };
+            line.picked_quantity -= quantity;
+            self.repo
+                .update_sales_order_line(uow, line)
+                .await
+                .map_err(ShipmentUsecaseError::DatabaseError)?;
+        }
+
+        let shipment = Shipment {
+            status: ShipmentStatus::Cancelled,
+            cancelled_at: Some(Utc::now()),
+            ..shipment
+        };
+        let shipment = self
+            .repo
+            .update_shipment(uow, shipment)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        Ok(ShipmentDetail { shipment, lines })
+    }
+
+    async fn suggest_picks_in(
+        &self,
+        uow: &mut R::Uow,
+        sales_order_id: Uuid,
+    ) -> Result<Vec<PickSuggestion>, ShipmentUsecaseError> {
+        let order = self
+            .repo
+            .get_sales_order_by_id(uow, &sales_order_id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::SalesOrderNotFound)?;
+        if !order.status.is_open() {
+            return Err(ShipmentUsecaseError::InvalidOrderStatus(order.status));
+        }
+        let order_lines = self.find_order_lines(uow, &order.id).await?;
+        let product_ids: Vec<Uuid> = order_lines.iter().map(|line| line.product_id).collect();
+        let stock = self
+            .repo
+            .find_pickable_stock(uow, &order.warehouse_id, &product_ids)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+
+        Ok(suggest_picks(&order_lines, stock, Utc::now().date_naive()))
+    }
+
+    async fn get_shipment_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<ShipmentDetail, ShipmentUsecaseError> {
+        let shipment = self
+            .repo
+            .get_shipment_by_id(uow, &id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::NotFound)?;
+        let lines = self.find_shipment_lines(uow, &id).await?;
+
+        Ok(ShipmentDetail { shipment, lines })
+    }
+
+    async fn list_shipments_in(
+        &self,
+        uow: &mut R::Uow,
+        sales_order_id: Uuid,
+    ) -> Result<Vec<Shipment>, ShipmentUsecaseError> {
+        self.repo
+            .get_sales_order_by_id(uow, &sales_order_id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::SalesOrderNotFound)?;
+
+        self.repo
+            .find_sales_order_shipments(uow, &sales_order_id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)
+    }
+
+    async fn check_shipment_number(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<(), ShipmentUsecaseError> {
+        let is_number_exist = self
+            .repo
+            .check_existing_shipment_number(uow, company_id, number)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(ShipmentUsecaseError::NumberAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn lock_order(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<SalesOrder, ShipmentUsecaseError> {
+        self.repo
+            .lock_sales_order(uow, id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::SalesOrderNotFound)
+    }
+
+    // shipments have no lock of their own, the lock on their order covers
+    // them. the shipment is read again once the order is locked
+    async fn lock_shipment(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<(SalesOrder, Shipment), ShipmentUsecaseError> {
+        let shipment = self
+            .repo
+            .get_shipment_by_id(uow, id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::NotFound)?;
+        let order = self.lock_order(uow, &shipment.sales_order_id).await?;
+        let shipment = self
+            .repo
+            .get_shipment_by_id(uow, id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)?
+            .ok_or(ShipmentUsecaseError::NotFound)?;
+
+        Ok((order, shipment))
+    }
+
+    async fn find_order_lines(
+        &self,
+        uow: &mut R::Uow,
+        sales_order_id: &Uuid,
+    ) -> Result<Vec<SalesOrderLine>, ShipmentUsecaseError> {
+        self.repo
+            .find_sales_order_lines(uow, sales_order_id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)
+    }
+
+    async fn find_shipment_lines(
+        &self,
+        uow: &mut R::Uow,
+        shipment_id: &Uuid,
+    ) -> Result<Vec<ShipmentLine>, ShipmentUsecaseError> {
+        self.repo
+            .find_shipment_lines(uow, shipment_id)
+            .await
+            .map_err(ShipmentUsecaseError::DatabaseError)
+    }
+}
+
+// first expired first out: the stock comes ordered by expiry, each line takes
+// what it can still pick from the front. lines of the same product share the
+// stock in the order of the lines
+fn suggest_picks(
+    order_lines: &[SalesOrderLine],
+    mut stock: Vec<PickableStock>,
+    today: NaiveDate,
+) -> Vec<PickSuggestion> {
+    stock.retain(|s| s.expires_on.is_none_or(|expires_on| expires_on >= today));
+
+    let mut suggestions = Vec::new();
+    for line in order_lines {
+        let mut needed = line.pickable_quantity();
+        for s in stock.iter_mut().filter(|s| s.product_id == line.product_id) {
+            if needed <= 0 {
+                break;
+            }
+            let quantity = needed.min(s.quantity);
+            if quantity <= 0 {
+                continue;
+            }
+            s.quantity -= quantity;
+            needed -= quantity;
+            suggestions.push(PickSuggestion {
+                line_id: line.id,
+                product_id: line.product_id,
+                location_id: s.location_id,
+                lot_id: s.lot_id,
+                expires_on: s.expires_on,
+                quantity,
+            });
+        }
+    }
+
+    suggestions
+}
+
+fn map_posting_error(err: StockUsecaseError, product_id: Uuid) -> ShipmentUsecaseError {
+    match err {
+        StockUsecaseError::InsufficientStock { available } => {
+            ShipmentUsecaseError::InsufficientStock {
+                product_id,
+                available,
+            }
+        }
+        StockUsecaseError::LocationNotFound => ShipmentUsecaseError::LocationNotFound,
+        StockUsecaseError::DatabaseError(err) => ShipmentUsecaseError::DatabaseError(err),
+        err => ShipmentUsecaseError::Posting(err),
+    }
+}
+
+// a concurrent unit of work can still claim the number or delete a location
+// between the check and the write, the constraints catch that
+fn map_shipment_write_error(err: sqlx::Error) -> ShipmentUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(SHIPMENT_NUMBER_CONSTRAINT) => ShipmentUsecaseError::NumberAlreadyExist,
+        Some(SHIPMENT_LOCATION_CONSTRAINT) => ShipmentUsecaseError::LocationNotFound,
+        _ => ShipmentUsecaseError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,565 @@
+use chrono::{Days, NaiveDate, TimeZone, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::sales::domain::availability::StockAvailabilityFilter;
+use crate::sales::domain::sales_order::{SalesOrderLine, SalesOrderStatus};
+use crate::sales::domain::shipment::ShipmentStatus;
+use crate::sales::usecase::dto::{
+    ReservationInput, SalesOrderDetail, SalesOrderInput, SalesOrderLineInput, ShipmentInput,
+    ShipmentLineInput,
+};
+use crate::sales::usecase::sales_order_usecase::SalesOrderUsecase;
+use crate::sales::usecase::shipment_usecase::{ShipmentUsecase, ShipmentUsecaseError};
+use crate::stock::domain::lot::StockLot;
+use crate::stock::domain::movement::{MovementType, StockMovementFilter};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::{LotInput, MovementInput};
+use crate::stock::usecase::lot_usecase::LotUsecase;
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+fn location(code: &str) -> LocationInput {
+    LocationInput {
+        parent_id: None,
+        code: code.into(),
+        name: format!("Bin {code}"),
+        kind: LocationKind::Bin,
+    }
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+struct Fixture {
+    usecase: ShipmentUsecase<StockRepositoryMemory>,
+    orders: SalesOrderUsecase<StockRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    lots: LotUsecase<StockRepositoryMemory>,
+    acme: Company,
+    globex: Company,
+    bolt: Product,
+    nut: Product,
+    // lot tracked, nothing of it in stock
+    milk: Product,
+    // both in the north warehouse, stock is received into the first
+    bin: Location,
+    empty_bin: Location,
+    elsewhere: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    async fn confirmed(&self, number: &str, lines: &[(&Product, i64)]) -> SalesOrderDetail {
+        let input = SalesOrderInput {
+            customer_id: self.globex.id,
+            warehouse_id: self.bin.warehouse_id,
+            number: number.into(),
+            note: None,
+            lines: lines
+                .iter()
+                .map(|(product, quantity)| SalesOrderLineInput {
+                    product_id: product.id,
+                    unit: None,
+                    quantity: Decimal::from(*quantity),
+                })
+                .collect(),
+        };
+        let draft = self
+            .orders
+            .create_order(self.acme.id, input, self.user)
+            .await
+            .unwrap();
+        let reservation = ReservationInput {
+            reserved_until: None,
+        };
+        self.orders
+            .confirm_order(draft.order.id, reservation, self.user)
+            .await
+            .unwrap()
+    }
+
+    fn shipment(&self, number: &str, lines: &[(&SalesOrderLine, &Location, i64)]) -> ShipmentInput {
+        ShipmentInput {
+            number: number.into(),
+            note: None,
+            lines: lines
+                .iter()
+                .map(|(line, location, quantity)| ShipmentLineInput {
+                    line_id: line.id,
+                    location_id: location.id,
+                    lot_id: None,
+                    quantity: *quantity,
+                    serial_numbers: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    async fn receive(&self, product: &Product, quantity: i64) {
+        self.stock
+            .post_movement(
+                MovementInput {
+                    product_id: product.id,
+                    location_id: self.bin.id,
+                    movement_type: MovementType::Receipt,
+                    lot_id: None,
+                    quantity,
+                    serial_numbers: Vec::new(),
+                    reference: None,
+                    note: None,
+                },
+                self.user,
+            )
+            .await
+            .unwrap();
+    }
+
+    // a lot of milk expiring days from today with quantity received into the bin
+    async fn receive_lot(
+        &self,
+        number: &str,
+        days: i64,
+        bin: &Location,
+        quantity: i64,
+    ) -> StockLot {
+        let today = Utc::now().date_naive();
+        let expires_on = if days < 0 {
+            today.checked_sub_days(Days::new(-days as u64))
+        } else {
+            today.checked_add_days(Days::new(days as u64))
+        };
+        let lot = self
+            .lots
+            .create_lot(LotInput {
+                product_id: self.milk.id,
+                lot_number: number.into(),
+                manufactured_on: None,
+                expires_on,
+            })
+            .await
+            .unwrap();
+        self.stock
+            .post_movement(
+                MovementInput {
+                    product_id: self.milk.id,
+                    location_id: bin.id,
+                    movement_type: MovementType::Receipt,
+                    lot_id: Some(lot.id),
+                    quantity,
+                    serial_numbers: Vec::new(),
+                    reference: None,
+                    note: None,
+                },
+                self.user,
+            )
+            .await
+            .unwrap();
+        lot
+    }
+
+    // (on hand, reserved) of the product in the north warehouse
+    async fn stock_of(&self, product: &Product) -> (i64, i64) {
+        let filter = StockAvailabilityFilter {
+            company_id: self.acme.id,
+            warehouse_id: self.bin.warehouse_id,
+            product_id: Some(product.id),
+        };
+        let rows = self.orders.get_availability(&filter).await.unwrap();
+        (rows[0].on_hand, rows[0].reserved)
+    }
+}
+
+// ACM ships bolts and nuts to GLB out of the north warehouse, where 10 bolts
+// and 10 nuts sit in one bin and another bin is empty
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let bolt = product(&acme, "BOLT");
+    let nut = product(&acme, "NUT");
+    let milk = Product {
+        is_lot_tracked: true,
+        ..product(&acme, "MILK")
+    };
+    let products = ProductRepositoryMemory::with_products(
+        companies.clone(),
+        vec![bolt.clone(), nut.clone(), milk.clone()],
+    );
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let warehouse_usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let mut bins = Vec::new();
+    for code in ["NORTH", "SOUTH"] {
+        let warehouse = warehouse_usecase
+            .create_warehouse(
+                acme.id,
+                WarehouseInput {
+                    code: code.into(),
+                    name: code.to_lowercase(),
+                    address: None,
+                },
+            )
+            .await
+            .unwrap();
+        for bin in ["A-01", "A-02"] {
+            let location = warehouse_usecase
+                .create_location(warehouse.id, location(bin))
+                .await
+                .unwrap();
+            bins.push(location);
+        }
+    }
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    let f = Fixture {
+        usecase: ShipmentUsecase::new(repo.clone(), AppMetrics::new()),
+        orders: SalesOrderUsecase::new(repo.clone(), AppMetrics::new()),
+        stock: StockUsecase::new(repo.clone(), AppMetrics::new()),
+        lots: LotUsecase::new(repo, AppMetrics::new()),
+        acme,
+        globex,
+        bolt,
+        nut,
+        milk,
+        bin: bins[0].clone(),
+        empty_bin: bins[1].clone(),
+        elsewhere: bins[2].clone(),
+        user: Uuid::new_v4(),
+    };
+    f.receive(&f.bolt, 10).await;
+    f.receive(&f.nut, 10).await;
+    f
+}
+
+#[tokio::test]
+async fn shipping_issues_reserved_stock_until_the_order_is_shipped() {
+    let f = setup().await;
+    let order = f.confirmed("SO-1", &[(&f.bolt, 6), (&f.nut, 4)]).await;
+    let (bolts, nuts) = (&order.lines[0], &order.lines[1]);
+
+    let first = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-1", &[(bolts, &f.bin, 6), (nuts, &f.bin, 1)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.shipment.status, ShipmentStatus::Picking);
+    let picked = f.orders.get_order(order.order.id).await.unwrap();
+    assert_eq!(picked.lines[0].picked_quantity, 6);
+    assert_eq!(picked.lines[0].pickable_quantity(), 0);
+    // picking leaves the ledger alone
+    assert_eq!(f.stock_of(&f.bolt).await, (10, 6));
+
+    let result = f.usecase.ship_shipment(first.shipment.id, f.user).await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::InvalidStatus(ShipmentStatus::Picking))
+    ));
+    let packed = f.usecase.pack_shipment(first.shipment.id).await.unwrap();
+    assert_eq!(packed.shipment.status, ShipmentStatus::Packed);
+    assert!(packed.shipment.packed_at.is_some());
+
+    let shipped = f
+        .usecase
+        .ship_shipment(first.shipment.id, f.user)
+        .await
+        .unwrap();
+    assert_eq!(shipped.shipment.status, ShipmentStatus::Shipped);
+    assert_eq!(shipped.shipment.shipped_by, Some(f.user));
+    assert!(shipped.lines.iter().all(|line| line.movement_id.is_some()));
+    // shipping takes the stock off hand and out of the reservation
+    assert_eq!(f.stock_of(&f.bolt).await, (4, 0));
+    assert_eq!(f.stock_of(&f.nut).await, (9, 3));
+
+    let partial = f.orders.get_order(order.order.id).await.unwrap();
+    assert_eq!(partial.order.status, SalesOrderStatus::PartiallyShipped);
+    assert_eq!(partial.lines[0].shipped_quantity, 6);
+    assert_eq!(partial.lines[0].reserved_quantity, 0);
+    assert_eq!(partial.lines[1].reserved_quantity, 3);
+
+    let filter = StockMovementFilter {
+        company_id: f.acme.id,
+        product_id: Some(f.bolt.id),
+        location_id: None,
+        movement_type: Some(MovementType::Issue),
+        lot_id: None,
+    };
+    let movements = f.stock.list_movements(&filter, &query()).await.unwrap();
+    assert_eq!(movements.total_data, 1);
+    assert_eq!(movements.data[0].quantity, -6);
+    assert_eq!(movements.data[0].reference.as_deref(), Some("SH-1"));
+
+    let second = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-2", &[(nuts, &f.bin, 3)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    f.usecase.pack_shipment(second.shipment.id).await.unwrap();
+    f.usecase
+        .ship_shipment(second.shipment.id, f.user)
+        .await
+        .unwrap();
+    let done = f.orders.get_order(order.order.id).await.unwrap();
+    assert_eq!(done.order.status, SalesOrderStatus::Shipped);
+    assert!(done.order.shipped_at.is_some());
+    assert_eq!(f.stock_of(&f.nut).await, (6, 0));
+
+    let shipments = f.usecase.list_shipments(order.order.id).await.unwrap();
+    let numbers: Vec<&str> = shipments.iter().map(|s| s.number.as_str()).collect();
+    assert_eq!(numbers, ["SH-1", "SH-2"]);
+}
+
+#[tokio::test]
+async fn cancelled_shipments_return_their_picks_to_the_reservation() {
+    let f = setup().await;
+    let order = f.confirmed("SO-1", &[(&f.bolt, 5)]).await;
+    let line = &order.lines[0];
+
+    let shipment = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-1", &[(line, &f.bin, 5)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    let cancelled = f
+        .usecase
+        .cancel_shipment(shipment.shipment.id)
+        .await
+        .unwrap();
+    assert_eq!(cancelled.shipment.status, ShipmentStatus::Cancelled);
+    let result = f.usecase.pack_shipment(shipment.shipment.id).await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::InvalidStatus(
+            ShipmentStatus::Cancelled
+        ))
+    ));
+
+    let reserved = f.orders.get_order(order.order.id).await.unwrap();
+    assert_eq!(reserved.lines[0].reserved_quantity, 5);
+    assert_eq!(reserved.lines[0].pickable_quantity(), 5);
+    assert_eq!(f.stock_of(&f.bolt).await, (10, 5));
+
+    // cancelling the order cancels its open shipments and releases everything
+    let open = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-2", &[(line, &f.bin, 2)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    f.orders.cancel_order(order.order.id).await.unwrap();
+    let open = f.usecase.get_shipment(open.shipment.id).await.unwrap();
+    assert_eq!(open.shipment.status, ShipmentStatus::Cancelled);
+    assert_eq!(f.stock_of(&f.bolt).await, (10, 0));
+
+    let result = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-3", &[(line, &f.bin, 1)]),
+            f.user,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::InvalidOrderStatus(
+            SalesOrderStatus::Cancelled
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn shipment_rejects_invalid_picks() {
+    let f = setup().await;
+    // 12 bolts ordered, 10 on hand so 2 are backordered
+    let order = f.confirmed("SO-1", &[(&f.bolt, 12)]).await;
+    let line = &order.lines[0];
+    assert_eq!(line.backordered_quantity(), 2);
+
+    let mut unknown_line = f.shipment("SH-1", &[(line, &f.bin, 1)]);
+    unknown_line.lines[0].line_id = Uuid::new_v4();
+    let cases = [
+        (f.shipment("SH-1", &[]), "shipment has no lines"),
+        (
+            f.shipment("SH-1", &[(line, &f.bin, 0)]),
+            "shipment quantity is invalid",
+        ),
+        (unknown_line, "sales order line not found"),
+        (
+            f.shipment("SH-1", &[(line, &f.elsewhere, 1)]),
+            "shipment location not found",
+        ),
+        // the backorder can not be picked, also not spread over two bins
+        (
+            f.shipment("SH-1", &[(line, &f.bin, 6), (line, &f.empty_bin, 5)]),
+            &format!("line {} can pick at most 10", line.id),
+        ),
+    ];
+    for (input, message) in cases {
+        let Err(err) = f
+            .usecase
+            .create_shipment(order.order.id, input, f.user)
+            .await
+        else {
+            panic!("{message} was accepted");
+        };
+        assert_eq!(err.to_string(), message);
+    }
+    // nothing of the refused picks stays behind
+    let unpicked = f.orders.get_order(order.order.id).await.unwrap();
+    assert_eq!(unpicked.lines[0].picked_quantity, 0);
+
+    // the warehouse has the stock reserved, the bin it is picked from does not
+    let shipment = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-1", &[(line, &f.bin, 6), (line, &f.empty_bin, 4)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    f.usecase.pack_shipment(shipment.shipment.id).await.unwrap();
+    let result = f.usecase.ship_shipment(shipment.shipment.id, f.user).await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::InsufficientStock { available: 0, .. })
+    ));
+    assert_eq!(f.stock_of(&f.bolt).await, (10, 10));
+
+    let result = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-1", &[(line, &f.bin, 0)]),
+            f.user,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::NumberAlreadyExist)
+    ));
+    let result = f.usecase.get_shipment(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(ShipmentUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn picks_are_suggested_first_expired_first_out() {
+    let f = setup().await;
+    let expired = f.receive_lot("L-OLD", -1, &f.bin, 5).await;
+    let soon = f.receive_lot("L-SOON", 5, &f.empty_bin, 3).await;
+    let later = f.receive_lot("L-LATER", 30, &f.bin, 4).await;
+    let order = f.confirmed("SO-1", &[(&f.milk, 6)]).await;
+    let line = &order.lines[0];
+
+    let picks = f.usecase.suggest_picks(order.order.id).await.unwrap();
+    let suggested: Vec<(Option<Uuid>, Uuid, i64)> = picks
+        .iter()
+        .map(|pick| (pick.lot_id, pick.location_id, pick.quantity))
+        .collect();
+    // the expired lot is left out even though it sits in the bin
+    assert_eq!(
+        suggested,
+        [
+            (Some(soon.id), f.empty_bin.id, 3),
+            (Some(later.id), f.bin.id, 3)
+        ]
+    );
+    let expires_on: Vec<Option<NaiveDate>> = picks.iter().map(|pick| pick.expires_on).collect();
+    assert_eq!(expires_on, [soon.expires_on, later.expires_on]);
+
+    let result = f
+        .usecase
+        .create_shipment(
+            order.order.id,
+            f.shipment("SH-1", &[(line, &f.bin, 1)]),
+            f.user,
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::Posting(
+            StockUsecaseError::LotRequired
+        ))
+    ));
+    let mut input = f.shipment("SH-1", &[(line, &f.bin, 1)]);
+    input.lines[0].lot_id = Some(expired.id);
+    let result = f
+        .usecase
+        .create_shipment(order.order.id, input, f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(ShipmentUsecaseError::Posting(StockUsecaseError::LotExpired))
+    ));
+
+    // what an open shipment picked is no longer suggested
+    let mut input = f.shipment("SH-1", &[(line, &f.empty_bin, 2)]);
+    input.lines[0].lot_id = Some(soon.id);
+    let shipment = f
+        .usecase
+        .create_shipment(order.order.id, input, f.user)
+        .await
+        .unwrap();
+    assert_eq!(shipment.lines[0].lot_id, Some(soon.id));
+    let picks = f.usecase.suggest_picks(order.order.id).await.unwrap();
+    let suggested: Vec<(Option<Uuid>, i64)> = picks
+        .iter()
+        .map(|pick| (pick.lot_id, pick.quantity))
+        .collect();
+    assert_eq!(suggested, [(Some(soon.id), 1), (Some(later.id), 3)]);
+
+    f.usecase.pack_shipment(shipment.shipment.id).await.unwrap();
+    f.usecase
+        .ship_shipment(shipment.shipment.id, f.user)
+        .await
+        .unwrap();
+    let lot = f.lots.get_lot(soon.id).await.unwrap();
+    assert_eq!(lot.on_hand, 1);
+}
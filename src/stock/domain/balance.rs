@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// on-hand quantity of a product at a location, the sum of its ledger
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockBalance {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub company_id: Uuid,
+    pub quantity: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+// narrows a balance listing, balances are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockBalanceFilter {
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    pub warehouse_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+}
+
+// a product and location whose stored balance differs from its ledger, either
+// side is 0 when it has no row
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct BalanceMismatch {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub balance: i64,
+    pub ledger: i64,
+}
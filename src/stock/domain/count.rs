@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// a stock take of a warehouse, or of one location and everything below it.
+// the expected quantities are snapshot when it is opened and movements on the
+// counted locations are refused until it is completed or cancelled
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockCount {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub number: String,
+    pub warehouse_id: Uuid,
+    /// None counts the whole warehouse
+    pub location_id: Option<Uuid>,
+    pub status: CountStatus,
+    /// counters do not see the expected quantities while it is open
+    pub is_blind: bool,
+    /// a line whose variance is larger than this, either way, needs an
+    /// approval before it is posted. None never needs one
+    pub approval_threshold: Option<i64>,
+    pub note: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+impl StockCount {
+    pub fn hides_expected(&self) -> bool {
+        self.is_blind && self.status == CountStatus::Open
+    }
+
+    // a line whose counters disagree always waits for an approval, whatever
+    // the threshold
+    pub fn needs_approval(&self, lines: &[StockCountLine], entries: &[StockCountEntry]) -> bool {
+        if !disputed_lines(entries).is_empty() {
+            return true;
+        }
+        let Some(threshold) = self.approval_threshold else {
+            return false;
+        };
+        lines
+            .iter()
+            .filter_map(StockCountLine::variance)
+            .any(|variance| variance.unsigned_abs() > threshold.unsigned_abs())
+    }
+}
+
+// lines where the latest entries of two counters differ. a counter recounting
+// replaces their own figure, so a dispute is settled once the counters agree.
+// entries are given in the order they were counted
+pub fn disputed_lines(entries: &[StockCountEntry]) -> HashSet<Uuid> {
+    let mut latest: HashMap<(Uuid, Uuid), i64> = HashMap::new();
+    for entry in entries {
+        latest.insert((entry.line_id, entry.counted_by), entry.quantity);
+    }
+
+    let mut found: HashMap<Uuid, i64> = HashMap::new();
+    let mut disputed = HashSet::new();
+    for ((line_id, _), quantity) in latest {
+        if *found.entry(line_id).or_insert(quantity) != quantity {
+            disputed.insert(line_id);
+        }
+    }
+    disputed
+}
+
+// stored as text in stock_counts.status. open -> completed, or open ->
+// pending_approval -> completed, cancelled from anything not yet completed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum CountStatus {
+    Open,
+    PendingApproval,
+    Completed,
+    Cancelled,
+}
+
+impl CountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CountStatus::Open => "open",
+            CountStatus::PendingApproval => "pending_approval",
+            CountStatus::Completed => "completed",
+            CountStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // the counted locations are frozen
+    pub fn is_active(&self) -> bool {
+        matches!(self, CountStatus::Open | CountStatus::PendingApproval)
+    }
+}
+
+// one product, or one lot of a lot tracked product, at a counted location
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockCountLine {
+    pub id: Uuid,
+    pub count_id: Uuid,
+    /// order of the lines in the count, from 1
+    pub position: i32,
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    /// on hand when the count was opened, 0 for stock the count found. taken
+    /// again under the balance lock when the count is completed or approved
+    pub expected_quantity: i64,
+    /// the latest entry, None until the line is counted
+    pub counted_quantity: Option<i64>,
+    pub counted_by: Option<Uuid>,
+    pub counted_at: Option<DateTime<Utc>>,
+    /// the adjustment posted for the variance when the count was completed
+    pub movement_id: Option<Uuid>,
+}
+
+impl StockCountLine {
+    // counted minus expected, None until the line is counted
+    pub fn variance(&self) -> Option<i64> {
+        self.counted_quantity
+            .map(|counted| counted - self.expected_quantity)
+    }
+}
+
+// what one counter found for a line, every entry is kept
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockCountEntry {
+    pub id: Uuid,
+    pub count_id: Uuid,
+    pub line_id: Uuid,
+    pub quantity: i64,
+    pub counted_by: Uuid,
+    pub counted_at: DateTime<Utc>,
+}
+
+// narrows a count listing on top of search, counts are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockCountFilter {
+    pub company_id: Uuid,
+    pub warehouse_id: Option<Uuid>,
+    pub status: Option<CountStatus>,
+}
+
+// on-hand quantity of a product, or of a lot for lot tracked products, at a
+// location when a count is opened
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct ExpectedQuantity {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// a batch of a lot tracked product, lot numbers are unique per product
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockLot {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub product_id: Uuid,
+    pub lot_number: String,
+    pub manufactured_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StockLot {
+    // a lot is usable up to and including its expiry date
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires_on.is_some_and(|expires_on| expires_on < today)
+    }
+}
+
+// on-hand quantity of a lot at a location, the lot balances of a product and
+// location add up to its stock balance
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockLotBalance {
+    pub lot_id: Uuid,
+    pub location_id: Uuid,
+    pub product_id: Uuid,
+    pub company_id: Uuid,
+    pub quantity: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+// narrows a lot listing, lots are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockLotFilter {
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+}
+
+// a lot with stock left that expires soon, on_hand sums its lot balances
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct ExpiringLot {
+    pub lot_id: Uuid,
+    pub product_id: Uuid,
+    pub lot_number: String,
+    pub expires_on: NaiveDate,
+    pub on_hand: i64,
+}
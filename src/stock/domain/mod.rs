@@ -0,0 +1,7 @@
+pub mod balance;
+pub mod count;
+pub mod lot;
+pub mod movement;
+pub mod serial;
+pub mod settings;
+pub mod transfer;
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// one posted line of the stock ledger, never changed once written. quantity is
+// signed, positive adds to the balance of the product at the location
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub movement_type: MovementType,
+    pub quantity: i64,
+    pub balance_after: i64,
+    pub reversal_of: Option<Uuid>,
+    // the document that posted the movement, source_id is its id and is None
+    // for movements posted by hand
+    pub source: MovementSource,
+    pub source_id: Option<Uuid>,
+    // set exactly for products that are lot tracked
+    pub lot_id: Option<Uuid>,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    pub posted_by: Uuid,
+    pub posted_at: DateTime<Utc>,
+}
+
+// stored as text in stock_movements.movement_type. a reversal undoes exactly one
+// earlier movement, see reversal_of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum MovementType {
+    Receipt,
+    Issue,
+    Adjustment,
+    TransferIn,
+    TransferOut,
+    Reversal,
+}
+
+impl MovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementType::Receipt => "receipt",
+            MovementType::Issue => "issue",
+            MovementType::Adjustment => "adjustment",
+            MovementType::TransferIn => "transfer_in",
+            MovementType::TransferOut => "transfer_out",
+            MovementType::Reversal => "reversal",
+        }
+    }
+}
+
+// stored as text in stock_movements.source. movements of a document are only
+// corrected through the document, a reversal by hand would leave it claiming
+// stock that is no longer there
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum MovementSource {
+    Manual,
+    Transfer,
+    GoodsReceipt,
+    Shipment,
+    Count,
+}
+
+impl MovementSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementSource::Manual => "manual",
+            MovementSource::Transfer => "transfer",
+            MovementSource::GoodsReceipt => "goods_receipt",
+            MovementSource::Shipment => "shipment",
+            MovementSource::Count => "count",
+        }
+    }
+}
+
+// narrows a ledger listing, movements are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockMovementFilter {
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub movement_type: Option<MovementType>,
+    pub lot_id: Option<Uuid>,
+}
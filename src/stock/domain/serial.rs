@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::stock::domain::movement::MovementType;
+
+// one unit of a serialised product, serial numbers are unique per product. the
+// serial is at a location exactly while it is in stock
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockSerial {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub product_id: Uuid,
+    pub serial_number: String,
+    pub status: SerialStatus,
+    pub location_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// stored as text in stock_serials.status. a receipt puts the serial in stock,
+// a transfer out sends it in transit and any other movement taking stock away
+// issues it. an issued serial can come back in stock, e.g. as a return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum SerialStatus {
+    InStock,
+    InTransit,
+    Issued,
+}
+
+impl SerialStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SerialStatus::InStock => "in_stock",
+            SerialStatus::InTransit => "in_transit",
+            SerialStatus::Issued => "issued",
+        }
+    }
+}
+
+// narrows a serial listing on top of search, serials are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockSerialFilter {
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub status: Option<SerialStatus>,
+}
+
+// a movement that moved the serial, oldest first. the supplier is set for goods
+// receipts and the customer for shipments
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct SerialEvent {
+    pub movement_id: Uuid,
+    pub movement_type: MovementType,
+    pub location_id: Uuid,
+    /// 1 when the serial came into the location, -1 when it left
+    pub quantity: i64,
+    pub reference: Option<String>,
+    pub supplier_id: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub posted_by: Uuid,
+    pub posted_at: DateTime<Utc>,
+}
+
+// a product and location whose balance differs from its count of serials in
+// stock, either side is 0 when it has none
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct SerialMismatch {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub balance: i64,
+    pub in_stock: i64,
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// stock rules of a company, companies without a row get the defaults
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockSettings {
+    pub company_id: Uuid,
+    // issues may take a balance below zero
+    pub allow_negative_stock: bool,
+    pub updated_at: DateTime<Utc>,
+}
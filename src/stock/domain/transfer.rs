@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// moves stock from one warehouse of a company to another. the lines are edited
+// while it is a draft, dispatching and receiving post to the ledger
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct StockTransfer {
+    pub id: Uuid,
+    pub company_id: Uuid,
+    pub number: String,
+    pub source_warehouse_id: Uuid,
+    pub destination_warehouse_id: Uuid,
+    pub status: TransferStatus,
+    pub note: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+    pub received_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+// stored as text in stock_transfers.status. draft -> dispatched ->
+// partially_received -> received, cancelled from anything not yet received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum TransferStatus {
+    Draft,
+    Dispatched,
+    PartiallyReceived,
+    Received,
+    Cancelled,
+}
+
+impl TransferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferStatus::Draft => "draft",
+            TransferStatus::Dispatched => "dispatched",
+            TransferStatus::PartiallyReceived => "partially_received",
+            TransferStatus::Received => "received",
+            TransferStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // dispatched and not fully received, the open lines are in transit
+    pub fn is_in_transit(&self) -> bool {
+        matches!(
+            self,
+            TransferStatus::Dispatched | TransferStatus::PartiallyReceived
+        )
+    }
+}
+
+// one product of a transfer, taken from a location of the source warehouse
+// and put into a location of the destination warehouse
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct TransferLine {
+    pub id: Uuid,
+    pub transfer_id: Uuid,
+    /// order of the lines in the transfer, from 1
+    pub position: i32,
+    pub product_id: Uuid,
+    pub source_location_id: Uuid,
+    pub destination_location_id: Uuid,
+    /// the lot moved, set for lot tracked products
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    pub received_quantity: i64,
+    /// the serials moved, set for serialised products. they arrive in this order
+    pub serial_numbers: Vec<String>,
+}
+
+impl TransferLine {
+    // dispatched but not received yet
+    pub fn open_quantity(&self) -> i64 {
+        self.quantity - self.received_quantity
+    }
+}
+
+// narrows a transfer listing on top of search, transfers are always listed per company
+#[derive(Debug, Clone)]
+pub struct StockTransferFilter {
+    pub company_id: Uuid,
+    pub status: Option<TransferStatus>,
+}
+
+// quantity of a product on its way between two warehouses, summed over the
+// open lines of dispatched transfers
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct InTransitQuantity {
+    pub product_id: Uuid,
+    pub source_warehouse_id: Uuid,
+    pub destination_warehouse_id: Uuid,
+    pub quantity: i64,
+}
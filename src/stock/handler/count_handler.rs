@@ -0,0 +1,271 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::stock::domain::count::StockCount;
+use crate::stock::handler::map_count_error::{map_usecase_count_error, validate_count_number};
+use crate::stock::handler::map_stock_error::posted_by;
+use crate::stock::handler::types::{
+    CountResponse, PostCountRequest, RecordCountRequest, StockCountFilterRequest,
+};
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::helper_query::STOCK_COUNT_SORT_COLUMNS;
+use crate::stock::usecase::count_usecase::CountUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/stock/counts",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    request_body = PostCountRequest,
+    responses(
+        (status = 201, description = "count opened with the expected quantities, movements on its locations are refused until it is completed or cancelled", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid input, number taken, a warehouse or location that is unknown or of another company, or locations another count already takes stock of", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostCountRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_count_number(&req.number)?;
+    let created_by = posted_by(&claims)?;
+
+    let count = usecase
+        .create_count(req.company_id, req.into(), created_by)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(CountResponse::from(count)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/counts",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockCountFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's counts without their lines, search matches the number", body = ResponseSuccessBody<Vec<StockCount>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_counts_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockCountFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_COUNT_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let count_list_data = usecase
+        .list_counts(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        count_list_data.total_data as u64,
+        Some(count_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/counts/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "count id")),
+    responses(
+        (status = 200, description = "the count with its lines and entries, expected quantities are left out while a blind count is open", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "count not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let count = usecase
+        .get_count(id)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(CountResponse::from(count)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/counts/{id}/entries",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "count id")),
+    request_body = RecordCountRequest,
+    responses(
+        (status = 200, description = "quantities recorded for the caller, each line takes its latest entry and is disputed while counters disagree", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid id or input, the count is not open, or a product or location the count does not take stock of", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "count not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn record_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<RecordCountRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let counted_by = posted_by(&claims)?;
+    let entries = req.entries.into_iter().map(Into::into).collect();
+
+    let count = usecase
+        .record_entries(id, entries, counted_by)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(CountResponse::from(count)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/counts/{id}/complete",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "count id")),
+    responses(
+        (status = 200, description = "variances posted as adjustments, or the count waits for an approval when one is over the threshold or counters disagree on a line", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid id, the count is not open or a line is not counted", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "count not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn complete_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let posted_by = posted_by(&claims)?;
+
+    let count = usecase
+        .complete_count(id, posted_by)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(CountResponse::from(count)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/counts/{id}/approve",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "count id")),
+    responses(
+        (status = 200, description = "variances posted as adjustments, the count is completed", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid id, the count is not pending approval, or the caller counted some of it", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "count not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn approve_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let approved_by = posted_by(&claims)?;
+
+    let count = usecase
+        .approve_count(id, approved_by)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(CountResponse::from(count)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/counts/{id}/cancel",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "count id")),
+    responses(
+        (status = 200, description = "count cancelled without posting, its locations are released", body = ResponseSuccessBody<CountResponse>),
+        (status = 400, description = "invalid id, or the count is already completed or cancelled", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "count not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn cancel_count_handler<R: CountRepository>(
+    State(usecase): State<Arc<CountUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let count = usecase
+        .cancel_count(id)
+        .await
+        .map_err(map_usecase_count_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(CountResponse::from(count)),
+    ))
+}
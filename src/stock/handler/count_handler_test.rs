@@ -0,0 +1,314 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_middleware::jwt_token::jwt::generate_token;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body, test_config};
+use crate::company::domain::company::Company;
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    product_id: String,
+    warehouse_id: String,
+    location_id: String,
+}
+
+impl Fixture {
+    fn receipt(&self, quantity: i64) -> Value {
+        json!({
+            "product_id": self.product_id,
+            "location_id": self.location_id,
+            "movement_type": "receipt",
+            "quantity": quantity,
+        })
+    }
+
+    async fn on_hand(&self) -> Value {
+        let response = self
+            .app
+            .request(
+                Method::GET,
+                &format!(
+                    "/stock/balances?company_id={}&location_id={}",
+                    self.acme.id, self.location_id
+                ),
+                Some(&self.token),
+                None,
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+        response.json()["data"][0]["quantity"].clone()
+    }
+}
+
+// ACM has 10 bolts in the only bin of its north warehouse
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "NORTH", "name": "North" }),
+        )
+        .await;
+    let warehouse_id = warehouse["id"].as_str().unwrap().to_string();
+    let location = app
+        .create(
+            &token,
+            &format!("/warehouse/{warehouse_id}/locations"),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+    let f = Fixture {
+        app,
+        token,
+        acme,
+        product_id: product["id"].as_str().unwrap().to_string(),
+        warehouse_id,
+        location_id: location["id"].as_str().unwrap().to_string(),
+    };
+    f.app
+        .create(&f.token, "/stock/movements", f.receipt(10))
+        .await;
+    f
+}
+
+#[tokio::test]
+async fn count_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/stock/counts?company_id={id}")),
+        (Method::POST, "/stock/counts".to_string()),
+        (Method::GET, format!("/stock/counts/{id}")),
+        (Method::POST, format!("/stock/counts/{id}/entries")),
+        (Method::POST, format!("/stock/counts/{id}/complete")),
+        (Method::POST, format!("/stock/counts/{id}/approve")),
+        (Method::POST, format!("/stock/counts/{id}/cancel")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn blind_count_hides_expected_and_posts_after_approval() {
+    let f = setup().await;
+
+    let count = f
+        .app
+        .create(
+            &f.token,
+            "/stock/counts",
+            json!({
+                "company_id": f.acme.id,
+                "number": "CC-1",
+                "warehouse_id": f.warehouse_id,
+                "is_blind": true,
+                "approval_threshold": 2,
+            }),
+        )
+        .await;
+    assert_eq!(count["status"], "open");
+    assert_eq!(count["created_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(count["lines"][0]["product_id"], f.product_id.as_str());
+    assert_eq!(count["lines"][0]["expected_quantity"], Value::Null);
+    let uri = format!("/stock/counts/{}", count["id"].as_str().unwrap());
+
+    let (status, body) = f
+        .app
+        .post(&f.token, "/stock/movements", Some(f.receipt(1)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            "location is being counted, movements wait until the count is completed"
+        )
+    );
+
+    let entries = json!({ "entries": [{
+        "product_id": f.product_id,
+        "location_id": f.location_id,
+        "quantity": 7,
+    }] });
+    let (status, body) = f
+        .app
+        .post(&f.token, &format!("{uri}/entries"), Some(entries))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let line = &body["data"]["lines"][0];
+    assert_eq!(line["counted_quantity"], 7);
+    assert_eq!(line["variance"], Value::Null);
+    assert_eq!(line["is_disputed"], false);
+    assert_eq!(
+        body["data"]["entries"][0]["counted_by"],
+        TEST_ADMIN_ID.to_string()
+    );
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/approve"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "count is open"));
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/complete"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "pending_approval");
+    assert_eq!(body["data"]["lines"][0]["expected_quantity"], 10);
+    assert_eq!(body["data"]["lines"][0]["variance"], -3);
+    assert_eq!(f.on_hand().await, 10);
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/approve"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "a counter of the count can not approve it")
+    );
+
+    let approver = Uuid::new_v4();
+    let token = generate_token(&test_config().jwt, &approver.to_string()).unwrap();
+    let (status, body) = f.app.post(&token, &format!("{uri}/approve"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "completed");
+    assert_eq!(body["data"]["approved_by"], approver.to_string());
+    let movement_id = body["data"]["lines"][0]["movement_id"].as_str().unwrap();
+    assert_eq!(f.on_hand().await, 7);
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/stock/movements/{movement_id}"),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let movement = response.json();
+    assert_eq!(movement["data"]["movement_type"], "adjustment");
+    assert_eq!(movement["data"]["reference"], "CC-1");
+    assert_eq!(movement["data"]["source"], "count");
+    assert_eq!(movement["data"]["source_id"], count["id"]);
+
+    let (status, body) = f
+        .app
+        .post(
+            &f.token,
+            &format!("/stock/movements/{movement_id}/reversal"),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            "movement was posted by a count, correct it through the document"
+        )
+    );
+    f.app
+        .create(&f.token, "/stock/movements", f.receipt(1))
+        .await;
+}
+
+#[tokio::test]
+async fn count_refuses_invalid_input_and_overlaps() {
+    let f = setup().await;
+    let count = |number: &str| {
+        json!({
+            "company_id": f.acme.id,
+            "number": number,
+            "warehouse_id": f.warehouse_id,
+            "location_id": f.location_id,
+        })
+    };
+
+    let cases = [
+        (count(" "), error_body(400, "Number is required")),
+        (
+            json!({
+                "company_id": f.acme.id,
+                "number": "CC-1",
+                "warehouse_id": f.warehouse_id,
+                "approval_threshold": -1,
+            }),
+            error_body(400, "approval threshold can not be negative"),
+        ),
+    ];
+    for (body, expected) in cases {
+        let (status, body) = f.app.post(&f.token, "/stock/counts", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, expected);
+    }
+
+    let open = f.app.create(&f.token, "/stock/counts", count("CC-1")).await;
+    let (status, body) = f
+        .app
+        .post(&f.token, "/stock/counts", Some(count("CC-2")))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            "another open count already takes stock of these locations"
+        )
+    );
+
+    let uri = format!("/stock/counts/{}", open["id"].as_str().unwrap());
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/complete"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "every line must be counted first"));
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/cancel"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "cancelled");
+    f.app.create(&f.token, "/stock/counts", count("CC-2")).await;
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!(
+                "/stock/counts?company_id={}&status=open&per_page=10",
+                f.acme.id
+            ),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["meta"]["total_data"], 1);
+    assert_eq!(body["data"][0]["number"], "CC-2");
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/stock/counts/{}", Uuid::new_v4()),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "data not found"));
+}
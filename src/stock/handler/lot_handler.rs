@@ -0,0 +1,155 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::stock::domain::lot::{ExpiringLot, StockLot};
+use crate::stock::handler::map_lot_error::{map_usecase_lot_error, validate_lot_input};
+use crate::stock::handler::types::{
+    ExpiringLotFilterRequest, LotResponse, PostLotRequest, StockLotFilterRequest,
+};
+use crate::stock::repository::helper_query::STOCK_LOT_SORT_COLUMNS;
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::lot_usecase::LotUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/stock/lots",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    request_body = PostLotRequest,
+    responses(
+        (status = 201, description = "lot created, stock is added to it by the movements naming it", body = ResponseSuccessBody<StockLot>),
+        (status = 400, description = "invalid input, lot number taken, or a product that is unknown or not lot tracked", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_lot_handler<R: StockRepository>(
+    State(usecase): State<Arc<LotUsecase<R>>>,
+    Json(req): Json<PostLotRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_lot_input(&req)?;
+
+    let lot = usecase
+        .create_lot(req.into())
+        .await
+        .map_err(map_usecase_lot_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(lot)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/lots",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockLotFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's lots, search matches the lot number", body = ResponseSuccessBody<Vec<StockLot>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_lots_handler<R: StockRepository>(
+    State(usecase): State<Arc<LotUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockLotFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_LOT_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let lot_list_data = usecase
+        .list_lots(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_lot_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        lot_list_data.total_data as u64,
+        Some(lot_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/lots/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "lot id")),
+    responses(
+        (status = 200, description = "the lot with its on-hand quantity per location", body = ResponseSuccessBody<LotResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "lot not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_lot_handler<R: StockRepository>(
+    State(usecase): State<Arc<LotUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let lot = usecase.get_lot(id).await.map_err(map_usecase_lot_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(LotResponse::from(lot)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/expiring-lots",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(ExpiringLotFilterRequest),
+    responses(
+        (status = 200, description = "lots with stock left that expire within the given days, soonest first", body = ResponseSuccessBody<Vec<ExpiringLot>>),
+        (status = 400, description = "missing company or days out of range", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_expiring_lots_handler<R: StockRepository>(
+    State(usecase): State<Arc<LotUsecase<R>>>,
+    Query(q): Query<ExpiringLotFilterRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let lots = usecase
+        .list_expiring_lots(q.company_id, q.days, q.warehouse_id)
+        .await
+        .map_err(map_usecase_lot_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(lots)))
+}
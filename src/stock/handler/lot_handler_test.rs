@@ -0,0 +1,220 @@
+use axum::http::{Method, StatusCode};
+use chrono::{Days, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TestApp, error_body};
+use crate::company::domain::company::Company;
+
+// days from today as an ISO date
+fn day(days: u64) -> String {
+    let date = Utc::now().date_naive().checked_add_days(Days::new(days));
+    date.unwrap().to_string()
+}
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    milk_id: String,
+    bolt_id: String,
+    bin_id: String,
+}
+
+impl Fixture {
+    async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        let response = self
+            .app
+            .request(Method::GET, uri, Some(&self.token), None)
+            .await;
+        if response.status.is_success() {
+            (response.status, response.json())
+        } else {
+            (response.status, response.error_json())
+        }
+    }
+
+    async fn receive(&self, product_id: &str, lot_id: Option<&str>) -> (StatusCode, Value) {
+        let response = self
+            .app
+            .request(
+                Method::POST,
+                "/stock/movements",
+                Some(&self.token),
+                Some(json!({
+                    "product_id": product_id,
+                    "location_id": self.bin_id,
+                    "movement_type": "receipt",
+                    "lot_id": lot_id,
+                    "quantity": 6,
+                })),
+            )
+            .await;
+        if response.status.is_success() {
+            (response.status, response.json())
+        } else {
+            (response.status, response.error_json())
+        }
+    }
+}
+
+// ACM keeps lot tracked milk and untracked bolts in one bin
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+
+    let mut ids = Vec::new();
+    for (sku, is_lot_tracked) in [("MILK", true), ("BOLT", false)] {
+        let product = app
+            .create(
+                &token,
+                "/product",
+                json!({
+                    "company_id": acme.id,
+                    "sku": sku,
+                    "name": sku,
+                    "unit_of_measure": "pcs",
+                    "is_lot_tracked": is_lot_tracked,
+                }),
+            )
+            .await;
+        assert_eq!(product["is_lot_tracked"], is_lot_tracked);
+        ids.push(product["id"].as_str().unwrap().to_string());
+    }
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "NORTH", "name": "North" }),
+        )
+        .await;
+    let bin = app
+        .create(
+            &token,
+            &format!("/warehouse/{}/locations", warehouse["id"].as_str().unwrap()),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+
+    Fixture {
+        app,
+        token,
+        acme,
+        bolt_id: ids.pop().unwrap(),
+        milk_id: ids.pop().unwrap(),
+        bin_id: bin["id"].as_str().unwrap().to_string(),
+    }
+}
+
+#[tokio::test]
+async fn lot_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/stock/lots?company_id={id}")),
+        (Method::POST, "/stock/lots".to_string()),
+        (Method::GET, format!("/stock/lots/{id}")),
+        (
+            Method::GET,
+            format!("/stock/expiring-lots?company_id={id}&days=30"),
+        ),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn received_lot_shows_on_hand_and_expiry() {
+    let f = setup().await;
+    let lot = f
+        .app
+        .create(
+            &f.token,
+            "/stock/lots",
+            json!({
+                "product_id": f.milk_id,
+                "lot_number": "L-1",
+                "manufactured_on": day(0),
+                "expires_on": day(10),
+            }),
+        )
+        .await;
+    let lot_id = lot["id"].as_str().unwrap();
+    assert_eq!(lot["expires_on"], day(10));
+
+    let (status, movement) = f.receive(&f.milk_id, Some(lot_id)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(movement["data"]["lot_id"], lot_id);
+
+    let (status, detail) = f.get(&format!("/stock/lots/{lot_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(detail["data"]["on_hand"], 6);
+    assert_eq!(detail["data"]["balances"][0]["location_id"], f.bin_id);
+
+    let (_, listed) = f
+        .get(&format!(
+            "/stock/lots?company_id={}&product_id={}",
+            f.acme.id, f.milk_id
+        ))
+        .await;
+    assert_eq!(listed["data"].as_array().unwrap().len(), 1);
+
+    let uri = format!("/stock/expiring-lots?company_id={}", f.acme.id);
+    let (_, soon) = f.get(&format!("{uri}&days=30")).await;
+    assert_eq!(soon["data"][0]["lot_number"], "L-1");
+    assert_eq!(soon["data"][0]["on_hand"], 6);
+    let (_, none) = f.get(&format!("{uri}&days=5")).await;
+    assert_eq!(none["data"], json!([]));
+}
+
+#[tokio::test]
+async fn lot_rejects_invalid_input() {
+    let f = setup().await;
+    let cases = [
+        (
+            json!({ "product_id": f.milk_id, "lot_number": " " }),
+            "Lot number is required",
+        ),
+        (
+            json!({ "product_id": f.bolt_id, "lot_number": "L-1" }),
+            "product is not lot tracked",
+        ),
+        (
+            json!({
+                "product_id": f.milk_id,
+                "lot_number": "L-1",
+                "manufactured_on": day(10),
+                "expires_on": day(0),
+            }),
+            "expiry date is before the manufacture date",
+        ),
+    ];
+    for (body, message) in cases {
+        let response = f
+            .app
+            .request(Method::POST, "/stock/lots", Some(&f.token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.error_json(), error_body(400, message));
+    }
+
+    let (status, body) = f.receive(&f.milk_id, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "lot is required for a lot tracked product")
+    );
+
+    let (status, body) = f
+        .get(&format!(
+            "/stock/expiring-lots?company_id={}&days=-1",
+            f.acme.id
+        ))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "days is invalid"));
+}
@@ -0,0 +1,62 @@
+use crate::app_response::error::ResponseError;
+use crate::stock::handler::map_stock_error::map_usecase_stock_error;
+use crate::stock::usecase::count_usecase::CountUsecaseError;
+
+pub fn map_usecase_count_error(err: CountUsecaseError) -> ResponseError {
+    match err {
+        CountUsecaseError::CompanyNotFound => ResponseError::NotFound("company not found".into()),
+        CountUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        CountUsecaseError::NumberAlreadyExist => {
+            ResponseError::BadRequest("count number already exists".into())
+        }
+        CountUsecaseError::WarehouseNotFound => {
+            ResponseError::BadRequest("warehouse not found".into())
+        }
+        CountUsecaseError::CompanyMismatch => ResponseError::BadRequest(
+            "warehouse and products must belong to the count's company".into(),
+        ),
+        CountUsecaseError::ProductNotFound => ResponseError::BadRequest("product not found".into()),
+        CountUsecaseError::LocationNotFound => {
+            ResponseError::BadRequest("location not found in what the count takes stock of".into())
+        }
+        CountUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        CountUsecaseError::InvalidThreshold => {
+            ResponseError::BadRequest("approval threshold can not be negative".into())
+        }
+        CountUsecaseError::AlreadyCounted => ResponseError::BadRequest(
+            "another open count already takes stock of these locations".into(),
+        ),
+        CountUsecaseError::SerialisedProduct => {
+            ResponseError::BadRequest("serialised products are not counted".into())
+        }
+        CountUsecaseError::NoEntries => ResponseError::BadRequest("entries are required".into()),
+        CountUsecaseError::InvalidStatus(status) => {
+            ResponseError::BadRequest(format!("count is {}", status.as_str()))
+        }
+        CountUsecaseError::NotCounted => {
+            ResponseError::BadRequest("every line must be counted first".into())
+        }
+        CountUsecaseError::SelfApproval => {
+            ResponseError::BadRequest("a counter of the count can not approve it".into())
+        }
+        CountUsecaseError::Posting(err) => map_usecase_stock_error(err),
+        CountUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column size of stock_counts.number
+const MAX_NUMBER_LEN: usize = 30;
+
+pub fn validate_count_number(number: &str) -> Result<(), ResponseError> {
+    if number.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Number is required".into()));
+    }
+    if number.chars().count() > MAX_NUMBER_LEN {
+        return Err(ResponseError::BadRequest(format!(
+            "Number can not be longer than {MAX_NUMBER_LEN} characters"
+        )));
+    }
+    Ok(())
+}
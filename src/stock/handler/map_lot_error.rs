@@ -0,0 +1,37 @@
+use crate::app_response::error::ResponseError;
+use crate::stock::handler::types::PostLotRequest;
+use crate::stock::usecase::lot_usecase::LotUsecaseError;
+
+pub fn map_usecase_lot_error(err: LotUsecaseError) -> ResponseError {
+    match &err {
+        LotUsecaseError::CompanyNotFound => ResponseError::NotFound("company not found".into()),
+        LotUsecaseError::ProductNotFound => ResponseError::BadRequest("product not found".into()),
+        LotUsecaseError::NotLotTracked => {
+            ResponseError::BadRequest("product is not lot tracked".into())
+        }
+        LotUsecaseError::LotNumberAlreadyExist => {
+            ResponseError::BadRequest("lot number already exists".into())
+        }
+        LotUsecaseError::InvalidDates => {
+            ResponseError::BadRequest("expiry date is before the manufacture date".into())
+        }
+        LotUsecaseError::InvalidDays => ResponseError::BadRequest("days is invalid".into()),
+        LotUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        LotUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column size of stock_lots.lot_number
+const MAX_LOT_NUMBER_LEN: usize = 50;
+
+pub fn validate_lot_input(req: &PostLotRequest) -> Result<(), ResponseError> {
+    if req.lot_number.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Lot number is required".into()));
+    }
+    if req.lot_number.chars().count() > MAX_LOT_NUMBER_LEN {
+        return Err(ResponseError::BadRequest(format!(
+            "Lot number can not be longer than {MAX_LOT_NUMBER_LEN} characters"
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use crate::app_response::error::ResponseError;
+use crate::stock::usecase::serial_usecase::SerialUsecaseError;
+
+pub fn map_usecase_serial_error(err: SerialUsecaseError) -> ResponseError {
+    match &err {
+        SerialUsecaseError::CompanyNotFound => ResponseError::NotFound("company not found".into()),
+        SerialUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        SerialUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
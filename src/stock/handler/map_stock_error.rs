@@ -0,0 +1,95 @@
+use uuid::Uuid;
+
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_response::error::ResponseError;
+use crate::stock::handler::types::PostMovementRequest;
+use crate::stock::usecase::stock_usecase::StockUsecaseError;
+
+pub fn map_usecase_stock_error(err: StockUsecaseError) -> ResponseError {
+    match &err {
+        StockUsecaseError::CompanyNotFound => ResponseError::NotFound("company not found".into()),
+        StockUsecaseError::ProductNotFound => ResponseError::BadRequest("product not found".into()),
+        StockUsecaseError::LocationNotFound => {
+            ResponseError::BadRequest("location not found".into())
+        }
+        StockUsecaseError::CompanyMismatch => {
+            ResponseError::BadRequest("product and location belong to different companies".into())
+        }
+        StockUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        StockUsecaseError::ReversalNotPostable => {
+            ResponseError::BadRequest("reversals are posted through the reversed movement".into())
+        }
+        StockUsecaseError::TransferNotPostable => ResponseError::BadRequest(
+            "transfer movements are posted by dispatching and receiving a transfer".into(),
+        ),
+        StockUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        StockUsecaseError::AlreadyReversed => {
+            ResponseError::BadRequest("movement already reversed".into())
+        }
+        StockUsecaseError::ReversalNotReversible => {
+            ResponseError::BadRequest("a reversal can not be reversed".into())
+        }
+        StockUsecaseError::DocumentMovement(source) => ResponseError::BadRequest(format!(
+            "movement was posted by a {}, correct it through the document",
+            source.as_str().replace('_', " ")
+        )),
+        StockUsecaseError::InsufficientStock { available } => {
+            ResponseError::BadRequest(format!("insufficient stock, {available} available"))
+        }
+        StockUsecaseError::LotRequired => {
+            ResponseError::BadRequest("lot is required for a lot tracked product".into())
+        }
+        StockUsecaseError::LotNotAllowed => {
+            ResponseError::BadRequest("product is not lot tracked".into())
+        }
+        StockUsecaseError::LotNotFound => {
+            ResponseError::BadRequest("lot not found for the product".into())
+        }
+        StockUsecaseError::LotExpired => ResponseError::BadRequest("lot is expired".into()),
+        StockUsecaseError::SerialsNotAllowed => {
+            ResponseError::BadRequest("product is not serialised".into())
+        }
+        StockUsecaseError::SerialCountMismatch { expected } => ResponseError::BadRequest(format!(
+            "{expected} serial numbers are required for a serialised product"
+        )),
+        StockUsecaseError::InvalidSerial => {
+            ResponseError::BadRequest("serial number is invalid".into())
+        }
+        StockUsecaseError::DuplicateSerial => {
+            ResponseError::BadRequest("serial number is given twice".into())
+        }
+        StockUsecaseError::SerialInStock(number) => {
+            ResponseError::BadRequest(format!("serial {number} is already in stock"))
+        }
+        StockUsecaseError::SerialNotInStock(number) => {
+            ResponseError::BadRequest(format!("serial {number} is not in stock at the location"))
+        }
+        StockUsecaseError::LocationCounted => ResponseError::BadRequest(
+            "location is being counted, movements wait until the count is completed".into(),
+        ),
+        StockUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column size of stock_movements.reference
+const MAX_REFERENCE_LEN: usize = 100;
+
+pub fn validate_movement_input(req: &PostMovementRequest) -> Result<(), ResponseError> {
+    if req
+        .reference
+        .as_ref()
+        .is_some_and(|r| r.chars().count() > MAX_REFERENCE_LEN)
+    {
+        return Err(ResponseError::BadRequest(format!(
+            "Reference can not be longer than {MAX_REFERENCE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+// every movement records who posted it
+pub fn posted_by(claims: &Claims) -> Result<Uuid, ResponseError> {
+    claims.user_id().ok_or(ResponseError::InvalidToken)
+}
@@ -0,0 +1,66 @@
+use crate::app_response::error::ResponseError;
+use crate::stock::handler::map_stock_error::map_usecase_stock_error;
+use crate::stock::usecase::transfer_usecase::TransferUsecaseError;
+
+pub fn map_usecase_transfer_error(err: TransferUsecaseError) -> ResponseError {
+    match err {
+        TransferUsecaseError::CompanyNotFound => {
+            ResponseError::NotFound("company not found".into())
+        }
+        TransferUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        TransferUsecaseError::NumberAlreadyExist => {
+            ResponseError::BadRequest("transfer number already exists".into())
+        }
+        TransferUsecaseError::WarehouseNotFound => {
+            ResponseError::BadRequest("warehouse not found".into())
+        }
+        TransferUsecaseError::SameWarehouse => {
+            ResponseError::BadRequest("source and destination must be different warehouses".into())
+        }
+        TransferUsecaseError::CompanyMismatch => ResponseError::BadRequest(
+            "warehouses and products must belong to the transfer's company".into(),
+        ),
+        TransferUsecaseError::ProductNotFound => {
+            ResponseError::BadRequest("product not found".into())
+        }
+        TransferUsecaseError::LocationNotFound => {
+            ResponseError::BadRequest("location not found in the line's warehouse".into())
+        }
+        TransferUsecaseError::InvalidQuantity => {
+            ResponseError::BadRequest("quantity is invalid".into())
+        }
+        TransferUsecaseError::NoLines => ResponseError::BadRequest("lines are required".into()),
+        TransferUsecaseError::InvalidStatus(status) => {
+            ResponseError::BadRequest(format!("transfer is {}", status.as_str()))
+        }
+        TransferUsecaseError::LineNotFound => {
+            ResponseError::BadRequest("transfer line not found".into())
+        }
+        TransferUsecaseError::ReceiptExceedsOpen => {
+            ResponseError::BadRequest("received quantity exceeds the quantity in transit".into())
+        }
+        TransferUsecaseError::InsufficientStock {
+            product_id,
+            available,
+        } => ResponseError::BadRequest(format!(
+            "insufficient stock of product {product_id}, {available} available"
+        )),
+        TransferUsecaseError::Posting(err) => map_usecase_stock_error(err),
+        TransferUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column size of stock_transfers.number
+const MAX_NUMBER_LEN: usize = 30;
+
+pub fn validate_transfer_number(number: &str) -> Result<(), ResponseError> {
+    if number.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Number is required".into()));
+    }
+    if number.chars().count() > MAX_NUMBER_LEN {
+        return Err(ResponseError::BadRequest(format!(
+            "Number can not be longer than {MAX_NUMBER_LEN} characters"
+        )));
+    }
+    Ok(())
+}
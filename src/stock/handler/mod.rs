@@ -0,0 +1,21 @@
+pub mod count_handler;
+#[cfg(test)]
+mod count_handler_test;
+pub mod lot_handler;
+#[cfg(test)]
+mod lot_handler_test;
+pub mod map_count_error;
+pub mod map_lot_error;
+pub mod map_serial_error;
+pub mod map_stock_error;
+pub mod map_transfer_error;
+pub mod serial_handler;
+#[cfg(test)]
+mod serial_handler_test;
+pub mod stock_handler;
+#[cfg(test)]
+mod stock_handler_test;
+pub mod transfer_handler;
+#[cfg(test)]
+mod transfer_handler_test;
+pub mod types;
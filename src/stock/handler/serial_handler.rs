@@ -0,0 +1,129 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::stock::domain::serial::{SerialMismatch, StockSerial};
+use crate::stock::handler::map_serial_error::map_usecase_serial_error;
+use crate::stock::handler::types::{SerialResponse, StockCompanyRequest, StockSerialFilterRequest};
+use crate::stock::repository::helper_query::STOCK_SERIAL_SORT_COLUMNS;
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::serial_usecase::SerialUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    get,
+    path = "/stock/serials",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockSerialFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's serials, search matches the serial number", body = ResponseSuccessBody<Vec<StockSerial>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_serials_handler<R: StockRepository>(
+    State(usecase): State<Arc<SerialUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockSerialFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_SERIAL_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let serial_list_data = usecase
+        .list_serials(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_serial_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        serial_list_data.total_data as u64,
+        Some(serial_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/serials/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "serial id")),
+    responses(
+        (status = 200, description = "the serial with every movement of it, receipts name the supplier and shipments the customer", body = ResponseSuccessBody<SerialResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "serial not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_serial_handler<R: StockRepository>(
+    State(usecase): State<Arc<SerialUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let serial = usecase
+        .get_serial(id)
+        .await
+        .map_err(map_usecase_serial_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(SerialResponse::from(serial)),
+    ))
+}
+
+// compares the balance of every serialised product with its serials in stock
+#[utoipa::path(
+    get,
+    path = "/stock/serial-reconciliation",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(StockCompanyRequest),
+    responses(
+        (status = 200, description = "balances that differ from their count of serials in stock, empty when all match", body = ResponseSuccessBody<Vec<SerialMismatch>>),
+        (status = 400, description = "missing company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_serial_reconciliation_handler<R: StockRepository>(
+    State(usecase): State<Arc<SerialUsecase<R>>>,
+    Query(q): Query<StockCompanyRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let mismatches = usecase
+        .reconcile(q.company_id)
+        .await
+        .map_err(map_usecase_serial_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(mismatches)))
+}
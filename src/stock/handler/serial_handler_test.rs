@@ -0,0 +1,353 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TestApp, error_body};
+use crate::company::domain::company::Company;
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    globex: Company,
+    supplier_id: String,
+    laptop_id: String,
+    north: (String, String),
+    south: (String, String),
+}
+
+impl Fixture {
+    async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        let response = self
+            .app
+            .request(Method::GET, uri, Some(&self.token), None)
+            .await;
+        if response.status.is_success() {
+            (response.status, response.json())
+        } else {
+            (response.status, response.error_json())
+        }
+    }
+
+    async fn post(&self, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+        self.app.post(&self.token, uri, body).await
+    }
+
+    // an approved purchase order of laptops, returned as (order uri, line id)
+    async fn purchase(&self, quantity: i64) -> (String, Value) {
+        let draft = self
+            .app
+            .create(
+                &self.token,
+                "/purchasing/orders",
+                json!({
+                    "company_id": self.acme.id,
+                    "supplier_id": self.supplier_id,
+                    "number": "PO-1",
+                    "lines": [{ "product_id": self.laptop_id, "quantity": quantity }],
+                }),
+            )
+            .await;
+        let uri = format!("/purchasing/orders/{}", draft["id"].as_str().unwrap());
+        let (status, _) = self.post(&format!("{uri}/approve"), None).await;
+        assert_eq!(status, StatusCode::OK);
+        (uri, draft["lines"][0]["id"].clone())
+    }
+}
+
+// ACM buys serialised laptops into its north warehouse and sells from south
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+
+    let supplier = app
+        .create(
+            &token,
+            &format!("/company/{}/suppliers", acme.id),
+            json!({ "code": "SUP", "name": "Supplier" }),
+        )
+        .await;
+    let laptop = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "LAPTOP",
+                "name": "Laptop",
+                "unit_of_measure": "pcs",
+                "is_serialised": true,
+            }),
+        )
+        .await;
+    assert_eq!(laptop["is_serialised"], true);
+
+    let mut sites = Vec::new();
+    for code in ["NORTH", "SOUTH"] {
+        let warehouse = app
+            .create(
+                &token,
+                &format!("/company/{}/warehouses", acme.id),
+                json!({ "code": code, "name": code }),
+            )
+            .await;
+        let warehouse_id = warehouse["id"].as_str().unwrap().to_string();
+        let bin = app
+            .create(
+                &token,
+                &format!("/warehouse/{warehouse_id}/locations"),
+                json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+            )
+            .await;
+        sites.push((warehouse_id, bin["id"].as_str().unwrap().to_string()));
+    }
+
+    Fixture {
+        app,
+        token,
+        acme,
+        globex,
+        supplier_id: supplier["id"].as_str().unwrap().to_string(),
+        laptop_id: laptop["id"].as_str().unwrap().to_string(),
+        south: sites.pop().unwrap(),
+        north: sites.pop().unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn serial_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for uri in [
+        format!("/stock/serials?company_id={id}"),
+        format!("/stock/serials/{id}"),
+        format!("/stock/serial-reconciliation?company_id={id}"),
+    ] {
+        let response = app.request(Method::GET, &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn serial_is_traced_from_supplier_to_customer() {
+    let f = setup().await;
+    let (north_warehouse, north_bin) = &f.north;
+    let (south_warehouse, south_bin) = &f.south;
+
+    let (order_uri, line_id) = f.purchase(2).await;
+    let receipt = f
+        .app
+        .create(
+            &f.token,
+            &format!("{order_uri}/receipts"),
+            json!({
+                "number": "GR-1",
+                "warehouse_id": north_warehouse,
+                "lines": [{
+                    "line_id": line_id,
+                    "location_id": north_bin,
+                    "quantity": 2,
+                    "serial_numbers": ["SN-1", "SN-2"],
+                }],
+            }),
+        )
+        .await;
+    assert_eq!(
+        receipt["lines"][0]["serial_numbers"],
+        json!(["SN-1", "SN-2"])
+    );
+
+    let transfer = f
+        .app
+        .create(
+            &f.token,
+            "/stock/transfers",
+            json!({
+                "company_id": f.acme.id,
+                "number": "TR-1",
+                "source_warehouse_id": north_warehouse,
+                "destination_warehouse_id": south_warehouse,
+                "lines": [{
+                    "product_id": f.laptop_id,
+                    "source_location_id": north_bin,
+                    "destination_location_id": south_bin,
+                    "quantity": 1,
+                    "serial_numbers": ["SN-1"],
+                }],
+            }),
+        )
+        .await;
+    let transfer_uri = format!("/stock/transfers/{}", transfer["id"].as_str().unwrap());
+    let (status, _) = f.post(&format!("{transfer_uri}/dispatch"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, in_transit) = f
+        .get(&format!(
+            "/stock/serials?company_id={}&status=in_transit",
+            f.acme.id
+        ))
+        .await;
+    assert_eq!(in_transit["data"][0]["serial_number"], "SN-1");
+    assert_eq!(in_transit["data"][0]["location_id"], Value::Null);
+    let (status, _) = f
+        .post(
+            &format!("{transfer_uri}/receive"),
+            Some(json!({ "lines": [{ "line_id": transfer["lines"][0]["id"], "quantity": 1 }] })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let order = f
+        .app
+        .create(
+            &f.token,
+            "/sales/orders",
+            json!({
+                "company_id": f.acme.id,
+                "customer_id": f.globex.id,
+                "warehouse_id": south_warehouse,
+                "number": "SO-1",
+                "lines": [{ "product_id": f.laptop_id, "quantity": 1 }],
+            }),
+        )
+        .await;
+    let order_uri = format!("/sales/orders/{}", order["id"].as_str().unwrap());
+    let (status, _) = f
+        .post(
+            &format!("{order_uri}/confirm"),
+            Some(json!({ "reserved_until": null })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let shipment = f
+        .app
+        .create(
+            &f.token,
+            &format!("{order_uri}/shipments"),
+            json!({
+                "number": "SH-1",
+                "lines": [{
+                    "line_id": order["lines"][0]["id"],
+                    "location_id": south_bin,
+                    "quantity": 1,
+                    "serial_numbers": ["SN-1"],
+                }],
+            }),
+        )
+        .await;
+    let shipment_uri = format!("/sales/shipments/{}", shipment["id"].as_str().unwrap());
+    f.post(&format!("{shipment_uri}/pack"), None).await;
+    let (status, _) = f.post(&format!("{shipment_uri}/ship"), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, listed) = f
+        .get(&format!(
+            "/stock/serials?company_id={}&product_id={}&per_page=10",
+            f.acme.id, f.laptop_id
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed["data"][0]["serial_number"], "SN-1");
+    assert_eq!(listed["data"][0]["status"], "issued");
+    assert_eq!(listed["data"][1]["serial_number"], "SN-2");
+    assert_eq!(listed["data"][1]["location_id"], north_bin.as_str());
+
+    let serial_id = listed["data"][0]["id"].as_str().unwrap();
+    let (status, serial) = f.get(&format!("/stock/serials/{serial_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    let history = serial["data"]["history"].as_array().unwrap();
+    let steps: Vec<(&str, i64, &str)> = history
+        .iter()
+        .map(|event| {
+            (
+                event["movement_type"].as_str().unwrap(),
+                event["quantity"].as_i64().unwrap(),
+                event["reference"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("receipt", 1, "GR-1"),
+            ("transfer_out", -1, "TR-1"),
+            ("transfer_in", 1, "TR-1"),
+            ("issue", -1, "SH-1"),
+        ]
+    );
+    assert_eq!(history[0]["supplier_id"], f.supplier_id);
+    assert_eq!(history[0]["location_id"], north_bin.as_str());
+    assert_eq!(history[2]["location_id"], south_bin.as_str());
+    assert_eq!(history[3]["customer_id"], f.globex.id.to_string());
+
+    let (status, mismatches) = f
+        .get(&format!(
+            "/stock/serial-reconciliation?company_id={}",
+            f.acme.id
+        ))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(mismatches["data"], json!([]));
+}
+
+#[tokio::test]
+async fn serialised_stock_rejects_missing_or_unknown_serials() {
+    let f = setup().await;
+    let (north_warehouse, north_bin) = &f.north;
+
+    let (order_uri, line_id) = f.purchase(2).await;
+    let (status, body) = f
+        .post(
+            &format!("{order_uri}/receipts"),
+            Some(json!({
+                "number": "GR-1",
+                "warehouse_id": north_warehouse,
+                "lines": [{
+                    "line_id": line_id,
+                    "location_id": north_bin,
+                    "quantity": 2,
+                    "serial_numbers": ["SN-1"],
+                }],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            "2 serial numbers are required for a serialised product"
+        )
+    );
+
+    let (status, body) = f
+        .post(
+            "/stock/movements",
+            Some(json!({
+                "product_id": f.laptop_id,
+                "location_id": north_bin,
+                "movement_type": "issue",
+                "quantity": 1,
+                "serial_numbers": ["SN-9"],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "serial SN-9 is not in stock at the location")
+    );
+
+    let (status, body) = f.get(&format!("/stock/serials/{}", Uuid::new_v4())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, error_body(404, "data not found"));
+
+    let (status, body) = f
+        .get(&format!("/stock/serials?company_id={}", Uuid::new_v4()))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, error_body(404, "company not found"));
+}
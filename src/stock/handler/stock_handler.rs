@@ -0,0 +1,302 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::stock::domain::balance::{BalanceMismatch, StockBalance};
+use crate::stock::domain::movement::StockMovement;
+use crate::stock::domain::settings::StockSettings;
+use crate::stock::handler::map_stock_error::{
+    map_usecase_stock_error, posted_by, validate_movement_input,
+};
+use crate::stock::handler::types::{
+    PostMovementRequest, ReverseMovementRequest, StockBalanceFilterRequest, StockCompanyRequest,
+    StockMovementFilterRequest, UpdateStockSettingsRequest,
+};
+use crate::stock::repository::helper_query::{
+    STOCK_BALANCE_SORT_COLUMNS, STOCK_MOVEMENT_SORT_COLUMNS,
+};
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::stock_usecase::StockUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/stock/movements",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    request_body = PostMovementRequest,
+    responses(
+        (status = 201, description = "movement posted, balance_after holds the new on-hand quantity", body = ResponseSuccessBody<StockMovement>),
+        (status = 400, description = "invalid input, a reversal or transfer movement, unknown product or location, or insufficient stock once the reservations of sales orders are held back", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_movement_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostMovementRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_movement_input(&req)?;
+    let posted_by = posted_by(&claims)?;
+
+    let movement = usecase
+        .post_movement(req.into(), posted_by)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(movement)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/movements",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockMovementFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's ledger, oldest first unless sorted, meta holds the totals", body = ResponseSuccessBody<Vec<StockMovement>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_movements_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockMovementFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_MOVEMENT_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: None,
+        sort: q.sort,
+    };
+    let movement_list_data = usecase
+        .list_movements(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        movement_list_data.total_data as u64,
+        Some(movement_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/movements/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "movement id")),
+    responses(
+        (status = 200, description = "the movement", body = ResponseSuccessBody<StockMovement>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "movement not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_movement_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let movement = usecase
+        .get_movement(id)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(movement)))
+}
+
+// movements are never changed, a reversal posts the opposite quantity. only
+// movements posted by hand are reversed here, see MovementSource
+#[utoipa::path(
+    post,
+    path = "/stock/movements/{id}/reversal",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "id of the movement to reverse")),
+    request_body = ReverseMovementRequest,
+    responses(
+        (status = 201, description = "reversal posted", body = ResponseSuccessBody<StockMovement>),
+        (status = 400, description = "invalid id, already reversed, a reversal itself, posted by a document or insufficient stock once the reservations of sales orders are held back", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "movement not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn reverse_movement_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<ReverseMovementRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let posted_by = posted_by(&claims)?;
+
+    let movement = usecase
+        .reverse_movement(id, req.note, posted_by)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(movement)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/balances",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockBalanceFilterRequest),
+    responses(
+        (status = 200, description = "one page of on-hand quantities per product and location, meta holds the totals", body = ResponseSuccessBody<Vec<StockBalance>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_balances_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockBalanceFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_BALANCE_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: None,
+        sort: q.sort,
+    };
+    let balance_list_data = usecase
+        .list_balances(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        balance_list_data.total_data as u64,
+        Some(balance_list_data.data),
+    ))
+}
+
+// compares every balance with the sum of its ledger
+#[utoipa::path(
+    get,
+    path = "/stock/reconciliation",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(StockCompanyRequest),
+    responses(
+        (status = 200, description = "balances that differ from their ledger, empty when all match", body = ResponseSuccessBody<Vec<BalanceMismatch>>),
+        (status = 400, description = "missing company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_reconciliation_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    Query(q): Query<StockCompanyRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let mismatches = usecase
+        .reconcile(q.company_id)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(mismatches)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/company/{id}/stock-settings",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    responses(
+        (status = 200, description = "the company's stock settings, defaults when never changed", body = ResponseSuccessBody<StockSettings>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_stock_settings_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let settings = usecase
+        .get_settings(company_id)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(settings)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/company/{id}/stock-settings",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    request_body = UpdateStockSettingsRequest,
+    responses(
+        (status = 200, description = "settings saved", body = ResponseSuccessBody<StockSettings>),
+        (status = 400, description = "invalid id or input", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_stock_settings_handler<R: StockRepository>(
+    State(usecase): State<Arc<StockUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+    Json(req): Json<UpdateStockSettingsRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let settings = usecase
+        .update_settings(company_id, req.allow_negative_stock)
+        .await
+        .map_err(map_usecase_stock_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(settings)))
+}
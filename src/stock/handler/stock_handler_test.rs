@@ -0,0 +1,289 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body};
+use crate::company::domain::company::Company;
+
+fn movement(product_id: &str, location_id: &str, movement_type: &str, quantity: i64) -> Value {
+    json!({
+        "product_id": product_id,
+        "location_id": location_id,
+        "movement_type": movement_type,
+        "quantity": quantity,
+        "reference": "PO-1",
+    })
+}
+
+// one product of ACM and one bin in its warehouse, returned as ids
+async fn setup() -> (TestApp, String, Company, String, String) {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let warehouse = app
+        .create(
+            &token,
+            &format!("/company/{}/warehouses", acme.id),
+            json!({ "code": "WH-1", "name": "Main" }),
+        )
+        .await;
+    let location = app
+        .create(
+            &token,
+            &format!("/warehouse/{}/locations", warehouse["id"].as_str().unwrap()),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+
+    let product_id = product["id"].as_str().unwrap().to_string();
+    let location_id = location["id"].as_str().unwrap().to_string();
+    (app, token, acme, product_id, location_id)
+}
+
+#[tokio::test]
+async fn stock_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, "/stock/movements".to_string()),
+        (Method::POST, "/stock/movements".to_string()),
+        (Method::GET, format!("/stock/movements/{id}")),
+        (Method::POST, format!("/stock/movements/{id}/reversal")),
+        (Method::GET, format!("/stock/balances?company_id={id}")),
+        (
+            Method::GET,
+            format!("/stock/reconciliation?company_id={id}"),
+        ),
+        (Method::GET, format!("/company/{id}/stock-settings")),
+        (Method::PUT, format!("/company/{id}/stock-settings")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn movements_update_balances_and_reverse() {
+    let (app, token, acme, product_id, location_id) = setup().await;
+
+    let receipt = app
+        .create(
+            &token,
+            "/stock/movements",
+            movement(&product_id, &location_id, "receipt", 10),
+        )
+        .await;
+    assert_eq!(receipt["balance_after"], 10);
+    assert_eq!(receipt["company_id"], acme.id.to_string());
+    assert_eq!(receipt["posted_by"], TEST_ADMIN_ID.to_string());
+
+    let issue = app
+        .create(
+            &token,
+            "/stock/movements",
+            movement(&product_id, &location_id, "issue", 4),
+        )
+        .await;
+    assert_eq!(issue["quantity"], -4);
+    assert_eq!(issue["balance_after"], 6);
+
+    let issue_uri = format!("/stock/movements/{}", issue["id"].as_str().unwrap());
+    let response = app
+        .request(Method::GET, &issue_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"], issue);
+
+    let reversal = app
+        .create(
+            &token,
+            &format!("{issue_uri}/reversal"),
+            json!({ "note": "wrong product" }),
+        )
+        .await;
+    assert_eq!(reversal["movement_type"], "reversal");
+    assert_eq!(reversal["reversal_of"], issue["id"]);
+    assert_eq!(reversal["balance_after"], 10);
+
+    let response = app
+        .request(
+            Method::POST,
+            &format!("{issue_uri}/reversal"),
+            Some(&token),
+            Some(json!({})),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "movement already reversed")
+    );
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!(
+                "/stock/movements?company_id={}&movement_type=issue",
+                acme.id
+            ),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["data"], json!([issue]));
+    assert_eq!(body["meta"]["total_data"], 1);
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/stock/balances?company_id={}", acme.id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["data"][0]["quantity"], 10);
+    assert_eq!(body["data"][0]["location_id"], location_id);
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/stock/reconciliation?company_id={}", acme.id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"], json!([]));
+}
+
+#[tokio::test]
+async fn post_movement_rejects_invalid_requests() {
+    let (app, token, _, product_id, location_id) = setup().await;
+
+    let mut long_reference = movement(&product_id, &location_id, "receipt", 1);
+    long_reference["reference"] = json!("R".repeat(101));
+    let cases = [
+        (
+            movement(&product_id, &location_id, "issue", 1),
+            "insufficient stock, 0 available",
+        ),
+        (
+            movement(&product_id, &location_id, "receipt", 0),
+            "quantity is invalid",
+        ),
+        (
+            movement(&product_id, &location_id, "reversal", 1),
+            "reversals are posted through the reversed movement",
+        ),
+        (
+            movement(&product_id, &location_id, "transfer_in", 1),
+            "transfer movements are posted by dispatching and receiving a transfer",
+        ),
+        (
+            movement(&Uuid::new_v4().to_string(), &location_id, "receipt", 1),
+            "product not found",
+        ),
+        (
+            long_reference,
+            "Reference can not be longer than 100 characters",
+        ),
+    ];
+    for (body, message) in cases {
+        let response = app
+            .request(Method::POST, "/stock/movements", Some(&token), Some(body))
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(response.error_json(), error_body(400, message));
+    }
+    assert!(app.stocks.tables().await.movements.is_empty());
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/stock/movements/{}", Uuid::new_v4()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/stock/balances?company_id={}", Uuid::new_v4()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.error_json(), error_body(404, "company not found"));
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/stock/movements?company_id={}&sort=name", Uuid::new_v4()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn stock_settings_allow_negative_stock() {
+    let (app, token, acme, product_id, location_id) = setup().await;
+    let uri = format!("/company/{}/stock-settings", acme.id);
+
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"]["allow_negative_stock"], false);
+
+    let response = app
+        .request(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            Some(json!({ "allow_negative_stock": true })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"]["allow_negative_stock"], true);
+
+    let issue = app
+        .create(
+            &token,
+            "/stock/movements",
+            movement(&product_id, &location_id, "issue", 3),
+        )
+        .await;
+    assert_eq!(issue["balance_after"], -3);
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("/company/{}/stock-settings", Uuid::new_v4()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,306 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_middleware::jwt_token::claims::Claims;
+use crate::app_request::{pagination::PaginationRequest, path_uuid::PathUuid};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::stock::domain::transfer::{InTransitQuantity, StockTransfer};
+use crate::stock::handler::map_stock_error::posted_by;
+use crate::stock::handler::map_transfer_error::{
+    map_usecase_transfer_error, validate_transfer_number,
+};
+use crate::stock::handler::types::{
+    PostTransferRequest, ReceiveTransferRequest, StockCompanyRequest, StockTransferFilterRequest,
+    TransferResponse, UpdateTransferRequest,
+};
+use crate::stock::repository::helper_query::STOCK_TRANSFER_SORT_COLUMNS;
+use crate::stock::repository::transfer_repository::TransferRepository;
+use crate::stock::usecase::transfer_usecase::TransferUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/stock/transfers",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    request_body = PostTransferRequest,
+    responses(
+        (status = 201, description = "draft transfer created, nothing is posted until it is dispatched", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid input, number taken, or a warehouse, product or location that is unknown or of another company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn post_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PostTransferRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_transfer_number(&req.number)?;
+    let created_by = posted_by(&claims)?;
+
+    let transfer = usecase
+        .create_transfer(req.company_id, req.into(), created_by)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/transfers",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(PaginationRequest, StockTransferFilterRequest),
+    responses(
+        (status = 200, description = "one page of the company's transfers without their lines, search matches the number", body = ResponseSuccessBody<Vec<StockTransfer>>),
+        (status = 400, description = "missing company or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_transfers_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    Query(q): Query<PaginationRequest>,
+    Query(filter): Query<StockTransferFilterRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&STOCK_TRANSFER_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let transfer_list_data = usecase
+        .list_transfers(&filter.into(), &query)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        transfer_list_data.total_data as u64,
+        Some(transfer_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/stock/transfers/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "transfer id")),
+    responses(
+        (status = 200, description = "the transfer with its lines", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "transfer not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let transfer = usecase
+        .get_transfer(id)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/stock/transfers/{id}",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "transfer id")),
+    request_body = UpdateTransferRequest,
+    responses(
+        (status = 200, description = "draft updated", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid id or input, number taken, or the transfer is no longer a draft", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "transfer not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<UpdateTransferRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_transfer_number(&req.number)?;
+
+    let transfer = usecase
+        .update_transfer(id, req.into())
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/transfers/{id}/dispatch",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "transfer id")),
+    responses(
+        (status = 200, description = "every line taken out of its source location, now in transit", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid id, not a draft, no lines or insufficient stock once the reservations of sales orders are held back", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "transfer not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn dispatch_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let posted_by = posted_by(&claims)?;
+
+    let transfer = usecase
+        .dispatch_transfer(id, posted_by)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/transfers/{id}/receive",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "transfer id")),
+    request_body = ReceiveTransferRequest,
+    responses(
+        (status = 200, description = "quantities put into the destination locations, received once nothing is left in transit", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid id or input, not in transit, unknown line or more than is in transit", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "transfer not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn receive_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<ReceiveTransferRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let posted_by = posted_by(&claims)?;
+    let lines = req.lines.into_iter().map(Into::into).collect();
+
+    let transfer = usecase
+        .receive_transfer(id, lines, posted_by)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/stock/transfers/{id}/cancel",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "transfer id")),
+    responses(
+        (status = 200, description = "transfer cancelled, what is still in transit goes back to the source locations", body = ResponseSuccessBody<TransferResponse>),
+        (status = 400, description = "invalid id, or the transfer is already received or cancelled", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "transfer not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn cancel_transfer_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let posted_by = posted_by(&claims)?;
+
+    let transfer = usecase
+        .cancel_transfer(id, posted_by)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::OK,
+        Some(TransferResponse::from(transfer)),
+    ))
+}
+
+// in transit stock is not on hand anywhere, it is listed apart from the balances
+#[utoipa::path(
+    get,
+    path = "/stock/in-transit",
+    tag = "stock",
+    security(("bearer_auth" = [])),
+    params(StockCompanyRequest),
+    responses(
+        (status = 200, description = "open quantities of dispatched transfers per product and warehouse pair", body = ResponseSuccessBody<Vec<InTransitQuantity>>),
+        (status = 400, description = "missing company", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_in_transit_handler<R: TransferRepository>(
+    State(usecase): State<Arc<TransferUsecase<R>>>,
+    Query(q): Query<StockCompanyRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    let in_transit = usecase
+        .list_in_transit(q.company_id)
+        .await
+        .map_err(map_usecase_transfer_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(in_transit)))
+}
@@ -0,0 +1,335 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TEST_ADMIN_ID, TestApp, error_body};
+use crate::company::domain::company::Company;
+
+// a warehouse with one bin, returned as (warehouse id, bin id)
+async fn warehouse(app: &TestApp, token: &str, company: &Company, code: &str) -> (String, String) {
+    let warehouse = app
+        .create(
+            token,
+            &format!("/company/{}/warehouses", company.id),
+            json!({ "code": code, "name": code }),
+        )
+        .await;
+    let warehouse_id = warehouse["id"].as_str().unwrap().to_string();
+    let location = app
+        .create(
+            token,
+            &format!("/warehouse/{warehouse_id}/locations"),
+            json!({ "code": "A-01", "name": "Bin A-01", "kind": "bin" }),
+        )
+        .await;
+    (warehouse_id, location["id"].as_str().unwrap().to_string())
+}
+
+struct Fixture {
+    app: TestApp,
+    token: String,
+    acme: Company,
+    globex: Company,
+    product_id: String,
+    north: (String, String),
+    south: (String, String),
+}
+
+impl Fixture {
+    fn transfer(&self, number: &str, quantity: i64) -> Value {
+        json!({
+            "company_id": self.acme.id,
+            "number": number,
+            "source_warehouse_id": self.north.0,
+            "destination_warehouse_id": self.south.0,
+            "lines": [{
+                "product_id": self.product_id,
+                "source_location_id": self.north.1,
+                "destination_location_id": self.south.1,
+                "quantity": quantity,
+            }],
+        })
+    }
+
+    async fn on_hand(&self, location_id: &str) -> Value {
+        let response = self
+            .app
+            .request(
+                Method::GET,
+                &format!(
+                    "/stock/balances?company_id={}&location_id={location_id}",
+                    self.acme.id
+                ),
+                Some(&self.token),
+                None,
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK);
+        response.json()["data"][0]["quantity"].clone()
+    }
+}
+
+// ACM has 10 bolts in its north warehouse and an empty south warehouse
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let app = TestApp::with_companies(vec![acme.clone(), globex.clone()]);
+    let token = app.login().await;
+
+    let product = app
+        .create(
+            &token,
+            "/product",
+            json!({
+                "company_id": acme.id,
+                "sku": "BOLT",
+                "name": "Bolt",
+                "unit_of_measure": "pcs",
+            }),
+        )
+        .await;
+    let product_id = product["id"].as_str().unwrap().to_string();
+    let north = warehouse(&app, &token, &acme, "NORTH").await;
+    let south = warehouse(&app, &token, &acme, "SOUTH").await;
+    app.create(
+        &token,
+        "/stock/movements",
+        json!({
+            "product_id": product_id,
+            "location_id": north.1,
+            "movement_type": "receipt",
+            "quantity": 10,
+        }),
+    )
+    .await;
+
+    Fixture {
+        app,
+        token,
+        acme,
+        globex,
+        product_id,
+        north,
+        south,
+    }
+}
+
+#[tokio::test]
+async fn transfer_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let id = Uuid::new_v4();
+
+    for (method, uri) in [
+        (Method::GET, format!("/stock/transfers?company_id={id}")),
+        (Method::POST, "/stock/transfers".to_string()),
+        (Method::GET, format!("/stock/transfers/{id}")),
+        (Method::PUT, format!("/stock/transfers/{id}")),
+        (Method::POST, format!("/stock/transfers/{id}/dispatch")),
+        (Method::POST, format!("/stock/transfers/{id}/receive")),
+        (Method::POST, format!("/stock/transfers/{id}/cancel")),
+        (Method::GET, format!("/stock/in-transit?company_id={id}")),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn transfer_dispatches_and_receives_in_parts() {
+    let f = setup().await;
+
+    let draft = f
+        .app
+        .create(&f.token, "/stock/transfers", f.transfer("TR-1", 4))
+        .await;
+    assert_eq!(draft["status"], "draft");
+    assert_eq!(draft["created_by"], TEST_ADMIN_ID.to_string());
+    assert_eq!(draft["lines"][0]["position"], 1);
+    let uri = format!("/stock/transfers/{}", draft["id"].as_str().unwrap());
+
+    let mut update = f.transfer("TR-1", 6);
+    update.as_object_mut().unwrap().remove("company_id");
+    update["note"] = json!("by truck");
+    let response = f
+        .app
+        .request(Method::PUT, &uri, Some(&f.token), Some(update))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+    assert_eq!(response.json()["data"]["lines"][0]["quantity"], 6);
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/dispatch"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "dispatched");
+    assert_eq!(f.on_hand(&f.north.1).await, 4);
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/stock/in-transit?company_id={}", f.acme.id),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.json()["data"],
+        json!([{
+            "product_id": f.product_id,
+            "source_warehouse_id": f.north.0,
+            "destination_warehouse_id": f.south.0,
+            "quantity": 6,
+        }])
+    );
+
+    let line_id = body["data"]["lines"][0]["id"].clone();
+    let receipt =
+        |quantity: i64| Some(json!({ "lines": [{ "line_id": line_id, "quantity": quantity }] }));
+    let (status, body) = f
+        .app
+        .post(&f.token, &format!("{uri}/receive"), receipt(7))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(400, "received quantity exceeds the quantity in transit")
+    );
+
+    let (status, body) = f
+        .app
+        .post(&f.token, &format!("{uri}/receive"), receipt(2))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "partially_received");
+    assert_eq!(f.on_hand(&f.south.1).await, 2);
+
+    let (status, body) = f
+        .app
+        .post(&f.token, &format!("{uri}/receive"), receipt(4))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "received");
+    assert_eq!(f.on_hand(&f.south.1).await, 6);
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/cancel"), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "transfer is received"));
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!(
+                "/stock/transfers?company_id={}&status=received&search=tr&sort=-number",
+                f.acme.id
+            ),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let body = response.json();
+    assert_eq!(body["meta"]["total_data"], 1);
+    assert_eq!(body["data"][0]["note"], "by truck");
+}
+
+#[tokio::test]
+async fn cancel_puts_dispatched_stock_back() {
+    let f = setup().await;
+    let draft = f
+        .app
+        .create(&f.token, "/stock/transfers", f.transfer("TR-1", 10))
+        .await;
+    let uri = format!("/stock/transfers/{}", draft["id"].as_str().unwrap());
+
+    let (status, _) = f.app.post(&f.token, &format!("{uri}/dispatch"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(f.on_hand(&f.north.1).await, 0);
+
+    let (status, body) = f.app.post(&f.token, &format!("{uri}/cancel"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["status"], "cancelled");
+    assert_eq!(f.on_hand(&f.north.1).await, 10);
+
+    let response = f.app.request(Method::GET, &uri, Some(&f.token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.json()["data"]["cancelled_at"].is_string());
+}
+
+#[tokio::test]
+async fn transfer_rejects_invalid_input() {
+    let f = setup().await;
+    let (globex_warehouse, _) = warehouse(&f.app, &f.token, &f.globex, "MAIN").await;
+
+    let mut same = f.transfer("TR-1", 1);
+    same["destination_warehouse_id"] = json!(f.north.0);
+    let mut foreign = f.transfer("TR-1", 1);
+    foreign["destination_warehouse_id"] = json!(globex_warehouse);
+    let mut wrong_bin = f.transfer("TR-1", 1);
+    wrong_bin["lines"][0]["destination_location_id"] = json!(f.north.1);
+    let cases = [
+        (f.transfer("", 1), "Number is required"),
+        (
+            f.transfer(&"T".repeat(31), 1),
+            "Number can not be longer than 30 characters",
+        ),
+        (f.transfer("TR-1", 0), "quantity is invalid"),
+        (same, "source and destination must be different warehouses"),
+        (
+            foreign,
+            "warehouses and products must belong to the transfer's company",
+        ),
+        (wrong_bin, "location not found in the line's warehouse"),
+    ];
+    for (body, message) in cases {
+        let (status, body) = f.app.post(&f.token, "/stock/transfers", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{message}");
+        assert_eq!(body, error_body(400, message));
+    }
+
+    let big = f
+        .app
+        .create(&f.token, "/stock/transfers", f.transfer("TR-1", 11))
+        .await;
+    let (status, body) = f
+        .app
+        .post(&f.token, "/stock/transfers", Some(f.transfer("TR-1", 1)))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, error_body(400, "transfer number already exists"));
+
+    let uri = format!("/stock/transfers/{}/dispatch", big["id"].as_str().unwrap());
+    let (status, body) = f.app.post(&f.token, &uri, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        error_body(
+            400,
+            &format!(
+                "insufficient stock of product {}, 10 available",
+                f.product_id
+            )
+        )
+    );
+
+    let mut unknown = f.transfer("TR-2", 1);
+    unknown["company_id"] = json!(Uuid::new_v4());
+    let (status, body) = f
+        .app
+        .post(&f.token, "/stock/transfers", Some(unknown))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, error_body(404, "company not found"));
+
+    let response = f
+        .app
+        .request(
+            Method::GET,
+            &format!("/stock/transfers?company_id={}&sort=note", f.acme.id),
+            Some(&f.token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
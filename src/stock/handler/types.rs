@@ -0,0 +1,497 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::stock::domain::balance::StockBalanceFilter;
+use crate::stock::domain::count::{
+    CountStatus, StockCount, StockCountEntry, StockCountFilter, StockCountLine, disputed_lines,
+};
+use crate::stock::domain::lot::{StockLot, StockLotBalance, StockLotFilter};
+use crate::stock::domain::movement::{MovementType, StockMovementFilter};
+use crate::stock::domain::serial::{SerialEvent, SerialStatus, StockSerial, StockSerialFilter};
+use crate::stock::domain::transfer::{
+    StockTransfer, StockTransferFilter, TransferLine, TransferStatus,
+};
+use crate::stock::usecase::dto::{
+    CountDetail, CountEntryInput, CountInput, LotDetail, LotInput, MovementInput, ReceiptLineInput,
+    SerialDetail, TransferDetail, TransferInput, TransferLineInput,
+};
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostMovementRequest {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    /// receipt, issue, adjustment, transfer_in or transfer_out
+    pub movement_type: MovementType,
+    /// required for lot tracked products, refused for any other
+    pub lot_id: Option<Uuid>,
+    /// positive, adjustments take a sign instead
+    pub quantity: i64,
+    /// one per unit for serialised products, refused for any other
+    #[serde(default)]
+    pub serial_numbers: Vec<String>,
+    /// e.g. a purchase or sales order number
+    pub reference: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ReverseMovementRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct UpdateStockSettingsRequest {
+    /// let issues take a balance below zero
+    pub allow_negative_stock: bool,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct TransferLineRequest {
+    pub product_id: Uuid,
+    /// a location of the source warehouse
+    pub source_location_id: Uuid,
+    /// a location of the destination warehouse
+    pub destination_location_id: Uuid,
+    /// required for lot tracked products
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    /// one per unit for serialised products, received in the given order
+    #[serde(default)]
+    pub serial_numbers: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostTransferRequest {
+    /// both warehouses and every product belong to this company
+    pub company_id: Uuid,
+    pub number: String,
+    pub source_warehouse_id: Uuid,
+    pub destination_warehouse_id: Uuid,
+    pub note: Option<String>,
+    pub lines: Vec<TransferLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct UpdateTransferRequest {
+    pub number: String,
+    pub source_warehouse_id: Uuid,
+    pub destination_warehouse_id: Uuid,
+    pub note: Option<String>,
+    /// replace the lines of the draft
+    pub lines: Vec<TransferLineRequest>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ReceiptLineRequest {
+    pub line_id: Uuid,
+    pub quantity: i64,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ReceiveTransferRequest {
+    /// lines left out stay in transit
+    pub lines: Vec<ReceiptLineRequest>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TransferResponse {
+    #[serde(flatten)]
+    pub transfer: StockTransfer,
+    pub lines: Vec<TransferLine>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockTransferFilterRequest {
+    /// the transfers of this company
+    pub company_id: Uuid,
+    pub status: Option<TransferStatus>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostCountRequest {
+    /// the warehouse belongs to this company
+    pub company_id: Uuid,
+    pub number: String,
+    pub warehouse_id: Uuid,
+    /// counts this location and everything below it, the whole warehouse when left out
+    pub location_id: Option<Uuid>,
+    /// hide the expected quantities from the counters until the count is submitted
+    #[serde(default)]
+    pub is_blind: bool,
+    /// variances larger than this, either way, wait for an approval
+    pub approval_threshold: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct CountEntryRequest {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    /// required for lot tracked products
+    pub lot_id: Option<Uuid>,
+    /// what was found, 0 or more
+    pub quantity: i64,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct RecordCountRequest {
+    /// a line the count did not expect is added, a line counted before takes the new quantity
+    pub entries: Vec<CountEntryRequest>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CountLineResponse {
+    pub id: Uuid,
+    pub position: i32,
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    /// left out while a blind count is open, taken again when the count is
+    /// completed so the variance is what was posted
+    pub expected_quantity: Option<i64>,
+    pub counted_quantity: Option<i64>,
+    /// counted minus expected, left out while a blind count is open
+    pub variance: Option<i64>,
+    pub counted_by: Option<Uuid>,
+    pub counted_at: Option<DateTime<Utc>>,
+    /// the latest entries of two counters differ, the count waits for an approval
+    pub is_disputed: bool,
+    /// the adjustment posted for the variance
+    pub movement_id: Option<Uuid>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CountResponse {
+    #[serde(flatten)]
+    pub count: StockCount,
+    pub lines: Vec<CountLineResponse>,
+    /// every quantity recorded, in the order it was counted
+    pub entries: Vec<StockCountEntry>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockCountFilterRequest {
+    /// the counts of this company
+    pub company_id: Uuid,
+    pub warehouse_id: Option<Uuid>,
+    pub status: Option<CountStatus>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockMovementFilterRequest {
+    /// the ledger of this company
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub movement_type: Option<MovementType>,
+    pub lot_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockBalanceFilterRequest {
+    /// the balances of this company
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    /// every location of this warehouse
+    pub warehouse_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockCompanyRequest {
+    pub company_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PostLotRequest {
+    /// a lot tracked product, the lot belongs to its company
+    pub product_id: Uuid,
+    /// unique per product
+    pub lot_number: String,
+    pub manufactured_on: Option<NaiveDate>,
+    /// the lot can be picked up to and including this day
+    pub expires_on: Option<NaiveDate>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LotResponse {
+    #[serde(flatten)]
+    pub lot: StockLot,
+    /// sum of the lot balances
+    pub on_hand: i64,
+    pub balances: Vec<StockLotBalance>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockLotFilterRequest {
+    /// the lots of this company
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExpiringLotFilterRequest {
+    /// the lots of this company
+    pub company_id: Uuid,
+    /// lots expiring within this many days from today, already expired lots included
+    pub days: i64,
+    /// only stock held in this warehouse
+    pub warehouse_id: Option<Uuid>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SerialResponse {
+    #[serde(flatten)]
+    pub serial: StockSerial,
+    /// every movement of the serial, oldest first
+    pub history: Vec<SerialEvent>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockSerialFilterRequest {
+    /// the serials of this company
+    pub company_id: Uuid,
+    pub product_id: Option<Uuid>,
+    /// serials in stock at this location
+    pub location_id: Option<Uuid>,
+    pub status: Option<SerialStatus>,
+}
+
+impl From<PostMovementRequest> for MovementInput {
+    fn from(req: PostMovementRequest) -> Self {
+        MovementInput {
+            product_id: req.product_id,
+            location_id: req.location_id,
+            movement_type: req.movement_type,
+            lot_id: req.lot_id,
+            quantity: req.quantity,
+            serial_numbers: req.serial_numbers,
+            reference: req.reference,
+            note: req.note,
+        }
+    }
+}
+
+impl From<StockMovementFilterRequest> for StockMovementFilter {
+    fn from(req: StockMovementFilterRequest) -> Self {
+        StockMovementFilter {
+            company_id: req.company_id,
+            product_id: req.product_id,
+            location_id: req.location_id,
+            movement_type: req.movement_type,
+            lot_id: req.lot_id,
+        }
+    }
+}
+
+impl From<StockBalanceFilterRequest> for StockBalanceFilter {
+    fn from(req: StockBalanceFilterRequest) -> Self {
+        StockBalanceFilter {
+            company_id: req.company_id,
+            product_id: req.product_id,
+            warehouse_id: req.warehouse_id,
+            location_id: req.location_id,
+        }
+    }
+}
+
+impl From<PostLotRequest> for LotInput {
+    fn from(req: PostLotRequest) -> Self {
+        LotInput {
+            product_id: req.product_id,
+            lot_number: req.lot_number,
+            manufactured_on: req.manufactured_on,
+            expires_on: req.expires_on,
+        }
+    }
+}
+
+impl From<LotDetail> for LotResponse {
+    fn from(detail: LotDetail) -> Self {
+        LotResponse {
+            lot: detail.lot,
+            on_hand: detail.on_hand,
+            balances: detail.balances,
+        }
+    }
+}
+
+impl From<StockLotFilterRequest> for StockLotFilter {
+    fn from(req: StockLotFilterRequest) -> Self {
+        StockLotFilter {
+            company_id: req.company_id,
+            product_id: req.product_id,
+        }
+    }
+}
+
+impl From<SerialDetail> for SerialResponse {
+    fn from(detail: SerialDetail) -> Self {
+        SerialResponse {
+            serial: detail.serial,
+            history: detail.history,
+        }
+    }
+}
+
+impl From<StockSerialFilterRequest> for StockSerialFilter {
+    fn from(req: StockSerialFilterRequest) -> Self {
+        StockSerialFilter {
+            company_id: req.company_id,
+            product_id: req.product_id,
+            location_id: req.location_id,
+            status: req.status,
+        }
+    }
+}
+
+impl From<TransferLineRequest> for TransferLineInput {
+    fn from(req: TransferLineRequest) -> Self {
+        TransferLineInput {
+            product_id: req.product_id,
+            source_location_id: req.source_location_id,
+            destination_location_id: req.destination_location_id,
+            lot_id: req.lot_id,
+            quantity: req.quantity,
+            serial_numbers: req.serial_numbers,
+        }
+    }
+}
+
+impl From<PostTransferRequest> for TransferInput {
+    fn from(req: PostTransferRequest) -> Self {
+        TransferInput {
+            number: req.number,
+            source_warehouse_id: req.source_warehouse_id,
+            destination_warehouse_id: req.destination_warehouse_id,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<UpdateTransferRequest> for TransferInput {
+    fn from(req: UpdateTransferRequest) -> Self {
+        TransferInput {
+            number: req.number,
+            source_warehouse_id: req.source_warehouse_id,
+            destination_warehouse_id: req.destination_warehouse_id,
+            note: req.note,
+            lines: req.lines.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ReceiptLineRequest> for ReceiptLineInput {
+    fn from(req: ReceiptLineRequest) -> Self {
+        ReceiptLineInput {
+            line_id: req.line_id,
+            quantity: req.quantity,
+        }
+    }
+}
+
+impl From<TransferDetail> for TransferResponse {
+    fn from(detail: TransferDetail) -> Self {
+        TransferResponse {
+            transfer: detail.transfer,
+            lines: detail.lines,
+        }
+    }
+}
+
+impl From<StockTransferFilterRequest> for StockTransferFilter {
+    fn from(req: StockTransferFilterRequest) -> Self {
+        StockTransferFilter {
+            company_id: req.company_id,
+            status: req.status,
+        }
+    }
+}
+
+impl From<PostCountRequest> for CountInput {
+    fn from(req: PostCountRequest) -> Self {
+        CountInput {
+            number: req.number,
+            warehouse_id: req.warehouse_id,
+            location_id: req.location_id,
+            is_blind: req.is_blind,
+            approval_threshold: req.approval_threshold,
+            note: req.note,
+        }
+    }
+}
+
+impl From<CountEntryRequest> for CountEntryInput {
+    fn from(req: CountEntryRequest) -> Self {
+        CountEntryInput {
+            product_id: req.product_id,
+            location_id: req.location_id,
+            lot_id: req.lot_id,
+            quantity: req.quantity,
+        }
+    }
+}
+
+impl CountLineResponse {
+    fn new(line: StockCountLine, hides_expected: bool, is_disputed: bool) -> Self {
+        let (expected_quantity, variance) = if hides_expected {
+            (None, None)
+        } else {
+            (Some(line.expected_quantity), line.variance())
+        };
+        CountLineResponse {
+            id: line.id,
+            position: line.position,
+            product_id: line.product_id,
+            location_id: line.location_id,
+            lot_id: line.lot_id,
+            expected_quantity,
+            counted_quantity: line.counted_quantity,
+            variance,
+            counted_by: line.counted_by,
+            counted_at: line.counted_at,
+            is_disputed,
+            movement_id: line.movement_id,
+        }
+    }
+}
+
+impl From<CountDetail> for CountResponse {
+    fn from(detail: CountDetail) -> Self {
+        let hides_expected = detail.count.hides_expected();
+        let disputed = disputed_lines(&detail.entries);
+        CountResponse {
+            count: detail.count,
+            lines: detail
+                .lines
+                .into_iter()
+                .map(|line| {
+                    let is_disputed = disputed.contains(&line.id);
+                    CountLineResponse::new(line, hides_expected, is_disputed)
+                })
+                .collect(),
+            entries: detail.entries,
+        }
+    }
+}
+
+impl From<StockCountFilterRequest> for StockCountFilter {
+    fn from(req: StockCountFilterRequest) -> Self {
+        StockCountFilter {
+            company_id: req.company_id,
+            warehouse_id: req.warehouse_id,
+            status: req.status,
+        }
+    }
+}
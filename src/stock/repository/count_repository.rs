@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::count::{ExpectedQuantity, StockCount, StockCountEntry, StockCountFilter, StockCountLine};
+use crate::stock::repository::stock_repository::StockRepository;
+
+// completing a count posts to the ledger in the same unit of work, so counts
+// live on top of the stock repository. is_location_counted is part of the
+// stock repository, every posting checks it
+#[async_trait]
+pub trait CountRepository: StockRepository {
+    async fn check_existing_count_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str) -> Result<bool, sqlx::Error>;
+    async fn create_count(&self, uow: &mut Self::Uow, count: StockCount) -> Result<StockCount, sqlx::Error>;
+    // stores status, approval and the timestamps, the scope never changes
+    async fn update_count(&self, uow: &mut Self::Uow, count: StockCount) -> Result<StockCount, sqlx::Error>;
+    async fn get_count_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockCount>, sqlx::Error>;
+    // like get_count_by_id, the row stays locked until the unit of work
+    // finishes so entries and status changes of one count queue up
+    async fn lock_count(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockCount>, sqlx::Error>;
+    // the company of the warehouse, the warehouse row stays locked until the
+    // unit of work finishes so counts of one warehouse are opened one at a time
+    async fn lock_warehouse_company(&self, uow: &mut Self::Uow, warehouse_id: &Uuid) -> Result<Option<Uuid>, sqlx::Error>;
+    async fn count_counts(&self, uow: &mut Self::Uow, filter: &StockCountFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_counts(&self, uow: &mut Self::Uow, filter: &StockCountFilter, query: &PaginationRequest) -> Result<Vec<StockCount>, sqlx::Error>;
+    // open or pending approval, ordered by creation
+    async fn find_active_counts(&self, uow: &mut Self::Uow, warehouse_id: &Uuid) -> Result<Vec<StockCount>, sqlx::Error>;
+    // every location of the warehouse, or the location and everything below
+    // it, ordered by id
+    async fn find_count_locations(&self, uow: &mut Self::Uow, warehouse_id: &Uuid, location_id: Option<&Uuid>) -> Result<Vec<Uuid>, sqlx::Error>;
+    // non-zero balances at the locations, per lot for lot tracked products and
+    // without serialised products, ordered by location, product and lot
+    async fn find_expected_quantities(&self, uow: &mut Self::Uow, location_ids: &[Uuid]) -> Result<Vec<ExpectedQuantity>, sqlx::Error>;
+    // ordered by position
+    async fn find_count_lines(&self, uow: &mut Self::Uow, count_id: &Uuid) -> Result<Vec<StockCountLine>, sqlx::Error>;
+    async fn create_count_lines(&self, uow: &mut Self::Uow, lines: Vec<StockCountLine>) -> Result<Vec<StockCountLine>, sqlx::Error>;
+    // stores the expected and counted quantity and the movement
+    async fn update_count_line(&self, uow: &mut Self::Uow, line: StockCountLine) -> Result<StockCountLine, sqlx::Error>;
+    async fn create_count_entry(&self, uow: &mut Self::Uow, entry: StockCountEntry) -> Result<StockCountEntry, sqlx::Error>;
+    // in the order they were counted
+    async fn find_count_entries(&self, uow: &mut Self::Uow, count_id: &Uuid) -> Result<Vec<StockCountEntry>, sqlx::Error>;
+}
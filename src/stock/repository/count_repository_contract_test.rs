@@ -0,0 +1,576 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// stock_repository_contract_test. ACM has a north warehouse with bin A inside
+// a zone and bin B beside it, and a south warehouse with one bin. GLB has one
+// warehouse. bolts and nuts are plain products, grain is lot tracked and
+// laptops are serialised. postgres runs need TEST_DATABASE_URL and are skipped
+// without it.
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{
+    ACME_ID, Catalogue, GLOBEX_ID, at, bin, companies, product, seed_catalogue, seed_companies,
+    warehouse,
+};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::stock::domain::count::{
+    CountStatus, ExpectedQuantity, StockCount, StockCountEntry, StockCountFilter, StockCountLine,
+};
+use crate::stock::domain::lot::StockLot;
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+const BOLT_ID: Uuid = Uuid::from_u128(11);
+const NUT_ID: Uuid = Uuid::from_u128(12);
+const GRAIN_ID: Uuid = Uuid::from_u128(13);
+const LAPTOP_ID: Uuid = Uuid::from_u128(14);
+const NORTH_ID: Uuid = Uuid::from_u128(21);
+const SOUTH_ID: Uuid = Uuid::from_u128(22);
+const GLOBEX_MAIN_ID: Uuid = Uuid::from_u128(23);
+const ZONE_ID: Uuid = Uuid::from_u128(30);
+const BIN_A_ID: Uuid = Uuid::from_u128(31);
+const BIN_B_ID: Uuid = Uuid::from_u128(32);
+const SOUTH_BIN_ID: Uuid = Uuid::from_u128(33);
+const GLOBEX_BIN_ID: Uuid = Uuid::from_u128(34);
+const LOT_ID: Uuid = Uuid::from_u128(41);
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                let products = super::ProductRepositoryMemory::new(companies.clone());
+                let warehouses = super::WarehouseRepositoryMemory::new(companies.clone());
+                super::seed_catalogue(&products, &warehouses, super::catalogue()).await;
+                super::$scenario(&super::StockRepositoryMemory::new(
+                    companies, products, warehouses,
+                ))
+                .await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::seed_catalogue(
+                    &super::ProductRepositorySqlx::new(db.pool.clone()),
+                    &super::WarehouseRepositorySqlx::new(db.pool.clone()),
+                    super::catalogue(),
+                )
+                .await;
+                super::$scenario(&super::StockRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(stores_counts_lines_and_entries);
+contract_test!(scopes_counts_and_freezes_their_locations);
+contract_test!(snapshots_expected_quantities);
+contract_test!(filters_sorts_and_pages_counts);
+contract_test!(enforces_count_constraints);
+
+// bin A sits in a zone of the north warehouse, bin B beside it
+fn catalogue() -> Catalogue {
+    Catalogue {
+        products: vec![
+            product(BOLT_ID, ACME_ID, "BOLT"),
+            product(NUT_ID, ACME_ID, "NUT"),
+            Product {
+                is_lot_tracked: true,
+                ..product(GRAIN_ID, ACME_ID, "GRAIN")
+            },
+            Product {
+                is_serialised: true,
+                ..product(LAPTOP_ID, ACME_ID, "LAPTOP")
+            },
+        ],
+        warehouses: vec![
+            warehouse(NORTH_ID, ACME_ID, "NORTH"),
+            warehouse(SOUTH_ID, ACME_ID, "SOUTH"),
+            warehouse(GLOBEX_MAIN_ID, GLOBEX_ID, "MAIN"),
+        ],
+        locations: vec![
+            Location {
+                name: "Zone Z".into(),
+                kind: LocationKind::Zone,
+                ..bin(ZONE_ID, NORTH_ID, "Z")
+            },
+            Location {
+                parent_id: Some(ZONE_ID),
+                ..bin(BIN_A_ID, NORTH_ID, "A")
+            },
+            bin(BIN_B_ID, NORTH_ID, "B"),
+            bin(SOUTH_BIN_ID, SOUTH_ID, "A"),
+            bin(GLOBEX_BIN_ID, GLOBEX_MAIN_ID, "A"),
+        ],
+    }
+}
+
+fn count(number: &str, location_id: Option<Uuid>, status: CountStatus, minutes: i64) -> StockCount {
+    StockCount {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        number: number.into(),
+        warehouse_id: NORTH_ID,
+        location_id,
+        status,
+        is_blind: false,
+        approval_threshold: None,
+        note: None,
+        created_by: Uuid::from_u128(99),
+        created_at: at(minutes),
+        submitted_at: None,
+        approved_by: None,
+        approved_at: None,
+        completed_at: None,
+        cancelled_at: None,
+    }
+}
+
+fn line(count: &StockCount, position: i32, product_id: Uuid, expected: i64) -> StockCountLine {
+    StockCountLine {
+        id: Uuid::new_v4(),
+        count_id: count.id,
+        position,
+        product_id,
+        location_id: BIN_A_ID,
+        lot_id: None,
+        expected_quantity: expected,
+        counted_quantity: None,
+        counted_by: None,
+        counted_at: None,
+        movement_id: None,
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+fn filter(status: Option<CountStatus>) -> StockCountFilter {
+    StockCountFilter {
+        company_id: ACME_ID,
+        warehouse_id: None,
+        status,
+    }
+}
+
+async fn seed_counts<R: CountRepository>(repo: &R, counts: &[(StockCount, Vec<StockCountLine>)]) {
+    let mut uow = repo.begin().await.unwrap();
+    for (count, lines) in counts {
+        repo.create_count(&mut uow, count.clone()).await.unwrap();
+        repo.create_count_lines(&mut uow, lines.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn numbers(counts: &[StockCount]) -> Vec<&str> {
+    counts.iter().map(|c| c.number.as_str()).collect()
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn stores_counts_lines_and_entries<R: CountRepository>(repo: &R) {
+    let open = count("CC-1", Some(ZONE_ID), CountStatus::Open, 0);
+    let lines = vec![line(&open, 1, BOLT_ID, 5), line(&open, 2, NUT_ID, 3)];
+    seed_counts(repo, &[(open.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_count_by_id(&mut uow, &open.id).await.unwrap();
+    assert_eq!(stored, Some(open.clone()));
+    let locked = repo.lock_count(&mut uow, &open.id).await.unwrap();
+    assert_eq!(locked, Some(open.clone()));
+    assert_eq!(
+        repo.get_count_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repo.find_count_lines(&mut uow, &open.id).await.unwrap(),
+        lines
+    );
+    let taken = repo
+        .check_existing_count_number(&mut uow, &ACME_ID, "CC-1")
+        .await
+        .unwrap();
+    assert!(taken);
+    let other_company = repo
+        .check_existing_count_number(&mut uow, &GLOBEX_ID, "CC-1")
+        .await
+        .unwrap();
+    assert!(!other_company);
+
+    // two counters, the line keeps the latest
+    let counters = [Uuid::from_u128(97), Uuid::from_u128(98)];
+    let mut entries = Vec::new();
+    for (minutes, (counted_by, quantity)) in (1..).zip(counters.into_iter().zip([4, 6])) {
+        let entry = StockCountEntry {
+            id: Uuid::new_v4(),
+            count_id: open.id,
+            line_id: lines[0].id,
+            quantity,
+            counted_by,
+            counted_at: at(minutes),
+        };
+        repo.create_count_entry(&mut uow, entry.clone())
+            .await
+            .unwrap();
+        entries.push(entry);
+    }
+    let counted = StockCountLine {
+        counted_quantity: Some(6),
+        counted_by: Some(counters[1]),
+        counted_at: Some(at(2)),
+        ..lines[0].clone()
+    };
+    repo.update_count_line(&mut uow, counted.clone())
+        .await
+        .unwrap();
+    let found = StockCountLine {
+        expected_quantity: 0,
+        location_id: BIN_B_ID,
+        ..line(&open, 3, BOLT_ID, 0)
+    };
+    repo.create_count_lines(&mut uow, vec![found.clone()])
+        .await
+        .unwrap();
+    let pending = StockCount {
+        status: CountStatus::PendingApproval,
+        submitted_at: Some(at(3)),
+        ..open.clone()
+    };
+    repo.update_count(&mut uow, pending.clone()).await.unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_count_by_id(&mut uow, &open.id).await.unwrap();
+    assert_eq!(stored, Some(pending.clone()));
+    let stored = repo.find_count_lines(&mut uow, &open.id).await.unwrap();
+    assert_eq!(stored, vec![counted, lines[1].clone(), found]);
+    let stored = repo.find_count_entries(&mut uow, &open.id).await.unwrap();
+    assert_eq!(stored, entries);
+    let active = repo.find_active_counts(&mut uow, &NORTH_ID).await.unwrap();
+    assert_eq!(active, vec![pending]);
+    let active = repo.find_active_counts(&mut uow, &SOUTH_ID).await.unwrap();
+    assert!(active.is_empty());
+    uow.rollback().await.unwrap();
+}
+
+async fn scopes_counts_and_freezes_their_locations<R: CountRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (NORTH_ID, None, vec![ZONE_ID, BIN_A_ID, BIN_B_ID]),
+        (NORTH_ID, Some(ZONE_ID), vec![ZONE_ID, BIN_A_ID]),
+        (NORTH_ID, Some(BIN_A_ID), vec![BIN_A_ID]),
+        (NORTH_ID, Some(SOUTH_BIN_ID), vec![]),
+    ];
+    for (warehouse_id, location_id, expected) in cases {
+        let mut expected = expected;
+        expected.sort();
+        let found = repo
+            .find_count_locations(&mut uow, &warehouse_id, location_id.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(found, expected, "{location_id:?}");
+    }
+    uow.rollback().await.unwrap();
+
+    let zone = count("CC-1", Some(ZONE_ID), CountStatus::Open, 0);
+    let done = StockCount {
+        completed_at: Some(at(2)),
+        ..count("CC-2", None, CountStatus::Completed, 1)
+    };
+    seed_counts(repo, &[(zone.clone(), vec![]), (done, vec![])]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (ZONE_ID, true),
+        (BIN_A_ID, true),
+        (BIN_B_ID, false),
+        (SOUTH_BIN_ID, false),
+        (Uuid::new_v4(), false),
+    ];
+    for (location_id, expected) in cases {
+        let is_counted = repo
+            .is_location_counted(&mut uow, &location_id)
+            .await
+            .unwrap();
+        assert_eq!(is_counted, expected, "{location_id}");
+    }
+
+    // a whole warehouse count freezes every location of it, until cancelled
+    let north = count("CC-3", None, CountStatus::PendingApproval, 3);
+    repo.create_count(&mut uow, north.clone()).await.unwrap();
+    assert!(repo.is_location_counted(&mut uow, &BIN_B_ID).await.unwrap());
+    let cancelled = StockCount {
+        status: CountStatus::Cancelled,
+        cancelled_at: Some(at(4)),
+        ..north
+    };
+    repo.update_count(&mut uow, cancelled).await.unwrap();
+    assert!(!repo.is_location_counted(&mut uow, &BIN_B_ID).await.unwrap());
+    uow.rollback().await.unwrap();
+}
+
+async fn snapshots_expected_quantities<R: CountRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    let lot = StockLot {
+        id: LOT_ID,
+        company_id: ACME_ID,
+        product_id: GRAIN_ID,
+        lot_number: "L-1".into(),
+        manufactured_on: None,
+        expires_on: None,
+        created_at: at(0),
+    };
+    repo.create_lot(&mut uow, lot).await.unwrap();
+    let balances = [
+        (BOLT_ID, BIN_B_ID, 4),
+        (NUT_ID, BIN_A_ID, -2),
+        (BOLT_ID, BIN_A_ID, 0),
+        (GRAIN_ID, BIN_A_ID, 9),
+        (LAPTOP_ID, BIN_A_ID, 1),
+        (BOLT_ID, SOUTH_BIN_ID, 8),
+    ];
+    for (product_id, location_id, quantity) in balances {
+        repo.lock_balance(&mut uow, &ACME_ID, &product_id, &location_id)
+            .await
+            .unwrap();
+        repo.update_balance(&mut uow, &product_id, &location_id, quantity, at(1))
+            .await
+            .unwrap();
+    }
+    repo.lock_lot_balance(&mut uow, &ACME_ID, &LOT_ID, &GRAIN_ID, &BIN_A_ID)
+        .await
+        .unwrap();
+    repo.update_lot_balance(&mut uow, &LOT_ID, &BIN_A_ID, 9, at(1))
+        .await
+        .unwrap();
+
+    let locations = repo
+        .find_count_locations(&mut uow, &NORTH_ID, None)
+        .await
+        .unwrap();
+    let found = repo
+        .find_expected_quantities(&mut uow, &locations)
+        .await
+        .unwrap();
+    let expected_quantity = |product_id, location_id, lot_id, quantity| ExpectedQuantity {
+        product_id,
+        location_id,
+        lot_id,
+        quantity,
+    };
+    // zero balances and serialised products are left out, the grain is counted per lot
+    assert_eq!(
+        found,
+        vec![
+            expected_quantity(NUT_ID, BIN_A_ID, None, -2),
+            expected_quantity(GRAIN_ID, BIN_A_ID, Some(LOT_ID), 9),
+            expected_quantity(BOLT_ID, BIN_B_ID, None, 4),
+        ]
+    );
+    let found = repo
+        .find_expected_quantities(&mut uow, &[SOUTH_BIN_ID])
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        vec![expected_quantity(BOLT_ID, SOUTH_BIN_ID, None, 8)]
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn filters_sorts_and_pages_counts<R: CountRepository>(repo: &R) {
+    let first = count("CC-2", None, CountStatus::Open, 0);
+    let second = StockCount {
+        completed_at: Some(at(3)),
+        ..count("CC-1", Some(BIN_B_ID), CountStatus::Completed, 1)
+    };
+    let third = StockCount {
+        warehouse_id: SOUTH_ID,
+        ..count("OTHER-1", None, CountStatus::Open, 2)
+    };
+    seed_counts(repo, &[(first, vec![]), (second, vec![]), (third, vec![])]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (None, None, None, 10, 0, vec!["CC-2", "CC-1", "OTHER-1"]),
+        (
+            None,
+            None,
+            Some("number"),
+            10,
+            0,
+            vec!["CC-1", "CC-2", "OTHER-1"],
+        ),
+        (None, None, Some("-created_at"), 2, 1, vec!["CC-1", "CC-2"]),
+        (
+            Some(CountStatus::Open),
+            None,
+            None,
+            10,
+            0,
+            vec!["CC-2", "OTHER-1"],
+        ),
+        (
+            None,
+            Some("cc-"),
+            Some("-number"),
+            10,
+            0,
+            vec!["CC-2", "CC-1"],
+        ),
+    ];
+    for (status, search, sort, per_page, offset, expected) in cases {
+        let query = query(search, sort, per_page, offset);
+        let found = repo
+            .find_counts(&mut uow, &filter(status), &query)
+            .await
+            .unwrap();
+        assert_eq!(numbers(&found), expected, "{status:?} {search:?} {sort:?}");
+    }
+
+    let south = StockCountFilter {
+        warehouse_id: Some(SOUTH_ID),
+        ..filter(None)
+    };
+    let count = repo
+        .count_counts(&mut uow, &south, &query(None, None, 1, 0))
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    let globex = StockCountFilter {
+        company_id: GLOBEX_ID,
+        ..filter(None)
+    };
+    let count = repo
+        .count_counts(&mut uow, &globex, &query(None, None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+    uow.rollback().await.unwrap();
+}
+
+async fn enforces_count_constraints<R: CountRepository>(repo: &R) {
+    let stored = count("CC-1", None, CountStatus::Open, 0);
+    let stored_line = line(&stored, 1, BOLT_ID, 1);
+    seed_counts(repo, &[(stored.clone(), vec![stored_line.clone()])]).await;
+
+    let cases = [
+        (
+            count("CC-1", None, CountStatus::Open, 1),
+            "stock_counts_company_id_number_key",
+        ),
+        (
+            StockCount {
+                warehouse_id: GLOBEX_MAIN_ID,
+                ..count("CC-2", None, CountStatus::Open, 1)
+            },
+            "stock_counts_warehouse_fkey",
+        ),
+        (
+            count("CC-2", Some(Uuid::new_v4()), CountStatus::Open, 1),
+            "stock_counts_location_id_fkey",
+        ),
+    ];
+    for (count, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_count(&mut uow, count).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let cases = [
+        (
+            line(&stored, 1, NUT_ID, 1),
+            "stock_count_lines_count_id_position_key",
+        ),
+        (
+            line(&stored, 2, BOLT_ID, 1),
+            "stock_count_lines_count_id_product_id_location_id_lot_id_key",
+        ),
+        (
+            line(&stored, 2, Uuid::new_v4(), 1),
+            "stock_count_lines_product_id_fkey",
+        ),
+        (
+            StockCountLine {
+                lot_id: Some(Uuid::new_v4()),
+                ..line(&stored, 2, NUT_ID, 1)
+            },
+            "stock_count_lines_lot_id_fkey",
+        ),
+    ];
+    for (line, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo
+            .create_count_lines(&mut uow, vec![line])
+            .await
+            .unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let mut uow = repo.begin().await.unwrap();
+    let unposted = StockCountLine {
+        movement_id: Some(Uuid::new_v4()),
+        ..stored_line
+    };
+    let err = repo
+        .update_count_line(&mut uow, unposted)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_count_lines_movement_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let entry = StockCountEntry {
+        id: Uuid::new_v4(),
+        count_id: stored.id,
+        line_id: Uuid::new_v4(),
+        quantity: 1,
+        counted_by: Uuid::from_u128(99),
+        counted_at: at(1),
+    };
+    let err = repo.create_count_entry(&mut uow, entry).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_count_entries_line_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
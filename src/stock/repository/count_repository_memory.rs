@@ -0,0 +1,398 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::app_database::memory::{foreign_key_violation, ilike, unique_violation};
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::count::{
+    ExpectedQuantity, StockCount, StockCountEntry, StockCountFilter, StockCountLine,
+};
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::helper_query::STOCK_COUNT_SORT_COLUMNS;
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::repository::stock_repository_memory::{
+    StockRepositoryMemory, compare_by, compare_nullable, page, sort_keys,
+};
+
+// mirrors the CountRepository part of StockRepositorySqlx, the warehouse and
+// location keys are checked against the committed warehouses
+impl StockRepositoryMemory {
+    // the location and every location above it, empty when it does not exist
+    pub(crate) async fn location_ancestors(&self, location_id: &Uuid) -> Vec<Uuid> {
+        let locations = self.warehouses.tables().await.locations;
+        let mut ancestors = Vec::new();
+        let mut next = Some(*location_id);
+        while let Some(id) = next {
+            let Some(location) = locations.iter().find(|l| l.id == id) else {
+                break;
+            };
+            if ancestors.contains(&location.id) {
+                break;
+            }
+            ancestors.push(location.id);
+            next = location.parent_id;
+        }
+        ancestors
+    }
+
+    async fn check_count_references(&self, count: &StockCount) -> Result<(), sqlx::Error> {
+        if !self.company_exists(&count.company_id).await {
+            return Err(foreign_key_violation("stock_counts_company_id_fkey"));
+        }
+        if self.warehouse_company(&count.warehouse_id).await != Some(count.company_id) {
+            return Err(foreign_key_violation("stock_counts_warehouse_fkey"));
+        }
+        if let Some(location_id) = &count.location_id
+            && self.location_warehouse(location_id).await.is_none()
+        {
+            return Err(foreign_key_violation("stock_counts_location_id_fkey"));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CountRepository for StockRepositoryMemory {
+    async fn check_existing_count_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .counts
+            .iter()
+            .any(|c| c.company_id == *company_id && c.number == number))
+    }
+
+    async fn create_count(
+        &self,
+        uow: &mut Self::Uow,
+        count: StockCount,
+    ) -> Result<StockCount, sqlx::Error> {
+        let counts = &uow.state().counts;
+        if counts.iter().any(|c| c.id == count.id) {
+            return Err(unique_violation("stock_counts_pkey"));
+        }
+        if counts
+            .iter()
+            .any(|c| c.company_id == count.company_id && c.number == count.number)
+        {
+            return Err(unique_violation("stock_counts_company_id_number_key"));
+        }
+        self.check_count_references(&count).await?;
+        uow.state_mut().counts.push(count.clone());
+
+        Ok(count)
+    }
+
+    async fn update_count(
+        &self,
+        uow: &mut Self::Uow,
+        count: StockCount,
+    ) -> Result<StockCount, sqlx::Error> {
+        let stored = uow
+            .state_mut()
+            .counts
+            .iter_mut()
+            .find(|c| c.id == count.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        stored.status = count.status;
+        stored.submitted_at = count.submitted_at;
+        stored.approved_by = count.approved_by;
+        stored.approved_at = count.approved_at;
+        stored.completed_at = count.completed_at;
+        stored.cancelled_at = count.cancelled_at;
+
+        Ok(count)
+    }
+
+    async fn get_count_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockCount>, sqlx::Error> {
+        Ok(uow.state().counts.iter().find(|c| c.id == *id).cloned())
+    }
+
+    async fn lock_count(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockCount>, sqlx::Error> {
+        self.get_count_by_id(uow, id).await
+    }
+
+    async fn lock_warehouse_company(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        self.get_warehouse_company(uow, warehouse_id).await
+    }
+
+    async fn count_counts(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_counts(&uow.state().counts, filter, &query.search).len() as i64)
+    }
+
+    async fn find_counts(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockCount>, sqlx::Error> {
+        let keys = sort_keys(query, &STOCK_COUNT_SORT_COLUMNS)?;
+        let mut counts: Vec<StockCount> = filter_counts(&uow.state().counts, filter, &query.search)
+            .into_iter()
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            counts.sort_by_key(|c| (c.created_at, c.id));
+        } else {
+            counts.sort_by(|a, b| compare_by(&keys, |column| compare_count(a, b, column)));
+        }
+
+        Ok(page(counts, query))
+    }
+
+    async fn find_active_counts(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Vec<StockCount>, sqlx::Error> {
+        let mut counts: Vec<StockCount> = uow
+            .state()
+            .counts
+            .iter()
+            .filter(|c| c.warehouse_id == *warehouse_id && c.status.is_active())
+            .cloned()
+            .collect();
+        counts.sort_by_key(|c| (c.created_at, c.id));
+
+        Ok(counts)
+    }
+
+    async fn find_count_locations(
+        &self,
+        _uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        location_id: Option<&Uuid>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let mut location_ids = Vec::new();
+        for id in self.warehouse_locations(warehouse_id).await {
+            let ancestors = self.location_ancestors(&id).await;
+            if location_id.is_none_or(|location_id| ancestors.contains(location_id)) {
+                location_ids.push(id);
+            }
+        }
+        location_ids.sort();
+
+        Ok(location_ids)
+    }
+
+    async fn find_expected_quantities(
+        &self,
+        uow: &mut Self::Uow,
+        location_ids: &[Uuid],
+    ) -> Result<Vec<ExpectedQuantity>, sqlx::Error> {
+        let mut quantities = Vec::new();
+        let tables = uow.state();
+        for balance in tables
+            .balances
+            .iter()
+            .filter(|b| location_ids.contains(&b.location_id) && b.quantity != 0)
+        {
+            if self.product_lot_tracked(&balance.product_id).await
+                || self.product_serialised(&balance.product_id).await
+            {
+                continue;
+            }
+            quantities.push(ExpectedQuantity {
+                product_id: balance.product_id,
+                location_id: balance.location_id,
+                lot_id: None,
+                quantity: balance.quantity,
+            });
+        }
+        for balance in tables
+            .lot_balances
+            .iter()
+            .filter(|b| location_ids.contains(&b.location_id) && b.quantity != 0)
+        {
+            if self.product_serialised(&balance.product_id).await {
+                continue;
+            }
+            quantities.push(ExpectedQuantity {
+                product_id: balance.product_id,
+                location_id: balance.location_id,
+                lot_id: Some(balance.lot_id),
+                quantity: balance.quantity,
+            });
+        }
+        quantities.sort_by_key(|q| (q.location_id, q.product_id, q.lot_id));
+
+        Ok(quantities)
+    }
+
+    async fn find_count_lines(
+        &self,
+        uow: &mut Self::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountLine>, sqlx::Error> {
+        let mut lines: Vec<StockCountLine> = uow
+            .state()
+            .count_lines
+            .iter()
+            .filter(|l| l.count_id == *count_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn create_count_lines(
+        &self,
+        uow: &mut Self::Uow,
+        lines: Vec<StockCountLine>,
+    ) -> Result<Vec<StockCountLine>, sqlx::Error> {
+        for line in &lines {
+            let tables = uow.state();
+            if tables.count_lines.iter().any(|l| l.id == line.id) {
+                return Err(unique_violation("stock_count_lines_pkey"));
+            }
+            if tables
+                .count_lines
+                .iter()
+                .any(|l| l.count_id == line.count_id && l.position == line.position)
+            {
+                return Err(unique_violation("stock_count_lines_count_id_position_key"));
+            }
+            if tables.count_lines.iter().any(|l| {
+                l.count_id == line.count_id
+                    && l.product_id == line.product_id
+                    && l.location_id == line.location_id
+                    && l.lot_id == line.lot_id
+            }) {
+                return Err(unique_violation(
+                    "stock_count_lines_count_id_product_id_location_id_lot_id_key",
+                ));
+            }
+            if !tables.counts.iter().any(|c| c.id == line.count_id) {
+                return Err(foreign_key_violation("stock_count_lines_count_id_fkey"));
+            }
+            if let Some(lot_id) = line.lot_id
+                && !tables.lots.iter().any(|l| l.id == lot_id)
+            {
+                return Err(foreign_key_violation("stock_count_lines_lot_id_fkey"));
+            }
+            if self.product_company(&line.product_id).await.is_none() {
+                return Err(foreign_key_violation("stock_count_lines_product_id_fkey"));
+            }
+            if self.location_warehouse(&line.location_id).await.is_none() {
+                return Err(foreign_key_violation("stock_count_lines_location_id_fkey"));
+            }
+            uow.state_mut().count_lines.push(line.clone());
+        }
+
+        Ok(lines)
+    }
+
+    async fn update_count_line(
+        &self,
+        uow: &mut Self::Uow,
+        line: StockCountLine,
+    ) -> Result<StockCountLine, sqlx::Error> {
+        if let Some(movement_id) = line.movement_id
+            && !uow.state().movements.iter().any(|m| m.id == movement_id)
+        {
+            return Err(foreign_key_violation("stock_count_lines_movement_id_fkey"));
+        }
+        let stored = uow
+            .state_mut()
+            .count_lines
+            .iter_mut()
+            .find(|l| l.id == line.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        stored.expected_quantity = line.expected_quantity;
+        stored.counted_quantity = line.counted_quantity;
+        stored.counted_by = line.counted_by;
+        stored.counted_at = line.counted_at;
+        stored.movement_id = line.movement_id;
+
+        Ok(line)
+    }
+
+    async fn create_count_entry(
+        &self,
+        uow: &mut Self::Uow,
+        entry: StockCountEntry,
+    ) -> Result<StockCountEntry, sqlx::Error> {
+        let tables = uow.state();
+        if tables.count_entries.iter().any(|e| e.id == entry.id) {
+            return Err(unique_violation("stock_count_entries_pkey"));
+        }
+        if !tables.counts.iter().any(|c| c.id == entry.count_id) {
+            return Err(foreign_key_violation("stock_count_entries_count_id_fkey"));
+        }
+        if !tables.count_lines.iter().any(|l| l.id == entry.line_id) {
+            return Err(foreign_key_violation("stock_count_entries_line_id_fkey"));
+        }
+        uow.state_mut().count_entries.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    async fn find_count_entries(
+        &self,
+        uow: &mut Self::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountEntry>, sqlx::Error> {
+        let mut entries: Vec<StockCountEntry> = uow
+            .state()
+            .count_entries
+            .iter()
+            .filter(|e| e.count_id == *count_id)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| (e.counted_at, e.id));
+
+        Ok(entries)
+    }
+}
+
+// see helper_query::apply_count_filter
+fn filter_counts<'a>(
+    counts: &'a [StockCount],
+    filter: &StockCountFilter,
+    search: &Option<String>,
+) -> Vec<&'a StockCount> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    counts
+        .iter()
+        .filter(|c| c.company_id == filter.company_id)
+        .filter(|c| filter.warehouse_id.is_none_or(|id| c.warehouse_id == id))
+        .filter(|c| filter.status.is_none_or(|status| c.status == status))
+        .filter(|c| pattern.as_ref().is_none_or(|p| ilike(&c.number, p)))
+        .collect()
+}
+
+fn compare_count(a: &StockCount, b: &StockCount, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "number" => a.number.cmp(&b.number),
+        "warehouse_id" => a.warehouse_id.cmp(&b.warehouse_id),
+        "status" => a.status.as_str().cmp(b.status.as_str()),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "submitted_at" => compare_nullable(&a.submitted_at, &b.submitted_at),
+        "completed_at" => compare_nullable(&a.completed_at, &b.completed_at),
+        _ => Ordering::Equal,
+    }
+}
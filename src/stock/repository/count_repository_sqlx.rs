@@ -0,0 +1,442 @@
+use async_trait::async_trait;
+use sqlx::QueryBuilder;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::count::{
+    CountStatus, ExpectedQuantity, StockCount, StockCountEntry, StockCountFilter, StockCountLine,
+};
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::helper_query::{STOCK_COUNT_DEFAULT_ORDER, apply_count_filter};
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+
+#[async_trait]
+impl CountRepository for StockRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_count_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM stock_counts WHERE company_id = $1 AND number = $2
+            )
+            "#,
+            company_id,
+            number,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %count.id))]
+    async fn create_count(
+        &self,
+        uow: &mut Self::Uow,
+        count: StockCount,
+    ) -> Result<StockCount, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_counts
+            (id, company_id, number, warehouse_id, location_id, status, is_blind,
+             approval_threshold, note, created_by, created_at, submitted_at, approved_by,
+             approved_at, completed_at, cancelled_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#,
+            count.id,
+            count.company_id,
+            count.number,
+            count.warehouse_id,
+            count.location_id,
+            count.status.as_str(),
+            count.is_blind,
+            count.approval_threshold,
+            count.note,
+            count.created_by,
+            count.created_at,
+            count.submitted_at,
+            count.approved_by,
+            count.approved_at,
+            count.completed_at,
+            count.cancelled_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %count.id))]
+    async fn update_count(
+        &self,
+        uow: &mut Self::Uow,
+        count: StockCount,
+    ) -> Result<StockCount, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_counts
+            SET status = $1,
+                submitted_at = $2,
+                approved_by = $3,
+                approved_at = $4,
+                completed_at = $5,
+                cancelled_at = $6
+            WHERE id = $7
+            RETURNING id
+            "#,
+            count.status.as_str(),
+            count.submitted_at,
+            count.approved_by,
+            count.approved_at,
+            count.completed_at,
+            count.cancelled_at,
+            count.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %id))]
+    async fn get_count_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockCount>, sqlx::Error> {
+        let count = sqlx::query_as!(
+            StockCount,
+            r#"
+            SELECT id, company_id, number, warehouse_id, location_id,
+                   status AS "status: CountStatus", is_blind, approval_threshold, note,
+                   created_by, created_at, submitted_at, approved_by, approved_at,
+                   completed_at, cancelled_at
+            FROM stock_counts
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %id))]
+    async fn lock_count(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockCount>, sqlx::Error> {
+        let count = sqlx::query_as!(
+            StockCount,
+            r#"
+            SELECT id, company_id, number, warehouse_id, location_id,
+                   status AS "status: CountStatus", is_blind, approval_threshold, note,
+                   created_by, created_at, submitted_at, approved_by, approved_at,
+                   completed_at, cancelled_at
+            FROM stock_counts
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id))]
+    async fn lock_warehouse_company(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let company_id = sqlx::query_scalar!(
+            r#"SELECT company_id FROM warehouses WHERE id = $1 FOR UPDATE"#,
+            warehouse_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(company_id)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_counts(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM stock_counts");
+
+        apply_count_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_counts(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockCount>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, number, warehouse_id, location_id, status, is_blind,
+                   approval_threshold, note, created_by, created_at, submitted_at,
+                   approved_by, approved_at, completed_at, cancelled_at
+            FROM stock_counts
+        ",
+        );
+
+        apply_count_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_COUNT_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let counts = qb
+            .build_query_as::<StockCount>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id))]
+    async fn find_active_counts(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Vec<StockCount>, sqlx::Error> {
+        let counts = sqlx::query_as!(
+            StockCount,
+            r#"
+            SELECT id, company_id, number, warehouse_id, location_id,
+                   status AS "status: CountStatus", is_blind, approval_threshold, note,
+                   created_by, created_at, submitted_at, approved_by, approved_at,
+                   completed_at, cancelled_at
+            FROM stock_counts
+            WHERE warehouse_id = $1 AND status IN ('open', 'pending_approval')
+            ORDER BY created_at, id
+            "#,
+            warehouse_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id))]
+    async fn find_count_locations(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        location_id: Option<&Uuid>,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let location_ids = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE scope AS (
+                SELECT id FROM warehouse_locations
+                WHERE warehouse_id = $1 AND ($2::uuid IS NULL OR id = $2)
+                UNION
+                SELECT l.id
+                FROM warehouse_locations l
+                JOIN scope s ON l.parent_id = s.id
+            )
+            SELECT id AS "id!" FROM scope ORDER BY id
+            "#,
+            warehouse_id,
+            location_id,
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(location_ids)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(locations = location_ids.len()))]
+    async fn find_expected_quantities(
+        &self,
+        uow: &mut Self::Uow,
+        location_ids: &[Uuid],
+    ) -> Result<Vec<ExpectedQuantity>, sqlx::Error> {
+        let quantities = sqlx::query_as!(
+            ExpectedQuantity,
+            r#"
+            SELECT b.product_id AS "product_id!", b.location_id AS "location_id!",
+                   NULL::uuid AS lot_id, b.quantity AS "quantity!"
+            FROM stock_balances b
+            JOIN products p ON p.id = b.product_id
+            WHERE b.location_id = ANY($1) AND b.quantity <> 0
+              AND NOT p.is_lot_tracked AND NOT p.is_serialised
+            UNION ALL
+            SELECT lb.product_id, lb.location_id, lb.lot_id, lb.quantity
+            FROM stock_lot_balances lb
+            JOIN products p ON p.id = lb.product_id
+            WHERE lb.location_id = ANY($1) AND lb.quantity <> 0 AND NOT p.is_serialised
+            ORDER BY 2, 1, 3
+            "#,
+            location_ids
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(quantities)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %count_id))]
+    async fn find_count_lines(
+        &self,
+        uow: &mut Self::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            StockCountLine,
+            r#"
+            SELECT id, count_id, position, product_id, location_id, lot_id,
+                   expected_quantity, counted_quantity, counted_by, counted_at, movement_id
+            FROM stock_count_lines
+            WHERE count_id = $1
+            ORDER BY position
+            "#,
+            count_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lines = lines.len()))]
+    async fn create_count_lines(
+        &self,
+        uow: &mut Self::Uow,
+        lines: Vec<StockCountLine>,
+    ) -> Result<Vec<StockCountLine>, sqlx::Error> {
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO stock_count_lines
+                (id, count_id, position, product_id, location_id, lot_id, expected_quantity,
+                 counted_quantity, counted_by, counted_at, movement_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+                line.id,
+                line.count_id,
+                line.position,
+                line.product_id,
+                line.location_id,
+                line.lot_id,
+                line.expected_quantity,
+                line.counted_quantity,
+                line.counted_by,
+                line.counted_at,
+                line.movement_id,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %line.id))]
+    async fn update_count_line(
+        &self,
+        uow: &mut Self::Uow,
+        line: StockCountLine,
+    ) -> Result<StockCountLine, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_count_lines
+            SET expected_quantity = $1,
+                counted_quantity = $2,
+                counted_by = $3,
+                counted_at = $4,
+                movement_id = $5
+            WHERE id = $6
+            RETURNING id
+            "#,
+            line.expected_quantity,
+            line.counted_quantity,
+            line.counted_by,
+            line.counted_at,
+            line.movement_id,
+            line.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(line)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %entry.line_id))]
+    async fn create_count_entry(
+        &self,
+        uow: &mut Self::Uow,
+        entry: StockCountEntry,
+    ) -> Result<StockCountEntry, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_count_entries
+            (id, count_id, line_id, quantity, counted_by, counted_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            entry.id,
+            entry.count_id,
+            entry.line_id,
+            entry.quantity,
+            entry.counted_by,
+            entry.counted_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(entry)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(count_id = %count_id))]
+    async fn find_count_entries(
+        &self,
+        uow: &mut Self::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountEntry>, sqlx::Error> {
+        let entries = sqlx::query_as!(
+            StockCountEntry,
+            r#"
+            SELECT id, count_id, line_id, quantity, counted_by, counted_at
+            FROM stock_count_entries
+            WHERE count_id = $1
+            ORDER BY counted_at, id
+            "#,
+            count_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,172 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::stock::domain::balance::StockBalanceFilter;
+use crate::stock::domain::count::StockCountFilter;
+use crate::stock::domain::lot::StockLotFilter;
+use crate::stock::domain::movement::StockMovementFilter;
+use crate::stock::domain::serial::StockSerialFilter;
+use crate::stock::domain::transfer::StockTransferFilter;
+
+// sortable columns, sort is pushed into ORDER BY as written so the handler
+// rejects anything else
+pub const STOCK_MOVEMENT_SORT_COLUMNS: [&str; 9] = [
+    "id",
+    "product_id",
+    "location_id",
+    "movement_type",
+    "quantity",
+    "balance_after",
+    "reference",
+    "posted_by",
+    "posted_at",
+];
+
+pub const STOCK_BALANCE_SORT_COLUMNS: [&str; 4] =
+    ["product_id", "location_id", "quantity", "updated_at"];
+
+pub const STOCK_LOT_SORT_COLUMNS: [&str; 6] = [
+    "product_id",
+    "lot_number",
+    "manufactured_on",
+    "expires_on",
+    "created_at",
+    "id",
+];
+
+pub const STOCK_SERIAL_SORT_COLUMNS: [&str; 7] = [
+    "product_id",
+    "serial_number",
+    "status",
+    "location_id",
+    "created_at",
+    "updated_at",
+    "id",
+];
+
+pub const STOCK_TRANSFER_SORT_COLUMNS: [&str; 8] = [
+    "id",
+    "number",
+    "source_warehouse_id",
+    "destination_warehouse_id",
+    "status",
+    "created_at",
+    "dispatched_at",
+    "received_at",
+];
+
+pub const STOCK_COUNT_SORT_COLUMNS: [&str; 7] = [
+    "id",
+    "number",
+    "warehouse_id",
+    "status",
+    "created_at",
+    "submitted_at",
+    "completed_at",
+];
+
+// without a sort the ledger reads in posting order
+pub const STOCK_MOVEMENT_DEFAULT_ORDER: &str = "posted_at, id";
+pub const STOCK_BALANCE_DEFAULT_ORDER: &str = "product_id, location_id";
+pub const STOCK_TRANSFER_DEFAULT_ORDER: &str = "created_at, id";
+pub const STOCK_COUNT_DEFAULT_ORDER: &str = "created_at, id";
+pub const STOCK_LOT_DEFAULT_ORDER: &str = "product_id, lot_number";
+pub const STOCK_SERIAL_DEFAULT_ORDER: &str = "product_id, serial_number";
+
+pub fn apply_movement_filter(qb: &mut QueryBuilder<Postgres>, filter: &StockMovementFilter) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(product_id) = filter.product_id {
+        qb.push(" AND product_id = ").push_bind(product_id);
+    }
+    if let Some(location_id) = filter.location_id {
+        qb.push(" AND location_id = ").push_bind(location_id);
+    }
+    if let Some(movement_type) = filter.movement_type {
+        qb.push(" AND movement_type = ")
+            .push_bind(movement_type.as_str());
+    }
+    if let Some(lot_id) = filter.lot_id {
+        qb.push(" AND lot_id = ").push_bind(lot_id);
+    }
+}
+
+pub fn apply_balance_filter(qb: &mut QueryBuilder<Postgres>, filter: &StockBalanceFilter) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(product_id) = filter.product_id {
+        qb.push(" AND product_id = ").push_bind(product_id);
+    }
+    if let Some(warehouse_id) = filter.warehouse_id {
+        qb.push(" AND location_id IN (SELECT id FROM warehouse_locations WHERE warehouse_id = ")
+            .push_bind(warehouse_id)
+            .push(")");
+    }
+    if let Some(location_id) = filter.location_id {
+        qb.push(" AND location_id = ").push_bind(location_id);
+    }
+}
+
+pub fn apply_transfer_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &StockTransferFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(s) = search {
+        qb.push(" AND number ILIKE ").push_bind(format!("%{s}%"));
+    }
+}
+
+pub fn apply_count_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &StockCountFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(warehouse_id) = filter.warehouse_id {
+        qb.push(" AND warehouse_id = ").push_bind(warehouse_id);
+    }
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(s) = search {
+        qb.push(" AND number ILIKE ").push_bind(format!("%{s}%"));
+    }
+}
+
+pub fn apply_lot_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &StockLotFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(product_id) = filter.product_id {
+        qb.push(" AND product_id = ").push_bind(product_id);
+    }
+    if let Some(s) = search {
+        qb.push(" AND lot_number ILIKE ")
+            .push_bind(format!("%{s}%"));
+    }
+}
+
+pub fn apply_serial_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    filter: &StockSerialFilter,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(filter.company_id);
+    if let Some(product_id) = filter.product_id {
+        qb.push(" AND product_id = ").push_bind(product_id);
+    }
+    if let Some(location_id) = filter.location_id {
+        qb.push(" AND location_id = ").push_bind(location_id);
+    }
+    if let Some(status) = filter.status {
+        qb.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(s) = search {
+        qb.push(" AND serial_number ILIKE ")
+            .push_bind(format!("%{s}%"));
+    }
+}
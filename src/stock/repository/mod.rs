@@ -0,0 +1,19 @@
+pub mod stock_repository;
+pub mod stock_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod stock_repository_memory;
+#[cfg(test)]
+mod stock_repository_contract_test;
+pub mod transfer_repository;
+pub mod transfer_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod transfer_repository_memory;
+#[cfg(test)]
+mod transfer_repository_contract_test;
+pub mod count_repository;
+pub mod count_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod count_repository_memory;
+#[cfg(test)]
+mod count_repository_contract_test;
+pub mod helper_query;
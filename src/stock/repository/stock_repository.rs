@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product_unit::ProductUnit;
+use crate::stock::domain::balance::{BalanceMismatch, StockBalance, StockBalanceFilter};
+use crate::stock::domain::lot::{ExpiringLot, StockLot, StockLotBalance, StockLotFilter};
+use crate::stock::domain::movement::{StockMovement, StockMovementFilter};
+use crate::stock::domain::serial::{SerialEvent, SerialMismatch, StockSerial, StockSerialFilter};
+use crate::stock::domain::settings::StockSettings;
+
+// the ledger is append only, there is no way to update or delete a movement
+#[async_trait]
+pub trait StockRepository: Transactional {
+    async fn check_existing_company(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<bool, sqlx::Error>;
+    // company owning the product, None when the product does not exist
+    async fn get_product_company(&self, uow: &mut Self::Uow, product_id: &Uuid) -> Result<Option<Uuid>, sqlx::Error>;
+    // unit_of_measure of the product, None when the product does not exist
+    async fn get_product_base_unit(&self, uow: &mut Self::Uow, product_id: &Uuid) -> Result<Option<String>, sqlx::Error>;
+    // one of the units of the product besides its base unit
+    async fn get_product_unit(&self, uow: &mut Self::Uow, product_id: &Uuid, code: &str) -> Result<Option<ProductUnit>, sqlx::Error>;
+    // company owning the warehouse of the location, None when the location does not exist
+    async fn get_location_company(&self, uow: &mut Self::Uow, location_id: &Uuid) -> Result<Option<Uuid>, sqlx::Error>;
+    // company owning the warehouse, None when the warehouse does not exist
+    async fn get_warehouse_company(&self, uow: &mut Self::Uow, warehouse_id: &Uuid) -> Result<Option<Uuid>, sqlx::Error>;
+    // warehouse of the location, None when the location does not exist
+    async fn get_location_warehouse(&self, uow: &mut Self::Uow, location_id: &Uuid) -> Result<Option<Uuid>, sqlx::Error>;
+    // true while an open or pending count takes stock of the location, every
+    // posting is refused then
+    async fn is_location_counted(&self, uow: &mut Self::Uow, location_id: &Uuid) -> Result<bool, sqlx::Error>;
+    async fn get_stock_settings(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<Option<StockSettings>, sqlx::Error>;
+    async fn save_stock_settings(&self, uow: &mut Self::Uow, settings: StockSettings) -> Result<StockSettings, sqlx::Error>;
+    // current quantity, the row is created at 0 when missing and stays locked
+    // until the unit of work finishes so concurrent postings queue up
+    async fn lock_balance(&self, uow: &mut Self::Uow, company_id: &Uuid, product_id: &Uuid, location_id: &Uuid) -> Result<i64, sqlx::Error>;
+    async fn update_balance(&self, uow: &mut Self::Uow, product_id: &Uuid, location_id: &Uuid, quantity: i64, updated_at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+    // sum of the product's balances over the locations of the warehouse, less
+    // the lots expired before today since they are never picked
+    async fn get_warehouse_usable(&self, uow: &mut Self::Uow, product_id: &Uuid, warehouse_id: &Uuid, today: NaiveDate) -> Result<i64, sqlx::Error>;
+    // quantity reserved for sales orders, the row is created at 0 when missing
+    // and stays locked until the unit of work finishes. it is locked before the
+    // balances, by reservations and by postings taking stock away alike
+    async fn lock_reservation(&self, uow: &mut Self::Uow, company_id: &Uuid, product_id: &Uuid, warehouse_id: &Uuid) -> Result<i64, sqlx::Error>;
+    async fn count_balances(&self, uow: &mut Self::Uow, filter: &StockBalanceFilter) -> Result<i64, sqlx::Error>;
+    async fn find_balances(&self, uow: &mut Self::Uow, filter: &StockBalanceFilter, query: &PaginationRequest) -> Result<Vec<StockBalance>, sqlx::Error>;
+    // balances that differ from the sum of their ledger, ordered by product and location
+    async fn find_balance_mismatches(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<Vec<BalanceMismatch>, sqlx::Error>;
+    async fn create_movement(&self, uow: &mut Self::Uow, movement: StockMovement) -> Result<StockMovement, sqlx::Error>;
+    async fn get_movement_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockMovement>, sqlx::Error>;
+    async fn find_reversal(&self, uow: &mut Self::Uow, movement_id: &Uuid) -> Result<Option<StockMovement>, sqlx::Error>;
+    async fn count_movements(&self, uow: &mut Self::Uow, filter: &StockMovementFilter) -> Result<i64, sqlx::Error>;
+    async fn find_movements(&self, uow: &mut Self::Uow, filter: &StockMovementFilter, query: &PaginationRequest) -> Result<Vec<StockMovement>, sqlx::Error>;
+    // false when the product does not exist
+    async fn is_product_lot_tracked(&self, uow: &mut Self::Uow, product_id: &Uuid) -> Result<bool, sqlx::Error>;
+    async fn check_existing_lot_number(&self, uow: &mut Self::Uow, product_id: &Uuid, lot_number: &str) -> Result<bool, sqlx::Error>;
+    async fn create_lot(&self, uow: &mut Self::Uow, lot: StockLot) -> Result<StockLot, sqlx::Error>;
+    async fn get_lot_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockLot>, sqlx::Error>;
+    async fn count_lots(&self, uow: &mut Self::Uow, filter: &StockLotFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_lots(&self, uow: &mut Self::Uow, filter: &StockLotFilter, query: &PaginationRequest) -> Result<Vec<StockLot>, sqlx::Error>;
+    // like lock_balance for the quantity of one lot at the location
+    async fn lock_lot_balance(&self, uow: &mut Self::Uow, company_id: &Uuid, lot_id: &Uuid, product_id: &Uuid, location_id: &Uuid) -> Result<i64, sqlx::Error>;
+    async fn update_lot_balance(&self, uow: &mut Self::Uow, lot_id: &Uuid, location_id: &Uuid, quantity: i64, updated_at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+    // ordered by location
+    async fn find_lot_balances(&self, uow: &mut Self::Uow, lot_id: &Uuid) -> Result<Vec<StockLotBalance>, sqlx::Error>;
+    // lots expiring on or before the date with stock left, in the warehouse
+    // when given, ordered by expiry date and lot number
+    async fn find_expiring_lots(&self, uow: &mut Self::Uow, company_id: &Uuid, expires_on_or_before: NaiveDate, warehouse_id: Option<&Uuid>) -> Result<Vec<ExpiringLot>, sqlx::Error>;
+    // false when the product does not exist
+    async fn is_product_serialised(&self, uow: &mut Self::Uow, product_id: &Uuid) -> Result<bool, sqlx::Error>;
+    // the serial stays locked until the unit of work finishes, None when the
+    // product has no serial with the number yet
+    async fn lock_serial(&self, uow: &mut Self::Uow, product_id: &Uuid, serial_number: &str) -> Result<Option<StockSerial>, sqlx::Error>;
+    async fn create_serial(&self, uow: &mut Self::Uow, serial: StockSerial) -> Result<StockSerial, sqlx::Error>;
+    // stores status, location_id and updated_at
+    async fn update_serial(&self, uow: &mut Self::Uow, serial: StockSerial) -> Result<StockSerial, sqlx::Error>;
+    async fn create_movement_serials(&self, uow: &mut Self::Uow, movement_id: &Uuid, serial_ids: &[Uuid]) -> Result<(), sqlx::Error>;
+    // ordered by serial number
+    async fn find_movement_serial_numbers(&self, uow: &mut Self::Uow, movement_id: &Uuid) -> Result<Vec<String>, sqlx::Error>;
+    async fn get_serial_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockSerial>, sqlx::Error>;
+    async fn count_serials(&self, uow: &mut Self::Uow, filter: &StockSerialFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_serials(&self, uow: &mut Self::Uow, filter: &StockSerialFilter, query: &PaginationRequest) -> Result<Vec<StockSerial>, sqlx::Error>;
+    // the movements of the serial in posting order
+    async fn find_serial_events(&self, uow: &mut Self::Uow, serial_id: &Uuid) -> Result<Vec<SerialEvent>, sqlx::Error>;
+    // balances of serialised products that differ from their count of serials
+    // in stock, ordered by product and location
+    async fn find_serial_mismatches(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<Vec<SerialMismatch>, sqlx::Error>;
+}
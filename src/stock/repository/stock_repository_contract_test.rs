@@ -0,0 +1,1092 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// company_repository_contract_test. both start with the companies ACM and GLB,
+// two ACM products stocked in two bins of one warehouse and one GLB product
+// and bin, next to lot tracked milk and serialised laptops of ACM. postgres runs need TEST_DATABASE_URL and are skipped without it.
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{
+    ACME_ID, Catalogue, GLOBEX_ID, at, bin, companies, product, seed_catalogue, seed_companies,
+    warehouse,
+};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::product::usecase::product_usecase::{ProductUsecase, ProductUsecaseError};
+use crate::stock::domain::balance::{BalanceMismatch, StockBalance, StockBalanceFilter};
+use crate::stock::domain::lot::{StockLot, StockLotFilter};
+use crate::stock::domain::movement::{
+    MovementSource, MovementType, StockMovement, StockMovementFilter,
+};
+use crate::stock::domain::serial::{SerialMismatch, SerialStatus, StockSerial, StockSerialFilter};
+use crate::stock::domain::settings::StockSettings;
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::stock::usecase::dto::MovementInput;
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+const BOLT_ID: Uuid = Uuid::from_u128(11);
+const NUT_ID: Uuid = Uuid::from_u128(12);
+const GEAR_ID: Uuid = Uuid::from_u128(13);
+const MILK_ID: Uuid = Uuid::from_u128(14);
+const LAPTOP_ID: Uuid = Uuid::from_u128(15);
+const MAIN_ID: Uuid = Uuid::from_u128(21);
+const GLOBEX_MAIN_ID: Uuid = Uuid::from_u128(22);
+const BIN_A_ID: Uuid = Uuid::from_u128(31);
+const BIN_B_ID: Uuid = Uuid::from_u128(32);
+const GLOBEX_BIN_ID: Uuid = Uuid::from_u128(33);
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                let products = super::ProductRepositoryMemory::new(companies.clone());
+                let warehouses = super::WarehouseRepositoryMemory::new(companies.clone());
+                super::seed_catalogue(&products, &warehouses, super::catalogue()).await;
+                super::$scenario(&super::StockRepositoryMemory::new(
+                    companies, products, warehouses,
+                ))
+                .await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::seed_catalogue(
+                    &super::ProductRepositorySqlx::new(db.pool.clone()),
+                    &super::WarehouseRepositorySqlx::new(db.pool.clone()),
+                    super::catalogue(),
+                )
+                .await;
+                super::$scenario(&super::StockRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(resolves_owning_companies);
+contract_test!(stores_stock_settings);
+contract_test!(locks_and_updates_balances);
+contract_test!(filters_sorts_and_pages_balances);
+contract_test!(stores_and_lists_movements);
+contract_test!(enforces_movement_constraints);
+contract_test!(reports_balance_mismatches);
+contract_test!(stores_lots_and_lot_balances);
+contract_test!(stores_serials_and_their_movements);
+
+fn catalogue() -> Catalogue {
+    Catalogue {
+        products: vec![
+            product(BOLT_ID, ACME_ID, "BOLT"),
+            product(NUT_ID, ACME_ID, "NUT"),
+            product(GEAR_ID, GLOBEX_ID, "GEAR"),
+            Product {
+                is_lot_tracked: true,
+                ..product(MILK_ID, ACME_ID, "MILK")
+            },
+            Product {
+                is_serialised: true,
+                ..product(LAPTOP_ID, ACME_ID, "LAPTOP")
+            },
+        ],
+        warehouses: vec![
+            warehouse(MAIN_ID, ACME_ID, "WH-1"),
+            warehouse(GLOBEX_MAIN_ID, GLOBEX_ID, "WH-1"),
+        ],
+        locations: vec![
+            bin(BIN_A_ID, MAIN_ID, "A"),
+            bin(BIN_B_ID, MAIN_ID, "B"),
+            bin(GLOBEX_BIN_ID, GLOBEX_MAIN_ID, "A"),
+        ],
+    }
+}
+
+fn movement(
+    product_id: Uuid,
+    location_id: Uuid,
+    movement_type: MovementType,
+    quantity: i64,
+    minutes: i64,
+) -> StockMovement {
+    let company_id = if product_id == GEAR_ID {
+        GLOBEX_ID
+    } else {
+        ACME_ID
+    };
+    StockMovement {
+        id: Uuid::new_v4(),
+        company_id,
+        product_id,
+        location_id,
+        movement_type,
+        quantity,
+        balance_after: quantity,
+        reversal_of: None,
+        source: MovementSource::Manual,
+        source_id: None,
+        lot_id: None,
+        reference: None,
+        note: None,
+        posted_by: Uuid::from_u128(99),
+        posted_at: at(minutes),
+    }
+}
+
+// an ACM milk lot expiring on the day of january 2026 when given
+fn lot(number: &str, expires_on: Option<u32>) -> StockLot {
+    StockLot {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        product_id: MILK_ID,
+        lot_number: number.into(),
+        manufactured_on: None,
+        expires_on: expires_on.map(|day| NaiveDate::from_ymd_opt(2026, 1, day).unwrap()),
+        created_at: at(0),
+    }
+}
+
+// an ACM laptop, in stock when given a location
+fn serial(number: &str, location_id: Option<Uuid>) -> StockSerial {
+    let status = match location_id {
+        Some(_) => SerialStatus::InStock,
+        None => SerialStatus::Issued,
+    };
+    StockSerial {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        product_id: LAPTOP_ID,
+        serial_number: number.into(),
+        status,
+        location_id,
+        created_at: at(0),
+        updated_at: at(0),
+    }
+}
+
+fn query(sort: Option<&str>, per_page: u32, offset: u32) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: None,
+        sort: sort.map(String::from),
+    }
+}
+
+fn balance_filter(company_id: Uuid) -> StockBalanceFilter {
+    StockBalanceFilter {
+        company_id,
+        product_id: None,
+        warehouse_id: None,
+        location_id: None,
+    }
+}
+
+fn movement_filter(company_id: Uuid) -> StockMovementFilter {
+    StockMovementFilter {
+        company_id,
+        product_id: None,
+        location_id: None,
+        movement_type: None,
+        lot_id: None,
+    }
+}
+
+// sets the balances directly, the usecase normally keeps them in step with the ledger
+async fn seed_balances<R: StockRepository>(repo: &R, balances: &[(Uuid, Uuid, i64, i64)]) {
+    let mut uow = repo.begin().await.unwrap();
+    for (product_id, location_id, quantity, minutes) in balances {
+        let company_id = if *product_id == GEAR_ID {
+            GLOBEX_ID
+        } else {
+            ACME_ID
+        };
+        repo.lock_balance(&mut uow, &company_id, product_id, location_id)
+            .await
+            .unwrap();
+        repo.update_balance(&mut uow, product_id, location_id, *quantity, at(*minutes))
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+async fn seed_movements<R: StockRepository>(repo: &R, movements: &[StockMovement]) {
+    let mut uow = repo.begin().await.unwrap();
+    for movement in movements {
+        repo.create_movement(&mut uow, movement.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn quantities(balances: &[StockBalance]) -> Vec<i64> {
+    balances.iter().map(|b| b.quantity).collect()
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn resolves_owning_companies<R: StockRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    assert!(
+        repo.check_existing_company(&mut uow, &ACME_ID)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap()
+    );
+
+    let products = [
+        (BOLT_ID, Some(ACME_ID)),
+        (GEAR_ID, Some(GLOBEX_ID)),
+        (Uuid::new_v4(), None),
+    ];
+    for (product_id, expected) in products {
+        let company_id = repo
+            .get_product_company(&mut uow, &product_id)
+            .await
+            .unwrap();
+        assert_eq!(company_id, expected, "{product_id}");
+    }
+
+    let locations = [
+        (BIN_B_ID, Some(ACME_ID)),
+        (GLOBEX_BIN_ID, Some(GLOBEX_ID)),
+        (Uuid::new_v4(), None),
+    ];
+    for (location_id, expected) in locations {
+        let company_id = repo
+            .get_location_company(&mut uow, &location_id)
+            .await
+            .unwrap();
+        assert_eq!(company_id, expected, "{location_id}");
+    }
+}
+
+async fn stores_stock_settings<R: StockRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_stock_settings(&mut uow, &ACME_ID).await.unwrap(),
+        None
+    );
+
+    let allowed = StockSettings {
+        company_id: ACME_ID,
+        allow_negative_stock: true,
+        updated_at: at(1),
+    };
+    repo.save_stock_settings(&mut uow, allowed.clone())
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    // saving again replaces the row
+    let refused = StockSettings {
+        allow_negative_stock: false,
+        updated_at: at(2),
+        ..allowed.clone()
+    };
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_stock_settings(&mut uow, &ACME_ID).await.unwrap(),
+        Some(allowed)
+    );
+    repo.save_stock_settings(&mut uow, refused.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.get_stock_settings(&mut uow, &ACME_ID).await.unwrap(),
+        Some(refused)
+    );
+    assert_eq!(
+        repo.get_stock_settings(&mut uow, &GLOBEX_ID).await.unwrap(),
+        None
+    );
+
+    let orphan = StockSettings {
+        company_id: Uuid::new_v4(),
+        allow_negative_stock: true,
+        updated_at: at(3),
+    };
+    let err = repo
+        .save_stock_settings(&mut uow, orphan)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("company_stock_settings_company_id_fkey")
+    );
+}
+
+async fn locks_and_updates_balances<R: StockRepository>(repo: &R) {
+    // a missing balance starts at 0 and is kept even when nothing is posted
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.lock_balance(&mut uow, &ACME_ID, &BOLT_ID, &BIN_A_ID)
+            .await
+            .unwrap(),
+        0
+    );
+    repo.update_balance(&mut uow, &BOLT_ID, &BIN_A_ID, 7, at(1))
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.lock_balance(&mut uow, &ACME_ID, &BOLT_ID, &BIN_A_ID)
+            .await
+            .unwrap(),
+        7
+    );
+    let found = repo
+        .find_balances(&mut uow, &balance_filter(ACME_ID), &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        vec![StockBalance {
+            product_id: BOLT_ID,
+            location_id: BIN_A_ID,
+            company_id: ACME_ID,
+            quantity: 7,
+            updated_at: at(1),
+        }]
+    );
+
+    let err = repo
+        .update_balance(&mut uow, &NUT_ID, &BIN_A_ID, 1, at(2))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx::Error::RowNotFound));
+    uow.rollback().await.unwrap();
+
+    let cases = [
+        (BOLT_ID, Uuid::new_v4(), "stock_balances_location_id_fkey"),
+        (Uuid::new_v4(), BIN_A_ID, "stock_balances_product_id_fkey"),
+    ];
+    for (product_id, location_id, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo
+            .lock_balance(&mut uow, &ACME_ID, &product_id, &location_id)
+            .await
+            .unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+}
+
+async fn filters_sorts_and_pages_balances<R: StockRepository>(repo: &R) {
+    seed_balances(
+        repo,
+        &[
+            (BOLT_ID, BIN_A_ID, 5, 3),
+            (BOLT_ID, BIN_B_ID, 2, 1),
+            (NUT_ID, BIN_A_ID, 9, 2),
+            (GEAR_ID, GLOBEX_BIN_ID, 4, 0),
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let all = balance_filter(ACME_ID);
+    assert_eq!(repo.count_balances(&mut uow, &all).await.unwrap(), 3);
+    // product then location by default
+    let found = repo
+        .find_balances(&mut uow, &all, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(quantities(&found), vec![5, 2, 9]);
+    let found = repo
+        .find_balances(&mut uow, &all, &query(Some("-quantity"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(quantities(&found), vec![5, 2]);
+    let found = repo
+        .find_balances(&mut uow, &all, &query(Some("updated_at"), 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(quantities(&found), vec![2, 9, 5]);
+
+    let cases = [
+        (Some(BOLT_ID), None, None, vec![5, 2]),
+        (None, Some(MAIN_ID), None, vec![5, 2, 9]),
+        (None, Some(GLOBEX_MAIN_ID), None, vec![]),
+        (None, None, Some(BIN_A_ID), vec![5, 9]),
+        (Some(NUT_ID), Some(MAIN_ID), Some(BIN_B_ID), vec![]),
+    ];
+    for (product_id, warehouse_id, location_id, expected) in cases {
+        let filter = StockBalanceFilter {
+            company_id: ACME_ID,
+            product_id,
+            warehouse_id,
+            location_id,
+        };
+        let found = repo
+            .find_balances(&mut uow, &filter, &query(None, 10, 0))
+            .await
+            .unwrap();
+        assert_eq!(quantities(&found), expected, "{filter:?}");
+        assert_eq!(
+            repo.count_balances(&mut uow, &filter).await.unwrap(),
+            expected.len() as i64
+        );
+    }
+}
+
+async fn stores_and_lists_movements<R: StockRepository>(repo: &R) {
+    let receipt = movement(BOLT_ID, BIN_A_ID, MovementType::Receipt, 10, 0);
+    let mut issue = movement(BOLT_ID, BIN_A_ID, MovementType::Issue, -4, 1);
+    issue.reference = Some("SO-1".into());
+    issue.note = Some("picked".into());
+    let moved = movement(NUT_ID, BIN_B_ID, MovementType::Adjustment, 3, 2);
+    let reversal = StockMovement {
+        movement_type: MovementType::Reversal,
+        quantity: 4,
+        reversal_of: Some(issue.id),
+        ..movement(BOLT_ID, BIN_A_ID, MovementType::Reversal, 4, 3)
+    };
+    let other = movement(GEAR_ID, GLOBEX_BIN_ID, MovementType::Receipt, 1, 4);
+    seed_movements(
+        repo,
+        &[
+            receipt.clone(),
+            issue.clone(),
+            moved.clone(),
+            reversal.clone(),
+            other,
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_movement_by_id(&mut uow, &issue.id).await.unwrap(),
+        Some(issue.clone())
+    );
+    assert_eq!(
+        repo.get_movement_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repo.find_reversal(&mut uow, &issue.id).await.unwrap(),
+        Some(reversal.clone())
+    );
+    assert_eq!(
+        repo.find_reversal(&mut uow, &receipt.id).await.unwrap(),
+        None
+    );
+
+    // oldest first by default
+    let all = movement_filter(ACME_ID);
+    assert_eq!(repo.count_movements(&mut uow, &all).await.unwrap(), 4);
+    let found = repo
+        .find_movements(&mut uow, &all, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        vec![
+            receipt.clone(),
+            issue.clone(),
+            moved.clone(),
+            reversal.clone()
+        ]
+    );
+    let found = repo
+        .find_movements(&mut uow, &all, &query(Some("-quantity"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(found, vec![reversal.clone(), moved.clone()]);
+
+    let filter = StockMovementFilter {
+        product_id: Some(BOLT_ID),
+        movement_type: Some(MovementType::Issue),
+        ..movement_filter(ACME_ID)
+    };
+    assert_eq!(repo.count_movements(&mut uow, &filter).await.unwrap(), 1);
+    let filter = StockMovementFilter {
+        location_id: Some(BIN_B_ID),
+        ..movement_filter(ACME_ID)
+    };
+    let found = repo
+        .find_movements(&mut uow, &filter, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(found, vec![moved]);
+}
+
+async fn enforces_movement_constraints<R: StockRepository>(repo: &R) {
+    let receipt = movement(BOLT_ID, BIN_A_ID, MovementType::Receipt, 10, 0);
+    let reversal = StockMovement {
+        reversal_of: Some(receipt.id),
+        ..movement(BOLT_ID, BIN_A_ID, MovementType::Reversal, -10, 1)
+    };
+    seed_movements(repo, &[receipt.clone(), reversal]).await;
+
+    let second = StockMovement {
+        reversal_of: Some(receipt.id),
+        ..movement(BOLT_ID, BIN_A_ID, MovementType::Reversal, -10, 2)
+    };
+    let dangling = StockMovement {
+        reversal_of: Some(Uuid::new_v4()),
+        ..movement(BOLT_ID, BIN_A_ID, MovementType::Reversal, -1, 2)
+    };
+    let cases = [
+        (
+            StockMovement {
+                id: receipt.id,
+                ..movement(BOLT_ID, BIN_A_ID, MovementType::Receipt, 1, 2)
+            },
+            "stock_movements_pkey",
+        ),
+        (second, "stock_movements_reversal_of_key"),
+        (dangling, "stock_movements_reversal_of_fkey"),
+        (
+            movement(Uuid::new_v4(), BIN_A_ID, MovementType::Receipt, 1, 2),
+            "stock_movements_product_id_fkey",
+        ),
+        (
+            movement(BOLT_ID, Uuid::new_v4(), MovementType::Receipt, 1, 2),
+            "stock_movements_location_id_fkey",
+        ),
+    ];
+    for (movement, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_movement(&mut uow, movement).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+}
+
+async fn reports_balance_mismatches<R: StockRepository>(repo: &R) {
+    seed_movements(
+        repo,
+        &[
+            movement(BOLT_ID, BIN_A_ID, MovementType::Receipt, 10, 0),
+            movement(BOLT_ID, BIN_A_ID, MovementType::Issue, -4, 1),
+            movement(NUT_ID, BIN_A_ID, MovementType::Receipt, 3, 2),
+            movement(GEAR_ID, GLOBEX_BIN_ID, MovementType::Receipt, 1, 3),
+        ],
+    )
+    .await;
+    // bolts match their ledger, nuts are off by one, the bolt balance in bin B
+    // has no ledger at all
+    seed_balances(
+        repo,
+        &[
+            (BOLT_ID, BIN_A_ID, 6, 1),
+            (NUT_ID, BIN_A_ID, 2, 2),
+            (BOLT_ID, BIN_B_ID, 5, 3),
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let found = repo
+        .find_balance_mismatches(&mut uow, &ACME_ID)
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        vec![
+            BalanceMismatch {
+                product_id: BOLT_ID,
+                location_id: BIN_B_ID,
+                balance: 5,
+                ledger: 0,
+            },
+            BalanceMismatch {
+                product_id: NUT_ID,
+                location_id: BIN_A_ID,
+                balance: 2,
+                ledger: 3,
+            },
+        ]
+    );
+    // the GLB ledger has no balance row, it was never posted through the usecase
+    let found = repo
+        .find_balance_mismatches(&mut uow, &GLOBEX_ID)
+        .await
+        .unwrap();
+    assert_eq!(
+        found,
+        vec![BalanceMismatch {
+            product_id: GEAR_ID,
+            location_id: GLOBEX_BIN_ID,
+            balance: 0,
+            ledger: 1,
+        }]
+    );
+}
+
+async fn stores_lots_and_lot_balances<R: StockRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    assert!(
+        repo.is_product_lot_tracked(&mut uow, &MILK_ID)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .is_product_lot_tracked(&mut uow, &BOLT_ID)
+            .await
+            .unwrap()
+    );
+
+    let soon = lot("L-2", Some(3));
+    let later = lot("L-1", Some(9));
+    let never = lot("L-3", None);
+    for lot in [&soon, &later, &never] {
+        repo.create_lot(&mut uow, lot.clone()).await.unwrap();
+    }
+    assert!(
+        repo.check_existing_lot_number(&mut uow, &MILK_ID, "L-1")
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_lot_number(&mut uow, &BOLT_ID, "L-1")
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        repo.get_lot_by_id(&mut uow, &soon.id).await.unwrap(),
+        Some(soon.clone())
+    );
+    let filter = StockLotFilter {
+        company_id: ACME_ID,
+        product_id: Some(MILK_ID),
+    };
+    assert_eq!(
+        repo.count_lots(&mut uow, &filter, &query(None, 10, 0))
+            .await
+            .unwrap(),
+        3
+    );
+    let found = repo
+        .find_lots(&mut uow, &filter, &query(None, 2, 0))
+        .await
+        .unwrap();
+    assert_eq!(found, vec![later.clone(), soon.clone()]);
+
+    for (lot, location_id, quantity) in [
+        (&soon, BIN_A_ID, 4),
+        (&soon, BIN_B_ID, 2),
+        (&later, BIN_A_ID, 5),
+        (&never, BIN_A_ID, 1),
+    ] {
+        assert_eq!(
+            repo.lock_lot_balance(&mut uow, &ACME_ID, &lot.id, &MILK_ID, &location_id)
+                .await
+                .unwrap(),
+            0
+        );
+        repo.update_lot_balance(&mut uow, &lot.id, &location_id, quantity, at(1))
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.lock_lot_balance(&mut uow, &ACME_ID, &soon.id, &MILK_ID, &BIN_A_ID)
+            .await
+            .unwrap(),
+        4
+    );
+    let balances = repo.find_lot_balances(&mut uow, &soon.id).await.unwrap();
+    let located: Vec<(Uuid, i64)> = balances
+        .iter()
+        .map(|b| (b.location_id, b.quantity))
+        .collect();
+    assert_eq!(located, [(BIN_A_ID, 4), (BIN_B_ID, 2)]);
+
+    // lots without an expiry date never expire
+    for (until, warehouse_id, expected) in [
+        (5, None, vec![("L-2", 6)]),
+        (9, Some(MAIN_ID), vec![("L-2", 6), ("L-1", 5)]),
+        (31, Some(GLOBEX_MAIN_ID), vec![]),
+    ] {
+        let until = NaiveDate::from_ymd_opt(2026, 1, until).unwrap();
+        let expiring = repo
+            .find_expiring_lots(&mut uow, &ACME_ID, until, warehouse_id.as_ref())
+            .await
+            .unwrap();
+        let found: Vec<(&str, i64)> = expiring
+            .iter()
+            .map(|lot| (lot.lot_number.as_str(), lot.on_hand))
+            .collect();
+        assert_eq!(found, expected);
+    }
+    uow.rollback().await.unwrap();
+
+    let cases = [
+        (lot("L-1", None), "stock_lots_product_id_lot_number_key"),
+        (
+            StockLot {
+                product_id: Uuid::new_v4(),
+                ..lot("L-9", None)
+            },
+            "stock_lots_product_id_fkey",
+        ),
+    ];
+    for (lot, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_lot(&mut uow, lot).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .lock_lot_balance(&mut uow, &ACME_ID, &Uuid::new_v4(), &MILK_ID, &BIN_A_ID)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_lot_balances_lot_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let dangling = StockMovement {
+        lot_id: Some(Uuid::new_v4()),
+        ..movement(MILK_ID, BIN_A_ID, MovementType::Receipt, 1, 2)
+    };
+    let err = repo.create_movement(&mut uow, dangling).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_movements_lot_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn stores_serials_and_their_movements<R: StockRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    assert!(
+        repo.is_product_serialised(&mut uow, &LAPTOP_ID)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .is_product_serialised(&mut uow, &BOLT_ID)
+            .await
+            .unwrap()
+    );
+
+    let in_a = serial("SN-2", Some(BIN_A_ID));
+    let in_b = serial("SN-1", Some(BIN_B_ID));
+    let issued = serial("SN-3", None);
+    for serial in [&in_a, &in_b, &issued] {
+        repo.create_serial(&mut uow, serial.clone()).await.unwrap();
+    }
+    assert_eq!(
+        repo.lock_serial(&mut uow, &LAPTOP_ID, "SN-1")
+            .await
+            .unwrap(),
+        Some(in_b.clone())
+    );
+    assert_eq!(
+        repo.lock_serial(&mut uow, &BOLT_ID, "SN-1").await.unwrap(),
+        None
+    );
+
+    let filter = StockSerialFilter {
+        company_id: ACME_ID,
+        product_id: Some(LAPTOP_ID),
+        location_id: None,
+        status: None,
+    };
+    let found = repo
+        .find_serials(&mut uow, &filter, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(found, vec![in_b.clone(), in_a.clone(), issued.clone()]);
+    let in_stock = StockSerialFilter {
+        status: Some(SerialStatus::InStock),
+        ..filter.clone()
+    };
+    assert_eq!(
+        repo.count_serials(&mut uow, &in_stock, &query(None, 10, 0))
+            .await
+            .unwrap(),
+        2
+    );
+    let at_a = StockSerialFilter {
+        location_id: Some(BIN_A_ID),
+        ..filter.clone()
+    };
+    let found = repo
+        .find_serials(&mut uow, &at_a, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(found, vec![in_a.clone()]);
+    let searched = PaginationRequest {
+        search: Some("n-3".into()),
+        ..query(Some("-serial_number"), 10, 0)
+    };
+    let found = repo
+        .find_serials(&mut uow, &filter, &searched)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![issued.clone()]);
+
+    // SN-2 is received into bin A and issued again
+    let received = movement(LAPTOP_ID, BIN_A_ID, MovementType::Receipt, 1, 1);
+    let shipped = movement(LAPTOP_ID, BIN_A_ID, MovementType::Issue, -1, 2);
+    for movement in [&shipped, &received] {
+        repo.create_movement(&mut uow, movement.clone())
+            .await
+            .unwrap();
+        repo.create_movement_serials(&mut uow, &movement.id, &[in_a.id])
+            .await
+            .unwrap();
+    }
+    repo.create_movement_serials(&mut uow, &received.id, &[in_b.id])
+        .await
+        .unwrap();
+    let in_a = StockSerial {
+        status: SerialStatus::Issued,
+        location_id: None,
+        updated_at: at(2),
+        ..in_a
+    };
+    repo.update_serial(&mut uow, in_a.clone()).await.unwrap();
+    assert_eq!(
+        repo.get_serial_by_id(&mut uow, &in_a.id).await.unwrap(),
+        Some(in_a.clone())
+    );
+    assert_eq!(
+        repo.find_movement_serial_numbers(&mut uow, &received.id)
+            .await
+            .unwrap(),
+        ["SN-1", "SN-2"]
+    );
+    let events = repo.find_serial_events(&mut uow, &in_a.id).await.unwrap();
+    let steps: Vec<(Uuid, i64, Option<Uuid>)> = events
+        .iter()
+        .map(|e| (e.movement_id, e.quantity, e.supplier_id))
+        .collect();
+    assert_eq!(steps, [(received.id, 1, None), (shipped.id, -1, None)]);
+
+    // bin B holds one laptop serial but a balance of two, bolts are not serialised
+    repo.lock_balance(&mut uow, &ACME_ID, &LAPTOP_ID, &BIN_B_ID)
+        .await
+        .unwrap();
+    repo.update_balance(&mut uow, &LAPTOP_ID, &BIN_B_ID, 2, at(3))
+        .await
+        .unwrap();
+    repo.lock_balance(&mut uow, &ACME_ID, &BOLT_ID, &BIN_A_ID)
+        .await
+        .unwrap();
+    repo.update_balance(&mut uow, &BOLT_ID, &BIN_A_ID, 5, at(3))
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.find_serial_mismatches(&mut uow, &ACME_ID)
+            .await
+            .unwrap(),
+        vec![SerialMismatch {
+            product_id: LAPTOP_ID,
+            location_id: BIN_B_ID,
+            balance: 2,
+            in_stock: 1,
+        }]
+    );
+    uow.commit().await.unwrap();
+
+    let cases = [
+        (
+            serial("SN-1", None),
+            "stock_serials_product_id_serial_number_key",
+        ),
+        (
+            StockSerial {
+                product_id: Uuid::new_v4(),
+                ..serial("SN-9", None)
+            },
+            "stock_serials_product_id_fkey",
+        ),
+        (
+            serial("SN-9", Some(Uuid::new_v4())),
+            "stock_serials_location_id_fkey",
+        ),
+    ];
+    for (serial, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_serial(&mut uow, serial).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_movement_serials(&mut uow, &received.id, &[Uuid::new_v4()])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_movement_serials_serial_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
+
+fn receipt(quantity: i64) -> MovementInput {
+    MovementInput {
+        product_id: BOLT_ID,
+        location_id: BIN_A_ID,
+        movement_type: MovementType::Receipt,
+        lot_id: None,
+        quantity,
+        serial_numbers: Vec::new(),
+        reference: None,
+        note: None,
+    }
+}
+
+fn issue(quantity: i64) -> MovementInput {
+    MovementInput {
+        movement_type: MovementType::Issue,
+        ..receipt(quantity)
+    }
+}
+
+// postgres only from here, the trigger and the row lock have no memory twin
+async fn seeded_database() -> Option<TestDatabase> {
+    let db = TestDatabase::create().await?;
+    seed_companies(&CompanyRepositorySqlx::new(db.pool.clone())).await;
+    seed_catalogue(
+        &ProductRepositorySqlx::new(db.pool.clone()),
+        &WarehouseRepositorySqlx::new(db.pool.clone()),
+        catalogue(),
+    )
+    .await;
+    Some(db)
+}
+
+#[tokio::test]
+async fn posted_movements_can_not_be_changed() {
+    let Some(db) = seeded_database().await else {
+        return;
+    };
+    let usecase = StockUsecase::new(StockRepositorySqlx::new(db.pool.clone()), AppMetrics::new());
+    let posted = usecase
+        .post_movement(receipt(5), Uuid::new_v4())
+        .await
+        .unwrap();
+
+    let statements = [
+        "UPDATE stock_movements SET quantity = 50 WHERE id = $1",
+        "DELETE FROM stock_movements WHERE id = $1",
+    ];
+    for statement in statements {
+        let err = sqlx::query(statement)
+            .bind(posted.id)
+            .execute(&db.pool)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("post a reversal instead"),
+            "{statement}: {err}"
+        );
+    }
+
+    // the product is referenced by the ledger now
+    let products = ProductUsecase::new(
+        ProductRepositorySqlx::new(db.pool.clone()),
+        AppMetrics::new(),
+    );
+    let result = products.delete_product(BOLT_ID).await;
+    assert!(matches!(result, Err(ProductUsecaseError::InUse)));
+
+    db.drop_database().await;
+}
+
+#[tokio::test]
+async fn concurrent_issues_never_drive_stock_negative() {
+    let Some(db) = seeded_database().await else {
+        return;
+    };
+    let usecase = StockUsecase::new(StockRepositorySqlx::new(db.pool.clone()), AppMetrics::new());
+    let user = Uuid::new_v4();
+    usecase.post_movement(receipt(5), user).await.unwrap();
+
+    // both read 5 without the lock, with it the second one waits and sees 2
+    let (first, second) = tokio::join!(
+        usecase.post_movement(issue(3), user),
+        usecase.post_movement(issue(3), user)
+    );
+    let results = [first, second];
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(results.iter().any(|r| matches!(
+        r,
+        Err(StockUsecaseError::InsufficientStock { available: 2 })
+    )));
+
+    let filter = balance_filter(ACME_ID);
+    let balances = usecase
+        .list_balances(&filter, &query(None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(quantities(&balances.data), vec![2]);
+    assert!(usecase.reconcile(ACME_ID).await.unwrap().is_empty());
+
+    db.drop_database().await;
+}
+
+#[tokio::test]
+async fn reconcile_reports_tampered_balances() {
+    let Some(db) = seeded_database().await else {
+        return;
+    };
+    let usecase = StockUsecase::new(StockRepositorySqlx::new(db.pool.clone()), AppMetrics::new());
+    let user = Uuid::new_v4();
+    usecase.post_movement(receipt(5), user).await.unwrap();
+    usecase.post_movement(issue(2), user).await.unwrap();
+
+    // balances are not protected like the ledger, a manual fix shows up here
+    sqlx::query("UPDATE stock_balances SET quantity = 10 WHERE product_id = $1")
+        .bind(BOLT_ID)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        usecase.reconcile(ACME_ID).await.unwrap(),
+        vec![BalanceMismatch {
+            product_id: BOLT_ID,
+            location_id: BIN_A_ID,
+            balance: 10,
+            ledger: 3,
+        }]
+    );
+
+    db.drop_database().await;
+}
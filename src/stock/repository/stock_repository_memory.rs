@@ -0,0 +1,1253 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::app_database::memory::{
+    MemoryStore, MemoryUnitOfWork, foreign_key_violation, ilike, unique_violation,
+};
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product_unit::ProductUnit;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::purchasing::domain::goods_receipt::{GoodsReceipt, GoodsReceiptLine};
+use crate::purchasing::domain::purchase_order::{PurchaseOrder, PurchaseOrderLine};
+use crate::purchasing::domain::supplier::Supplier;
+use crate::sales::domain::availability::StockReservation;
+use crate::sales::domain::sales_order::{SalesOrder, SalesOrderLine};
+use crate::sales::domain::shipment::{Shipment, ShipmentLine};
+use crate::stock::domain::balance::{BalanceMismatch, StockBalance, StockBalanceFilter};
+use crate::stock::domain::count::{StockCount, StockCountEntry, StockCountLine};
+use crate::stock::domain::lot::{ExpiringLot, StockLot, StockLotBalance, StockLotFilter};
+use crate::stock::domain::movement::{StockMovement, StockMovementFilter};
+use crate::stock::domain::serial::{
+    SerialEvent, SerialMismatch, SerialStatus, StockSerial, StockSerialFilter,
+};
+use crate::stock::domain::settings::StockSettings;
+use crate::stock::domain::transfer::{StockTransfer, TransferLine};
+use crate::stock::repository::helper_query::{
+    STOCK_BALANCE_SORT_COLUMNS, STOCK_LOT_SORT_COLUMNS, STOCK_MOVEMENT_SORT_COLUMNS,
+    STOCK_SERIAL_SORT_COLUMNS,
+};
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+
+#[derive(Debug, Clone, Default)]
+pub struct StockTables {
+    pub movements: Vec<StockMovement>,
+    pub balances: Vec<StockBalance>,
+    pub lots: Vec<StockLot>,
+    pub lot_balances: Vec<StockLotBalance>,
+    pub serials: Vec<StockSerial>,
+    // (movement_id, serial_id)
+    pub movement_serials: Vec<(Uuid, Uuid)>,
+    pub settings: Vec<StockSettings>,
+    pub transfers: Vec<StockTransfer>,
+    pub transfer_lines: Vec<TransferLine>,
+    pub counts: Vec<StockCount>,
+    pub count_lines: Vec<StockCountLine>,
+    pub count_entries: Vec<StockCountEntry>,
+    pub suppliers: Vec<Supplier>,
+    pub purchase_orders: Vec<PurchaseOrder>,
+    pub purchase_order_lines: Vec<PurchaseOrderLine>,
+    pub goods_receipts: Vec<GoodsReceipt>,
+    pub goods_receipt_lines: Vec<GoodsReceiptLine>,
+    pub sales_orders: Vec<SalesOrder>,
+    pub sales_order_lines: Vec<SalesOrderLine>,
+    pub reservations: Vec<StockReservation>,
+    pub shipments: Vec<Shipment>,
+    pub shipment_lines: Vec<ShipmentLine>,
+}
+
+// mirrors StockRepositorySqlx on top of vectors. the foreign keys to companies,
+// products and locations are checked against the committed rows of the
+// repositories it was built with. a unit of work holds the store lock, which
+// stands in for the balance, transfer, count, order and reservation row locks.
+// the check constraints are left to the usecase. transfers are in
+// transfer_repository_memory, counts in count_repository_memory, purchasing in
+// purchasing_repository_memory, sales in sales_repository_memory
+#[derive(Clone, Default)]
+pub struct StockRepositoryMemory {
+    pub(super) store: MemoryStore<StockTables>,
+    companies: CompanyRepositoryMemory,
+    products: ProductRepositoryMemory,
+    pub(super) warehouses: WarehouseRepositoryMemory,
+}
+
+impl StockRepositoryMemory {
+    pub fn new(
+        companies: CompanyRepositoryMemory,
+        products: ProductRepositoryMemory,
+        warehouses: WarehouseRepositoryMemory,
+    ) -> Self {
+        Self {
+            store: MemoryStore::default(),
+            companies,
+            products,
+            warehouses,
+        }
+    }
+
+    // committed rows, in insertion order
+    pub async fn tables(&self) -> StockTables {
+        self.store.snapshot().await
+    }
+
+    pub(crate) async fn company_exists(&self, company_id: &Uuid) -> bool {
+        self.companies
+            .companies()
+            .await
+            .iter()
+            .any(|c| c.id == *company_id)
+    }
+
+    pub(crate) async fn product_company(&self, product_id: &Uuid) -> Option<Uuid> {
+        self.products
+            .products()
+            .await
+            .iter()
+            .find(|p| p.id == *product_id)
+            .map(|p| p.company_id)
+    }
+
+    pub(crate) async fn product_lot_tracked(&self, product_id: &Uuid) -> bool {
+        self.products
+            .products()
+            .await
+            .iter()
+            .any(|p| p.id == *product_id && p.is_lot_tracked)
+    }
+
+    pub(crate) async fn product_serialised(&self, product_id: &Uuid) -> bool {
+        self.products
+            .products()
+            .await
+            .iter()
+            .any(|p| p.id == *product_id && p.is_serialised)
+    }
+
+    async fn location_company(&self, location_id: &Uuid) -> Option<Uuid> {
+        let tables = self.warehouses.tables().await;
+        let location = tables.locations.iter().find(|l| l.id == *location_id)?;
+        tables
+            .warehouses
+            .iter()
+            .find(|w| w.id == location.warehouse_id)
+            .map(|w| w.company_id)
+    }
+
+    pub(crate) async fn warehouse_company(&self, warehouse_id: &Uuid) -> Option<Uuid> {
+        self.warehouses
+            .tables()
+            .await
+            .warehouses
+            .iter()
+            .find(|w| w.id == *warehouse_id)
+            .map(|w| w.company_id)
+    }
+
+    pub(crate) async fn location_warehouse(&self, location_id: &Uuid) -> Option<Uuid> {
+        self.warehouses
+            .tables()
+            .await
+            .locations
+            .iter()
+            .find(|l| l.id == *location_id)
+            .map(|l| l.warehouse_id)
+    }
+
+    pub(crate) async fn warehouse_locations(&self, warehouse_id: &Uuid) -> Vec<Uuid> {
+        self.warehouses
+            .tables()
+            .await
+            .locations
+            .iter()
+            .filter(|l| l.warehouse_id == *warehouse_id)
+            .map(|l| l.id)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Transactional for StockRepositoryMemory {
+    type Uow = MemoryUnitOfWork<StockTables>;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        Ok(self.store.begin().await)
+    }
+}
+
+#[async_trait]
+impl StockRepository for StockRepositoryMemory {
+    async fn check_existing_company(
+        &self,
+        _uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(self.company_exists(company_id).await)
+    }
+
+    async fn get_product_company(
+        &self,
+        _uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        Ok(self.product_company(product_id).await)
+    }
+
+    async fn get_product_base_unit(
+        &self,
+        _uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        Ok(self
+            .products
+            .products()
+            .await
+            .into_iter()
+            .find(|p| p.id == *product_id)
+            .map(|p| p.unit_of_measure))
+    }
+
+    async fn get_product_unit(
+        &self,
+        _uow: &mut Self::Uow,
+        product_id: &Uuid,
+        code: &str,
+    ) -> Result<Option<ProductUnit>, sqlx::Error> {
+        Ok(self
+            .products
+            .units()
+            .await
+            .into_iter()
+            .find(|u| u.product_id == *product_id && u.code == code))
+    }
+
+    async fn get_location_company(
+        &self,
+        _uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        Ok(self.location_company(location_id).await)
+    }
+
+    async fn get_warehouse_company(
+        &self,
+        _uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        Ok(self.warehouse_company(warehouse_id).await)
+    }
+
+    async fn get_location_warehouse(
+        &self,
+        _uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        Ok(self.location_warehouse(location_id).await)
+    }
+
+    async fn is_location_counted(
+        &self,
+        uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let ancestors = self.location_ancestors(location_id).await;
+        let Some(warehouse_id) = self.location_warehouse(location_id).await else {
+            return Ok(false);
+        };
+        Ok(uow.state().counts.iter().any(|c| {
+            c.status.is_active()
+                && c.warehouse_id == warehouse_id
+                && c.location_id.is_none_or(|id| ancestors.contains(&id))
+        }))
+    }
+
+    async fn get_stock_settings(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Option<StockSettings>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .settings
+            .iter()
+            .find(|s| s.company_id == *company_id)
+            .cloned())
+    }
+
+    async fn save_stock_settings(
+        &self,
+        uow: &mut Self::Uow,
+        settings: StockSettings,
+    ) -> Result<StockSettings, sqlx::Error> {
+        if !self.company_exists(&settings.company_id).await {
+            return Err(foreign_key_violation(
+                "company_stock_settings_company_id_fkey",
+            ));
+        }
+        let rows = &mut uow.state_mut().settings;
+        rows.retain(|s| s.company_id != settings.company_id);
+        rows.push(settings.clone());
+
+        Ok(settings)
+    }
+
+    async fn lock_balance(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        product_id: &Uuid,
+        location_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        if let Some(balance) = find_balance(&uow.state().balances, product_id, location_id) {
+            return Ok(balance.quantity);
+        }
+        if !self.company_exists(company_id).await {
+            return Err(foreign_key_violation("stock_balances_company_id_fkey"));
+        }
+        if self.product_company(product_id).await.is_none() {
+            return Err(foreign_key_violation("stock_balances_product_id_fkey"));
+        }
+        if self.location_company(location_id).await.is_none() {
+            return Err(foreign_key_violation("stock_balances_location_id_fkey"));
+        }
+        uow.state_mut().balances.push(StockBalance {
+            product_id: *product_id,
+            location_id: *location_id,
+            company_id: *company_id,
+            quantity: 0,
+            updated_at: Utc::now(),
+        });
+
+        Ok(0)
+    }
+
+    async fn update_balance(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        location_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let balance = uow
+            .state_mut()
+            .balances
+            .iter_mut()
+            .find(|b| b.product_id == *product_id && b.location_id == *location_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        balance.quantity = quantity;
+        balance.updated_at = updated_at;
+
+        Ok(())
+    }
+
+    async fn get_warehouse_usable(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+        today: NaiveDate,
+    ) -> Result<i64, sqlx::Error> {
+        let locations = self.warehouse_locations(warehouse_id).await;
+        let tables = uow.state();
+        let on_hand: i64 = tables
+            .balances
+            .iter()
+            .filter(|b| b.product_id == *product_id && locations.contains(&b.location_id))
+            .map(|b| b.quantity)
+            .sum();
+
+        Ok(on_hand - expired_quantity(tables, product_id, &locations, today))
+    }
+
+    async fn lock_reservation(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        if let Some(reservation) = uow
+            .state()
+            .reservations
+            .iter()
+            .find(|r| r.product_id == *product_id && r.warehouse_id == *warehouse_id)
+        {
+            return Ok(reservation.quantity);
+        }
+        if !self.company_exists(company_id).await {
+            return Err(foreign_key_violation("stock_reservations_company_id_fkey"));
+        }
+        if self.product_company(product_id).await.is_none() {
+            return Err(foreign_key_violation("stock_reservations_product_id_fkey"));
+        }
+        if self.warehouse_company(warehouse_id).await != Some(*company_id) {
+            return Err(foreign_key_violation("stock_reservations_warehouse_fkey"));
+        }
+        uow.state_mut().reservations.push(StockReservation {
+            product_id: *product_id,
+            warehouse_id: *warehouse_id,
+            company_id: *company_id,
+            quantity: 0,
+            updated_at: Utc::now(),
+        });
+
+        Ok(0)
+    }
+
+    async fn count_balances(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockBalanceFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let locations = self.filter_locations(filter).await;
+        Ok(filter_balances(&uow.state().balances, filter, &locations).len() as i64)
+    }
+
+    async fn find_balances(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockBalanceFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockBalance>, sqlx::Error> {
+        let locations = self.filter_locations(filter).await;
+        let keys = sort_keys(query, &STOCK_BALANCE_SORT_COLUMNS)?;
+        let mut balances: Vec<StockBalance> =
+            filter_balances(&uow.state().balances, filter, &locations)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            balances.sort_by_key(|b| (b.product_id, b.location_id));
+        } else {
+            balances.sort_by(|a, b| compare_by(&keys, |column| compare_balance(a, b, column)));
+        }
+
+        Ok(page(balances, query))
+    }
+
+    async fn find_balance_mismatches(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<BalanceMismatch>, sqlx::Error> {
+        // (product, location) -> (balance, ledger), ordered like ORDER BY 1, 2
+        let mut totals: BTreeMap<(Uuid, Uuid), (i64, i64)> = BTreeMap::new();
+        let tables = uow.state();
+        for balance in tables
+            .balances
+            .iter()
+            .filter(|b| b.company_id == *company_id)
+        {
+            totals
+                .entry((balance.product_id, balance.location_id))
+                .or_default()
+                .0 = balance.quantity;
+        }
+        for movement in tables
+            .movements
+            .iter()
+            .filter(|m| m.company_id == *company_id)
+        {
+            totals
+                .entry((movement.product_id, movement.location_id))
+                .or_default()
+                .1 += movement.quantity;
+        }
+
+        Ok(totals
+            .into_iter()
+            .filter(|(_, (balance, ledger))| balance != ledger)
+            .map(
+                |((product_id, location_id), (balance, ledger))| BalanceMismatch {
+                    product_id,
+                    location_id,
+                    balance,
+                    ledger,
+                },
+            )
+            .collect())
+    }
+
+    async fn create_movement(
+        &self,
+        uow: &mut Self::Uow,
+        movement: StockMovement,
+    ) -> Result<StockMovement, sqlx::Error> {
+        let movements = &uow.state().movements;
+        if movements.iter().any(|m| m.id == movement.id) {
+            return Err(unique_violation("stock_movements_pkey"));
+        }
+        if let Some(reversal_of) = movement.reversal_of {
+            if movements.iter().any(|m| m.reversal_of == Some(reversal_of)) {
+                return Err(unique_violation("stock_movements_reversal_of_key"));
+            }
+            if !movements.iter().any(|m| m.id == reversal_of) {
+                return Err(foreign_key_violation("stock_movements_reversal_of_fkey"));
+            }
+        }
+        if !self.company_exists(&movement.company_id).await {
+            return Err(foreign_key_violation("stock_movements_company_id_fkey"));
+        }
+        if self.product_company(&movement.product_id).await.is_none() {
+            return Err(foreign_key_violation("stock_movements_product_id_fkey"));
+        }
+        if self.location_company(&movement.location_id).await.is_none() {
+            return Err(foreign_key_violation("stock_movements_location_id_fkey"));
+        }
+        if let Some(lot_id) = movement.lot_id
+            && !uow.state().lots.iter().any(|l| l.id == lot_id)
+        {
+            return Err(foreign_key_violation("stock_movements_lot_id_fkey"));
+        }
+        uow.state_mut().movements.push(movement.clone());
+
+        Ok(movement)
+    }
+
+    async fn get_movement_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockMovement>, sqlx::Error> {
+        Ok(uow.state().movements.iter().find(|m| m.id == *id).cloned())
+    }
+
+    async fn find_reversal(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+    ) -> Result<Option<StockMovement>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .movements
+            .iter()
+            .find(|m| m.reversal_of == Some(*movement_id))
+            .cloned())
+    }
+
+    async fn count_movements(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockMovementFilter,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_movements(&uow.state().movements, filter).len() as i64)
+    }
+
+    async fn find_movements(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockMovementFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockMovement>, sqlx::Error> {
+        let keys = sort_keys(query, &STOCK_MOVEMENT_SORT_COLUMNS)?;
+        let mut movements: Vec<StockMovement> = filter_movements(&uow.state().movements, filter)
+            .into_iter()
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            movements.sort_by_key(|m| (m.posted_at, m.id));
+        } else {
+            movements.sort_by(|a, b| compare_by(&keys, |column| compare_movement(a, b, column)));
+        }
+
+        Ok(page(movements, query))
+    }
+
+    async fn is_product_lot_tracked(
+        &self,
+        _uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(self.product_lot_tracked(product_id).await)
+    }
+
+    async fn check_existing_lot_number(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        lot_number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .lots
+            .iter()
+            .any(|l| l.product_id == *product_id && l.lot_number == lot_number))
+    }
+
+    async fn create_lot(
+        &self,
+        uow: &mut Self::Uow,
+        lot: StockLot,
+    ) -> Result<StockLot, sqlx::Error> {
+        let lots = &uow.state().lots;
+        if lots.iter().any(|l| l.id == lot.id) {
+            return Err(unique_violation("stock_lots_pkey"));
+        }
+        if lots
+            .iter()
+            .any(|l| l.product_id == lot.product_id && l.lot_number == lot.lot_number)
+        {
+            return Err(unique_violation("stock_lots_product_id_lot_number_key"));
+        }
+        if !self.company_exists(&lot.company_id).await {
+            return Err(foreign_key_violation("stock_lots_company_id_fkey"));
+        }
+        if self.product_company(&lot.product_id).await.is_none() {
+            return Err(foreign_key_violation("stock_lots_product_id_fkey"));
+        }
+        uow.state_mut().lots.push(lot.clone());
+
+        Ok(lot)
+    }
+
+    async fn get_lot_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockLot>, sqlx::Error> {
+        Ok(uow.state().lots.iter().find(|l| l.id == *id).cloned())
+    }
+
+    async fn count_lots(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_lots(&uow.state().lots, filter, &query.search).len() as i64)
+    }
+
+    async fn find_lots(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockLot>, sqlx::Error> {
+        let keys = sort_keys(query, &STOCK_LOT_SORT_COLUMNS)?;
+        let mut lots: Vec<StockLot> = filter_lots(&uow.state().lots, filter, &query.search)
+            .into_iter()
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            lots.sort_by(|a, b| (a.product_id, &a.lot_number).cmp(&(b.product_id, &b.lot_number)));
+        } else {
+            lots.sort_by(|a, b| compare_by(&keys, |column| compare_lot(a, b, column)));
+        }
+
+        Ok(page(lots, query))
+    }
+
+    async fn lock_lot_balance(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        lot_id: &Uuid,
+        product_id: &Uuid,
+        location_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let tables = uow.state();
+        if let Some(balance) = tables
+            .lot_balances
+            .iter()
+            .find(|b| b.lot_id == *lot_id && b.location_id == *location_id)
+        {
+            return Ok(balance.quantity);
+        }
+        if !tables.lots.iter().any(|l| l.id == *lot_id) {
+            return Err(foreign_key_violation("stock_lot_balances_lot_id_fkey"));
+        }
+        if self.location_company(location_id).await.is_none() {
+            return Err(foreign_key_violation("stock_lot_balances_location_id_fkey"));
+        }
+        uow.state_mut().lot_balances.push(StockLotBalance {
+            lot_id: *lot_id,
+            location_id: *location_id,
+            product_id: *product_id,
+            company_id: *company_id,
+            quantity: 0,
+            updated_at: Utc::now(),
+        });
+
+        Ok(0)
+    }
+
+    async fn update_lot_balance(
+        &self,
+        uow: &mut Self::Uow,
+        lot_id: &Uuid,
+        location_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let balance = uow
+            .state_mut()
+            .lot_balances
+            .iter_mut()
+            .find(|b| b.lot_id == *lot_id && b.location_id == *location_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        balance.quantity = quantity;
+        balance.updated_at = updated_at;
+
+        Ok(())
+    }
+
+    async fn find_lot_balances(
+        &self,
+        uow: &mut Self::Uow,
+        lot_id: &Uuid,
+    ) -> Result<Vec<StockLotBalance>, sqlx::Error> {
+        let mut balances: Vec<StockLotBalance> = uow
+            .state()
+            .lot_balances
+            .iter()
+            .filter(|b| b.lot_id == *lot_id)
+            .cloned()
+            .collect();
+        balances.sort_by_key(|b| b.location_id);
+
+        Ok(balances)
+    }
+
+    async fn find_expiring_lots(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        expires_on_or_before: NaiveDate,
+        warehouse_id: Option<&Uuid>,
+    ) -> Result<Vec<ExpiringLot>, sqlx::Error> {
+        let locations = match warehouse_id {
+            Some(warehouse_id) => Some(self.warehouse_locations(warehouse_id).await),
+            None => None,
+        };
+        let tables = uow.state();
+        let mut lots: Vec<ExpiringLot> = tables
+            .lots
+            .iter()
+            .filter(|l| l.company_id == *company_id)
+            .filter_map(|lot| {
+                let expires_on = lot.expires_on.filter(|e| *e <= expires_on_or_before)?;
+                let on_hand: i64 = tables
+                    .lot_balances
+                    .iter()
+                    .filter(|b| b.lot_id == lot.id)
+                    .filter(|b| {
+                        locations
+                            .as_ref()
+                            .is_none_or(|locations| locations.contains(&b.location_id))
+                    })
+                    .map(|b| b.quantity)
+                    .sum();
+                (on_hand > 0).then(|| ExpiringLot {
+                    lot_id: lot.id,
+                    product_id: lot.product_id,
+                    lot_number: lot.lot_number.clone(),
+                    expires_on,
+                    on_hand,
+                })
+            })
+            .collect();
+        lots.sort_by(|a, b| {
+            (a.expires_on, &a.lot_number, a.lot_id).cmp(&(b.expires_on, &b.lot_number, b.lot_id))
+        });
+
+        Ok(lots)
+    }
+
+    async fn is_product_serialised(
+        &self,
+        _uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(self.product_serialised(product_id).await)
+    }
+
+    async fn lock_serial(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        serial_number: &str,
+    ) -> Result<Option<StockSerial>, sqlx::Error> {
+        Ok(uow
+            .state()
+            .serials
+            .iter()
+            .find(|s| s.product_id == *product_id && s.serial_number == serial_number)
+            .cloned())
+    }
+
+    async fn create_serial(
+        &self,
+        uow: &mut Self::Uow,
+        serial: StockSerial,
+    ) -> Result<StockSerial, sqlx::Error> {
+        let serials = &uow.state().serials;
+        if serials.iter().any(|s| s.id == serial.id) {
+            return Err(unique_violation("stock_serials_pkey"));
+        }
+        if serials
+            .iter()
+            .any(|s| s.product_id == serial.product_id && s.serial_number == serial.serial_number)
+        {
+            return Err(unique_violation(
+                "stock_serials_product_id_serial_number_key",
+            ));
+        }
+        if !self.company_exists(&serial.company_id).await {
+            return Err(foreign_key_violation("stock_serials_company_id_fkey"));
+        }
+        if self.product_company(&serial.product_id).await.is_none() {
+            return Err(foreign_key_violation("stock_serials_product_id_fkey"));
+        }
+        if let Some(location_id) = serial.location_id
+            && self.location_company(&location_id).await.is_none()
+        {
+            return Err(foreign_key_violation("stock_serials_location_id_fkey"));
+        }
+        uow.state_mut().serials.push(serial.clone());
+
+        Ok(serial)
+    }
+
+    async fn update_serial(
+        &self,
+        uow: &mut Self::Uow,
+        serial: StockSerial,
+    ) -> Result<StockSerial, sqlx::Error> {
+        if let Some(location_id) = serial.location_id
+            && self.location_company(&location_id).await.is_none()
+        {
+            return Err(foreign_key_violation("stock_serials_location_id_fkey"));
+        }
+        let stored = uow
+            .state_mut()
+            .serials
+            .iter_mut()
+            .find(|s| s.id == serial.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        stored.status = serial.status;
+        stored.location_id = serial.location_id;
+        stored.updated_at = serial.updated_at;
+
+        Ok(serial)
+    }
+
+    async fn create_movement_serials(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+        serial_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let tables = uow.state();
+        if !tables.movements.iter().any(|m| m.id == *movement_id) {
+            return Err(foreign_key_violation(
+                "stock_movement_serials_movement_id_fkey",
+            ));
+        }
+        for serial_id in serial_ids {
+            if !tables.serials.iter().any(|s| s.id == *serial_id) {
+                return Err(foreign_key_violation(
+                    "stock_movement_serials_serial_id_fkey",
+                ));
+            }
+            if tables
+                .movement_serials
+                .contains(&(*movement_id, *serial_id))
+            {
+                return Err(unique_violation("stock_movement_serials_pkey"));
+            }
+        }
+        uow.state_mut().movement_serials.extend(
+            serial_ids
+                .iter()
+                .map(|serial_id| (*movement_id, *serial_id)),
+        );
+
+        Ok(())
+    }
+
+    async fn find_movement_serial_numbers(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let tables = uow.state();
+        let mut numbers: Vec<String> = tables
+            .movement_serials
+            .iter()
+            .filter(|(m, _)| m == movement_id)
+            .filter_map(|(_, serial_id)| tables.serials.iter().find(|s| s.id == *serial_id))
+            .map(|s| s.serial_number.clone())
+            .collect();
+        numbers.sort();
+
+        Ok(numbers)
+    }
+
+    async fn get_serial_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockSerial>, sqlx::Error> {
+        Ok(uow.state().serials.iter().find(|s| s.id == *id).cloned())
+    }
+
+    async fn count_serials(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_serials(&uow.state().serials, filter, &query.search).len() as i64)
+    }
+
+    async fn find_serials(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockSerial>, sqlx::Error> {
+        let keys = sort_keys(query, &STOCK_SERIAL_SORT_COLUMNS)?;
+        let mut serials: Vec<StockSerial> =
+            filter_serials(&uow.state().serials, filter, &query.search)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            serials.sort_by(|a, b| {
+                (a.product_id, &a.serial_number).cmp(&(b.product_id, &b.serial_number))
+            });
+        } else {
+            serials.sort_by(|a, b| compare_by(&keys, |column| compare_serial(a, b, column)));
+        }
+
+        Ok(page(serials, query))
+    }
+
+    async fn find_serial_events(
+        &self,
+        uow: &mut Self::Uow,
+        serial_id: &Uuid,
+    ) -> Result<Vec<SerialEvent>, sqlx::Error> {
+        let tables = uow.state();
+        let supplier = |movement_id: Uuid| {
+            let line = tables
+                .goods_receipt_lines
+                .iter()
+                .find(|l| l.movement_id == movement_id)?;
+            let receipt = tables
+                .goods_receipts
+                .iter()
+                .find(|r| r.id == line.goods_receipt_id)?;
+            tables
+                .purchase_orders
+                .iter()
+                .find(|o| o.id == receipt.purchase_order_id)
+                .map(|o| o.supplier_id)
+        };
+        let customer = |movement_id: Uuid| {
+            let line = tables
+                .shipment_lines
+                .iter()
+                .find(|l| l.movement_id == Some(movement_id))?;
+            let shipment = tables.shipments.iter().find(|s| s.id == line.shipment_id)?;
+            tables
+                .sales_orders
+                .iter()
+                .find(|o| o.id == shipment.sales_order_id)
+                .map(|o| o.customer_id)
+        };
+        let mut events: Vec<SerialEvent> = tables
+            .movement_serials
+            .iter()
+            .filter(|(_, s)| s == serial_id)
+            .filter_map(|(movement_id, _)| tables.movements.iter().find(|m| m.id == *movement_id))
+            .map(|m| SerialEvent {
+                movement_id: m.id,
+                movement_type: m.movement_type,
+                location_id: m.location_id,
+                quantity: m.quantity.signum(),
+                reference: m.reference.clone(),
+                supplier_id: supplier(m.id),
+                customer_id: customer(m.id),
+                posted_by: m.posted_by,
+                posted_at: m.posted_at,
+            })
+            .collect();
+        events.sort_by_key(|e| (e.posted_at, e.movement_id));
+
+        Ok(events)
+    }
+
+    async fn find_serial_mismatches(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<SerialMismatch>, sqlx::Error> {
+        // (product, location) -> (balance, in stock), ordered like ORDER BY 1, 2
+        let mut totals: BTreeMap<(Uuid, Uuid), (i64, i64)> = BTreeMap::new();
+        let tables = uow.state();
+        for balance in tables
+            .balances
+            .iter()
+            .filter(|b| b.company_id == *company_id)
+        {
+            if self.product_serialised(&balance.product_id).await {
+                totals
+                    .entry((balance.product_id, balance.location_id))
+                    .or_default()
+                    .0 = balance.quantity;
+            }
+        }
+        for serial in tables
+            .serials
+            .iter()
+            .filter(|s| s.company_id == *company_id && s.status == SerialStatus::InStock)
+        {
+            if let Some(location_id) = serial.location_id {
+                totals
+                    .entry((serial.product_id, location_id))
+                    .or_default()
+                    .1 += 1;
+            }
+        }
+
+        Ok(totals
+            .into_iter()
+            .filter(|(_, (balance, in_stock))| balance != in_stock)
+            .map(
+                |((product_id, location_id), (balance, in_stock))| SerialMismatch {
+                    product_id,
+                    location_id,
+                    balance,
+                    in_stock,
+                },
+            )
+            .collect())
+    }
+}
+
+impl StockRepositoryMemory {
+    // locations of filter.warehouse_id, None when the filter has no warehouse
+    async fn filter_locations(&self, filter: &StockBalanceFilter) -> Option<Vec<Uuid>> {
+        match &filter.warehouse_id {
+            Some(warehouse_id) => Some(self.warehouse_locations(warehouse_id).await),
+            None => None,
+        }
+    }
+}
+
+fn find_balance<'a>(
+    balances: &'a [StockBalance],
+    product_id: &Uuid,
+    location_id: &Uuid,
+) -> Option<&'a StockBalance> {
+    balances
+        .iter()
+        .find(|b| b.product_id == *product_id && b.location_id == *location_id)
+}
+
+// stock of the product at the locations in lots expired before today, shared
+// with the availability of the sales repository
+pub(crate) fn expired_quantity(
+    tables: &StockTables,
+    product_id: &Uuid,
+    locations: &[Uuid],
+    today: NaiveDate,
+) -> i64 {
+    tables
+        .lot_balances
+        .iter()
+        .filter(|b| b.product_id == *product_id && locations.contains(&b.location_id))
+        .filter(|b| {
+            tables
+                .lots
+                .iter()
+                .any(|l| l.id == b.lot_id && l.is_expired(today))
+        })
+        .map(|b| b.quantity)
+        .sum()
+}
+
+// see helper_query::apply_balance_filter
+fn filter_balances<'a>(
+    balances: &'a [StockBalance],
+    filter: &StockBalanceFilter,
+    warehouse_locations: &Option<Vec<Uuid>>,
+) -> Vec<&'a StockBalance> {
+    balances
+        .iter()
+        .filter(|b| b.company_id == filter.company_id)
+        .filter(|b| filter.product_id.is_none_or(|id| b.product_id == id))
+        .filter(|b| filter.location_id.is_none_or(|id| b.location_id == id))
+        .filter(|b| {
+            warehouse_locations
+                .as_ref()
+                .is_none_or(|locations| locations.contains(&b.location_id))
+        })
+        .collect()
+}
+
+// see helper_query::apply_movement_filter
+fn filter_movements<'a>(
+    movements: &'a [StockMovement],
+    filter: &StockMovementFilter,
+) -> Vec<&'a StockMovement> {
+    movements
+        .iter()
+        .filter(|m| m.company_id == filter.company_id)
+        .filter(|m| filter.product_id.is_none_or(|id| m.product_id == id))
+        .filter(|m| filter.location_id.is_none_or(|id| m.location_id == id))
+        .filter(|m| filter.movement_type.is_none_or(|t| m.movement_type == t))
+        .filter(|m| filter.lot_id.is_none_or(|id| m.lot_id == Some(id)))
+        .collect()
+}
+
+// see helper_query::apply_lot_filter
+fn filter_lots<'a>(
+    lots: &'a [StockLot],
+    filter: &StockLotFilter,
+    search: &Option<String>,
+) -> Vec<&'a StockLot> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    lots.iter()
+        .filter(|l| l.company_id == filter.company_id)
+        .filter(|l| filter.product_id.is_none_or(|id| l.product_id == id))
+        .filter(|l| pattern.as_ref().is_none_or(|p| ilike(&l.lot_number, p)))
+        .collect()
+}
+
+// see helper_query::apply_serial_filter
+fn filter_serials<'a>(
+    serials: &'a [StockSerial],
+    filter: &StockSerialFilter,
+    search: &Option<String>,
+) -> Vec<&'a StockSerial> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    serials
+        .iter()
+        .filter(|s| s.company_id == filter.company_id)
+        .filter(|s| filter.product_id.is_none_or(|id| s.product_id == id))
+        .filter(|s| {
+            filter
+                .location_id
+                .is_none_or(|id| s.location_id == Some(id))
+        })
+        .filter(|s| filter.status.is_none_or(|status| s.status == status))
+        .filter(|s| pattern.as_ref().is_none_or(|p| ilike(&s.serial_number, p)))
+        .collect()
+}
+
+pub(crate) fn page<T>(rows: Vec<T>, query: &PaginationRequest) -> Vec<T> {
+    let offset = query.offset.unwrap_or(0) as usize;
+    let limit = query.per_page.unwrap_or(1) as usize;
+    rows.into_iter().skip(offset).take(limit).collect()
+}
+
+// same parsing as PaginationRequest::format_sort, unknown columns fail like postgres does
+pub(crate) fn sort_keys(
+    query: &PaginationRequest,
+    columns: &[&str],
+) -> Result<Vec<(String, bool)>, sqlx::Error> {
+    let Some(sort) = &query.sort else {
+        return Ok(Vec::new());
+    };
+
+    let mut keys = Vec::new();
+    for s in sort.split(",") {
+        let s = s.trim();
+        if s.is_empty() {
+            continue;
+        }
+
+        let (column, descending) = match s.strip_prefix("-") {
+            Some(column) => (column, true),
+            None => (s, false),
+        };
+        if !columns.contains(&column) {
+            return Err(sqlx::Error::ColumnNotFound(column.to_string()));
+        }
+        keys.push((column.to_string(), descending));
+    }
+    Ok(keys)
+}
+
+// stable sort, rows equal on every key keep insertion order
+pub(crate) fn compare_by(keys: &[(String, bool)], compare: impl Fn(&str) -> Ordering) -> Ordering {
+    keys.iter()
+        .map(|(column, descending)| {
+            let ordering = compare(column);
+            if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn compare_movement(a: &StockMovement, b: &StockMovement, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "product_id" => a.product_id.cmp(&b.product_id),
+        "location_id" => a.location_id.cmp(&b.location_id),
+        "movement_type" => a.movement_type.as_str().cmp(b.movement_type.as_str()),
+        "quantity" => a.quantity.cmp(&b.quantity),
+        "balance_after" => a.balance_after.cmp(&b.balance_after),
+        "reference" => compare_nullable(&a.reference, &b.reference),
+        "posted_by" => a.posted_by.cmp(&b.posted_by),
+        "posted_at" => a.posted_at.cmp(&b.posted_at),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_lot(a: &StockLot, b: &StockLot, column: &str) -> Ordering {
+    match column {
+        "product_id" => a.product_id.cmp(&b.product_id),
+        "lot_number" => a.lot_number.cmp(&b.lot_number),
+        "manufactured_on" => compare_nullable(&a.manufactured_on, &b.manufactured_on),
+        "expires_on" => compare_nullable(&a.expires_on, &b.expires_on),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "id" => a.id.cmp(&b.id),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_serial(a: &StockSerial, b: &StockSerial, column: &str) -> Ordering {
+    match column {
+        "product_id" => a.product_id.cmp(&b.product_id),
+        "serial_number" => a.serial_number.cmp(&b.serial_number),
+        "status" => a.status.as_str().cmp(b.status.as_str()),
+        "location_id" => compare_nullable(&a.location_id, &b.location_id),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "updated_at" => a.updated_at.cmp(&b.updated_at),
+        "id" => a.id.cmp(&b.id),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_balance(a: &StockBalance, b: &StockBalance, column: &str) -> Ordering {
+    match column {
+        "product_id" => a.product_id.cmp(&b.product_id),
+        "location_id" => a.location_id.cmp(&b.location_id),
+        "quantity" => a.quantity.cmp(&b.quantity),
+        "updated_at" => a.updated_at.cmp(&b.updated_at),
+        _ => Ordering::Equal,
+    }
+}
+
+// postgres puts NULL after every value when sorting ascending
+pub(crate) fn compare_nullable<T: Ord>(a: &Option<T>, b: &Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
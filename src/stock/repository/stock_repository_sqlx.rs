@@ -0,0 +1,1165 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgPool, QueryBuilder};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::{PgUnitOfWork, Transactional};
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product_unit::{ProductUnit, UnitRounding};
+use crate::stock::domain::balance::{BalanceMismatch, StockBalance, StockBalanceFilter};
+use crate::stock::domain::lot::{ExpiringLot, StockLot, StockLotBalance, StockLotFilter};
+use crate::stock::domain::movement::{
+    MovementSource, MovementType, StockMovement, StockMovementFilter,
+};
+use crate::stock::domain::serial::{
+    SerialEvent, SerialMismatch, SerialStatus, StockSerial, StockSerialFilter,
+};
+use crate::stock::domain::settings::StockSettings;
+use crate::stock::repository::helper_query::{
+    STOCK_BALANCE_DEFAULT_ORDER, STOCK_LOT_DEFAULT_ORDER, STOCK_MOVEMENT_DEFAULT_ORDER,
+    STOCK_SERIAL_DEFAULT_ORDER, apply_balance_filter, apply_lot_filter, apply_movement_filter,
+    apply_serial_filter,
+};
+use crate::stock::repository::stock_repository::StockRepository;
+
+#[derive(Clone)]
+pub struct StockRepositorySqlx {
+    pool: PgPool,
+}
+
+impl StockRepositorySqlx {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Transactional for StockRepositorySqlx {
+    type Uow = PgUnitOfWork;
+
+    #[instrument(level = "debug", skip_all)]
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.pool.begin().await
+    }
+}
+
+#[async_trait]
+impl StockRepository for StockRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_company(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM companies WHERE id = $1)"#,
+            company_id
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id))]
+    async fn get_product_company(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let company_id = sqlx::query_scalar!(
+            r#"SELECT company_id FROM products WHERE id = $1"#,
+            product_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(company_id)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id))]
+    async fn get_product_base_unit(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let unit = sqlx::query_scalar!(
+            r#"SELECT unit_of_measure FROM products WHERE id = $1"#,
+            product_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(unit)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, code = %code))]
+    async fn get_product_unit(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        code: &str,
+    ) -> Result<Option<ProductUnit>, sqlx::Error> {
+        let unit = sqlx::query_as!(
+            ProductUnit,
+            r#"
+            SELECT id, product_id, code, factor, decimal_places,
+                   rounding AS "rounding: UnitRounding", is_purchase_unit, is_sales_unit,
+                   created_at
+            FROM product_units
+            WHERE product_id = $1 AND code = $2
+            "#,
+            product_id,
+            code
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(unit)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %location_id))]
+    async fn get_location_company(
+        &self,
+        uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let company_id = sqlx::query_scalar!(
+            r#"
+            SELECT w.company_id
+            FROM warehouse_locations l
+            JOIN warehouses w ON w.id = l.warehouse_id
+            WHERE l.id = $1
+            "#,
+            location_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(company_id)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id))]
+    async fn get_warehouse_company(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let company_id = sqlx::query_scalar!(
+            r#"SELECT company_id FROM warehouses WHERE id = $1"#,
+            warehouse_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(company_id)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %location_id))]
+    async fn get_location_warehouse(
+        &self,
+        uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let warehouse_id = sqlx::query_scalar!(
+            r#"SELECT warehouse_id FROM warehouse_locations WHERE id = $1"#,
+            location_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(warehouse_id)
+    }
+
+    // a count of the warehouse, of the location or of any location above it
+    #[instrument(level = "debug", skip_all, fields(location_id = %location_id))]
+    async fn is_location_counted(
+        &self,
+        uow: &mut Self::Uow,
+        location_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_counted = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, parent_id, warehouse_id FROM warehouse_locations WHERE id = $1
+                UNION ALL
+                SELECT l.id, l.parent_id, l.warehouse_id
+                FROM warehouse_locations l
+                JOIN ancestors a ON l.id = a.parent_id
+            )
+            SELECT EXISTS (
+                SELECT 1 FROM stock_counts c
+                WHERE c.status IN ('open', 'pending_approval')
+                  AND c.warehouse_id IN (SELECT warehouse_id FROM ancestors)
+                  AND (c.location_id IS NULL OR c.location_id IN (SELECT id FROM ancestors))
+            )
+            "#,
+            location_id
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_counted.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn get_stock_settings(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Option<StockSettings>, sqlx::Error> {
+        let settings = sqlx::query_as!(
+            StockSettings,
+            r#"
+            SELECT company_id, allow_negative_stock, updated_at
+            FROM company_stock_settings
+            WHERE company_id = $1
+            "#,
+            company_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(settings)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %settings.company_id))]
+    async fn save_stock_settings(
+        &self,
+        uow: &mut Self::Uow,
+        settings: StockSettings,
+    ) -> Result<StockSettings, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO company_stock_settings (company_id, allow_negative_stock, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (company_id) DO UPDATE
+            SET allow_negative_stock = EXCLUDED.allow_negative_stock,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            settings.company_id,
+            settings.allow_negative_stock,
+            settings.updated_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(settings)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, location_id = %location_id))]
+    async fn lock_balance(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        product_id: &Uuid,
+        location_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        // a concurrent first posting waits on the conflicting insert, then
+        // both lock the same row
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_balances (product_id, location_id, company_id, quantity, updated_at)
+            VALUES ($1, $2, $3, 0, now())
+            ON CONFLICT (product_id, location_id) DO NOTHING
+            "#,
+            product_id,
+            location_id,
+            company_id,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        let quantity = sqlx::query_scalar!(
+            r#"
+            SELECT quantity
+            FROM stock_balances
+            WHERE product_id = $1 AND location_id = $2
+            FOR UPDATE
+            "#,
+            product_id,
+            location_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(quantity)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, location_id = %location_id))]
+    async fn update_balance(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        location_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_balances
+            SET quantity = $1,
+                updated_at = $2
+            WHERE product_id = $3 AND location_id = $4
+            RETURNING product_id
+            "#,
+            quantity,
+            updated_at,
+            product_id,
+            location_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, warehouse_id = %warehouse_id))]
+    async fn get_warehouse_usable(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+        today: NaiveDate,
+    ) -> Result<i64, sqlx::Error> {
+        let usable = sqlx::query_scalar!(
+            r#"
+            SELECT (
+                SELECT COALESCE(SUM(b.quantity), 0)::bigint
+                FROM stock_balances b
+                JOIN warehouse_locations l ON l.id = b.location_id
+                WHERE b.product_id = $1 AND l.warehouse_id = $2
+            ) - (
+                SELECT COALESCE(SUM(lb.quantity), 0)::bigint
+                FROM stock_lot_balances lb
+                JOIN stock_lots lo ON lo.id = lb.lot_id
+                JOIN warehouse_locations l ON l.id = lb.location_id
+                WHERE lb.product_id = $1 AND l.warehouse_id = $2 AND lo.expires_on < $3
+            ) AS "usable!"
+            "#,
+            product_id,
+            warehouse_id,
+            today,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(usable)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, warehouse_id = %warehouse_id))]
+    async fn lock_reservation(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        product_id: &Uuid,
+        warehouse_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        // a concurrent first reservation waits on the conflicting insert, then
+        // both lock the same row
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_reservations (product_id, warehouse_id, company_id, quantity, updated_at)
+            VALUES ($1, $2, $3, 0, now())
+            ON CONFLICT (product_id, warehouse_id) DO NOTHING
+            "#,
+            product_id,
+            warehouse_id,
+            company_id,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        let quantity = sqlx::query_scalar!(
+            r#"
+            SELECT quantity
+            FROM stock_reservations
+            WHERE product_id = $1 AND warehouse_id = $2
+            FOR UPDATE
+            "#,
+            product_id,
+            warehouse_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(quantity)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_balances(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockBalanceFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(*) FROM stock_balances");
+
+        apply_balance_filter(&mut qb, filter);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_balances(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockBalanceFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockBalance>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT product_id, location_id, company_id, quantity, updated_at
+            FROM stock_balances
+        ",
+        );
+
+        apply_balance_filter(&mut qb, filter);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_BALANCE_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let balances = qb
+            .build_query_as::<StockBalance>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(balances)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn find_balance_mismatches(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<BalanceMismatch>, sqlx::Error> {
+        let mismatches = sqlx::query_as!(
+            BalanceMismatch,
+            r#"
+            SELECT COALESCE(b.product_id, l.product_id) AS "product_id!",
+                   COALESCE(b.location_id, l.location_id) AS "location_id!",
+                   COALESCE(b.quantity, 0) AS "balance!",
+                   COALESCE(l.total, 0) AS "ledger!"
+            FROM (
+                SELECT product_id, location_id, quantity
+                FROM stock_balances
+                WHERE company_id = $1
+            ) b
+            FULL OUTER JOIN (
+                SELECT product_id, location_id, SUM(quantity)::bigint AS total
+                FROM stock_movements
+                WHERE company_id = $1
+                GROUP BY product_id, location_id
+            ) l ON l.product_id = b.product_id AND l.location_id = b.location_id
+            WHERE COALESCE(b.quantity, 0) <> COALESCE(l.total, 0)
+            ORDER BY 1, 2
+            "#,
+            company_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(mismatches)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(movement_id = %movement.id))]
+    async fn create_movement(
+        &self,
+        uow: &mut Self::Uow,
+        movement: StockMovement,
+    ) -> Result<StockMovement, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_movements
+            (id, company_id, product_id, location_id, movement_type, quantity, balance_after,
+             reversal_of, source, source_id, lot_id, reference, note, posted_by, posted_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+            movement.id,
+            movement.company_id,
+            movement.product_id,
+            movement.location_id,
+            movement.movement_type.as_str(),
+            movement.quantity,
+            movement.balance_after,
+            movement.reversal_of,
+            movement.source.as_str(),
+            movement.source_id,
+            movement.lot_id,
+            movement.reference,
+            movement.note,
+            movement.posted_by,
+            movement.posted_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(movement)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(movement_id = %id))]
+    async fn get_movement_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockMovement>, sqlx::Error> {
+        let movement = sqlx::query_as!(
+            StockMovement,
+            r#"
+            SELECT id, company_id, product_id, location_id,
+                   movement_type AS "movement_type: MovementType", quantity, balance_after,
+                   reversal_of, source AS "source: MovementSource", source_id, lot_id,
+                   reference, note, posted_by, posted_at
+            FROM stock_movements
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(movement)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(movement_id = %movement_id))]
+    async fn find_reversal(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+    ) -> Result<Option<StockMovement>, sqlx::Error> {
+        let movement = sqlx::query_as!(
+            StockMovement,
+            r#"
+            SELECT id, company_id, product_id, location_id,
+                   movement_type AS "movement_type: MovementType", quantity, balance_after,
+                   reversal_of, source AS "source: MovementSource", source_id, lot_id,
+                   reference, note, posted_by, posted_at
+            FROM stock_movements
+            WHERE reversal_of = $1
+            "#,
+            movement_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(movement)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_movements(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockMovementFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM stock_movements");
+
+        apply_movement_filter(&mut qb, filter);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_movements(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockMovementFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockMovement>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, product_id, location_id, movement_type, quantity,
+                   balance_after, reversal_of, source, source_id, lot_id, reference, note,
+                   posted_by, posted_at
+            FROM stock_movements
+        ",
+        );
+
+        apply_movement_filter(&mut qb, filter);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_MOVEMENT_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let movements = qb
+            .build_query_as::<StockMovement>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(movements)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id))]
+    async fn is_product_lot_tracked(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_lot_tracked = sqlx::query_scalar!(
+            r#"SELECT is_lot_tracked FROM products WHERE id = $1"#,
+            product_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(is_lot_tracked.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, lot_number = %lot_number))]
+    async fn check_existing_lot_number(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        lot_number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM stock_lots WHERE product_id = $1 AND lot_number = $2
+            )
+            "#,
+            product_id,
+            lot_number,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lot_id = %lot.id))]
+    async fn create_lot(
+        &self,
+        uow: &mut Self::Uow,
+        lot: StockLot,
+    ) -> Result<StockLot, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_lots
+            (id, company_id, product_id, lot_number, manufactured_on, expires_on, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            lot.id,
+            lot.company_id,
+            lot.product_id,
+            lot.lot_number,
+            lot.manufactured_on,
+            lot.expires_on,
+            lot.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(lot)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lot_id = %id))]
+    async fn get_lot_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockLot>, sqlx::Error> {
+        let lot = sqlx::query_as!(
+            StockLot,
+            r#"
+            SELECT id, company_id, product_id, lot_number, manufactured_on, expires_on,
+                   created_at
+            FROM stock_lots
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(lot)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_lots(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM stock_lots");
+
+        apply_lot_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_lots(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockLot>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, product_id, lot_number, manufactured_on, expires_on,
+                   created_at
+            FROM stock_lots
+        ",
+        );
+
+        apply_lot_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_LOT_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let lots = qb
+            .build_query_as::<StockLot>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(lots)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lot_id = %lot_id, location_id = %location_id))]
+    async fn lock_lot_balance(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        lot_id: &Uuid,
+        product_id: &Uuid,
+        location_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_lot_balances
+            (lot_id, location_id, product_id, company_id, quantity, updated_at)
+            VALUES ($1, $2, $3, $4, 0, now())
+            ON CONFLICT (lot_id, location_id) DO NOTHING
+            "#,
+            lot_id,
+            location_id,
+            product_id,
+            company_id,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        let quantity = sqlx::query_scalar!(
+            r#"
+            SELECT quantity
+            FROM stock_lot_balances
+            WHERE lot_id = $1 AND location_id = $2
+            FOR UPDATE
+            "#,
+            lot_id,
+            location_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(quantity)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lot_id = %lot_id, location_id = %location_id))]
+    async fn update_lot_balance(
+        &self,
+        uow: &mut Self::Uow,
+        lot_id: &Uuid,
+        location_id: &Uuid,
+        quantity: i64,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_lot_balances
+            SET quantity = $1,
+                updated_at = $2
+            WHERE lot_id = $3 AND location_id = $4
+            RETURNING lot_id
+            "#,
+            quantity,
+            updated_at,
+            lot_id,
+            location_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(lot_id = %lot_id))]
+    async fn find_lot_balances(
+        &self,
+        uow: &mut Self::Uow,
+        lot_id: &Uuid,
+    ) -> Result<Vec<StockLotBalance>, sqlx::Error> {
+        let balances = sqlx::query_as!(
+            StockLotBalance,
+            r#"
+            SELECT lot_id, location_id, product_id, company_id, quantity, updated_at
+            FROM stock_lot_balances
+            WHERE lot_id = $1
+            ORDER BY location_id
+            "#,
+            lot_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(balances)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id, expires_on_or_before = %expires_on_or_before))]
+    async fn find_expiring_lots(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        expires_on_or_before: NaiveDate,
+        warehouse_id: Option<&Uuid>,
+    ) -> Result<Vec<ExpiringLot>, sqlx::Error> {
+        let lots = sqlx::query_as!(
+            ExpiringLot,
+            r#"
+            SELECT l.id AS lot_id, l.product_id, l.lot_number,
+                   l.expires_on AS "expires_on!",
+                   SUM(b.quantity)::bigint AS "on_hand!"
+            FROM stock_lots l
+            JOIN stock_lot_balances b ON b.lot_id = l.id
+            WHERE l.company_id = $1
+              AND l.expires_on <= $2
+              AND ($3::uuid IS NULL OR b.location_id IN (
+                  SELECT id FROM warehouse_locations WHERE warehouse_id = $3
+              ))
+            GROUP BY l.id
+            HAVING SUM(b.quantity) > 0
+            ORDER BY l.expires_on, l.lot_number, l.id
+            "#,
+            company_id,
+            expires_on_or_before,
+            warehouse_id as Option<&Uuid>,
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lots)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id))]
+    async fn is_product_serialised(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_serialised = sqlx::query_scalar!(
+            r#"SELECT is_serialised FROM products WHERE id = $1"#,
+            product_id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(is_serialised.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(product_id = %product_id, serial_number = %serial_number))]
+    async fn lock_serial(
+        &self,
+        uow: &mut Self::Uow,
+        product_id: &Uuid,
+        serial_number: &str,
+    ) -> Result<Option<StockSerial>, sqlx::Error> {
+        let serial = sqlx::query_as!(
+            StockSerial,
+            r#"
+            SELECT id, company_id, product_id, serial_number,
+                   status AS "status: SerialStatus", location_id, created_at, updated_at
+            FROM stock_serials
+            WHERE product_id = $1 AND serial_number = $2
+            FOR UPDATE
+            "#,
+            product_id,
+            serial_number,
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(serial)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(serial_id = %serial.id))]
+    async fn create_serial(
+        &self,
+        uow: &mut Self::Uow,
+        serial: StockSerial,
+    ) -> Result<StockSerial, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_serials
+            (id, company_id, product_id, serial_number, status, location_id, created_at,
+             updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            serial.id,
+            serial.company_id,
+            serial.product_id,
+            serial.serial_number,
+            serial.status.as_str(),
+            serial.location_id,
+            serial.created_at,
+            serial.updated_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(serial)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(serial_id = %serial.id))]
+    async fn update_serial(
+        &self,
+        uow: &mut Self::Uow,
+        serial: StockSerial,
+    ) -> Result<StockSerial, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_serials
+            SET status = $1,
+                location_id = $2,
+                updated_at = $3
+            WHERE id = $4
+            RETURNING id
+            "#,
+            serial.status.as_str(),
+            serial.location_id,
+            serial.updated_at,
+            serial.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(serial)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(movement_id = %movement_id, serials = serial_ids.len()))]
+    async fn create_movement_serials(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+        serial_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_movement_serials (movement_id, serial_id)
+            SELECT $1, serial_id FROM UNNEST($2::uuid[]) AS s(serial_id)
+            "#,
+            movement_id,
+            serial_ids,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(movement_id = %movement_id))]
+    async fn find_movement_serial_numbers(
+        &self,
+        uow: &mut Self::Uow,
+        movement_id: &Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let numbers = sqlx::query_scalar!(
+            r#"
+            SELECT s.serial_number
+            FROM stock_movement_serials ms
+            JOIN stock_serials s ON s.id = ms.serial_id
+            WHERE ms.movement_id = $1
+            ORDER BY s.serial_number
+            "#,
+            movement_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(numbers)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(serial_id = %id))]
+    async fn get_serial_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockSerial>, sqlx::Error> {
+        let serial = sqlx::query_as!(
+            StockSerial,
+            r#"
+            SELECT id, company_id, product_id, serial_number,
+                   status AS "status: SerialStatus", location_id, created_at, updated_at
+            FROM stock_serials
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(serial)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_serials(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM stock_serials");
+
+        apply_serial_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_serials(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockSerial>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, product_id, serial_number, status, location_id,
+                   created_at, updated_at
+            FROM stock_serials
+        ",
+        );
+
+        apply_serial_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_SERIAL_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let serials = qb
+            .build_query_as::<StockSerial>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(serials)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(serial_id = %serial_id))]
+    async fn find_serial_events(
+        &self,
+        uow: &mut Self::Uow,
+        serial_id: &Uuid,
+    ) -> Result<Vec<SerialEvent>, sqlx::Error> {
+        let events = sqlx::query_as!(
+            SerialEvent,
+            r#"
+            SELECT m.id AS movement_id,
+                   m.movement_type AS "movement_type: MovementType",
+                   m.location_id,
+                   SIGN(m.quantity)::bigint AS "quantity!",
+                   m.reference,
+                   po.supplier_id AS "supplier_id?",
+                   so.customer_id AS "customer_id?",
+                   m.posted_by,
+                   m.posted_at
+            FROM stock_movement_serials ms
+            JOIN stock_movements m ON m.id = ms.movement_id
+            LEFT JOIN goods_receipt_lines grl ON grl.movement_id = m.id
+            LEFT JOIN goods_receipts gr ON gr.id = grl.goods_receipt_id
+            LEFT JOIN purchase_orders po ON po.id = gr.purchase_order_id
+            LEFT JOIN shipment_lines sl ON sl.movement_id = m.id
+            LEFT JOIN shipments sh ON sh.id = sl.shipment_id
+            LEFT JOIN sales_orders so ON so.id = sh.sales_order_id
+            WHERE ms.serial_id = $1
+            ORDER BY m.posted_at, m.id
+            "#,
+            serial_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(events)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn find_serial_mismatches(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<SerialMismatch>, sqlx::Error> {
+        let mismatches = sqlx::query_as!(
+            SerialMismatch,
+            r#"
+            SELECT COALESCE(b.product_id, s.product_id) AS "product_id!",
+                   COALESCE(b.location_id, s.location_id) AS "location_id!",
+                   COALESCE(b.quantity, 0) AS "balance!",
+                   COALESCE(s.total, 0) AS "in_stock!"
+            FROM (
+                SELECT b.product_id, b.location_id, b.quantity
+                FROM stock_balances b
+                JOIN products p ON p.id = b.product_id
+                WHERE b.company_id = $1 AND p.is_serialised
+            ) b
+            FULL OUTER JOIN (
+                SELECT product_id, location_id, COUNT(id) AS total
+                FROM stock_serials
+                WHERE company_id = $1 AND status = 'in_stock'
+                GROUP BY product_id, location_id
+            ) s ON s.product_id = b.product_id AND s.location_id = b.location_id
+            WHERE COALESCE(b.quantity, 0) <> COALESCE(s.total, 0)
+            ORDER BY 1, 2
+            "#,
+            company_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(mismatches)
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::transfer::{InTransitQuantity, StockTransfer, StockTransferFilter, TransferLine};
+use crate::stock::repository::stock_repository::StockRepository;
+
+// transfers post to the ledger in the same unit of work, so they live on top of
+// the stock repository
+#[async_trait]
+pub trait TransferRepository: StockRepository {
+    async fn check_existing_transfer_number(&self, uow: &mut Self::Uow, company_id: &Uuid, number: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_transfer(&self, uow: &mut Self::Uow, transfer: StockTransfer) -> Result<StockTransfer, sqlx::Error>;
+    async fn update_transfer(&self, uow: &mut Self::Uow, transfer: StockTransfer) -> Result<StockTransfer, sqlx::Error>;
+    async fn get_transfer_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockTransfer>, sqlx::Error>;
+    // like get_transfer_by_id, the row stays locked until the unit of work
+    // finishes so status changes of one transfer queue up
+    async fn lock_transfer(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<StockTransfer>, sqlx::Error>;
+    async fn count_transfers(&self, uow: &mut Self::Uow, filter: &StockTransferFilter, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_transfers(&self, uow: &mut Self::Uow, filter: &StockTransferFilter, query: &PaginationRequest) -> Result<Vec<StockTransfer>, sqlx::Error>;
+    // ordered by position
+    async fn find_transfer_lines(&self, uow: &mut Self::Uow, transfer_id: &Uuid) -> Result<Vec<TransferLine>, sqlx::Error>;
+    // drops the lines of the transfer and stores the given ones
+    async fn replace_transfer_lines(&self, uow: &mut Self::Uow, transfer_id: &Uuid, lines: Vec<TransferLine>) -> Result<Vec<TransferLine>, sqlx::Error>;
+    async fn update_received_quantity(&self, uow: &mut Self::Uow, line_id: &Uuid, received_quantity: i64) -> Result<(), sqlx::Error>;
+    // ordered by product, source and destination warehouse
+    async fn find_in_transit(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<Vec<InTransitQuantity>, sqlx::Error>;
+}
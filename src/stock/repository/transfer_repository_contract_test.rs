@@ -0,0 +1,504 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// stock_repository_contract_test. ACM has a north and a south warehouse with a
+// bin each and two products, GLB one warehouse. postgres runs need
+// TEST_DATABASE_URL and are skipped without it.
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{
+    ACME_ID, Catalogue, GLOBEX_ID, at, bin, companies, product, seed_catalogue, seed_companies,
+    warehouse,
+};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::product::repository::product_repository_sqlx::ProductRepositorySqlx;
+use crate::stock::domain::transfer::{
+    InTransitQuantity, StockTransfer, StockTransferFilter, TransferLine, TransferStatus,
+};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::stock::repository::transfer_repository::TransferRepository;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+const BOLT_ID: Uuid = Uuid::from_u128(11);
+const NUT_ID: Uuid = Uuid::from_u128(12);
+const NORTH_ID: Uuid = Uuid::from_u128(21);
+const SOUTH_ID: Uuid = Uuid::from_u128(22);
+const GLOBEX_MAIN_ID: Uuid = Uuid::from_u128(23);
+const NORTH_BIN_ID: Uuid = Uuid::from_u128(31);
+const SOUTH_BIN_ID: Uuid = Uuid::from_u128(32);
+const GLOBEX_BIN_ID: Uuid = Uuid::from_u128(33);
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                let products = super::ProductRepositoryMemory::new(companies.clone());
+                let warehouses = super::WarehouseRepositoryMemory::new(companies.clone());
+                super::seed_catalogue(&products, &warehouses, super::catalogue()).await;
+                super::$scenario(&super::StockRepositoryMemory::new(
+                    companies, products, warehouses,
+                ))
+                .await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::seed_catalogue(
+                    &super::ProductRepositorySqlx::new(db.pool.clone()),
+                    &super::WarehouseRepositorySqlx::new(db.pool.clone()),
+                    super::catalogue(),
+                )
+                .await;
+                super::$scenario(&super::StockRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(resolves_warehouses_and_locations);
+contract_test!(stores_transfers_and_lines);
+contract_test!(filters_sorts_and_pages_transfers);
+contract_test!(enforces_transfer_constraints);
+contract_test!(sums_open_lines_in_transit);
+
+fn catalogue() -> Catalogue {
+    Catalogue {
+        products: vec![
+            product(BOLT_ID, ACME_ID, "BOLT"),
+            product(NUT_ID, ACME_ID, "NUT"),
+        ],
+        warehouses: vec![
+            warehouse(NORTH_ID, ACME_ID, "NORTH"),
+            warehouse(SOUTH_ID, ACME_ID, "SOUTH"),
+            warehouse(GLOBEX_MAIN_ID, GLOBEX_ID, "MAIN"),
+        ],
+        locations: vec![
+            bin(NORTH_BIN_ID, NORTH_ID, "A"),
+            bin(SOUTH_BIN_ID, SOUTH_ID, "A"),
+            bin(GLOBEX_BIN_ID, GLOBEX_MAIN_ID, "A"),
+        ],
+    }
+}
+
+fn transfer(number: &str, status: TransferStatus, minutes: i64) -> StockTransfer {
+    StockTransfer {
+        id: Uuid::new_v4(),
+        company_id: ACME_ID,
+        number: number.into(),
+        source_warehouse_id: NORTH_ID,
+        destination_warehouse_id: SOUTH_ID,
+        status,
+        note: None,
+        created_by: Uuid::from_u128(99),
+        created_at: at(minutes),
+        dispatched_at: None,
+        received_at: None,
+        cancelled_at: None,
+    }
+}
+
+fn line(transfer: &StockTransfer, position: i32, product_id: Uuid, quantity: i64) -> TransferLine {
+    TransferLine {
+        id: Uuid::new_v4(),
+        transfer_id: transfer.id,
+        position,
+        product_id,
+        source_location_id: NORTH_BIN_ID,
+        destination_location_id: SOUTH_BIN_ID,
+        lot_id: None,
+        quantity,
+        received_quantity: 0,
+        serial_numbers: Vec::new(),
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+fn filter(status: Option<TransferStatus>) -> StockTransferFilter {
+    StockTransferFilter {
+        company_id: ACME_ID,
+        status,
+    }
+}
+
+async fn seed_transfers<R: TransferRepository>(
+    repo: &R,
+    transfers: &[(StockTransfer, Vec<TransferLine>)],
+) {
+    let mut uow = repo.begin().await.unwrap();
+    for (transfer, lines) in transfers {
+        repo.create_transfer(&mut uow, transfer.clone())
+            .await
+            .unwrap();
+        repo.replace_transfer_lines(&mut uow, &transfer.id, lines.clone())
+            .await
+            .unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn numbers(transfers: &[StockTransfer]) -> Vec<&str> {
+    transfers.iter().map(|t| t.number.as_str()).collect()
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn resolves_warehouses_and_locations<R: TransferRepository>(repo: &R) {
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (NORTH_ID, Some(ACME_ID)),
+        (GLOBEX_MAIN_ID, Some(GLOBEX_ID)),
+        (Uuid::new_v4(), None),
+    ];
+    for (warehouse_id, expected) in cases {
+        let company = repo
+            .get_warehouse_company(&mut uow, &warehouse_id)
+            .await
+            .unwrap();
+        assert_eq!(company, expected);
+    }
+    let cases = [
+        (SOUTH_BIN_ID, Some(SOUTH_ID)),
+        (GLOBEX_BIN_ID, Some(GLOBEX_MAIN_ID)),
+        (Uuid::new_v4(), None),
+    ];
+    for (location_id, expected) in cases {
+        let warehouse = repo
+            .get_location_warehouse(&mut uow, &location_id)
+            .await
+            .unwrap();
+        assert_eq!(warehouse, expected);
+    }
+    uow.rollback().await.unwrap();
+}
+
+async fn stores_transfers_and_lines<R: TransferRepository>(repo: &R) {
+    let draft = transfer("TR-1", TransferStatus::Draft, 0);
+    let lines = vec![line(&draft, 1, BOLT_ID, 5), line(&draft, 2, NUT_ID, 3)];
+    seed_transfers(repo, &[(draft.clone(), lines.clone())]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_transfer_by_id(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(stored, Some(draft.clone()));
+    let locked = repo.lock_transfer(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(locked, Some(draft.clone()));
+    assert_eq!(
+        repo.get_transfer_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        repo.find_transfer_lines(&mut uow, &draft.id).await.unwrap(),
+        lines
+    );
+
+    let taken = repo
+        .check_existing_transfer_number(&mut uow, &ACME_ID, "TR-1", None)
+        .await
+        .unwrap();
+    assert!(taken);
+    let own = repo
+        .check_existing_transfer_number(&mut uow, &ACME_ID, "TR-1", Some(&draft.id))
+        .await
+        .unwrap();
+    assert!(!own);
+    let other_company = repo
+        .check_existing_transfer_number(&mut uow, &GLOBEX_ID, "TR-1", None)
+        .await
+        .unwrap();
+    assert!(!other_company);
+
+    // the new lines replace the old ones in their own order
+    let replaced = vec![line(&draft, 1, NUT_ID, 4)];
+    repo.replace_transfer_lines(&mut uow, &draft.id, replaced.clone())
+        .await
+        .unwrap();
+    let dispatched = StockTransfer {
+        status: TransferStatus::Dispatched,
+        note: Some("by truck".into()),
+        dispatched_at: Some(at(5)),
+        ..draft.clone()
+    };
+    repo.update_transfer(&mut uow, dispatched.clone())
+        .await
+        .unwrap();
+    repo.update_received_quantity(&mut uow, &replaced[0].id, 2)
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let stored = repo.get_transfer_by_id(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(stored, Some(dispatched));
+    let stored = repo.find_transfer_lines(&mut uow, &draft.id).await.unwrap();
+    assert_eq!(
+        stored,
+        vec![TransferLine {
+            received_quantity: 2,
+            ..replaced[0].clone()
+        }]
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn filters_sorts_and_pages_transfers<R: TransferRepository>(repo: &R) {
+    let first = transfer("TR-2", TransferStatus::Draft, 0);
+    let second = StockTransfer {
+        dispatched_at: Some(at(3)),
+        ..transfer("TR-1", TransferStatus::Dispatched, 1)
+    };
+    let third = transfer("OTHER-1", TransferStatus::Draft, 2);
+    seed_transfers(repo, &[(first, vec![]), (second, vec![]), (third, vec![])]).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let cases = [
+        (None, None, None, 10, 0, vec!["TR-2", "TR-1", "OTHER-1"]),
+        (
+            None,
+            None,
+            Some("number"),
+            10,
+            0,
+            vec!["OTHER-1", "TR-1", "TR-2"],
+        ),
+        (None, None, Some("-created_at"), 2, 1, vec!["TR-1", "TR-2"]),
+        (
+            Some(TransferStatus::Draft),
+            None,
+            None,
+            10,
+            0,
+            vec!["TR-2", "OTHER-1"],
+        ),
+        (
+            None,
+            Some("tr-"),
+            Some("-number"),
+            10,
+            0,
+            vec!["TR-2", "TR-1"],
+        ),
+    ];
+    for (status, search, sort, per_page, offset, expected) in cases {
+        let query = query(search, sort, per_page, offset);
+        let found = repo
+            .find_transfers(&mut uow, &filter(status), &query)
+            .await
+            .unwrap();
+        assert_eq!(numbers(&found), expected, "{status:?} {search:?} {sort:?}");
+    }
+
+    let count = repo
+        .count_transfers(&mut uow, &filter(None), &query(Some("TR"), None, 1, 0))
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    let globex = StockTransferFilter {
+        company_id: GLOBEX_ID,
+        status: None,
+    };
+    let count = repo
+        .count_transfers(&mut uow, &globex, &query(None, None, 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+    uow.rollback().await.unwrap();
+}
+
+async fn enforces_transfer_constraints<R: TransferRepository>(repo: &R) {
+    let stored = transfer("TR-1", TransferStatus::Draft, 0);
+    seed_transfers(repo, &[(stored.clone(), vec![])]).await;
+
+    let cases = [
+        (
+            StockTransfer {
+                id: stored.id,
+                ..transfer("TR-2", TransferStatus::Draft, 1)
+            },
+            "stock_transfers_pkey",
+        ),
+        (
+            transfer("TR-1", TransferStatus::Draft, 1),
+            "stock_transfers_company_id_number_key",
+        ),
+        // warehouses of another company fail the composite keys
+        (
+            StockTransfer {
+                source_warehouse_id: GLOBEX_MAIN_ID,
+                ..transfer("TR-2", TransferStatus::Draft, 1)
+            },
+            "stock_transfers_source_warehouse_fkey",
+        ),
+        (
+            StockTransfer {
+                destination_warehouse_id: Uuid::new_v4(),
+                ..transfer("TR-2", TransferStatus::Draft, 1)
+            },
+            "stock_transfers_destination_warehouse_fkey",
+        ),
+    ];
+    for (transfer, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo.create_transfer(&mut uow, transfer).await.unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let cases = [
+        (
+            TransferLine {
+                product_id: Uuid::new_v4(),
+                ..line(&stored, 1, BOLT_ID, 1)
+            },
+            "stock_transfer_lines_product_id_fkey",
+        ),
+        (
+            TransferLine {
+                source_location_id: Uuid::new_v4(),
+                ..line(&stored, 1, BOLT_ID, 1)
+            },
+            "stock_transfer_lines_source_location_id_fkey",
+        ),
+        (
+            TransferLine {
+                destination_location_id: Uuid::new_v4(),
+                ..line(&stored, 1, BOLT_ID, 1)
+            },
+            "stock_transfer_lines_destination_location_id_fkey",
+        ),
+    ];
+    for (line, constraint) in cases {
+        let mut uow = repo.begin().await.unwrap();
+        let err = repo
+            .replace_transfer_lines(&mut uow, &stored.id, vec![line])
+            .await
+            .unwrap_err();
+        assert_eq!(violated(err).as_deref(), Some(constraint));
+        uow.rollback().await.unwrap();
+    }
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .replace_transfer_lines(
+            &mut uow,
+            &stored.id,
+            vec![line(&stored, 1, BOLT_ID, 1), line(&stored, 1, NUT_ID, 1)],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("stock_transfer_lines_transfer_id_position_key")
+    );
+    uow.rollback().await.unwrap();
+}
+
+async fn sums_open_lines_in_transit<R: TransferRepository>(repo: &R) {
+    let draft = transfer("TR-1", TransferStatus::Draft, 0);
+    let dispatched = transfer("TR-2", TransferStatus::Dispatched, 1);
+    let partial = transfer("TR-3", TransferStatus::PartiallyReceived, 2);
+    let received = transfer("TR-4", TransferStatus::Received, 3);
+    let back = StockTransfer {
+        source_warehouse_id: SOUTH_ID,
+        destination_warehouse_id: NORTH_ID,
+        ..transfer("TR-5", TransferStatus::Dispatched, 4)
+    };
+    let back_line = TransferLine {
+        source_location_id: SOUTH_BIN_ID,
+        destination_location_id: NORTH_BIN_ID,
+        ..line(&back, 1, BOLT_ID, 2)
+    };
+    seed_transfers(
+        repo,
+        &[
+            (draft.clone(), vec![line(&draft, 1, BOLT_ID, 100)]),
+            (
+                dispatched.clone(),
+                vec![
+                    line(&dispatched, 1, BOLT_ID, 5),
+                    line(&dispatched, 2, NUT_ID, 1),
+                ],
+            ),
+            (
+                partial.clone(),
+                vec![
+                    TransferLine {
+                        received_quantity: 3,
+                        ..line(&partial, 1, BOLT_ID, 4)
+                    },
+                    TransferLine {
+                        received_quantity: 2,
+                        ..line(&partial, 2, NUT_ID, 2)
+                    },
+                ],
+            ),
+            (
+                received.clone(),
+                vec![TransferLine {
+                    received_quantity: 7,
+                    ..line(&received, 1, NUT_ID, 7)
+                }],
+            ),
+            (back, vec![back_line]),
+        ],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let found = repo.find_in_transit(&mut uow, &ACME_ID).await.unwrap();
+    assert_eq!(
+        found,
+        vec![
+            InTransitQuantity {
+                product_id: BOLT_ID,
+                source_warehouse_id: NORTH_ID,
+                destination_warehouse_id: SOUTH_ID,
+                quantity: 6,
+            },
+            InTransitQuantity {
+                product_id: BOLT_ID,
+                source_warehouse_id: SOUTH_ID,
+                destination_warehouse_id: NORTH_ID,
+                quantity: 2,
+            },
+            InTransitQuantity {
+                product_id: NUT_ID,
+                source_warehouse_id: NORTH_ID,
+                destination_warehouse_id: SOUTH_ID,
+                quantity: 1,
+            },
+        ]
+    );
+    let found = repo.find_in_transit(&mut uow, &GLOBEX_ID).await.unwrap();
+    assert!(found.is_empty());
+    uow.rollback().await.unwrap();
+}
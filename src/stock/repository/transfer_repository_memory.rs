@@ -0,0 +1,331 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::app_database::memory::{foreign_key_violation, ilike, unique_violation};
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::transfer::{
+    InTransitQuantity, StockTransfer, StockTransferFilter, TransferLine,
+};
+use crate::stock::repository::helper_query::STOCK_TRANSFER_SORT_COLUMNS;
+use crate::stock::repository::stock_repository_memory::{
+    StockRepositoryMemory, compare_by, compare_nullable, page, sort_keys,
+};
+use crate::stock::repository::transfer_repository::TransferRepository;
+
+// mirrors the TransferRepository part of StockRepositorySqlx, the composite
+// warehouse keys are checked against the committed warehouses
+impl StockRepositoryMemory {
+    async fn check_transfer_references(&self, transfer: &StockTransfer) -> Result<(), sqlx::Error> {
+        if !self.company_exists(&transfer.company_id).await {
+            return Err(foreign_key_violation("stock_transfers_company_id_fkey"));
+        }
+        let source_company = self.warehouse_company(&transfer.source_warehouse_id).await;
+        if source_company != Some(transfer.company_id) {
+            return Err(foreign_key_violation(
+                "stock_transfers_source_warehouse_fkey",
+            ));
+        }
+        let destination_company = self
+            .warehouse_company(&transfer.destination_warehouse_id)
+            .await;
+        if destination_company != Some(transfer.company_id) {
+            return Err(foreign_key_violation(
+                "stock_transfers_destination_warehouse_fkey",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TransferRepository for StockRepositoryMemory {
+    async fn check_existing_transfer_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().transfers.iter().any(|t| {
+            t.company_id == *company_id && t.number == number && id.is_none_or(|id| t.id != *id)
+        }))
+    }
+
+    async fn create_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        transfer: StockTransfer,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        let transfers = &uow.state().transfers;
+        if transfers.iter().any(|t| t.id == transfer.id) {
+            return Err(unique_violation("stock_transfers_pkey"));
+        }
+        if transfers
+            .iter()
+            .any(|t| t.company_id == transfer.company_id && t.number == transfer.number)
+        {
+            return Err(unique_violation("stock_transfers_company_id_number_key"));
+        }
+        self.check_transfer_references(&transfer).await?;
+        uow.state_mut().transfers.push(transfer.clone());
+
+        Ok(transfer)
+    }
+
+    async fn update_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        transfer: StockTransfer,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        let transfers = &uow.state().transfers;
+        if transfers.iter().any(|t| {
+            t.id != transfer.id
+                && t.company_id == transfer.company_id
+                && t.number == transfer.number
+        }) {
+            return Err(unique_violation("stock_transfers_company_id_number_key"));
+        }
+        self.check_transfer_references(&transfer).await?;
+        let stored = uow
+            .state_mut()
+            .transfers
+            .iter_mut()
+            .find(|t| t.id == transfer.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        // the company and the creator never change
+        *stored = StockTransfer {
+            company_id: stored.company_id,
+            created_by: stored.created_by,
+            created_at: stored.created_at,
+            ..transfer.clone()
+        };
+
+        Ok(transfer)
+    }
+
+    async fn get_transfer_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockTransfer>, sqlx::Error> {
+        Ok(uow.state().transfers.iter().find(|t| t.id == *id).cloned())
+    }
+
+    async fn lock_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockTransfer>, sqlx::Error> {
+        self.get_transfer_by_id(uow, id).await
+    }
+
+    async fn count_transfers(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(filter_transfers(&uow.state().transfers, filter, &query.search).len() as i64)
+    }
+
+    async fn find_transfers(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockTransfer>, sqlx::Error> {
+        let keys = sort_keys(query, &STOCK_TRANSFER_SORT_COLUMNS)?;
+        let mut transfers: Vec<StockTransfer> =
+            filter_transfers(&uow.state().transfers, filter, &query.search)
+                .into_iter()
+                .cloned()
+                .collect();
+        if keys.is_empty() {
+            transfers.sort_by_key(|t| (t.created_at, t.id));
+        } else {
+            transfers.sort_by(|a, b| compare_by(&keys, |column| compare_transfer(a, b, column)));
+        }
+
+        Ok(page(transfers, query))
+    }
+
+    async fn find_transfer_lines(
+        &self,
+        uow: &mut Self::Uow,
+        transfer_id: &Uuid,
+    ) -> Result<Vec<TransferLine>, sqlx::Error> {
+        let mut lines: Vec<TransferLine> = uow
+            .state()
+            .transfer_lines
+            .iter()
+            .filter(|l| l.transfer_id == *transfer_id)
+            .cloned()
+            .collect();
+        lines.sort_by_key(|l| l.position);
+
+        Ok(lines)
+    }
+
+    async fn replace_transfer_lines(
+        &self,
+        uow: &mut Self::Uow,
+        transfer_id: &Uuid,
+        lines: Vec<TransferLine>,
+    ) -> Result<Vec<TransferLine>, sqlx::Error> {
+        let mut kept: Vec<TransferLine> = uow
+            .state()
+            .transfer_lines
+            .iter()
+            .filter(|l| l.transfer_id != *transfer_id)
+            .cloned()
+            .collect();
+        for line in &lines {
+            if kept.iter().any(|l| l.id == line.id) {
+                return Err(unique_violation("stock_transfer_lines_pkey"));
+            }
+            if kept
+                .iter()
+                .any(|l| l.transfer_id == line.transfer_id && l.position == line.position)
+            {
+                return Err(unique_violation(
+                    "stock_transfer_lines_transfer_id_position_key",
+                ));
+            }
+            if !uow
+                .state()
+                .transfers
+                .iter()
+                .any(|t| t.id == line.transfer_id)
+            {
+                return Err(foreign_key_violation(
+                    "stock_transfer_lines_transfer_id_fkey",
+                ));
+            }
+            if self.product_company(&line.product_id).await.is_none() {
+                return Err(foreign_key_violation(
+                    "stock_transfer_lines_product_id_fkey",
+                ));
+            }
+            if self
+                .location_warehouse(&line.source_location_id)
+                .await
+                .is_none()
+            {
+                return Err(foreign_key_violation(
+                    "stock_transfer_lines_source_location_id_fkey",
+                ));
+            }
+            if self
+                .location_warehouse(&line.destination_location_id)
+                .await
+                .is_none()
+            {
+                return Err(foreign_key_violation(
+                    "stock_transfer_lines_destination_location_id_fkey",
+                ));
+            }
+            if let Some(lot_id) = line.lot_id
+                && !uow.state().lots.iter().any(|l| l.id == lot_id)
+            {
+                return Err(foreign_key_violation("stock_transfer_lines_lot_id_fkey"));
+            }
+            kept.push(line.clone());
+        }
+        uow.state_mut().transfer_lines = kept;
+
+        Ok(lines)
+    }
+
+    async fn update_received_quantity(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        received_quantity: i64,
+    ) -> Result<(), sqlx::Error> {
+        let line = uow
+            .state_mut()
+            .transfer_lines
+            .iter_mut()
+            .find(|l| l.id == *line_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        line.received_quantity = received_quantity;
+
+        Ok(())
+    }
+
+    async fn find_in_transit(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<InTransitQuantity>, sqlx::Error> {
+        // (product, source, destination) -> open quantity, ordered like ORDER BY 1, 2, 3
+        let mut totals: BTreeMap<(Uuid, Uuid, Uuid), i64> = BTreeMap::new();
+        let tables = uow.state();
+        for transfer in tables
+            .transfers
+            .iter()
+            .filter(|t| t.company_id == *company_id && t.status.is_in_transit())
+        {
+            for line in tables
+                .transfer_lines
+                .iter()
+                .filter(|l| l.transfer_id == transfer.id)
+            {
+                *totals
+                    .entry((
+                        line.product_id,
+                        transfer.source_warehouse_id,
+                        transfer.destination_warehouse_id,
+                    ))
+                    .or_default() += line.open_quantity();
+            }
+        }
+
+        Ok(totals
+            .into_iter()
+            .filter(|(_, quantity)| *quantity > 0)
+            .map(
+                |((product_id, source_warehouse_id, destination_warehouse_id), quantity)| {
+                    InTransitQuantity {
+                        product_id,
+                        source_warehouse_id,
+                        destination_warehouse_id,
+                        quantity,
+                    }
+                },
+            )
+            .collect())
+    }
+}
+
+// see helper_query::apply_transfer_filter
+fn filter_transfers<'a>(
+    transfers: &'a [StockTransfer],
+    filter: &StockTransferFilter,
+    search: &Option<String>,
+) -> Vec<&'a StockTransfer> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    transfers
+        .iter()
+        .filter(|t| t.company_id == filter.company_id)
+        .filter(|t| filter.status.is_none_or(|status| t.status == status))
+        .filter(|t| pattern.as_ref().is_none_or(|p| ilike(&t.number, p)))
+        .collect()
+}
+
+fn compare_transfer(a: &StockTransfer, b: &StockTransfer, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "number" => a.number.cmp(&b.number),
+        "source_warehouse_id" => a.source_warehouse_id.cmp(&b.source_warehouse_id),
+        "destination_warehouse_id" => a.destination_warehouse_id.cmp(&b.destination_warehouse_id),
+        "status" => a.status.as_str().cmp(b.status.as_str()),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        "dispatched_at" => compare_nullable(&a.dispatched_at, &b.dispatched_at),
+        "received_at" => compare_nullable(&a.received_at, &b.received_at),
+        _ => Ordering::Equal,
+    }
+}
@@ -0,0 +1,325 @@
+use async_trait::async_trait;
+use sqlx::QueryBuilder;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::transfer::{
+    InTransitQuantity, StockTransfer, StockTransferFilter, TransferLine, TransferStatus,
+};
+use crate::stock::repository::helper_query::{STOCK_TRANSFER_DEFAULT_ORDER, apply_transfer_filter};
+use crate::stock::repository::stock_repository_sqlx::StockRepositorySqlx;
+use crate::stock::repository::transfer_repository::TransferRepository;
+
+#[async_trait]
+impl TransferRepository for StockRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_transfer_number(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM stock_transfers
+                WHERE company_id = $1 AND number = $2 AND ($3::uuid IS NULL OR id <> $3)
+            )
+            "#,
+            company_id,
+            number,
+            id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %transfer.id))]
+    async fn create_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        transfer: StockTransfer,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO stock_transfers
+            (id, company_id, number, source_warehouse_id, destination_warehouse_id, status,
+             note, created_by, created_at, dispatched_at, received_at, cancelled_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+            transfer.id,
+            transfer.company_id,
+            transfer.number,
+            transfer.source_warehouse_id,
+            transfer.destination_warehouse_id,
+            transfer.status.as_str(),
+            transfer.note,
+            transfer.created_by,
+            transfer.created_at,
+            transfer.dispatched_at,
+            transfer.received_at,
+            transfer.cancelled_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    // the company and the creator never change
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %transfer.id))]
+    async fn update_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        transfer: StockTransfer,
+    ) -> Result<StockTransfer, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_transfers
+            SET number = $1,
+                source_warehouse_id = $2,
+                destination_warehouse_id = $3,
+                status = $4,
+                note = $5,
+                dispatched_at = $6,
+                received_at = $7,
+                cancelled_at = $8
+            WHERE id = $9
+            RETURNING id
+            "#,
+            transfer.number,
+            transfer.source_warehouse_id,
+            transfer.destination_warehouse_id,
+            transfer.status.as_str(),
+            transfer.note,
+            transfer.dispatched_at,
+            transfer.received_at,
+            transfer.cancelled_at,
+            transfer.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %id))]
+    async fn get_transfer_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockTransfer>, sqlx::Error> {
+        let transfer = sqlx::query_as!(
+            StockTransfer,
+            r#"
+            SELECT id, company_id, number, source_warehouse_id, destination_warehouse_id,
+                   status AS "status: TransferStatus", note, created_by, created_at,
+                   dispatched_at, received_at, cancelled_at
+            FROM stock_transfers
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %id))]
+    async fn lock_transfer(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<StockTransfer>, sqlx::Error> {
+        let transfer = sqlx::query_as!(
+            StockTransfer,
+            r#"
+            SELECT id, company_id, number, source_warehouse_id, destination_warehouse_id,
+                   status AS "status: TransferStatus", note, created_by, created_at,
+                   dispatched_at, received_at, cancelled_at
+            FROM stock_transfers
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %filter.company_id))]
+    async fn count_transfers(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM stock_transfers");
+
+        apply_transfer_filter(&mut qb, filter, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_transfers(
+        &self,
+        uow: &mut Self::Uow,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<Vec<StockTransfer>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, number, source_warehouse_id, destination_warehouse_id,
+                   status, note, created_by, created_at, dispatched_at, received_at,
+                   cancelled_at
+            FROM stock_transfers
+        ",
+        );
+
+        apply_transfer_filter(&mut qb, filter, &query.search);
+
+        let order = query.format_sort().filter(|s| !s.is_empty());
+        qb.push(" ORDER BY ")
+            .push(order.as_deref().unwrap_or(STOCK_TRANSFER_DEFAULT_ORDER));
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let transfers = qb
+            .build_query_as::<StockTransfer>()
+            .fetch_all(&mut **uow)
+            .await?;
+        Ok(transfers)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %transfer_id))]
+    async fn find_transfer_lines(
+        &self,
+        uow: &mut Self::Uow,
+        transfer_id: &Uuid,
+    ) -> Result<Vec<TransferLine>, sqlx::Error> {
+        let lines = sqlx::query_as!(
+            TransferLine,
+            r#"
+            SELECT id, transfer_id, position, product_id, source_location_id,
+                   destination_location_id, lot_id, quantity, received_quantity,
+                   serial_numbers
+            FROM stock_transfer_lines
+            WHERE transfer_id = $1
+            ORDER BY position
+            "#,
+            transfer_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(transfer_id = %transfer_id, lines = lines.len()))]
+    async fn replace_transfer_lines(
+        &self,
+        uow: &mut Self::Uow,
+        transfer_id: &Uuid,
+        lines: Vec<TransferLine>,
+    ) -> Result<Vec<TransferLine>, sqlx::Error> {
+        sqlx::query!(
+            r#"DELETE FROM stock_transfer_lines WHERE transfer_id = $1"#,
+            transfer_id
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        for line in &lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO stock_transfer_lines
+                (id, transfer_id, position, product_id, source_location_id,
+                 destination_location_id, lot_id, quantity, received_quantity,
+                 serial_numbers)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                "#,
+                line.id,
+                line.transfer_id,
+                line.position,
+                line.product_id,
+                line.source_location_id,
+                line.destination_location_id,
+                line.lot_id,
+                line.quantity,
+                line.received_quantity,
+                &line.serial_numbers,
+            )
+            .execute(&mut **uow)
+            .await?;
+        }
+
+        Ok(lines)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(line_id = %line_id))]
+    async fn update_received_quantity(
+        &self,
+        uow: &mut Self::Uow,
+        line_id: &Uuid,
+        received_quantity: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE stock_transfer_lines
+            SET received_quantity = $1
+            WHERE id = $2
+            RETURNING id
+            "#,
+            received_quantity,
+            line_id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn find_in_transit(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<InTransitQuantity>, sqlx::Error> {
+        let quantities = sqlx::query_as!(
+            InTransitQuantity,
+            r#"
+            SELECT l.product_id, t.source_warehouse_id, t.destination_warehouse_id,
+                   SUM(l.quantity - l.received_quantity)::bigint AS "quantity!"
+            FROM stock_transfer_lines l
+            JOIN stock_transfers t ON t.id = l.transfer_id
+            WHERE t.company_id = $1 AND t.status IN ('dispatched', 'partially_received')
+            GROUP BY l.product_id, t.source_warehouse_id, t.destination_warehouse_id
+            HAVING SUM(l.quantity - l.received_quantity) > 0
+            ORDER BY 1, 2, 3
+            "#,
+            company_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(quantities)
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::atuh_middleware::auth_middleware;
+use crate::stock::handler::count_handler::{
+    approve_count_handler, cancel_count_handler, complete_count_handler, get_count_handler,
+    get_counts_handler, post_count_handler, record_count_handler,
+};
+use crate::stock::handler::lot_handler::{
+    get_expiring_lots_handler, get_lot_handler, get_lots_handler, post_lot_handler,
+};
+use crate::stock::handler::serial_handler::{
+    get_serial_handler, get_serial_reconciliation_handler, get_serials_handler,
+};
+use crate::stock::handler::stock_handler::{
+    get_balances_handler, get_movement_handler, get_movements_handler, get_reconciliation_handler,
+    get_stock_settings_handler, post_movement_handler, reverse_movement_handler,
+    update_stock_settings_handler,
+};
+use crate::stock::handler::transfer_handler::{
+    cancel_transfer_handler, dispatch_transfer_handler, get_in_transit_handler,
+    get_transfer_handler, get_transfers_handler, post_transfer_handler, receive_transfer_handler,
+    update_transfer_handler,
+};
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::repository::transfer_repository::TransferRepository;
+use crate::stock::usecase::count_usecase::CountUsecase;
+use crate::stock::usecase::lot_usecase::LotUsecase;
+use crate::stock::usecase::serial_usecase::SerialUsecase;
+use crate::stock::usecase::stock_usecase::StockUsecase;
+use crate::stock::usecase::transfer_usecase::TransferUsecase;
+use axum::middleware;
+use axum::{Router, routing::get, routing::post, routing::put};
+
+// full paths, the settings of a company hang below /company. movements,
+// transfers, lots, serials and counts share the repository so all post to the
+// same ledger
+pub fn stock_routes<R: TransferRepository + CountRepository + Clone + 'static>(
+    repo: R,
+    metrics: AppMetrics,
+) -> Router {
+    let usecase = Arc::new(StockUsecase::new(repo.clone(), metrics.clone()));
+    let lot_usecase = Arc::new(LotUsecase::new(repo.clone(), metrics.clone()));
+    let serial_usecase = Arc::new(SerialUsecase::new(repo.clone()));
+    let transfer_usecase = Arc::new(TransferUsecase::new(repo.clone(), metrics.clone()));
+    let count_usecase = Arc::new(CountUsecase::new(repo, metrics));
+
+    let lots = Router::new()
+        .route("/stock/lots", get(get_lots_handler))
+        .route("/stock/lots", post(post_lot_handler))
+        .route("/stock/lots/:id", get(get_lot_handler))
+        .route("/stock/expiring-lots", get(get_expiring_lots_handler))
+        .with_state(lot_usecase);
+
+    let serials = Router::new()
+        .route("/stock/serials", get(get_serials_handler))
+        .route("/stock/serials/:id", get(get_serial_handler))
+        .route(
+            "/stock/serial-reconciliation",
+            get(get_serial_reconciliation_handler),
+        )
+        .with_state(serial_usecase);
+
+    let transfers = Router::new()
+        .route("/stock/transfers", get(get_transfers_handler))
+        .route("/stock/transfers", post(post_transfer_handler))
+        .route("/stock/transfers/:id", get(get_transfer_handler))
+        .route("/stock/transfers/:id", put(update_transfer_handler))
+        .route(
+            "/stock/transfers/:id/dispatch",
+            post(dispatch_transfer_handler),
+        )
+        .route(
+            "/stock/transfers/:id/receive",
+            post(receive_transfer_handler),
+        )
+        .route("/stock/transfers/:id/cancel", post(cancel_transfer_handler))
+        .route("/stock/in-transit", get(get_in_transit_handler))
+        .with_state(transfer_usecase);
+
+    let counts = Router::new()
+        .route("/stock/counts", get(get_counts_handler))
+        .route("/stock/counts", post(post_count_handler))
+        .route("/stock/counts/:id", get(get_count_handler))
+        .route("/stock/counts/:id/entries", post(record_count_handler))
+        .route("/stock/counts/:id/complete", post(complete_count_handler))
+        .route("/stock/counts/:id/approve", post(approve_count_handler))
+        .route("/stock/counts/:id/cancel", post(cancel_count_handler))
+        .with_state(count_usecase);
+
+    Router::new()
+        .route("/stock/movements", get(get_movements_handler))
+        .route("/stock/movements", post(post_movement_handler))
+        .route("/stock/movements/:id", get(get_movement_handler))
+        .route(
+            "/stock/movements/:id/reversal",
+            post(reverse_movement_handler),
+        )
+        .route("/stock/balances", get(get_balances_handler))
+        .route("/stock/reconciliation", get(get_reconciliation_handler))
+        .route(
+            "/company/:id/stock-settings",
+            get(get_stock_settings_handler),
+        )
+        .route(
+            "/company/:id/stock-settings",
+            put(update_stock_settings_handler),
+        )
+        .with_state(usecase)
+        .merge(transfers)
+        .merge(counts)
+        .merge(lots)
+        .merge(serials)
+        // merged at the root, layer would put the fallback behind auth as well
+        .route_layer(middleware::from_fn(auth_middleware))
+}
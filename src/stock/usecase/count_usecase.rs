@@ -0,0 +1,865 @@
+use core::fmt;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::count::{
+    CountStatus, StockCount, StockCountEntry, StockCountFilter, StockCountLine,
+};
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::repository::count_repository::CountRepository;
+use crate::stock::usecase::dto::{CountDetail, CountEntryInput, CountInput, ListStockCountResult};
+use crate::stock::usecase::stock_usecase::{Posting, StockUsecaseError, check_lot_in, post_in};
+
+// names of the constraints on stock_counts and stock_count_lines, see migrations
+const COUNT_NUMBER_CONSTRAINT: &str = "stock_counts_company_id_number_key";
+const COUNT_COMPANY_CONSTRAINT: &str = "stock_counts_company_id_fkey";
+const COUNT_WAREHOUSE_CONSTRAINT: &str = "stock_counts_warehouse_fkey";
+const COUNT_LOCATION_CONSTRAINT: &str = "stock_counts_location_id_fkey";
+const LINE_PRODUCT_CONSTRAINT: &str = "stock_count_lines_product_id_fkey";
+const LINE_LOCATION_CONSTRAINT: &str = "stock_count_lines_location_id_fkey";
+const LINE_LOT_CONSTRAINT: &str = "stock_count_lines_lot_id_fkey";
+
+pub struct CountUsecase<R: CountRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum CountUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    NumberAlreadyExist,
+    WarehouseNotFound,
+    // a warehouse or product of another company
+    CompanyMismatch,
+    ProductNotFound,
+    // the location is missing or outside what the count takes stock of
+    LocationNotFound,
+    InvalidQuantity,
+    InvalidThreshold,
+    // another open or pending count takes stock of some of the locations
+    AlreadyCounted,
+    // serials are counted through their own history, not through counts
+    SerialisedProduct,
+    NoEntries,
+    // the count can not take this step from its current status
+    InvalidStatus(CountStatus),
+    // a line has no entry yet
+    NotCounted,
+    // the approver counted some of the lines
+    SelfApproval,
+    // an entry or an adjustment failed for a reason the count does not translate
+    Posting(StockUsecaseError),
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for CountUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountUsecaseError::CompanyNotFound => write!(f, "count company not found"),
+            CountUsecaseError::NotFound => write!(f, "count not found"),
+            CountUsecaseError::NumberAlreadyExist => write!(f, "count number already exists"),
+            CountUsecaseError::WarehouseNotFound => write!(f, "count warehouse not found"),
+            CountUsecaseError::CompanyMismatch => write!(f, "count refers to another company"),
+            CountUsecaseError::ProductNotFound => write!(f, "count product not found"),
+            CountUsecaseError::LocationNotFound => write!(f, "count location not found"),
+            CountUsecaseError::InvalidQuantity => write!(f, "counted quantity is invalid"),
+            CountUsecaseError::InvalidThreshold => write!(f, "approval threshold is invalid"),
+            CountUsecaseError::AlreadyCounted => {
+                write!(f, "locations are already being counted")
+            }
+            CountUsecaseError::SerialisedProduct => write!(f, "count product is serialised"),
+            CountUsecaseError::NoEntries => write!(f, "count has no entries"),
+            CountUsecaseError::InvalidStatus(status) => write!(f, "count is {}", status.as_str()),
+            CountUsecaseError::NotCounted => write!(f, "count has uncounted lines"),
+            CountUsecaseError::SelfApproval => write!(f, "count approved by one of its counters"),
+            CountUsecaseError::Posting(err) => write!(f, "count posting failed: {err}"),
+            CountUsecaseError::DatabaseError(_) => write!(f, "count storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for CountUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CountUsecaseError::Posting(err) => Some(err),
+            CountUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: CountRepository> CountUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    // snapshots what is on hand at the counted locations, they are frozen
+    // from here until the count is completed or cancelled
+    #[instrument(skip_all, fields(company_id = %company_id, number = %input.number))]
+    pub async fn create_count(
+        &self,
+        company_id: Uuid,
+        input: CountInput,
+        created_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .create_count_in(&mut uow, company_id, input, created_by)
+            .await;
+        let detail = finish(uow, result, CountUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_count_operation("create");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(count_id = %id))]
+    pub async fn get_count(&self, id: Uuid) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_count_in(&mut uow, id).await;
+        finish(uow, result, CountUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_counts(
+        &self,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockCountResult, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_counts_in(&mut uow, filter, query).await;
+        finish(uow, result, CountUsecaseError::DatabaseError).await
+    }
+
+    // every entry is kept, the line takes the latest one so a recount
+    // replaces what was counted before. when two counters disagree the line
+    // is disputed and the count waits for an approval, see disputed_lines
+    #[instrument(skip_all, fields(count_id = %id, entries = entries.len()))]
+    pub async fn record_entries(
+        &self,
+        id: Uuid,
+        entries: Vec<CountEntryInput>,
+        counted_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .record_entries_in(&mut uow, id, entries, counted_by)
+            .await;
+        let detail = finish(uow, result, CountUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_count_operation("count");
+        Ok(detail)
+    }
+
+    // posts the variances, or waits for an approval when one of them is over
+    // the threshold
+    #[instrument(skip_all, fields(count_id = %id))]
+    pub async fn complete_count(
+        &self,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.complete_count_in(&mut uow, id, posted_by).await;
+        let (detail, movements) = finish(uow, result, CountUsecaseError::DatabaseError).await?;
+
+        self.record_postings("complete", &movements);
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(count_id = %id))]
+    pub async fn approve_count(
+        &self,
+        id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.approve_count_in(&mut uow, id, approved_by).await;
+        let (detail, movements) = finish(uow, result, CountUsecaseError::DatabaseError).await?;
+
+        self.record_postings("approve", &movements);
+        Ok(detail)
+    }
+
+    // nothing is posted, the locations are released as they are
+    #[instrument(skip_all, fields(count_id = %id))]
+    pub async fn cancel_count(&self, id: Uuid) -> Result<CountDetail, CountUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.cancel_count_in(&mut uow, id).await;
+        let detail = finish(uow, result, CountUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_count_operation("cancel");
+        Ok(detail)
+    }
+
+    async fn begin(&self) -> Result<R::Uow, CountUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(CountUsecaseError::DatabaseError)
+    }
+
+    fn record_postings(&self, operation: &str, movements: &[StockMovement]) {
+        self.metrics.stock_count_operation(operation);
+        for movement in movements {
+            self.metrics
+                .stock_movement_posted(movement.movement_type.as_str());
+        }
+    }
+
+    async fn create_count_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: CountInput,
+        created_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+        if input.approval_threshold.is_some_and(|t| t < 0) {
+            return Err(CountUsecaseError::InvalidThreshold);
+        }
+        let is_number_exist = self
+            .repo
+            .check_existing_count_number(uow, &company_id, &input.number)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(CountUsecaseError::NumberAlreadyExist);
+        }
+        // opening counts of a warehouse one at a time keeps two of them from
+        // both passing the overlap check
+        let warehouse_company = self
+            .repo
+            .lock_warehouse_company(uow, &input.warehouse_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?
+            .ok_or(CountUsecaseError::WarehouseNotFound)?;
+        if warehouse_company != company_id {
+            return Err(CountUsecaseError::CompanyMismatch);
+        }
+        if let Some(location_id) = &input.location_id {
+            self.check_location(uow, location_id, &input.warehouse_id)
+                .await?;
+        }
+
+        let location_ids = self
+            .find_locations(uow, &input.warehouse_id, input.location_id.as_ref())
+            .await?;
+        self.check_overlap(uow, &input, &location_ids).await?;
+
+        let count = StockCount {
+            id: Uuid::new_v4(),
+            company_id,
+            number: input.number,
+            warehouse_id: input.warehouse_id,
+            location_id: input.location_id,
+            status: CountStatus::Open,
+            is_blind: input.is_blind,
+            approval_threshold: input.approval_threshold,
+            note: input.note,
+            created_by,
+            created_at: Utc::now(),
+            submitted_at: None,
+            approved_by: None,
+            approved_at: None,
+            completed_at: None,
+            cancelled_at: None,
+        };
+        let count = self
+            .repo
+            .create_count(uow, count)
+            .await
+            .map_err(map_count_write_error)?;
+
+        let expected = self
+            .repo
+            .find_expected_quantities(uow, &location_ids)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        let lines = (1..)
+            .zip(expected)
+            .map(|(position, expected)| StockCountLine {
+                id: Uuid::new_v4(),
+                count_id: count.id,
+                position,
+                product_id: expected.product_id,
+                location_id: expected.location_id,
+                lot_id: expected.lot_id,
+                expected_quantity: expected.quantity,
+                counted_quantity: None,
+                counted_by: None,
+                counted_at: None,
+                movement_id: None,
+            })
+            .collect();
+        let lines = self
+            .repo
+            .create_count_lines(uow, lines)
+            .await
+            .map_err(map_count_write_error)?;
+
+        Ok(CountDetail {
+            count,
+            lines,
+            entries: Vec::new(),
+        })
+    }
+
+    async fn get_count_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let count = self
+            .repo
+            .get_count_by_id(uow, &id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?
+            .ok_or(CountUsecaseError::NotFound)?;
+
+        self.detail(uow, count).await
+    }
+
+    async fn list_counts_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockCountFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockCountResult, CountUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_count = self
+            .repo
+            .count_counts(uow, filter, query)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+
+        let counts = self
+            .repo
+            .find_counts(uow, filter, query)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+
+        Ok(ListStockCountResult {
+            data: counts,
+            total_data: total_count,
+        })
+    }
+
+    async fn record_entries_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        inputs: Vec<CountEntryInput>,
+        counted_by: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let count = self.lock_count(uow, &id).await?;
+        if count.status != CountStatus::Open {
+            return Err(CountUsecaseError::InvalidStatus(count.status));
+        }
+        if inputs.is_empty() {
+            return Err(CountUsecaseError::NoEntries);
+        }
+        let mut lines = self.find_lines(uow, &id).await?;
+        let location_ids = self
+            .find_locations(uow, &count.warehouse_id, count.location_id.as_ref())
+            .await?;
+
+        let counted_at = Utc::now();
+        for input in inputs {
+            if input.quantity < 0 {
+                return Err(CountUsecaseError::InvalidQuantity);
+            }
+            let existing = lines.iter().position(|line| {
+                line.product_id == input.product_id
+                    && line.location_id == input.location_id
+                    && line.lot_id == input.lot_id
+            });
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    let line = self
+                        .found_line(uow, &count, &lines, &location_ids, &input)
+                        .await?;
+                    let created = self
+                        .repo
+                        .create_count_lines(uow, vec![line])
+                        .await
+                        .map_err(map_count_write_error)?;
+                    lines.extend(created);
+                    lines.len() - 1
+                }
+            };
+
+            let entry = StockCountEntry {
+                id: Uuid::new_v4(),
+                count_id: count.id,
+                line_id: lines[index].id,
+                quantity: input.quantity,
+                counted_by,
+                counted_at,
+            };
+            self.repo
+                .create_count_entry(uow, entry)
+                .await
+                .map_err(CountUsecaseError::DatabaseError)?;
+            let line = StockCountLine {
+                counted_quantity: Some(input.quantity),
+                counted_by: Some(counted_by),
+                counted_at: Some(counted_at),
+                ..lines[index].clone()
+            };
+            lines[index] = self
+                .repo
+                .update_count_line(uow, line)
+                .await
+                .map_err(CountUsecaseError::DatabaseError)?;
+        }
+
+        let entries = self.find_entries(uow, &id).await?;
+        Ok(CountDetail {
+            count,
+            lines,
+            entries,
+        })
+    }
+
+    async fn complete_count_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<(CountDetail, Vec<StockMovement>), CountUsecaseError> {
+        let count = self.lock_count(uow, &id).await?;
+        if count.status != CountStatus::Open {
+            return Err(CountUsecaseError::InvalidStatus(count.status));
+        }
+        let lines = self.find_lines(uow, &id).await?;
+        if lines.iter().any(|line| line.counted_quantity.is_none()) {
+            return Err(CountUsecaseError::NotCounted);
+        }
+
+        let lines = self.rebase_lines(uow, &count, lines).await?;
+        let entries = self.find_entries(uow, &id).await?;
+        if count.needs_approval(&lines, &entries) {
+            let count = StockCount {
+                status: CountStatus::PendingApproval,
+                submitted_at: Some(Utc::now()),
+                ..count
+            };
+            let count = self
+                .repo
+                .update_count(uow, count)
+                .await
+                .map_err(map_count_write_error)?;
+            return Ok((
+                CountDetail {
+                    count,
+                    lines,
+                    entries,
+                },
+                Vec::new(),
+            ));
+        }
+
+        let now = Utc::now();
+        let count = StockCount {
+            status: CountStatus::Completed,
+            submitted_at: Some(now),
+            completed_at: Some(now),
+            ..count
+        };
+        self.post_variances(uow, count, lines, posted_by).await
+    }
+
+    async fn approve_count_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<(CountDetail, Vec<StockMovement>), CountUsecaseError> {
+        let count = self.lock_count(uow, &id).await?;
+        if count.status != CountStatus::PendingApproval {
+            return Err(CountUsecaseError::InvalidStatus(count.status));
+        }
+        // variances are approved by someone who did not count them
+        let entries = self.find_entries(uow, &id).await?;
+        if entries.iter().any(|entry| entry.counted_by == approved_by) {
+            return Err(CountUsecaseError::SelfApproval);
+        }
+        let lines = self.find_lines(uow, &id).await?;
+        let lines = self.rebase_lines(uow, &count, lines).await?;
+
+        let now = Utc::now();
+        let count = StockCount {
+            status: CountStatus::Completed,
+            approved_by: Some(approved_by),
+            approved_at: Some(now),
+            completed_at: Some(now),
+            ..count
+        };
+        self.post_variances(uow, count, lines, approved_by).await
+    }
+
+    async fn cancel_count_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let count = self.lock_count(uow, &id).await?;
+        if !count.status.is_active() {
+            return Err(CountUsecaseError::InvalidStatus(count.status));
+        }
+
+        let count = StockCount {
+            status: CountStatus::Cancelled,
+            cancelled_at: Some(Utc::now()),
+            ..count
+        };
+        let count = self
+            .repo
+            .update_count(uow, count)
+            .await
+            .map_err(map_count_write_error)?;
+
+        self.detail(uow, count).await
+    }
+
+    // a posting that checked a location before the count was opened can still
+    // have committed after the snapshot, so each line takes what is on hand
+    // under the balance lock as its expected quantity. the approval is decided
+    // and the adjustments are posted on the same figures, the locks hold until
+    // the unit of work finishes. balances are locked in product, location and
+    // lot order like the transfers do
+    async fn rebase_lines(
+        &self,
+        uow: &mut R::Uow,
+        count: &StockCount,
+        mut lines: Vec<StockCountLine>,
+    ) -> Result<Vec<StockCountLine>, CountUsecaseError> {
+        let mut order: Vec<usize> = (0..lines.len()).collect();
+        order.sort_by_key(|&i| (lines[i].product_id, lines[i].location_id, lines[i].lot_id));
+
+        for index in order {
+            let on_hand = self.lock_on_hand(uow, count, &lines[index]).await?;
+            if on_hand == lines[index].expected_quantity {
+                continue;
+            }
+            let line = StockCountLine {
+                expected_quantity: on_hand,
+                ..lines[index].clone()
+            };
+            lines[index] = self
+                .repo
+                .update_count_line(uow, line)
+                .await
+                .map_err(CountUsecaseError::DatabaseError)?;
+        }
+
+        Ok(lines)
+    }
+
+    // the count is stored as completed first, that releases its locations for
+    // the adjustments. the lines are rebased, each adjustment brings the
+    // balance to what was counted
+    async fn post_variances(
+        &self,
+        uow: &mut R::Uow,
+        count: StockCount,
+        mut lines: Vec<StockCountLine>,
+        posted_by: Uuid,
+    ) -> Result<(CountDetail, Vec<StockMovement>), CountUsecaseError> {
+        let count = self
+            .repo
+            .update_count(uow, count)
+            .await
+            .map_err(map_count_write_error)?;
+
+        let mut order: Vec<usize> = (0..lines.len()).collect();
+        order.sort_by_key(|&i| (lines[i].product_id, lines[i].location_id, lines[i].lot_id));
+
+        let mut movements = Vec::new();
+        for index in order {
+            let Some(variance) = lines[index].variance().filter(|v| *v != 0) else {
+                continue;
+            };
+            let posting = Posting {
+                company_id: count.company_id,
+                product_id: lines[index].product_id,
+                location_id: lines[index].location_id,
+                movement_type: MovementType::Adjustment,
+                lot_id: lines[index].lot_id,
+                quantity: variance,
+                serial_numbers: Vec::new(),
+                reversal_of: None,
+                source: MovementSource::Count,
+                source_id: Some(count.id),
+                reference: Some(count.number.clone()),
+                note: None,
+                posted_by,
+            };
+            let movement = post_in(&self.repo, uow, posting)
+                .await
+                .map_err(map_posting_error)?;
+            let line = StockCountLine {
+                movement_id: Some(movement.id),
+                ..lines[index].clone()
+            };
+            movements.push(movement);
+            lines[index] = self
+                .repo
+                .update_count_line(uow, line)
+                .await
+                .map_err(CountUsecaseError::DatabaseError)?;
+        }
+
+        let entries = self.find_entries(uow, &count.id).await?;
+        Ok((
+            CountDetail {
+                count,
+                lines,
+                entries,
+            },
+            movements,
+        ))
+    }
+
+    // the balance of the line, of its lot for lot tracked products. the product
+    // balance is locked before the lot balance as post_in does
+    async fn lock_on_hand(
+        &self,
+        uow: &mut R::Uow,
+        count: &StockCount,
+        line: &StockCountLine,
+    ) -> Result<i64, CountUsecaseError> {
+        let on_hand = self
+            .repo
+            .lock_balance(uow, &count.company_id, &line.product_id, &line.location_id)
+            .await
+            .map_err(map_count_write_error)?;
+        let Some(lot_id) = &line.lot_id else {
+            return Ok(on_hand);
+        };
+
+        self.repo
+            .lock_lot_balance(
+                uow,
+                &count.company_id,
+                lot_id,
+                &line.product_id,
+                &line.location_id,
+            )
+            .await
+            .map_err(map_count_write_error)
+    }
+
+    // a line for stock the snapshot did not expect, on a location the count
+    // takes stock of
+    async fn found_line(
+        &self,
+        uow: &mut R::Uow,
+        count: &StockCount,
+        lines: &[StockCountLine],
+        location_ids: &[Uuid],
+        input: &CountEntryInput,
+    ) -> Result<StockCountLine, CountUsecaseError> {
+        if !location_ids.contains(&input.location_id) {
+            return Err(CountUsecaseError::LocationNotFound);
+        }
+        let product_company = self
+            .repo
+            .get_product_company(uow, &input.product_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?
+            .ok_or(CountUsecaseError::ProductNotFound)?;
+        if product_company != count.company_id {
+            return Err(CountUsecaseError::CompanyMismatch);
+        }
+        let is_serialised = self
+            .repo
+            .is_product_serialised(uow, &input.product_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        if is_serialised {
+            return Err(CountUsecaseError::SerialisedProduct);
+        }
+        check_lot_in(&self.repo, uow, &input.product_id, input.lot_id.as_ref())
+            .await
+            .map_err(map_posting_error)?;
+
+        let position = lines.iter().map(|line| line.position).max().unwrap_or(0) + 1;
+        Ok(StockCountLine {
+            id: Uuid::new_v4(),
+            count_id: count.id,
+            position,
+            product_id: input.product_id,
+            location_id: input.location_id,
+            lot_id: input.lot_id,
+            expected_quantity: 0,
+            counted_quantity: None,
+            counted_by: None,
+            counted_at: None,
+            movement_id: None,
+        })
+    }
+
+    // two active counts of a warehouse never share a location, a whole
+    // warehouse count shares all of them
+    async fn check_overlap(
+        &self,
+        uow: &mut R::Uow,
+        input: &CountInput,
+        location_ids: &[Uuid],
+    ) -> Result<(), CountUsecaseError> {
+        let active = self
+            .repo
+            .find_active_counts(uow, &input.warehouse_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        for count in active {
+            let Some(location_id) = &count.location_id else {
+                return Err(CountUsecaseError::AlreadyCounted);
+            };
+            if input.location_id.is_none() {
+                return Err(CountUsecaseError::AlreadyCounted);
+            }
+            let counted = self
+                .find_locations(uow, &count.warehouse_id, Some(location_id))
+                .await?;
+            if counted.iter().any(|id| location_ids.contains(id)) {
+                return Err(CountUsecaseError::AlreadyCounted);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_location(
+        &self,
+        uow: &mut R::Uow,
+        location_id: &Uuid,
+        warehouse_id: &Uuid,
+    ) -> Result<(), CountUsecaseError> {
+        let location_warehouse = self
+            .repo
+            .get_location_warehouse(uow, location_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        if location_warehouse != Some(*warehouse_id) {
+            return Err(CountUsecaseError::LocationNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), CountUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(CountUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn lock_count(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<StockCount, CountUsecaseError> {
+        self.repo
+            .lock_count(uow, id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)?
+            .ok_or(CountUsecaseError::NotFound)
+    }
+
+    async fn detail(
+        &self,
+        uow: &mut R::Uow,
+        count: StockCount,
+    ) -> Result<CountDetail, CountUsecaseError> {
+        let lines = self.find_lines(uow, &count.id).await?;
+        let entries = self.find_entries(uow, &count.id).await?;
+
+        Ok(CountDetail {
+            count,
+            lines,
+            entries,
+        })
+    }
+
+    async fn find_locations(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: &Uuid,
+        location_id: Option<&Uuid>,
+    ) -> Result<Vec<Uuid>, CountUsecaseError> {
+        self.repo
+            .find_count_locations(uow, warehouse_id, location_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)
+    }
+
+    async fn find_lines(
+        &self,
+        uow: &mut R::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountLine>, CountUsecaseError> {
+        self.repo
+            .find_count_lines(uow, count_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)
+    }
+
+    async fn find_entries(
+        &self,
+        uow: &mut R::Uow,
+        count_id: &Uuid,
+    ) -> Result<Vec<StockCountEntry>, CountUsecaseError> {
+        self.repo
+            .find_count_entries(uow, count_id)
+            .await
+            .map_err(CountUsecaseError::DatabaseError)
+    }
+}
+
+fn map_posting_error(err: StockUsecaseError) -> CountUsecaseError {
+    match err {
+        StockUsecaseError::DatabaseError(err) => CountUsecaseError::DatabaseError(err),
+        err => CountUsecaseError::Posting(err),
+    }
+}
+
+// a concurrent unit of work can still claim the number or delete a warehouse,
+// product, lot or location between the check and the write, the constraints
+// catch that
+fn map_count_write_error(err: sqlx::Error) -> CountUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(COUNT_NUMBER_CONSTRAINT) => CountUsecaseError::NumberAlreadyExist,
+        Some(COUNT_COMPANY_CONSTRAINT) => CountUsecaseError::CompanyNotFound,
+        Some(COUNT_WAREHOUSE_CONSTRAINT) => CountUsecaseError::WarehouseNotFound,
+        Some(LINE_PRODUCT_CONSTRAINT) => CountUsecaseError::ProductNotFound,
+        Some(COUNT_LOCATION_CONSTRAINT | LINE_LOCATION_CONSTRAINT) => {
+            CountUsecaseError::LocationNotFound
+        }
+        Some(LINE_LOT_CONSTRAINT) => CountUsecaseError::Posting(StockUsecaseError::LotNotFound),
+        _ => CountUsecaseError::DatabaseError(err),
+    }
+}
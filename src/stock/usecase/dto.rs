@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::stock::domain::balance::StockBalance;
+use crate::stock::domain::count::{StockCount, StockCountEntry, StockCountLine};
+use crate::stock::domain::lot::{StockLot, StockLotBalance};
+use crate::stock::domain::movement::{MovementType, StockMovement};
+use crate::stock::domain::serial::{SerialEvent, StockSerial};
+use crate::stock::domain::transfer::{StockTransfer, TransferLine};
+
+pub struct ListStockMovementResult {
+    pub data: Vec<StockMovement>,
+    pub total_data: i64,
+}
+
+pub struct ListStockBalanceResult {
+    pub data: Vec<StockBalance>,
+    pub total_data: i64,
+}
+
+// quantity is what moved, always positive except for adjustments where the
+// sign gives the direction. the company comes from the product
+pub struct MovementInput {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub movement_type: MovementType,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    pub serial_numbers: Vec<String>,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+}
+
+pub struct TransferLineInput {
+    pub product_id: Uuid,
+    pub source_location_id: Uuid,
+    pub destination_location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    pub serial_numbers: Vec<String>,
+}
+
+// the lines replace whatever the draft held, in the given order
+pub struct TransferInput {
+    pub number: String,
+    pub source_warehouse_id: Uuid,
+    pub destination_warehouse_id: Uuid,
+    pub note: Option<String>,
+    pub lines: Vec<TransferLineInput>,
+}
+
+// quantity arriving for one line of a dispatched transfer
+pub struct ReceiptLineInput {
+    pub line_id: Uuid,
+    pub quantity: i64,
+}
+
+pub struct TransferDetail {
+    pub transfer: StockTransfer,
+    pub lines: Vec<TransferLine>,
+}
+
+pub struct ListStockTransferResult {
+    pub data: Vec<StockTransfer>,
+    pub total_data: i64,
+}
+
+// the company comes from the product
+pub struct LotInput {
+    pub product_id: Uuid,
+    pub lot_number: String,
+    pub manufactured_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+}
+
+pub struct LotDetail {
+    pub lot: StockLot,
+    pub on_hand: i64,
+    pub balances: Vec<StockLotBalance>,
+}
+
+pub struct ListStockLotResult {
+    pub data: Vec<StockLot>,
+    pub total_data: i64,
+}
+
+pub struct SerialDetail {
+    pub serial: StockSerial,
+    pub history: Vec<SerialEvent>,
+}
+
+pub struct ListStockSerialResult {
+    pub data: Vec<StockSerial>,
+    pub total_data: i64,
+}
+
+// None for location counts the whole warehouse
+pub struct CountInput {
+    pub number: String,
+    pub warehouse_id: Uuid,
+    pub location_id: Option<Uuid>,
+    pub is_blind: bool,
+    pub approval_threshold: Option<i64>,
+    pub note: Option<String>,
+}
+
+// what a counter found of a product, or of a lot of it, at a location. stock
+// the count did not expect gets a line of its own
+pub struct CountEntryInput {
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+}
+
+pub struct CountDetail {
+    pub count: StockCount,
+    pub lines: Vec<StockCountLine>,
+    pub entries: Vec<StockCountEntry>,
+}
+
+pub struct ListStockCountResult {
+    pub data: Vec<StockCount>,
+    pub total_data: i64,
+}
@@ -0,0 +1,271 @@
+use core::fmt;
+
+use chrono::{Days, NaiveDate, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::lot::{ExpiringLot, StockLot, StockLotFilter};
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::dto::{ListStockLotResult, LotDetail, LotInput};
+
+// names of the constraints on stock_lots, see migrations
+const LOT_NUMBER_CONSTRAINT: &str = "stock_lots_product_id_lot_number_key";
+const LOT_PRODUCT_CONSTRAINT: &str = "stock_lots_product_id_fkey";
+const LOT_DATES_CONSTRAINT: &str = "stock_lots_dates_check";
+
+// how far ahead the expiry listing may look
+const MAX_EXPIRY_DAYS: i64 = 3650;
+
+pub struct LotUsecase<R: StockRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum LotUsecaseError {
+    CompanyNotFound,
+    ProductNotFound,
+    NotLotTracked,
+    LotNumberAlreadyExist,
+    // expires before it was manufactured
+    InvalidDates,
+    InvalidDays,
+    NotFound,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for LotUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LotUsecaseError::CompanyNotFound => write!(f, "lot company not found"),
+            LotUsecaseError::ProductNotFound => write!(f, "lot product not found"),
+            LotUsecaseError::NotLotTracked => write!(f, "product is not lot tracked"),
+            LotUsecaseError::LotNumberAlreadyExist => write!(f, "lot number already exists"),
+            LotUsecaseError::InvalidDates => {
+                write!(f, "lot expires before it was manufactured")
+            }
+            LotUsecaseError::InvalidDays => write!(f, "expiry window is invalid"),
+            LotUsecaseError::NotFound => write!(f, "lot not found"),
+            LotUsecaseError::DatabaseError(_) => write!(f, "lot storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for LotUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LotUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: StockRepository> LotUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(product_id = %input.product_id, lot_number = %input.lot_number))]
+    pub async fn create_lot(&self, input: LotInput) -> Result<StockLot, LotUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_lot_in(&mut uow, input).await;
+        let lot = finish(uow, result, LotUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_lot_operation("create");
+        Ok(lot)
+    }
+
+    #[instrument(skip_all, fields(lot_id = %id))]
+    pub async fn get_lot(&self, id: Uuid) -> Result<LotDetail, LotUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_lot_in(&mut uow, &id).await;
+        finish(uow, result, LotUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_lots(
+        &self,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockLotResult, LotUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_lots_in(&mut uow, filter, query).await;
+        finish(uow, result, LotUsecaseError::DatabaseError).await
+    }
+
+    // lots with stock left that expire within days from today, lots already
+    // past their expiry date come first
+    #[instrument(skip_all, fields(company_id = %company_id, days = days))]
+    pub async fn list_expiring_lots(
+        &self,
+        company_id: Uuid,
+        days: i64,
+        warehouse_id: Option<Uuid>,
+    ) -> Result<Vec<ExpiringLot>, LotUsecaseError> {
+        if !(0..=MAX_EXPIRY_DAYS).contains(&days) {
+            return Err(LotUsecaseError::InvalidDays);
+        }
+        let until = Utc::now()
+            .date_naive()
+            .checked_add_days(Days::new(days as u64))
+            .ok_or(LotUsecaseError::InvalidDays)?;
+
+        let mut uow = self.begin().await?;
+        let result = self
+            .list_expiring_lots_in(&mut uow, &company_id, until, warehouse_id.as_ref())
+            .await;
+        finish(uow, result, LotUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, LotUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(LotUsecaseError::DatabaseError)
+    }
+
+    async fn create_lot_in(
+        &self,
+        uow: &mut R::Uow,
+        input: LotInput,
+    ) -> Result<StockLot, LotUsecaseError> {
+        if let (Some(manufactured_on), Some(expires_on)) = (input.manufactured_on, input.expires_on)
+            && expires_on < manufactured_on
+        {
+            return Err(LotUsecaseError::InvalidDates);
+        }
+        let company_id = self
+            .repo
+            .get_product_company(uow, &input.product_id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?
+            .ok_or(LotUsecaseError::ProductNotFound)?;
+        let is_lot_tracked = self
+            .repo
+            .is_product_lot_tracked(uow, &input.product_id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+        if !is_lot_tracked {
+            return Err(LotUsecaseError::NotLotTracked);
+        }
+        let is_number_exist = self
+            .repo
+            .check_existing_lot_number(uow, &input.product_id, &input.lot_number)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(LotUsecaseError::LotNumberAlreadyExist);
+        }
+
+        let lot = StockLot {
+            id: Uuid::new_v4(),
+            company_id,
+            product_id: input.product_id,
+            lot_number: input.lot_number,
+            manufactured_on: input.manufactured_on,
+            expires_on: input.expires_on,
+            created_at: Utc::now(),
+        };
+        self.repo
+            .create_lot(uow, lot)
+            .await
+            .map_err(map_lot_write_error)
+    }
+
+    async fn get_lot_in(&self, uow: &mut R::Uow, id: &Uuid) -> Result<LotDetail, LotUsecaseError> {
+        let lot = self
+            .repo
+            .get_lot_by_id(uow, id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?
+            .ok_or(LotUsecaseError::NotFound)?;
+        let balances = self
+            .repo
+            .find_lot_balances(uow, id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+        let on_hand = balances.iter().map(|b| b.quantity).sum();
+
+        Ok(LotDetail {
+            lot,
+            on_hand,
+            balances,
+        })
+    }
+
+    async fn list_lots_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockLotFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockLotResult, LotUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_lot = self
+            .repo
+            .count_lots(uow, filter, query)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+
+        let lots = self
+            .repo
+            .find_lots(uow, filter, query)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+
+        Ok(ListStockLotResult {
+            data: lots,
+            total_data: total_lot,
+        })
+    }
+
+    async fn list_expiring_lots_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        until: NaiveDate,
+        warehouse_id: Option<&Uuid>,
+    ) -> Result<Vec<ExpiringLot>, LotUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        self.repo
+            .find_expiring_lots(uow, company_id, until, warehouse_id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), LotUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(LotUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(LotUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+// a concurrent unit of work can still claim the lot number or delete the
+// product between the check and the write, the constraints catch that case
+fn map_lot_write_error(err: sqlx::Error) -> LotUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(LOT_NUMBER_CONSTRAINT) => LotUsecaseError::LotNumberAlreadyExist,
+        Some(LOT_PRODUCT_CONSTRAINT) => LotUsecaseError::ProductNotFound,
+        Some(LOT_DATES_CONSTRAINT) => LotUsecaseError::InvalidDates,
+        _ => LotUsecaseError::DatabaseError(err),
+    }
+}
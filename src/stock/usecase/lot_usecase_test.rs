@@ -0,0 +1,284 @@
+use chrono::{Days, NaiveDate, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::stock::domain::lot::{StockLot, StockLotFilter};
+use crate::stock::domain::movement::MovementType;
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::{LotInput, MovementInput};
+use crate::stock::usecase::lot_usecase::{LotUsecase, LotUsecaseError};
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str, is_lot_tracked: bool) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+// days from today, negative for the past
+fn day(days: i64) -> NaiveDate {
+    let today = Utc::now().date_naive();
+    if days < 0 {
+        today.checked_sub_days(Days::new(-days as u64)).unwrap()
+    } else {
+        today.checked_add_days(Days::new(days as u64)).unwrap()
+    }
+}
+
+struct Fixture {
+    usecase: LotUsecase<StockRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    acme: Company,
+    milk: Product,
+    bolt: Product,
+    bin: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    async fn lot(&self, number: &str, expires_on: Option<NaiveDate>) -> StockLot {
+        self.usecase
+            .create_lot(LotInput {
+                product_id: self.milk.id,
+                lot_number: number.into(),
+                manufactured_on: None,
+                expires_on,
+            })
+            .await
+            .unwrap()
+    }
+
+    fn movement(
+        &self,
+        product: &Product,
+        lot_id: Option<Uuid>,
+        movement_type: MovementType,
+        quantity: i64,
+    ) -> MovementInput {
+        MovementInput {
+            product_id: product.id,
+            location_id: self.bin.id,
+            movement_type,
+            lot_id,
+            quantity,
+            serial_numbers: Vec::new(),
+            reference: None,
+            note: None,
+        }
+    }
+}
+
+// ACM keeps lot tracked milk and untracked bolts in a single bin
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone()]);
+    let milk = product(&acme, "MILK", true);
+    let bolt = product(&acme, "BOLT", false);
+    let products =
+        ProductRepositoryMemory::with_products(companies.clone(), vec![milk.clone(), bolt.clone()]);
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let warehouse_usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = warehouse_usecase
+        .create_warehouse(
+            acme.id,
+            WarehouseInput {
+                code: "NORTH".into(),
+                name: "north".into(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    let bin = warehouse_usecase
+        .create_location(
+            warehouse.id,
+            LocationInput {
+                parent_id: None,
+                code: "A-01".into(),
+                name: "Bin A-01".into(),
+                kind: LocationKind::Bin,
+            },
+        )
+        .await
+        .unwrap();
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    Fixture {
+        usecase: LotUsecase::new(repo.clone(), AppMetrics::new()),
+        stock: StockUsecase::new(repo, AppMetrics::new()),
+        acme,
+        milk,
+        bolt,
+        bin,
+        user: Uuid::new_v4(),
+    }
+}
+
+#[tokio::test]
+async fn create_lot_validates_product_and_dates() {
+    let f = setup().await;
+    let lot = f.lot("L-1", Some(day(30))).await;
+    assert_eq!(lot.company_id, f.acme.id);
+
+    let cases = [
+        (f.bolt.id, "L-2", None, "product is not lot tracked"),
+        (Uuid::new_v4(), "L-2", None, "lot product not found"),
+        (f.milk.id, "L-1", None, "lot number already exists"),
+        (
+            f.milk.id,
+            "L-2",
+            Some(day(60)),
+            "lot expires before it was manufactured",
+        ),
+    ];
+    for (product_id, number, manufactured_on, message) in cases {
+        let Err(err) = f
+            .usecase
+            .create_lot(LotInput {
+                product_id,
+                lot_number: number.into(),
+                manufactured_on,
+                expires_on: Some(day(30)),
+            })
+            .await
+        else {
+            panic!("lot {number} should be refused");
+        };
+        assert_eq!(err.to_string(), message);
+    }
+}
+
+#[tokio::test]
+async fn movements_carry_the_lot_of_tracked_products() {
+    let f = setup().await;
+    let lot = f.lot("L-1", Some(day(30))).await;
+
+    let cases = [
+        (&f.milk, None, "product is lot tracked, lot required"),
+        (&f.bolt, Some(lot.id), "product is not lot tracked"),
+        (&f.milk, Some(Uuid::new_v4()), "stock lot not found"),
+    ];
+    for (product, lot_id, message) in cases {
+        let Err(err) = f
+            .stock
+            .post_movement(
+                f.movement(product, lot_id, MovementType::Receipt, 5),
+                f.user,
+            )
+            .await
+        else {
+            panic!("movement of {} should be refused", product.sku);
+        };
+        assert_eq!(err.to_string(), message);
+    }
+
+    f.stock
+        .post_movement(
+            f.movement(&f.milk, Some(lot.id), MovementType::Receipt, 5),
+            f.user,
+        )
+        .await
+        .unwrap();
+    f.stock
+        .post_movement(
+            f.movement(&f.milk, Some(lot.id), MovementType::Issue, 2),
+            f.user,
+        )
+        .await
+        .unwrap();
+    let detail = f.usecase.get_lot(lot.id).await.unwrap();
+    assert_eq!(detail.on_hand, 3);
+    assert_eq!(detail.balances.len(), 1);
+    assert_eq!(detail.balances[0].location_id, f.bin.id);
+
+    // the lot can not go below zero
+    let Err(err) = f
+        .stock
+        .post_movement(
+            f.movement(&f.milk, Some(lot.id), MovementType::Issue, 4),
+            f.user,
+        )
+        .await
+    else {
+        panic!("issuing more than the lot holds should be refused");
+    };
+    assert!(matches!(err, StockUsecaseError::InsufficientStock { .. }));
+}
+
+#[tokio::test]
+async fn expiring_lots_are_listed_within_the_window() {
+    let f = setup().await;
+    let expired = f.lot("L-OLD", Some(day(-1))).await;
+    let soon = f.lot("L-SOON", Some(day(5))).await;
+    let later = f.lot("L-LATER", Some(day(60))).await;
+    let empty = f.lot("L-EMPTY", Some(day(2))).await;
+    let never = f.lot("L-NEVER", None).await;
+    for lot in [&expired, &soon, &later, &never] {
+        f.stock
+            .post_movement(
+                f.movement(&f.milk, Some(lot.id), MovementType::Receipt, 4),
+                f.user,
+            )
+            .await
+            .unwrap();
+    }
+
+    let lots = f
+        .usecase
+        .list_expiring_lots(f.acme.id, 7, None)
+        .await
+        .unwrap();
+    let numbers: Vec<&str> = lots.iter().map(|lot| lot.lot_number.as_str()).collect();
+    assert_eq!(numbers, ["L-OLD", "L-SOON"]);
+    assert!(lots.iter().all(|lot| lot.on_hand == 4));
+    assert!(!numbers.contains(&empty.lot_number.as_str()));
+
+    let elsewhere = f
+        .usecase
+        .list_expiring_lots(f.acme.id, 7, Some(Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert!(elsewhere.is_empty());
+
+    let Err(err) = f.usecase.list_expiring_lots(f.acme.id, -1, None).await else {
+        panic!("a negative window should be refused");
+    };
+    assert!(matches!(err, LotUsecaseError::InvalidDays));
+
+    let filter = StockLotFilter {
+        company_id: f.acme.id,
+        product_id: Some(f.milk.id),
+    };
+    let listed = f.usecase.list_lots(&filter, &query()).await.unwrap();
+    assert_eq!(listed.total_data, 5);
+}
@@ -0,0 +1,16 @@
+pub mod count_usecase;
+pub mod lot_usecase;
+pub mod serial_usecase;
+pub mod stock_usecase;
+pub mod transfer_usecase;
+pub mod dto;
+#[cfg(test)]
+mod count_usecase_test;
+#[cfg(test)]
+mod lot_usecase_test;
+#[cfg(test)]
+mod serial_usecase_test;
+#[cfg(test)]
+mod stock_usecase_test;
+#[cfg(test)]
+mod transfer_usecase_test;
@@ -0,0 +1,160 @@
+use core::fmt;
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::serial::{SerialMismatch, StockSerialFilter};
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::dto::{ListStockSerialResult, SerialDetail};
+
+// serials are written by the movements naming them, see stock_usecase::post_in
+pub struct SerialUsecase<R: StockRepository> {
+    repo: R,
+}
+
+#[derive(Debug)]
+pub enum SerialUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for SerialUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerialUsecaseError::CompanyNotFound => write!(f, "serial company not found"),
+            SerialUsecaseError::NotFound => write!(f, "serial not found"),
+            SerialUsecaseError::DatabaseError(_) => write!(f, "serial storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for SerialUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SerialUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: StockRepository> SerialUsecase<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    #[instrument(skip_all, fields(serial_id = %id))]
+    pub async fn get_serial(&self, id: Uuid) -> Result<SerialDetail, SerialUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_serial_in(&mut uow, &id).await;
+        finish(uow, result, SerialUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_serials(
+        &self,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockSerialResult, SerialUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_serials_in(&mut uow, filter, query).await;
+        finish(uow, result, SerialUsecaseError::DatabaseError).await
+    }
+
+    // balances of serialised products that differ from their count of serials
+    // in stock, empty when everything adds up
+    #[instrument(skip_all, fields(company_id = %company_id))]
+    pub async fn reconcile(
+        &self,
+        company_id: Uuid,
+    ) -> Result<Vec<SerialMismatch>, SerialUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.reconcile_in(&mut uow, &company_id).await;
+        finish(uow, result, SerialUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, SerialUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)
+    }
+
+    async fn get_serial_in(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<SerialDetail, SerialUsecaseError> {
+        let serial = self
+            .repo
+            .get_serial_by_id(uow, id)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)?
+            .ok_or(SerialUsecaseError::NotFound)?;
+        let history = self
+            .repo
+            .find_serial_events(uow, id)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)?;
+
+        Ok(SerialDetail { serial, history })
+    }
+
+    async fn list_serials_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockSerialFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockSerialResult, SerialUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_serial = self
+            .repo
+            .count_serials(uow, filter, query)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)?;
+
+        let serials = self
+            .repo
+            .find_serials(uow, filter, query)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)?;
+
+        Ok(ListStockSerialResult {
+            data: serials,
+            total_data: total_serial,
+        })
+    }
+
+    async fn reconcile_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<SerialMismatch>, SerialUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        self.repo
+            .find_serial_mismatches(uow, company_id)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), SerialUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(SerialUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(SerialUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+}
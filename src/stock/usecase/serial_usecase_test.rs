@@ -0,0 +1,289 @@
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::stock::domain::movement::MovementType;
+use crate::stock::domain::serial::{SerialStatus, StockSerial, StockSerialFilter};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::MovementInput;
+use crate::stock::usecase::serial_usecase::{SerialUsecase, SerialUsecaseError};
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str, is_serialised: bool) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+struct Fixture {
+    usecase: SerialUsecase<StockRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    acme: Company,
+    laptop: Product,
+    bolt: Product,
+    bin_a: Location,
+    bin_b: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    fn movement(
+        &self,
+        product: &Product,
+        location: &Location,
+        movement_type: MovementType,
+        quantity: i64,
+        serial_numbers: &[&str],
+    ) -> MovementInput {
+        MovementInput {
+            product_id: product.id,
+            location_id: location.id,
+            movement_type,
+            lot_id: None,
+            quantity,
+            serial_numbers: serial_numbers.iter().map(|s| s.to_string()).collect(),
+            reference: None,
+            note: None,
+        }
+    }
+
+    async fn serial(&self, number: &str) -> StockSerial {
+        let filter = StockSerialFilter {
+            company_id: self.acme.id,
+            product_id: Some(self.laptop.id),
+            location_id: None,
+            status: None,
+        };
+        let serials = self.usecase.list_serials(&filter, &query()).await.unwrap();
+        serials
+            .data
+            .into_iter()
+            .find(|s| s.serial_number == number)
+            .unwrap()
+    }
+}
+
+// ACM keeps serialised laptops and plain bolts in two bins
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone()]);
+    let laptop = product(&acme, "LAPTOP", true);
+    let bolt = product(&acme, "BOLT", false);
+    let products = ProductRepositoryMemory::with_products(
+        companies.clone(),
+        vec![laptop.clone(), bolt.clone()],
+    );
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let warehouse_usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = warehouse_usecase
+        .create_warehouse(
+            acme.id,
+            WarehouseInput {
+                code: "NORTH".into(),
+                name: "north".into(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    let mut bins = Vec::new();
+    for code in ["A-01", "B-01"] {
+        let bin = warehouse_usecase
+            .create_location(
+                warehouse.id,
+                LocationInput {
+                    parent_id: None,
+                    code: code.into(),
+                    name: format!("Bin {code}"),
+                    kind: LocationKind::Bin,
+                },
+            )
+            .await
+            .unwrap();
+        bins.push(bin);
+    }
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    Fixture {
+        usecase: SerialUsecase::new(repo.clone()),
+        stock: StockUsecase::new(repo, AppMetrics::new()),
+        acme,
+        laptop,
+        bolt,
+        bin_b: bins.pop().unwrap(),
+        bin_a: bins.pop().unwrap(),
+        user: Uuid::new_v4(),
+    }
+}
+
+#[tokio::test]
+async fn movements_carry_one_serial_per_unit_of_serialised_products() {
+    let f = setup().await;
+    f.stock
+        .post_movement(
+            f.movement(
+                &f.laptop,
+                &f.bin_a,
+                MovementType::Receipt,
+                2,
+                &["SN-1", "SN-2"],
+            ),
+            f.user,
+        )
+        .await
+        .unwrap();
+
+    let cases = [
+        (
+            f.movement(&f.bolt, &f.bin_a, MovementType::Receipt, 1, &["SN-1"]),
+            "product is not serialised",
+        ),
+        (
+            f.movement(&f.laptop, &f.bin_a, MovementType::Receipt, 2, &["SN-3"]),
+            "product is serialised, 2 serial numbers required",
+        ),
+        (
+            f.movement(
+                &f.laptop,
+                &f.bin_a,
+                MovementType::Receipt,
+                2,
+                &["SN-3", " SN-3 "],
+            ),
+            "serial number given twice",
+        ),
+        (
+            f.movement(&f.laptop, &f.bin_a, MovementType::Receipt, 1, &[" "]),
+            "serial number is invalid",
+        ),
+        (
+            f.movement(&f.laptop, &f.bin_b, MovementType::Receipt, 1, &["SN-1"]),
+            "serial SN-1 is already in stock",
+        ),
+        (
+            f.movement(&f.laptop, &f.bin_b, MovementType::Issue, 1, &["SN-1"]),
+            "serial SN-1 is not in stock at the location",
+        ),
+        (
+            f.movement(&f.laptop, &f.bin_a, MovementType::Adjustment, -1, &["SN-9"]),
+            "serial SN-9 is not in stock at the location",
+        ),
+    ];
+    for (input, message) in cases {
+        let Err(err) = f.stock.post_movement(input, f.user).await else {
+            panic!("movement should be refused with {message}");
+        };
+        assert_eq!(err.to_string(), message);
+    }
+
+    let issued = f
+        .stock
+        .post_movement(
+            f.movement(&f.laptop, &f.bin_a, MovementType::Issue, 1, &["SN-1"]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    let serial = f.serial("SN-1").await;
+    assert_eq!(serial.status, SerialStatus::Issued);
+    assert_eq!(serial.location_id, None);
+
+    // an issued serial can come back, e.g. as a customer return into another bin
+    f.stock
+        .post_movement(
+            f.movement(&f.laptop, &f.bin_b, MovementType::Receipt, 1, &["SN-1"]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    let serial = f.serial("SN-1").await;
+    assert_eq!(serial.status, SerialStatus::InStock);
+    assert_eq!(serial.location_id, Some(f.bin_b.id));
+
+    // reversing the issue would put SN-1 in stock twice
+    let Err(err) = f.stock.reverse_movement(issued.id, None, f.user).await else {
+        panic!("reversing the issue should be refused");
+    };
+    assert!(matches!(err, StockUsecaseError::SerialInStock(number) if number == "SN-1"));
+
+    let mismatches = f.usecase.reconcile(f.acme.id).await.unwrap();
+    assert!(mismatches.is_empty());
+}
+
+#[tokio::test]
+async fn serial_history_lists_every_movement_in_order() {
+    let f = setup().await;
+    let received = f
+        .stock
+        .post_movement(
+            f.movement(&f.laptop, &f.bin_a, MovementType::Receipt, 1, &["SN-1"]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    let reversal = f
+        .stock
+        .reverse_movement(received.id, None, f.user)
+        .await
+        .unwrap();
+    assert_eq!(reversal.quantity, -1);
+
+    let serial = f.serial("SN-1").await;
+    assert_eq!(serial.status, SerialStatus::Issued);
+    let detail = f.usecase.get_serial(serial.id).await.unwrap();
+    let steps: Vec<(MovementType, i64)> = detail
+        .history
+        .iter()
+        .map(|event| (event.movement_type, event.quantity))
+        .collect();
+    assert_eq!(
+        steps,
+        [(MovementType::Receipt, 1), (MovementType::Reversal, -1)]
+    );
+    assert!(detail.history.iter().all(|e| e.location_id == f.bin_a.id));
+    assert!(detail.history.iter().all(|e| e.supplier_id.is_none()));
+
+    let filter = StockSerialFilter {
+        company_id: f.acme.id,
+        product_id: None,
+        location_id: Some(f.bin_a.id),
+        status: Some(SerialStatus::InStock),
+    };
+    let in_stock = f.usecase.list_serials(&filter, &query()).await.unwrap();
+    assert_eq!(in_stock.total_data, 0);
+
+    let Err(err) = f.usecase.get_serial(Uuid::new_v4()).await else {
+        panic!("an unknown serial should not be found");
+    };
+    assert!(matches!(err, SerialUsecaseError::NotFound));
+}
@@ -0,0 +1,913 @@
+use core::fmt;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::product::domain::product_unit::{UnitConversion, UnitPurpose};
+use crate::stock::domain::balance::{BalanceMismatch, StockBalanceFilter};
+use crate::stock::domain::lot::StockLot;
+use crate::stock::domain::movement::{
+    MovementSource, MovementType, StockMovement, StockMovementFilter,
+};
+use crate::stock::domain::serial::{SerialStatus, StockSerial};
+use crate::stock::domain::settings::StockSettings;
+use crate::stock::repository::stock_repository::StockRepository;
+use crate::stock::usecase::dto::{ListStockBalanceResult, ListStockMovementResult, MovementInput};
+
+// names of the constraints on the stock tables, see migrations
+const MOVEMENT_REVERSAL_CONSTRAINT: &str = "stock_movements_reversal_of_key";
+const MOVEMENT_PRODUCT_CONSTRAINT: &str = "stock_movements_product_id_fkey";
+const MOVEMENT_LOCATION_CONSTRAINT: &str = "stock_movements_location_id_fkey";
+const BALANCE_PRODUCT_CONSTRAINT: &str = "stock_balances_product_id_fkey";
+const BALANCE_LOCATION_CONSTRAINT: &str = "stock_balances_location_id_fkey";
+const MOVEMENT_LOT_CONSTRAINT: &str = "stock_movements_lot_id_fkey";
+const LOT_BALANCE_LOT_CONSTRAINT: &str = "stock_lot_balances_lot_id_fkey";
+const LOT_BALANCE_LOCATION_CONSTRAINT: &str = "stock_lot_balances_location_id_fkey";
+const SETTINGS_COMPANY_CONSTRAINT: &str = "company_stock_settings_company_id_fkey";
+const RESERVATION_PRODUCT_CONSTRAINT: &str = "stock_reservations_product_id_fkey";
+const RESERVATION_WAREHOUSE_CONSTRAINT: &str = "stock_reservations_warehouse_fkey";
+const SERIAL_NUMBER_CONSTRAINT: &str = "stock_serials_product_id_serial_number_key";
+const SERIAL_LOCATION_CONSTRAINT: &str = "stock_serials_location_id_fkey";
+
+// column size of stock_serials.serial_number
+const MAX_SERIAL_LEN: usize = 100;
+
+pub struct StockUsecase<R: StockRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum StockUsecaseError {
+    CompanyNotFound,
+    ProductNotFound,
+    LocationNotFound,
+    // product and location belong to different companies
+    CompanyMismatch,
+    InvalidQuantity,
+    // reversals are only posted through the movement they undo
+    ReversalNotPostable,
+    // transfer movements are only posted by dispatching and receiving a transfer
+    TransferNotPostable,
+    NotFound,
+    AlreadyReversed,
+    ReversalNotReversible,
+    // the movement was posted by a document and is corrected through it
+    DocumentMovement(MovementSource),
+    InsufficientStock { available: i64 },
+    // movements of a lot tracked product name their lot, others have none
+    LotRequired,
+    LotNotAllowed,
+    // the lot does not exist or is a lot of another product
+    LotNotFound,
+    LotExpired,
+    // movements of a serialised product name one serial per unit, others have none
+    SerialsNotAllowed,
+    SerialCountMismatch { expected: i64 },
+    InvalidSerial,
+    DuplicateSerial,
+    SerialInStock(String),
+    // the serial is not in stock at the location the movement takes it from
+    SerialNotInStock(String),
+    // an open count takes stock of the location
+    LocationCounted,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for StockUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StockUsecaseError::CompanyNotFound => write!(f, "stock company not found"),
+            StockUsecaseError::ProductNotFound => write!(f, "stock product not found"),
+            StockUsecaseError::LocationNotFound => write!(f, "stock location not found"),
+            StockUsecaseError::CompanyMismatch => {
+                write!(f, "product and location belong to different companies")
+            }
+            StockUsecaseError::InvalidQuantity => write!(f, "stock quantity is invalid"),
+            StockUsecaseError::ReversalNotPostable => {
+                write!(f, "reversals are posted through the reversed movement")
+            }
+            StockUsecaseError::TransferNotPostable => {
+                write!(f, "transfer movements are posted through a transfer")
+            }
+            StockUsecaseError::NotFound => write!(f, "stock movement not found"),
+            StockUsecaseError::AlreadyReversed => write!(f, "stock movement already reversed"),
+            StockUsecaseError::ReversalNotReversible => {
+                write!(f, "a reversal can not be reversed")
+            }
+            StockUsecaseError::DocumentMovement(source) => {
+                write!(f, "stock movement belongs to a {}", source.as_str())
+            }
+            StockUsecaseError::InsufficientStock { available } => {
+                write!(f, "insufficient stock, {available} available")
+            }
+            StockUsecaseError::LotRequired => write!(f, "product is lot tracked, lot required"),
+            StockUsecaseError::LotNotAllowed => write!(f, "product is not lot tracked"),
+            StockUsecaseError::LotNotFound => write!(f, "stock lot not found"),
+            StockUsecaseError::LotExpired => write!(f, "stock lot expired"),
+            StockUsecaseError::SerialsNotAllowed => write!(f, "product is not serialised"),
+            StockUsecaseError::SerialCountMismatch { expected } => {
+                write!(
+                    f,
+                    "product is serialised, {expected} serial numbers required"
+                )
+            }
+            StockUsecaseError::InvalidSerial => write!(f, "serial number is invalid"),
+            StockUsecaseError::DuplicateSerial => write!(f, "serial number given twice"),
+            StockUsecaseError::SerialInStock(number) => {
+                write!(f, "serial {number} is already in stock")
+            }
+            StockUsecaseError::SerialNotInStock(number) => {
+                write!(f, "serial {number} is not in stock at the location")
+            }
+            StockUsecaseError::LocationCounted => write!(f, "stock location is being counted"),
+            StockUsecaseError::DatabaseError(_) => write!(f, "stock storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for StockUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StockUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// a movement ready to be written, quantity already signed
+pub(crate) struct Posting {
+    pub company_id: Uuid,
+    pub product_id: Uuid,
+    pub location_id: Uuid,
+    pub movement_type: MovementType,
+    pub lot_id: Option<Uuid>,
+    pub quantity: i64,
+    // one per unit for serialised products
+    pub serial_numbers: Vec<String>,
+    pub reversal_of: Option<Uuid>,
+    // the document posting the movement, Manual and None for postings by hand
+    pub source: MovementSource,
+    pub source_id: Option<Uuid>,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    pub posted_by: Uuid,
+}
+
+impl<R: StockRepository> StockUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(product_id = %input.product_id, location_id = %input.location_id, movement_type = input.movement_type.as_str()))]
+    pub async fn post_movement(
+        &self,
+        input: MovementInput,
+        posted_by: Uuid,
+    ) -> Result<StockMovement, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.post_movement_in(&mut uow, input, posted_by).await;
+        let movement = finish(uow, result, StockUsecaseError::DatabaseError).await?;
+
+        self.metrics
+            .stock_movement_posted(movement.movement_type.as_str());
+        Ok(movement)
+    }
+
+    #[instrument(skip_all, fields(movement_id = %id))]
+    pub async fn reverse_movement(
+        &self,
+        id: Uuid,
+        note: Option<String>,
+        posted_by: Uuid,
+    ) -> Result<StockMovement, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .reverse_movement_in(&mut uow, id, note, posted_by)
+            .await;
+        let movement = finish(uow, result, StockUsecaseError::DatabaseError).await?;
+
+        self.metrics
+            .stock_movement_posted(movement.movement_type.as_str());
+        Ok(movement)
+    }
+
+    #[instrument(skip_all, fields(movement_id = %id))]
+    pub async fn get_movement(&self, id: Uuid) -> Result<StockMovement, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .repo
+            .get_movement_by_id(&mut uow, &id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)
+            .and_then(|movement| movement.ok_or(StockUsecaseError::NotFound));
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_movements(
+        &self,
+        filter: &StockMovementFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockMovementResult, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_movements_in(&mut uow, filter, query).await;
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_balances(
+        &self,
+        filter: &StockBalanceFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockBalanceResult, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_balances_in(&mut uow, filter, query).await;
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    // balances that no longer match their ledger, empty when everything adds up
+    #[instrument(skip_all, fields(company_id = %company_id))]
+    pub async fn reconcile(
+        &self,
+        company_id: Uuid,
+    ) -> Result<Vec<BalanceMismatch>, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.reconcile_in(&mut uow, &company_id).await;
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id))]
+    pub async fn get_settings(&self, company_id: Uuid) -> Result<StockSettings, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_settings_in(&mut uow, &company_id).await;
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id))]
+    pub async fn update_settings(
+        &self,
+        company_id: Uuid,
+        allow_negative_stock: bool,
+    ) -> Result<StockSettings, StockUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .update_settings_in(&mut uow, company_id, allow_negative_stock)
+            .await;
+        finish(uow, result, StockUsecaseError::DatabaseError).await
+    }
+
+    async fn begin(&self) -> Result<R::Uow, StockUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(StockUsecaseError::DatabaseError)
+    }
+
+    async fn post_movement_in(
+        &self,
+        uow: &mut R::Uow,
+        input: MovementInput,
+        posted_by: Uuid,
+    ) -> Result<StockMovement, StockUsecaseError> {
+        let quantity = signed_quantity(input.movement_type, input.quantity)?;
+
+        let company_id = self
+            .repo
+            .get_product_company(uow, &input.product_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?
+            .ok_or(StockUsecaseError::ProductNotFound)?;
+        let location_company_id = self
+            .repo
+            .get_location_company(uow, &input.location_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?
+            .ok_or(StockUsecaseError::LocationNotFound)?;
+        if company_id != location_company_id {
+            return Err(StockUsecaseError::CompanyMismatch);
+        }
+
+        let posting = Posting {
+            company_id,
+            product_id: input.product_id,
+            location_id: input.location_id,
+            movement_type: input.movement_type,
+            lot_id: input.lot_id,
+            quantity,
+            serial_numbers: input.serial_numbers,
+            reversal_of: None,
+            source: MovementSource::Manual,
+            source_id: None,
+            reference: input.reference,
+            note: input.note,
+            posted_by,
+        };
+        post_in(&self.repo, uow, posting).await
+    }
+
+    async fn reverse_movement_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        note: Option<String>,
+        posted_by: Uuid,
+    ) -> Result<StockMovement, StockUsecaseError> {
+        let movement = self
+            .repo
+            .get_movement_by_id(uow, &id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?
+            .ok_or(StockUsecaseError::NotFound)?;
+        if movement.movement_type == MovementType::Reversal {
+            return Err(StockUsecaseError::ReversalNotReversible);
+        }
+        if movement.source != MovementSource::Manual {
+            return Err(StockUsecaseError::DocumentMovement(movement.source));
+        }
+        let reversal = self
+            .repo
+            .find_reversal(uow, &id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+        if reversal.is_some() {
+            return Err(StockUsecaseError::AlreadyReversed);
+        }
+        let serial_numbers = self
+            .repo
+            .find_movement_serial_numbers(uow, &movement.id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+
+        let posting = Posting {
+            company_id: movement.company_id,
+            product_id: movement.product_id,
+            location_id: movement.location_id,
+            movement_type: MovementType::Reversal,
+            lot_id: movement.lot_id,
+            quantity: -movement.quantity,
+            serial_numbers,
+            reversal_of: Some(movement.id),
+            source: MovementSource::Manual,
+            source_id: None,
+            reference: movement.reference,
+            note,
+            posted_by,
+        };
+        post_in(&self.repo, uow, posting).await
+    }
+
+    async fn list_movements_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockMovementFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockMovementResult, StockUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_movement = self
+            .repo
+            .count_movements(uow, filter)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+
+        let movements = self
+            .repo
+            .find_movements(uow, filter, query)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+
+        Ok(ListStockMovementResult {
+            data: movements,
+            total_data: total_movement,
+        })
+    }
+
+    async fn list_balances_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockBalanceFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockBalanceResult, StockUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_balance = self
+            .repo
+            .count_balances(uow, filter)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+
+        let balances = self
+            .repo
+            .find_balances(uow, filter, query)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+
+        Ok(ListStockBalanceResult {
+            data: balances,
+            total_data: total_balance,
+        })
+    }
+
+    async fn reconcile_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<BalanceMismatch>, StockUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        self.repo
+            .find_balance_mismatches(uow, company_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)
+    }
+
+    async fn get_settings_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<StockSettings, StockUsecaseError> {
+        let settings = self
+            .repo
+            .get_stock_settings(uow, company_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+        if let Some(settings) = settings {
+            return Ok(settings);
+        }
+
+        self.check_company(uow, company_id).await?;
+        Ok(default_settings(*company_id))
+    }
+
+    async fn update_settings_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        allow_negative_stock: bool,
+    ) -> Result<StockSettings, StockUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+
+        let settings = StockSettings {
+            company_id,
+            allow_negative_stock,
+            updated_at: Utc::now(),
+        };
+        self.repo
+            .save_stock_settings(uow, settings)
+            .await
+            .map_err(map_stock_write_error)
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), StockUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(StockUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+// how a document line of the product entered in the unit comes to base units,
+// no unit means the base unit. None when the product has no such unit for the
+// purpose, or no longer exists
+pub(crate) async fn find_conversion_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    product_id: &Uuid,
+    unit: Option<&str>,
+    purpose: UnitPurpose,
+) -> Result<Option<UnitConversion>, sqlx::Error> {
+    let Some(base_unit) = repo.get_product_base_unit(uow, product_id).await? else {
+        return Ok(None);
+    };
+    let code = match unit {
+        Some(code) if code != base_unit => code,
+        _ => return Ok(Some(UnitConversion::base(base_unit))),
+    };
+    let conversion = repo
+        .get_product_unit(uow, product_id, code)
+        .await?
+        .filter(|unit| unit.allows(purpose))
+        .map(UnitConversion::from);
+
+    Ok(conversion)
+}
+
+// the lot a movement of the product has to carry, None for products that are
+// not lot tracked. shared with the documents that check their lines up front
+pub(crate) async fn check_lot_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    product_id: &Uuid,
+    lot_id: Option<&Uuid>,
+) -> Result<Option<StockLot>, StockUsecaseError> {
+    let is_lot_tracked = repo
+        .is_product_lot_tracked(uow, product_id)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?;
+    match (is_lot_tracked, lot_id) {
+        (false, None) => Ok(None),
+        (false, Some(_)) => Err(StockUsecaseError::LotNotAllowed),
+        (true, None) => Err(StockUsecaseError::LotRequired),
+        (true, Some(lot_id)) => repo
+            .get_lot_by_id(uow, lot_id)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?
+            .filter(|lot| lot.product_id == *product_id)
+            .map(Some)
+            .ok_or(StockUsecaseError::LotNotFound),
+    }
+}
+
+// the serials a movement of the product moves, locked and checked against the
+// direction of the movement. None in the list is a serial received for the
+// first time. shared with the shipments, which check their lines up front
+pub(crate) async fn check_serials_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    product_id: &Uuid,
+    location_id: &Uuid,
+    quantity: i64,
+    serial_numbers: &[String],
+) -> Result<Vec<(String, Option<StockSerial>)>, StockUsecaseError> {
+    let is_serialised = repo
+        .is_product_serialised(uow, product_id)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?;
+    if !is_serialised {
+        if !serial_numbers.is_empty() {
+            return Err(StockUsecaseError::SerialsNotAllowed);
+        }
+        return Ok(Vec::new());
+    }
+    if serial_numbers.len() as u64 != quantity.unsigned_abs() {
+        return Err(StockUsecaseError::SerialCountMismatch {
+            expected: quantity.abs(),
+        });
+    }
+
+    let mut serials: Vec<(String, Option<StockSerial>)> = Vec::new();
+    for number in serial_numbers {
+        let number = number.trim();
+        if number.is_empty() || number.chars().count() > MAX_SERIAL_LEN {
+            return Err(StockUsecaseError::InvalidSerial);
+        }
+        if serials.iter().any(|(n, _)| n == number) {
+            return Err(StockUsecaseError::DuplicateSerial);
+        }
+        let serial = repo
+            .lock_serial(uow, product_id, number)
+            .await
+            .map_err(StockUsecaseError::DatabaseError)?;
+        let in_stock = serial
+            .as_ref()
+            .is_some_and(|s| s.status == SerialStatus::InStock);
+        if quantity > 0 && in_stock {
+            return Err(StockUsecaseError::SerialInStock(number.into()));
+        }
+        let at_location = serial
+            .as_ref()
+            .is_some_and(|s| in_stock && s.location_id == Some(*location_id));
+        if quantity < 0 && !at_location {
+            return Err(StockUsecaseError::SerialNotInStock(number.into()));
+        }
+        serials.push((number.into(), serial));
+    }
+
+    Ok(serials)
+}
+
+// locks the balance row first, a concurrent posting for the same product and
+// location waits here until this unit of work finishes and then sees the new
+// balance. the lot balance is locked after it, so its lock order follows the
+// product and location. the serials are locked before both, and the
+// reservation of the warehouse before the serials when stock is taken away.
+// nothing is posted on a location while a count takes stock of it. shared with
+// the transfers, which post several movements in one unit of work
+pub(crate) async fn post_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    posting: Posting,
+) -> Result<StockMovement, StockUsecaseError> {
+    let is_counted = repo
+        .is_location_counted(uow, &posting.location_id)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?;
+    if is_counted {
+        return Err(StockUsecaseError::LocationCounted);
+    }
+    let lot = check_lot_in(repo, uow, &posting.product_id, posting.lot_id.as_ref()).await?;
+    let reservation = lock_reservation_in(repo, uow, &posting).await?;
+    let serials = check_serials_in(
+        repo,
+        uow,
+        &posting.product_id,
+        &posting.location_id,
+        posting.quantity,
+        &posting.serial_numbers,
+    )
+    .await?;
+
+    let available = repo
+        .lock_balance(
+            uow,
+            &posting.company_id,
+            &posting.product_id,
+            &posting.location_id,
+        )
+        .await
+        .map_err(map_stock_write_error)?;
+
+    let balance_after = available
+        .checked_add(posting.quantity)
+        .ok_or(StockUsecaseError::InvalidQuantity)?;
+    check_available(repo, uow, &posting, available, balance_after).await?;
+    check_unreserved(repo, uow, &posting, lot.as_ref(), reservation).await?;
+
+    let lot_balance_after = match &posting.lot_id {
+        Some(lot_id) => {
+            let lot_available = repo
+                .lock_lot_balance(
+                    uow,
+                    &posting.company_id,
+                    lot_id,
+                    &posting.product_id,
+                    &posting.location_id,
+                )
+                .await
+                .map_err(map_stock_write_error)?;
+            let lot_balance_after = lot_available
+                .checked_add(posting.quantity)
+                .ok_or(StockUsecaseError::InvalidQuantity)?;
+            check_available(repo, uow, &posting, lot_available, lot_balance_after).await?;
+            Some(lot_balance_after)
+        }
+        None => None,
+    };
+
+    let posted_at = Utc::now();
+    let movement = StockMovement {
+        id: Uuid::new_v4(),
+        company_id: posting.company_id,
+        product_id: posting.product_id,
+        location_id: posting.location_id,
+        movement_type: posting.movement_type,
+        quantity: posting.quantity,
+        balance_after,
+        reversal_of: posting.reversal_of,
+        source: posting.source,
+        source_id: posting.source_id,
+        lot_id: posting.lot_id,
+        reference: posting.reference,
+        note: posting.note,
+        posted_by: posting.posted_by,
+        posted_at,
+    };
+    let movement = repo
+        .create_movement(uow, movement)
+        .await
+        .map_err(map_stock_write_error)?;
+
+    repo.update_balance(
+        uow,
+        &movement.product_id,
+        &movement.location_id,
+        balance_after,
+        posted_at,
+    )
+    .await
+    .map_err(StockUsecaseError::DatabaseError)?;
+
+    if let (Some(lot_id), Some(lot_balance_after)) = (&movement.lot_id, lot_balance_after) {
+        repo.update_lot_balance(
+            uow,
+            lot_id,
+            &movement.location_id,
+            lot_balance_after,
+            posted_at,
+        )
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?;
+    }
+
+    if !serials.is_empty() {
+        move_serials_in(repo, uow, &movement, serials).await?;
+    }
+
+    Ok(movement)
+}
+
+// a serial coming in is in stock at the location, one going out is in transit
+// for a transfer and issued otherwise
+async fn move_serials_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    movement: &StockMovement,
+    serials: Vec<(String, Option<StockSerial>)>,
+) -> Result<(), StockUsecaseError> {
+    let (status, location_id) = match movement.movement_type {
+        _ if movement.quantity > 0 => (SerialStatus::InStock, Some(movement.location_id)),
+        MovementType::TransferOut => (SerialStatus::InTransit, None),
+        _ => (SerialStatus::Issued, None),
+    };
+
+    let mut serial_ids = Vec::new();
+    for (serial_number, serial) in serials {
+        let serial = match serial {
+            Some(serial) => {
+                let serial = StockSerial {
+                    status,
+                    location_id,
+                    updated_at: movement.posted_at,
+                    ..serial
+                };
+                repo.update_serial(uow, serial)
+                    .await
+                    .map_err(map_stock_write_error)?
+            }
+            None => {
+                let serial = StockSerial {
+                    id: Uuid::new_v4(),
+                    company_id: movement.company_id,
+                    product_id: movement.product_id,
+                    serial_number,
+                    status,
+                    location_id,
+                    created_at: movement.posted_at,
+                    updated_at: movement.posted_at,
+                };
+                repo.create_serial(uow, serial)
+                    .await
+                    .map_err(map_stock_write_error)?
+            }
+        };
+        serial_ids.push(serial.id);
+    }
+
+    repo.create_movement_serials(uow, &movement.id, &serial_ids)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)
+}
+
+// only taking stock away is refused, a receipt onto a negative balance left
+// from when the company allowed it still goes through
+async fn check_available<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    posting: &Posting,
+    available: i64,
+    balance_after: i64,
+) -> Result<(), StockUsecaseError> {
+    if posting.quantity >= 0 || balance_after >= 0 {
+        return Ok(());
+    }
+    if !allows_negative_stock(repo, uow, &posting.company_id).await? {
+        return Err(StockUsecaseError::InsufficientStock { available });
+    }
+
+    Ok(())
+}
+
+// the reservation of the location's warehouse when the posting takes stock
+// away, with its warehouse. a count records what is really there and is never
+// held back by reservations, it leaves them short instead
+async fn lock_reservation_in<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    posting: &Posting,
+) -> Result<Option<(Uuid, i64)>, StockUsecaseError> {
+    if posting.quantity >= 0 || posting.source == MovementSource::Count {
+        return Ok(None);
+    }
+    // an unknown location is refused by the balance lock
+    let Some(warehouse_id) = repo
+        .get_location_warehouse(uow, &posting.location_id)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?
+    else {
+        return Ok(None);
+    };
+    let reserved = repo
+        .lock_reservation(uow, &posting.company_id, &posting.product_id, &warehouse_id)
+        .await
+        .map_err(map_stock_write_error)?;
+
+    Ok(Some((warehouse_id, reserved)))
+}
+
+// stock held for open sales orders is not available to other postings, what
+// the warehouse has left must still cover the reservations. stock of an
+// expired lot is never reserved, taking it away is not held back
+async fn check_unreserved<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    posting: &Posting,
+    lot: Option<&StockLot>,
+    reservation: Option<(Uuid, i64)>,
+) -> Result<(), StockUsecaseError> {
+    let Some((warehouse_id, reserved)) = reservation else {
+        return Ok(());
+    };
+    let today = Utc::now().date_naive();
+    if reserved == 0 || lot.is_some_and(|lot| lot.is_expired(today)) {
+        return Ok(());
+    }
+    let usable = repo
+        .get_warehouse_usable(uow, &posting.product_id, &warehouse_id, today)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?;
+    let unreserved = usable - reserved;
+    if unreserved + posting.quantity >= 0 {
+        return Ok(());
+    }
+    if !allows_negative_stock(repo, uow, &posting.company_id).await? {
+        return Err(StockUsecaseError::InsufficientStock {
+            available: unreserved.max(0),
+        });
+    }
+
+    Ok(())
+}
+
+async fn allows_negative_stock<R: StockRepository>(
+    repo: &R,
+    uow: &mut R::Uow,
+    company_id: &Uuid,
+) -> Result<bool, StockUsecaseError> {
+    Ok(repo
+        .get_stock_settings(uow, company_id)
+        .await
+        .map_err(StockUsecaseError::DatabaseError)?
+        .is_some_and(|settings| settings.allow_negative_stock))
+}
+
+// companies that never changed their settings
+fn default_settings(company_id: Uuid) -> StockSettings {
+    StockSettings {
+        company_id,
+        allow_negative_stock: false,
+        updated_at: Utc::now(),
+    }
+}
+
+// the ledger stores what the movement did to the balance. transfer movements
+// come in pairs of a transfer, one posted alone would leave stock in transit
+// that no transfer accounts for
+fn signed_quantity(movement_type: MovementType, quantity: i64) -> Result<i64, StockUsecaseError> {
+    match movement_type {
+        MovementType::Reversal => Err(StockUsecaseError::ReversalNotPostable),
+        MovementType::TransferIn | MovementType::TransferOut => {
+            Err(StockUsecaseError::TransferNotPostable)
+        }
+        _ if quantity == 0 => Err(StockUsecaseError::InvalidQuantity),
+        MovementType::Adjustment => Ok(quantity),
+        _ if quantity < 0 => Err(StockUsecaseError::InvalidQuantity),
+        MovementType::Receipt => Ok(quantity),
+        MovementType::Issue => Ok(-quantity),
+    }
+}
+
+// a concurrent unit of work can still reverse the same movement or delete the
+// product or location between the check and the write, the constraints catch that
+fn map_stock_write_error(err: sqlx::Error) -> StockUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(MOVEMENT_REVERSAL_CONSTRAINT) => StockUsecaseError::AlreadyReversed,
+        Some(
+            MOVEMENT_PRODUCT_CONSTRAINT
+            | BALANCE_PRODUCT_CONSTRAINT
+            | RESERVATION_PRODUCT_CONSTRAINT,
+        ) => StockUsecaseError::ProductNotFound,
+        Some(
+            MOVEMENT_LOCATION_CONSTRAINT
+            | BALANCE_LOCATION_CONSTRAINT
+            | RESERVATION_WAREHOUSE_CONSTRAINT
+            | LOT_BALANCE_LOCATION_CONSTRAINT
+            | SERIAL_LOCATION_CONSTRAINT,
+        ) => StockUsecaseError::LocationNotFound,
+        Some(MOVEMENT_LOT_CONSTRAINT | LOT_BALANCE_LOT_CONSTRAINT) => {
+            StockUsecaseError::LotNotFound
+        }
+        Some(SETTINGS_COMPANY_CONSTRAINT) => StockUsecaseError::CompanyNotFound,
+        // a concurrent unit of work received the same new serial first
+        Some(SERIAL_NUMBER_CONSTRAINT) => StockUsecaseError::DuplicateSerial,
+        _ => StockUsecaseError::DatabaseError(err),
+    }
+}
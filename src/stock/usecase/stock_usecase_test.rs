@@ -0,0 +1,400 @@
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::stock::domain::balance::StockBalanceFilter;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovementFilter};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::MovementInput;
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+async fn bin(warehouses: &WarehouseRepositoryMemory, company: &Company) -> Location {
+    let usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = usecase
+        .create_warehouse(
+            company.id,
+            WarehouseInput {
+                code: "WH-1".into(),
+                name: "Main".into(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    usecase
+        .create_location(
+            warehouse.id,
+            LocationInput {
+                parent_id: None,
+                code: "A-01".into(),
+                name: "Bin A-01".into(),
+                kind: LocationKind::Bin,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+fn input(
+    product: &Product,
+    location: &Location,
+    movement_type: MovementType,
+    quantity: i64,
+) -> MovementInput {
+    MovementInput {
+        product_id: product.id,
+        location_id: location.id,
+        movement_type,
+        lot_id: None,
+        quantity,
+        serial_numbers: Vec::new(),
+        reference: None,
+        note: None,
+    }
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+struct Fixture {
+    usecase: StockUsecase<StockRepositoryMemory>,
+    repo: StockRepositoryMemory,
+    acme: Company,
+    bolt: Product,
+    bin: Location,
+    gear: Product,
+    globex_bin: Location,
+}
+
+// ACM stocks bolts in one bin, GLB gears in another. the clone of the
+// repository shares the store so the test can look at what was committed
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let bolt = product(&acme, "BOLT");
+    let gear = product(&globex, "GEAR");
+    let products =
+        ProductRepositoryMemory::with_products(companies.clone(), vec![bolt.clone(), gear.clone()]);
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let bin = bin(&warehouses, &acme).await;
+    let globex_bin = self::bin(&warehouses, &globex).await;
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    Fixture {
+        usecase: StockUsecase::new(repo.clone(), AppMetrics::new()),
+        repo,
+        acme,
+        bolt,
+        bin,
+        gear,
+        globex_bin,
+    }
+}
+
+#[tokio::test]
+async fn post_movement_signs_quantity_and_tracks_balance() {
+    let f = setup().await;
+    let user = Uuid::new_v4();
+
+    let receipt = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Receipt, 10), user)
+        .await
+        .unwrap();
+    assert_eq!(receipt.company_id, f.acme.id);
+    assert_eq!((receipt.quantity, receipt.balance_after), (10, 10));
+    assert_eq!(receipt.posted_by, user);
+    assert_eq!(receipt.source, MovementSource::Manual);
+    assert_eq!(receipt.source_id, None);
+
+    let cases = [
+        (MovementType::Issue, 3, -3, 7),
+        (MovementType::Adjustment, -1, -1, 6),
+        (MovementType::Adjustment, 4, 4, 10),
+    ];
+    for (movement_type, quantity, signed, balance_after) in cases {
+        let movement = f
+            .usecase
+            .post_movement(input(&f.bolt, &f.bin, movement_type, quantity), user)
+            .await
+            .unwrap();
+        assert_eq!(
+            (movement.quantity, movement.balance_after),
+            (signed, balance_after),
+            "{movement_type:?} {quantity}"
+        );
+    }
+
+    let tables = f.repo.tables().await;
+    assert_eq!(tables.movements.len(), 4);
+    assert_eq!(tables.balances.len(), 1);
+    assert_eq!(tables.balances[0].quantity, 10);
+}
+
+#[tokio::test]
+async fn post_movement_rejects_invalid_input() {
+    let f = setup().await;
+    let user = Uuid::new_v4();
+
+    let cases = [
+        (input(&f.bolt, &f.bin, MovementType::Receipt, 0), "zero"),
+        (
+            input(&f.bolt, &f.bin, MovementType::Adjustment, 0),
+            "zero adjustment",
+        ),
+        (
+            input(&f.bolt, &f.bin, MovementType::Issue, -1),
+            "negative issue",
+        ),
+        (
+            input(&f.bolt, &f.bin, MovementType::Receipt, -1),
+            "negative receipt",
+        ),
+    ];
+    for (input, case) in cases {
+        let result = f.usecase.post_movement(input, user).await;
+        assert!(
+            matches!(result, Err(StockUsecaseError::InvalidQuantity)),
+            "{case}"
+        );
+    }
+
+    let result = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Reversal, 1), user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::ReversalNotPostable)
+    ));
+    // transfer movements only come from dispatching and receiving a transfer
+    for movement_type in [MovementType::TransferIn, MovementType::TransferOut] {
+        let result = f
+            .usecase
+            .post_movement(input(&f.bolt, &f.bin, movement_type, 1), user)
+            .await;
+        assert!(
+            matches!(result, Err(StockUsecaseError::TransferNotPostable)),
+            "{movement_type:?}"
+        );
+    }
+
+    let mut missing = input(&f.bolt, &f.bin, MovementType::Receipt, 1);
+    missing.product_id = Uuid::new_v4();
+    let result = f.usecase.post_movement(missing, user).await;
+    assert!(matches!(result, Err(StockUsecaseError::ProductNotFound)));
+
+    let mut missing = input(&f.bolt, &f.bin, MovementType::Receipt, 1);
+    missing.location_id = Uuid::new_v4();
+    let result = f.usecase.post_movement(missing, user).await;
+    assert!(matches!(result, Err(StockUsecaseError::LocationNotFound)));
+
+    // a bolt of ACM can not be put into a bin of GLB
+    let result = f
+        .usecase
+        .post_movement(
+            input(&f.bolt, &f.globex_bin, MovementType::Receipt, 1),
+            user,
+        )
+        .await;
+    assert!(matches!(result, Err(StockUsecaseError::CompanyMismatch)));
+
+    assert!(f.repo.tables().await.movements.is_empty());
+}
+
+#[tokio::test]
+async fn post_movement_refuses_negative_stock_unless_allowed() {
+    let f = setup().await;
+    let user = Uuid::new_v4();
+    f.usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Receipt, 5), user)
+        .await
+        .unwrap();
+
+    let result = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 6), user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::InsufficientStock { available: 5 })
+    ));
+    let result = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Adjustment, -6), user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::InsufficientStock { available: 5 })
+    ));
+    // taking exactly what is there is fine
+    f.usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 5), user)
+        .await
+        .unwrap();
+
+    let settings = f.usecase.update_settings(f.acme.id, true).await.unwrap();
+    assert!(settings.allow_negative_stock);
+    let issue = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 2), user)
+        .await
+        .unwrap();
+    assert_eq!(issue.balance_after, -2);
+
+    // switching it off again still lets receipts in
+    f.usecase.update_settings(f.acme.id, false).await.unwrap();
+    let receipt = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Receipt, 1), user)
+        .await
+        .unwrap();
+    assert_eq!(receipt.balance_after, -1);
+    let result = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 1), user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::InsufficientStock { available: -1 })
+    ));
+}
+
+#[tokio::test]
+async fn reverse_movement_posts_the_opposite_once() {
+    let f = setup().await;
+    let user = Uuid::new_v4();
+    let mut receipt = input(&f.bolt, &f.bin, MovementType::Receipt, 8);
+    receipt.reference = Some("PO-1".into());
+    let receipt = f.usecase.post_movement(receipt, user).await.unwrap();
+    let issue = f
+        .usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 3), user)
+        .await
+        .unwrap();
+
+    let reversal = f
+        .usecase
+        .reverse_movement(issue.id, Some("wrong bin".into()), user)
+        .await
+        .unwrap();
+    assert_eq!(reversal.movement_type, MovementType::Reversal);
+    assert_eq!(reversal.reversal_of, Some(issue.id));
+    assert_eq!((reversal.quantity, reversal.balance_after), (3, 8));
+    assert_eq!(reversal.note.as_deref(), Some("wrong bin"));
+
+    let result = f.usecase.reverse_movement(issue.id, None, user).await;
+    assert!(matches!(result, Err(StockUsecaseError::AlreadyReversed)));
+    let result = f.usecase.reverse_movement(reversal.id, None, user).await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::ReversalNotReversible)
+    ));
+    let result = f.usecase.reverse_movement(Uuid::new_v4(), None, user).await;
+    assert!(matches!(result, Err(StockUsecaseError::NotFound)));
+
+    // reversing the receipt would take the stock below zero
+    f.usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Issue, 1), user)
+        .await
+        .unwrap();
+    let result = f.usecase.reverse_movement(receipt.id, None, user).await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::InsufficientStock { available: 7 })
+    ));
+
+    // the original rows are untouched
+    let stored = f.usecase.get_movement(issue.id).await.unwrap();
+    assert_eq!(stored, issue);
+}
+
+#[tokio::test]
+async fn lists_and_reconciles_company_stock() {
+    let f = setup().await;
+    let user = Uuid::new_v4();
+    f.usecase
+        .post_movement(input(&f.bolt, &f.bin, MovementType::Receipt, 4), user)
+        .await
+        .unwrap();
+    f.usecase
+        .post_movement(
+            input(&f.gear, &f.globex_bin, MovementType::Receipt, 9),
+            user,
+        )
+        .await
+        .unwrap();
+
+    let filter = StockMovementFilter {
+        company_id: f.acme.id,
+        product_id: None,
+        location_id: None,
+        movement_type: None,
+        lot_id: None,
+    };
+    let result = f.usecase.list_movements(&filter, &query()).await.unwrap();
+    assert_eq!(result.total_data, 1);
+    assert_eq!(result.data[0].product_id, f.bolt.id);
+
+    let filter = StockBalanceFilter {
+        company_id: f.acme.id,
+        product_id: None,
+        warehouse_id: None,
+        location_id: None,
+    };
+    let result = f.usecase.list_balances(&filter, &query()).await.unwrap();
+    assert_eq!(result.total_data, 1);
+    assert_eq!(result.data[0].quantity, 4);
+
+    assert!(f.usecase.reconcile(f.acme.id).await.unwrap().is_empty());
+
+    let unknown = StockBalanceFilter {
+        company_id: Uuid::new_v4(),
+        ..filter
+    };
+    let result = f.usecase.list_balances(&unknown, &query()).await;
+    assert!(matches!(result, Err(StockUsecaseError::CompanyNotFound)));
+    let result = f.usecase.reconcile(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(StockUsecaseError::CompanyNotFound)));
+    let result = f.usecase.get_settings(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(StockUsecaseError::CompanyNotFound)));
+    let result = f.usecase.update_settings(Uuid::new_v4(), true).await;
+    assert!(matches!(result, Err(StockUsecaseError::CompanyNotFound)));
+
+    let settings = f.usecase.get_settings(f.acme.id).await.unwrap();
+    assert!(!settings.allow_negative_stock);
+}
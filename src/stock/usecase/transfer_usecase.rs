@@ -0,0 +1,812 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovement};
+use crate::stock::domain::transfer::{
+    InTransitQuantity, StockTransfer, StockTransferFilter, TransferLine, TransferStatus,
+};
+use crate::stock::repository::transfer_repository::TransferRepository;
+use crate::stock::usecase::dto::{
+    ListStockTransferResult, ReceiptLineInput, TransferDetail, TransferInput, TransferLineInput,
+};
+use crate::stock::usecase::stock_usecase::{
+    Posting, StockUsecaseError, check_lot_in, check_serials_in, post_in,
+};
+
+// names of the constraints on stock_transfers and stock_transfer_lines, see migrations
+const TRANSFER_NUMBER_CONSTRAINT: &str = "stock_transfers_company_id_number_key";
+const TRANSFER_COMPANY_CONSTRAINT: &str = "stock_transfers_company_id_fkey";
+const TRANSFER_SOURCE_CONSTRAINT: &str = "stock_transfers_source_warehouse_fkey";
+const TRANSFER_DESTINATION_CONSTRAINT: &str = "stock_transfers_destination_warehouse_fkey";
+const LINE_PRODUCT_CONSTRAINT: &str = "stock_transfer_lines_product_id_fkey";
+const LINE_SOURCE_CONSTRAINT: &str = "stock_transfer_lines_source_location_id_fkey";
+const LINE_DESTINATION_CONSTRAINT: &str = "stock_transfer_lines_destination_location_id_fkey";
+
+pub struct TransferUsecase<R: TransferRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum TransferUsecaseError {
+    CompanyNotFound,
+    NotFound,
+    NumberAlreadyExist,
+    WarehouseNotFound,
+    SameWarehouse,
+    // a warehouse or product of another company
+    CompanyMismatch,
+    ProductNotFound,
+    // the location is missing or not in the warehouse the line needs
+    LocationNotFound,
+    InvalidQuantity,
+    NoLines,
+    // the transfer can not take this step from its current status
+    InvalidStatus(TransferStatus),
+    LineNotFound,
+    // more than what is still in transit for the line
+    ReceiptExceedsOpen,
+    InsufficientStock { product_id: Uuid, available: i64 },
+    // a posting failed for a reason the transfer does not translate
+    Posting(StockUsecaseError),
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for TransferUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferUsecaseError::CompanyNotFound => write!(f, "transfer company not found"),
+            TransferUsecaseError::NotFound => write!(f, "transfer not found"),
+            TransferUsecaseError::NumberAlreadyExist => {
+                write!(f, "transfer number already exists")
+            }
+            TransferUsecaseError::WarehouseNotFound => write!(f, "transfer warehouse not found"),
+            TransferUsecaseError::SameWarehouse => {
+                write!(f, "source and destination are the same warehouse")
+            }
+            TransferUsecaseError::CompanyMismatch => {
+                write!(f, "transfer refers to another company")
+            }
+            TransferUsecaseError::ProductNotFound => write!(f, "transfer product not found"),
+            TransferUsecaseError::LocationNotFound => write!(f, "transfer location not found"),
+            TransferUsecaseError::InvalidQuantity => write!(f, "transfer quantity is invalid"),
+            TransferUsecaseError::NoLines => write!(f, "transfer has no lines"),
+            TransferUsecaseError::InvalidStatus(status) => {
+                write!(f, "transfer is {}", status.as_str())
+            }
+            TransferUsecaseError::LineNotFound => write!(f, "transfer line not found"),
+            TransferUsecaseError::ReceiptExceedsOpen => {
+                write!(f, "received more than is in transit")
+            }
+            TransferUsecaseError::InsufficientStock {
+                product_id,
+                available,
+            } => write!(
+                f,
+                "insufficient stock of {product_id}, {available} available"
+            ),
+            TransferUsecaseError::Posting(err) => write!(f, "transfer posting failed: {err}"),
+            TransferUsecaseError::DatabaseError(_) => write!(f, "transfer storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for TransferUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransferUsecaseError::Posting(err) => Some(err),
+            TransferUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: TransferRepository> TransferUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, number = %input.number))]
+    pub async fn create_transfer(
+        &self,
+        company_id: Uuid,
+        input: TransferInput,
+        created_by: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .create_transfer_in(&mut uow, company_id, input, created_by)
+            .await;
+        let detail = finish(uow, result, TransferUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_transfer_operation("create");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(transfer_id = %id))]
+    pub async fn update_transfer(
+        &self,
+        id: Uuid,
+        input: TransferInput,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_transfer_in(&mut uow, id, input).await;
+        let detail = finish(uow, result, TransferUsecaseError::DatabaseError).await?;
+
+        self.metrics.stock_transfer_operation("update");
+        Ok(detail)
+    }
+
+    #[instrument(skip_all, fields(transfer_id = %id))]
+    pub async fn get_transfer(&self, id: Uuid) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_transfer_in(&mut uow, id).await;
+        finish(uow, result, TransferUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(company_id = %filter.company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_transfers(
+        &self,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockTransferResult, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_transfers_in(&mut uow, filter, query).await;
+        finish(uow, result, TransferUsecaseError::DatabaseError).await
+    }
+
+    // what left the source warehouses and has not arrived yet
+    #[instrument(skip_all, fields(company_id = %company_id))]
+    pub async fn list_in_transit(
+        &self,
+        company_id: Uuid,
+    ) -> Result<Vec<InTransitQuantity>, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_in_transit_in(&mut uow, &company_id).await;
+        finish(uow, result, TransferUsecaseError::DatabaseError).await
+    }
+
+    // takes every line out of its source location
+    #[instrument(skip_all, fields(transfer_id = %id))]
+    pub async fn dispatch_transfer(
+        &self,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.dispatch_transfer_in(&mut uow, id, posted_by).await;
+        let (detail, movements) = finish(uow, result, TransferUsecaseError::DatabaseError).await?;
+
+        self.record_postings("dispatch", &movements);
+        Ok(detail)
+    }
+
+    // puts the received quantities into their destination locations, lines
+    // that are not listed stay in transit
+    #[instrument(skip_all, fields(transfer_id = %id, lines = lines.len()))]
+    pub async fn receive_transfer(
+        &self,
+        id: Uuid,
+        lines: Vec<ReceiptLineInput>,
+        posted_by: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .receive_transfer_in(&mut uow, id, lines, posted_by)
+            .await;
+        let (detail, movements) = finish(uow, result, TransferUsecaseError::DatabaseError).await?;
+
+        self.record_postings("receive", &movements);
+        Ok(detail)
+    }
+
+    // a dispatched transfer puts what is still in transit back into the
+    // source locations
+    #[instrument(skip_all, fields(transfer_id = %id))]
+    pub async fn cancel_transfer(
+        &self,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.cancel_transfer_in(&mut uow, id, posted_by).await;
+        let (detail, movements) = finish(uow, result, TransferUsecaseError::DatabaseError).await?;
+
+        self.record_postings("cancel", &movements);
+        Ok(detail)
+    }
+
+    async fn begin(&self) -> Result<R::Uow, TransferUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)
+    }
+
+    fn record_postings(&self, operation: &str, movements: &[StockMovement]) {
+        self.metrics.stock_transfer_operation(operation);
+        for movement in movements {
+            self.metrics
+                .stock_movement_posted(movement.movement_type.as_str());
+        }
+    }
+
+    async fn create_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: TransferInput,
+        created_by: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, &company_id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(TransferUsecaseError::CompanyNotFound);
+        }
+        self.check_transfer_number(uow, &company_id, &input.number, None)
+            .await?;
+        self.check_warehouses(uow, &company_id, &input).await?;
+
+        let transfer = StockTransfer {
+            id: Uuid::new_v4(),
+            company_id,
+            number: input.number,
+            source_warehouse_id: input.source_warehouse_id,
+            destination_warehouse_id: input.destination_warehouse_id,
+            status: TransferStatus::Draft,
+            note: input.note,
+            created_by,
+            created_at: Utc::now(),
+            dispatched_at: None,
+            received_at: None,
+            cancelled_at: None,
+        };
+        let lines = self.build_lines(uow, &transfer, input.lines).await?;
+
+        let transfer = self
+            .repo
+            .create_transfer(uow, transfer)
+            .await
+            .map_err(map_transfer_write_error)?;
+        let lines = self
+            .repo
+            .replace_transfer_lines(uow, &transfer.id, lines)
+            .await
+            .map_err(map_transfer_write_error)?;
+
+        Ok(TransferDetail { transfer, lines })
+    }
+
+    async fn update_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: TransferInput,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let transfer = self.lock_transfer(uow, &id).await?;
+        if transfer.status != TransferStatus::Draft {
+            return Err(TransferUsecaseError::InvalidStatus(transfer.status));
+        }
+        self.check_transfer_number(uow, &transfer.company_id, &input.number, Some(&id))
+            .await?;
+        self.check_warehouses(uow, &transfer.company_id, &input)
+            .await?;
+
+        let transfer = StockTransfer {
+            number: input.number,
+            source_warehouse_id: input.source_warehouse_id,
+            destination_warehouse_id: input.destination_warehouse_id,
+            note: input.note,
+            ..transfer
+        };
+        let lines = self.build_lines(uow, &transfer, input.lines).await?;
+
+        let transfer = self
+            .repo
+            .update_transfer(uow, transfer)
+            .await
+            .map_err(map_transfer_write_error)?;
+        let lines = self
+            .repo
+            .replace_transfer_lines(uow, &transfer.id, lines)
+            .await
+            .map_err(map_transfer_write_error)?;
+
+        Ok(TransferDetail { transfer, lines })
+    }
+
+    async fn get_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<TransferDetail, TransferUsecaseError> {
+        let transfer = self
+            .repo
+            .get_transfer_by_id(uow, &id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?
+            .ok_or(TransferUsecaseError::NotFound)?;
+        let lines = self.find_lines(uow, &id).await?;
+
+        Ok(TransferDetail { transfer, lines })
+    }
+
+    async fn list_transfers_in(
+        &self,
+        uow: &mut R::Uow,
+        filter: &StockTransferFilter,
+        query: &PaginationRequest,
+    ) -> Result<ListStockTransferResult, TransferUsecaseError> {
+        self.check_company(uow, &filter.company_id).await?;
+
+        let total_transfer = self
+            .repo
+            .count_transfers(uow, filter, query)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+
+        let transfers = self
+            .repo
+            .find_transfers(uow, filter, query)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+
+        Ok(ListStockTransferResult {
+            data: transfers,
+            total_data: total_transfer,
+        })
+    }
+
+    async fn list_in_transit_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<Vec<InTransitQuantity>, TransferUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        self.repo
+            .find_in_transit(uow, company_id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)
+    }
+
+    async fn dispatch_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<(TransferDetail, Vec<StockMovement>), TransferUsecaseError> {
+        let transfer = self.lock_transfer(uow, &id).await?;
+        if transfer.status != TransferStatus::Draft {
+            return Err(TransferUsecaseError::InvalidStatus(transfer.status));
+        }
+        let lines = self.find_lines(uow, &id).await?;
+        if lines.is_empty() {
+            return Err(TransferUsecaseError::NoLines);
+        }
+
+        let postings = lines
+            .iter()
+            .map(|line| LinePosting {
+                product_id: line.product_id,
+                location_id: line.source_location_id,
+                lot_id: line.lot_id,
+                movement_type: MovementType::TransferOut,
+                quantity: -line.quantity,
+                serial_numbers: line.serial_numbers.clone(),
+            })
+            .collect();
+        let movements = self.post_all(uow, &transfer, postings, posted_by).await?;
+
+        let transfer = StockTransfer {
+            status: TransferStatus::Dispatched,
+            dispatched_at: Some(Utc::now()),
+            ..transfer
+        };
+        let transfer = self
+            .repo
+            .update_transfer(uow, transfer)
+            .await
+            .map_err(map_transfer_write_error)?;
+
+        Ok((TransferDetail { transfer, lines }, movements))
+    }
+
+    async fn receive_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        receipts: Vec<ReceiptLineInput>,
+        posted_by: Uuid,
+    ) -> Result<(TransferDetail, Vec<StockMovement>), TransferUsecaseError> {
+        let transfer = self.lock_transfer(uow, &id).await?;
+        if !transfer.status.is_in_transit() {
+            return Err(TransferUsecaseError::InvalidStatus(transfer.status));
+        }
+        if receipts.is_empty() {
+            return Err(TransferUsecaseError::NoLines);
+        }
+        let mut lines = self.find_lines(uow, &id).await?;
+
+        // the same line may be listed more than once, it is received in total
+        let mut received: HashMap<Uuid, i64> = HashMap::new();
+        for receipt in &receipts {
+            if receipt.quantity <= 0 {
+                return Err(TransferUsecaseError::InvalidQuantity);
+            }
+            if !lines.iter().any(|line| line.id == receipt.line_id) {
+                return Err(TransferUsecaseError::LineNotFound);
+            }
+            let total = received.entry(receipt.line_id).or_default();
+            *total = total
+                .checked_add(receipt.quantity)
+                .ok_or(TransferUsecaseError::InvalidQuantity)?;
+        }
+
+        let mut postings = Vec::new();
+        for line in lines.iter_mut() {
+            let Some(quantity) = received.get(&line.id).copied() else {
+                continue;
+            };
+            if quantity > line.open_quantity() {
+                return Err(TransferUsecaseError::ReceiptExceedsOpen);
+            }
+            let serial_numbers = arriving_serials(line, quantity);
+            line.received_quantity += quantity;
+            self.repo
+                .update_received_quantity(uow, &line.id, line.received_quantity)
+                .await
+                .map_err(TransferUsecaseError::DatabaseError)?;
+            postings.push(LinePosting {
+                product_id: line.product_id,
+                location_id: line.destination_location_id,
+                lot_id: line.lot_id,
+                movement_type: MovementType::TransferIn,
+                quantity,
+                serial_numbers,
+            });
+        }
+        let movements = self.post_all(uow, &transfer, postings, posted_by).await?;
+
+        let is_complete = lines.iter().all(|line| line.open_quantity() == 0);
+        let transfer = if is_complete {
+            StockTransfer {
+                status: TransferStatus::Received,
+                received_at: Some(Utc::now()),
+                ..transfer
+            }
+        } else {
+            StockTransfer {
+                status: TransferStatus::PartiallyReceived,
+                ..transfer
+            }
+        };
+        let transfer = self
+            .repo
+            .update_transfer(uow, transfer)
+            .await
+            .map_err(map_transfer_write_error)?;
+
+        Ok((TransferDetail { transfer, lines }, movements))
+    }
+
+    async fn cancel_transfer_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        posted_by: Uuid,
+    ) -> Result<(TransferDetail, Vec<StockMovement>), TransferUsecaseError> {
+        let transfer = self.lock_transfer(uow, &id).await?;
+        let lines = self.find_lines(uow, &id).await?;
+
+        let movements = match transfer.status {
+            TransferStatus::Draft => Vec::new(),
+            TransferStatus::Dispatched | TransferStatus::PartiallyReceived => {
+                let postings = lines
+                    .iter()
+                    .filter(|line| line.open_quantity() > 0)
+                    .map(|line| LinePosting {
+                        product_id: line.product_id,
+                        location_id: line.source_location_id,
+                        lot_id: line.lot_id,
+                        movement_type: MovementType::TransferIn,
+                        quantity: line.open_quantity(),
+                        serial_numbers: arriving_serials(line, line.open_quantity()),
+                    })
+                    .collect();
+                self.post_all(uow, &transfer, postings, posted_by).await?
+            }
+            TransferStatus::Received | TransferStatus::Cancelled => {
+                return Err(TransferUsecaseError::InvalidStatus(transfer.status));
+            }
+        };
+
+        let transfer = StockTransfer {
+            status: TransferStatus::Cancelled,
+            cancelled_at: Some(Utc::now()),
+            ..transfer
+        };
+        let transfer = self
+            .repo
+            .update_transfer(uow, transfer)
+            .await
+            .map_err(map_transfer_write_error)?;
+
+        Ok((TransferDetail { transfer, lines }, movements))
+    }
+
+    // one movement per posting. balances are locked in product, location and
+    // lot order so two transfers over the same products can not deadlock each
+    // other
+    async fn post_all(
+        &self,
+        uow: &mut R::Uow,
+        transfer: &StockTransfer,
+        mut postings: Vec<LinePosting>,
+        posted_by: Uuid,
+    ) -> Result<Vec<StockMovement>, TransferUsecaseError> {
+        postings.sort_by_key(|p| (p.product_id, p.location_id, p.lot_id));
+
+        let mut movements = Vec::with_capacity(postings.len());
+        for line in postings {
+            let product_id = line.product_id;
+            let posting = Posting {
+                company_id: transfer.company_id,
+                product_id,
+                location_id: line.location_id,
+                movement_type: line.movement_type,
+                lot_id: line.lot_id,
+                quantity: line.quantity,
+                serial_numbers: line.serial_numbers,
+                reversal_of: None,
+                source: MovementSource::Transfer,
+                source_id: Some(transfer.id),
+                reference: Some(transfer.number.clone()),
+                note: None,
+                posted_by,
+            };
+            let movement = post_in(&self.repo, uow, posting)
+                .await
+                .map_err(|err| map_posting_error(err, product_id))?;
+            movements.push(movement);
+        }
+
+        Ok(movements)
+    }
+
+    async fn build_lines(
+        &self,
+        uow: &mut R::Uow,
+        transfer: &StockTransfer,
+        inputs: Vec<TransferLineInput>,
+    ) -> Result<Vec<TransferLine>, TransferUsecaseError> {
+        let mut lines = Vec::with_capacity(inputs.len());
+        for (position, input) in (1..).zip(inputs) {
+            if input.quantity <= 0 {
+                return Err(TransferUsecaseError::InvalidQuantity);
+            }
+            let product_company = self
+                .repo
+                .get_product_company(uow, &input.product_id)
+                .await
+                .map_err(TransferUsecaseError::DatabaseError)?
+                .ok_or(TransferUsecaseError::ProductNotFound)?;
+            if product_company != transfer.company_id {
+                return Err(TransferUsecaseError::CompanyMismatch);
+            }
+            self.check_location(
+                uow,
+                &input.source_location_id,
+                &transfer.source_warehouse_id,
+            )
+            .await?;
+            self.check_location(
+                uow,
+                &input.destination_location_id,
+                &transfer.destination_warehouse_id,
+            )
+            .await?;
+            check_lot_in(&self.repo, uow, &input.product_id, input.lot_id.as_ref())
+                .await
+                .map_err(|err| map_posting_error(err, input.product_id))?;
+            // the serials have to be at the source when the draft is saved,
+            // the dispatch checks them again
+            check_serials_in(
+                &self.repo,
+                uow,
+                &input.product_id,
+                &input.source_location_id,
+                -input.quantity,
+                &input.serial_numbers,
+            )
+            .await
+            .map_err(|err| map_posting_error(err, input.product_id))?;
+
+            lines.push(TransferLine {
+                id: Uuid::new_v4(),
+                transfer_id: transfer.id,
+                position,
+                product_id: input.product_id,
+                source_location_id: input.source_location_id,
+                destination_location_id: input.destination_location_id,
+                lot_id: input.lot_id,
+                quantity: input.quantity,
+                received_quantity: 0,
+                serial_numbers: input
+                    .serial_numbers
+                    .iter()
+                    .map(|number| number.trim().to_string())
+                    .collect(),
+            });
+        }
+
+        Ok(lines)
+    }
+
+    async fn check_location(
+        &self,
+        uow: &mut R::Uow,
+        location_id: &Uuid,
+        warehouse_id: &Uuid,
+    ) -> Result<(), TransferUsecaseError> {
+        let location_warehouse = self
+            .repo
+            .get_location_warehouse(uow, location_id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+        if location_warehouse != Some(*warehouse_id) {
+            return Err(TransferUsecaseError::LocationNotFound);
+        }
+
+        Ok(())
+    }
+
+    // both warehouses must belong to the company of the transfer
+    async fn check_warehouses(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        input: &TransferInput,
+    ) -> Result<(), TransferUsecaseError> {
+        if input.source_warehouse_id == input.destination_warehouse_id {
+            return Err(TransferUsecaseError::SameWarehouse);
+        }
+        for warehouse_id in [&input.source_warehouse_id, &input.destination_warehouse_id] {
+            let warehouse_company = self
+                .repo
+                .get_warehouse_company(uow, warehouse_id)
+                .await
+                .map_err(TransferUsecaseError::DatabaseError)?
+                .ok_or(TransferUsecaseError::WarehouseNotFound)?;
+            if warehouse_company != *company_id {
+                return Err(TransferUsecaseError::CompanyMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_transfer_number(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        number: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), TransferUsecaseError> {
+        let is_number_exist = self
+            .repo
+            .check_existing_transfer_number(uow, company_id, number, id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+        if is_number_exist {
+            return Err(TransferUsecaseError::NumberAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), TransferUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(TransferUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn lock_transfer(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<StockTransfer, TransferUsecaseError> {
+        self.repo
+            .lock_transfer(uow, id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)?
+            .ok_or(TransferUsecaseError::NotFound)
+    }
+
+    async fn find_lines(
+        &self,
+        uow: &mut R::Uow,
+        transfer_id: &Uuid,
+    ) -> Result<Vec<TransferLine>, TransferUsecaseError> {
+        self.repo
+            .find_transfer_lines(uow, transfer_id)
+            .await
+            .map_err(TransferUsecaseError::DatabaseError)
+    }
+}
+
+// a movement a transfer posts for one of its lines
+struct LinePosting {
+    product_id: Uuid,
+    location_id: Uuid,
+    lot_id: Option<Uuid>,
+    movement_type: MovementType,
+    quantity: i64,
+    serial_numbers: Vec<String>,
+}
+
+// serials of a line arrive in the order they were listed, the next quantity
+// of them follows the ones already received
+fn arriving_serials(line: &TransferLine, quantity: i64) -> Vec<String> {
+    line.serial_numbers
+        .iter()
+        .skip(line.received_quantity as usize)
+        .take(quantity as usize)
+        .cloned()
+        .collect()
+}
+
+fn map_posting_error(err: StockUsecaseError, product_id: Uuid) -> TransferUsecaseError {
+    match err {
+        StockUsecaseError::InsufficientStock { available } => {
+            TransferUsecaseError::InsufficientStock {
+                product_id,
+                available,
+            }
+        }
+        StockUsecaseError::DatabaseError(err) => TransferUsecaseError::DatabaseError(err),
+        err => TransferUsecaseError::Posting(err),
+    }
+}
+
+// a concurrent unit of work can still claim the number or delete a warehouse,
+// product or location between the check and the write, the constraints catch that
+fn map_transfer_write_error(err: sqlx::Error) -> TransferUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(TRANSFER_NUMBER_CONSTRAINT) => TransferUsecaseError::NumberAlreadyExist,
+        Some(TRANSFER_COMPANY_CONSTRAINT) => TransferUsecaseError::CompanyNotFound,
+        Some(TRANSFER_SOURCE_CONSTRAINT | TRANSFER_DESTINATION_CONSTRAINT) => {
+            TransferUsecaseError::WarehouseNotFound
+        }
+        Some(LINE_PRODUCT_CONSTRAINT) => TransferUsecaseError::ProductNotFound,
+        Some(LINE_SOURCE_CONSTRAINT | LINE_DESTINATION_CONSTRAINT) => {
+            TransferUsecaseError::LocationNotFound
+        }
+        _ => TransferUsecaseError::DatabaseError(err),
+    }
+}
@@ -0,0 +1,599 @@
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::domain::company::Company;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::product::domain::product::Product;
+use crate::product::repository::product_repository_memory::ProductRepositoryMemory;
+use crate::stock::domain::movement::{MovementSource, MovementType, StockMovementFilter};
+use crate::stock::domain::transfer::{StockTransferFilter, TransferStatus};
+use crate::stock::repository::stock_repository_memory::StockRepositoryMemory;
+use crate::stock::usecase::dto::{
+    MovementInput, ReceiptLineInput, TransferInput, TransferLineInput,
+};
+use crate::stock::usecase::stock_usecase::{StockUsecase, StockUsecaseError};
+use crate::stock::usecase::transfer_usecase::{TransferUsecase, TransferUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+fn product(company: &Company, sku: &str) -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        company_id: company.id,
+        sku: sku.into(),
+        name: sku.to_lowercase(),
+        description: None,
+        category: None,
+        unit_of_measure: "pcs".into(),
+        barcode: None,
+        is_active: true,
+        is_lot_tracked: false,
+        is_serialised: false,
+        created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+// a warehouse with a single bin
+async fn bin(warehouses: &WarehouseRepositoryMemory, company: &Company, code: &str) -> Location {
+    let usecase = WarehouseUsecase::new(warehouses.clone(), AppMetrics::new());
+    let warehouse = usecase
+        .create_warehouse(
+            company.id,
+            WarehouseInput {
+                code: code.into(),
+                name: code.to_lowercase(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    usecase
+        .create_location(
+            warehouse.id,
+            LocationInput {
+                parent_id: None,
+                code: "A-01".into(),
+                name: "Bin A-01".into(),
+                kind: LocationKind::Bin,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+fn query() -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: None,
+        sort: None,
+    }
+}
+
+struct Fixture {
+    usecase: TransferUsecase<StockRepositoryMemory>,
+    stock: StockUsecase<StockRepositoryMemory>,
+    acme: Company,
+    bolt: Product,
+    nut: Product,
+    gear: Product,
+    north: Location,
+    south: Location,
+    globex_bin: Location,
+    user: Uuid,
+}
+
+impl Fixture {
+    fn transfer(&self, number: &str, lines: &[(&Product, i64)]) -> TransferInput {
+        TransferInput {
+            number: number.into(),
+            source_warehouse_id: self.north.warehouse_id,
+            destination_warehouse_id: self.south.warehouse_id,
+            note: None,
+            lines: lines
+                .iter()
+                .map(|(product, quantity)| TransferLineInput {
+                    product_id: product.id,
+                    source_location_id: self.north.id,
+                    destination_location_id: self.south.id,
+                    lot_id: None,
+                    quantity: *quantity,
+                    serial_numbers: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    async fn on_hand(&self, product: &Product, location: &Location) -> i64 {
+        let filter = StockMovementFilter {
+            company_id: product.company_id,
+            product_id: Some(product.id),
+            location_id: Some(location.id),
+            movement_type: None,
+            lot_id: None,
+        };
+        let result = self.stock.list_movements(&filter, &query()).await.unwrap();
+        result.data.iter().map(|movement| movement.quantity).sum()
+    }
+}
+
+// ACM has bolts and nuts in its north warehouse and an empty south one, GLB
+// has a warehouse of its own
+async fn setup() -> Fixture {
+    let acme = company("ACM");
+    let globex = company("GLB");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone(), globex.clone()]);
+    let bolt = product(&acme, "BOLT");
+    let nut = product(&acme, "NUT");
+    let gear = product(&globex, "GEAR");
+    let products = ProductRepositoryMemory::with_products(
+        companies.clone(),
+        vec![bolt.clone(), nut.clone(), gear.clone()],
+    );
+    let warehouses = WarehouseRepositoryMemory::new(companies.clone());
+    let north = bin(&warehouses, &acme, "NORTH").await;
+    let south = bin(&warehouses, &acme, "SOUTH").await;
+    let globex_bin = bin(&warehouses, &globex, "MAIN").await;
+    let repo = StockRepositoryMemory::new(companies, products, warehouses);
+    let stock = StockUsecase::new(repo.clone(), AppMetrics::new());
+    let user = Uuid::new_v4();
+    for (product, quantity) in [(&bolt, 10), (&nut, 5)] {
+        stock
+            .post_movement(
+                MovementInput {
+                    product_id: product.id,
+                    location_id: north.id,
+                    movement_type: MovementType::Receipt,
+                    lot_id: None,
+                    quantity,
+                    serial_numbers: Vec::new(),
+                    reference: None,
+                    note: None,
+                },
+                user,
+            )
+            .await
+            .unwrap();
+    }
+    Fixture {
+        usecase: TransferUsecase::new(repo, AppMetrics::new()),
+        stock,
+        acme,
+        bolt,
+        nut,
+        gear,
+        north,
+        south,
+        globex_bin,
+        user,
+    }
+}
+
+#[tokio::test]
+async fn transfer_moves_stock_through_transit() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_transfer(
+            f.acme.id,
+            f.transfer("TR-1", &[(&f.bolt, 6), (&f.nut, 5)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(draft.transfer.status, TransferStatus::Draft);
+    assert_eq!(draft.transfer.created_by, f.user);
+    let positions: Vec<i32> = draft.lines.iter().map(|line| line.position).collect();
+    assert_eq!(positions, [1, 2]);
+    // nothing moves while it is a draft
+    assert_eq!(f.on_hand(&f.bolt, &f.north).await, 10);
+
+    let dispatched = f
+        .usecase
+        .dispatch_transfer(draft.transfer.id, f.user)
+        .await
+        .unwrap();
+    assert_eq!(dispatched.transfer.status, TransferStatus::Dispatched);
+    assert!(dispatched.transfer.dispatched_at.is_some());
+    assert_eq!(f.on_hand(&f.bolt, &f.north).await, 4);
+    assert_eq!(f.on_hand(&f.nut, &f.north).await, 0);
+    assert_eq!(f.on_hand(&f.bolt, &f.south).await, 0);
+
+    let in_transit = f.usecase.list_in_transit(f.acme.id).await.unwrap();
+    assert_eq!(in_transit.len(), 2);
+    assert!(in_transit.iter().all(|row| {
+        row.source_warehouse_id == f.north.warehouse_id
+            && row.destination_warehouse_id == f.south.warehouse_id
+    }));
+
+    let bolt_line = dispatched.lines[0].id;
+    let nut_line = dispatched.lines[1].id;
+    let partial = f
+        .usecase
+        .receive_transfer(
+            draft.transfer.id,
+            vec![
+                ReceiptLineInput {
+                    line_id: bolt_line,
+                    quantity: 2,
+                },
+                ReceiptLineInput {
+                    line_id: bolt_line,
+                    quantity: 2,
+                },
+            ],
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(partial.transfer.status, TransferStatus::PartiallyReceived);
+    assert!(partial.transfer.received_at.is_none());
+    assert_eq!(partial.lines[0].received_quantity, 4);
+    assert_eq!(f.on_hand(&f.bolt, &f.south).await, 4);
+
+    let in_transit = f.usecase.list_in_transit(f.acme.id).await.unwrap();
+    let open: Vec<(Uuid, i64)> = in_transit
+        .iter()
+        .map(|row| (row.product_id, row.quantity))
+        .collect();
+    let mut expected = vec![(f.bolt.id, 2), (f.nut.id, 5)];
+    expected.sort();
+    assert_eq!(open, expected);
+
+    let received = f
+        .usecase
+        .receive_transfer(
+            draft.transfer.id,
+            vec![
+                ReceiptLineInput {
+                    line_id: bolt_line,
+                    quantity: 2,
+                },
+                ReceiptLineInput {
+                    line_id: nut_line,
+                    quantity: 5,
+                },
+            ],
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(received.transfer.status, TransferStatus::Received);
+    assert!(received.transfer.received_at.is_some());
+    assert_eq!(f.on_hand(&f.bolt, &f.south).await, 6);
+    assert_eq!(f.on_hand(&f.nut, &f.south).await, 5);
+    assert!(
+        f.usecase
+            .list_in_transit(f.acme.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // the ledger carries the transfer number on every movement, and the
+    // movements are only corrected through the transfer
+    let filter = StockMovementFilter {
+        company_id: f.acme.id,
+        product_id: None,
+        location_id: None,
+        movement_type: None,
+        lot_id: None,
+    };
+    let movements = f.stock.list_movements(&filter, &query()).await.unwrap();
+    assert_eq!(movements.total_data, 7);
+    let referenced: Vec<_> = movements
+        .data
+        .iter()
+        .filter(|movement| movement.reference.as_deref() == Some("TR-1"))
+        .collect();
+    assert_eq!(referenced.len(), 5);
+    assert!(referenced.iter().all(|movement| {
+        movement.source == MovementSource::Transfer && movement.source_id == Some(draft.transfer.id)
+    }));
+    let result = f
+        .stock
+        .reverse_movement(referenced[0].id, None, f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(StockUsecaseError::DocumentMovement(
+            MovementSource::Transfer
+        ))
+    ));
+
+    let result = f.usecase.cancel_transfer(draft.transfer.id, f.user).await;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::InvalidStatus(
+            TransferStatus::Received
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn receive_rejects_more_than_in_transit() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-1", &[(&f.bolt, 3)]), f.user)
+        .await
+        .unwrap();
+    let line_id = draft.lines[0].id;
+    let receipt = |quantity| vec![ReceiptLineInput { line_id, quantity }];
+
+    let result = f
+        .usecase
+        .receive_transfer(draft.transfer.id, receipt(1), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::InvalidStatus(TransferStatus::Draft))
+    ));
+
+    f.usecase
+        .dispatch_transfer(draft.transfer.id, f.user)
+        .await
+        .unwrap();
+    let cases = [
+        (receipt(4), "over the line"),
+        (receipt(0), "zero"),
+        (
+            vec![ReceiptLineInput {
+                line_id: Uuid::new_v4(),
+                quantity: 1,
+            }],
+            "unknown line",
+        ),
+        (vec![], "no lines"),
+    ];
+    for (lines, case) in cases {
+        let result = f
+            .usecase
+            .receive_transfer(draft.transfer.id, lines, f.user)
+            .await;
+        assert!(
+            matches!(
+                result,
+                Err(TransferUsecaseError::ReceiptExceedsOpen
+                    | TransferUsecaseError::InvalidQuantity
+                    | TransferUsecaseError::LineNotFound
+                    | TransferUsecaseError::NoLines)
+            ),
+            "{case}"
+        );
+    }
+    assert_eq!(f.on_hand(&f.bolt, &f.south).await, 0);
+
+    let result = f.usecase.dispatch_transfer(draft.transfer.id, f.user).await;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::InvalidStatus(
+            TransferStatus::Dispatched
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn cancel_returns_open_quantity_to_source() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-1", &[(&f.bolt, 8)]), f.user)
+        .await
+        .unwrap();
+    f.usecase
+        .dispatch_transfer(draft.transfer.id, f.user)
+        .await
+        .unwrap();
+    f.usecase
+        .receive_transfer(
+            draft.transfer.id,
+            vec![ReceiptLineInput {
+                line_id: draft.lines[0].id,
+                quantity: 3,
+            }],
+            f.user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(f.on_hand(&f.bolt, &f.north).await, 2);
+
+    let cancelled = f
+        .usecase
+        .cancel_transfer(draft.transfer.id, f.user)
+        .await
+        .unwrap();
+    assert_eq!(cancelled.transfer.status, TransferStatus::Cancelled);
+    assert!(cancelled.transfer.cancelled_at.is_some());
+    // what already arrived stays at the destination
+    assert_eq!(f.on_hand(&f.bolt, &f.north).await, 7);
+    assert_eq!(f.on_hand(&f.bolt, &f.south).await, 3);
+    assert!(
+        f.usecase
+            .list_in_transit(f.acme.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let result = f.usecase.cancel_transfer(draft.transfer.id, f.user).await;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::InvalidStatus(
+            TransferStatus::Cancelled
+        ))
+    ));
+
+    // a draft is cancelled without touching the ledger
+    let draft = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-2", &[(&f.bolt, 1)]), f.user)
+        .await
+        .unwrap();
+    let cancelled = f
+        .usecase
+        .cancel_transfer(draft.transfer.id, f.user)
+        .await
+        .unwrap();
+    assert_eq!(cancelled.transfer.status, TransferStatus::Cancelled);
+    assert_eq!(f.on_hand(&f.bolt, &f.north).await, 7);
+}
+
+#[tokio::test]
+async fn dispatch_refuses_insufficient_stock() {
+    let f = setup().await;
+    let draft = f
+        .usecase
+        .create_transfer(
+            f.acme.id,
+            f.transfer("TR-1", &[(&f.nut, 2), (&f.bolt, 11)]),
+            f.user,
+        )
+        .await
+        .unwrap();
+
+    let result = f.usecase.dispatch_transfer(draft.transfer.id, f.user).await;
+    let bolt_id = f.bolt.id;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::InsufficientStock { product_id, available: 10 }) if product_id == bolt_id
+    ));
+    // the whole dispatch is rolled back
+    assert_eq!(f.on_hand(&f.nut, &f.north).await, 5);
+    let detail = f.usecase.get_transfer(draft.transfer.id).await.unwrap();
+    assert_eq!(detail.transfer.status, TransferStatus::Draft);
+
+    let empty = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-2", &[]), f.user)
+        .await
+        .unwrap();
+    let result = f.usecase.dispatch_transfer(empty.transfer.id, f.user).await;
+    assert!(matches!(result, Err(TransferUsecaseError::NoLines)));
+}
+
+#[tokio::test]
+async fn create_validates_company_of_warehouses_and_lines() {
+    let f = setup().await;
+
+    let mut same = f.transfer("TR-1", &[(&f.bolt, 1)]);
+    same.destination_warehouse_id = f.north.warehouse_id;
+    let mut foreign_warehouse = f.transfer("TR-1", &[(&f.bolt, 1)]);
+    foreign_warehouse.destination_warehouse_id = f.globex_bin.warehouse_id;
+    let mut missing_warehouse = f.transfer("TR-1", &[(&f.bolt, 1)]);
+    missing_warehouse.source_warehouse_id = Uuid::new_v4();
+    let foreign_product = f.transfer("TR-1", &[(&f.gear, 1)]);
+    let mut missing_product = f.transfer("TR-1", &[(&f.bolt, 1)]);
+    missing_product.lines[0].product_id = Uuid::new_v4();
+    // the source bin of the line is not in the source warehouse
+    let mut wrong_location = f.transfer("TR-1", &[(&f.bolt, 1)]);
+    wrong_location.lines[0].source_location_id = f.south.id;
+    let zero = f.transfer("TR-1", &[(&f.bolt, 0)]);
+
+    let cases = [
+        (same, "same warehouse"),
+        (foreign_warehouse, "foreign warehouse"),
+        (missing_warehouse, "missing warehouse"),
+        (foreign_product, "foreign product"),
+        (missing_product, "missing product"),
+        (wrong_location, "wrong location"),
+        (zero, "zero quantity"),
+    ];
+    let mut errors = Vec::new();
+    for (input, case) in cases {
+        let result = f.usecase.create_transfer(f.acme.id, input, f.user).await;
+        errors.push((case, result.err().map(|err| err.to_string())));
+    }
+    assert_eq!(
+        errors,
+        [
+            (
+                "same warehouse",
+                Some(TransferUsecaseError::SameWarehouse.to_string())
+            ),
+            (
+                "foreign warehouse",
+                Some(TransferUsecaseError::CompanyMismatch.to_string())
+            ),
+            (
+                "missing warehouse",
+                Some(TransferUsecaseError::WarehouseNotFound.to_string())
+            ),
+            (
+                "foreign product",
+                Some(TransferUsecaseError::CompanyMismatch.to_string())
+            ),
+            (
+                "missing product",
+                Some(TransferUsecaseError::ProductNotFound.to_string())
+            ),
+            (
+                "wrong location",
+                Some(TransferUsecaseError::LocationNotFound.to_string())
+            ),
+            (
+                "zero quantity",
+                Some(TransferUsecaseError::InvalidQuantity.to_string())
+            ),
+        ]
+    );
+
+    let result = f
+        .usecase
+        .create_transfer(Uuid::new_v4(), f.transfer("TR-1", &[]), f.user)
+        .await;
+    assert!(matches!(result, Err(TransferUsecaseError::CompanyNotFound)));
+
+    let first = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-1", &[(&f.bolt, 1)]), f.user)
+        .await
+        .unwrap();
+    let result = f
+        .usecase
+        .create_transfer(f.acme.id, f.transfer("TR-1", &[]), f.user)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransferUsecaseError::NumberAlreadyExist)
+    ));
+
+    // a draft takes new lines, keeping its own number
+    let updated = f
+        .usecase
+        .update_transfer(
+            first.transfer.id,
+            f.transfer("TR-1", &[(&f.nut, 2), (&f.bolt, 3)]),
+        )
+        .await
+        .unwrap();
+    let lines: Vec<(Uuid, i64)> = updated
+        .lines
+        .iter()
+        .map(|line| (line.product_id, line.quantity))
+        .collect();
+    assert_eq!(lines, [(f.nut.id, 2), (f.bolt.id, 3)]);
+    let detail = f.usecase.get_transfer(first.transfer.id).await.unwrap();
+    assert_eq!(detail.lines, updated.lines);
+
+    let filter = StockTransferFilter {
+        company_id: f.acme.id,
+        status: Some(TransferStatus::Draft),
+    };
+    let result = f.usecase.list_transfers(&filter, &query()).await.unwrap();
+    assert_eq!(result.total_data, 1);
+    let unknown = StockTransferFilter {
+        company_id: Uuid::new_v4(),
+        status: None,
+    };
+    let result = f.usecase.list_transfers(&unknown, &query()).await;
+    assert!(matches!(result, Err(TransferUsecaseError::CompanyNotFound)));
+    let result = f.usecase.get_transfer(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(TransferUsecaseError::NotFound)));
+}
@@ -0,0 +1,3 @@
+pub mod domain;
+pub mod repository;
+pub mod usecase;
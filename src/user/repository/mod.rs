@@ -0,0 +1,4 @@
+pub mod user_repository;
+pub mod user_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod user_repository_memory;
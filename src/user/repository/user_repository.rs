@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::app_database::unit_of_work::Transactional;
+use crate::user::domain::user::User;
+
+#[async_trait]
+pub trait UserRepository: Transactional {
+    async fn get_user_by_username(&self, uow: &mut Self::Uow, username: &str) -> Result<Option<User>, sqlx::Error>;
+    async fn check_existing_username(&self, uow: &mut Self::Uow, username: &str) -> Result<bool, sqlx::Error>;
+    async fn check_existing_user_email(&self, uow: &mut Self::Uow, email: &str) -> Result<bool, sqlx::Error>;
+    async fn create_user(&self, uow: &mut Self::Uow, user: User) -> Result<User, sqlx::Error>;
+}
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+
+use crate::app_database::memory::{MemoryStore, MemoryUnitOfWork, unique_violation};
+use crate::app_database::unit_of_work::Transactional;
+use crate::user::domain::user::User;
+use crate::user::repository::user_repository::UserRepository;
+
+// mirrors UserRepositorySqlx, including the unique constraints of the users table
+#[derive(Clone, Default)]
+pub struct UserRepositoryMemory {
+    store: MemoryStore<Vec<User>>,
+}
+
+impl UserRepositoryMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_users(users: Vec<User>) -> Self {
+        Self {
+            store: MemoryStore::new(users),
+        }
+    }
+
+    // committed rows, in insertion order
+    pub async fn users(&self) -> Vec<User> {
+        self.store.snapshot().await
+    }
+}
+
+#[async_trait]
+impl Transactional for UserRepositoryMemory {
+    type Uow = MemoryUnitOfWork<Vec<User>>;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        Ok(self.store.begin().await)
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserRepositoryMemory {
+    async fn get_user_by_username(
+        &self,
+        uow: &mut Self::Uow,
+        username: &str,
+    ) -> Result<Option<User>, sqlx::Error> {
+        Ok(uow.state().iter().find(|u| u.username == username).cloned())
+    }
+
+    async fn check_existing_username(
+        &self,
+        uow: &mut Self::Uow,
+        username: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().iter().any(|u| u.username == username))
+    }
+
+    async fn check_existing_user_email(
+        &self,
+        uow: &mut Self::Uow,
+        email: &str,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow.state().iter().any(|u| u.email == email))
+    }
+
+    async fn create_user(&self, uow: &mut Self::Uow, user: User) -> Result<User, sqlx::Error> {
+        let users = uow.state_mut();
+        if users.iter().any(|u| u.id == user.id) {
+            return Err(unique_violation("users_pkey"));
+        }
+        if users.iter().any(|u| u.username == user.username) {
+            return Err(unique_violation("users_username_key"));
+        }
+        if users.iter().any(|u| u.email == user.email) {
+            return Err(unique_violation("users_email_key"));
+        }
+
+        users.push(user.clone());
+        Ok(user)
+    }
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::instrument;
+
+use crate::app_database::unit_of_work::{PgUnitOfWork, Transactional};
+use crate::user::domain::user::{User, UserRole};
+use crate::user::repository::user_repository::UserRepository;
+
+pub struct UserRepositorySqlx {
+    pool: PgPool,
+}
+
+impl UserRepositorySqlx {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Transactional for UserRepositorySqlx {
+    type Uow = PgUnitOfWork;
+
+    #[instrument(level = "debug", skip_all)]
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.pool.begin().await
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(username = %username))]
+    async fn get_user_by_username(
+        &self,
+        uow: &mut Self::Uow,
+        username: &str,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, name, username, email, phone_number, encrypted_password,
+                role AS "role: UserRole", created_at
+            FROM users
+            WHERE username = $1
+            "#,
+            username
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(username = %username))]
+    async fn check_existing_username(
+        &self,
+        uow: &mut Self::Uow,
+        username: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM users WHERE username = $1)"#,
+            username
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn check_existing_user_email(
+        &self,
+        uow: &mut Self::Uow,
+        email: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM users WHERE email = $1)"#,
+            email
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(user_id = %user.id))]
+    async fn create_user(&self, uow: &mut Self::Uow, user: User) -> Result<User, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO users
+            (id, name, username, email, phone_number, encrypted_password, role, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            user.id,
+            user.name,
+            user.username,
+            user.email,
+            user.phone_number,
+            user.encrypted_password,
+            user.role.as_str(),
+            user.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(user)
+    }
+}
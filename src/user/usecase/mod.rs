@@ -0,0 +1,4 @@
+pub mod password;
+pub mod user_usecase;
+#[cfg(test)]
+mod user_usecase_test;
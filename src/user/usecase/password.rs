@@ -0,0 +1,49 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+
+// argon2id with the crate defaults, the parameters are stored in the hash so
+// they can be raised later without invalidating existing passwords
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    hash_password_with(&Argon2::default(), password)
+}
+
+pub fn hash_password_with(
+    argon2: &Argon2,
+    password: &str,
+) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(argon2
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+// hash of a throwaway password with the default parameters. a login without a
+// stored hash is verified against it, so an unknown username costs the same
+// time as a wrong password
+pub(crate) const DUMMY_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$zD33VpNQbSQgiNhHUbj/tA$1SCc27ep3Pd5HEMt+jZDY0QJzI9BxYpgTous9Z8sITA";
+
+// false for a wrong password and for a hash that can not be parsed
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+// verify_password on the blocking pool, argon2 keeps a core busy long enough
+// to stall the other requests of a runtime worker. without a hash the password
+// is checked against DUMMY_HASH and always refused
+pub async fn verify_login(password: String, hash: Option<String>) -> bool {
+    tokio::task::spawn_blocking(move || match hash {
+        Some(hash) => verify_password(&password, &hash),
+        None => {
+            verify_password(&password, DUMMY_HASH);
+            false
+        }
+    })
+    .await
+    .unwrap_or(false)
+}
@@ -0,0 +1,195 @@
+use core::fmt;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::{UnitOfWork, finish};
+use crate::user::domain::user::{User, UserRole};
+use crate::user::repository::user_repository::UserRepository;
+use crate::user::usecase::password::{hash_password, verify_login};
+
+// names of the unique constraints on users, see migrations
+const USER_USERNAME_CONSTRAINT: &str = "users_username_key";
+const USER_EMAIL_CONSTRAINT: &str = "users_email_key";
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+pub struct UserUsecase<R: UserRepository> {
+    repo: R,
+}
+
+pub struct CreateUserInput {
+    pub name: String,
+    pub username: String,
+    pub email: String,
+    pub phone_number: Option<String>,
+    pub role: UserRole,
+    pub password: String,
+}
+
+#[derive(Debug)]
+pub enum UserUsecaseError {
+    Invalid(String),
+    UsernameAlreadyExist,
+    EmailAlreadyExist,
+    // unknown username or wrong password, deliberately not told apart
+    InvalidCredentials,
+    PasswordHash(argon2::password_hash::Error),
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for UserUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserUsecaseError::Invalid(msg) => write!(f, "{msg}"),
+            UserUsecaseError::UsernameAlreadyExist => write!(f, "username already exists"),
+            UserUsecaseError::EmailAlreadyExist => write!(f, "user email already exists"),
+            UserUsecaseError::InvalidCredentials => write!(f, "invalid username or password"),
+            UserUsecaseError::PasswordHash(err) => write!(f, "password hashing failed: {err}"),
+            UserUsecaseError::DatabaseError(_) => write!(f, "user storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for UserUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UserUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: UserRepository> UserUsecase<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    #[instrument(skip_all, fields(username = %input.username, role = %input.role))]
+    pub async fn create_user(&self, input: CreateUserInput) -> Result<User, UserUsecaseError> {
+        validate_user_input(&input)?;
+        let encrypted_password =
+            hash_password(&input.password).map_err(UserUsecaseError::PasswordHash)?;
+
+        let mut uow = self
+            .repo
+            .begin()
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+        let result = self
+            .create_user_in(&mut uow, input, encrypted_password)
+            .await;
+        finish(uow, result, UserUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(username = %username))]
+    pub async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<User, UserUsecaseError> {
+        let mut uow = self
+            .repo
+            .begin()
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+        let user = self
+            .repo
+            .get_user_by_username(&mut uow, username)
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+        uow.rollback()
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+
+        // unknown users and users without a password are verified too, see
+        // verify_login
+        let hash = user.as_ref().and_then(|u| u.encrypted_password.clone());
+        let is_valid = verify_login(password.to_owned(), hash).await;
+        match user {
+            Some(user) if is_valid => Ok(user),
+            _ => Err(UserUsecaseError::InvalidCredentials),
+        }
+    }
+
+    async fn create_user_in(
+        &self,
+        uow: &mut R::Uow,
+        input: CreateUserInput,
+        encrypted_password: String,
+    ) -> Result<User, UserUsecaseError> {
+        let username_exists = self
+            .repo
+            .check_existing_username(uow, &input.username)
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+        if username_exists {
+            return Err(UserUsecaseError::UsernameAlreadyExist);
+        }
+
+        let email_exists = self
+            .repo
+            .check_existing_user_email(uow, &input.email)
+            .await
+            .map_err(UserUsecaseError::DatabaseError)?;
+        if email_exists {
+            return Err(UserUsecaseError::EmailAlreadyExist);
+        }
+
+        let user = User {
+            id: Uuid::new_v4(),
+            name: input.name,
+            username: input.username,
+            email: input.email,
+            phone_number: input.phone_number,
+            encrypted_password: Some(encrypted_password),
+            role: input.role,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_user(uow, user)
+            .await
+            .map_err(map_user_write_error)
+    }
+}
+
+// a concurrent insert can still hit the unique constraints after the checks
+fn map_user_write_error(err: sqlx::Error) -> UserUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .filter(|db_err| db_err.is_unique_violation())
+        .and_then(|db_err| db_err.constraint())
+        .map(str::to_string);
+
+    match constraint.as_deref() {
+        Some(USER_USERNAME_CONSTRAINT) => UserUsecaseError::UsernameAlreadyExist,
+        Some(USER_EMAIL_CONSTRAINT) => UserUsecaseError::EmailAlreadyExist,
+        _ => UserUsecaseError::DatabaseError(err),
+    }
+}
+
+fn validate_user_input(input: &CreateUserInput) -> Result<(), UserUsecaseError> {
+    let invalid = |msg: &str| Err(UserUsecaseError::Invalid(msg.into()));
+
+    if input.name.trim().is_empty() {
+        return invalid("name is required");
+    }
+    if input.username.trim().is_empty() {
+        return invalid("username is required");
+    }
+    if input.username.chars().any(char::is_whitespace) {
+        return invalid("username can not contain spaces");
+    }
+    if !input.email.contains('@') {
+        return invalid("email is invalid");
+    }
+    if input.password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(UserUsecaseError::Invalid(format!(
+            "password must be at least {MIN_PASSWORD_LEN} characters"
+        )));
+    }
+
+    Ok(())
+}
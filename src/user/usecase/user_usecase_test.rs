@@ -0,0 +1,130 @@
+use argon2::PasswordHash;
+
+use crate::app_router::test_app::test_admin;
+use crate::user::domain::user::UserRole;
+use crate::user::repository::user_repository_memory::UserRepositoryMemory;
+use crate::user::usecase::password::{DUMMY_HASH, hash_password, verify_password};
+use crate::user::usecase::user_usecase::{CreateUserInput, UserUsecase, UserUsecaseError};
+
+fn input(username: &str, email: &str) -> CreateUserInput {
+    CreateUserInput {
+        name: format!("User {username}"),
+        username: username.into(),
+        email: email.into(),
+        phone_number: None,
+        role: UserRole::Admin,
+        password: "correct horse".into(),
+    }
+}
+
+fn setup() -> (UserUsecase<UserRepositoryMemory>, UserRepositoryMemory) {
+    let repo = UserRepositoryMemory::with_users(vec![test_admin()]);
+    (UserUsecase::new(repo.clone()), repo)
+}
+
+#[tokio::test]
+async fn create_user_stores_an_argon2_hash() {
+    let (usecase, repo) = setup();
+
+    let user = usecase
+        .create_user(input("budi", "budi@mail.com"))
+        .await
+        .unwrap();
+
+    assert_eq!(user.role, UserRole::Admin);
+    let hash = user.encrypted_password.as_deref().unwrap();
+    assert!(hash.starts_with("$argon2id$"), "{hash}");
+    assert!(verify_password("correct horse", hash));
+    assert_eq!(repo.users().await.len(), 2);
+
+    let signed_in = usecase.authenticate("budi", "correct horse").await.unwrap();
+    assert_eq!(signed_in.id, user.id);
+}
+
+#[tokio::test]
+async fn create_user_rejects_taken_username_and_email() {
+    let (usecase, repo) = setup();
+
+    let taken_username = usecase.create_user(input("admin", "other@mail.com")).await;
+    let taken_email = usecase
+        .create_user(input("other", "admin@inventory.test"))
+        .await;
+
+    assert!(matches!(
+        taken_username,
+        Err(UserUsecaseError::UsernameAlreadyExist)
+    ));
+    assert!(matches!(
+        taken_email,
+        Err(UserUsecaseError::EmailAlreadyExist)
+    ));
+    assert_eq!(repo.users().await.len(), 1);
+}
+
+#[tokio::test]
+async fn create_user_validates_input() {
+    let (usecase, _) = setup();
+    let cases = [
+        (
+            CreateUserInput {
+                name: " ".into(),
+                ..input("a", "a@mail.com")
+            },
+            "name is required",
+        ),
+        (
+            input("with space", "a@mail.com"),
+            "username can not contain spaces",
+        ),
+        (input("a", "not-an-email"), "email is invalid"),
+        (
+            CreateUserInput {
+                password: "short".into(),
+                ..input("a", "a@mail.com")
+            },
+            "password must be at least 8 characters",
+        ),
+    ];
+
+    for (input, message) in cases {
+        match usecase.create_user(input).await {
+            Err(UserUsecaseError::Invalid(msg)) => assert_eq!(msg, message),
+            other => panic!("expected {message:?}, got {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn authenticate_does_not_tell_unknown_users_from_wrong_passwords() {
+    let mut no_password = test_admin();
+    no_password.id = uuid::Uuid::new_v4();
+    no_password.username = "locked".into();
+    no_password.email = "locked@mail.com".into();
+    no_password.encrypted_password = None;
+    let usecase = UserUsecase::new(UserRepositoryMemory::with_users(vec![
+        test_admin(),
+        no_password,
+    ]));
+
+    for (username, password) in [("admin", "wrong"), ("nobody", "password"), ("locked", "")] {
+        let result = usecase.authenticate(username, password).await;
+        assert!(
+            matches!(result, Err(UserUsecaseError::InvalidCredentials)),
+            "{username}: {result:?}"
+        );
+    }
+    assert!(usecase.authenticate("admin", "password").await.is_ok());
+}
+
+// verifying against the dummy hash only costs what a real login does while
+// it shares the parameters of new hashes
+#[test]
+fn dummy_hash_uses_the_parameters_of_new_hashes() {
+    let fresh = hash_password("password").unwrap();
+    let fresh = PasswordHash::new(&fresh).unwrap();
+    let dummy = PasswordHash::new(DUMMY_HASH).unwrap();
+
+    assert_eq!(dummy.algorithm, fresh.algorithm);
+    assert_eq!(dummy.version, fresh.version);
+    assert_eq!(dummy.params, fresh.params);
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// a place inside a warehouse, parent_id None for the top level
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow, ToSchema)]
+pub struct Location {
+    pub id: Uuid,
+    pub warehouse_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub kind: LocationKind,
+    pub created_at: DateTime<Utc>,
+}
+
+// stored as lowercase text in warehouse_locations.kind, nothing enforces an
+// order between kinds, a bin may sit directly in a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum LocationKind {
+    Zone,
+    Aisle,
+    Rack,
+    Shelf,
+    Bin,
+}
+
+impl LocationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocationKind::Zone => "zone",
+            LocationKind::Aisle => "aisle",
+            LocationKind::Rack => "rack",
+            LocationKind::Shelf => "shelf",
+            LocationKind::Bin => "bin",
+        }
+    }
+}
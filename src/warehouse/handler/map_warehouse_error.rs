@@ -0,0 +1,74 @@
+use crate::app_response::error::ResponseError;
+use crate::warehouse::handler::types::{ProcessLocationRequest, ProcessWarehouseRequest};
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecaseError;
+
+pub fn map_usecase_warehouse_error(err: WarehouseUsecaseError) -> ResponseError {
+    match &err {
+        WarehouseUsecaseError::CodeAlreadyExist => {
+            ResponseError::BadRequest("code already exist".into())
+        }
+        WarehouseUsecaseError::CompanyNotFound => {
+            ResponseError::NotFound("company not found".into())
+        }
+        WarehouseUsecaseError::NotFound => ResponseError::NotFound("data not found".into()),
+        WarehouseUsecaseError::InUse => {
+            ResponseError::BadRequest("warehouse is still in use".into())
+        }
+        WarehouseUsecaseError::LocationCodeAlreadyExist => {
+            ResponseError::BadRequest("location code already exist".into())
+        }
+        WarehouseUsecaseError::LocationNotFound => {
+            ResponseError::NotFound("location not found".into())
+        }
+        WarehouseUsecaseError::LocationInUse => {
+            ResponseError::BadRequest("location is still in use".into())
+        }
+        WarehouseUsecaseError::ParentNotFound => {
+            ResponseError::BadRequest("parent location not found".into())
+        }
+        WarehouseUsecaseError::ParentCycle => {
+            ResponseError::BadRequest("location can not be moved below itself".into())
+        }
+        WarehouseUsecaseError::DatabaseError(_) => ResponseError::database(err),
+    }
+}
+
+// column sizes of the warehouses and warehouse_locations tables
+const MAX_WAREHOUSE_CODE_LEN: usize = 20;
+const MAX_LOCATION_CODE_LEN: usize = 30;
+const MAX_NAME_LEN: usize = 100;
+
+pub fn validate_warehouse_input(req: &ProcessWarehouseRequest) -> Result<(), ResponseError> {
+    validate_code_and_name(&req.code, &req.name, MAX_WAREHOUSE_CODE_LEN)
+}
+
+pub fn validate_location_input(req: &ProcessLocationRequest) -> Result<(), ResponseError> {
+    // the code is a segment of the location path
+    if req.code.contains('/') {
+        return Err(ResponseError::BadRequest("Code can not contain /".into()));
+    }
+    validate_code_and_name(&req.code, &req.name, MAX_LOCATION_CODE_LEN)
+}
+
+fn validate_code_and_name(
+    code: &str,
+    name: &str,
+    max_code_len: usize,
+) -> Result<(), ResponseError> {
+    if code.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Code is required".into()));
+    }
+    if name.trim().is_empty() {
+        return Err(ResponseError::BadRequest("Name is required".into()));
+    }
+
+    let lengths = [("Code", code, max_code_len), ("Name", name, MAX_NAME_LEN)];
+    for (field, value, max) in lengths {
+        if value.chars().count() > max {
+            return Err(ResponseError::BadRequest(format!(
+                "{field} can not be longer than {max} characters"
+            )));
+        }
+    }
+    Ok(())
+}
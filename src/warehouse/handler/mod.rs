@@ -0,0 +1,5 @@
+pub mod map_warehouse_error;
+pub mod warehouse_handler;
+#[cfg(test)]
+mod warehouse_handler_test;
+pub mod types;
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::usecase::dto::{LocationInput, LocationNode, WarehouseInput};
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProcessWarehouseRequest {
+    pub code: String,
+    pub name: String,
+    pub address: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ProcessLocationRequest {
+    /// location of the same warehouse this one sits in, none for the top level
+    pub parent_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub kind: LocationKind,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LocationResponse {
+    #[serde(flatten)]
+    pub location: Location,
+    /// codes from the top level down, e.g. A/01/03
+    pub path: String,
+    /// 0 for top level locations
+    pub depth: usize,
+}
+
+impl From<ProcessWarehouseRequest> for WarehouseInput {
+    fn from(req: ProcessWarehouseRequest) -> Self {
+        WarehouseInput {
+            code: req.code,
+            name: req.name,
+            address: req.address,
+        }
+    }
+}
+
+impl From<ProcessLocationRequest> for LocationInput {
+    fn from(req: ProcessLocationRequest) -> Self {
+        LocationInput {
+            parent_id: req.parent_id,
+            code: req.code,
+            name: req.name,
+            kind: req.kind,
+        }
+    }
+}
+
+impl From<LocationNode> for LocationResponse {
+    fn from(node: LocationNode) -> Self {
+        LocationResponse {
+            location: node.location,
+            path: node.path,
+            depth: node.depth,
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+
+use crate::app_config::config::AppConfig;
+use crate::app_request::{
+    pagination::PaginationRequest,
+    path_uuid::{PathUuid, PathUuidPair},
+};
+use crate::app_response::error::{ResponseError, ResponseErrorBody};
+use crate::app_response::success::{ResponseSuccess, ResponseSuccessBody};
+use crate::warehouse::domain::location::Location;
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::handler::map_warehouse_error::{
+    map_usecase_warehouse_error, validate_location_input, validate_warehouse_input,
+};
+use crate::warehouse::handler::types::{
+    LocationResponse, ProcessLocationRequest, ProcessWarehouseRequest,
+};
+use crate::warehouse::repository::helper_query::WAREHOUSE_SORT_COLUMNS;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+
+// order parameter in handler MUST
+// 1. STATE
+// 2. PATH
+// 3. QUERY
+// 4. HEADER / EXTENSION
+// 5. JSON / FORM / MULTIPART
+
+#[utoipa::path(
+    post,
+    path = "/company/{id}/warehouses",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id")),
+    request_body = ProcessWarehouseRequest,
+    responses(
+        (status = 201, description = "warehouse created", body = ResponseSuccessBody<Warehouse>),
+        (status = 400, description = "invalid input or taken code", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn create_warehouse_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+    Json(req): Json<ProcessWarehouseRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_warehouse_input(&req)?;
+
+    let warehouse = usecase
+        .create_warehouse(company_id, req.into())
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Object(
+        StatusCode::CREATED,
+        Some(warehouse),
+    ))
+}
+
+// search matches code or name
+#[utoipa::path(
+    get,
+    path = "/company/{id}/warehouses",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "company id"), PaginationRequest),
+    responses(
+        (status = 200, description = "one page of the company's warehouses, meta holds the totals", body = ResponseSuccessBody<Vec<Warehouse>>),
+        (status = 400, description = "invalid id or unknown sort column", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "company not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_company_warehouses_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(company_id): PathUuid,
+    Query(q): Query<PaginationRequest>,
+    Extension(config): Extension<Arc<AppConfig>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    q.validate_sort(&WAREHOUSE_SORT_COLUMNS)?;
+
+    let page = q.page.unwrap_or(1).max(1);
+    let per_page = config.pagination.per_page(q.per_page);
+
+    let query = PaginationRequest {
+        page: Some(page),
+        per_page: Some(per_page),
+        offset: Some((page - 1) * per_page),
+        search: q.search,
+        sort: q.sort,
+    };
+    let warehouse_list_data = usecase
+        .list_company_warehouses(company_id, &query)
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Pagination(
+        page,
+        per_page,
+        warehouse_list_data.total_data as u64,
+        Some(warehouse_list_data.data),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/warehouse/{id}",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "warehouse id")),
+    responses(
+        (status = 200, description = "the warehouse", body = ResponseSuccessBody<Warehouse>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "warehouse not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_warehouse_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let warehouse = usecase
+        .get_warehouse(id)
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(warehouse)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/warehouse/{id}",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "warehouse id")),
+    request_body = ProcessWarehouseRequest,
+    responses(
+        (status = 200, description = "warehouse updated", body = ResponseSuccessBody<Warehouse>),
+        (status = 400, description = "invalid input or taken code", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "warehouse not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_warehouse_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(id): PathUuid,
+    Json(req): Json<ProcessWarehouseRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_warehouse_input(&req)?;
+
+    let warehouse = usecase
+        .update_warehouse(id, req.into())
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(warehouse)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/warehouse/{id}",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "warehouse id")),
+    responses(
+        (status = 200, description = "warehouse deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or the warehouse still has locations", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "warehouse not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_warehouse_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_warehouse(id)
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
+
+// the whole tree, parents come before their children
+#[utoipa::path(
+    get,
+    path = "/warehouse/{id}/locations",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "warehouse id")),
+    responses(
+        (status = 200, description = "every location of the warehouse", body = ResponseSuccessBody<Vec<LocationResponse>>),
+        (status = 400, description = "invalid id", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "warehouse not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn get_locations_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(warehouse_id): PathUuid,
+) -> Result<impl IntoResponse, ResponseError> {
+    let locations: Vec<LocationResponse> = usecase
+        .list_locations(warehouse_id)
+        .await
+        .map_err(map_usecase_warehouse_error)?
+        .into_iter()
+        .map(LocationResponse::from)
+        .collect();
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(locations)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/warehouse/{id}/locations",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(("id" = Uuid, Path, description = "warehouse id")),
+    request_body = ProcessLocationRequest,
+    responses(
+        (status = 201, description = "location created", body = ResponseSuccessBody<Location>),
+        (status = 400, description = "invalid input, taken code or unknown parent", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "warehouse not found", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn create_location_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuid(warehouse_id): PathUuid,
+    Json(req): Json<ProcessLocationRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_location_input(&req)?;
+
+    let location = usecase
+        .create_location(warehouse_id, req.into())
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::CREATED, Some(location)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/warehouse/{id}/locations/{location_id}",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "warehouse id"),
+        ("location_id" = Uuid, Path, description = "location id"),
+    ),
+    request_body = ProcessLocationRequest,
+    responses(
+        (status = 200, description = "location updated", body = ResponseSuccessBody<Location>),
+        (status = 400, description = "invalid input, taken code, unknown parent or a move below itself", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "location not found in this warehouse", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn update_location_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuidPair(warehouse_id, id): PathUuidPair,
+    Json(req): Json<ProcessLocationRequest>,
+) -> Result<impl IntoResponse, ResponseError> {
+    validate_location_input(&req)?;
+
+    let location = usecase
+        .update_location(warehouse_id, id, req.into())
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::Object(StatusCode::OK, Some(location)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/warehouse/{id}/locations/{location_id}",
+    tag = "warehouse",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "warehouse id"),
+        ("location_id" = Uuid, Path, description = "location id"),
+    ),
+    responses(
+        (status = 200, description = "location deleted", body = ResponseSuccessBody<serde_json::Value>),
+        (status = 400, description = "invalid id or the location still has children", body = ResponseErrorBody),
+        (status = 401, description = "missing or invalid bearer token", body = ResponseErrorBody),
+        (status = 404, description = "location not found in this warehouse", body = ResponseErrorBody),
+        (status = 429, description = "rate limited, see Retry-After", body = ResponseErrorBody),
+        (status = 500, description = "storage or server failure", body = ResponseErrorBody),
+    )
+)]
+pub async fn delete_location_handler<R: WarehouseRepository>(
+    State(usecase): State<Arc<WarehouseUsecase<R>>>,
+    PathUuidPair(warehouse_id, id): PathUuidPair,
+) -> Result<impl IntoResponse, ResponseError> {
+    usecase
+        .delete_location(warehouse_id, id)
+        .await
+        .map_err(map_usecase_warehouse_error)?;
+
+    Ok(ResponseSuccess::NoData::<()>(StatusCode::OK))
+}
@@ -0,0 +1,260 @@
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_router::test_app::{TestApp, error_body};
+use crate::company::domain::company::Company;
+
+fn payload(code: &str) -> Value {
+    json!({
+        "code": code,
+        "name": format!("Warehouse {code}"),
+        "address": "Jakarta",
+    })
+}
+
+fn location_payload(parent_id: Option<&Value>, code: &str, kind: &str) -> Value {
+    json!({
+        "parent_id": parent_id,
+        "code": code,
+        "name": format!("Location {code}"),
+        "kind": kind,
+    })
+}
+
+async fn setup() -> (TestApp, String, Company) {
+    let acme = company("ACM");
+    let app = TestApp::with_companies(vec![acme.clone()]);
+    let token = app.login().await;
+    (app, token, acme)
+}
+
+#[tokio::test]
+async fn warehouse_routes_require_bearer_token() {
+    let app = TestApp::new();
+    let (id, location_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+    for (method, uri) in [
+        (Method::GET, format!("/company/{id}/warehouses")),
+        (Method::POST, format!("/company/{id}/warehouses")),
+        (Method::GET, format!("/warehouse/{id}")),
+        (Method::PUT, format!("/warehouse/{id}")),
+        (Method::DELETE, format!("/warehouse/{id}")),
+        (Method::GET, format!("/warehouse/{id}/locations")),
+        (Method::POST, format!("/warehouse/{id}/locations")),
+        (
+            Method::PUT,
+            format!("/warehouse/{id}/locations/{location_id}"),
+        ),
+        (
+            Method::DELETE,
+            format!("/warehouse/{id}/locations/{location_id}"),
+        ),
+    ] {
+        let response = app.request(method.clone(), &uri, None, None).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn warehouse_crud_round_trip() {
+    let (app, token, acme) = setup().await;
+    let company_uri = format!("/company/{}/warehouses", acme.id);
+
+    let created = app.create(&token, &company_uri, payload("WH-1")).await;
+    assert_eq!(created["company_id"], acme.id.to_string());
+    let uri = format!("/warehouse/{}", created["id"].as_str().unwrap());
+
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"], created);
+
+    let response = app
+        .request(Method::PUT, &uri, Some(&token), Some(payload("WH-0")))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["data"]["code"], "WH-0");
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("{company_uri}?search=wh"),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.json()["meta"]["total_data"], 1);
+
+    let response = app.request(Method::DELETE, &uri, Some(&token), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(response.error_json(), error_body(404, "data not found"));
+}
+
+#[tokio::test]
+async fn create_warehouse_rejects_invalid_input() {
+    let (app, token, acme) = setup().await;
+    let company_uri = format!("/company/{}/warehouses", acme.id);
+    app.create(&token, &company_uri, payload("WH-1")).await;
+
+    let cases = [
+        (company_uri.clone(), payload(" "), 400, "Code is required"),
+        (
+            company_uri.clone(),
+            payload(&"W".repeat(21)),
+            400,
+            "Code can not be longer than 20 characters",
+        ),
+        (
+            company_uri.clone(),
+            payload("WH-1"),
+            400,
+            "code already exist",
+        ),
+        (
+            format!("/company/{}/warehouses", Uuid::new_v4()),
+            payload("WH-2"),
+            404,
+            "company not found",
+        ),
+    ];
+    for (uri, body, status, message) in cases {
+        let response = app
+            .request(Method::POST, &uri, Some(&token), Some(body))
+            .await;
+        assert_eq!(response.status.as_u16(), status, "{message}");
+        assert_eq!(response.error_json(), error_body(status, message));
+    }
+
+    let response = app
+        .request(
+            Method::GET,
+            &format!("{company_uri}?sort=secret"),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(
+        response.error_json(),
+        error_body(400, "Sort by secret is not supported")
+    );
+}
+
+#[tokio::test]
+async fn locations_form_a_tree_per_warehouse() {
+    let (app, token, acme) = setup().await;
+    let company_uri = format!("/company/{}/warehouses", acme.id);
+    let main = app.create(&token, &company_uri, payload("WH-1")).await;
+    let spare = app.create(&token, &company_uri, payload("WH-2")).await;
+    let main_uri = format!("/warehouse/{}", main["id"].as_str().unwrap());
+    let locations_uri = format!("{main_uri}/locations");
+
+    let zone = app
+        .create(&token, &locations_uri, location_payload(None, "A", "zone"))
+        .await;
+    let shelf = app
+        .create(
+            &token,
+            &locations_uri,
+            location_payload(Some(&zone["id"]), "S1", "shelf"),
+        )
+        .await;
+    app.create(
+        &token,
+        &locations_uri,
+        location_payload(Some(&shelf["id"]), "B1", "bin"),
+    )
+    .await;
+
+    let response = app
+        .request(Method::GET, &locations_uri, Some(&token), None)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let data = response.json()["data"].clone();
+    let paths: Vec<(&str, u64, &str)> = data
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| {
+            let path = l["path"].as_str().unwrap();
+            (
+                path,
+                l["depth"].as_u64().unwrap(),
+                l["kind"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            ("A", 0, "zone"),
+            ("A/S1", 1, "shelf"),
+            ("A/S1/B1", 2, "bin")
+        ]
+    );
+
+    let zone_uri = format!("{locations_uri}/{}", zone["id"].as_str().unwrap());
+    let spare_locations_uri = format!("/warehouse/{}/locations", spare["id"].as_str().unwrap());
+    let cases = [
+        (
+            Method::PUT,
+            zone_uri.clone(),
+            Some(location_payload(Some(&shelf["id"]), "A", "zone")),
+            400,
+            "location can not be moved below itself",
+        ),
+        (
+            Method::POST,
+            spare_locations_uri,
+            Some(location_payload(Some(&zone["id"]), "A", "zone")),
+            400,
+            "parent location not found",
+        ),
+        (
+            Method::POST,
+            locations_uri.clone(),
+            Some(location_payload(None, "A/B", "zone")),
+            400,
+            "Code can not contain /",
+        ),
+        (
+            Method::DELETE,
+            zone_uri,
+            None,
+            400,
+            "location is still in use",
+        ),
+        (
+            Method::DELETE,
+            format!("{locations_uri}/{}", Uuid::new_v4()),
+            None,
+            404,
+            "location not found",
+        ),
+        (
+            Method::DELETE,
+            main_uri,
+            None,
+            400,
+            "warehouse is still in use",
+        ),
+    ];
+    for (method, uri, body, status, message) in cases {
+        let response = app.request(method, &uri, Some(&token), body).await;
+        assert_eq!(response.status.as_u16(), status, "{message}");
+        assert_eq!(response.error_json(), error_body(status, message));
+    }
+
+    let response = app
+        .request(
+            Method::POST,
+            &locations_uri,
+            Some(&token),
+            Some(location_payload(None, "Z", "pallet")),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(app.warehouses.tables().await.locations.len(), 3);
+}
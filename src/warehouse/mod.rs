@@ -0,0 +1,5 @@
+pub mod domain;
+pub mod repository;
+pub mod usecase;
+pub mod handler;
+pub mod routes;
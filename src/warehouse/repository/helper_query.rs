@@ -0,0 +1,23 @@
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+// sortable columns of warehouses, sort is pushed into ORDER BY as written so
+// the handler rejects anything else
+pub const WAREHOUSE_SORT_COLUMNS: [&str; 6] =
+    ["id", "company_id", "code", "name", "address", "created_at"];
+
+pub fn apply_warehouse_filter(
+    qb: &mut QueryBuilder<Postgres>,
+    company_id: &Uuid,
+    search: &Option<String>,
+) {
+    qb.push(" WHERE company_id = ").push_bind(*company_id);
+    if let Some(s) = search {
+        qb.push(" AND (")
+            .push(" code ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(" OR name ILIKE ")
+            .push_bind(format!("%{s}%"))
+            .push(")");
+    }
+}
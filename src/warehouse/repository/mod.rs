@@ -0,0 +1,7 @@
+pub mod warehouse_repository;
+pub mod warehouse_repository_sqlx;
+#[cfg(any(test, feature = "testing"))]
+pub mod warehouse_repository_memory;
+#[cfg(test)]
+mod warehouse_repository_contract_test;
+pub mod helper_query;
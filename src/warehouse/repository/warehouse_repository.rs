@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::warehouse::domain::location::Location;
+use crate::warehouse::domain::warehouse::Warehouse;
+
+// warehouses and their locations share one repository so a usecase can check
+// and write both in the same unit of work
+#[async_trait]
+pub trait WarehouseRepository: Transactional {
+    async fn get_warehouse_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Warehouse>, sqlx::Error>;
+    async fn count_company_warehouses(&self, uow: &mut Self::Uow, company_id: &Uuid, query: &PaginationRequest) -> Result<i64, sqlx::Error>;
+    async fn find_company_warehouses(&self, uow: &mut Self::Uow, company_id: &Uuid, query: &PaginationRequest) -> Result<Vec<Warehouse>, sqlx::Error>;
+    async fn check_existing_company(&self, uow: &mut Self::Uow, company_id: &Uuid) -> Result<bool, sqlx::Error>;
+    async fn check_existing_warehouse_code(&self, uow: &mut Self::Uow, company_id: &Uuid, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_warehouse(&self, uow: &mut Self::Uow, warehouse: Warehouse) -> Result<Warehouse, sqlx::Error>;
+    async fn update_warehouse(&self, uow: &mut Self::Uow, warehouse: Warehouse) -> Result<Warehouse, sqlx::Error>;
+    async fn delete_warehouse(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+    // every location of the warehouse, ordered by code
+    async fn find_warehouse_locations(&self, uow: &mut Self::Uow, warehouse_id: &Uuid) -> Result<Vec<Location>, sqlx::Error>;
+    async fn get_location_by_id(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<Option<Location>, sqlx::Error>;
+    async fn check_existing_location_code(&self, uow: &mut Self::Uow, warehouse_id: &Uuid, code: &str, id: Option<&Uuid>) -> Result<bool, sqlx::Error>;
+    async fn create_location(&self, uow: &mut Self::Uow, location: Location) -> Result<Location, sqlx::Error>;
+    async fn update_location(&self, uow: &mut Self::Uow, location: Location) -> Result<Location, sqlx::Error>;
+    async fn delete_location(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error>;
+}
@@ -0,0 +1,454 @@
+// the same scenarios run against the in-memory and the postgres repository, see
+// company_repository_contract_test. both start with the companies ACM and GLB.
+// postgres runs need TEST_DATABASE_URL and are skipped without it.
+use uuid::Uuid;
+
+use crate::app_database::test_database::TestDatabase;
+use crate::app_database::test_seed::{ACME_ID, GLOBEX_ID, at, companies, seed_companies};
+use crate::app_database::unit_of_work::UnitOfWork;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::company::repository::company_repository_sqlx::CompanyRepositorySqlx;
+use crate::company::usecase::company_usecase::{CompanyUsecase, CompanyUsecaseError};
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::repository::warehouse_repository_sqlx::WarehouseRepositorySqlx;
+
+macro_rules! contract_test {
+    ($scenario:ident) => {
+        mod $scenario {
+            #[tokio::test]
+            async fn memory() {
+                let companies = super::CompanyRepositoryMemory::with_companies(super::companies());
+                super::$scenario(&super::WarehouseRepositoryMemory::new(companies)).await;
+            }
+
+            #[tokio::test]
+            async fn sqlx() {
+                let Some(db) = super::TestDatabase::create().await else {
+                    return;
+                };
+                super::seed_companies(&super::CompanyRepositorySqlx::new(db.pool.clone())).await;
+                super::$scenario(&super::WarehouseRepositorySqlx::new(db.pool.clone())).await;
+                db.drop_database().await;
+            }
+        }
+    };
+}
+
+contract_test!(creates_and_checks_warehouses);
+contract_test!(enforces_warehouse_constraints);
+contract_test!(searches_sorts_and_pages_company_warehouses);
+contract_test!(updates_and_deletes_warehouses);
+contract_test!(stores_location_hierarchy);
+contract_test!(enforces_location_constraints);
+
+fn warehouse(company_id: Uuid, code: &str, name: &str, minutes: i64) -> Warehouse {
+    Warehouse {
+        id: Uuid::new_v4(),
+        company_id,
+        code: code.into(),
+        name: name.into(),
+        address: None,
+        created_at: at(minutes),
+    }
+}
+
+fn location(
+    warehouse: &Warehouse,
+    parent: Option<&Location>,
+    code: &str,
+    kind: LocationKind,
+) -> Location {
+    Location {
+        id: Uuid::new_v4(),
+        warehouse_id: warehouse.id,
+        parent_id: parent.map(|p| p.id),
+        code: code.into(),
+        name: format!("Location {code}"),
+        kind,
+        created_at: at(0),
+    }
+}
+
+fn query(
+    search: Option<&str>,
+    sort: Option<&str>,
+    per_page: u32,
+    offset: u32,
+) -> PaginationRequest {
+    PaginationRequest {
+        page: None,
+        per_page: Some(per_page),
+        offset: Some(offset),
+        search: search.map(String::from),
+        sort: sort.map(String::from),
+    }
+}
+
+async fn seed<R: WarehouseRepository>(
+    repo: &R,
+    warehouses: Vec<Warehouse>,
+    locations: Vec<Location>,
+) {
+    let mut uow = repo.begin().await.unwrap();
+    for warehouse in warehouses {
+        repo.create_warehouse(&mut uow, warehouse).await.unwrap();
+    }
+    for location in locations {
+        repo.create_location(&mut uow, location).await.unwrap();
+    }
+    uow.commit().await.unwrap();
+}
+
+fn codes(warehouses: &[Warehouse]) -> Vec<&str> {
+    warehouses.iter().map(|w| w.code.as_str()).collect()
+}
+
+fn violated(err: sqlx::Error) -> Option<String> {
+    err.as_database_error()
+        .and_then(|e| e.constraint().map(String::from))
+}
+
+async fn creates_and_checks_warehouses<R: WarehouseRepository>(repo: &R) {
+    let mut main = warehouse(ACME_ID, "WH-1", "Main", 0);
+    main.address = Some("Jakarta".into());
+    seed(repo, vec![main.clone()], Vec::new()).await;
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_warehouse_by_id(&mut uow, &main.id).await.unwrap(),
+        Some(main.clone())
+    );
+    assert_eq!(
+        repo.get_warehouse_by_id(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap(),
+        None
+    );
+    assert!(
+        repo.check_existing_company(&mut uow, &ACME_ID)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !repo
+            .check_existing_company(&mut uow, &Uuid::new_v4())
+            .await
+            .unwrap()
+    );
+
+    let cases = [
+        (ACME_ID, "WH-1", None, true),
+        (ACME_ID, "WH-1", Some(Uuid::new_v4()), true),
+        (ACME_ID, "WH-1", Some(main.id), false),
+        // per company and case sensitive
+        (GLOBEX_ID, "WH-1", None, false),
+        (ACME_ID, "wh-1", None, false),
+    ];
+    for (company_id, code, id, expected) in cases {
+        let exists = repo
+            .check_existing_warehouse_code(&mut uow, &company_id, code, id.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(exists, expected, "{code} {id:?}");
+    }
+}
+
+async fn enforces_warehouse_constraints<R: WarehouseRepository>(repo: &R) {
+    let main = warehouse(ACME_ID, "WH-1", "Main", 0);
+    let spare = warehouse(ACME_ID, "WH-2", "Spare", 1);
+    seed(repo, vec![main.clone(), spare.clone()], Vec::new()).await;
+    // the same code in another company is fine
+    seed(
+        repo,
+        vec![warehouse(GLOBEX_ID, "WH-1", "Main", 2)],
+        Vec::new(),
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_warehouse(&mut uow, warehouse(ACME_ID, "WH-1", "Copy", 3))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("warehouses_company_id_code_key")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .create_warehouse(&mut uow, warehouse(Uuid::new_v4(), "WH-9", "Orphan", 3))
+        .await
+        .unwrap_err();
+    assert_eq!(violated(err).as_deref(), Some("warehouses_company_id_fkey"));
+    uow.rollback().await.unwrap();
+
+    let mut same_code = spare.clone();
+    same_code.code = main.code.clone();
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .update_warehouse(&mut uow, same_code)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("warehouses_company_id_code_key")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo
+        .update_warehouse(&mut uow, warehouse(ACME_ID, "WH-8", "Ghost", 3))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx::Error::RowNotFound));
+}
+
+async fn searches_sorts_and_pages_company_warehouses<R: WarehouseRepository>(repo: &R) {
+    let mut north = warehouse(ACME_ID, "N-1", "North Hub", 0);
+    north.address = Some("Medan".into());
+    let mut south = warehouse(ACME_ID, "S-1", "South", 1);
+    south.address = Some("Bali".into());
+    seed(
+        repo,
+        vec![
+            north,
+            south,
+            warehouse(ACME_ID, "HUB", "Central", 2),
+            warehouse(GLOBEX_ID, "N-1", "North Hub", 3),
+        ],
+        Vec::new(),
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+
+    // code or name, case insensitive, only the given company
+    let search = query(Some("hub"), Some("code"), 10, 0);
+    assert_eq!(
+        repo.count_company_warehouses(&mut uow, &ACME_ID, &search)
+            .await
+            .unwrap(),
+        2
+    );
+    let found = repo
+        .find_company_warehouses(&mut uow, &ACME_ID, &search)
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["HUB", "N-1"]);
+
+    let found = repo
+        .find_company_warehouses(&mut uow, &ACME_ID, &query(None, Some("-created_at"), 2, 1))
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["S-1", "N-1"]);
+
+    // NULL addresses sort last ascending
+    let found = repo
+        .find_company_warehouses(&mut uow, &ACME_ID, &query(None, Some("address"), 10, 0))
+        .await
+        .unwrap();
+    assert_eq!(codes(&found), vec!["S-1", "N-1", "HUB"]);
+
+    assert_eq!(
+        repo.count_company_warehouses(&mut uow, &Uuid::new_v4(), &query(None, None, 10, 0))
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+async fn updates_and_deletes_warehouses<R: WarehouseRepository>(repo: &R) {
+    let main = warehouse(ACME_ID, "WH-1", "Main", 0);
+    let spare = warehouse(ACME_ID, "WH-2", "Spare", 1);
+    seed(repo, vec![main.clone(), spare.clone()], Vec::new()).await;
+
+    let mut changed = main.clone();
+    changed.code = "WH-0".into();
+    changed.name = "Main Hub".into();
+    changed.address = Some("Bandung".into());
+
+    let mut uow = repo.begin().await.unwrap();
+    repo.update_warehouse(&mut uow, changed.clone())
+        .await
+        .unwrap();
+    repo.delete_warehouse(&mut uow, &spare.id).await.unwrap();
+    // deleting a missing row is not an error
+    repo.delete_warehouse(&mut uow, &Uuid::new_v4())
+        .await
+        .unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_warehouse_by_id(&mut uow, &main.id).await.unwrap(),
+        Some(changed)
+    );
+    assert_eq!(
+        repo.get_warehouse_by_id(&mut uow, &spare.id).await.unwrap(),
+        None
+    );
+}
+
+async fn stores_location_hierarchy<R: WarehouseRepository>(repo: &R) {
+    let main = warehouse(ACME_ID, "WH-1", "Main", 0);
+    let spare = warehouse(ACME_ID, "WH-2", "Spare", 1);
+    let zone = location(&main, None, "B", LocationKind::Zone);
+    let shelf = location(&main, Some(&zone), "B-01", LocationKind::Shelf);
+    let bin = location(&main, Some(&shelf), "A-01", LocationKind::Bin);
+    let other = location(&spare, None, "A", LocationKind::Zone);
+    seed(
+        repo,
+        vec![main.clone(), spare.clone()],
+        vec![zone.clone(), shelf.clone(), bin.clone(), other.clone()],
+    )
+    .await;
+
+    let mut uow = repo.begin().await.unwrap();
+    // flat and ordered by code, the usecase builds the tree
+    let found = repo
+        .find_warehouse_locations(&mut uow, &main.id)
+        .await
+        .unwrap();
+    assert_eq!(found, vec![bin.clone(), zone.clone(), shelf.clone()]);
+    assert_eq!(
+        repo.get_location_by_id(&mut uow, &shelf.id).await.unwrap(),
+        Some(shelf.clone())
+    );
+
+    let cases = [
+        (main.id, "B-01", None, true),
+        (main.id, "B-01", Some(shelf.id), false),
+        (spare.id, "B-01", None, false),
+        (spare.id, "A", Some(zone.id), true),
+    ];
+    for (warehouse_id, code, id, expected) in cases {
+        let exists = repo
+            .check_existing_location_code(&mut uow, &warehouse_id, code, id.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(exists, expected, "{code} {id:?}");
+    }
+
+    // move the bin up to the zone and make it a shelf
+    let mut moved = bin.clone();
+    moved.parent_id = Some(zone.id);
+    moved.kind = LocationKind::Shelf;
+    repo.update_location(&mut uow, moved.clone()).await.unwrap();
+    repo.delete_location(&mut uow, &other.id).await.unwrap();
+    uow.commit().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    assert_eq!(
+        repo.get_location_by_id(&mut uow, &bin.id).await.unwrap(),
+        Some(moved)
+    );
+    assert!(
+        repo.find_warehouse_locations(&mut uow, &spare.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+async fn enforces_location_constraints<R: WarehouseRepository>(repo: &R) {
+    let main = warehouse(ACME_ID, "WH-1", "Main", 0);
+    let spare = warehouse(ACME_ID, "WH-2", "Spare", 1);
+    let zone = location(&main, None, "A", LocationKind::Zone);
+    let bin = location(&main, Some(&zone), "A-01", LocationKind::Bin);
+    let other = location(&spare, None, "X", LocationKind::Zone);
+    seed(
+        repo,
+        vec![main.clone(), spare.clone()],
+        vec![zone.clone(), bin.clone(), other.clone()],
+    )
+    .await;
+
+    let cases = [
+        (
+            location(&main, None, "A", LocationKind::Zone),
+            "warehouse_locations_warehouse_id_code_key",
+        ),
+        (location(&spare, None, "A", LocationKind::Zone), ""),
+        // the parent has to be in the same warehouse
+        (
+            location(&spare, Some(&zone), "A-02", LocationKind::Bin),
+            "warehouse_locations_parent_fkey",
+        ),
+        (
+            location(
+                &warehouse(ACME_ID, "WH-9", "Ghost", 2),
+                None,
+                "A",
+                LocationKind::Zone,
+            ),
+            "warehouse_locations_warehouse_id_fkey",
+        ),
+    ];
+    for (location, constraint) in cases {
+        let code = location.code.clone();
+        let mut uow = repo.begin().await.unwrap();
+        let result = repo.create_location(&mut uow, location).await;
+        match result {
+            Ok(_) => assert_eq!(constraint, "", "{code}"),
+            Err(err) => assert_eq!(violated(err).as_deref(), Some(constraint), "{code}"),
+        }
+        uow.rollback().await.unwrap();
+    }
+
+    let mut moved = bin.clone();
+    moved.parent_id = Some(other.id);
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.update_location(&mut uow, moved).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("warehouse_locations_parent_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    // children and locations keep their parent
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.delete_location(&mut uow, &zone.id).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("warehouse_locations_parent_fkey")
+    );
+    uow.rollback().await.unwrap();
+
+    let mut uow = repo.begin().await.unwrap();
+    let err = repo.delete_warehouse(&mut uow, &main.id).await.unwrap_err();
+    assert_eq!(
+        violated(err).as_deref(),
+        Some("warehouse_locations_warehouse_id_fkey")
+    );
+    uow.rollback().await.unwrap();
+}
+
+// only postgres enforces the foreign key on the company side
+#[tokio::test]
+async fn company_with_warehouses_cannot_be_deleted() {
+    let Some(db) = TestDatabase::create().await else {
+        return;
+    };
+    let company_repo = CompanyRepositorySqlx::new(db.pool.clone());
+    seed_companies(&company_repo).await;
+    seed(
+        &WarehouseRepositorySqlx::new(db.pool.clone()),
+        vec![warehouse(ACME_ID, "WH-1", "Main", 0)],
+        Vec::new(),
+    )
+    .await;
+
+    let usecase = CompanyUsecase::new(company_repo, AppMetrics::new());
+    let result = usecase.delete_company(ACME_ID).await;
+    assert!(matches!(result, Err(CompanyUsecaseError::InUse)));
+    usecase.delete_company(GLOBEX_ID).await.unwrap();
+
+    db.drop_database().await;
+}
@@ -0,0 +1,407 @@
+use std::cmp::Ordering;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::app_database::memory::{
+    MemoryStore, MemoryUnitOfWork, foreign_key_violation, ilike, unique_violation,
+};
+use crate::app_database::unit_of_work::Transactional;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::warehouse::domain::location::Location;
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::helper_query::WAREHOUSE_SORT_COLUMNS;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+
+// both tables in one store, a unit of work sees them together
+#[derive(Debug, Clone, Default)]
+pub struct WarehouseTables {
+    pub warehouses: Vec<Warehouse>,
+    pub locations: Vec<Location>,
+}
+
+// mirrors WarehouseRepositorySqlx on top of vectors, the same way
+// CompanyRepositoryMemory does. the foreign key to companies is checked against
+// the committed rows of the company repository it was built with
+#[derive(Clone, Default)]
+pub struct WarehouseRepositoryMemory {
+    store: MemoryStore<WarehouseTables>,
+    companies: CompanyRepositoryMemory,
+}
+
+impl WarehouseRepositoryMemory {
+    pub fn new(companies: CompanyRepositoryMemory) -> Self {
+        Self {
+            store: MemoryStore::default(),
+            companies,
+        }
+    }
+
+    // committed rows, in insertion order
+    pub async fn tables(&self) -> WarehouseTables {
+        self.store.snapshot().await
+    }
+
+    async fn company_exists(&self, company_id: &Uuid) -> bool {
+        self.companies
+            .companies()
+            .await
+            .iter()
+            .any(|c| c.id == *company_id)
+    }
+}
+
+#[async_trait]
+impl Transactional for WarehouseRepositoryMemory {
+    type Uow = MemoryUnitOfWork<WarehouseTables>;
+
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        Ok(self.store.begin().await)
+    }
+}
+
+#[async_trait]
+impl WarehouseRepository for WarehouseRepositoryMemory {
+    async fn get_warehouse_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Warehouse>, sqlx::Error> {
+        Ok(uow.state().warehouses.iter().find(|w| w.id == *id).cloned())
+    }
+
+    async fn count_company_warehouses(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(search_warehouses(&uow.state().warehouses, company_id, &query.search).len() as i64)
+    }
+
+    async fn find_company_warehouses(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Warehouse>, sqlx::Error> {
+        let warehouses = sorted_warehouses(&uow.state().warehouses, company_id, query)?;
+
+        let offset = query.offset.unwrap_or(0) as usize;
+        let limit = query.per_page.unwrap_or(1) as usize;
+        Ok(warehouses.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn check_existing_company(
+        &self,
+        _uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(self.company_exists(company_id).await)
+    }
+
+    async fn check_existing_warehouse_code(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .warehouses
+            .iter()
+            .any(|w| w.company_id == *company_id && w.code == code && Some(&w.id) != id))
+    }
+
+    async fn create_warehouse(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse: Warehouse,
+    ) -> Result<Warehouse, sqlx::Error> {
+        check_warehouse_constraints(&uow.state().warehouses, &warehouse, true)?;
+        if !self.company_exists(&warehouse.company_id).await {
+            return Err(foreign_key_violation("warehouses_company_id_fkey"));
+        }
+        uow.state_mut().warehouses.push(warehouse.clone());
+
+        Ok(warehouse)
+    }
+
+    async fn update_warehouse(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse: Warehouse,
+    ) -> Result<Warehouse, sqlx::Error> {
+        // UPDATE .. RETURNING fetched with fetch_one fails when no row matched
+        let warehouses = &uow.state().warehouses;
+        if !warehouses.iter().any(|w| w.id == warehouse.id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_warehouse_constraints(warehouses, &warehouse, false)?;
+
+        let existing = uow
+            .state_mut()
+            .warehouses
+            .iter_mut()
+            .find(|w| w.id == warehouse.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        existing.code = warehouse.code.clone();
+        existing.name = warehouse.name.clone();
+        existing.address = warehouse.address.clone();
+
+        Ok(warehouse)
+    }
+
+    async fn delete_warehouse(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        if uow.state().locations.iter().any(|l| l.warehouse_id == *id) {
+            return Err(foreign_key_violation(
+                "warehouse_locations_warehouse_id_fkey",
+            ));
+        }
+        uow.state_mut().warehouses.retain(|w| w.id != *id);
+
+        Ok(())
+    }
+
+    async fn find_warehouse_locations(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Vec<Location>, sqlx::Error> {
+        let mut locations: Vec<Location> = uow
+            .state()
+            .locations
+            .iter()
+            .filter(|l| l.warehouse_id == *warehouse_id)
+            .cloned()
+            .collect();
+        locations.sort_by(|a, b| a.code.cmp(&b.code));
+
+        Ok(locations)
+    }
+
+    async fn get_location_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Location>, sqlx::Error> {
+        Ok(uow.state().locations.iter().find(|l| l.id == *id).cloned())
+    }
+
+    async fn check_existing_location_code(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(uow
+            .state()
+            .locations
+            .iter()
+            .any(|l| l.warehouse_id == *warehouse_id && l.code == code && Some(&l.id) != id))
+    }
+
+    async fn create_location(
+        &self,
+        uow: &mut Self::Uow,
+        location: Location,
+    ) -> Result<Location, sqlx::Error> {
+        check_location_constraints(uow.state(), &location, true)?;
+        uow.state_mut().locations.push(location.clone());
+
+        Ok(location)
+    }
+
+    async fn update_location(
+        &self,
+        uow: &mut Self::Uow,
+        location: Location,
+    ) -> Result<Location, sqlx::Error> {
+        if !uow.state().locations.iter().any(|l| l.id == location.id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        check_location_constraints(uow.state(), &location, false)?;
+
+        let existing = uow
+            .state_mut()
+            .locations
+            .iter_mut()
+            .find(|l| l.id == location.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+        existing.parent_id = location.parent_id;
+        existing.code = location.code.clone();
+        existing.name = location.name.clone();
+        existing.kind = location.kind;
+
+        Ok(location)
+    }
+
+    async fn delete_location(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        if uow
+            .state()
+            .locations
+            .iter()
+            .any(|l| l.parent_id == Some(*id))
+        {
+            return Err(foreign_key_violation("warehouse_locations_parent_fkey"));
+        }
+        uow.state_mut().locations.retain(|l| l.id != *id);
+
+        Ok(())
+    }
+}
+
+fn check_warehouse_constraints(
+    warehouses: &[Warehouse],
+    warehouse: &Warehouse,
+    is_insert: bool,
+) -> Result<(), sqlx::Error> {
+    if is_insert && warehouses.iter().any(|w| w.id == warehouse.id) {
+        return Err(unique_violation("warehouses_pkey"));
+    }
+    let company_id = warehouses
+        .iter()
+        .find(|w| w.id == warehouse.id)
+        .map_or(warehouse.company_id, |w| w.company_id);
+    for other in warehouses.iter().filter(|w| w.id != warehouse.id) {
+        if other.company_id == company_id && other.code == warehouse.code {
+            return Err(unique_violation("warehouses_company_id_code_key"));
+        }
+    }
+    Ok(())
+}
+
+fn check_location_constraints(
+    tables: &WarehouseTables,
+    location: &Location,
+    is_insert: bool,
+) -> Result<(), sqlx::Error> {
+    if is_insert && tables.locations.iter().any(|l| l.id == location.id) {
+        return Err(unique_violation("warehouse_locations_pkey"));
+    }
+    let warehouse_id = tables
+        .locations
+        .iter()
+        .find(|l| l.id == location.id)
+        .map_or(location.warehouse_id, |l| l.warehouse_id);
+    for other in tables.locations.iter().filter(|l| l.id != location.id) {
+        if other.warehouse_id == warehouse_id && other.code == location.code {
+            return Err(unique_violation(
+                "warehouse_locations_warehouse_id_code_key",
+            ));
+        }
+    }
+    if !tables.warehouses.iter().any(|w| w.id == warehouse_id) {
+        return Err(foreign_key_violation(
+            "warehouse_locations_warehouse_id_fkey",
+        ));
+    }
+    // the parent must be a location of the same warehouse
+    if let Some(parent_id) = location.parent_id {
+        let same_warehouse = tables
+            .locations
+            .iter()
+            .any(|l| l.id == parent_id && l.warehouse_id == warehouse_id);
+        if !same_warehouse {
+            return Err(foreign_key_violation("warehouse_locations_parent_fkey"));
+        }
+    }
+    Ok(())
+}
+
+// WHERE company_id = $1 AND (code ILIKE %s% OR name ILIKE %s%), see
+// helper_query::apply_warehouse_filter
+fn search_warehouses<'a>(
+    warehouses: &'a [Warehouse],
+    company_id: &Uuid,
+    search: &Option<String>,
+) -> Vec<&'a Warehouse> {
+    let pattern = search.as_ref().map(|s| format!("%{s}%"));
+    warehouses
+        .iter()
+        .filter(|w| w.company_id == *company_id)
+        .filter(|w| match &pattern {
+            Some(pattern) => ilike(&w.code, pattern) || ilike(&w.name, pattern),
+            None => true,
+        })
+        .collect()
+}
+
+fn sorted_warehouses(
+    warehouses: &[Warehouse],
+    company_id: &Uuid,
+    query: &PaginationRequest,
+) -> Result<Vec<Warehouse>, sqlx::Error> {
+    let keys = sort_keys(query)?;
+    let mut warehouses: Vec<Warehouse> = search_warehouses(warehouses, company_id, &query.search)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    // stable sort, rows equal on every key keep insertion order
+    warehouses.sort_by(|a, b| {
+        keys.iter()
+            .map(|(column, descending)| {
+                let ordering = compare_column(a, b, column);
+                if *descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    Ok(warehouses)
+}
+
+// same parsing as PaginationRequest::format_sort, unknown columns fail like postgres does
+fn sort_keys(query: &PaginationRequest) -> Result<Vec<(String, bool)>, sqlx::Error> {
+    let Some(sort) = &query.sort else {
+        return Ok(Vec::new());
+    };
+
+    let mut keys = Vec::new();
+    for s in sort.split(",") {
+        let s = s.trim();
+        if s.is_empty() {
+            continue;
+        }
+
+        let (column, descending) = match s.strip_prefix("-") {
+            Some(column) => (column, true),
+            None => (s, false),
+        };
+        if !WAREHOUSE_SORT_COLUMNS.contains(&column) {
+            return Err(sqlx::Error::ColumnNotFound(column.to_string()));
+        }
+        keys.push((column.to_string(), descending));
+    }
+    Ok(keys)
+}
+
+fn compare_column(a: &Warehouse, b: &Warehouse, column: &str) -> Ordering {
+    match column {
+        "id" => a.id.cmp(&b.id),
+        "company_id" => a.company_id.cmp(&b.company_id),
+        "code" => a.code.cmp(&b.code),
+        "name" => a.name.cmp(&b.name),
+        "address" => compare_nullable(&a.address, &b.address),
+        "created_at" => a.created_at.cmp(&b.created_at),
+        _ => Ordering::Equal,
+    }
+}
+
+// postgres puts NULL after every value when sorting ascending
+fn compare_nullable(a: &Option<String>, b: &Option<String>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,341 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, QueryBuilder};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::{PgUnitOfWork, Transactional};
+use crate::app_request::pagination::PaginationRequest;
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::helper_query::apply_warehouse_filter;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+
+pub struct WarehouseRepositorySqlx {
+    pool: PgPool,
+}
+
+impl WarehouseRepositorySqlx {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Transactional for WarehouseRepositorySqlx {
+    type Uow = PgUnitOfWork;
+
+    #[instrument(level = "debug", skip_all)]
+    async fn begin(&self) -> Result<Self::Uow, sqlx::Error> {
+        self.pool.begin().await
+    }
+}
+
+#[async_trait]
+impl WarehouseRepository for WarehouseRepositorySqlx {
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %id))]
+    async fn get_warehouse_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Warehouse>, sqlx::Error> {
+        let warehouse = sqlx::query_as!(
+            Warehouse,
+            r#"
+            SELECT id, company_id, code, name, address, created_at
+            FROM warehouses
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(warehouse)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn count_company_warehouses(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(id) FROM warehouses");
+
+        apply_warehouse_filter(&mut qb, company_id, &query.search);
+
+        let total: i64 = qb.build_query_scalar().fetch_one(&mut **uow).await?;
+        Ok(total)
+    }
+
+    #[instrument(
+        level = "debug",
+        skip_all,
+        fields(company_id = %company_id, per_page = ?query.per_page, offset = ?query.offset)
+    )]
+    async fn find_company_warehouses(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<Vec<Warehouse>, sqlx::Error> {
+        let mut qb = QueryBuilder::new(
+            "
+            SELECT id, company_id, code, name, address, created_at
+            FROM warehouses
+        ",
+        );
+
+        apply_warehouse_filter(&mut qb, company_id, &query.search);
+
+        if let Some(s) = query.format_sort() {
+            qb.push(" ORDER BY ").push(s);
+        }
+
+        qb.push(" LIMIT ")
+            .push_bind(query.per_page.unwrap_or(1) as i64);
+
+        if let Some(o) = query.offset {
+            qb.push(" OFFSET ").push_bind(o as i64);
+        }
+
+        let warehouses = qb.build_query_as::<Warehouse>().fetch_all(&mut **uow).await?;
+        Ok(warehouses)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id))]
+    async fn check_existing_company(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM companies WHERE id = $1)"#,
+            company_id
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(company_id = %company_id, code = %code))]
+    async fn check_existing_warehouse_code(
+        &self,
+        uow: &mut Self::Uow,
+        company_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM warehouses
+                WHERE company_id = $1 AND code = $2 AND id IS DISTINCT FROM $3
+            )
+            "#,
+            company_id,
+            code,
+            id as Option<&Uuid>,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse.id))]
+    async fn create_warehouse(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse: Warehouse,
+    ) -> Result<Warehouse, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO warehouses
+            (id, company_id, code, name, address, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            warehouse.id,
+            warehouse.company_id,
+            warehouse.code,
+            warehouse.name,
+            warehouse.address,
+            warehouse.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(warehouse)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse.id))]
+    async fn update_warehouse(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse: Warehouse,
+    ) -> Result<Warehouse, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE warehouses
+            SET code = $1,
+                name = $2,
+                address = $3
+            WHERE id = $4
+            RETURNING id
+            "#,
+            warehouse.code,
+            warehouse.name,
+            warehouse.address,
+            warehouse.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(warehouse)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %id))]
+    async fn delete_warehouse(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM warehouses WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id))]
+    async fn find_warehouse_locations(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Vec<Location>, sqlx::Error> {
+        let locations = sqlx::query_as!(
+            Location,
+            r#"
+            SELECT id, warehouse_id, parent_id, code, name, kind AS "kind: LocationKind",
+                   created_at
+            FROM warehouse_locations
+            WHERE warehouse_id = $1
+            ORDER BY code
+            "#,
+            warehouse_id
+        )
+        .fetch_all(&mut **uow)
+        .await?;
+
+        Ok(locations)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %id))]
+    async fn get_location_by_id(
+        &self,
+        uow: &mut Self::Uow,
+        id: &Uuid,
+    ) -> Result<Option<Location>, sqlx::Error> {
+        let location = sqlx::query_as!(
+            Location,
+            r#"
+            SELECT id, warehouse_id, parent_id, code, name, kind AS "kind: LocationKind",
+                   created_at
+            FROM warehouse_locations
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&mut **uow)
+        .await?;
+
+        Ok(location)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(warehouse_id = %warehouse_id, code = %code))]
+    async fn check_existing_location_code(
+        &self,
+        uow: &mut Self::Uow,
+        warehouse_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let is_exist = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM warehouse_locations
+                WHERE warehouse_id = $1 AND code = $2 AND id IS DISTINCT FROM $3
+            )
+            "#,
+            warehouse_id,
+            code,
+            id as Option<&Uuid>,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(is_exist.unwrap_or(false))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %location.id))]
+    async fn create_location(
+        &self,
+        uow: &mut Self::Uow,
+        location: Location,
+    ) -> Result<Location, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO warehouse_locations
+            (id, warehouse_id, parent_id, code, name, kind, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            location.id,
+            location.warehouse_id,
+            location.parent_id,
+            location.code,
+            location.name,
+            location.kind.as_str(),
+            location.created_at,
+        )
+        .execute(&mut **uow)
+        .await?;
+
+        Ok(location)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %location.id))]
+    async fn update_location(
+        &self,
+        uow: &mut Self::Uow,
+        location: Location,
+    ) -> Result<Location, sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE warehouse_locations
+            SET parent_id = $1,
+                code = $2,
+                name = $3,
+                kind = $4
+            WHERE id = $5
+            RETURNING id
+            "#,
+            location.parent_id,
+            location.code,
+            location.name,
+            location.kind.as_str(),
+            location.id,
+        )
+        .fetch_one(&mut **uow)
+        .await?;
+
+        Ok(location)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(location_id = %id))]
+    async fn delete_location(&self, uow: &mut Self::Uow, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(r#"DELETE FROM warehouse_locations WHERE id = $1"#, id)
+            .execute(&mut **uow)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_middleware::atuh_middleware::auth_middleware;
+use crate::warehouse::handler::warehouse_handler::{
+    create_location_handler, create_warehouse_handler, delete_location_handler,
+    delete_warehouse_handler, get_company_warehouses_handler, get_locations_handler,
+    get_warehouse_handler, update_location_handler, update_warehouse_handler,
+};
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+use crate::warehouse::usecase::warehouse_usecase::WarehouseUsecase;
+use axum::middleware;
+use axum::{Router, routing::delete, routing::get, routing::post, routing::put};
+
+// full paths, the warehouses of a company hang below /company
+pub fn warehouse_routes<R: WarehouseRepository + 'static>(repo: R, metrics: AppMetrics) -> Router {
+    let usecase = Arc::new(WarehouseUsecase::new(repo, metrics));
+
+    Router::new()
+        .route(
+            "/company/:id/warehouses",
+            get(get_company_warehouses_handler),
+        )
+        .route("/company/:id/warehouses", post(create_warehouse_handler))
+        .route("/warehouse/:id", get(get_warehouse_handler))
+        .route("/warehouse/:id", put(update_warehouse_handler))
+        .route("/warehouse/:id", delete(delete_warehouse_handler))
+        .route("/warehouse/:id/locations", get(get_locations_handler))
+        .route("/warehouse/:id/locations", post(create_location_handler))
+        .route(
+            "/warehouse/:id/locations/:location_id",
+            put(update_location_handler),
+        )
+        .route(
+            "/warehouse/:id/locations/:location_id",
+            delete(delete_location_handler),
+        )
+        .with_state(usecase)
+        // merged at the root, layer would put the fallback behind auth as well
+        .route_layer(middleware::from_fn(auth_middleware))
+}
@@ -0,0 +1,31 @@
+use uuid::Uuid;
+
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::domain::warehouse::Warehouse;
+
+pub struct ListWarehouseResult {
+    pub data: Vec<Warehouse>,
+    pub total_data: i64,
+}
+
+// the fields a client sets, the owning company comes from the path on create
+pub struct WarehouseInput {
+    pub code: String,
+    pub name: String,
+    pub address: Option<String>,
+}
+
+pub struct LocationInput {
+    pub parent_id: Option<Uuid>,
+    pub code: String,
+    pub name: String,
+    pub kind: LocationKind,
+}
+
+// a location with its place in the tree, path joins the codes from the top
+// level down, e.g. A/01/03
+pub struct LocationNode {
+    pub location: Location,
+    pub path: String,
+    pub depth: usize,
+}
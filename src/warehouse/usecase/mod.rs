@@ -0,0 +1,4 @@
+pub mod warehouse_usecase;
+pub mod dto;
+#[cfg(test)]
+mod warehouse_usecase_test;
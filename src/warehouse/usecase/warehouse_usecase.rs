@@ -0,0 +1,556 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use chrono::Utc;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::app_database::unit_of_work::finish;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::warehouse::domain::location::Location;
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::warehouse_repository::WarehouseRepository;
+use crate::warehouse::usecase::dto::{
+    ListWarehouseResult, LocationInput, LocationNode, WarehouseInput,
+};
+
+// names of the constraints on warehouses and warehouse_locations, see migrations
+const WAREHOUSE_CODE_CONSTRAINT: &str = "warehouses_company_id_code_key";
+const WAREHOUSE_COMPANY_CONSTRAINT: &str = "warehouses_company_id_fkey";
+const LOCATION_CODE_CONSTRAINT: &str = "warehouse_locations_warehouse_id_code_key";
+const LOCATION_WAREHOUSE_CONSTRAINT: &str = "warehouse_locations_warehouse_id_fkey";
+const LOCATION_PARENT_CONSTRAINT: &str = "warehouse_locations_parent_fkey";
+
+pub struct WarehouseUsecase<R: WarehouseRepository> {
+    repo: R,
+    metrics: AppMetrics,
+}
+
+#[derive(Debug)]
+pub enum WarehouseUsecaseError {
+    CodeAlreadyExist,
+    CompanyNotFound,
+    NotFound,
+    InUse,
+    LocationCodeAlreadyExist,
+    LocationNotFound,
+    LocationInUse,
+    ParentNotFound,
+    ParentCycle,
+    DatabaseError(sqlx::Error),
+}
+
+impl fmt::Display for WarehouseUsecaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarehouseUsecaseError::CodeAlreadyExist => write!(f, "warehouse code already exists"),
+            WarehouseUsecaseError::CompanyNotFound => write!(f, "warehouse company not found"),
+            WarehouseUsecaseError::NotFound => write!(f, "warehouse not found"),
+            WarehouseUsecaseError::InUse => write!(f, "warehouse is still in use"),
+            WarehouseUsecaseError::LocationCodeAlreadyExist => {
+                write!(f, "location code already exists")
+            }
+            WarehouseUsecaseError::LocationNotFound => write!(f, "location not found"),
+            WarehouseUsecaseError::LocationInUse => write!(f, "location is still in use"),
+            WarehouseUsecaseError::ParentNotFound => write!(f, "parent location not found"),
+            WarehouseUsecaseError::ParentCycle => {
+                write!(f, "location can not be moved below itself")
+            }
+            WarehouseUsecaseError::DatabaseError(_) => write!(f, "warehouse storage failed"),
+        }
+    }
+}
+
+impl std::error::Error for WarehouseUsecaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WarehouseUsecaseError::DatabaseError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<R: WarehouseRepository> WarehouseUsecase<R> {
+    pub fn new(repo: R, metrics: AppMetrics) -> Self {
+        Self { repo, metrics }
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, code = %input.code))]
+    pub async fn create_warehouse(
+        &self,
+        company_id: Uuid,
+        input: WarehouseInput,
+    ) -> Result<Warehouse, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_warehouse_in(&mut uow, company_id, input).await;
+        let warehouse = finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("create");
+        Ok(warehouse)
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %id))]
+    pub async fn get_warehouse(&self, id: Uuid) -> Result<Warehouse, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.get_warehouse_in(&mut uow, &id).await;
+        finish(uow, result, WarehouseUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %id, code = %input.code))]
+    pub async fn update_warehouse(
+        &self,
+        id: Uuid,
+        input: WarehouseInput,
+    ) -> Result<Warehouse, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.update_warehouse_in(&mut uow, id, input).await;
+        let warehouse = finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("update");
+        Ok(warehouse)
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %id))]
+    pub async fn delete_warehouse(&self, id: Uuid) -> Result<(), WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_warehouse_in(&mut uow, id).await;
+        finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("delete");
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(company_id = %company_id, per_page = ?query.per_page, offset = ?query.offset))]
+    pub async fn list_company_warehouses(
+        &self,
+        company_id: Uuid,
+        query: &PaginationRequest,
+    ) -> Result<ListWarehouseResult, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .list_company_warehouses_in(&mut uow, &company_id, query)
+            .await;
+        finish(uow, result, WarehouseUsecaseError::DatabaseError).await
+    }
+
+    // every location of the warehouse, parents before their children
+    #[instrument(skip_all, fields(warehouse_id = %warehouse_id))]
+    pub async fn list_locations(
+        &self,
+        warehouse_id: Uuid,
+    ) -> Result<Vec<LocationNode>, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.list_locations_in(&mut uow, &warehouse_id).await;
+        finish(uow, result, WarehouseUsecaseError::DatabaseError).await
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %warehouse_id, code = %input.code))]
+    pub async fn create_location(
+        &self,
+        warehouse_id: Uuid,
+        input: LocationInput,
+    ) -> Result<Location, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.create_location_in(&mut uow, warehouse_id, input).await;
+        let location = finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("location_create");
+        Ok(location)
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %warehouse_id, location_id = %id, code = %input.code))]
+    pub async fn update_location(
+        &self,
+        warehouse_id: Uuid,
+        id: Uuid,
+        input: LocationInput,
+    ) -> Result<Location, WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self
+            .update_location_in(&mut uow, warehouse_id, id, input)
+            .await;
+        let location = finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("location_update");
+        Ok(location)
+    }
+
+    #[instrument(skip_all, fields(warehouse_id = %warehouse_id, location_id = %id))]
+    pub async fn delete_location(
+        &self,
+        warehouse_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), WarehouseUsecaseError> {
+        let mut uow = self.begin().await?;
+        let result = self.delete_location_in(&mut uow, warehouse_id, id).await;
+        finish(uow, result, WarehouseUsecaseError::DatabaseError).await?;
+
+        self.metrics.warehouse_operation("location_delete");
+        Ok(())
+    }
+
+    async fn begin(&self) -> Result<R::Uow, WarehouseUsecaseError> {
+        self.repo
+            .begin()
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)
+    }
+
+    async fn get_warehouse_in(
+        &self,
+        uow: &mut R::Uow,
+        id: &Uuid,
+    ) -> Result<Warehouse, WarehouseUsecaseError> {
+        self.repo
+            .get_warehouse_by_id(uow, id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?
+            .ok_or(WarehouseUsecaseError::NotFound)
+    }
+
+    async fn create_warehouse_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: Uuid,
+        input: WarehouseInput,
+    ) -> Result<Warehouse, WarehouseUsecaseError> {
+        self.check_company(uow, &company_id).await?;
+        self.check_warehouse_code(uow, &company_id, &input.code, None)
+            .await?;
+
+        let warehouse = Warehouse {
+            id: Uuid::new_v4(),
+            company_id,
+            code: input.code,
+            name: input.name,
+            address: input.address,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_warehouse(uow, warehouse)
+            .await
+            .map_err(map_warehouse_write_error)
+    }
+
+    async fn update_warehouse_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+        input: WarehouseInput,
+    ) -> Result<Warehouse, WarehouseUsecaseError> {
+        let mut warehouse = self.get_warehouse_in(uow, &id).await?;
+
+        self.check_warehouse_code(uow, &warehouse.company_id, &input.code, Some(&id))
+            .await?;
+
+        warehouse.code = input.code;
+        warehouse.name = input.name;
+        warehouse.address = input.address;
+
+        self.repo
+            .update_warehouse(uow, warehouse)
+            .await
+            .map_err(map_warehouse_write_error)
+    }
+
+    async fn delete_warehouse_in(
+        &self,
+        uow: &mut R::Uow,
+        id: Uuid,
+    ) -> Result<(), WarehouseUsecaseError> {
+        self.get_warehouse_in(uow, &id).await?;
+
+        // locations keep the warehouse, they have to be removed first
+        self.repo
+            .delete_warehouse(uow, &id)
+            .await
+            .map_err(|err| map_delete_error(err, WarehouseUsecaseError::InUse))
+    }
+
+    async fn list_company_warehouses_in(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        query: &PaginationRequest,
+    ) -> Result<ListWarehouseResult, WarehouseUsecaseError> {
+        self.check_company(uow, company_id).await?;
+
+        let total_warehouse = self
+            .repo
+            .count_company_warehouses(uow, company_id, query)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+
+        let warehouses = self
+            .repo
+            .find_company_warehouses(uow, company_id, query)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+
+        Ok(ListWarehouseResult {
+            data: warehouses,
+            total_data: total_warehouse,
+        })
+    }
+
+    async fn list_locations_in(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: &Uuid,
+    ) -> Result<Vec<LocationNode>, WarehouseUsecaseError> {
+        self.get_warehouse_in(uow, warehouse_id).await?;
+
+        let locations = self
+            .repo
+            .find_warehouse_locations(uow, warehouse_id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+
+        Ok(location_tree(locations))
+    }
+
+    async fn create_location_in(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: Uuid,
+        input: LocationInput,
+    ) -> Result<Location, WarehouseUsecaseError> {
+        self.get_warehouse_in(uow, &warehouse_id).await?;
+        self.check_location_code(uow, &warehouse_id, &input.code, None)
+            .await?;
+        if let Some(parent_id) = &input.parent_id {
+            self.get_location_in(uow, &warehouse_id, parent_id)
+                .await
+                .map_err(parent_not_found)?;
+        }
+
+        let location = Location {
+            id: Uuid::new_v4(),
+            warehouse_id,
+            parent_id: input.parent_id,
+            code: input.code,
+            name: input.name,
+            kind: input.kind,
+            created_at: Utc::now(),
+        };
+
+        self.repo
+            .create_location(uow, location)
+            .await
+            .map_err(map_warehouse_write_error)
+    }
+
+    async fn update_location_in(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: Uuid,
+        id: Uuid,
+        input: LocationInput,
+    ) -> Result<Location, WarehouseUsecaseError> {
+        let mut location = self.get_location_in(uow, &warehouse_id, &id).await?;
+
+        self.check_location_code(uow, &warehouse_id, &input.code, Some(&id))
+            .await?;
+        if let Some(parent_id) = input.parent_id {
+            self.check_location_parent(uow, &warehouse_id, &id, parent_id)
+                .await?;
+        }
+
+        location.parent_id = input.parent_id;
+        location.code = input.code;
+        location.name = input.name;
+        location.kind = input.kind;
+
+        self.repo
+            .update_location(uow, location)
+            .await
+            .map_err(map_warehouse_write_error)
+    }
+
+    async fn delete_location_in(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), WarehouseUsecaseError> {
+        self.get_location_in(uow, &warehouse_id, &id).await?;
+
+        // children keep their parent, they have to be moved or removed first
+        self.repo
+            .delete_location(uow, &id)
+            .await
+            .map_err(|err| map_delete_error(err, WarehouseUsecaseError::LocationInUse))
+    }
+
+    // a location of another warehouse is reported as missing
+    async fn get_location_in(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: &Uuid,
+        id: &Uuid,
+    ) -> Result<Location, WarehouseUsecaseError> {
+        self.repo
+            .get_location_by_id(uow, id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?
+            .filter(|location| location.warehouse_id == *warehouse_id)
+            .ok_or(WarehouseUsecaseError::LocationNotFound)
+    }
+
+    // walks up from the new parent, reaching the moved location means it
+    // would end up below itself
+    async fn check_location_parent(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: &Uuid,
+        id: &Uuid,
+        parent_id: Uuid,
+    ) -> Result<(), WarehouseUsecaseError> {
+        let mut parent = self
+            .get_location_in(uow, warehouse_id, &parent_id)
+            .await
+            .map_err(parent_not_found)?;
+        loop {
+            if parent.id == *id {
+                return Err(WarehouseUsecaseError::ParentCycle);
+            }
+            let Some(next_id) = parent.parent_id else {
+                return Ok(());
+            };
+            parent = self
+                .get_location_in(uow, warehouse_id, &next_id)
+                .await
+                .map_err(parent_not_found)?;
+        }
+    }
+
+    async fn check_company(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+    ) -> Result<(), WarehouseUsecaseError> {
+        let is_company_exist = self
+            .repo
+            .check_existing_company(uow, company_id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+        if !is_company_exist {
+            return Err(WarehouseUsecaseError::CompanyNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn check_warehouse_code(
+        &self,
+        uow: &mut R::Uow,
+        company_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), WarehouseUsecaseError> {
+        let is_code_exist = self
+            .repo
+            .check_existing_warehouse_code(uow, company_id, code, id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+        if is_code_exist {
+            return Err(WarehouseUsecaseError::CodeAlreadyExist);
+        }
+
+        Ok(())
+    }
+
+    async fn check_location_code(
+        &self,
+        uow: &mut R::Uow,
+        warehouse_id: &Uuid,
+        code: &str,
+        id: Option<&Uuid>,
+    ) -> Result<(), WarehouseUsecaseError> {
+        let is_code_exist = self
+            .repo
+            .check_existing_location_code(uow, warehouse_id, code, id)
+            .await
+            .map_err(WarehouseUsecaseError::DatabaseError)?;
+        if is_code_exist {
+            return Err(WarehouseUsecaseError::LocationCodeAlreadyExist);
+        }
+
+        Ok(())
+    }
+}
+
+fn parent_not_found(err: WarehouseUsecaseError) -> WarehouseUsecaseError {
+    match err {
+        WarehouseUsecaseError::LocationNotFound => WarehouseUsecaseError::ParentNotFound,
+        err => err,
+    }
+}
+
+// depth first from the top level locations, siblings keep the code order the
+// repository returns them in
+fn location_tree(locations: Vec<Location>) -> Vec<LocationNode> {
+    let mut children: HashMap<Option<Uuid>, Vec<Location>> = HashMap::new();
+    for location in locations {
+        children
+            .entry(location.parent_id)
+            .or_default()
+            .push(location);
+    }
+
+    let mut nodes = Vec::new();
+    let mut stack: Vec<(Location, String, usize)> = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|location| {
+            let path = location.code.clone();
+            (location, path, 0)
+        })
+        .collect();
+    while let Some((location, path, depth)) = stack.pop() {
+        if let Some(below) = children.remove(&Some(location.id)) {
+            for child in below.into_iter().rev() {
+                let child_path = format!("{path}/{}", child.code);
+                stack.push((child, child_path, depth + 1));
+            }
+        }
+        nodes.push(LocationNode {
+            location,
+            path,
+            depth,
+        });
+    }
+    nodes
+}
+
+// a concurrent unit of work can still claim a code, delete the company or
+// remove the parent between the check and the write, the constraints catch that
+fn map_warehouse_write_error(err: sqlx::Error) -> WarehouseUsecaseError {
+    let constraint = err
+        .as_database_error()
+        .and_then(|e| e.constraint())
+        .map(String::from);
+    match constraint.as_deref() {
+        Some(WAREHOUSE_CODE_CONSTRAINT) => WarehouseUsecaseError::CodeAlreadyExist,
+        Some(WAREHOUSE_COMPANY_CONSTRAINT) => WarehouseUsecaseError::CompanyNotFound,
+        Some(LOCATION_CODE_CONSTRAINT) => WarehouseUsecaseError::LocationCodeAlreadyExist,
+        Some(LOCATION_WAREHOUSE_CONSTRAINT) => WarehouseUsecaseError::NotFound,
+        Some(LOCATION_PARENT_CONSTRAINT) => WarehouseUsecaseError::ParentNotFound,
+        _ => WarehouseUsecaseError::DatabaseError(err),
+    }
+}
+
+// on delete every foreign key violation means something still points at the row
+fn map_delete_error(err: sqlx::Error, in_use: WarehouseUsecaseError) -> WarehouseUsecaseError {
+    let is_referenced = err
+        .as_database_error()
+        .is_some_and(|e| e.is_foreign_key_violation());
+    if is_referenced {
+        return in_use;
+    }
+    WarehouseUsecaseError::DatabaseError(err)
+}
@@ -0,0 +1,288 @@
+use uuid::Uuid;
+
+use crate::app_database::test_seed::company;
+use crate::app_metrics::metrics::AppMetrics;
+use crate::app_request::pagination::PaginationRequest;
+use crate::company::repository::company_repository_memory::CompanyRepositoryMemory;
+use crate::warehouse::domain::location::{Location, LocationKind};
+use crate::warehouse::domain::warehouse::Warehouse;
+use crate::warehouse::repository::warehouse_repository_memory::WarehouseRepositoryMemory;
+use crate::warehouse::usecase::dto::{LocationInput, WarehouseInput};
+use crate::warehouse::usecase::warehouse_usecase::{WarehouseUsecase, WarehouseUsecaseError};
+
+fn input(code: &str) -> WarehouseInput {
+    WarehouseInput {
+        code: code.into(),
+        name: format!("Warehouse {code}"),
+        address: None,
+    }
+}
+
+fn location_input(parent: Option<&Location>, code: &str, kind: LocationKind) -> LocationInput {
+    LocationInput {
+        parent_id: parent.map(|p| p.id),
+        code: code.into(),
+        name: format!("Location {code}"),
+        kind,
+    }
+}
+
+fn query(search: Option<&str>) -> PaginationRequest {
+    PaginationRequest {
+        page: Some(1),
+        per_page: Some(10),
+        offset: Some(0),
+        search: search.map(String::from),
+        sort: Some("code".into()),
+    }
+}
+
+// one company with two warehouses, the clone of the repository shares the store
+// so the test can look at what was committed
+async fn setup() -> (
+    WarehouseUsecase<WarehouseRepositoryMemory>,
+    WarehouseRepositoryMemory,
+    Warehouse,
+    Warehouse,
+) {
+    let acme = company("ACM");
+    let companies = CompanyRepositoryMemory::with_companies(vec![acme.clone()]);
+    let repo = WarehouseRepositoryMemory::new(companies);
+    let usecase = WarehouseUsecase::new(repo.clone(), AppMetrics::new());
+    let main = usecase
+        .create_warehouse(acme.id, input("WH-1"))
+        .await
+        .unwrap();
+    let spare = usecase
+        .create_warehouse(acme.id, input("WH-2"))
+        .await
+        .unwrap();
+    (usecase, repo, main, spare)
+}
+
+#[tokio::test]
+async fn create_warehouse_checks_company_and_code() {
+    let (usecase, repo, main, _) = setup().await;
+
+    let result = usecase
+        .create_warehouse(main.company_id, input("WH-1"))
+        .await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::CodeAlreadyExist)
+    ));
+
+    let result = usecase
+        .create_warehouse(Uuid::new_v4(), input("WH-3"))
+        .await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::CompanyNotFound)
+    ));
+
+    assert_eq!(repo.tables().await.warehouses.len(), 2);
+}
+
+#[tokio::test]
+async fn update_warehouse_keeps_its_own_code() {
+    let (usecase, _, main, spare) = setup().await;
+
+    let mut changed = input("WH-1");
+    changed.address = Some("Jakarta".into());
+    let updated = usecase.update_warehouse(main.id, changed).await.unwrap();
+    assert_eq!(updated.address.as_deref(), Some("Jakarta"));
+    assert_eq!(updated.company_id, main.company_id);
+
+    let result = usecase.update_warehouse(spare.id, input("WH-1")).await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::CodeAlreadyExist)
+    ));
+
+    let result = usecase
+        .update_warehouse(Uuid::new_v4(), input("WH-9"))
+        .await;
+    assert!(matches!(result, Err(WarehouseUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn list_company_warehouses_needs_an_existing_company() {
+    let (usecase, _, main, _) = setup().await;
+
+    let listed = usecase
+        .list_company_warehouses(main.company_id, &query(Some("wh-1")))
+        .await
+        .unwrap();
+    assert_eq!(listed.total_data, 1);
+    assert_eq!(listed.data, vec![main]);
+
+    let result = usecase
+        .list_company_warehouses(Uuid::new_v4(), &query(None))
+        .await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::CompanyNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn warehouse_with_locations_cannot_be_deleted() {
+    let (usecase, repo, main, spare) = setup().await;
+    usecase
+        .create_location(main.id, location_input(None, "A", LocationKind::Zone))
+        .await
+        .unwrap();
+
+    let result = usecase.delete_warehouse(main.id).await;
+    assert!(matches!(result, Err(WarehouseUsecaseError::InUse)));
+
+    usecase.delete_warehouse(spare.id).await.unwrap();
+    assert_eq!(repo.tables().await.warehouses, vec![main]);
+}
+
+#[tokio::test]
+async fn list_locations_walks_the_tree() {
+    let (usecase, _, main, _) = setup().await;
+    let create = |input| usecase.create_location(main.id, input);
+
+    let zone_b = create(location_input(None, "B", LocationKind::Zone))
+        .await
+        .unwrap();
+    let zone_a = create(location_input(None, "A", LocationKind::Zone))
+        .await
+        .unwrap();
+    let rack = create(location_input(Some(&zone_a), "R2", LocationKind::Rack))
+        .await
+        .unwrap();
+    create(location_input(Some(&rack), "S1", LocationKind::Shelf))
+        .await
+        .unwrap();
+    create(location_input(Some(&zone_a), "R1", LocationKind::Rack))
+        .await
+        .unwrap();
+    create(location_input(Some(&zone_b), "BIN", LocationKind::Bin))
+        .await
+        .unwrap();
+
+    let nodes = usecase.list_locations(main.id).await.unwrap();
+    let tree: Vec<(&str, usize)> = nodes.iter().map(|n| (n.path.as_str(), n.depth)).collect();
+    assert_eq!(
+        tree,
+        vec![
+            ("A", 0),
+            ("A/R1", 1),
+            ("A/R2", 1),
+            ("A/R2/S1", 2),
+            ("B", 0),
+            ("B/BIN", 1),
+        ]
+    );
+
+    let result = usecase.list_locations(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(WarehouseUsecaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn location_parent_has_to_be_in_the_same_warehouse() {
+    let (usecase, _, main, spare) = setup().await;
+    let zone = usecase
+        .create_location(main.id, location_input(None, "A", LocationKind::Zone))
+        .await
+        .unwrap();
+
+    let result = usecase
+        .create_location(
+            spare.id,
+            location_input(Some(&zone), "A-01", LocationKind::Bin),
+        )
+        .await;
+    assert!(matches!(result, Err(WarehouseUsecaseError::ParentNotFound)));
+
+    let result = usecase
+        .create_location(main.id, location_input(None, "A", LocationKind::Zone))
+        .await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::LocationCodeAlreadyExist)
+    ));
+
+    // the location is looked up through its warehouse
+    let result = usecase
+        .update_location(
+            spare.id,
+            zone.id,
+            location_input(None, "A", LocationKind::Zone),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::LocationNotFound)
+    ));
+    let result = usecase.delete_location(spare.id, zone.id).await;
+    assert!(matches!(
+        result,
+        Err(WarehouseUsecaseError::LocationNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn location_cannot_be_moved_below_itself() {
+    let (usecase, repo, main, _) = setup().await;
+    let create = |input| usecase.create_location(main.id, input);
+    let zone = create(location_input(None, "A", LocationKind::Zone))
+        .await
+        .unwrap();
+    let rack = create(location_input(Some(&zone), "R1", LocationKind::Rack))
+        .await
+        .unwrap();
+    let shelf = create(location_input(Some(&rack), "S1", LocationKind::Shelf))
+        .await
+        .unwrap();
+
+    for parent in [&zone, &shelf] {
+        let result = usecase
+            .update_location(
+                main.id,
+                zone.id,
+                location_input(Some(parent), "A", LocationKind::Zone),
+            )
+            .await;
+        assert!(matches!(result, Err(WarehouseUsecaseError::ParentCycle)));
+    }
+
+    // moving the shelf up to the zone is fine
+    let moved = usecase
+        .update_location(
+            main.id,
+            shelf.id,
+            location_input(Some(&zone), "S1", LocationKind::Shelf),
+        )
+        .await
+        .unwrap();
+    assert_eq!(moved.parent_id, Some(zone.id));
+    let stored = repo.tables().await.locations;
+    assert!(stored.contains(&moved));
+}
+
+#[tokio::test]
+async fn location_with_children_cannot_be_deleted() {
+    let (usecase, repo, main, _) = setup().await;
+    let zone = usecase
+        .create_location(main.id, location_input(None, "A", LocationKind::Zone))
+        .await
+        .unwrap();
+    let bin = usecase
+        .create_location(
+            main.id,
+            location_input(Some(&zone), "A-01", LocationKind::Bin),
+        )
+        .await
+        .unwrap();
+
+    let result = usecase.delete_location(main.id, zone.id).await;
+    assert!(matches!(result, Err(WarehouseUsecaseError::LocationInUse)));
+
+    usecase.delete_location(main.id, bin.id).await.unwrap();
+    usecase.delete_location(main.id, zone.id).await.unwrap();
+    assert!(repo.tables().await.locations.is_empty());
+}